@@ -1,4 +1,4 @@
-use super::projection::Projection;
+use super::projection::{DepthMode, Projection};
 
 #[derive(Clone, Debug)]
 pub struct Camera<Controls>
@@ -6,6 +6,7 @@ where
     Controls: super::controls::Controls,
 {
     projection: Projection,
+    depth_mode: DepthMode,
     controls: Controls,
     changed: bool,
 }
@@ -29,13 +30,29 @@ where
     pub fn new(projection: Projection, controls: Controls) -> Self {
         Self {
             projection,
+            depth_mode: DepthMode::Standard,
             controls,
             changed: true,
         }
     }
 
     pub fn projection(&self) -> mint::ColumnMatrix4<f32> {
-        self.projection.into()
+        self.projection.matrix(self.depth_mode).into()
+    }
+
+    pub fn depth_mode(&self) -> DepthMode {
+        self.depth_mode
+    }
+
+    /// Must match the depth mode the renderer was built with.
+    pub fn set_depth_mode(&mut self, depth_mode: DepthMode) {
+        self.depth_mode = depth_mode;
+        self.changed = true;
+    }
+
+    /// The projection as it was set, rather than its matrix.
+    pub fn projection_settings(&self) -> Projection {
+        self.projection
     }
 
     pub fn set_controls(&mut self, controls: Controls) -> Controls {
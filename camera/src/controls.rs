@@ -1,8 +1,41 @@
 mod first_person;
 mod flat;
 mod flying;
+mod ground;
 mod orbit;
 
+/// What the view is centered on and how close in it is, so cameras can be
+/// moved between two of them.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraFocus {
+    /// World position at the middle of the view.
+    pub center: [f32; 3],
+    /// Larger is closer in. Flat controls scale by it and orbit controls
+    /// sit at 1.0 / zoom from the center.
+    pub zoom: f32,
+}
+
+impl CameraFocus {
+    pub fn new(center: [f32; 3], zoom: f32) -> Self {
+        Self { center, zoom }
+    }
+
+    /// The center moves in a straight line while the zoom changes by the
+    /// same factor each step, so zooming in and out feel the same speed.
+    pub fn lerp(self, other: CameraFocus, t: f32) -> CameraFocus {
+        let center = glam::Vec3::from(self.center)
+            .lerp(other.center.into(), t)
+            .into();
+        let zoom = if self.zoom > 0.0 && other.zoom > 0.0 {
+            self.zoom * (other.zoom / self.zoom).powf(t)
+        } else {
+            self.zoom + (other.zoom - self.zoom) * t
+        };
+
+        Self { center, zoom }
+    }
+}
+
 pub trait Controls {
     /// Retrieves the eye position.
     fn eye(&self) -> [f32; 3];
@@ -18,6 +51,21 @@ pub trait Controls {
     fn scale(&self) -> f32 {
         1.0
     }
+
+    /// Keeps the view inside [left, bottom, right, top] of the world for a
+    /// viewport of this size. None removes the limit. Controls that can't
+    /// be limited ignore it.
+    fn set_bounds(&mut self, _bounds: Option<[f32; 4]>, _viewport: [f32; 2]) {}
+
+    /// The focus of the view for a viewport of this size. None if the
+    /// controls can't be moved to a focus.
+    fn focus(&self, _viewport: [f32; 2]) -> Option<CameraFocus> {
+        None
+    }
+
+    /// Moves the view onto focus for a viewport of this size. Controls
+    /// without a focus ignore it.
+    fn set_focus(&mut self, _focus: CameraFocus, _viewport: [f32; 2]) {}
 }
 
 pub use first_person::{
@@ -25,4 +73,5 @@ pub use first_person::{
 };
 pub use flat::{FlatControls, FlatInputs, FlatSettings};
 pub use flying::{FlyingControls, FlyingInputs, FlyingSettings};
+pub use ground::{GroundControls, GroundSettings};
 pub use orbit::{OrbitControls, OrbitInputs, OrbitSettings};
@@ -1,5 +1,5 @@
-use super::Controls;
-use glam::{Mat4, Vec3};
+use super::{CameraFocus, Controls};
+use glam::{Mat4, Vec2, Vec3};
 #[derive(Clone, Debug, Default)]
 pub struct FlatInputs {
     /// move in this direction.
@@ -12,11 +12,16 @@ pub struct FlatInputs {
 #[derive(Clone, Debug)]
 pub struct FlatSettings {
     pub zoom: f32,
+    /// World units per second the inputs scroll at.
+    pub speed: f32,
 }
 
 impl Default for FlatSettings {
     fn default() -> Self {
-        Self { zoom: 1.0 }
+        Self {
+            zoom: 1.0,
+            speed: 256.0,
+        }
     }
 }
 
@@ -26,6 +31,11 @@ pub struct FlatControls {
     settings: FlatSettings,
     view: Mat4,
     eye: Vec3,
+    /// World position shown at the bottom left of the viewport.
+    position: Vec2,
+    /// [left, bottom, right, top] the view is kept in.
+    bounds: Option<[f32; 4]>,
+    viewport: [f32; 2],
     changed: bool,
 }
 
@@ -40,6 +50,9 @@ impl FlatControls {
             settings,
             view: Mat4::IDENTITY,
             eye: Vec3::ZERO,
+            position: Vec2::ZERO,
+            bounds: None,
+            viewport: [0.0; 2],
             changed: true,
         }
     }
@@ -48,6 +61,48 @@ impl FlatControls {
         self.inputs = inputs;
         self.changed = true;
     }
+
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    /// Moves the view so position is at the bottom left. Clamped to the
+    /// bounds on the next update.
+    pub fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+        self.changed = true;
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.settings.zoom
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.settings.zoom = zoom;
+        self.changed = true;
+    }
+
+    /// Clamps one axis. A world smaller than the view is centered.
+    fn clamp_axis(position: f32, min: f32, max: f32, visible: f32) -> f32 {
+        let size = max - min;
+
+        if visible >= size {
+            min - (visible - size) * 0.5
+        } else {
+            position.clamp(min, max - visible)
+        }
+    }
+
+    fn clamp_position(&mut self) {
+        if let Some([left, bottom, right, top]) = self.bounds {
+            let visible = Vec2::from(self.viewport) / self.settings.zoom;
+
+            self.position = Vec2::new(
+                Self::clamp_axis(self.position.x, left, right, visible.x),
+                Self::clamp_axis(self.position.y, bottom, top, visible.y),
+            );
+        }
+    }
 }
 
 impl Controls for FlatControls {
@@ -55,16 +110,28 @@ impl Controls for FlatControls {
         self.eye.into()
     }
 
-    fn update(&mut self, _delta: f32) -> bool {
+    fn update(&mut self, delta: f32) -> bool {
+        let direction = Vec2::new(
+            self.inputs.right - self.inputs.left,
+            self.inputs.up - self.inputs.down,
+        );
+
+        if direction != Vec2::ZERO {
+            self.position += direction * self.settings.speed * delta;
+            self.changed = true;
+        }
+
         let changed = self.changed;
 
         if changed {
+            self.clamp_position();
             self.view = Mat4::IDENTITY
                 * Mat4::from_scale(Vec3::new(
                     self.settings.zoom,
                     self.settings.zoom,
                     self.settings.zoom,
-                ));
+                ))
+                * Mat4::from_translation(-self.position.extend(0.0));
         }
 
         self.changed = false;
@@ -78,4 +145,28 @@ impl Controls for FlatControls {
     fn scale(&self) -> f32 {
         self.settings.zoom
     }
+
+    fn set_bounds(&mut self, bounds: Option<[f32; 4]>, viewport: [f32; 2]) {
+        self.bounds = bounds;
+        self.viewport = viewport;
+        self.changed = true;
+    }
+
+    fn focus(&self, viewport: [f32; 2]) -> Option<CameraFocus> {
+        let half = Vec2::from(viewport) * 0.5 / self.settings.zoom;
+
+        Some(CameraFocus::new(
+            (self.position + half).extend(0.0).into(),
+            self.settings.zoom,
+        ))
+    }
+
+    fn set_focus(&mut self, focus: CameraFocus, viewport: [f32; 2]) {
+        let zoom = focus.zoom.max(f32::EPSILON);
+        let half = Vec2::from(viewport) * 0.5 / zoom;
+
+        self.settings.zoom = zoom;
+        self.position = Vec3::from(focus.center).truncate() - half;
+        self.changed = true;
+    }
 }
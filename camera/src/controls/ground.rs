@@ -0,0 +1,149 @@
+use super::{CameraFocus, Controls, FlatInputs};
+use glam::{Mat4, Vec2, Vec3};
+
+#[derive(Clone, Debug)]
+pub struct GroundSettings {
+    /// World units per second the inputs move the target at.
+    pub speed: f32,
+    /// Radians the camera leans back from looking straight down.
+    pub tilt: f32,
+    /// How far the camera sits from the target.
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+}
+
+impl Default for GroundSettings {
+    fn default() -> Self {
+        Self {
+            speed: 256.0,
+            tilt: (45.0_f32).to_radians(),
+            distance: 512.0,
+            min_distance: 16.0,
+            max_distance: 4096.0,
+        }
+    }
+}
+
+/// Looks down at the 2D world from an angle for 2.5D views, with a
+/// perspective projection. Maps lie on the ground and billboarded images
+/// stand up on it. Lower z stays in front, like in the flat view.
+#[derive(Clone, Debug)]
+pub struct GroundControls {
+    inputs: FlatInputs,
+    settings: GroundSettings,
+    /// World position on the ground the camera looks at.
+    target: Vec2,
+    view: Mat4,
+    eye: Vec3,
+    changed: bool,
+}
+
+impl GroundControls {
+    pub fn inputs(&self) -> &FlatInputs {
+        &self.inputs
+    }
+
+    pub fn new(settings: GroundSettings, target: [f32; 2]) -> Self {
+        let mut controls = Self {
+            inputs: FlatInputs::default(),
+            settings,
+            target: target.into(),
+            view: Mat4::IDENTITY,
+            eye: Vec3::ZERO,
+            changed: true,
+        };
+
+        controls.set_tilt(controls.settings.tilt);
+        controls.set_distance(controls.settings.distance);
+        controls
+    }
+
+    pub fn set_inputs(&mut self, inputs: FlatInputs) {
+        self.inputs = inputs;
+        self.changed = true;
+    }
+
+    pub fn target(&self) -> Vec2 {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: Vec2) {
+        self.target = target;
+        self.changed = true;
+    }
+
+    pub fn tilt(&self) -> f32 {
+        self.settings.tilt
+    }
+
+    /// Kept short of level with the ground, where the view would flip.
+    pub fn set_tilt(&mut self, tilt: f32) {
+        self.settings.tilt = tilt.clamp(0.0, (89.0_f32).to_radians());
+        self.changed = true;
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.settings.distance
+    }
+
+    pub fn set_distance(&mut self, distance: f32) {
+        self.settings.distance = distance
+            .clamp(self.settings.min_distance, self.settings.max_distance);
+        self.changed = true;
+    }
+}
+
+impl Controls for GroundControls {
+    fn eye(&self) -> [f32; 3] {
+        self.eye.into()
+    }
+
+    fn update(&mut self, delta: f32) -> bool {
+        let direction = Vec2::new(
+            self.inputs.right - self.inputs.left,
+            self.inputs.up - self.inputs.down,
+        );
+
+        if direction != Vec2::ZERO {
+            self.target += direction * self.settings.speed * delta;
+            self.changed = true;
+        }
+
+        let changed = self.changed;
+
+        if changed {
+            let (tilt_sin, tilt_cos) = self.settings.tilt.sin_cos();
+            let distance = self.settings.distance;
+            // Worked out with z flipped so lower z is nearer the camera.
+            let eye = Vec3::new(
+                self.target.x,
+                self.target.y - tilt_sin * distance,
+                tilt_cos * distance,
+            );
+
+            self.eye = Vec3::new(eye.x, eye.y, -eye.z);
+            self.view = Mat4::look_at_rh(eye, self.target.extend(0.0), Vec3::Y)
+                * Mat4::from_scale(Vec3::new(1.0, 1.0, -1.0));
+        }
+
+        self.changed = false;
+        changed
+    }
+
+    fn view(&self) -> mint::ColumnMatrix4<f32> {
+        self.view.into()
+    }
+
+    fn focus(&self, _viewport: [f32; 2]) -> Option<CameraFocus> {
+        Some(CameraFocus::new(
+            self.target.extend(0.0).into(),
+            1.0 / self.settings.distance,
+        ))
+    }
+
+    fn set_focus(&mut self, focus: CameraFocus, _viewport: [f32; 2]) {
+        self.set_target(Vec3::from(focus.center).truncate());
+        self.set_distance(1.0 / focus.zoom.max(f32::EPSILON));
+    }
+}
@@ -1,4 +1,4 @@
-use super::Controls;
+use super::{CameraFocus, Controls};
 use glam::{Mat4, Vec3};
 
 #[derive(Clone, Debug, Default)]
@@ -188,4 +188,13 @@ impl Controls for OrbitControls {
     fn view(&self) -> mint::ColumnMatrix4<f32> {
         self.view.into()
     }
+
+    fn focus(&self, _viewport: [f32; 2]) -> Option<CameraFocus> {
+        Some(CameraFocus::new(self.center.into(), 1.0 / self.radius))
+    }
+
+    fn set_focus(&mut self, focus: CameraFocus, _viewport: [f32; 2]) {
+        self.set_center(focus.center);
+        self.set_radius(1.0 / focus.zoom.max(f32::EPSILON));
+    }
 }
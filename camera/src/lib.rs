@@ -3,4 +3,4 @@ pub mod controls;
 pub mod projection;
 
 pub use camera::Camera;
-pub use projection::Projection;
+pub use projection::{DepthMode, Projection};
@@ -1,6 +1,36 @@
-use glam::Mat4;
+use glam::{Mat4, Vec4};
 
-#[derive(Clone, Copy, Debug)]
+/// How depth is stored in the depth buffer. The renderer's pipelines and
+/// depth clear have to match the camera's, which the graphics crate keeps
+/// in step when the mode is set on its renderer builder.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DepthMode {
+    /// Near is 0.0 and far is 1.0, drawing where depth is less or equal.
+    #[default]
+    Standard,
+    /// Near is 1.0 and far is 0.0, drawing where depth is greater or
+    /// equal. Floats are finer near 0.0, so this evens out precision over
+    /// perspective views and keeps the back layers of flat ones apart.
+    Reversed,
+}
+
+impl DepthMode {
+    pub fn is_reversed(self) -> bool {
+        self == DepthMode::Reversed
+    }
+
+    /// Value to clear the depth buffer to, the far plane's depth.
+    pub fn clear_value(self) -> f32 {
+        match self {
+            DepthMode::Standard => 1.0,
+            DepthMode::Reversed => 0.0,
+        }
+    }
+}
+
+/// Both map depth into wgpu's 0.0..1.0 range, so either works with the
+/// renderer's depth buffer.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Projection {
     Orthographic {
         left: f32,
@@ -10,7 +40,10 @@ pub enum Projection {
         near: f32,
         far: f32,
     },
+    /// For 2.5D views, like with [`crate::controls::GroundControls`].
+    /// Things order by their distance from the camera rather than their z.
     Perspective {
+        /// Vertical field of view in radians.
         fov: f32,
         aspect_ratio: f32,
         near: f32,
@@ -18,6 +51,126 @@ pub enum Projection {
     },
 }
 
+impl Projection {
+    /// Near and far planes for flat views showing z from 0.0 at the front
+    /// to layers at the back, with a spare unit in front of 0.0. Lower z
+    /// draws in front. 100 layers gives the usual near 1.0 and far -100.0.
+    pub fn layer_planes(layers: u32) -> (f32, f32) {
+        (1.0, -(layers.max(1) as f32))
+    }
+
+    /// Orthographic projection over the rectangle showing layers, see
+    /// [`Projection::layer_planes`].
+    pub fn orthographic_layers(
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        layers: u32,
+    ) -> Self {
+        let (near, far) = Self::layer_planes(layers);
+
+        Projection::Orthographic {
+            left,
+            right,
+            bottom,
+            top,
+            near,
+            far,
+        }
+    }
+
+    /// About the smallest z difference the depth buffer tells apart
+    /// anywhere in an orthographic view. Things closer together in z than
+    /// this can z-fight. None for perspective, where it depends on the
+    /// distance.
+    pub fn depth_resolution(&self) -> Option<f32> {
+        match self {
+            Projection::Orthographic { near, far, .. } => {
+                Some((near - far).abs() * f32::EPSILON)
+            }
+            Projection::Perspective { .. } => None,
+        }
+    }
+
+    /// The projection matrix storing depth as mode does.
+    pub fn matrix(self, mode: DepthMode) -> Mat4 {
+        let matrix = Mat4::from(self);
+
+        match mode {
+            DepthMode::Standard => matrix,
+            // depth becomes w - depth, which is 1.0 - depth once divided.
+            DepthMode::Reversed => {
+                Mat4::from_cols(
+                    Vec4::X,
+                    Vec4::Y,
+                    Vec4::new(0.0, 0.0, -1.0, 0.0),
+                    Vec4::new(0.0, 0.0, 1.0, 1.0),
+                ) * matrix
+            }
+        }
+    }
+
+    pub fn is_perspective(&self) -> bool {
+        matches!(self, Projection::Perspective { .. })
+    }
+
+    /// Blends the settings of two projections of the same kind. Different
+    /// kinds can't be blended, so it cuts over halfway.
+    pub fn lerp(self, other: Projection, t: f32) -> Projection {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+
+        match (self, other) {
+            (
+                Projection::Orthographic {
+                    left,
+                    right,
+                    bottom,
+                    top,
+                    near,
+                    far,
+                },
+                Projection::Orthographic {
+                    left: left2,
+                    right: right2,
+                    bottom: bottom2,
+                    top: top2,
+                    near: near2,
+                    far: far2,
+                },
+            ) => Projection::Orthographic {
+                left: mix(left, left2),
+                right: mix(right, right2),
+                bottom: mix(bottom, bottom2),
+                top: mix(top, top2),
+                near: mix(near, near2),
+                far: mix(far, far2),
+            },
+            (
+                Projection::Perspective {
+                    fov,
+                    aspect_ratio,
+                    near,
+                    far,
+                },
+                Projection::Perspective {
+                    fov: fov2,
+                    aspect_ratio: aspect_ratio2,
+                    near: near2,
+                    far: far2,
+                },
+            ) => Projection::Perspective {
+                fov: mix(fov, fov2),
+                aspect_ratio: mix(aspect_ratio, aspect_ratio2),
+                near: mix(near, near2),
+                far: mix(far, far2),
+            },
+            _ if t < 0.5 => self,
+            _ => other,
+        }
+    }
+}
+
 impl From<Projection> for Mat4 {
     fn from(proj: Projection) -> Mat4 {
         match proj {
@@ -34,7 +187,7 @@ impl From<Projection> for Mat4 {
                 aspect_ratio,
                 near,
                 far,
-            } => Mat4::perspective_rh_gl(fov, aspect_ratio, near, far),
+            } => Mat4::perspective_rh(fov, aspect_ratio, near, far),
         }
     }
 }
@@ -10,7 +10,7 @@ where
     /// World Camera Controls and time. Deturmines how the world is looked at.
     pub system: System<Controls>,
     /// Data stores for render types
-    pub sprites: Vec<Image>,
+    pub sprites: SpriteBatch,
     pub lights: Lights,
     pub animation: Image,
     pub map: Map,
@@ -26,6 +26,8 @@ where
     pub map_renderer: MapRenderer,
     pub light_renderer: LightRenderer,
     pub mesh_renderer: Mesh2DRenderer,
+    /// Draws and state switches of the last frame.
+    pub render_stats: RenderStats,
 }
 
 impl<Controls> Pass for State<Controls>
@@ -40,8 +42,8 @@ where
         let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("render pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: renderer.frame_buffer().as_ref().expect("no frame view?"),
-                resolve_target: None,
+                view: renderer.render_target().expect("no frame view?"),
+                resolve_target: renderer.resolve_target(),
                 ops: wgpu::Operations {
                     load: wgpu::LoadOp::Clear(wgpu::Color {
                         r: 0.0,
@@ -56,7 +58,7 @@ where
                 wgpu::RenderPassDepthStencilAttachment {
                     view: renderer.depth_buffer(),
                     depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
+                        load: renderer.gpu_device().depth_clear(),
                         store: wgpu::StoreOp::Store,
                     }),
                     stencil_ops: Some(wgpu::Operations {
@@ -69,26 +71,34 @@ where
             occlusion_query_set: None,
         });
 
+        // Recorded into a batch first so the bind groups and pipelines
+        // shared between renderers are only set when they change.
+        let mut batch = DrawBatch::new();
+
         // Lets set the System's Shader information here, mostly Camera, Size and Time
-        pass.set_bind_group(0, self.system.bind_group(), &[]);
+        batch.set_bind_group(0, self.system.bind_group(), &[]);
         // Lets set the Reusable Vertices and Indicies here.
         // This is used for each Renderer, Should be more performant since it is shared.
-        pass.set_vertex_buffer(0, renderer.buffer_object.vertices());
-        pass.set_index_buffer(
-            renderer.buffer_object.indices(),
-            wgpu::IndexFormat::Uint32,
-        );
+        batch.set_buffers(renderer.buffer_object.as_buffer_pass());
+
+        batch.render_lower_maps(renderer, &self.map_renderer, &self.map_atlas);
 
-        pass.render_lower_maps(renderer, &self.map_renderer, &self.map_atlas);
+        batch.render_image(renderer, &self.sprite_renderer, &self.image_atlas);
 
-        pass.render_image(renderer, &self.sprite_renderer, &self.image_atlas);
+        batch.render_upper_maps(renderer, &self.map_renderer, &self.map_atlas);
+
+        batch.render_silhouettes(
+            renderer,
+            &self.sprite_renderer,
+            &self.image_atlas,
+        );
 
-        pass.render_upper_maps(renderer, &self.map_renderer, &self.map_atlas);
+        batch.render_lights(renderer, &self.light_renderer);
 
-        pass.render_lights(renderer, &self.light_renderer);
+        batch.render_text(renderer, &self.text_renderer, &self.text_atlas);
 
-        pass.render_text(renderer, &self.text_renderer, &self.text_atlas);
+        batch.render_2dmeshs(renderer, &self.mesh_renderer);
 
-        pass.render_2dmeshs(renderer, &self.mesh_renderer);
+        self.render_stats = batch.submit(&mut pass);
     }
 }
@@ -2,7 +2,7 @@
 use backtrace::Backtrace;
 use camera::{
     controls::{Controls, FlatControls, FlatSettings},
-    Projection,
+    DepthMode, Projection,
 };
 use cosmic_text::{Attrs, Metrics};
 use glam::vec4;
@@ -10,7 +10,6 @@ use graphics::{iced_winit::core::window, *};
 use hecs::World;
 use input::{Bindings, FrameTime, InputHandler};
 use log::{error, info, warn, Level, LevelFilter, Metadata, Record};
-use naga::{front::wgsl, valid::Validator};
 use serde::{Deserialize, Serialize};
 use std::{
     cell::RefCell,
@@ -22,7 +21,6 @@ use std::{
     rc::Rc,
     time::Duration,
 };
-use wgpu::{Backends, Dx12Compiler, InstanceDescriptor, InstanceFlags};
 use winit::{
     dpi::PhysicalSize,
     event::*,
@@ -37,7 +35,7 @@ use graphics::iced_winit::{
     futures,
     runtime::{program, Debug},
     style::Theme,
-    winit, Clipboard,
+    winit,
 };
 
 mod gamestate;
@@ -58,6 +56,9 @@ enum Axis {
     Pitch,
 }
 
+// how many z layers the orthographic camera keeps apart in the depth buffer.
+const DEPTH_LAYERS: u32 = 100;
+
 // creates a static global logger type for setting the logger
 static MY_LOGGER: MyLogger = MyLogger(Level::Debug);
 
@@ -118,52 +119,37 @@ async fn main() -> Result<(), AscendingError> {
         .build(&event_loop)
         .unwrap();
 
-    // Generates an Instance for WGPU. Sets WGPU to be allowed on all possible supported backends
-    // These are DX12, DX11, Vulkan, Metal and Gles. if none of these work on a system they cant
-    // play the game basically.
-    let instance = wgpu::Instance::new(InstanceDescriptor {
-        backends: Backends::all(),
-        flags: InstanceFlags::default(),
-        dx12_shader_compiler: Dx12Compiler::default(),
-        gles_minor_version: wgpu::Gles3MinorVersion::Automatic,
-    });
-
-    // This is used to ensure the GPU can load the correct.
-    let compatible_surface =
-        unsafe { instance.create_surface(&window).unwrap() };
-
-    print!("{:?}", &compatible_surface);
     // This creates the Window Struct and Device struct that holds all the rendering information
     // we need to render to the screen. Window holds most of the window information including
     // the surface type. device includes the queue and GPU device for rendering.
-    // This then adds gpu_window and gpu_device and creates our renderer type. for easy passing of window, device and font system.
-    let mut renderer = instance
-        .create_device(
-            window,
-            &wgpu::RequestAdapterOptions {
-                // High performance mode says to use Dedicated Graphics devices first.
-                // Low power is APU graphic devices First.
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&compatible_surface),
-                // we will never use this as this forces us to use an alternative renderer.
-                force_fallback_adapter: false,
-            },
-            // used to deturmine if we need special limits or features for our backends.
-            &wgpu::DeviceDescriptor {
-                features: wgpu::Features::default(),
-                limits: wgpu::Limits::default(),
-                label: None,
-            },
-            None,
-            // How we are presenting the screen which causes it to either clip to a FPS limit or be unlimited.
-            wgpu::PresentMode::AutoVsync,
-        )
+    // The builder sets WGPU to be allowed on all possible supported backends by default.
+    // These are DX12, DX11, Vulkan, Metal and Gles. if none of these work on a system they cant
+    // play the game basically.
+    let mut renderer = RendererBuilder::new()
+        .with_window(window)
+        // High performance mode says to use Dedicated Graphics devices first.
+        // Low power is APU graphic devices First.
+        .with_power_preference(wgpu::PowerPreference::HighPerformance)
+        // How we are presenting the screen which causes it to either clip to a FPS limit or be unlimited.
+        .with_present_mode(wgpu::PresentMode::AutoVsync)
+        // Reversed-Z keeps the back layers as precise as the front ones.
+        .with_depth_mode(DepthMode::Reversed)
+        .build()
         .await
         .unwrap();
 
     // we print the GPU it decided to use here for testing purposes.
     println!("{:?}", renderer.adapter().get_info());
 
+    // Catch shader mistakes with readable errors before any pipeline uses them.
+    if let Err(errors) =
+        shaders::ShaderValidator::for_device(renderer.gpu_device()).validate()
+    {
+        for e in errors {
+            error!("{e}");
+        }
+    }
+
     // We generate Texture atlases to use with out types.
     let mut atlases: Vec<AtlasGroup> = iter::from_fn(|| {
         Some(AtlasGroup::new(
@@ -185,7 +171,8 @@ async fn main() -> Result<(), AscendingError> {
         .group_upload(&mut atlases[0], &renderer)
         .ok_or_else(|| OtherError::new("failed to upload image"))?;
 
-    let mut sprites = Vec::with_capacity(2001);
+    let mut sprites = SpriteBatch::with_capacity(&mut renderer, 1, 2001);
+    let mut handles = Vec::with_capacity(2);
 
     let mut x = 0.0;
     let y = 0.0;
@@ -201,12 +188,14 @@ async fn main() -> Result<(), AscendingError> {
         sprite.hw = Vec2::new(48.0, 48.0);
         sprite.uv = Vec4::new(48.0, 96.0, 48.0, 48.0);
         sprite.color = Color::rgba(255, 255, 255, 255);
-        sprites.push(sprite);
+        handles.push(sprites.insert(sprite));
         x += 12.0;
     }
 
-    sprites[0].pos.z = 4.0;
-    sprites[0].color = Color::rgba(255, 255, 255, 120);
+    if let Some(sprite) = sprites.get_mut(handles[0]) {
+        sprite.pos.z = 4.0;
+        sprite.color = Color::rgba(255, 255, 255, 120);
+    }
 
     // We establish the different renderers here to load their data up to use them.
     let text_renderer = TextRenderer::new(&renderer).unwrap();
@@ -220,20 +209,25 @@ async fn main() -> Result<(), AscendingError> {
 
     // setup our system which includes Camera and projection as well as our controls.
     // for the camera.
-    let system = System::new(
+    let mut system = System::new(
         &mut renderer,
-        Projection::Orthographic {
-            left: 0.0,
-            right: size.width,
-            bottom: 0.0,
-            top: size.height,
-            near: 1.0,
-            far: -100.0,
-        },
-        FlatControls::new(FlatSettings { zoom: 1.5 }),
+        Projection::orthographic_layers(
+            0.0,
+            size.width,
+            0.0,
+            size.height,
+            DEPTH_LAYERS,
+        ),
+        FlatControls::new(FlatSettings {
+            zoom: 1.5,
+            ..Default::default()
+        }),
         [size.width, size.height],
     );
 
+    // The GUI follows the monitor's scale. set_ui_scale adds a user scale on top.
+    system.set_hidpi_scale(renderer.window().scale_factor() as f32);
+
     // We make a new Map to render here.
     let mut map = Map::new(&mut renderer, 20);
 
@@ -277,6 +271,33 @@ async fn main() -> Result<(), AscendingError> {
     map.pos = Vec2::new(0.0, 0.0);
     map.can_render = true;
 
+    // Keeps the edited map on disk so a panic or lost device doesn't lose
+    // the work. Installed after our panic hook so both run.
+    let mut autosave = Autosave::new("autosave")?;
+    autosave.install_panic_hook();
+
+    if autosave.has_recovery() {
+        warn!(
+            "the last session did not close, recovering {:?}. reason: {}",
+            autosave.recovery_names(),
+            autosave.crash_reason().unwrap_or("unknown")
+        );
+
+        if let Some(bytes) = autosave.recover("map")? {
+            match Map::from_bytes(&mut renderer, &bytes) {
+                Ok(recovered) => {
+                    map = recovered;
+                    map.can_render = true;
+                }
+                Err(e) => error!("could not recover the map: {e}"),
+            }
+        }
+
+        autosave.discard_recovery()?;
+    }
+
+    autosave.capture("map", &map);
+
     let _tilesheet = Texture::from_file(format!("images/tiles/1.png"))?
         .new_tilesheet(&mut atlases[1], &renderer, 20)
         .ok_or_else(|| OtherError::new("failed to upload tiles"))?;
@@ -408,6 +429,8 @@ async fn main() -> Result<(), AscendingError> {
         animate: false,
         anim_speed: 5.0,
         dither: 5.0,
+        animation: LightAnimation::None,
+        cookie: None,
     });
 
     lights.insert_area_light(AreaLight {
@@ -417,6 +440,8 @@ async fn main() -> Result<(), AscendingError> {
         animate: false,
         anim_speed: 5.0,
         dither: 2.0,
+        animation: LightAnimation::None,
+        cookie: None,
     });*/
 
     lights.insert_area_light(AreaLight {
@@ -426,6 +451,8 @@ async fn main() -> Result<(), AscendingError> {
         animate: false,
         anim_speed: 5.0,
         dither: 0.5,
+        animation: LightAnimation::None,
+        cookie: None,
     });
 
     lights.insert_area_light(AreaLight {
@@ -435,6 +462,8 @@ async fn main() -> Result<(), AscendingError> {
         animate: true,
         anim_speed: 5.0,
         dither: 0.8,
+        animation: LightAnimation::None,
+        cookie: None,
     });
 
     lights.insert_directional_light(DirectionalLight {
@@ -448,6 +477,8 @@ async fn main() -> Result<(), AscendingError> {
         fade_distance: 5.0,
         edge_fade_distance: 0.5,
         animate: false,
+        animation: LightAnimation::None,
+        cookie: None,
     });
 
     lights.insert_directional_light(DirectionalLight {
@@ -461,6 +492,8 @@ async fn main() -> Result<(), AscendingError> {
         fade_distance: 4.0,
         edge_fade_distance: 0.6,
         animate: true,
+        animation: LightAnimation::None,
+        cookie: None,
     });
     // Allow the window to be seen. hiding it then making visible speeds up
     // load times.
@@ -483,6 +516,7 @@ async fn main() -> Result<(), AscendingError> {
         mesh_renderer,
         lights,
         light_renderer,
+        render_stats: RenderStats::default(),
     };
 
     // Create the mouse/keyboard bindings for our stuff.
@@ -496,11 +530,13 @@ async fn main() -> Result<(), AscendingError> {
     let mut input_handler = InputHandler::new(bindings);
 
     let mut frame_time = FrameTime::new();
+    // scaled and pausable time the animations run on.
+    let mut clock = AnimationClock::new();
     let mut time = 0.0f32;
     let mut fps = 0u32;
 
-    // this is for Copy paste stuff within Iced.
-    let mut clipboard = Clipboard::connect(renderer.window());
+    // this is for Copy paste stuff within Iced and the game.
+    let mut clipboard = Clipboard::new();
 
     #[allow(deprecated)]
     event_loop.run(move |event, _, control_flow| {
@@ -514,9 +550,26 @@ async fn main() -> Result<(), AscendingError> {
                 if let WindowEvent::CloseRequested = *event {
                     *control_flow = ControlFlow::Exit;
                 }
+
+                if let WindowEvent::ScaleFactorChanged {
+                    scale_factor, ..
+                } = *event
+                {
+                    state.system.set_hidpi_scale(scale_factor as f32);
+                }
+            }
+            Event::LoopDestroyed => {
+                if let Err(e) = autosave.finish() {
+                    warn!("could not clean up the autosave: {e}");
+                }
+
+                return;
             }
             Event::MainEventsCleared => {
-                if !iced_state.is_queue_empty() {
+                // A changed GUI scale needs a relayout even without events.
+                if state.system.take_gui_scale_changed()
+                    || !iced_state.is_queue_empty()
+                {
                     // We update iced
                     let _ = iced_state.update(
                         state.system.iced_view().logical_size(),
@@ -574,7 +627,7 @@ async fn main() -> Result<(), AscendingError> {
             if let Some(event) = graphics::iced_winit::conversion::window_event(
                 window::Id::MAIN,
                 event,
-                renderer.window().scale_factor(),
+                state.system.gui_scale() as f64,
                 input_handler.modifiers(),
             ) {
                 iced_state.queue_event(event);
@@ -590,14 +643,13 @@ async fn main() -> Result<(), AscendingError> {
             size = new_size;
 
             // Reset screen size for the Surface here.
-            state.system.set_projection(Projection::Orthographic {
-                left: 0.0,
-                right: new_size.width,
-                bottom: 0.0,
-                top: new_size.height,
-                near: 1.0,
-                far: -100.0,
-            });
+            state.system.set_projection(Projection::orthographic_layers(
+                0.0,
+                new_size.width,
+                0.0,
+                new_size.height,
+                DEPTH_LAYERS,
+            ));
 
             renderer.update_depth_texture();
         }
@@ -608,18 +660,20 @@ async fn main() -> Result<(), AscendingError> {
         }
 
         let seconds = frame_time.seconds();
+        clock.update(&frame_time);
         // update our systems data to the gpu. this is the Camera in the shaders.
-        state.system.update(&renderer, &frame_time);
+        state.system.update(&renderer, &clock);
 
         // update our systems data to the gpu. this is the Screen in the shaders.
         state
             .system
             .update_screen(&renderer, [new_size.width, new_size.height]);
 
-        // This adds the Image data to the Buffer for rendering.
-        state.sprites.iter_mut().for_each(|sprite| {
-            state.sprite_renderer.image_update(sprite, &mut renderer);
-        });
+        // This adds the batched sprites to the Buffer for rendering as one
+        // store, rebuilt only when a sprite changed.
+        state
+            .sprite_renderer
+            .batch_update(&mut state.sprites, &mut renderer);
 
         state
             .sprite_renderer
@@ -636,6 +690,15 @@ async fn main() -> Result<(), AscendingError> {
             .text_update(&mut text, &mut state.text_atlas, &mut renderer)
             .unwrap();
         state.text_renderer.finalize(&mut renderer);
+        // map_update clears changed, so snapshot edits before it.
+        if state.map.changed {
+            autosave.capture("map", &state.map);
+        }
+
+        if let Err(e) = autosave.update() {
+            warn!("autosave failed: {e}");
+        }
+
         state.map_renderer.map_update(&mut state.map, &mut renderer);
         state.map_renderer.finalize(&mut renderer);
 
@@ -0,0 +1,183 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields,
+    LitStr, Path,
+};
+
+/// Derives `EngineUniform` for a struct of uniform fields.
+///
+/// The std140 layout is worked out at compile time from the fields'
+/// `UniformField` impls. Every field also gets a `<FIELD>_OFFSET` const and
+/// a `write_<field>` function that updates just that part of a buffer.
+///
+/// Options go in `#[uniform(..)]`:
+/// - `visibility = "vertex | fragment"` stages the binding is visible to,
+///   vertex and fragment by default.
+/// - `label = "..."` debug label of the buffer, the struct name by default.
+/// - `crate = "..."` path of the graphics crate, `graphics` by default.
+#[proc_macro_derive(EngineUniform, attributes(uniform))]
+pub fn derive_engine_uniform(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct Options {
+    krate: Path,
+    visibility: Vec<&'static str>,
+    label: String,
+}
+
+fn parse_options(input: &DeriveInput) -> syn::Result<Options> {
+    let mut options = Options {
+        krate: syn::parse_quote!(::graphics),
+        visibility: vec!["VERTEX", "FRAGMENT"],
+        label: input.ident.to_string(),
+    };
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("uniform")) {
+        attr.parse_nested_meta(|meta| {
+            let value: LitStr = meta.value()?.parse()?;
+
+            if meta.path.is_ident("crate") {
+                options.krate = value.parse()?;
+            } else if meta.path.is_ident("label") {
+                options.label = value.value();
+            } else if meta.path.is_ident("visibility") {
+                options.visibility = value
+                    .value()
+                    .split(['|', ','])
+                    .map(|stage| match stage.trim() {
+                        "vertex" => Ok("VERTEX"),
+                        "fragment" => Ok("FRAGMENT"),
+                        "compute" => Ok("COMPUTE"),
+                        other => Err(Error::new(
+                            value.span(),
+                            format!("unknown shader stage `{other}`"),
+                        )),
+                    })
+                    .collect::<syn::Result<_>>()?;
+            } else {
+                return Err(meta.error("unknown uniform option"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(options)
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let options = parse_options(&input)?;
+    let name = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "EngineUniform can't be derived for generic structs",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    input.span(),
+                    "EngineUniform needs named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "EngineUniform can only be derived for structs",
+            ))
+        }
+    };
+
+    let krate = &options.krate;
+    let field_trait = quote!(#krate::UniformField);
+    let mut consts = Vec::new();
+    let mut writes = Vec::new();
+    let mut setters = Vec::new();
+    // Where the previous field ended, as a const expression.
+    let mut end = quote!(0);
+
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let offset = format_ident!(
+            "{}_OFFSET",
+            ident.to_string().to_uppercase(),
+            span = ident.span()
+        );
+        let setter = format_ident!("write_{}", ident, span = ident.span());
+        let doc = format!("Byte offset of `{ident}` in the std140 layout.");
+        let setter_doc =
+            format!("Writes only `{ident}` into a buffer of this uniform.");
+
+        consts.push(quote! {
+            #[doc = #doc]
+            pub const #offset: usize = #krate::std140_align(
+                #end,
+                <#ty as #field_trait>::ALIGN,
+            );
+        });
+
+        writes.push(quote! {
+            #field_trait::write_std140(
+                &self.#ident,
+                &mut out[Self::#offset..Self::#offset + <#ty as #field_trait>::SIZE],
+            );
+        });
+
+        setters.push(quote! {
+            #[doc = #setter_doc]
+            pub fn #setter(
+                queue: &#krate::wgpu::Queue,
+                buffer: &#krate::wgpu::Buffer,
+                value: &#ty,
+            ) {
+                let mut bytes = vec![0; <#ty as #field_trait>::SIZE];
+
+                #field_trait::write_std140(value, &mut bytes);
+                queue.write_buffer(buffer, Self::#offset as u64, &bytes);
+            }
+        });
+
+        end = quote!(Self::#offset + <#ty as #field_trait>::SIZE);
+    }
+
+    let stages = options
+        .visibility
+        .iter()
+        .map(|stage| format_ident!("{}", stage, span = Span::call_site()));
+    let label = &options.label;
+
+    Ok(quote! {
+        // Not every field's helpers get used.
+        #[allow(dead_code)]
+        impl #name {
+            #(#consts)*
+            #(#setters)*
+        }
+
+        impl #krate::EngineUniform for #name {
+            const SIZE: usize = #krate::std140_align(#end, 16);
+            const VISIBILITY: #krate::wgpu::ShaderStages =
+                #krate::wgpu::ShaderStages::empty()
+                    #(.union(#krate::wgpu::ShaderStages::#stages))*;
+            const LABEL: &'static str = #label;
+
+            fn write_std140(&self, out: &mut [u8]) {
+                #(#writes)*
+            }
+        }
+    })
+}
@@ -0,0 +1,551 @@
+use crate::{
+    next_grid_id, ActionBarWidget, Allocation, AscendingError, Bounds, Color,
+    CooldownOverlay, GpuRenderer, GridItem, Image, ImageRenderer, ItemDrag,
+    MessageRouter, Rect, RectRenderer, SlotRef, Text, TextAtlas, TextRenderer,
+    UiSound, Vec2, Vec3, Vec4, WidgetHandle, WidgetInput, WidgetSounds,
+};
+use cosmic_text::{Attrs, Metrics};
+use input::InputHandler;
+use std::hash::Hash;
+
+/// What a slot of an [`ActionBar`] holds.
+#[derive(Clone, Debug)]
+pub struct Ability {
+    /// The app's id for the ability or item.
+    pub id: u32,
+    pub icon: Allocation,
+    pub name: String,
+}
+
+impl From<GridItem> for Ability {
+    fn from(item: GridItem) -> Self {
+        Self {
+            id: item.item,
+            icon: item.icon,
+            name: item.tooltip,
+        }
+    }
+}
+
+/// What an [`ActionBar`] emits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ActionBarEvent {
+    /// The slot was clicked or its key pressed while off cooldown.
+    Used { slot: usize, ability: u32 },
+    /// Something was dropped on the slot, from another widget or another
+    /// slot of this bar. Apply it with [`ActionBar::set_slot`], the source
+    /// is left as it was.
+    Assigned {
+        slot: usize,
+        ability: u32,
+        from: SlotRef,
+    },
+}
+
+struct ActionCell<A> {
+    background: Rect,
+    icon: Image,
+    key: Text,
+    cooldown: CooldownOverlay,
+    /// Action whose key shows on the slot and uses it.
+    binding: Option<A>,
+    /// Key text last read from the bindings.
+    label: String,
+    was_action_down: bool,
+}
+
+/// A row of ability slots. Each shows its icon, the key bound to its
+/// action read live from the input handler's bindings, and a cooldown
+/// sweep. Abilities can be dragged onto it from an [`crate::ItemGrid`] or
+/// between its slots through a shared [`ItemDrag`].
+///
+/// A is the bindings' action id. Placed in render space like the
+/// [`crate::Spinner`]. Events are emitted through the router when a
+/// handle is set.
+pub struct ActionBar<A>
+where
+    A: Clone + Eq + Hash + Send + Sync,
+{
+    pub handle: Option<WidgetHandle<ActionBarWidget>>,
+    pub sounds: WidgetSounds,
+    /// Stops abilities being dragged off the bar.
+    pub locked: bool,
+    pub cell_color: Color,
+    pub hover_color: Color,
+    pub border_color: Color,
+    pub key_color: Color,
+    /// Dims the icon while cooling down.
+    pub cooldown_tint: Color,
+    id: u32,
+    slot_size: Vec2,
+    spacing: f32,
+    position: Vec3,
+    slots: Vec<Option<Ability>>,
+    cells: Vec<ActionCell<A>>,
+    hovered: Option<usize>,
+    pressed: Option<usize>,
+    was_down: bool,
+    pending_sounds: Vec<UiSound>,
+    changed: bool,
+}
+
+impl<A> ActionBar<A>
+where
+    A: Clone + Eq + Hash + Send + Sync,
+{
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        slots: usize,
+        render_layer: u32,
+    ) -> Self {
+        let cells = (0..slots)
+            .map(|_| {
+                let mut key = Text::new(
+                    renderer,
+                    Some(Metrics::new(12.0, 14.0)),
+                    Vec3::ZERO,
+                    Vec2::ZERO,
+                );
+
+                key.set_default_color(Color::rgba(255, 255, 255, 255));
+
+                ActionCell {
+                    background: Rect::new(renderer, render_layer),
+                    icon: Image::new(None, renderer, render_layer),
+                    key,
+                    cooldown: CooldownOverlay::new(renderer, render_layer),
+                    binding: None,
+                    label: String::new(),
+                    was_action_down: false,
+                }
+            })
+            .collect();
+
+        Self {
+            handle: None,
+            sounds: WidgetSounds::default(),
+            locked: false,
+            cell_color: Color::rgba(35, 35, 35, 230),
+            hover_color: Color::rgba(65, 65, 65, 240),
+            border_color: Color::rgba(90, 90, 90, 255),
+            key_color: Color::rgba(255, 255, 255, 255),
+            cooldown_tint: Color::rgba(150, 150, 150, 255),
+            id: next_grid_id(),
+            slot_size: Vec2::splat(40.0),
+            spacing: 4.0,
+            position: Vec3::ZERO,
+            slots: vec![None; slots],
+            cells,
+            hovered: None,
+            pressed: None,
+            was_down: false,
+            pending_sounds: Vec::new(),
+            changed: true,
+        }
+    }
+
+    /// The id in this bar's [`SlotRef`]s.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn slot_size(&self) -> Vec2 {
+        self.slot_size
+    }
+
+    /// Bottom left corner and z.
+    pub fn set_position(&mut self, position: Vec3) -> &mut Self {
+        self.position = position;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_slot_size(&mut self, size: Vec2, spacing: f32) -> &mut Self {
+        self.slot_size = size;
+        self.spacing = spacing;
+        self.changed = true;
+        self
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let count = self.slots.len() as f32;
+        let width = (self.slot_size.x + self.spacing) * count - self.spacing;
+
+        Bounds::new(
+            self.position.x,
+            self.position.y,
+            self.position.x + width.max(0.0),
+            self.position.y + self.slot_size.y,
+        )
+    }
+
+    /// Binds the slot to an action, showing its key and using the slot
+    /// when it's pressed.
+    pub fn set_binding(&mut self, slot: usize, binding: Option<A>) {
+        if let Some(cell) = self.cells.get_mut(slot) {
+            cell.binding = binding;
+            cell.was_action_down = false;
+            self.changed = true;
+        }
+    }
+
+    pub fn slot(&self, slot: usize) -> Option<&Ability> {
+        self.slots.get(slot).and_then(Option::as_ref)
+    }
+
+    pub fn slots(&self) -> &[Option<Ability>] {
+        &self.slots
+    }
+
+    /// Replaces the slot's ability and returns the old one. Clears its
+    /// cooldown.
+    pub fn set_slot(
+        &mut self,
+        slot: usize,
+        ability: Option<Ability>,
+    ) -> Option<Ability> {
+        let old = std::mem::replace(self.slots.get_mut(slot)?, ability);
+
+        self.cells[slot].cooldown.reset();
+        self.changed = true;
+        old
+    }
+
+    pub fn swap(&mut self, a: usize, b: usize) {
+        if a < self.slots.len() && b < self.slots.len() {
+            self.slots.swap(a, b);
+            self.changed = true;
+        }
+    }
+
+    /// The slot under pos.
+    pub fn slot_at(&self, pos: Vec2) -> Option<usize> {
+        (0..self.slots.len()).find(|slot| self.slot_bounds(*slot).contains(pos))
+    }
+
+    pub fn hovered(&self) -> Option<usize> {
+        self.hovered
+    }
+
+    /// Starts the slot's cooldown sweep, times as in [`CooldownOverlay`].
+    pub fn start_cooldown(&mut self, slot: usize, now: f32, duration: f32) {
+        if let Some(cell) = self.cells.get_mut(slot) {
+            cell.cooldown.start(now, duration);
+            self.changed = true;
+        }
+    }
+
+    pub fn is_ready(&self, slot: usize) -> bool {
+        self.cells
+            .get(slot)
+            .is_some_and(|cell| !cell.cooldown.is_cooling())
+    }
+
+    /// Moves the cooldown sweeps along to now.
+    pub fn update(&mut self, now: f32) {
+        for cell in &mut self.cells {
+            if cell.cooldown.update(now) {
+                self.changed = true;
+            }
+        }
+    }
+
+    /// Applies a frame of input and reads the key labels from the
+    /// handler's bindings. Call before [`ItemDrag::update`]. Returns the
+    /// events, which are also emitted if a handle is set.
+    pub fn handle_input<AxisId, M: 'static>(
+        &mut self,
+        input: &WidgetInput,
+        handler: &InputHandler<A, AxisId>,
+        drag: &mut ItemDrag,
+        router: &mut MessageRouter<M>,
+    ) -> Vec<ActionBarEvent>
+    where
+        AxisId: Clone + Eq + Hash + Send + Sync,
+    {
+        let pressed = input.mouse_down && !self.was_down;
+        let released = !input.mouse_down && self.was_down;
+        let hovered = input.mouse_pos.and_then(|pos| self.slot_at(pos));
+        let mut events = Vec::new();
+
+        self.was_down = input.mouse_down;
+
+        if hovered != self.hovered {
+            if hovered.is_some_and(|slot| self.slot(slot).is_some()) {
+                self.pending_sounds.push(UiSound::Hover);
+            }
+
+            self.hovered = hovered;
+            self.changed = true;
+        }
+
+        match hovered {
+            Some(slot) if pressed && self.slot(slot).is_some() => {
+                self.pressed = Some(slot);
+
+                if !self.locked {
+                    let ability = self.slots[slot].clone().unwrap();
+                    let item = GridItem {
+                        item: ability.id,
+                        icon: ability.icon,
+                        count: 1,
+                        max_stack: 1,
+                        tooltip: ability.name,
+                    };
+
+                    drag.begin(
+                        SlotRef {
+                            grid: self.id,
+                            slot,
+                        },
+                        item,
+                        input.mouse_pos.unwrap_or_default(),
+                    );
+                }
+            }
+            Some(slot) if released => {
+                if drag.is_dragging() {
+                    events.extend(self.drop_onto(slot, drag));
+                } else if self.pressed == Some(slot) {
+                    events.extend(self.use_slot(slot));
+                }
+            }
+            _ => {}
+        }
+
+        if released {
+            self.pressed = None;
+        }
+
+        for slot in 0..self.cells.len() {
+            let cell = &mut self.cells[slot];
+            let Some(binding) = cell.binding.as_ref() else {
+                continue;
+            };
+            let label =
+                handler.bindings().action_label(binding).unwrap_or_default();
+            let down = handler.is_action_down(binding);
+            let press = down && !cell.was_action_down;
+
+            cell.was_action_down = down;
+
+            if label != cell.label {
+                cell.label = label;
+                self.changed = true;
+            }
+
+            if press {
+                events.extend(self.use_slot(slot));
+            }
+        }
+
+        for sound in self.pending_sounds.drain(..) {
+            router.play_sound(self.sounds.cue(sound));
+        }
+
+        if let Some(handle) = self.handle {
+            for event in &events {
+                router.emit(handle, *event);
+            }
+        }
+
+        events
+    }
+
+    /// The use event if the slot has a ready ability.
+    fn use_slot(&mut self, slot: usize) -> Option<ActionBarEvent> {
+        let ability = self.slot(slot)?.id;
+
+        if !self.is_ready(slot) {
+            self.pending_sounds.push(UiSound::Error);
+            return None;
+        }
+
+        self.pending_sounds.push(UiSound::Click);
+        Some(ActionBarEvent::Used { slot, ability })
+    }
+
+    /// The assignment for dropping the dragged item on slot.
+    fn drop_onto(
+        &mut self,
+        slot: usize,
+        drag: &mut ItemDrag,
+    ) -> Option<ActionBarEvent> {
+        let (from, item) = drag.drop_item()?;
+
+        if from.grid == self.id && from.slot == slot {
+            return None;
+        }
+
+        self.pending_sounds.push(UiSound::Close);
+        Some(ActionBarEvent::Assigned {
+            slot,
+            ability: item.item,
+            from,
+        })
+    }
+
+    fn slot_bounds(&self, slot: usize) -> Bounds {
+        let x =
+            self.position.x + slot as f32 * (self.slot_size.x + self.spacing);
+
+        Bounds::new(
+            x,
+            self.position.y,
+            x + self.slot_size.x,
+            self.position.y + self.slot_size.y,
+        )
+    }
+
+    /// Lays out the slots if anything changed.
+    fn refresh(&mut self, renderer: &mut GpuRenderer) {
+        if !self.changed {
+            return;
+        }
+
+        let z = self.position.z;
+        let size = self.slot_size;
+
+        for slot in 0..self.cells.len() {
+            let bounds = self.slot_bounds(slot);
+            let corner = Vec2::new(bounds.left, bounds.bottom);
+            let color = if self.hovered == Some(slot) {
+                self.hover_color
+            } else {
+                self.cell_color
+            };
+            let ability = self.slots[slot].as_ref();
+            let cell = &mut self.cells[slot];
+
+            cell.background
+                .set_position(corner.extend(z))
+                .set_size(size)
+                .set_color(color)
+                .set_border_width(1.0)
+                .set_border_color(self.border_color);
+
+            // lower Z renders over the slot.
+            cell.icon.texture = ability.map(|ability| ability.icon);
+            cell.icon.use_camera = false;
+            cell.icon.pos = (corner + 2.0).extend(z - 0.01);
+            cell.icon.hw = size - 4.0;
+            cell.icon.color = if cell.cooldown.is_cooling() {
+                self.cooldown_tint
+            } else {
+                Color::rgba(255, 255, 255, 255)
+            };
+
+            if let Some(icon) = ability.map(|ability| ability.icon) {
+                let (width, height) = icon.size();
+
+                cell.icon.uv = Vec4::new(0.0, 0.0, width as f32, height as f32);
+            }
+
+            cell.icon.changed = true;
+            cell.cooldown
+                .set_position((corner + 2.0).extend(z - 0.02))
+                .set_size(size - 4.0);
+
+            let line_height = cell.key.buffer.metrics().line_height;
+
+            cell.key
+                .set_buffer_size(renderer, size.x as i32, line_height as i32)
+                .set_default_color(self.key_color)
+                .set_text(renderer, &cell.label, Attrs::new())
+                .set_position(Vec3::new(
+                    corner.x + 3.0,
+                    bounds.top - line_height - 2.0,
+                    z - 0.03,
+                ));
+            cell.key.size = Vec2::new(size.x, line_height);
+        }
+
+        self.changed = false;
+    }
+
+    /// Frees the slots' buffers.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        for cell in self.cells {
+            cell.background.unload(renderer);
+            cell.icon.unload(renderer);
+            cell.key.unload(renderer);
+            cell.cooldown.unload(renderer);
+        }
+    }
+}
+
+impl RectRenderer {
+    pub fn action_bar_update<A>(
+        &mut self,
+        bar: &mut ActionBar<A>,
+        renderer: &mut GpuRenderer,
+    ) where
+        A: Clone + Eq + Hash + Send + Sync,
+    {
+        bar.refresh(renderer);
+
+        for cell in &mut bar.cells {
+            self.rect_update(&mut cell.background, renderer);
+            self.cooldown_overlay_update(&mut cell.cooldown, renderer);
+        }
+    }
+}
+
+impl ImageRenderer {
+    /// The dragged ability stays dimmed in its slot until dropped.
+    pub fn action_bar_update<A>(
+        &mut self,
+        bar: &mut ActionBar<A>,
+        drag: &ItemDrag,
+        renderer: &mut GpuRenderer,
+    ) where
+        A: Clone + Eq + Hash + Send + Sync,
+    {
+        bar.refresh(renderer);
+
+        let dragged = drag
+            .source()
+            .filter(|source| source.grid == bar.id)
+            .map(|source| source.slot);
+
+        for (slot, cell) in bar.cells.iter_mut().enumerate() {
+            if cell.icon.texture.is_none() {
+                continue;
+            }
+
+            let alpha = if dragged == Some(slot) { 90 } else { 255 };
+
+            if cell.icon.color.a() != alpha {
+                let color = cell.icon.color;
+
+                cell.icon.color =
+                    Color::rgba(color.r(), color.g(), color.b(), alpha);
+                cell.icon.changed = true;
+            }
+
+            self.image_update(&mut cell.icon, renderer);
+        }
+    }
+}
+
+impl TextRenderer {
+    pub fn action_bar_update<A>(
+        &mut self,
+        bar: &mut ActionBar<A>,
+        atlas: &mut TextAtlas,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError>
+    where
+        A: Clone + Eq + Hash + Send + Sync,
+    {
+        bar.refresh(renderer);
+
+        for cell in &mut bar.cells {
+            if !cell.label.is_empty() {
+                self.text_update(&mut cell.key, atlas, renderer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
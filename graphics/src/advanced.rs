@@ -0,0 +1,35 @@
+//! The raw GPU side of the renderers: bind group layouts, buffers,
+//! pipelines and the vertex and uniform types they upload. Only needed to
+//! write custom pipelines or renderers, and more likely to change between
+//! versions than what the prelude holds.
+
+pub use crate::systems::arena::BufferArena;
+pub use crate::systems::buffer::{
+    Buffer, BufferData, BufferLayout, BufferStore,
+};
+pub use crate::systems::instance_buffer::*;
+pub use crate::systems::layout::*;
+pub use crate::systems::pipelines::*;
+pub use crate::systems::static_vbo::*;
+pub use crate::systems::system::{GlobalUniform, SystemLayout};
+pub use crate::systems::vbo::*;
+pub use crate::textures::texturelayout::TextureLayout;
+pub use crate::transitions::pipeline::*;
+pub use crate::transitions::uniforms::*;
+
+#[cfg(feature = "text")]
+pub use crate::font::{pipeline::*, vertex::*};
+#[cfg(feature = "sprites")]
+pub use crate::images::{pipeline::*, vertex::*};
+#[cfg(feature = "lights")]
+pub use crate::lights::{pipeline::*, uniforms::*, vertex::*};
+#[cfg(feature = "maps")]
+pub use crate::maps::{pipeline::*, vertex::*};
+#[cfg(feature = "shapes")]
+pub use crate::mesh2d::pipeline::*;
+#[cfg(feature = "shapes")]
+pub use crate::rects::{pipeline::*, vertex::*};
+#[cfg(all(feature = "sprites", feature = "shapes"))]
+pub use crate::selection::{pipeline::*, vertex::*};
+#[cfg(feature = "maps")]
+pub use crate::water::{pipeline::*, uniforms::*, vertex::*};
@@ -1,11 +1,13 @@
 mod allocation;
 mod allocator;
+mod alpha_mask;
 mod group;
 mod handler;
 mod layer;
 
 pub use allocation::Allocation;
 pub use allocator::Allocator;
+pub use alpha_mask::AlphaMask;
 pub use group::AtlasGroup;
 pub use handler::Atlas;
 pub use layer::Layer;
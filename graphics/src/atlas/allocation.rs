@@ -4,6 +4,9 @@ pub struct Allocation<Data: Copy + Default = i32> {
     pub layer: usize,
     //Store any Extra data per Allocation.
     pub data: Data,
+    /// Repacks of the atlas before this was allocated. Older allocations
+    /// are looked up with [`crate::Atlas::current`].
+    pub generation: u32,
 }
 
 impl<Data: Copy + Default> Allocation<Data> {
@@ -0,0 +1,69 @@
+/// A CPU side copy of which parts of an upload are not transparent.
+/// Each bit covers a scale x scale block of pixels and is set if any pixel
+/// in the block has alpha above the threshold.
+#[derive(Clone, Debug)]
+pub struct AlphaMask {
+    /// Size in pixels of the upload.
+    pub width: u32,
+    pub height: u32,
+    /// Pixels per mask cell on each side.
+    pub scale: u32,
+    columns: u32,
+    bits: Vec<u64>,
+}
+
+impl AlphaMask {
+    /// Alpha at or below this counts as transparent.
+    pub const THRESHOLD: u8 = 8;
+
+    /// bytes_per_pixel is 4 for rgba or 1 for a single alpha channel. Alpha
+    /// is the last byte of each pixel.
+    pub fn from_bytes(
+        bytes: &[u8],
+        width: u32,
+        height: u32,
+        bytes_per_pixel: u32,
+        scale: u32,
+    ) -> Self {
+        let scale = scale.max(1);
+        let columns = width.div_ceil(scale);
+        let rows = height.div_ceil(scale);
+        let cells = (columns * rows) as usize;
+        let mut bits = vec![0u64; cells.div_ceil(64)];
+
+        for y in 0..height {
+            for x in 0..width {
+                let index = ((x + y * width) * bytes_per_pixel
+                    + bytes_per_pixel
+                    - 1) as usize;
+
+                if bytes.get(index).copied().unwrap_or(0) > Self::THRESHOLD {
+                    let cell = ((x / scale) + (y / scale) * columns) as usize;
+
+                    bits[cell / 64] |= 1 << (cell % 64);
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            scale,
+            columns,
+            bits,
+        }
+    }
+
+    /// Pixel position from the top left of the upload. Out of bounds
+    /// positions are transparent.
+    pub fn is_opaque(&self, x: u32, y: u32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+
+        let cell =
+            ((x / self.scale) + (y / self.scale) * self.columns) as usize;
+
+        self.bits[cell / 64] & (1 << (cell % 64)) != 0
+    }
+}
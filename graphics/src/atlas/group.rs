@@ -1,5 +1,7 @@
-use crate::{Allocation, Atlas, GpuRenderer, TextureGroup, TextureLayout};
-use std::hash::Hash;
+use crate::{
+    Allocation, AlphaMask, Atlas, GpuRenderer, TextureGroup, TextureLayout,
+};
+use std::{hash::Hash, sync::Arc};
 
 /// Group of a Atlas Details
 pub struct AtlasGroup<U: Hash + Eq + Clone = String, Data: Copy + Default = i32>
@@ -26,6 +28,29 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> AtlasGroup<U, Data> {
         Self { atlas, texture }
     }
 
+    /// Evicts the least recently used allocations once more than
+    /// pressure_max of the atlas is in use, down to pressure_min. See
+    /// [`Atlas::pressure`].
+    pub fn with_pressure(
+        renderer: &mut GpuRenderer,
+        format: wgpu::TextureFormat,
+        pressure_min: f32,
+        pressure_max: f32,
+    ) -> Self {
+        let mut group = Self::new(renderer, format);
+
+        group.atlas.set_pressure(pressure_min, pressure_max);
+        group
+    }
+
+    pub fn pin(&mut self, key: U) {
+        self.atlas.pin(key);
+    }
+
+    pub fn unpin(&mut self, key: &U) {
+        self.atlas.unpin(key);
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn upload(
         &mut self,
@@ -40,6 +65,21 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> AtlasGroup<U, Data> {
             .upload(hash, bytes, width, height, data, renderer)
     }
 
+    /// Repacks the atlas, see [`Atlas::repack`], and binds its new
+    /// texture.
+    pub fn repack(&mut self, renderer: &mut GpuRenderer) -> bool {
+        if !self.atlas.repack(renderer) {
+            return false;
+        }
+
+        self.texture = TextureGroup::from_view(
+            renderer,
+            &self.atlas.texture_view,
+            TextureLayout,
+        );
+        true
+    }
+
     pub fn trim(&mut self) {
         self.atlas.trim();
     }
@@ -63,4 +103,18 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> AtlasGroup<U, Data> {
     pub fn get(&mut self, key: &U) -> Option<Allocation<Data>> {
         self.atlas.get(key)
     }
+
+    pub fn alpha_mask(
+        &self,
+        allocation: &Allocation<Data>,
+    ) -> Option<Arc<AlphaMask>> {
+        self.atlas.alpha_mask(allocation)
+    }
+
+    pub fn current(
+        &self,
+        allocation: &Allocation<Data>,
+    ) -> Option<Allocation<Data>> {
+        self.atlas.current(allocation)
+    }
 }
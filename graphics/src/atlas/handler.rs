@@ -1,6 +1,10 @@
-use crate::{Allocation, GpuRenderer, Layer};
+use crate::{Allocation, AlphaMask, GpuRenderer, Layer};
 use lru::LruCache;
-use std::{collections::HashSet, hash::Hash};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    sync::Arc,
+};
 
 pub struct Atlas<U: Hash + Eq + Clone = String, Data: Copy + Default = i32> {
     /// Texture in GRAM
@@ -19,6 +23,31 @@ pub struct Atlas<U: Hash + Eq + Clone = String, Data: Copy + Default = i32> {
     /// When the System will Error if reached. This is the max allowed Layers
     /// Default is 256 as Most GPU allow a max of 256.
     pub max_layers: u32,
+    /// Pixels per cell of the alpha masks made on upload. None skips making
+    /// them.
+    pub alpha_mask_scale: Option<u32>,
+    /// Alpha masks by allocation layer and position.
+    alpha_masks: HashMap<(usize, u32, u32), Arc<AlphaMask>>,
+    /// Allocations freed so far.
+    evictions: u64,
+    /// Fraction of the atlas in use, see [`Atlas::pressure`], past which
+    /// uploads evict the least recently used allocations. 1.0 or more
+    /// only evicts when out of room.
+    pub pressure_max: f32,
+    /// Pressure eviction stops once it is down to this.
+    pub pressure_min: f32,
+    /// Layers the pressure is measured against, as a texel budget. None
+    /// measures against the layers allocated so far.
+    pub pressure_layers: Option<u32>,
+    /// Keys never evicted, only freed by remove or clear.
+    pinned: HashSet<U>,
+    /// Texels of all allocations.
+    used_area: u64,
+    /// Repacks so far.
+    generation: u32,
+    /// Where allocations from before the repacks are now, by generation,
+    /// layer and position.
+    relocations: HashMap<(u32, usize, u32, u32), Allocation<Data>>,
 }
 
 impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
@@ -40,31 +69,27 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
                     allocation,
                     layer: i,
                     data,
+                    generation: self.generation,
                 });
             }
         }
 
-        /* Try to see if we can clear out unused allocations first. */
-        loop {
-            let (key, _) = self.cache.peek_lru()?;
+        /* Try to see if we can clear out unused allocations first. Pinned
+        ones are skipped. */
+        while let Some(key) = self.lru_evictable() {
+            let allocation = self.cache.pop(&key)?;
+            let layer_id = allocation.layer;
 
-            //Check if ID has been used yet?
-            if self.last_used.contains(key) {
-                //Failed to find any unused allocations so lets try to add a layer.
-                break;
-            }
+            self.free(&allocation);
 
-            let (_, allocation) = self.cache.pop_lru()?;
-            let layer_id = allocation.layer;
             let layer = self.layers.get_mut(layer_id).unwrap();
 
-            layer.allocator.deallocate(allocation.allocation);
-
             if let Some(allocation) = layer.allocator.allocate(width, height) {
                 return Some(Allocation {
                     allocation,
                     layer: layer_id,
                     data,
+                    generation: self.generation,
                 });
             }
         }
@@ -85,6 +110,7 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
                 allocation,
                 layer: self.layers.len() - 1,
                 data,
+                generation: self.generation,
             });
         }
 
@@ -97,8 +123,324 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
             layer.allocator.clear();
         }
 
+        self.evictions += self.cache.len() as u64;
+        self.used_area = 0;
         self.cache.clear();
         self.last_used.clear();
+        self.alpha_masks.clear();
+        self.relocations.clear();
+    }
+
+    /// Allocations freed so far by eviction, remove or clear. Holders of
+    /// allocations can compare it with an earlier count to know if theirs
+    /// may be gone.
+    pub fn evictions(&self) -> u64 {
+        self.evictions
+    }
+
+    /// Repacks so far. Holders of allocations can compare it with an
+    /// earlier count to know if theirs may have moved.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// The allocation as it is now, moved by any repacks since it was
+    /// made. None if it was freed before a repack moved it.
+    pub fn current(
+        &self,
+        allocation: &Allocation<Data>,
+    ) -> Option<Allocation<Data>> {
+        if allocation.generation == self.generation {
+            return Some(*allocation);
+        }
+
+        let (layer, x, y) = Self::mask_key(allocation);
+
+        self.relocations
+            .get(&(allocation.generation, layer, x, y))
+            .copied()
+    }
+
+    /// Packs every live allocation again from an empty atlas, tallest
+    /// first, so the space freed by removed textures is joined up again.
+    /// The texels are moved with copies on the GPU. Allocations handed out
+    /// before then are out of date, get their new place from
+    /// [`Atlas::current`]. Bind groups made from the old texture view need
+    /// making again, which [`crate::AtlasGroup::repack`] does.
+    ///
+    /// Returns false and changes nothing if they no longer fit.
+    pub fn repack(&mut self, renderer: &GpuRenderer) -> bool {
+        let mut live: Vec<(U, Allocation<Data>)> = self
+            .cache
+            .iter()
+            .map(|(key, allocation)| (key.clone(), *allocation))
+            .collect();
+
+        live.sort_by_key(|(_, allocation)| {
+            let (width, height) = allocation.size();
+
+            std::cmp::Reverse((height, width))
+        });
+
+        let generation = self.generation.wrapping_add(1);
+        let mut layers: Vec<Layer> = Vec::new();
+        let mut moved = Vec::with_capacity(live.len());
+
+        for (key, old) in live {
+            let (width, height) = old.size();
+            let placed =
+                layers.iter_mut().enumerate().find_map(|(i, layer)| {
+                    layer
+                        .allocator
+                        .allocate(width, height)
+                        .map(|allocation| (i, allocation))
+                });
+            let (layer, allocation) = match placed {
+                Some(placed) => placed,
+                None => {
+                    if layers.len() + 1 >= self.max_layers as usize {
+                        return false;
+                    }
+
+                    let mut layer = Layer::new(self.extent.width);
+                    let Some(allocation) =
+                        layer.allocator.allocate(width, height)
+                    else {
+                        return false;
+                    };
+
+                    layers.push(layer);
+                    (layers.len() - 1, allocation)
+                }
+            };
+
+            moved.push((
+                key,
+                old,
+                Allocation {
+                    allocation,
+                    layer,
+                    data: old.data,
+                    generation,
+                },
+            ));
+        }
+
+        // Same as a new atlas.
+        while layers.len() < 2 {
+            layers.push(Layer::new(self.extent.width));
+        }
+
+        let texture =
+            renderer.device().create_texture(&wgpu::TextureDescriptor {
+                label: Some("Texture"),
+                size: wgpu::Extent3d {
+                    width: self.extent.width,
+                    height: self.extent.height,
+                    depth_or_array_layers: layers.len() as u32,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[self.format],
+            });
+        let mut encoder = renderer.device().create_command_encoder(
+            &wgpu::CommandEncoderDescriptor {
+                label: Some("Atlas repack encoder"),
+            },
+        );
+
+        for (_, old, new) in &moved {
+            let (x, y) = old.position();
+            let (new_x, new_y) = new.position();
+            let (width, height) = old.size();
+
+            encoder.copy_texture_to_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &self.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x,
+                        y,
+                        z: old.layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: new_x,
+                        y: new_y,
+                        z: new.layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        renderer.queue().submit(std::iter::once(encoder.finish()));
+
+        let by_position: HashMap<(usize, u32, u32), Allocation<Data>> = moved
+            .iter()
+            .map(|(_, old, new)| (Self::mask_key(old), *new))
+            .collect();
+
+        // Earlier relocations follow their allocation, or are dropped with
+        // it.
+        self.relocations.retain(|_, current| {
+            match by_position.get(&Self::mask_key(current)) {
+                Some(new) => {
+                    *current = *new;
+                    true
+                }
+                None => false,
+            }
+        });
+
+        let mut alpha_masks = HashMap::with_capacity(self.alpha_masks.len());
+
+        for (key, old, new) in moved {
+            let (layer, x, y) = Self::mask_key(&old);
+
+            if let Some(mask) = self.alpha_masks.remove(&(layer, x, y)) {
+                alpha_masks.insert(Self::mask_key(&new), mask);
+            }
+
+            self.relocations.insert((old.generation, layer, x, y), new);
+
+            // In place, so the eviction order is kept.
+            if let Some(allocation) = self.cache.peek_mut(&key) {
+                *allocation = new;
+            }
+        }
+
+        self.texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Texture Atlas"),
+            format: Some(self.format),
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            aspect: wgpu::TextureAspect::All,
+            base_mip_level: 0,
+            mip_level_count: Some(1),
+            base_array_layer: 0,
+            array_layer_count: Some(layers.len() as u32),
+        });
+        self.texture = texture;
+        self.layers = layers;
+        self.alpha_masks = alpha_masks;
+        self.generation = generation;
+        true
+    }
+
+    fn area(allocation: &Allocation<Data>) -> u64 {
+        let (width, height) = allocation.size();
+
+        width as u64 * height as u64
+    }
+
+    /// Least recently used key that is neither used since the last trim
+    /// nor pinned.
+    fn lru_evictable(&self) -> Option<U> {
+        self.cache
+            .iter()
+            .rev()
+            .map(|(key, _)| key)
+            .find(|key| {
+                !self.last_used.contains(*key) && !self.pinned.contains(*key)
+            })
+            .cloned()
+    }
+
+    /// Texels pressure is measured against, pressure_layers layers or
+    /// else the layers allocated so far.
+    fn capacity(&self) -> f64 {
+        let layers = self.pressure_layers.unwrap_or(self.layers.len() as u32);
+
+        self.extent.width as f64
+            * self.extent.height as f64
+            * layers.max(1) as f64
+    }
+
+    /// Fraction of the texels of the allocated layers in use, or of
+    /// pressure_layers layers if set.
+    pub fn pressure(&self) -> f32 {
+        (self.used_area as f64 / self.capacity()) as f32
+    }
+
+    pub fn set_pressure(&mut self, pressure_min: f32, pressure_max: f32) {
+        self.pressure_min = pressure_min.min(pressure_max);
+        self.pressure_max = pressure_max;
+    }
+
+    /// Keeps the key's allocation from eviction, like for UI skins that
+    /// must always be there. It can be pinned before it is uploaded.
+    pub fn pin(&mut self, key: U) {
+        self.pinned.insert(key);
+    }
+
+    pub fn unpin(&mut self, key: &U) {
+        self.pinned.remove(key);
+    }
+
+    pub fn is_pinned(&self, key: &U) -> bool {
+        self.pinned.contains(key)
+    }
+
+    /// Past pressure_max, evicts least recently used allocations until
+    /// down to pressure_min. Allocations used since the last trim, pinned
+    /// ones and keep are left.
+    fn relieve_pressure(&mut self, keep: &U) {
+        if self.pressure() <= self.pressure_max {
+            return;
+        }
+
+        let mut evict = Vec::new();
+        let mut area = self.used_area;
+        let capacity = self.capacity();
+
+        for (key, allocation) in self.cache.iter().rev() {
+            if (area as f64 / capacity) as f32 <= self.pressure_min {
+                break;
+            }
+
+            if key == keep
+                || self.last_used.contains(key)
+                || self.pinned.contains(key)
+            {
+                continue;
+            }
+
+            area -= Self::area(allocation);
+            evict.push(key.clone());
+        }
+
+        for key in evict {
+            self.remove(&key);
+        }
+    }
+
+    fn mask_key(allocation: &Allocation<Data>) -> (usize, u32, u32) {
+        let (x, y) = allocation.position();
+
+        (allocation.layer, x, y)
+    }
+
+    /// The allocation's alpha mask if masks were enabled when it was
+    /// uploaded.
+    pub fn alpha_mask(
+        &self,
+        allocation: &Allocation<Data>,
+    ) -> Option<Arc<AlphaMask>> {
+        self.alpha_masks.get(&Self::mask_key(allocation)).cloned()
     }
 
     pub fn trim(&mut self) {
@@ -250,6 +592,16 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
             last_used: HashSet::default(),
             format,
             max_layers: limits.max_texture_array_layers,
+            alpha_mask_scale: None,
+            alpha_masks: HashMap::new(),
+            evictions: 0,
+            pressure_max: 1.0,
+            pressure_min: 1.0,
+            pressure_layers: None,
+            pinned: HashSet::default(),
+            used_area: 0,
+            generation: 0,
+            relocations: HashMap::new(),
         }
     }
 
@@ -275,12 +627,96 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
             };
 
             self.upload_allocation(bytes, &allocation, renderer);
+
+            if let Some(scale) = self.alpha_mask_scale {
+                let bytes_per_pixel =
+                    if self.format == wgpu::TextureFormat::Rgba8UnormSrgb {
+                        4
+                    } else {
+                        1
+                    };
+
+                self.alpha_masks.insert(
+                    Self::mask_key(&allocation),
+                    Arc::new(AlphaMask::from_bytes(
+                        bytes,
+                        width,
+                        height,
+                        bytes_per_pixel,
+                        scale,
+                    )),
+                );
+            }
+
+            self.used_area += Self::area(&allocation);
             self.cache.push(key.clone(), allocation);
+            self.relieve_pressure(&key);
             Some(allocation)
         }
     }
 
-    fn upload_allocation(
+    /// Allocates space under key without uploading anything, for filling
+    /// on the GPU. Keeps the key's allocation if it is the same size,
+    /// otherwise frees it and allocates again.
+    pub fn reserve(
+        &mut self,
+        key: U,
+        width: u32,
+        height: u32,
+        data: Data,
+        renderer: &GpuRenderer,
+    ) -> Option<Allocation<Data>> {
+        if let Some(allocation) = self.cache.get_mut(&key) {
+            if allocation.size() == (width, height) {
+                allocation.data = data;
+                let allocation = *allocation;
+
+                self.last_used.insert(key);
+                return Some(allocation);
+            }
+
+            self.remove(&key);
+        }
+
+        let nlayers = self.layers.len();
+        let allocation = self.allocate(width, height, data)?;
+
+        self.grow(self.layers.len() - nlayers, renderer);
+        self.used_area += Self::area(&allocation);
+        self.cache.push(key.clone(), allocation);
+        self.relieve_pressure(&key);
+        Some(allocation)
+    }
+
+    /// Frees the key's allocation.
+    pub fn remove(&mut self, key: &U) {
+        if let Some(allocation) = self.cache.pop(key) {
+            self.last_used.remove(key);
+            self.free(&allocation);
+        }
+    }
+
+    /// Gives back the space of an allocation already taken out of the
+    /// cache, along with its alpha mask and the relocations leading to it,
+    /// so older handles can't resolve to whatever is allocated there next.
+    fn free(&mut self, allocation: &Allocation<Data>) {
+        let key = Self::mask_key(allocation);
+
+        self.alpha_masks.remove(&key);
+        self.evictions += 1;
+        self.used_area -= Self::area(allocation);
+
+        if !self.relocations.is_empty() {
+            self.relocations
+                .retain(|_, current| Self::mask_key(current) != key);
+        }
+
+        if let Some(layer) = self.layers.get_mut(allocation.layer) {
+            layer.allocator.deallocate(allocation.allocation);
+        }
+    }
+
+    pub(crate) fn upload_allocation(
         &mut self,
         buffer: &[u8],
         allocation: &Allocation<Data>,
@@ -320,4 +756,92 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
             },
         );
     }
+
+    /// Uploads many allocations through one staging buffer and a single
+    /// submit rather than a write each, for bursts like a frame's new
+    /// glyphs.
+    #[cfg(feature = "text")]
+    pub(crate) fn upload_allocations(
+        &self,
+        uploads: &[(&[u8], Allocation<Data>)],
+        renderer: &GpuRenderer,
+    ) {
+        use wgpu::util::DeviceExt;
+
+        if uploads.is_empty() {
+            return;
+        }
+
+        let bytes_per_pixel =
+            if self.format == wgpu::TextureFormat::Rgba8UnormSrgb {
+                4
+            } else {
+                1
+            };
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+        let mut staging = Vec::new();
+        let mut copies = Vec::with_capacity(uploads.len());
+
+        // Rows are padded to the copy alignment, which keeps every
+        // allocation's offset aligned too.
+        for (bytes, allocation) in uploads {
+            let (width, height) = allocation.size();
+            let row = width as usize * bytes_per_pixel;
+            let padded = row.div_ceil(align) * align;
+            let offset = staging.len() as u64;
+
+            for line in bytes.chunks(row).take(height as usize) {
+                staging.extend_from_slice(line);
+                staging.resize(staging.len() + padded - line.len(), 0);
+            }
+
+            copies.push((offset, padded as u32, *allocation));
+        }
+
+        let buffer = renderer.device().create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Atlas staging buffer"),
+                contents: &staging,
+                usage: wgpu::BufferUsages::COPY_SRC,
+            },
+        );
+        let mut encoder = renderer.device().create_command_encoder(
+            &wgpu::CommandEncoderDescriptor {
+                label: Some("Atlas upload encoder"),
+            },
+        );
+
+        for (offset, bytes_per_row, allocation) in copies {
+            let (x, y) = allocation.position();
+            let (width, height) = allocation.size();
+
+            encoder.copy_buffer_to_texture(
+                wgpu::ImageCopyBuffer {
+                    buffer: &buffer,
+                    layout: wgpu::ImageDataLayout {
+                        offset,
+                        bytes_per_row: Some(bytes_per_row),
+                        rows_per_image: Some(height),
+                    },
+                },
+                wgpu::ImageCopyTexture {
+                    texture: &self.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x,
+                        y,
+                        z: allocation.layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        renderer.queue().submit(std::iter::once(encoder.finish()));
+    }
 }
@@ -0,0 +1,271 @@
+use crate::AscendingError;
+#[cfg(feature = "maps")]
+use crate::{Map, MapCompression};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    panic,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Exists while a session runs. Finding it on startup means the last one
+/// never finished.
+const LOCK_FILE: &str = "session.lock";
+/// The panic message of the crashed session.
+const CRASH_FILE: &str = "crash.log";
+/// Where a crashed session's files are kept until recovered or discarded.
+const RECOVERY_DIR: &str = "recovery";
+const SNAPSHOT_EXT: &str = "snap";
+
+/// Editor state that can be saved for crash recovery.
+pub trait Snapshot {
+    fn snapshot(&self) -> Vec<u8>;
+}
+
+#[cfg(feature = "maps")]
+impl Snapshot for Map {
+    /// Read it back with [`Map::from_bytes`].
+    fn snapshot(&self) -> Vec<u8> {
+        self.to_bytes(MapCompression::Lz4)
+    }
+}
+
+type Snapshots = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+/// Periodically writes snapshots of editor state into a folder so a session
+/// lost to a panic or device loss can be recovered on the next startup.
+///
+/// Capture snapshots when things change, update writes them out once the
+/// interval passes. A panic writes whatever is still pending. Call finish
+/// on a clean exit, otherwise the next session offers recovery.
+pub struct Autosave {
+    dir: PathBuf,
+    pub interval: Duration,
+    last_save: Instant,
+    /// Latest captured snapshots, shared with the panic hook.
+    pending: Snapshots,
+    recovery: Vec<String>,
+    crash_reason: Option<String>,
+}
+
+impl Autosave {
+    /// Starts the session in dir, collecting what the last session left
+    /// behind if it did not finish.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, AscendingError> {
+        let dir = dir.into();
+        let recovery_dir = dir.join(RECOVERY_DIR);
+
+        fs::create_dir_all(&dir)?;
+
+        // Move the crashed session's files aside so this session's saves
+        // can't overwrite them before they are recovered.
+        if dir.join(LOCK_FILE).exists() {
+            fs::create_dir_all(&recovery_dir)?;
+
+            for path in snapshot_files(&dir)? {
+                if let Some(file) = path.file_name() {
+                    fs::rename(&path, recovery_dir.join(file))?;
+                }
+            }
+
+            if dir.join(CRASH_FILE).exists() {
+                fs::rename(
+                    dir.join(CRASH_FILE),
+                    recovery_dir.join(CRASH_FILE),
+                )?;
+            }
+        } else {
+            // Leftovers of a session that finished are not worth offering.
+            for path in snapshot_files(&dir)? {
+                remove_file(&path)?;
+            }
+        }
+
+        // Also offers recovery the user didn't decide on last time.
+        let mut recovery: Vec<String> = if recovery_dir.is_dir() {
+            snapshot_files(&recovery_dir)?
+                .iter()
+                .filter_map(|path| path.file_stem())
+                .map(|name| name.to_string_lossy().into_owned())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let crash_reason =
+            fs::read_to_string(recovery_dir.join(CRASH_FILE)).ok();
+
+        recovery.sort();
+        fs::write(dir.join(LOCK_FILE), std::process::id().to_string())?;
+
+        Ok(Self {
+            dir,
+            interval: Duration::from_secs(60),
+            last_save: Instant::now(),
+            pending: Snapshots::default(),
+            recovery,
+            crash_reason,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// if the last session did not finish and left snapshots behind.
+    pub fn has_recovery(&self) -> bool {
+        !self.recovery.is_empty()
+    }
+
+    /// Names of the snapshots the last session left behind.
+    pub fn recovery_names(&self) -> &[String] {
+        &self.recovery
+    }
+
+    /// The panic message of the last session, if it panicked.
+    pub fn crash_reason(&self) -> Option<&str> {
+        self.crash_reason.as_deref()
+    }
+
+    /// The bytes of a snapshot left by the last session.
+    pub fn recover(
+        &self,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>, AscendingError> {
+        if !self.recovery.iter().any(|n| n == name) {
+            return Ok(None);
+        }
+
+        let path = self
+            .dir
+            .join(RECOVERY_DIR)
+            .join(format!("{name}.{SNAPSHOT_EXT}"));
+
+        match fs::read(path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Deletes the last session's snapshots, once recovered or declined.
+    pub fn discard_recovery(&mut self) -> Result<(), AscendingError> {
+        self.recovery.clear();
+        self.crash_reason = None;
+
+        match fs::remove_dir_all(self.dir.join(RECOVERY_DIR)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Keeps a snapshot to write on the next save. name is the file name
+    /// so it should be short and plain, like `map` or `gui`.
+    pub fn capture<T: Snapshot>(&mut self, name: &str, target: &T) {
+        let bytes = target.snapshot();
+
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(name.to_owned(), bytes);
+        }
+    }
+
+    /// if the interval has passed since the last save.
+    pub fn is_due(&self) -> bool {
+        self.last_save.elapsed() >= self.interval
+    }
+
+    /// Saves the captured snapshots once the interval has passed. Returns
+    /// true if it saved.
+    pub fn update(&mut self) -> Result<bool, AscendingError> {
+        if !self.is_due() {
+            return Ok(false);
+        }
+
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Writes the captured snapshots now, like before a risky operation.
+    pub fn save(&mut self) -> Result<(), AscendingError> {
+        self.last_save = Instant::now();
+
+        let pending = match self.pending.lock() {
+            Ok(mut pending) => std::mem::take(&mut *pending),
+            Err(_) => return Ok(()),
+        };
+
+        write_snapshots(&self.dir, &pending)
+    }
+
+    /// Chains a panic hook that writes the pending snapshots and the panic
+    /// message. Install it after the app's own hook.
+    pub fn install_panic_hook(&self) {
+        let dir = self.dir.clone();
+        let pending = self.pending.clone();
+        let previous = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            // try_lock as the panic may have happened while it was locked.
+            if let Ok(pending) = pending.try_lock() {
+                let _ = write_snapshots(&dir, &pending);
+            }
+
+            let _ = fs::write(dir.join(CRASH_FILE), info.to_string());
+            previous(info);
+        }));
+    }
+
+    /// Ends the session cleanly, removing its snapshots and lock. Recovery
+    /// that wasn't discarded is offered again next time.
+    pub fn finish(&mut self) -> Result<(), AscendingError> {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.clear();
+        }
+
+        for path in snapshot_files(&self.dir)? {
+            remove_file(&path)?;
+        }
+
+        remove_file(&self.dir.join(CRASH_FILE))?;
+        remove_file(&self.dir.join(LOCK_FILE))
+    }
+}
+
+/// Writes through a temporary file so a crash mid write keeps the old one.
+fn write_snapshots(
+    dir: &Path,
+    snapshots: &BTreeMap<String, Vec<u8>>,
+) -> Result<(), AscendingError> {
+    for (name, bytes) in snapshots {
+        let path = dir.join(format!("{name}.{SNAPSHOT_EXT}"));
+        let temp = path.with_extension("tmp");
+
+        fs::write(&temp, bytes)?;
+        fs::rename(&temp, &path)?;
+    }
+
+    Ok(())
+}
+
+fn snapshot_files(dir: &Path) -> Result<Vec<PathBuf>, AscendingError> {
+    let mut files = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.extension().is_some_and(|ext| ext == SNAPSHOT_EXT) {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+fn remove_file(path: &Path) -> Result<(), AscendingError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
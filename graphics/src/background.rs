@@ -0,0 +1,153 @@
+use crate::AnimationClock;
+use winit::event::WindowEvent;
+
+/// The window's focus and visibility changing, as handed to
+/// [`crate::App::window_activity`] and plugins.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WindowActivity {
+    FocusGained,
+    FocusLost,
+    /// Fully covered by other windows or minimized. Not every platform
+    /// reports it.
+    Occluded,
+    Revealed,
+}
+
+impl WindowActivity {
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        match event {
+            WindowEvent::Focused(true) => Some(WindowActivity::FocusGained),
+            WindowEvent::Focused(false) => Some(WindowActivity::FocusLost),
+            WindowEvent::Occluded(true) => Some(WindowActivity::Occluded),
+            WindowEvent::Occluded(false) => Some(WindowActivity::Revealed),
+            _ => None,
+        }
+    }
+}
+
+/// What the [`crate::Runner`] does while the game is in the background.
+/// Everything is off by default.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BackgroundSettings {
+    /// Pauses the animation clock, unless it was already paused.
+    pub pause_clock: bool,
+    /// Asks audio plugins to go quiet through
+    /// [`BackgroundState::is_audio_muted`].
+    pub mute_audio: bool,
+    /// Frame cap while in the background, below the normal one.
+    pub fps: Option<f32>,
+}
+
+/// Tracks if the window is in the background and applies the
+/// [`BackgroundSettings`].
+#[derive(Clone, Debug)]
+pub struct BackgroundState {
+    settings: BackgroundSettings,
+    focused: bool,
+    occluded: bool,
+    /// Set by the app to act as if in the background or not, whatever the
+    /// window is doing.
+    forced: Option<bool>,
+    /// What background was when last applied.
+    applied: bool,
+    /// If pausing the clock was our doing, so a pause the game made itself
+    /// isn't undone.
+    paused_clock: bool,
+}
+
+impl Default for BackgroundState {
+    fn default() -> Self {
+        Self {
+            settings: BackgroundSettings::default(),
+            focused: true,
+            occluded: false,
+            forced: None,
+            applied: false,
+            paused_clock: false,
+        }
+    }
+}
+
+impl BackgroundState {
+    pub fn new(settings: BackgroundSettings) -> Self {
+        Self {
+            settings,
+            ..Self::default()
+        }
+    }
+
+    pub fn settings(&self) -> &BackgroundSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: BackgroundSettings) {
+        self.settings = settings;
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn is_occluded(&self) -> bool {
+        self.occluded
+    }
+
+    /// Unfocused or occluded, unless overridden.
+    pub fn is_background(&self) -> bool {
+        self.forced.unwrap_or(!self.focused || self.occluded)
+    }
+
+    /// Some(false) keeps the game running at full speed in the background,
+    /// like for a hosted server. Some(true) treats it as in the
+    /// background. None follows the window again.
+    pub fn set_override(&mut self, background: Option<bool>) {
+        self.forced = background;
+    }
+
+    pub fn is_audio_muted(&self) -> bool {
+        self.settings.mute_audio && self.is_background()
+    }
+
+    /// The frame cap to use given the normal one.
+    pub fn target_fps(&self, fps: Option<f32>) -> Option<f32> {
+        match (self.is_background(), self.settings.fps) {
+            (true, Some(background)) => {
+                Some(fps.map_or(background, |fps| fps.min(background)).max(1.0))
+            }
+            _ => fps,
+        }
+    }
+
+    pub fn handle_activity(&mut self, activity: WindowActivity) {
+        match activity {
+            WindowActivity::FocusGained => self.focused = true,
+            WindowActivity::FocusLost => self.focused = false,
+            WindowActivity::Occluded => self.occluded = true,
+            WindowActivity::Revealed => self.occluded = false,
+        }
+    }
+
+    /// Pauses or resumes the clock when going in or out of the background.
+    /// Returns if that changed since the last call.
+    pub fn apply(&mut self, clock: &mut AnimationClock) -> bool {
+        let background = self.is_background();
+
+        if background == self.applied {
+            return false;
+        }
+
+        self.applied = background;
+
+        if background {
+            if self.settings.pause_clock && !clock.is_paused() {
+                clock.pause();
+                self.paused_clock = true;
+            }
+        } else if self.paused_clock {
+            clock.resume();
+            self.paused_clock = false;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{
+    AscendingError, Color, DrawOrder, GpuRenderer, Index, OrderedIndex,
+    RectRenderer, RectVertex, Text, TextAtlas, TextRenderer, Vec2, Vec3,
+};
+use cosmic_text::{Attrs, Metrics};
+
+/// How much closer each draw call is placed than the one before it.
+const CANVAS_Z_STEP: f32 = 0.001;
+
+/// What a pooled text was last set to so unchanged text isn't reshaped.
+struct CanvasText {
+    text: Text,
+    string: String,
+    font_size: f32,
+    color: Color,
+}
+
+/// Quick drawing for tools, HUDs and debug views without keeping objects
+/// around. Call clear at the start of the frame, draw, then update to batch
+/// everything into the rect and text renderers. Later calls draw on top of
+/// earlier ones.
+pub struct Canvas {
+    rects: Vec<RectVertex>,
+    rect_store_id: Index,
+    /// Reused between frames. Only grows.
+    texts: Vec<CanvasText>,
+    /// How many of texts are used this frame.
+    text_count: usize,
+    /// Depth of the first draw call.
+    pub z: f32,
+    next_z: f32,
+    /// if the shapes should render with the camera's view.
+    pub use_camera: bool,
+    pub render_layer: u32,
+    /// Multiplies positions, sizes and font sizes when not using the
+    /// camera. Set it to [`crate::System::gui_scale`] for HUDs.
+    pub scale: f32,
+}
+
+impl Canvas {
+    pub fn new(renderer: &mut GpuRenderer, z: f32, render_layer: u32) -> Self {
+        Self {
+            rects: Vec::new(),
+            rect_store_id: renderer.new_buffer(),
+            texts: Vec::new(),
+            text_count: 0,
+            z,
+            next_z: z,
+            use_camera: false,
+            render_layer,
+            scale: 1.0,
+        }
+    }
+
+    /// Removes everything drawn last frame.
+    pub fn clear(&mut self) {
+        self.rects.clear();
+        self.text_count = 0;
+        self.next_z = self.z;
+    }
+
+    fn take_z(&mut self) -> f32 {
+        let z = self.next_z;
+
+        self.next_z -= CANVAS_Z_STEP;
+        z
+    }
+
+    fn ui_scale(&self) -> f32 {
+        if self.use_camera {
+            1.0
+        } else {
+            self.scale
+        }
+    }
+
+    fn push_rect(
+        &mut self,
+        pos: Vec2,
+        size: Vec2,
+        color: Color,
+        border_width: f32,
+        border_color: Color,
+        rotation: f32,
+    ) {
+        let z = self.take_z();
+        let scale = self.ui_scale();
+        let (pos, size) = (pos * scale, size * scale);
+
+        self.rects.push(RectVertex {
+            position: [pos.x, pos.y, z],
+            size: size.to_array(),
+            color: color.0,
+            border_width: border_width * scale,
+            border_color: border_color.0,
+            radius: 0.0,
+            use_camera: u32::from(self.use_camera),
+            rotation,
+            sweep: 1.0,
+            user_data: [0.0; 4],
+        });
+    }
+
+    pub fn rect(&mut self, pos: Vec2, size: Vec2, color: Color) -> &mut Self {
+        self.push_rect(pos, size, color, 0.0, Color::rgba(0, 0, 0, 0), 0.0);
+        self
+    }
+
+    /// A rect with only its border drawn.
+    pub fn rect_outline(
+        &mut self,
+        pos: Vec2,
+        size: Vec2,
+        width: f32,
+        color: Color,
+    ) -> &mut Self {
+        self.push_rect(pos, size, Color::rgba(0, 0, 0, 0), width, color, 0.0);
+        self
+    }
+
+    pub fn line(
+        &mut self,
+        from: Vec2,
+        to: Vec2,
+        thickness: f32,
+        color: Color,
+    ) -> &mut Self {
+        let dir = to - from;
+        let angle = dir.y.atan2(dir.x);
+        // The rect rotates around its corner so move it down half the
+        // thickness to center it on the line.
+        let offset = Vec2::new(angle.sin(), -angle.cos()) * (thickness * 0.5);
+
+        self.push_rect(
+            from + offset,
+            Vec2::new(dir.length(), thickness),
+            color,
+            0.0,
+            Color::rgba(0, 0, 0, 0),
+            angle.to_degrees(),
+        );
+        self
+    }
+
+    /// Text with its top left corner at pos.
+    pub fn text(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        pos: Vec2,
+        string: &str,
+        font_size: f32,
+        color: Color,
+    ) -> &mut Self {
+        let z = self.take_z();
+        let scale = self.ui_scale();
+        let (pos, font_size) = (pos * scale, font_size * scale);
+        let lines = string.lines().count().max(1) as f32;
+        let size = Vec2::new(renderer.size().width, font_size * lines);
+        let pos = Vec3::new(pos.x, pos.y - size.y, z);
+        let metrics = Metrics::new(font_size, font_size);
+
+        if self.text_count == self.texts.len() {
+            self.texts.push(CanvasText {
+                text: Text::new(renderer, Some(metrics), pos, size),
+                string: String::new(),
+                font_size,
+                color,
+            });
+        }
+
+        let entry = &mut self.texts[self.text_count];
+        self.text_count += 1;
+
+        if entry.font_size != font_size {
+            entry.font_size = font_size;
+            entry
+                .text
+                .buffer
+                .set_metrics(&mut renderer.font_sys, metrics);
+            entry.text.changed = true;
+        }
+
+        if entry.string != string {
+            entry.string = string.to_owned();
+            entry.text.set_text(renderer, string, Attrs::new());
+        }
+
+        if entry.color != color {
+            entry.color = color;
+            entry.text.set_default_color(color);
+        }
+
+        if entry.text.pos != pos || entry.text.size != size {
+            entry.text.size = size;
+            entry.text.set_position(pos).set_buffer_size(
+                renderer,
+                size.x as i32,
+                size.y as i32,
+            );
+        }
+
+        if entry.text.use_camera != self.use_camera {
+            entry.text.use_camera = self.use_camera;
+            entry.text.changed = true;
+        }
+
+        self
+    }
+
+    /// Batches this frame's drawing into the renderers.
+    pub fn update(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        rect_renderer: &mut RectRenderer,
+        text_renderer: &mut TextRenderer,
+        text_atlas: &mut TextAtlas,
+    ) -> Result<(), AscendingError> {
+        if !self.rects.is_empty() {
+            if let Some(store) = renderer.get_buffer_mut(&self.rect_store_id) {
+                store.store = bytemuck::cast_slice(&self.rects).to_vec();
+                store.changed = true;
+            }
+
+            let order = DrawOrder::new(
+                true,
+                &Vec3::new(0.0, 0.0, self.z),
+                self.render_layer,
+            );
+
+            rect_renderer.add_buffer_store(
+                renderer,
+                OrderedIndex::new(order, self.rect_store_id, 0),
+            );
+        }
+
+        for entry in &mut self.texts[..self.text_count] {
+            text_renderer.text_update(&mut entry.text, text_atlas, renderer)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{Color, GpuRenderer, Rect, RectRenderer, Vec2, Vec3};
+
+/// Where a [`CastBar`] is in its cast.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CastState {
+    #[default]
+    Idle,
+    Casting,
+    Finished,
+    Interrupted,
+}
+
+/// A bar filling up over a cast's duration, or draining for channeled
+/// casts. Interrupting holds the fill where it was in another color.
+/// Finished and interrupted bars stay up for linger seconds.
+///
+/// Times are seconds from one clock, like [`crate::AnimationClock::seconds`]
+/// so pausing the clock pauses the cast. Placed in render space like the
+/// [`crate::Spinner`].
+pub struct CastBar {
+    pub background: Rect,
+    pub fill: Rect,
+    pub color: Color,
+    pub finished_color: Color,
+    pub interrupted_color: Color,
+    /// Drains from full instead of filling up.
+    pub channeled: bool,
+    /// Seconds a finished or interrupted bar stays shown.
+    pub linger: f32,
+    state: CastState,
+    start: f32,
+    duration: f32,
+    /// When it finished or was interrupted.
+    ended: f32,
+    /// Fill shown, 0.0 to 1.0.
+    shown: f32,
+    position: Vec3,
+    size: Vec2,
+    changed: bool,
+}
+
+impl CastBar {
+    pub fn new(renderer: &mut GpuRenderer, render_layer: u32) -> Self {
+        let mut background = Rect::new(renderer, render_layer);
+        let mut fill = Rect::new(renderer, render_layer);
+
+        background
+            .set_color(Color::rgba(20, 20, 20, 220))
+            .set_border_width(1.0)
+            .set_border_color(Color::rgba(90, 90, 90, 255));
+        fill.set_color(Color::rgba(220, 180, 60, 255));
+
+        Self {
+            background,
+            fill,
+            color: Color::rgba(220, 180, 60, 255),
+            finished_color: Color::rgba(90, 200, 90, 255),
+            interrupted_color: Color::rgba(200, 60, 60, 255),
+            channeled: false,
+            linger: 0.5,
+            state: CastState::Idle,
+            start: 0.0,
+            duration: 0.0,
+            ended: 0.0,
+            shown: 0.0,
+            position: Vec3::ZERO,
+            size: Vec2::new(200.0, 16.0),
+            changed: true,
+        }
+    }
+
+    /// Bottom left corner and z.
+    pub fn set_position(&mut self, position: Vec3) -> &mut Self {
+        self.position = position;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_size(&mut self, size: Vec2) -> &mut Self {
+        self.size = size;
+        self.changed = true;
+        self
+    }
+
+    pub fn state(&self) -> CastState {
+        self.state
+    }
+
+    /// Starts a cast at now lasting duration seconds, replacing any other.
+    pub fn start(&mut self, now: f32, duration: f32) {
+        self.state = CastState::Casting;
+        self.start = now;
+        self.duration = duration.max(0.0);
+        self.shown = self.fill_at(0.0);
+        self.changed = true;
+    }
+
+    /// Stops the cast, keeping the fill where it got to.
+    pub fn interrupt(&mut self, now: f32) {
+        if self.state != CastState::Casting {
+            return;
+        }
+
+        self.state = CastState::Interrupted;
+        self.ended = now;
+        self.changed = true;
+    }
+
+    /// Hides the bar right away.
+    pub fn clear(&mut self) {
+        self.state = CastState::Idle;
+        self.changed = true;
+    }
+
+    /// Share of the cast done at now, 0.0 to 1.0.
+    pub fn progress(&self, now: f32) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+
+        ((now - self.start) / self.duration).clamp(0.0, 1.0)
+    }
+
+    /// Seconds left in the cast.
+    pub fn remaining(&self, now: f32) -> f32 {
+        match self.state {
+            CastState::Casting => (self.start + self.duration - now).max(0.0),
+            _ => 0.0,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.state != CastState::Idle
+    }
+
+    /// Moves the bar along to now. Returns the new state when the cast
+    /// finishes or the bar hides.
+    pub fn update(&mut self, now: f32) -> Option<CastState> {
+        match self.state {
+            CastState::Idle => None,
+            CastState::Casting => {
+                let shown = self.fill_at(self.progress(now));
+
+                if shown != self.shown {
+                    self.shown = shown;
+                    self.changed = true;
+                }
+
+                if now >= self.start + self.duration {
+                    self.state = CastState::Finished;
+                    self.ended = now;
+                    self.changed = true;
+                    return Some(CastState::Finished);
+                }
+
+                None
+            }
+            CastState::Finished | CastState::Interrupted => {
+                if now - self.ended < self.linger {
+                    return None;
+                }
+
+                self.state = CastState::Idle;
+                self.changed = true;
+                Some(CastState::Idle)
+            }
+        }
+    }
+
+    fn fill_at(&self, progress: f32) -> f32 {
+        if self.channeled {
+            1.0 - progress
+        } else {
+            progress
+        }
+    }
+
+    /// Lays out the background and fill if anything changed.
+    fn refresh(&mut self) {
+        if !self.changed {
+            return;
+        }
+
+        let (pos, size) = (self.position, self.size);
+        let color = match self.state {
+            CastState::Finished => self.finished_color,
+            CastState::Interrupted => self.interrupted_color,
+            _ => self.color,
+        };
+
+        self.background.set_position(pos).set_size(size);
+        // lower Z renders over the background.
+        self.fill
+            .set_position(Vec3::new(pos.x + 1.0, pos.y + 1.0, pos.z - 0.01))
+            .set_size(Vec2::new(
+                (size.x - 2.0).max(0.0) * self.shown,
+                (size.y - 2.0).max(0.0),
+            ))
+            .set_color(color);
+
+        self.changed = false;
+    }
+
+    /// Frees the bar's rects.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        self.background.unload(renderer);
+        self.fill.unload(renderer);
+    }
+}
+
+impl RectRenderer {
+    /// Adds nothing while no cast is shown.
+    pub fn cast_bar_update(
+        &mut self,
+        bar: &mut CastBar,
+        renderer: &mut GpuRenderer,
+    ) {
+        if !bar.is_visible() {
+            return;
+        }
+
+        bar.refresh();
+        self.rect_update(&mut bar.background, renderer);
+
+        if bar.fill.size.x > 0.0 {
+            self.rect_update(&mut bar.fill, renderer);
+        }
+    }
+}
@@ -0,0 +1,548 @@
+use crate::{
+    AscendingError, Bounds, ChatBoxWidget, Color, GpuRenderer, MessageRouter,
+    Rect, RectRenderer, Text, TextAtlas, TextRenderer, UiSound, Vec2, Vec3,
+    WidgetHandle, WidgetInput, WidgetSounds,
+};
+use cosmic_text::{Attrs, Metrics, Shaping};
+use std::collections::{HashMap, VecDeque};
+
+/// What a link in a chat line points at.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChatLink {
+    /// The app's item id.
+    Item(u32),
+    Player(String),
+    Custom(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChatSegment {
+    Text(String),
+    Link { text: String, link: ChatLink },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChatLine {
+    pub channel: u32,
+    /// Shown as a link to the player.
+    pub sender: Option<String>,
+    /// Seconds since midnight, shown as `[hh:mm]`.
+    pub time: Option<u32>,
+    pub segments: Vec<ChatSegment>,
+}
+
+impl ChatLine {
+    pub fn new(channel: u32, text: &str) -> Self {
+        Self {
+            channel,
+            sender: None,
+            time: None,
+            segments: vec![ChatSegment::Text(text.to_owned())],
+        }
+    }
+
+    pub fn with_sender(mut self, sender: &str) -> Self {
+        self.sender = Some(sender.to_owned());
+        self
+    }
+
+    pub fn with_time(mut self, time: u32) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    pub fn text(mut self, text: &str) -> Self {
+        self.segments.push(ChatSegment::Text(text.to_owned()));
+        self
+    }
+
+    pub fn link(mut self, text: &str, link: ChatLink) -> Self {
+        self.segments.push(ChatSegment::Link {
+            text: text.to_owned(),
+            link,
+        });
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatChannel {
+    /// Shown before each line, empty for none.
+    pub name: String,
+    pub color: Color,
+    /// Hidden channels still keep their lines.
+    pub shown: bool,
+}
+
+/// What a [`ChatBox`] emits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChatEvent {
+    /// A line typed and sent to a channel.
+    Send {
+        channel: u32,
+        text: String,
+    },
+    LinkClicked(ChatLink),
+}
+
+/// A chat window: scrollback of lines in colored channels with clickable
+/// item and player links, and an input line with history. Enter starts
+/// typing and sends, escape stops. Input should not reach the game while
+/// [`ChatBox::is_typing`].
+///
+/// Placed in render space like the [`crate::Console`]. Events are emitted
+/// through the router when a handle is set.
+pub struct ChatBox {
+    pub handle: Option<WidgetHandle<ChatBoxWidget>>,
+    pub sounds: WidgetSounds,
+    pub channels: HashMap<u32, ChatChannel>,
+    /// Scrollback kept. Older lines are dropped a batch at a time once a
+    /// quarter more have built up.
+    pub max_lines: usize,
+    pub show_timestamps: bool,
+    pub text_color: Color,
+    pub link_color: Color,
+    pub timestamp_color: Color,
+    pub background: Rect,
+    pub log_text: Text,
+    pub input_text: Text,
+    /// Channel lines are sent to.
+    channel: u32,
+    typing: bool,
+    input: String,
+    lines: VecDeque<ChatLine>,
+    history: Vec<String>,
+    /// Index into history while browsing it.
+    history_pos: Option<usize>,
+    /// Lines scrolled up from the newest.
+    scroll: usize,
+    /// Links shown in the log, by glyph metadata minus one.
+    links: Vec<ChatLink>,
+    was_down: bool,
+    position: Vec3,
+    size: Vec2,
+    /// if the text or layout needs updating.
+    changed: bool,
+}
+
+impl ChatBox {
+    pub fn new(renderer: &mut GpuRenderer, render_layer: u32) -> Self {
+        let mut background = Rect::new(renderer, render_layer);
+        let metrics = Some(Metrics::new(14.0, 16.0));
+
+        background.set_color(Color::rgba(10, 10, 14, 160));
+
+        Self {
+            handle: None,
+            sounds: WidgetSounds::default(),
+            channels: HashMap::new(),
+            max_lines: 200,
+            show_timestamps: true,
+            text_color: Color::rgba(230, 230, 230, 255),
+            link_color: Color::rgba(120, 190, 255, 255),
+            timestamp_color: Color::rgba(150, 150, 150, 255),
+            background,
+            log_text: Text::new(renderer, metrics, Vec3::ZERO, Vec2::ZERO),
+            input_text: Text::new(renderer, metrics, Vec3::ZERO, Vec2::ZERO),
+            channel: 0,
+            typing: false,
+            input: String::new(),
+            lines: VecDeque::new(),
+            history: Vec::new(),
+            history_pos: None,
+            scroll: 0,
+            links: Vec::new(),
+            was_down: false,
+            position: Vec3::ZERO,
+            size: Vec2::new(420.0, 200.0),
+            changed: true,
+        }
+    }
+
+    /// Bottom left corner and z.
+    pub fn set_position(&mut self, position: Vec3) -> &mut Self {
+        self.position = position;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_size(&mut self, size: Vec2) -> &mut Self {
+        self.size = size;
+        self.changed = true;
+        self
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(
+            self.position.x,
+            self.position.y,
+            self.position.x + self.size.x,
+            self.position.y + self.size.y,
+        )
+    }
+
+    pub fn add_channel(&mut self, id: u32, name: &str, color: Color) {
+        self.channels.insert(
+            id,
+            ChatChannel {
+                name: name.to_owned(),
+                color,
+                shown: true,
+            },
+        );
+        self.changed = true;
+    }
+
+    pub fn set_channel_shown(&mut self, id: u32, shown: bool) {
+        if let Some(channel) = self.channels.get_mut(&id) {
+            channel.shown = shown;
+            self.changed = true;
+        }
+    }
+
+    pub fn channel(&self) -> u32 {
+        self.channel
+    }
+
+    /// The channel typed lines are sent to.
+    pub fn set_channel(&mut self, id: u32) -> &mut Self {
+        self.channel = id;
+        self.changed = true;
+        self
+    }
+
+    pub fn lines(&self) -> &VecDeque<ChatLine> {
+        &self.lines
+    }
+
+    pub fn push(&mut self, line: ChatLine) {
+        self.lines.push_back(line);
+
+        // Keeps older lines in view while scrolled up.
+        if self.scroll > 0 {
+            self.scroll += 1;
+        }
+
+        if self.lines.len() > self.max_lines + self.max_lines / 4 {
+            let excess = self.lines.len() - self.max_lines;
+
+            self.lines.drain(..excess);
+            self.scroll = self.scroll.min(self.lines.len().saturating_sub(1));
+        }
+
+        self.changed = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.scroll = 0;
+        self.changed = true;
+    }
+
+    /// Scrolls the log, positive goes back to older lines.
+    pub fn scroll_by(&mut self, lines: i32) {
+        let max = self.lines.len().saturating_sub(1);
+
+        self.scroll =
+            self.scroll.saturating_add_signed(lines as isize).min(max);
+        self.changed = true;
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.typing
+    }
+
+    pub fn set_typing(&mut self, typing: bool) -> &mut Self {
+        if typing != self.typing {
+            self.typing = typing;
+            self.history_pos = None;
+            self.changed = true;
+        }
+
+        self
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub fn set_input(&mut self, input: impl Into<String>) -> &mut Self {
+        self.input = input.into();
+        self.changed = true;
+        self
+    }
+
+    /// Puts the previous sent line in the input.
+    pub fn history_prev(&mut self) {
+        let pos = match self.history_pos {
+            Some(pos) => pos.saturating_sub(1),
+            None if self.history.is_empty() => return,
+            None => self.history.len() - 1,
+        };
+
+        self.history_pos = Some(pos);
+        self.input = self.history[pos].clone();
+        self.changed = true;
+    }
+
+    /// Goes forward in history, ending on an empty input.
+    pub fn history_next(&mut self) {
+        let Some(pos) = self.history_pos else {
+            return;
+        };
+
+        if pos + 1 < self.history.len() {
+            self.history_pos = Some(pos + 1);
+            self.input = self.history[pos + 1].clone();
+        } else {
+            self.history_pos = None;
+            self.input.clear();
+        }
+
+        self.changed = true;
+    }
+
+    /// Applies a frame of input. Returns the event, which is also emitted
+    /// if a handle is set.
+    pub fn handle_input<M: 'static>(
+        &mut self,
+        input: &WidgetInput,
+        router: &mut MessageRouter<M>,
+    ) -> Option<ChatEvent> {
+        let pressed = input.mouse_down && !self.was_down;
+        let mut event = None;
+
+        self.was_down = input.mouse_down;
+
+        if pressed {
+            if let Some(link) =
+                input.mouse_pos.and_then(|pos| self.link_at(pos))
+            {
+                router.play_sound(self.sounds.cue(UiSound::Click));
+                event = Some(ChatEvent::LinkClicked(link));
+            }
+        }
+
+        for c in input.typed.chars() {
+            if !self.typing {
+                if matches!(c, '\r' | '\n') {
+                    self.set_typing(true);
+                    router.play_sound(self.sounds.cue(UiSound::Open));
+                }
+
+                continue;
+            }
+
+            match c {
+                '\u{1b}' => {
+                    self.set_typing(false);
+                    router.play_sound(self.sounds.cue(UiSound::Close));
+                }
+                '\r' | '\n' => {
+                    let text = std::mem::take(&mut self.input);
+                    let text = text.trim();
+
+                    self.set_typing(false);
+                    router.play_sound(self.sounds.cue(UiSound::Close));
+
+                    if !text.is_empty() {
+                        if self.history.last().map(String::as_str) != Some(text)
+                        {
+                            self.history.push(text.to_owned());
+                        }
+
+                        self.scroll = 0;
+                        event = Some(ChatEvent::Send {
+                            channel: self.channel,
+                            text: text.to_owned(),
+                        });
+                    }
+                }
+                '\u{8}' => {
+                    self.input.pop();
+                }
+                c if c.is_control() => {}
+                c => self.input.push(c),
+            }
+
+            self.changed = true;
+        }
+
+        if let (Some(event), Some(handle)) = (&event, self.handle) {
+            router.emit(handle, event.clone());
+        }
+
+        event
+    }
+
+    /// The link under pos in the log as it was last laid out.
+    pub fn link_at(&self, pos: Vec2) -> Option<ChatLink> {
+        let text = &self.log_text;
+        let x = pos.x - text.pos.x - text.offsets.x;
+        // Buffer lines go down from the top.
+        let y = text.pos.y + text.offsets.y + text.size.y - pos.y;
+        let line_height = text.buffer.metrics().line_height;
+
+        text.buffer
+            .layout_runs()
+            .filter(|run| y >= run.line_top && y < run.line_top + line_height)
+            .flat_map(|run| run.glyphs.iter())
+            .find(|glyph| x >= glyph.x && x < glyph.x + glyph.w)
+            .and_then(|glyph| glyph.metadata.checked_sub(1))
+            .and_then(|index| self.links.get(index).cloned())
+    }
+
+    /// Lays out the box and sets the shown text if anything changed.
+    fn refresh(&mut self, renderer: &mut GpuRenderer) {
+        if !self.changed {
+            return;
+        }
+
+        let (pos, size) = (self.position, self.size);
+        let line_height = self.log_text.buffer.metrics().line_height;
+        let padding = 6.0;
+        let width = (size.x - padding * 2.0).max(0.0);
+        let log_height = (size.y - line_height - padding * 3.0).max(0.0);
+        let visible = (log_height / line_height) as usize;
+        let shown: Vec<&ChatLine> = self
+            .lines
+            .iter()
+            .filter(|line| {
+                self.channels
+                    .get(&line.channel)
+                    .is_none_or(|channel| channel.shown)
+            })
+            .collect();
+        let end = shown.len().saturating_sub(self.scroll);
+        let start = end.saturating_sub(visible);
+        // (text, color, link index + 1 or 0)
+        let mut spans: Vec<(String, Color, usize)> = Vec::new();
+        let mut links = Vec::new();
+
+        for line in &shown[start..end] {
+            let channel = self.channels.get(&line.channel);
+            let color =
+                channel.map_or(self.text_color, |channel| channel.color);
+            let mut link = |text: String, link: ChatLink, color: Color| {
+                links.push(link);
+                (text, color, links.len())
+            };
+
+            if let Some(time) = line.time.filter(|_| self.show_timestamps) {
+                spans.push((
+                    format!("[{:02}:{:02}] ", time / 3600 % 24, time / 60 % 60),
+                    self.timestamp_color,
+                    0,
+                ));
+            }
+
+            if let Some(channel) = channel.filter(|c| !c.name.is_empty()) {
+                spans.push((format!("[{}] ", channel.name), color, 0));
+            }
+
+            if let Some(sender) = &line.sender {
+                spans.push(link(
+                    sender.clone(),
+                    ChatLink::Player(sender.clone()),
+                    color,
+                ));
+                spans.push((": ".to_owned(), color, 0));
+            }
+
+            for segment in &line.segments {
+                spans.push(match segment {
+                    ChatSegment::Text(text) => (text.clone(), color, 0),
+                    ChatSegment::Link { text, link: target } => link(
+                        format!("[{text}]"),
+                        target.clone(),
+                        self.link_color,
+                    ),
+                });
+            }
+
+            spans.push(("\n".to_owned(), color, 0));
+        }
+
+        self.links = links;
+        self.background.set_position(pos).set_size(size);
+
+        // lower Z renders over the background.
+        self.log_text
+            .set_buffer_size(renderer, width as i32, log_height as i32)
+            .set_position(Vec3::new(
+                pos.x + padding,
+                pos.y + line_height + padding * 2.0,
+                pos.z - 0.01,
+            ))
+            .set_change(true);
+        self.log_text.size = Vec2::new(width, log_height);
+        self.log_text.buffer.set_rich_text(
+            &mut renderer.font_sys,
+            spans.iter().map(|(text, color, link)| {
+                (text.as_str(), Attrs::new().color(*color).metadata(*link))
+            }),
+            Shaping::Advanced,
+        );
+
+        let prompt = match self.channels.get(&self.channel) {
+            Some(channel) if !channel.name.is_empty() => {
+                format!("[{}] ", channel.name)
+            }
+            _ => String::new(),
+        };
+        let input = if self.typing {
+            format!("{prompt}{}_", self.input)
+        } else {
+            String::new()
+        };
+
+        self.input_text
+            .set_buffer_size(renderer, width as i32, line_height as i32)
+            .set_default_color(self.text_color)
+            .set_text(renderer, &input, Attrs::new())
+            .set_position(Vec3::new(
+                pos.x + padding,
+                pos.y + padding,
+                pos.z - 0.01,
+            ));
+        self.input_text.size = Vec2::new(width, line_height);
+        self.changed = false;
+    }
+
+    /// Frees the chat box's rect and text.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        self.background.unload(renderer);
+        self.log_text.unload(renderer);
+        self.input_text.unload(renderer);
+    }
+}
+
+impl RectRenderer {
+    pub fn chat_box_update(
+        &mut self,
+        chat: &mut ChatBox,
+        renderer: &mut GpuRenderer,
+    ) {
+        chat.refresh(renderer);
+        self.rect_update(&mut chat.background, renderer);
+    }
+}
+
+impl TextRenderer {
+    /// Adds the input line only while typing.
+    pub fn chat_box_update(
+        &mut self,
+        chat: &mut ChatBox,
+        atlas: &mut TextAtlas,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        chat.refresh(renderer);
+        self.text_update(&mut chat.log_text, atlas, renderer)?;
+
+        if chat.typing {
+            self.text_update(&mut chat.input_text, atlas, renderer)?;
+        }
+
+        Ok(())
+    }
+}
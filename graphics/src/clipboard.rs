@@ -0,0 +1,210 @@
+use crate::{Allocation, AtlasGroup, GpuRenderer, Texture};
+#[cfg(feature = "sprites")]
+use crate::{Image, Vec2, Vec4};
+use std::cell::RefCell;
+
+/// An RGBA8 image on the clipboard.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClipboardImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl ClipboardImage {
+    pub fn from_texture(texture: &Texture) -> Self {
+        let (width, height) = texture.size();
+
+        Self {
+            width,
+            height,
+            rgba: texture.bytes().to_vec(),
+        }
+    }
+
+    pub fn into_texture(self, name: String) -> Texture {
+        Texture::from_rgba(name, self.rgba, (self.width, self.height))
+    }
+
+    /// Adds the image to the atlas under name.
+    pub fn upload(
+        &self,
+        name: String,
+        atlas: &mut AtlasGroup,
+        renderer: &GpuRenderer,
+    ) -> Option<Allocation> {
+        atlas.upload(name, &self.rgba, self.width, self.height, 0, renderer)
+    }
+}
+
+/// Where the clipboard contents are kept.
+pub trait ClipboardBackend {
+    fn get_text(&mut self) -> Option<String>;
+
+    fn set_text(&mut self, text: String);
+
+    fn get_image(&mut self) -> Option<ClipboardImage>;
+
+    fn set_image(&mut self, image: ClipboardImage);
+}
+
+/// Only shared within the app. Used when there is no system clipboard,
+/// like headless or without the clipboard feature.
+#[derive(Default)]
+pub struct MemoryClipboard {
+    text: Option<String>,
+    image: Option<ClipboardImage>,
+}
+
+impl ClipboardBackend for MemoryClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.text.clone()
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.text = Some(text);
+    }
+
+    fn get_image(&mut self) -> Option<ClipboardImage> {
+        self.image.clone()
+    }
+
+    fn set_image(&mut self, image: ClipboardImage) {
+        self.image = Some(image);
+    }
+}
+
+/// The operating system's clipboard.
+#[cfg(feature = "clipboard")]
+pub struct SystemClipboard {
+    clipboard: arboard::Clipboard,
+}
+
+#[cfg(feature = "clipboard")]
+impl SystemClipboard {
+    pub fn new() -> Option<Self> {
+        match arboard::Clipboard::new() {
+            Ok(clipboard) => Some(Self { clipboard }),
+            Err(e) => {
+                log::warn!("no system clipboard: {e}");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(feature = "clipboard")]
+impl ClipboardBackend for SystemClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.clipboard.get_text().ok()
+    }
+
+    fn set_text(&mut self, text: String) {
+        if let Err(e) = self.clipboard.set_text(text) {
+            log::warn!("could not copy text: {e}");
+        }
+    }
+
+    fn get_image(&mut self) -> Option<ClipboardImage> {
+        let image = self.clipboard.get_image().ok()?;
+
+        Some(ClipboardImage {
+            width: image.width as u32,
+            height: image.height as u32,
+            rgba: image.bytes.into_owned(),
+        })
+    }
+
+    fn set_image(&mut self, image: ClipboardImage) {
+        let image = arboard::ImageData {
+            width: image.width as usize,
+            height: image.height as usize,
+            bytes: image.rgba.into(),
+        };
+
+        if let Err(e) = self.clipboard.set_image(image) {
+            log::warn!("could not copy image: {e}");
+        }
+    }
+}
+
+/// Clipboard for the GUI and the game. Uses the system clipboard with the
+/// clipboard feature when there is one, otherwise a [`MemoryClipboard`].
+pub struct Clipboard {
+    backend: RefCell<Box<dyn ClipboardBackend>>,
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        #[cfg(feature = "clipboard")]
+        if let Some(system) = SystemClipboard::new() {
+            return Self::with_backend(system);
+        }
+
+        Self::with_backend(MemoryClipboard::default())
+    }
+
+    pub fn with_backend<B: ClipboardBackend + 'static>(backend: B) -> Self {
+        Self {
+            backend: RefCell::new(Box::new(backend)),
+        }
+    }
+
+    pub fn get_text(&self) -> Option<String> {
+        self.backend.borrow_mut().get_text()
+    }
+
+    pub fn set_text(&self, text: impl Into<String>) {
+        self.backend.borrow_mut().set_text(text.into());
+    }
+
+    pub fn get_image(&self) -> Option<ClipboardImage> {
+        self.backend.borrow_mut().get_image()
+    }
+
+    pub fn set_image(&self, image: ClipboardImage) {
+        self.backend.borrow_mut().set_image(image);
+    }
+
+    pub fn copy_texture(&self, texture: &Texture) {
+        self.set_image(ClipboardImage::from_texture(texture));
+    }
+
+    /// Uploads a pasted image to the atlas under name and makes an
+    /// [`Image`] of its full size. None if there is no image or the atlas
+    /// is full.
+    #[cfg(feature = "sprites")]
+    pub fn paste_image(
+        &self,
+        name: String,
+        atlas: &mut AtlasGroup,
+        renderer: &mut GpuRenderer,
+        render_layer: u32,
+    ) -> Option<Image> {
+        let pasted = self.get_image()?;
+        let allocation = pasted.upload(name, atlas, renderer)?;
+        let (width, height) = (pasted.width as f32, pasted.height as f32);
+        let mut image = Image::new(Some(allocation), renderer, render_layer);
+
+        image.hw = Vec2::new(width, height);
+        image.uv = Vec4::new(0.0, 0.0, width, height);
+        Some(image)
+    }
+}
+
+#[cfg(feature = "iced")]
+impl iced_winit::core::Clipboard for Clipboard {
+    fn read(&self) -> Option<String> {
+        self.get_text()
+    }
+
+    fn write(&mut self, contents: String) {
+        self.set_text(contents);
+    }
+}
@@ -0,0 +1,5 @@
+mod commands;
+mod overlay;
+
+pub use commands::*;
+pub use overlay::*;
@@ -0,0 +1,376 @@
+use std::{collections::BTreeMap, str::FromStr};
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ConsoleError {
+    #[error("Unknown command `{0}`. Type `help` for a list.")]
+    UnknownCommand(String),
+    #[error("Missing {name}. Usage: {usage}")]
+    MissingArg { name: String, usage: String },
+    #[error("Invalid {name} `{value}`. Usage: {usage}")]
+    InvalidArg {
+        name: String,
+        value: String,
+        usage: String,
+    },
+    #[error("Unclosed quote.")]
+    UnclosedQuote,
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// Arguments after the command name, split on spaces. Quotes keep spaces
+/// in one argument.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConsoleArgs {
+    args: Vec<String>,
+    usage: String,
+}
+
+impl ConsoleArgs {
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.args.get(index).map(String::as_str)
+    }
+
+    /// Parses a required argument. name is shown in the error.
+    pub fn parse<T: FromStr>(
+        &self,
+        index: usize,
+        name: &str,
+    ) -> Result<T, ConsoleError> {
+        let value =
+            self.get(index).ok_or_else(|| ConsoleError::MissingArg {
+                name: name.to_owned(),
+                usage: self.usage.clone(),
+            })?;
+
+        value.parse().map_err(|_| ConsoleError::InvalidArg {
+            name: name.to_owned(),
+            value: value.to_owned(),
+            usage: self.usage.clone(),
+        })
+    }
+
+    /// Parses an optional argument, None when it isn't given.
+    pub fn parse_opt<T: FromStr>(
+        &self,
+        index: usize,
+        name: &str,
+    ) -> Result<Option<T>, ConsoleError> {
+        if index < self.len() {
+            self.parse(index, name).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Accepts on/off, true/false, yes/no and 1/0.
+    pub fn parse_bool(
+        &self,
+        index: usize,
+        name: &str,
+    ) -> Result<bool, ConsoleError> {
+        let value: String = self.parse(index, name)?;
+
+        match value.to_ascii_lowercase().as_str() {
+            "on" | "true" | "yes" | "1" => Ok(true),
+            "off" | "false" | "no" | "0" => Ok(false),
+            _ => Err(ConsoleError::InvalidArg {
+                name: name.to_owned(),
+                value,
+                usage: self.usage.clone(),
+            }),
+        }
+    }
+
+    /// Arguments from index on, joined with spaces.
+    pub fn rest(&self, index: usize) -> String {
+        self.args.get(index..).unwrap_or_default().join(" ")
+    }
+}
+
+type CommandFn<C> =
+    Box<dyn FnMut(&mut C, &ConsoleArgs) -> Result<String, ConsoleError>>;
+type CompleteFn = Box<dyn Fn(usize, &str) -> Vec<String>>;
+
+/// A console command. The handler gets the app's context and returns what
+/// to print.
+pub struct ConsoleCommand<C> {
+    name: String,
+    help: String,
+    usage: String,
+    handler: CommandFn<C>,
+    /// Fixed choices per argument.
+    choices: Vec<Vec<String>>,
+    completer: Option<CompleteFn>,
+}
+
+impl<C> ConsoleCommand<C> {
+    /// Names are usually grouped with dots, like `r.vsync` or `map.teleport`.
+    pub fn new<F>(name: impl Into<String>, handler: F) -> Self
+    where
+        F: FnMut(&mut C, &ConsoleArgs) -> Result<String, ConsoleError>
+            + 'static,
+    {
+        let name = name.into();
+
+        Self {
+            usage: name.clone(),
+            name,
+            help: String::new(),
+            handler: Box::new(handler),
+            choices: Vec::new(),
+            completer: None,
+        }
+    }
+
+    pub fn help(mut self, help: impl Into<String>) -> Self {
+        self.help = help.into();
+        self
+    }
+
+    /// Arguments shown after the name, like `<x> <y>`.
+    pub fn usage(mut self, args: &str) -> Self {
+        self.usage = format!("{} {args}", self.name);
+        self
+    }
+
+    /// Values the argument at index autocompletes to.
+    pub fn arg_choices<I, S>(mut self, index: usize, choices: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        if self.choices.len() <= index {
+            self.choices.resize(index + 1, Vec::new());
+        }
+
+        self.choices[index] = choices.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Completes arguments that change at runtime, like map or file names.
+    /// Gets the argument index and what is typed of it so far.
+    pub fn completer<F>(mut self, completer: F) -> Self
+    where
+        F: Fn(usize, &str) -> Vec<String> + 'static,
+    {
+        self.completer = Some(Box::new(completer));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Usage and help on one line.
+    fn describe(&self) -> String {
+        if self.help.is_empty() {
+            self.usage.clone()
+        } else {
+            format!("{} - {}", self.usage, self.help)
+        }
+    }
+
+    fn complete_arg(&self, index: usize, prefix: &str) -> Vec<String> {
+        let mut found: Vec<String> = self
+            .choices
+            .get(index)
+            .into_iter()
+            .flatten()
+            .filter(|choice| choice.starts_with(prefix))
+            .cloned()
+            .collect();
+
+        if let Some(completer) = &self.completer {
+            found.extend(completer(index, prefix));
+        }
+
+        found
+    }
+}
+
+/// Commands the console can run. The application and engine subsystems
+/// register theirs; C is what handlers get to change, usually the app
+/// state. `help` is built in.
+pub struct CommandRegistry<C> {
+    commands: BTreeMap<String, ConsoleCommand<C>>,
+}
+
+impl<C> Default for CommandRegistry<C> {
+    fn default() -> Self {
+        Self {
+            commands: BTreeMap::new(),
+        }
+    }
+}
+
+impl<C> CommandRegistry<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces a command of the same name.
+    pub fn register(&mut self, command: ConsoleCommand<C>) -> &mut Self {
+        self.commands.insert(command.name.clone(), command);
+        self
+    }
+
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.commands.remove(name).is_some()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
+    /// Command names in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.commands.keys().map(String::as_str)
+    }
+
+    /// Runs a line and returns what to print. Empty lines do nothing.
+    pub fn execute(
+        &mut self,
+        context: &mut C,
+        line: &str,
+    ) -> Result<String, ConsoleError> {
+        let mut words = tokenize(line)?.into_iter();
+        let Some(name) = words.next() else {
+            return Ok(String::new());
+        };
+        let args: Vec<String> = words.collect();
+
+        if name == "help" {
+            return self.help(args.first().map(String::as_str));
+        }
+
+        let command = self
+            .commands
+            .get_mut(&name)
+            .ok_or(ConsoleError::UnknownCommand(name))?;
+        let args = ConsoleArgs {
+            args,
+            usage: command.usage.clone(),
+        };
+
+        (command.handler)(context, &args)
+    }
+
+    /// Lines the input could complete to, sorted. Names complete first,
+    /// then the arguments of the typed command.
+    pub fn complete(&self, line: &str) -> Vec<String> {
+        let words = tokenize(line).unwrap_or_default();
+        // A trailing space means the next word is started but empty.
+        let started = line.ends_with(char::is_whitespace) || words.is_empty();
+        let (done, current) = if started {
+            (&words[..], "")
+        } else {
+            (&words[..words.len() - 1], words[words.len() - 1].as_str())
+        };
+
+        let found: Vec<String> = match done.split_first() {
+            None => std::iter::once("help")
+                .chain(self.names())
+                .filter(|name| name.starts_with(current))
+                .map(str::to_owned)
+                .collect(),
+            Some((name, _)) if name == "help" && done.len() == 1 => self
+                .names()
+                .filter(|name| name.starts_with(current))
+                .map(str::to_owned)
+                .collect(),
+            Some((name, args)) => match self.commands.get(name) {
+                Some(command) => command.complete_arg(args.len(), current),
+                None => Vec::new(),
+            },
+        };
+
+        let mut lines: Vec<String> = found
+            .into_iter()
+            .map(|word| {
+                let mut line =
+                    done.iter().map(|w| quote(w)).collect::<Vec<_>>();
+
+                line.push(quote(&word));
+                line.join(" ")
+            })
+            .collect();
+
+        lines.sort();
+        lines.dedup();
+        lines
+    }
+
+    fn help(&self, name: Option<&str>) -> Result<String, ConsoleError> {
+        match name {
+            Some(name) => {
+                let command = self
+                    .commands
+                    .get(name)
+                    .ok_or_else(|| ConsoleError::UnknownCommand(name.into()))?;
+
+                Ok(command.describe())
+            }
+            None => Ok(self
+                .commands
+                .values()
+                .map(ConsoleCommand::describe)
+                .collect::<Vec<_>>()
+                .join("\n")),
+        }
+    }
+}
+
+/// Splits on whitespace, keeping quoted parts together.
+fn tokenize(line: &str) -> Result<Vec<String>, ConsoleError> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quoted = false;
+
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if quoted {
+        return Err(ConsoleError::UnclosedQuote);
+    }
+
+    if in_word {
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+fn quote(word: &str) -> String {
+    if word.is_empty() || word.contains(char::is_whitespace) {
+        format!("\"{word}\"")
+    } else {
+        word.to_owned()
+    }
+}
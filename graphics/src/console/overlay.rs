@@ -0,0 +1,404 @@
+use crate::{
+    AscendingError, Color, CommandRegistry, GpuRenderer, Rect, RectRenderer,
+    Text, TextAtlas, TextRenderer, Vec2, Vec3, WidgetInput,
+};
+use cosmic_text::{Attrs, Metrics, Shaping};
+use std::collections::VecDeque;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConsoleLineKind {
+    /// A command that was run.
+    Input,
+    Output,
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsoleLine {
+    pub text: String,
+    pub kind: ConsoleLineKind,
+}
+
+/// A drop down console. Typing the toggle character opens and closes it,
+/// enter runs the line through a [`CommandRegistry`], tab completes and
+/// escape closes. Input should not reach the game while it is open.
+///
+/// Placed in render space like rects that don't use the camera.
+pub struct Console {
+    open: bool,
+    /// Typed character that opens and closes the console.
+    pub toggle_char: Option<char>,
+    /// Scrollback kept before the oldest lines are dropped.
+    pub max_lines: usize,
+    pub input_color: Color,
+    pub output_color: Color,
+    pub error_color: Color,
+    pub background: Rect,
+    pub log_text: Text,
+    pub input_text: Text,
+    input: String,
+    lines: VecDeque<ConsoleLine>,
+    history: Vec<String>,
+    /// Index into history while browsing it.
+    history_pos: Option<usize>,
+    /// Lines scrolled up from the newest.
+    scroll: usize,
+    position: Vec3,
+    size: Vec2,
+    /// if the text or layout needs updating.
+    changed: bool,
+}
+
+impl Console {
+    pub fn new(renderer: &mut GpuRenderer, render_layer: u32) -> Self {
+        let mut background = Rect::new(renderer, render_layer);
+        let metrics = Some(Metrics::new(14.0, 16.0));
+
+        background.set_color(Color::rgba(10, 10, 14, 220));
+
+        let mut log_text = Text::new(renderer, metrics, Vec3::ZERO, Vec2::ZERO);
+        let mut input_text =
+            Text::new(renderer, metrics, Vec3::ZERO, Vec2::ZERO);
+
+        log_text.set_default_color(Color::rgba(220, 220, 220, 255));
+        input_text.set_default_color(Color::rgba(255, 255, 255, 255));
+
+        Self {
+            open: false,
+            toggle_char: Some('`'),
+            max_lines: 500,
+            input_color: Color::rgba(140, 180, 255, 255),
+            output_color: Color::rgba(220, 220, 220, 255),
+            error_color: Color::rgba(255, 110, 110, 255),
+            background,
+            log_text,
+            input_text,
+            input: String::new(),
+            lines: VecDeque::new(),
+            history: Vec::new(),
+            history_pos: None,
+            scroll: 0,
+            position: Vec3::ZERO,
+            size: Vec2::new(640.0, 240.0),
+            changed: true,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) -> &mut Self {
+        self.open = open;
+        self.changed = true;
+        self
+    }
+
+    pub fn toggle(&mut self) -> &mut Self {
+        self.set_open(!self.open)
+    }
+
+    /// Bottom left corner and z.
+    pub fn set_position(&mut self, position: Vec3) -> &mut Self {
+        self.position = position;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_size(&mut self, size: Vec2) -> &mut Self {
+        self.size = size;
+        self.changed = true;
+        self
+    }
+
+    pub fn lines(&self) -> &VecDeque<ConsoleLine> {
+        &self.lines
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub fn set_input(&mut self, input: impl Into<String>) -> &mut Self {
+        self.input = input.into();
+        self.changed = true;
+        self
+    }
+
+    /// Adds output lines, for engine subsystems to report into.
+    pub fn print(&mut self, text: &str) {
+        self.push(text, ConsoleLineKind::Output);
+    }
+
+    pub fn print_error(&mut self, text: &str) {
+        self.push(text, ConsoleLineKind::Error);
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.scroll = 0;
+        self.changed = true;
+    }
+
+    /// Scrolls the log, positive goes back to older lines.
+    pub fn scroll_by(&mut self, lines: i32) {
+        let max = self.lines.len().saturating_sub(1);
+
+        self.scroll =
+            self.scroll.saturating_add_signed(lines as isize).min(max);
+        self.changed = true;
+    }
+
+    /// Puts the previous run line in the input.
+    pub fn history_prev(&mut self) {
+        let pos = match self.history_pos {
+            Some(pos) => pos.saturating_sub(1),
+            None if self.history.is_empty() => return,
+            None => self.history.len() - 1,
+        };
+
+        self.history_pos = Some(pos);
+        self.input = self.history[pos].clone();
+        self.changed = true;
+    }
+
+    /// Goes forward in history, ending on an empty input.
+    pub fn history_next(&mut self) {
+        let Some(pos) = self.history_pos else {
+            return;
+        };
+
+        if pos + 1 < self.history.len() {
+            self.history_pos = Some(pos + 1);
+            self.input = self.history[pos + 1].clone();
+        } else {
+            self.history_pos = None;
+            self.input.clear();
+        }
+
+        self.changed = true;
+    }
+
+    /// Runs a line as if it was typed and prints the result.
+    pub fn submit<C>(
+        &mut self,
+        registry: &mut CommandRegistry<C>,
+        context: &mut C,
+        line: &str,
+    ) {
+        let line = line.trim();
+
+        if line.is_empty() {
+            return;
+        }
+
+        self.push(&format!("> {line}"), ConsoleLineKind::Input);
+
+        if self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.to_owned());
+        }
+
+        self.history_pos = None;
+
+        match registry.execute(context, line) {
+            Ok(output) if output.is_empty() => {}
+            Ok(output) => self.print(&output),
+            Err(e) => self.print_error(&e.to_string()),
+        }
+    }
+
+    /// Completes the input. A single match replaces it, several fill in
+    /// what they share and get printed.
+    pub fn complete<C>(&mut self, registry: &CommandRegistry<C>) {
+        let found = registry.complete(&self.input);
+
+        match found.as_slice() {
+            [] => {}
+            [line] => self.input = format!("{line} "),
+            [first, rest @ ..] => {
+                let shared = rest.iter().fold(first.len(), |len, line| {
+                    first
+                        .chars()
+                        .zip(line.chars())
+                        .take(len)
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                let shared: String = first.chars().take(shared).collect();
+
+                if shared.len() > self.input.len() {
+                    self.input = shared;
+                }
+
+                self.print(&found.join("  "));
+            }
+        }
+
+        self.changed = true;
+    }
+
+    /// Applies a frame of typed input. Returns true while the console is
+    /// open and took the input.
+    pub fn handle_input<C>(
+        &mut self,
+        input: &WidgetInput,
+        registry: &mut CommandRegistry<C>,
+        context: &mut C,
+    ) -> bool {
+        let mut typed = input.typed.chars();
+
+        if !self.open {
+            if self.toggle_char.is_none()
+                || !typed.any(|c| Some(c) == self.toggle_char)
+            {
+                return false;
+            }
+
+            self.set_open(true);
+        }
+
+        for c in typed {
+            match c {
+                c if Some(c) == self.toggle_char => {
+                    self.set_open(false);
+                    break;
+                }
+                '\u{1b}' => {
+                    self.set_open(false);
+                    break;
+                }
+                '\r' | '\n' => {
+                    let line = std::mem::take(&mut self.input);
+
+                    self.submit(registry, context, &line);
+                    self.scroll = 0;
+                }
+                '\t' => self.complete(registry),
+                '\u{8}' => {
+                    self.input.pop();
+                }
+                c if c.is_control() => {}
+                c => self.input.push(c),
+            }
+
+            self.changed = true;
+        }
+
+        true
+    }
+
+    fn push(&mut self, text: &str, kind: ConsoleLineKind) {
+        for line in text.lines() {
+            self.lines.push_back(ConsoleLine {
+                text: line.to_owned(),
+                kind,
+            });
+        }
+
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+        }
+
+        self.changed = true;
+    }
+
+    /// Lays out the overlay and sets the shown text if anything changed.
+    fn refresh(&mut self, renderer: &mut GpuRenderer) {
+        if !self.changed {
+            return;
+        }
+
+        let (pos, size) = (self.position, self.size);
+        let line_height = self.log_text.buffer.metrics().line_height;
+        let padding = 6.0;
+        let width = (size.x - padding * 2.0).max(0.0);
+        let log_height = (size.y - line_height - padding * 3.0).max(0.0);
+        let visible = (log_height / line_height) as usize;
+        let end = self.lines.len().saturating_sub(self.scroll);
+        let start = end.saturating_sub(visible);
+
+        self.background.set_position(pos).set_size(size);
+
+        let spans: Vec<(String, Color)> = self
+            .lines
+            .range(start..end)
+            .map(|line| {
+                let color = match line.kind {
+                    ConsoleLineKind::Input => self.input_color,
+                    ConsoleLineKind::Output => self.output_color,
+                    ConsoleLineKind::Error => self.error_color,
+                };
+
+                (format!("{}\n", line.text), color)
+            })
+            .collect();
+
+        // lower Z renders over the background.
+        self.log_text
+            .set_buffer_size(renderer, width as i32, log_height as i32)
+            .set_position(Vec3::new(
+                pos.x + padding,
+                pos.y + line_height + padding * 2.0,
+                pos.z - 0.01,
+            ))
+            .set_change(true);
+        self.log_text.size = Vec2::new(width, log_height);
+        self.log_text.buffer.set_rich_text(
+            &mut renderer.font_sys,
+            spans.iter().map(|(text, color)| {
+                (text.as_str(), Attrs::new().color(*color))
+            }),
+            Shaping::Advanced,
+        );
+
+        self.input_text
+            .set_buffer_size(renderer, width as i32, line_height as i32)
+            .set_text(renderer, &format!("> {}_", self.input), Attrs::new())
+            .set_position(Vec3::new(
+                pos.x + padding,
+                pos.y + padding,
+                pos.z - 0.01,
+            ));
+        self.input_text.size = Vec2::new(width, line_height);
+        self.changed = false;
+    }
+
+    /// Frees the console's rect and text.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        self.background.unload(renderer);
+        self.log_text.unload(renderer);
+        self.input_text.unload(renderer);
+    }
+}
+
+impl RectRenderer {
+    /// Adds the console's background while it is open.
+    pub fn console_update(
+        &mut self,
+        console: &mut Console,
+        renderer: &mut GpuRenderer,
+    ) {
+        if console.open {
+            console.refresh(renderer);
+            self.rect_update(&mut console.background, renderer);
+        }
+    }
+}
+
+impl TextRenderer {
+    /// Adds the console's text while it is open.
+    pub fn console_update(
+        &mut self,
+        console: &mut Console,
+        atlas: &mut TextAtlas,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        if console.open {
+            console.refresh(renderer);
+            self.text_update(&mut console.log_text, atlas, renderer)?;
+            self.text_update(&mut console.input_text, atlas, renderer)?;
+        }
+
+        Ok(())
+    }
+}
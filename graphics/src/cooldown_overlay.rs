@@ -0,0 +1,139 @@
+use crate::{Color, GpuRenderer, Rect, RectRenderer, Vec2, Vec3};
+
+/// A dark sweep over an action icon showing how much of a cooldown is
+/// left. It starts covering the icon and uncovers it clockwise from the
+/// top, drawn with the rect shader's sweep.
+///
+/// Times are seconds from one clock, like [`crate::AnimationClock::seconds`].
+/// Place it just in front of the icon in render space.
+pub struct CooldownOverlay {
+    pub overlay: Rect,
+    start: f32,
+    duration: f32,
+    cooling: bool,
+    position: Vec3,
+    size: Vec2,
+    changed: bool,
+}
+
+impl CooldownOverlay {
+    pub fn new(renderer: &mut GpuRenderer, render_layer: u32) -> Self {
+        let mut overlay = Rect::new(renderer, render_layer);
+
+        overlay.set_color(Color::rgba(0, 0, 0, 160));
+
+        Self {
+            overlay,
+            start: 0.0,
+            duration: 0.0,
+            cooling: false,
+            position: Vec3::ZERO,
+            size: Vec2::splat(32.0),
+            changed: true,
+        }
+    }
+
+    /// Bottom left corner and z, usually the icon's with a lower z.
+    pub fn set_position(&mut self, position: Vec3) -> &mut Self {
+        self.position = position;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_size(&mut self, size: Vec2) -> &mut Self {
+        self.size = size;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_color(&mut self, color: Color) -> &mut Self {
+        self.overlay.set_color(color);
+        self
+    }
+
+    /// Starts a cooldown at now lasting duration seconds.
+    pub fn start(&mut self, now: f32, duration: f32) {
+        self.start = now;
+        self.duration = duration.max(0.0);
+        self.cooling = self.duration > 0.0;
+        self.overlay.set_sweep(1.0);
+    }
+
+    /// Ends the cooldown early.
+    pub fn reset(&mut self) {
+        self.cooling = false;
+    }
+
+    pub fn is_cooling(&self) -> bool {
+        self.cooling
+    }
+
+    /// Seconds left, 0.0 when ready.
+    pub fn remaining(&self, now: f32) -> f32 {
+        if !self.cooling {
+            return 0.0;
+        }
+
+        (self.start + self.duration - now).max(0.0)
+    }
+
+    /// Share of the cooldown left, 1.0 just started to 0.0 ready.
+    pub fn fraction(&self, now: f32) -> f32 {
+        if !self.cooling {
+            return 0.0;
+        }
+
+        (self.remaining(now) / self.duration).clamp(0.0, 1.0)
+    }
+
+    /// Moves the sweep along to now. Returns true the frame it becomes
+    /// ready.
+    pub fn update(&mut self, now: f32) -> bool {
+        if !self.cooling {
+            return false;
+        }
+
+        let fraction = self.fraction(now);
+
+        if fraction <= 0.0 {
+            self.cooling = false;
+            return true;
+        }
+
+        if fraction != self.overlay.sweep {
+            self.overlay.set_sweep(fraction);
+        }
+
+        false
+    }
+
+    fn refresh(&mut self) {
+        if !self.changed {
+            return;
+        }
+
+        self.overlay.set_position(self.position).set_size(self.size);
+        self.changed = false;
+    }
+
+    /// Frees the overlay's rect.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        self.overlay.unload(renderer);
+    }
+}
+
+impl RectRenderer {
+    /// Adds nothing while the action is ready.
+    pub fn cooldown_overlay_update(
+        &mut self,
+        overlay: &mut CooldownOverlay,
+        renderer: &mut GpuRenderer,
+    ) {
+        if !overlay.is_cooling() {
+            return;
+        }
+
+        overlay.refresh();
+        self.rect_update(&mut overlay.overlay, renderer);
+    }
+}
@@ -0,0 +1,7 @@
+pub(crate) mod pipeline;
+mod render;
+mod tracker;
+
+pub(crate) use pipeline::*;
+pub use render::*;
+pub use tracker::*;
@@ -0,0 +1,81 @@
+use crate::{shaders, GpuDevice, LayoutStorage, PipeLineLayout};
+use bytemuck::{Pod, Zeroable};
+
+/// Clears the scissored region of a pass to its blend constant.
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct DamageClearRenderPipeline;
+
+impl PipeLineLayout for DamageClearRenderPipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        _layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let shader = gpu_device.device().create_shader_module(
+            wgpu::ShaderModuleDescriptor {
+                label: Some("Shader"),
+                source: wgpu::ShaderSource::Wgsl(shaders::DAMAGE_SHADER.into()),
+            },
+        );
+
+        let clear = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Constant,
+            dst_factor: wgpu::BlendFactor::Zero,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        // Create the render pipeline.
+        // A single fullscreen triangle so no vertex buffers are needed.
+        gpu_device.device().create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: Some("Damage clear render pipeline"),
+                layout: Some(&gpu_device.device().create_pipeline_layout(
+                    &wgpu::PipelineLayoutDescriptor {
+                        label: Some("damage_clear_render_pipeline_layout"),
+                        bind_group_layouts: &[],
+                        push_constant_ranges: &[],
+                    },
+                )),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vertex",
+                    buffers: &[],
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    unclipped_depth: false,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    conservative: false,
+                },
+                // Drawn into a RenderTarget pass, which has depth, but
+                // never tests or writes it.
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: false,
+                    depth_compare: wgpu::CompareFunction::Always,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: gpu_device.multisample_state(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fragment",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: surface_format,
+                        blend: Some(wgpu::BlendState {
+                            color: clear,
+                            alpha: clear,
+                        }),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview: None,
+            },
+        )
+    }
+}
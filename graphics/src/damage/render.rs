@@ -0,0 +1,109 @@
+use crate::{
+    AscendingError, Damage, DamageClearRenderPipeline, DamageRect,
+    DamageTracker, GpuRenderer, Pass, RenderTarget, ResolutionScale,
+    UpscaleRenderer,
+};
+
+/// Partial redraw for tool style apps where little changes per frame. The
+/// scene lives in [`DamageRenderer::target`] across frames and only the
+/// damaged regions of it are drawn again, then this pass copies it to the
+/// frame.
+///
+/// wgpu has no way to hand damage rects to the swapchain, so the present
+/// itself always covers the window. What is saved is the scene's draws,
+/// the copy is one fullscreen triangle.
+pub struct DamageRenderer {
+    pub damage: DamageTracker,
+    /// Color damaged regions are cleared to before they are drawn.
+    pub clear: wgpu::Color,
+    /// Owns the target and copies it over the frame at a scale of 1.0.
+    present: UpscaleRenderer,
+}
+
+impl DamageRenderer {
+    pub fn new(renderer: &mut GpuRenderer) -> Result<Self, AscendingError> {
+        let present =
+            UpscaleRenderer::new(renderer, ResolutionScale::fixed(1.0))?;
+
+        Ok(Self {
+            damage: DamageTracker::new(present.target().size()),
+            clear: wgpu::Color::BLACK,
+            present,
+        })
+    }
+
+    /// Holds the last frame's scene.
+    pub fn target(&self) -> &RenderTarget {
+        self.present.target()
+    }
+
+    /// Remakes the target if the window was resized, which damages all of
+    /// it, and takes the damage added for this frame. Call once per frame
+    /// after adding the damage and before redrawing it.
+    pub fn update(&mut self, renderer: &mut GpuRenderer) -> Damage {
+        self.present.update(renderer, None);
+        self.damage.set_size(self.present.target().size());
+        self.damage.take()
+    }
+
+    /// The regions to redraw for damage, the whole target when it is full.
+    pub fn regions(&self, damage: &Damage) -> Vec<DamageRect> {
+        let (width, height) = self.target().size();
+
+        match damage {
+            Damage::None => Vec::new(),
+            Damage::Regions(regions) => regions.clone(),
+            Damage::Full => vec![DamageRect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            }],
+        }
+    }
+
+    /// Starts a pass over region of the target, cleared to
+    /// [`DamageRenderer::clear`] and clipped to it. Draw everything that
+    /// can reach into the region, the rest keeps the last frame.
+    pub fn begin_region_pass<'a>(
+        &'a self,
+        renderer: &'a GpuRenderer,
+        encoder: &'a mut wgpu::CommandEncoder,
+        region: DamageRect,
+    ) -> wgpu::RenderPass<'a> {
+        let target = self.target();
+        let (width, height) = target.size();
+        let x = region.x.min(width);
+        let y = region.y.min(height);
+        let region = DamageRect {
+            x,
+            y,
+            width: region.width.min(width - x),
+            height: region.height.min(height - y),
+        };
+
+        if region.area() == width as u64 * height as u64 {
+            return target.begin_pass(encoder, self.clear);
+        }
+
+        let mut pass = target.begin_load_pass(encoder);
+
+        pass.set_scissor_rect(region.x, region.y, region.width, region.height);
+        pass.set_blend_constant(self.clear);
+        pass.set_pipeline(
+            renderer.get_pipelines(DamageClearRenderPipeline).unwrap(),
+        );
+        pass.draw(0..3, 0..1);
+        pass
+    }
+}
+
+impl Pass for DamageRenderer {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        self.present.render(renderer, encoder);
+    }
+}
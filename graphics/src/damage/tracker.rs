@@ -0,0 +1,184 @@
+use crate::Bounds;
+
+/// Regions kept apart before they are merged into their bounds, since
+/// every region is its own pass.
+const MAX_REGIONS: usize = 8;
+/// Share of the target past which redrawing it all is cheaper than the
+/// regions.
+const FULL_AREA: f32 = 0.5;
+/// Pixels added around each region for antialiased edges and glyphs that
+/// reach past their bounds.
+const PADDING: f32 = 2.0;
+
+/// A region of the target in pixels from its top left, like a scissor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DamageRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl DamageRect {
+    pub fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+
+    fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+
+    /// if they overlap or share an edge.
+    fn touches(&self, other: &DamageRect) -> bool {
+        self.x <= other.right()
+            && other.x <= self.right()
+            && self.y <= other.bottom()
+            && other.y <= self.bottom()
+    }
+
+    fn union(&self, other: &DamageRect) -> DamageRect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+
+        DamageRect {
+            x,
+            y,
+            width: self.right().max(other.right()) - x,
+            height: self.bottom().max(other.bottom()) - y,
+        }
+    }
+}
+
+/// What needs redrawing this frame.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Damage {
+    /// Nothing changed, the last frame can be shown again.
+    #[default]
+    None,
+    Regions(Vec<DamageRect>),
+    Full,
+}
+
+impl Damage {
+    pub fn is_none(&self) -> bool {
+        matches!(self, Damage::None)
+    }
+
+    pub fn is_full(&self) -> bool {
+        matches!(self, Damage::Full)
+    }
+}
+
+/// Collects the areas that changed during a frame, like a blinking caret
+/// or a hovered button, so only those get redrawn. Areas are in render
+/// space, pixels from the bottom left, the same as widget bounds.
+pub struct DamageTracker {
+    regions: Vec<DamageRect>,
+    full: bool,
+    size: (u32, u32),
+}
+
+impl DamageTracker {
+    /// Fully damaged, as nothing was drawn yet.
+    pub fn new(size: (u32, u32)) -> Self {
+        Self {
+            regions: Vec::new(),
+            full: true,
+            size,
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Damages everything if the size changed.
+    pub fn set_size(&mut self, size: (u32, u32)) {
+        if self.size != size {
+            self.size = size;
+            self.add_full();
+        }
+    }
+
+    /// Damages the area of bounds. Call it with both the old and new
+    /// bounds of something that moved.
+    pub fn add(&mut self, bounds: Bounds) {
+        if self.full {
+            return;
+        }
+
+        let (width, height) = (self.size.0 as f32, self.size.1 as f32);
+        let left = (bounds.left - PADDING).floor().clamp(0.0, width);
+        let right = (bounds.right + PADDING).ceil().clamp(0.0, width);
+        // Flipped to count from the top.
+        let top = (height - bounds.top - PADDING).floor().clamp(0.0, height);
+        let bottom =
+            (height - bounds.bottom + PADDING).ceil().clamp(0.0, height);
+
+        if right <= left || bottom <= top {
+            return;
+        }
+
+        let mut rect = DamageRect {
+            x: left as u32,
+            y: top as u32,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        };
+
+        // Merges until nothing it touches is left, as a merge can grow it
+        // into regions it missed before.
+        while let Some(index) =
+            self.regions.iter().position(|other| rect.touches(other))
+        {
+            rect = rect.union(&self.regions.swap_remove(index));
+        }
+
+        self.regions.push(rect);
+
+        if self.regions.len() > MAX_REGIONS {
+            let bounds = self
+                .regions
+                .iter()
+                .skip(1)
+                .fold(self.regions[0], |bounds, rect| bounds.union(rect));
+
+            self.regions.clear();
+            self.regions.push(bounds);
+        }
+    }
+
+    /// Damages everything, like after a camera move or theme change.
+    pub fn add_full(&mut self) {
+        self.full = true;
+        self.regions.clear();
+    }
+
+    /// if anything was damaged since the last take.
+    pub fn is_damaged(&self) -> bool {
+        self.full || !self.regions.is_empty()
+    }
+
+    /// The damage so far, which starts over. Regions covering most of the
+    /// target come back as [`Damage::Full`].
+    pub fn take(&mut self) -> Damage {
+        let full = std::mem::take(&mut self.full);
+        let regions = std::mem::take(&mut self.regions);
+        let area = self.size.0 as u64 * self.size.1 as u64;
+
+        if full
+            || regions.iter().map(DamageRect::area).sum::<u64>() as f32
+                > area as f32 * FULL_AREA
+        {
+            Damage::Full
+        } else if regions.is_empty() {
+            Damage::None
+        } else {
+            Damage::Regions(regions)
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use rfd::AsyncFileDialog;
+use std::path::PathBuf;
+
+/// Native open and save dialogs. The calls are async so the event loop
+/// keeps running while the dialog is open; spawn them on the runtime and
+/// pick up the result later.
+#[derive(Clone, Debug, Default)]
+pub struct FileDialog {
+    title: Option<String>,
+    directory: Option<PathBuf>,
+    file_name: Option<String>,
+    /// Name and extensions without the dot.
+    filters: Vec<(String, Vec<String>)>,
+}
+
+impl FileDialog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Folder the dialog starts in.
+    pub fn set_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// File name the save dialog starts with.
+    pub fn set_file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    /// Only shows files with these extensions, like ("Images", &["png"]).
+    pub fn add_filter(
+        mut self,
+        name: impl Into<String>,
+        extensions: &[&str],
+    ) -> Self {
+        self.filters.push((
+            name.into(),
+            extensions.iter().map(|ext| ext.to_string()).collect(),
+        ));
+        self
+    }
+
+    /// None if the dialog was cancelled.
+    pub async fn open(self) -> Option<PathBuf> {
+        let file = self.build().pick_file().await?;
+
+        Some(file.path().to_path_buf())
+    }
+
+    /// Empty if the dialog was cancelled.
+    pub async fn open_many(self) -> Vec<PathBuf> {
+        self.build()
+            .pick_files()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|file| file.path().to_path_buf())
+            .collect()
+    }
+
+    pub async fn open_folder(self) -> Option<PathBuf> {
+        let folder = self.build().pick_folder().await?;
+
+        Some(folder.path().to_path_buf())
+    }
+
+    /// Path to save to, None if the dialog was cancelled.
+    pub async fn save(self) -> Option<PathBuf> {
+        let file = self.build().save_file().await?;
+
+        Some(file.path().to_path_buf())
+    }
+
+    fn build(&self) -> AsyncFileDialog {
+        let mut dialog = AsyncFileDialog::new();
+
+        if let Some(title) = &self.title {
+            dialog = dialog.set_title(title.as_str());
+        }
+
+        if let Some(directory) = &self.directory {
+            dialog = dialog.set_directory(directory);
+        }
+
+        if let Some(file_name) = &self.file_name {
+            dialog = dialog.set_file_name(file_name.as_str());
+        }
+
+        for (name, extensions) in &self.filters {
+            dialog = dialog.add_filter(name.as_str(), extensions.as_slice());
+        }
+
+        dialog
+    }
+}
@@ -0,0 +1,7 @@
+mod graph;
+mod player;
+mod view;
+
+pub use graph::*;
+pub use player::*;
+pub use view::*;
@@ -0,0 +1,136 @@
+use crate::AscendingError;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path};
+
+/// A test on the dialogue's variables, see [`crate::DialoguePlayer::set_var`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DialogueCondition {
+    /// The variable is set to anything but "" or "false".
+    Set(String),
+    NotSet(String),
+    Equals(String, String),
+    All(Vec<DialogueCondition>),
+    Any(Vec<DialogueCondition>),
+}
+
+impl DialogueCondition {
+    pub fn check(&self, vars: &HashMap<String, String>) -> bool {
+        let is_set = |name: &String| {
+            vars.get(name)
+                .is_some_and(|value| !value.is_empty() && value != "false")
+        };
+
+        match self {
+            DialogueCondition::Set(name) => is_set(name),
+            DialogueCondition::NotSet(name) => !is_set(name),
+            DialogueCondition::Equals(name, value) => {
+                vars.get(name) == Some(value)
+            }
+            DialogueCondition::All(all) => all.iter().all(|c| c.check(vars)),
+            DialogueCondition::Any(any) => any.iter().any(|c| c.check(vars)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DialogueChoice {
+    pub text: String,
+    /// Node it leads to, None ends the dialogue.
+    #[serde(default)]
+    pub next: Option<String>,
+    /// Hidden unless it passes.
+    #[serde(default)]
+    pub condition: Option<DialogueCondition>,
+    /// Variables set when it's picked.
+    #[serde(default)]
+    pub set: HashMap<String, String>,
+}
+
+/// One line of the dialogue.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DialogueNode {
+    /// Who says it, matched to where their bubble is shown.
+    pub speaker: String,
+    /// `{name}` is replaced by the variable or substitution hook's value.
+    pub text: String,
+    /// Picked from when any pass their condition.
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+    /// Node after this one without choices, None ends the dialogue.
+    #[serde(default)]
+    pub next: Option<String>,
+    /// Variables set when the node is entered.
+    #[serde(default)]
+    pub set: HashMap<String, String>,
+}
+
+/// Nodes by id and the one to start at. Loaded from RON like
+///
+/// ```ron
+/// (
+///     start: "greet",
+///     nodes: {
+///         "greet": (speaker: "guard", text: "Halt, {player}!", next: Some("ask")),
+///         "ask": (speaker: "guard", text: "Where to?", choices: [
+///             (text: "The castle.", next: Some("castle")),
+///             (text: "Nowhere."),
+///         ]),
+///         "castle": (speaker: "guard", text: "Go on then."),
+///     },
+/// )
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DialogueGraph {
+    pub start: String,
+    pub nodes: HashMap<String, DialogueNode>,
+}
+
+impl DialogueGraph {
+    /// Parses and validates a graph. name is used in errors.
+    pub fn from_ron(name: &str, source: &str) -> Result<Self, AscendingError> {
+        let graph: Self = ron::from_str(source)?;
+
+        graph.validate(name)?;
+        Ok(graph)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AscendingError> {
+        let path = path.as_ref();
+
+        Self::from_ron(
+            &path.display().to_string(),
+            &std::fs::read_to_string(path)?,
+        )
+    }
+
+    pub fn node(&self, id: &str) -> Option<&DialogueNode> {
+        self.nodes.get(id)
+    }
+
+    /// Checks that the start and every next point at nodes in the graph.
+    pub fn validate(&self, name: &str) -> Result<(), AscendingError> {
+        let missing = |id: &str, from: &str| AscendingError::InvalidDialogue {
+            name: name.to_owned(),
+            message: format!("{from} leads to missing node {id}"),
+        };
+
+        if !self.nodes.contains_key(&self.start) {
+            return Err(missing(&self.start, "start"));
+        }
+
+        for (id, node) in &self.nodes {
+            let nexts = node
+                .next
+                .iter()
+                .chain(node.choices.iter().filter_map(|c| c.next.as_ref()));
+
+            for next in nexts {
+                if !self.nodes.contains_key(next) {
+                    return Err(missing(next, id));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
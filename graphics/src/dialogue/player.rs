@@ -0,0 +1,247 @@
+use crate::{DialogueGraph, DialogueNode};
+use std::collections::HashMap;
+
+/// Fills `{name}` placeholders the variables don't, like the player's name
+/// or a key binding. Returns None to leave the placeholder as it is.
+pub type DialogueSubstitution = Box<dyn Fn(&str) -> Option<String>>;
+
+/// What a dialogue reports as it runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DialogueEvent {
+    /// Entered the node with this id.
+    Node(String),
+    /// A choice was picked, by its index in the node's choices.
+    Choice {
+        node: String,
+        choice: usize,
+    },
+    Ended,
+}
+
+/// Walks a [`DialogueGraph`]: the current node, its line typed out over
+/// time, the choices whose conditions pass and the variables they read and
+/// set. Shown on screen by a [`crate::DialogueView`].
+pub struct DialoguePlayer {
+    pub graph: DialogueGraph,
+    /// Characters typed per second, 0.0 shows whole lines at once.
+    pub chars_per_second: f32,
+    vars: HashMap<String, String>,
+    substitution: Option<DialogueSubstitution>,
+    current: Option<String>,
+    /// The current node's text with placeholders filled.
+    line: String,
+    line_chars: usize,
+    revealed: f32,
+    events: Vec<DialogueEvent>,
+}
+
+impl DialoguePlayer {
+    pub fn new(graph: DialogueGraph) -> Self {
+        Self {
+            graph,
+            chars_per_second: 40.0,
+            vars: HashMap::new(),
+            substitution: None,
+            current: None,
+            line: String::new(),
+            line_chars: 0,
+            revealed: 0.0,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn var(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(String::as_str)
+    }
+
+    pub fn set_var(&mut self, name: &str, value: &str) {
+        self.vars.insert(name.to_owned(), value.to_owned());
+    }
+
+    pub fn vars(&self) -> &HashMap<String, String> {
+        &self.vars
+    }
+
+    /// Used for placeholders no variable is set for.
+    pub fn set_substitution(
+        &mut self,
+        substitution: Option<DialogueSubstitution>,
+    ) {
+        self.substitution = substitution;
+    }
+
+    /// Starts at the graph's start node, dropping where it was.
+    pub fn start(&mut self) {
+        let start = self.graph.start.clone();
+
+        self.enter(Some(start));
+    }
+
+    pub fn start_at(&mut self, id: &str) {
+        self.enter(Some(id.to_owned()));
+    }
+
+    /// Ends the dialogue without going through the graph.
+    pub fn stop(&mut self) {
+        if self.current.is_some() {
+            self.enter(None);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub fn current_id(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn current(&self) -> Option<&DialogueNode> {
+        self.graph.node(self.current.as_deref()?)
+    }
+
+    pub fn speaker(&self) -> Option<&str> {
+        self.current().map(|node| node.speaker.as_str())
+    }
+
+    /// The current line with placeholders filled.
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// Characters of the line typed so far.
+    pub fn revealed(&self) -> usize {
+        (self.revealed as usize).min(self.line_chars)
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.revealed() < self.line_chars
+    }
+
+    /// Types the line along by delta seconds.
+    pub fn advance(&mut self, delta: f32) {
+        if !self.is_typing() {
+            return;
+        }
+
+        if self.chars_per_second <= 0.0 {
+            self.skip_typing();
+        } else {
+            self.revealed += delta * self.chars_per_second;
+        }
+    }
+
+    pub fn skip_typing(&mut self) {
+        self.revealed = self.line_chars as f32;
+    }
+
+    /// The current node's choices that pass their conditions, as their
+    /// index in the node and filled text. Empty while typing.
+    pub fn choices(&self) -> Vec<(usize, String)> {
+        let Some(node) = self.current().filter(|_| !self.is_typing()) else {
+            return Vec::new();
+        };
+
+        node.choices
+            .iter()
+            .enumerate()
+            .filter(|(_, choice)| {
+                choice
+                    .condition
+                    .as_ref()
+                    .is_none_or(|condition| condition.check(&self.vars))
+            })
+            .map(|(index, choice)| (index, self.substitute(&choice.text)))
+            .collect()
+    }
+
+    /// Finishes typing the line, or moves past a line without choices.
+    /// Returns false while choices are waiting on a pick.
+    pub fn proceed(&mut self) -> bool {
+        if self.is_typing() {
+            self.skip_typing();
+            return true;
+        }
+
+        if !self.choices().is_empty() {
+            return false;
+        }
+
+        let next = self.current().and_then(|node| node.next.clone());
+
+        self.enter(next);
+        true
+    }
+
+    /// Picks a choice by its index in the node. Returns false if it isn't
+    /// one of [`DialoguePlayer::choices`].
+    pub fn choose(&mut self, choice: usize) -> bool {
+        if !self.choices().iter().any(|(index, _)| *index == choice) {
+            return false;
+        }
+
+        let (Some(id), Some(node)) = (self.current.clone(), self.current())
+        else {
+            return false;
+        };
+        let picked = node.choices[choice].clone();
+
+        self.vars.extend(picked.set);
+        self.events.push(DialogueEvent::Choice { node: id, choice });
+        self.enter(picked.next);
+        true
+    }
+
+    /// Takes what happened since the last call.
+    pub fn take_events(&mut self) -> Vec<DialogueEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn enter(&mut self, id: Option<String>) {
+        let node = id.as_deref().and_then(|id| self.graph.node(id)).cloned();
+
+        self.revealed = 0.0;
+
+        let (Some(id), Some(node)) = (id, node) else {
+            self.current = None;
+            self.line.clear();
+            self.line_chars = 0;
+            self.events.push(DialogueEvent::Ended);
+            return;
+        };
+
+        self.vars.extend(node.set);
+        self.line = self.substitute(&node.text);
+        self.line_chars = self.line.chars().count();
+        self.events.push(DialogueEvent::Node(id.clone()));
+        self.current = Some(id);
+    }
+
+    /// Fills `{name}` from the variables, then the substitution hook.
+    pub fn substitute(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(open) = rest.find('{') {
+            let Some(close) = rest[open..].find('}') else {
+                break;
+            };
+            let name = &rest[open + 1..open + close];
+            let value = self.vars.get(name).cloned().or_else(|| {
+                self.substitution.as_ref().and_then(|hook| hook(name))
+            });
+
+            out.push_str(&rest[..open]);
+
+            match value {
+                Some(value) => out.push_str(&value),
+                None => out.push_str(&rest[open..=open + close]),
+            }
+
+            rest = &rest[open + close + 1..];
+        }
+
+        out.push_str(rest);
+        out
+    }
+}
@@ -0,0 +1,353 @@
+use crate::{
+    AscendingError, Bounds, Color, DialogueEvent, DialoguePlayer,
+    DialogueWidget, GpuRenderer, MessageRouter, Rect, RectRenderer,
+    SpeechBubble, System, Text, TextAtlas, TextRenderer, UiSound, Vec2, Vec3,
+    WidgetHandle, WidgetInput, WidgetSounds,
+};
+use cosmic_text::{Attrs, Metrics};
+use std::collections::HashMap;
+
+/// Shows a [`DialoguePlayer`]: the line typing out in a [`SpeechBubble`]
+/// over its speaker and the choices in a list below. Clicking a choice or
+/// typing its number picks it, clicking anywhere else or pressing space or
+/// enter skips the typing and moves on.
+///
+/// The choice list is placed in render space like the [`crate::Spinner`].
+/// The player's events are emitted through the router when a handle is
+/// set.
+pub struct DialogueView {
+    pub handle: Option<WidgetHandle<DialogueWidget>>,
+    pub sounds: WidgetSounds,
+    pub bubble: SpeechBubble,
+    pub choice_background: Rect,
+    pub highlight: Rect,
+    /// Where speakers without an anchor get their bubble.
+    pub fallback_anchor: Vec3,
+    pub text_color: Color,
+    choice_texts: Vec<Text>,
+    speakers: HashMap<String, Vec3>,
+    choice_position: Vec3,
+    choice_width: f32,
+    /// Node and characters the bubble shows.
+    shown: Option<(String, usize)>,
+    choices: Vec<(usize, String)>,
+    hovered: Option<usize>,
+    was_down: bool,
+    pending_sounds: Vec<UiSound>,
+    changed: bool,
+}
+
+impl DialogueView {
+    /// z is the bubble's, see [`SpeechBubble::new`].
+    pub fn new(renderer: &mut GpuRenderer, z: f32) -> Self {
+        let mut choice_background = Rect::new(renderer, 0);
+        let mut highlight = Rect::new(renderer, 0);
+
+        choice_background
+            .set_color(Color::rgba(20, 20, 20, 230))
+            .set_border_width(1.0)
+            .set_border_color(Color::rgba(90, 90, 90, 255))
+            .set_radius(4.0);
+        highlight.set_color(Color::rgba(90, 120, 200, 160));
+
+        Self {
+            handle: None,
+            sounds: WidgetSounds::default(),
+            bubble: SpeechBubble::new(renderer, "", Vec3::ZERO, z),
+            choice_background,
+            highlight,
+            fallback_anchor: Vec3::ZERO,
+            text_color: Color::rgba(230, 230, 230, 255),
+            choice_texts: Vec::new(),
+            speakers: HashMap::new(),
+            choice_position: Vec3::ZERO,
+            choice_width: 320.0,
+            shown: None,
+            choices: Vec::new(),
+            hovered: None,
+            was_down: false,
+            pending_sounds: Vec::new(),
+            changed: true,
+        }
+    }
+
+    /// World position the speaker's bubble points at. Call again as they
+    /// move.
+    pub fn set_speaker_anchor(&mut self, speaker: &str, anchor: Vec3) {
+        self.speakers.insert(speaker.to_owned(), anchor);
+    }
+
+    pub fn remove_speaker(&mut self, speaker: &str) {
+        self.speakers.remove(speaker);
+    }
+
+    /// Bottom left corner and z of the choice list.
+    pub fn set_choice_position(&mut self, position: Vec3) -> &mut Self {
+        self.choice_position = position;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_choice_width(&mut self, width: f32) -> &mut Self {
+        self.choice_width = width;
+        self.changed = true;
+        self
+    }
+
+    /// Applies a frame of input to the player. Returns the player's
+    /// events, which are also emitted if a handle is set.
+    pub fn handle_input<M: 'static>(
+        &mut self,
+        player: &mut DialoguePlayer,
+        input: &WidgetInput,
+        router: &mut MessageRouter<M>,
+    ) -> Vec<DialogueEvent> {
+        let pressed = input.mouse_down && !self.was_down;
+        let hovered = input.mouse_pos.and_then(|pos| self.choice_at(pos));
+
+        self.was_down = input.mouse_down;
+
+        if hovered != self.hovered {
+            if hovered.is_some() {
+                self.pending_sounds.push(UiSound::Hover);
+            }
+
+            self.hovered = hovered;
+            self.changed = true;
+        }
+
+        if player.is_running() {
+            let typed_choice =
+                input.typed.chars().filter_map(|c| c.to_digit(10)).find_map(
+                    |digit| self.choices.get((digit as usize).checked_sub(1)?),
+                );
+            let picked = match (pressed, hovered) {
+                (true, Some(row)) => self.choices.get(row),
+                _ => typed_choice,
+            }
+            .map(|(index, _)| *index);
+
+            if let Some(index) = picked {
+                if player.choose(index) {
+                    self.pending_sounds.push(UiSound::Click);
+                }
+            } else if pressed || input.typed.contains([' ', '\r', '\n']) {
+                player.proceed();
+            }
+        }
+
+        let events = player.take_events();
+
+        for sound in self.pending_sounds.drain(..) {
+            router.play_sound(self.sounds.cue(sound));
+        }
+
+        if let Some(handle) = self.handle {
+            for event in &events {
+                router.emit(handle, event.clone());
+            }
+        }
+
+        events
+    }
+
+    /// Types the player's line along by delta seconds and moves the bubble
+    /// over its speaker. Must be called before the renderers update it.
+    pub fn update<Controls>(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        player: &mut DialoguePlayer,
+        system: &System<Controls>,
+        delta: f32,
+    ) where
+        Controls: camera::controls::Controls,
+    {
+        player.advance(delta);
+
+        let node = player.current_id().map(str::to_owned);
+        let revealed = player.revealed();
+
+        if node.as_ref() != self.shown.as_ref().map(|(id, _)| id) {
+            self.bubble.set_text(renderer, player.line());
+            self.hovered = None;
+            self.changed = true;
+        }
+
+        if let Some(node) = node {
+            if self.shown.as_ref() != Some(&(node.clone(), revealed)) {
+                self.bubble.set_revealed(renderer, revealed);
+            }
+
+            self.shown = Some((node, revealed));
+        } else {
+            self.shown = None;
+        }
+
+        let anchor = player
+            .speaker()
+            .and_then(|speaker| self.speakers.get(speaker))
+            .copied()
+            .unwrap_or(self.fallback_anchor);
+
+        self.bubble.set_anchor(anchor);
+        self.bubble.update(system, delta);
+
+        let choices = player.choices();
+
+        if choices != self.choices {
+            self.choices = choices;
+            self.changed = true;
+        }
+
+        self.refresh(renderer);
+    }
+
+    fn row_height(&self) -> f32 {
+        self.choice_texts
+            .first()
+            .map_or(18.0, |text| text.buffer.metrics().line_height)
+            + 6.0
+    }
+
+    fn choice_bounds(&self) -> Bounds {
+        let pos = self.choice_position;
+        let height = self.row_height() * self.choices.len() as f32 + 8.0;
+
+        Bounds::new(pos.x, pos.y, pos.x + self.choice_width, pos.y + height)
+    }
+
+    /// The row of the choice under pos.
+    fn choice_at(&self, pos: Vec2) -> Option<usize> {
+        let bounds = self.choice_bounds();
+
+        if self.choices.is_empty() || !bounds.contains(pos) {
+            return None;
+        }
+
+        let row = ((bounds.top - 4.0 - pos.y) / self.row_height()) as usize;
+
+        (row < self.choices.len()).then_some(row)
+    }
+
+    /// Lays out the choice list if anything changed.
+    fn refresh(&mut self, renderer: &mut GpuRenderer) {
+        if !self.changed {
+            return;
+        }
+
+        while self.choice_texts.len() < self.choices.len() {
+            let text = Text::new(
+                renderer,
+                Some(Metrics::new(14.0, 18.0)),
+                Vec3::ZERO,
+                Vec2::ZERO,
+            );
+
+            self.choice_texts.push(text);
+        }
+
+        let bounds = self.choice_bounds();
+        let z = self.choice_position.z;
+        let row_height = self.row_height();
+
+        self.choice_background
+            .set_position(Vec3::new(bounds.left, bounds.bottom, z))
+            .set_size(Vec2::new(
+                bounds.right - bounds.left,
+                bounds.top - bounds.bottom,
+            ));
+
+        for (row, (_, content)) in self.choices.iter().enumerate() {
+            let top = bounds.top - 4.0 - row_height * row as f32;
+            let text = &mut self.choice_texts[row];
+            let line_height = text.buffer.metrics().line_height;
+
+            if self.hovered == Some(row) {
+                // lower Z renders over the background.
+                self.highlight
+                    .set_position(Vec3::new(
+                        bounds.left + 2.0,
+                        top - row_height,
+                        z - 0.01,
+                    ))
+                    .set_size(Vec2::new(self.choice_width - 4.0, row_height));
+            }
+
+            text.set_buffer_size(
+                renderer,
+                (self.choice_width - 16.0) as i32,
+                line_height as i32,
+            )
+            .set_default_color(self.text_color)
+            .set_text(
+                renderer,
+                &format!("{}. {content}", row + 1),
+                Attrs::new(),
+            )
+            .set_position(Vec3::new(
+                bounds.left + 8.0,
+                top - (row_height + line_height) / 2.0,
+                z - 0.02,
+            ));
+            text.size = Vec2::new(self.choice_width - 16.0, line_height);
+        }
+
+        self.changed = false;
+    }
+
+    /// Frees the bubble and choice list.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        self.bubble.unload(renderer);
+        self.choice_background.unload(renderer);
+        self.highlight.unload(renderer);
+
+        for text in self.choice_texts {
+            text.unload(renderer);
+        }
+    }
+}
+
+impl RectRenderer {
+    /// Adds the bubble while a line is shown and the list while there are
+    /// choices.
+    pub fn dialogue_update(
+        &mut self,
+        view: &mut DialogueView,
+        renderer: &mut GpuRenderer,
+    ) {
+        if view.shown.is_some() {
+            self.speech_bubble_update(&mut view.bubble, renderer);
+        }
+
+        if view.choices.is_empty() {
+            return;
+        }
+
+        self.rect_update(&mut view.choice_background, renderer);
+
+        if view.hovered.is_some() {
+            self.rect_update(&mut view.highlight, renderer);
+        }
+    }
+}
+
+impl TextRenderer {
+    pub fn dialogue_update(
+        &mut self,
+        view: &mut DialogueView,
+        atlas: &mut TextAtlas,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        if view.shown.is_some() {
+            self.speech_bubble_update(&mut view.bubble, atlas, renderer)?;
+        }
+
+        let count = view.choices.len();
+
+        for text in view.choice_texts.iter_mut().take(count) {
+            self.text_update(text, atlas, renderer)?;
+        }
+
+        Ok(())
+    }
+}
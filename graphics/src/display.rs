@@ -0,0 +1,9 @@
+pub(crate) mod pipeline;
+mod render;
+mod settings;
+pub(crate) mod uniforms;
+
+pub(crate) use pipeline::*;
+pub use render::*;
+pub use settings::*;
+pub(crate) use uniforms::*;
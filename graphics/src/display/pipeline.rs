@@ -0,0 +1,67 @@
+use crate::{shaders, DisplayLayout, GpuDevice, LayoutStorage, PipeLineLayout};
+use bytemuck::{Pod, Zeroable};
+
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct DisplayRenderPipeline;
+
+impl PipeLineLayout for DisplayRenderPipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let shader = gpu_device.device().create_shader_module(
+            wgpu::ShaderModuleDescriptor {
+                label: Some("Shader"),
+                source: wgpu::ShaderSource::Wgsl(
+                    shaders::DISPLAY_SHADER.into(),
+                ),
+            },
+        );
+
+        let display_layout = layouts.create_layout(gpu_device, DisplayLayout);
+
+        // Create the render pipeline.
+        // A single fullscreen triangle so no vertex buffers are needed.
+        gpu_device.device().create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: Some("Display render pipeline"),
+                layout: Some(&gpu_device.device().create_pipeline_layout(
+                    &wgpu::PipelineLayoutDescriptor {
+                        label: Some("display_render_pipeline_layout"),
+                        bind_group_layouts: &[&display_layout],
+                        push_constant_ranges: &[],
+                    },
+                )),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vertex",
+                    buffers: &[],
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    unclipped_depth: false,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fragment",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: surface_format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview: None,
+            },
+        )
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{
+    AscendingError, ColorblindMode, DisplayLayout, DisplayRaw,
+    DisplayRenderPipeline, DisplaySettings, GpuRenderer, Pass,
+};
+use wgpu::util::DeviceExt;
+
+/// Applies the player's [`DisplaySettings`] to the finished frame. Render
+/// it last, after the UI, so everything gets them. It works on a copy of
+/// the frame, so nothing is drawn when the frame can't be copied, see
+/// [`GpuRenderer::frame_copyable`], or when the settings change nothing.
+pub struct DisplayRenderer {
+    settings: DisplaySettings,
+    buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    frame_texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    /// Size of the frame copy texture.
+    size: [u32; 2],
+    /// if the uniform needs uploading.
+    changed: bool,
+}
+
+impl DisplayRenderer {
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        settings: DisplaySettings,
+    ) -> Result<Self, AscendingError> {
+        let buffer = renderer.device().create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Display buffer"),
+                contents: bytemuck::bytes_of(&DisplayRaw::default()),
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+
+        let sampler =
+            renderer.device().create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("display_sampler"),
+                lod_max_clamp: 0.0,
+                ..Default::default()
+            });
+
+        let size = [
+            (renderer.size().width as u32).max(1),
+            (renderer.size().height as u32).max(1),
+        ];
+        let frame_texture = Self::create_frame_texture(renderer, size);
+        let bind_group = Self::create_bind_group(
+            renderer,
+            &buffer,
+            &sampler,
+            &frame_texture,
+        );
+
+        Ok(Self {
+            settings: settings.clamped(),
+            buffer,
+            sampler,
+            frame_texture,
+            bind_group,
+            size,
+            changed: true,
+        })
+    }
+
+    fn create_frame_texture(
+        renderer: &GpuRenderer,
+        size: [u32; 2],
+    ) -> wgpu::Texture {
+        renderer.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some("display frame texture"),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: renderer.surface_format(),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    fn create_bind_group(
+        renderer: &mut GpuRenderer,
+        buffer: &wgpu::Buffer,
+        sampler: &wgpu::Sampler,
+        frame_texture: &wgpu::Texture,
+    ) -> wgpu::BindGroup {
+        let layout = renderer.create_layout(DisplayLayout);
+        let view =
+            frame_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        renderer
+            .device()
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                ],
+                label: Some("display_bind_group"),
+            })
+    }
+
+    pub fn settings(&self) -> &DisplaySettings {
+        &self.settings
+    }
+
+    /// For the options menu. Values are clamped to the
+    /// [`DisplaySettings`] limits.
+    pub fn set_settings(&mut self, settings: DisplaySettings) -> &mut Self {
+        self.settings = settings.clamped();
+        self.changed = true;
+        self
+    }
+
+    /// Uploads the settings if they changed and follows the window size.
+    /// Call once per frame before rendering.
+    pub fn display_update(&mut self, renderer: &mut GpuRenderer) {
+        let size = [
+            (renderer.size().width as u32).max(1),
+            (renderer.size().height as u32).max(1),
+        ];
+
+        if size != self.size {
+            self.size = size;
+            self.frame_texture = Self::create_frame_texture(renderer, size);
+            self.bind_group = Self::create_bind_group(
+                renderer,
+                &self.buffer,
+                &self.sampler,
+                &self.frame_texture,
+            );
+            self.changed = true;
+        }
+
+        if self.changed {
+            let settings = &self.settings;
+            let raw = DisplayRaw {
+                size: [size[0] as f32, size[1] as f32],
+                brightness: settings.brightness,
+                contrast: settings.contrast,
+                gamma: settings.gamma,
+                colorblind: match settings.colorblind {
+                    ColorblindMode::Off => 0,
+                    ColorblindMode::Protanopia => 1,
+                    ColorblindMode::Deuteranopia => 2,
+                    ColorblindMode::Tritanopia => 3,
+                },
+                strength: settings.colorblind_strength,
+                linear: u32::from(renderer.surface_format().is_srgb()),
+            };
+
+            renderer.queue().write_buffer(
+                &self.buffer,
+                0,
+                bytemuck::bytes_of(&raw),
+            );
+            self.changed = false;
+        }
+    }
+}
+
+impl Pass for DisplayRenderer {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        if self.settings.is_identity() || !renderer.frame_copyable() {
+            return;
+        }
+
+        let (view, frame) =
+            match (renderer.frame_buffer(), renderer.frame_texture()) {
+                (Some(view), Some(frame)) => (view, frame),
+                _ => return,
+            };
+        let frame_size = frame.size();
+
+        encoder.copy_texture_to_texture(
+            frame.as_image_copy(),
+            self.frame_texture.as_image_copy(),
+            wgpu::Extent3d {
+                width: frame_size.width.min(self.size[0]),
+                height: frame_size.height.min(self.size[1]),
+                depth_or_array_layers: 1,
+            },
+        );
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("display pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        pass.set_pipeline(
+            renderer.get_pipelines(DisplayRenderPipeline).unwrap(),
+        );
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
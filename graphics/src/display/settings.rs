@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+/// Color vision deficiency the frame is corrected for. Colors the player
+/// can't tell apart are shifted towards ones they can.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum ColorblindMode {
+    #[default]
+    Off,
+    /// Red weak.
+    Protanopia,
+    /// Green weak.
+    Deuteranopia,
+    /// Blue weak.
+    Tritanopia,
+}
+
+/// Player display options, applied by a [`crate::DisplayRenderer`] over the
+/// finished frame. Serializable so an options menu can save them.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    /// Multiplies the color. 1.0 leaves it as is.
+    pub brightness: f32,
+    /// Spreads colors away from mid grey. 1.0 leaves them as is.
+    pub contrast: f32,
+    /// Above 1.0 lifts the darks, below darkens them.
+    pub gamma: f32,
+    pub colorblind: ColorblindMode,
+    /// How much of the colorblind correction is applied, 0.0 to 1.0.
+    pub colorblind_strength: f32,
+}
+
+impl DisplaySettings {
+    /// Limits the players can set them to before the frame gets unusable.
+    pub const BRIGHTNESS: (f32, f32) = (0.5, 1.5);
+    pub const CONTRAST: (f32, f32) = (0.5, 1.5);
+    pub const GAMMA: (f32, f32) = (0.5, 2.5);
+
+    /// if drawing them would change the frame.
+    pub fn is_identity(&self) -> bool {
+        self.brightness == 1.0
+            && self.contrast == 1.0
+            && self.gamma == 1.0
+            && (self.colorblind == ColorblindMode::Off
+                || self.colorblind_strength <= 0.0)
+    }
+
+    /// Copy with every value within its limits.
+    pub fn clamped(&self) -> Self {
+        Self {
+            brightness: self
+                .brightness
+                .clamp(Self::BRIGHTNESS.0, Self::BRIGHTNESS.1),
+            contrast: self.contrast.clamp(Self::CONTRAST.0, Self::CONTRAST.1),
+            gamma: self.gamma.clamp(Self::GAMMA.0, Self::GAMMA.1),
+            colorblind: self.colorblind,
+            colorblind_strength: self.colorblind_strength.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            brightness: 1.0,
+            contrast: 1.0,
+            gamma: 1.0,
+            colorblind: ColorblindMode::Off,
+            colorblind_strength: 1.0,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{Bounds, Color, GpuRenderer, Rect, RectRenderer, Vec2, Vec3};
+use std::path::PathBuf;
+
+/// Files dropped onto a target.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileDrop {
+    pub target: usize,
+    pub files: Vec<PathBuf>,
+}
+
+/// Areas of the window files can be dragged onto, like an asset list or an
+/// image slot. The target under the cursor gets highlighted while files are
+/// hovering. Bounds and positions are in render space, the same as rects
+/// that don't use the camera.
+pub struct DropTargets {
+    targets: Vec<(usize, Bounds)>,
+    /// Drawn over the hovered target.
+    pub highlight: Rect,
+    /// Z the highlight renders at.
+    pub z: f32,
+    hovered: Option<usize>,
+}
+
+impl DropTargets {
+    pub fn new(renderer: &mut GpuRenderer, render_layer: u32, z: f32) -> Self {
+        let mut highlight = Rect::new(renderer, render_layer);
+
+        highlight
+            .set_color(Color::rgba(90, 150, 255, 60))
+            .set_border_width(2.0)
+            .set_border_color(Color::rgba(90, 150, 255, 255));
+
+        Self {
+            targets: Vec::new(),
+            highlight,
+            z,
+            hovered: None,
+        }
+    }
+
+    /// Adds a target or moves it if the id is already used. Later targets
+    /// are on top of earlier ones where they overlap.
+    pub fn set_target(&mut self, id: usize, bounds: Bounds) -> &mut Self {
+        if let Some(target) = self.targets.iter_mut().find(|(i, _)| *i == id) {
+            target.1 = bounds;
+        } else {
+            self.targets.push((id, bounds));
+        }
+
+        self
+    }
+
+    pub fn remove_target(&mut self, id: usize) {
+        self.targets.retain(|(i, _)| *i != id);
+
+        if self.hovered == Some(id) {
+            self.hovered = None;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.targets.clear();
+        self.hovered = None;
+    }
+
+    /// Target the files would be dropped on.
+    pub fn hovered(&self) -> Option<usize> {
+        self.hovered
+    }
+
+    /// Topmost target under pos.
+    pub fn target_at(&self, pos: Vec2) -> Option<usize> {
+        self.targets
+            .iter()
+            .rev()
+            .find(|(_, bounds)| bounds.contains(pos))
+            .map(|(id, _)| *id)
+    }
+
+    /// Call every frame with if files are hovering the window, see
+    /// `InputHandler::is_file_hovering`. Returns the hovered target.
+    pub fn update(&mut self, hovering: bool, mouse_pos: Vec2) -> Option<usize> {
+        let hovered = if hovering {
+            self.target_at(mouse_pos)
+        } else {
+            None
+        };
+
+        if hovered != self.hovered {
+            self.hovered = hovered;
+
+            if let Some(bounds) = hovered.and_then(|id| self.bounds(id)) {
+                self.highlight
+                    .set_position(Vec3::new(bounds.left, bounds.bottom, self.z))
+                    .set_size(Vec2::new(
+                        bounds.right - bounds.left,
+                        bounds.top - bounds.bottom,
+                    ));
+            }
+        }
+
+        hovered
+    }
+
+    /// Gives the files to the target under pos, None if there are no files
+    /// or no target there. Ends the hover highlight either way.
+    pub fn take_drop(
+        &mut self,
+        files: Vec<PathBuf>,
+        pos: Vec2,
+    ) -> Option<FileDrop> {
+        if files.is_empty() {
+            return None;
+        }
+
+        self.hovered = None;
+        self.target_at(pos).map(|target| FileDrop { target, files })
+    }
+
+    /// Frees the highlight's buffer.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        self.highlight.unload(renderer);
+    }
+
+    fn bounds(&self, id: usize) -> Option<Bounds> {
+        self.targets
+            .iter()
+            .find(|(i, _)| *i == id)
+            .map(|(_, bounds)| *bounds)
+    }
+}
+
+impl RectRenderer {
+    /// Adds the highlight while files hover a target.
+    pub fn drop_targets_update(
+        &mut self,
+        targets: &mut DropTargets,
+        renderer: &mut GpuRenderer,
+    ) {
+        if targets.hovered.is_some() {
+            self.rect_update(&mut targets.highlight, renderer);
+        }
+    }
+}
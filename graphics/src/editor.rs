@@ -0,0 +1,7 @@
+mod command;
+mod commands;
+mod snapping;
+
+pub use command::*;
+pub use commands::*;
+pub use snapping::*;
@@ -0,0 +1,121 @@
+use std::{any::Any, collections::VecDeque};
+
+/// A reversible edit on T. Commands are applied through a [`CommandStack`]
+/// which keeps them around so they can be undone and redone.
+pub trait EditorCommand<T>: Any {
+    fn apply(&mut self, target: &mut T);
+
+    fn undo(&mut self, target: &mut T);
+
+    /// Folds next into self so they undo as one step, like the tiles of a
+    /// paint stroke. Returns false if they can not be merged.
+    fn merge(&mut self, _next: &dyn Any) -> bool {
+        false
+    }
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Undo and redo history for edits on T. Holds at most `limit` steps and
+/// drops the oldest once full.
+pub struct CommandStack<T> {
+    undo: VecDeque<Box<dyn EditorCommand<T>>>,
+    redo: Vec<Box<dyn EditorCommand<T>>>,
+    limit: usize,
+    /// Set to stop the next push from merging into the last step.
+    sealed: bool,
+}
+
+impl<T: 'static> Default for CommandStack<T> {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl<T: 'static> CommandStack<T> {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit: limit.max(1),
+            sealed: false,
+        }
+    }
+
+    /// Applies the command and records it. Clears the redo history.
+    pub fn push<C: EditorCommand<T>>(
+        &mut self,
+        mut command: C,
+        target: &mut T,
+    ) {
+        command.apply(target);
+        self.redo.clear();
+
+        if !self.sealed {
+            if let Some(last) = self.undo.back_mut() {
+                if last.merge(command.as_any()) {
+                    return;
+                }
+            }
+        }
+
+        self.sealed = false;
+        self.undo.push_back(Box::new(command));
+
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Ends the current merge so the next push starts a new undo step.
+    /// Call when a paint stroke or drag finishes.
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    pub fn undo(&mut self, target: &mut T) -> bool {
+        match self.undo.pop_back() {
+            Some(mut command) => {
+                command.undo(target);
+                self.redo.push(command);
+                self.sealed = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self, target: &mut T) -> bool {
+        match self.redo.pop() {
+            Some(mut command) => {
+                command.apply(target);
+                self.undo.push_back(command);
+                self.sealed = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit.max(1);
+
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.sealed = false;
+    }
+}
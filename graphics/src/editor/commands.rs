@@ -0,0 +1,226 @@
+use crate::{
+    EditorCommand, Map, MapOverlay, Snapping, TileData, Vec2, WorldUi,
+};
+use std::any::Any;
+
+/// Sets tiles on a [`Map`]. Pushes with the same non zero stroke merge into
+/// one undo step.
+pub struct SetTile {
+    pub stroke: u32,
+    /// Position, tile before and tile after.
+    tiles: Vec<((u32, u32, u32), TileData, TileData)>,
+}
+
+impl SetTile {
+    pub fn new(map: &Map, pos: (u32, u32, u32), tile: TileData) -> Self {
+        Self::with_stroke(map, pos, tile, 0)
+    }
+
+    pub fn with_stroke(
+        map: &Map,
+        pos: (u32, u32, u32),
+        tile: TileData,
+        stroke: u32,
+    ) -> Self {
+        Self {
+            stroke,
+            tiles: vec![(pos, map.get_tile(pos), tile)],
+        }
+    }
+
+    /// Commits an overlay taken with [`Map::take_overlay`] as one step.
+    pub fn from_overlay(map: &Map, overlay: &MapOverlay) -> Self {
+        Self {
+            stroke: 0,
+            tiles: overlay
+                .iter()
+                .map(|(pos, tile)| (pos, map.get_tile(pos), tile))
+                .collect(),
+        }
+    }
+}
+
+impl EditorCommand<Map> for SetTile {
+    fn apply(&mut self, target: &mut Map) {
+        for (pos, _, tile) in &self.tiles {
+            target.set_tile(*pos, *tile);
+        }
+    }
+
+    fn undo(&mut self, target: &mut Map) {
+        for (pos, old, _) in self.tiles.iter().rev() {
+            target.set_tile(*pos, *old);
+        }
+    }
+
+    fn merge(&mut self, next: &dyn Any) -> bool {
+        match next.downcast_ref::<SetTile>() {
+            Some(next) if self.stroke != 0 && next.stroke == self.stroke => {
+                for (pos, old, tile) in &next.tiles {
+                    // Keep the first old tile so undo restores the original.
+                    match self.tiles.iter_mut().find(|v| v.0 == *pos) {
+                        Some(entry) => entry.2 = *tile,
+                        None => self.tiles.push((*pos, *old, *tile)),
+                    }
+                }
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Something with movable and resizable parts picked by index.
+pub trait EditorWidgets {
+    fn widget_position(&self, id: usize) -> Option<Vec2>;
+
+    fn set_widget_position(&mut self, id: usize, position: Vec2);
+
+    fn widget_size(&self, id: usize) -> Option<Vec2>;
+
+    fn set_widget_size(&mut self, id: usize, size: Vec2);
+}
+
+impl EditorWidgets for WorldUi {
+    fn widget_position(&self, id: usize) -> Option<Vec2> {
+        self.elements.get(id).map(|element| element.offset)
+    }
+
+    fn set_widget_position(&mut self, id: usize, position: Vec2) {
+        if let Some(element) = self.elements.get_mut(id) {
+            element.offset = position;
+            self.mark_changed();
+        }
+    }
+
+    fn widget_size(&self, id: usize) -> Option<Vec2> {
+        self.elements.get(id).map(|element| element.size)
+    }
+
+    fn set_widget_size(&mut self, id: usize, size: Vec2) {
+        if let Some(element) = self.elements.get_mut(id) {
+            element.size = size;
+            self.mark_changed();
+        }
+    }
+}
+
+/// Moves a widget. Consecutive moves of the same widget merge so a drag
+/// undoes in one step.
+pub struct MoveWidget {
+    pub id: usize,
+    from: Option<Vec2>,
+    to: Vec2,
+}
+
+impl MoveWidget {
+    pub fn new<T: EditorWidgets>(target: &T, id: usize, to: Vec2) -> Self {
+        Self {
+            id,
+            from: target.widget_position(id),
+            to,
+        }
+    }
+
+    /// A move to where the widget's edges snap to the grid or guides.
+    pub fn snapped<T: EditorWidgets>(
+        target: &T,
+        id: usize,
+        to: Vec2,
+        snapping: &Snapping,
+    ) -> Self {
+        let size = target.widget_size(id).unwrap_or_default();
+
+        Self::new(target, id, snapping.snap_box(to, size))
+    }
+}
+
+impl<T: EditorWidgets> EditorCommand<T> for MoveWidget {
+    fn apply(&mut self, target: &mut T) {
+        target.set_widget_position(self.id, self.to);
+    }
+
+    fn undo(&mut self, target: &mut T) {
+        if let Some(from) = self.from {
+            target.set_widget_position(self.id, from);
+        }
+    }
+
+    fn merge(&mut self, next: &dyn Any) -> bool {
+        match next.downcast_ref::<MoveWidget>() {
+            Some(next) if next.id == self.id => {
+                self.to = next.to;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Resizes a widget. Consecutive resizes of the same widget merge.
+pub struct ResizeWidget {
+    pub id: usize,
+    from: Option<Vec2>,
+    to: Vec2,
+}
+
+impl ResizeWidget {
+    pub fn new<T: EditorWidgets>(target: &T, id: usize, to: Vec2) -> Self {
+        Self {
+            id,
+            from: target.widget_size(id),
+            to,
+        }
+    }
+
+    /// A resize where the widget's top right corner snaps to the grid or
+    /// guides.
+    pub fn snapped<T: EditorWidgets>(
+        target: &T,
+        id: usize,
+        to: Vec2,
+        snapping: &Snapping,
+    ) -> Self {
+        let to = match target.widget_position(id) {
+            Some(pos) => (snapping.snap(pos + to) - pos).max(Vec2::ZERO),
+            None => to,
+        };
+
+        Self::new(target, id, to)
+    }
+}
+
+impl<T: EditorWidgets> EditorCommand<T> for ResizeWidget {
+    fn apply(&mut self, target: &mut T) {
+        target.set_widget_size(self.id, self.to);
+    }
+
+    fn undo(&mut self, target: &mut T) {
+        if let Some(from) = self.from {
+            target.set_widget_size(self.id, from);
+        }
+    }
+
+    fn merge(&mut self, next: &dyn Any) -> bool {
+        match next.downcast_ref::<ResizeWidget>() {
+            Some(next) if next.id == self.id => {
+                self.to = next.to;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
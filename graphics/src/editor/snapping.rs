@@ -0,0 +1,417 @@
+use crate::{Canvas, Color, Vec2, Vec4, WidgetInput};
+
+/// Most grid lines drawn per axis, so a tiny grid on a big view stays cheap.
+const MAX_GRID_LINES: f32 = 512.0;
+/// Mouse distance in pixels that still grabs a guide.
+const GUIDE_HIT: f32 = 4.0;
+
+/// Snaps positions to the nearest grid point.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GridSnap {
+    pub enabled: bool,
+    /// Cell size per axis. An axis with a size of 0.0 doesn't snap.
+    pub size: Vec2,
+    /// Where the grid starts, for grids not lined up with the origin.
+    pub offset: Vec2,
+}
+
+impl Default for GridSnap {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            size: Vec2::splat(16.0),
+            offset: Vec2::ZERO,
+        }
+    }
+}
+
+impl GridSnap {
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            size,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// The nearest grid point, or pos as is while disabled.
+    pub fn snap(&self, pos: Vec2) -> Vec2 {
+        if !self.enabled {
+            return pos;
+        }
+
+        Vec2::new(
+            snap_axis(pos.x, self.size.x, self.offset.x),
+            snap_axis(pos.y, self.size.y, self.offset.y),
+        )
+    }
+
+    /// Draws the grid lines within bounds, given as x, y, width and height.
+    pub fn draw(&self, canvas: &mut Canvas, bounds: Vec4, color: Color) {
+        let (min, max) = (bounds.truncate().truncate(), bounds_max(bounds));
+
+        if self.size.x > 0.0 && bounds.z / self.size.x <= MAX_GRID_LINES {
+            let mut x = first_line(min.x, self.size.x, self.offset.x);
+
+            while x <= max.x {
+                canvas.line(
+                    Vec2::new(x, min.y),
+                    Vec2::new(x, max.y),
+                    1.0,
+                    color,
+                );
+                x += self.size.x;
+            }
+        }
+
+        if self.size.y > 0.0 && bounds.w / self.size.y <= MAX_GRID_LINES {
+            let mut y = first_line(min.y, self.size.y, self.offset.y);
+
+            while y <= max.y {
+                canvas.line(
+                    Vec2::new(min.x, y),
+                    Vec2::new(max.x, y),
+                    1.0,
+                    color,
+                );
+                y += self.size.y;
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GuideAxis {
+    /// A vertical line at an x position, pulled from the left ruler.
+    Vertical,
+    /// A horizontal line at a y position, pulled from the top ruler.
+    Horizontal,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Guide {
+    pub axis: GuideAxis,
+    pub position: f32,
+}
+
+/// Ruler guides objects snap to. Drag from a ruler to add a guide, drag a
+/// guide to move it and drop it back on its ruler to remove it.
+///
+/// The rulers run along the top and left of the bounds given to
+/// handle_input and draw, which are in the same space as the guides.
+pub struct Guides {
+    guides: Vec<Guide>,
+    /// if objects snap to the guides. They still show while off.
+    pub enabled: bool,
+    /// How close an edge has to be to snap onto a guide.
+    pub snap_distance: f32,
+    /// Thickness of the rulers.
+    pub ruler_size: f32,
+    /// Distance between ruler ticks. Every fifth one is longer.
+    pub tick_step: f32,
+    pub color: Color,
+    /// Color of the guide under the mouse or being dragged.
+    pub active_color: Color,
+    pub ruler_color: Color,
+    pub tick_color: Color,
+    hovered: Option<usize>,
+    dragging: Option<usize>,
+    was_down: bool,
+}
+
+impl Default for Guides {
+    fn default() -> Self {
+        Self {
+            guides: Vec::new(),
+            enabled: true,
+            snap_distance: 8.0,
+            ruler_size: 16.0,
+            tick_step: 10.0,
+            color: Color::rgba(0, 200, 255, 200),
+            active_color: Color::rgba(250, 210, 50, 255),
+            ruler_color: Color::rgba(40, 40, 46, 230),
+            tick_color: Color::rgba(160, 160, 170, 255),
+            hovered: None,
+            dragging: None,
+            was_down: false,
+        }
+    }
+}
+
+impl Guides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn guides(&self) -> &[Guide] {
+        &self.guides
+    }
+
+    /// Returns the new guide's index.
+    pub fn add(&mut self, axis: GuideAxis, position: f32) -> usize {
+        self.guides.push(Guide { axis, position });
+        self.guides.len() - 1
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Guide> {
+        if index >= self.guides.len() {
+            return None;
+        }
+
+        self.hovered = None;
+        self.dragging = None;
+        Some(self.guides.remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.guides.clear();
+        self.hovered = None;
+        self.dragging = None;
+    }
+
+    pub fn hovered(&self) -> Option<usize> {
+        self.hovered
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.dragging.is_some()
+    }
+
+    /// The guide whose line is under pos.
+    pub fn guide_at(&self, pos: Vec2) -> Option<usize> {
+        self.guides
+            .iter()
+            .enumerate()
+            .map(|(i, guide)| {
+                (i, (guide.position - along(guide.axis, pos)).abs())
+            })
+            .filter(|(_, distance)| *distance <= GUIDE_HIT)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// The offset that moves the nearest of a span's two edges onto a
+    /// guide within snap distance.
+    pub fn snap_span(
+        &self,
+        axis: GuideAxis,
+        start: f32,
+        length: f32,
+    ) -> Option<f32> {
+        if !self.enabled {
+            return None;
+        }
+
+        self.guides
+            .iter()
+            .filter(|guide| guide.axis == axis)
+            .flat_map(|guide| {
+                [guide.position - start, guide.position - (start + length)]
+            })
+            .filter(|offset| offset.abs() <= self.snap_distance)
+            .min_by(|a, b| a.abs().total_cmp(&b.abs()))
+    }
+
+    /// Applies a frame of mouse input. Returns true while the guides use
+    /// the mouse, so editor tools can ignore it.
+    pub fn handle_input(&mut self, input: &WidgetInput, bounds: Vec4) -> bool {
+        let pressed = input.mouse_down && !self.was_down;
+
+        self.was_down = input.mouse_down;
+
+        let Some(pos) = input.mouse_pos else {
+            return self.dragging.is_some();
+        };
+
+        if let Some(index) = self.dragging {
+            let axis = self.guides[index].axis;
+
+            if input.mouse_down {
+                self.guides[index].position = along(axis, pos);
+            } else {
+                self.dragging = None;
+
+                // Dropped back on its ruler or outside the view.
+                if self.ruler_hit(bounds, pos) == Some(axis)
+                    || !contains(bounds, pos)
+                {
+                    self.remove(index);
+                }
+            }
+
+            return true;
+        }
+
+        if !contains(bounds, pos) {
+            self.hovered = None;
+            return false;
+        }
+
+        let ruler = self.ruler_hit(bounds, pos);
+
+        self.hovered = self.guide_at(pos).filter(|_| ruler.is_none());
+
+        if pressed {
+            if let Some(axis) = ruler {
+                self.dragging = Some(self.add(axis, along(axis, pos)));
+            } else {
+                self.dragging = self.hovered;
+            }
+        }
+
+        ruler.is_some() || self.hovered.is_some()
+    }
+
+    /// Draws the guide lines and rulers within bounds.
+    pub fn draw(&self, canvas: &mut Canvas, bounds: Vec4) {
+        let (min, max) = (bounds.truncate().truncate(), bounds_max(bounds));
+        let active = self.dragging.or(self.hovered);
+
+        for (i, guide) in self.guides.iter().enumerate() {
+            let color = if active == Some(i) {
+                self.active_color
+            } else {
+                self.color
+            };
+            let (from, to) = match guide.axis {
+                GuideAxis::Vertical => (
+                    Vec2::new(guide.position, min.y),
+                    Vec2::new(guide.position, max.y),
+                ),
+                GuideAxis::Horizontal => (
+                    Vec2::new(min.x, guide.position),
+                    Vec2::new(max.x, guide.position),
+                ),
+            };
+
+            canvas.line(from, to, 1.0, color);
+        }
+
+        let ruler = self.ruler_size;
+
+        canvas
+            .rect(
+                Vec2::new(min.x, max.y - ruler),
+                Vec2::new(bounds.z, ruler),
+                self.ruler_color,
+            )
+            .rect(min, Vec2::new(ruler, bounds.w - ruler), self.ruler_color);
+
+        if self.tick_step <= 0.0 {
+            return;
+        }
+
+        let tick = |i: i32| {
+            if i % 5 == 0 {
+                ruler * 0.6
+            } else {
+                ruler * 0.3
+            }
+        };
+        let mut i = 0;
+        let mut x = min.x + ruler;
+
+        while x <= max.x {
+            canvas.line(
+                Vec2::new(x, max.y),
+                Vec2::new(x, max.y - tick(i)),
+                1.0,
+                self.tick_color,
+            );
+            x += self.tick_step;
+            i += 1;
+        }
+
+        let mut i = 0;
+        let mut y = max.y - ruler;
+
+        while y >= min.y {
+            canvas.line(
+                Vec2::new(min.x, y),
+                Vec2::new(min.x + tick(i), y),
+                1.0,
+                self.tick_color,
+            );
+            y -= self.tick_step;
+            i += 1;
+        }
+    }
+
+    /// Which guide a ruler under pos would make.
+    fn ruler_hit(&self, bounds: Vec4, pos: Vec2) -> Option<GuideAxis> {
+        let max = bounds_max(bounds);
+
+        if pos.y >= max.y - self.ruler_size && pos.y <= max.y {
+            Some(GuideAxis::Horizontal)
+        } else if pos.x >= bounds.x && pos.x <= bounds.x + self.ruler_size {
+            Some(GuideAxis::Vertical)
+        } else {
+            None
+        }
+    }
+}
+
+/// Grid and guide snapping shared by the editor's tools. Guides win over
+/// the grid on an axis where one is in reach.
+#[derive(Default)]
+pub struct Snapping {
+    pub grid: GridSnap,
+    pub guides: Guides,
+}
+
+impl Snapping {
+    pub fn snap(&self, pos: Vec2) -> Vec2 {
+        self.snap_box(pos, Vec2::ZERO)
+    }
+
+    /// Snaps a box's position so its nearest edge meets a guide, otherwise
+    /// its corner to the grid. Each axis snaps on its own.
+    pub fn snap_box(&self, pos: Vec2, size: Vec2) -> Vec2 {
+        let grid = self.grid.snap(pos);
+        let x = self.guides.snap_span(GuideAxis::Vertical, pos.x, size.x);
+        let y = self.guides.snap_span(GuideAxis::Horizontal, pos.y, size.y);
+
+        Vec2::new(
+            x.map_or(grid.x, |offset| pos.x + offset),
+            y.map_or(grid.y, |offset| pos.y + offset),
+        )
+    }
+}
+
+fn snap_axis(value: f32, size: f32, offset: f32) -> f32 {
+    if size > 0.0 {
+        ((value - offset) / size).round() * size + offset
+    } else {
+        value
+    }
+}
+
+fn first_line(min: f32, size: f32, offset: f32) -> f32 {
+    ((min - offset) / size).ceil() * size + offset
+}
+
+fn bounds_max(bounds: Vec4) -> Vec2 {
+    Vec2::new(bounds.x + bounds.z, bounds.y + bounds.w)
+}
+
+fn contains(bounds: Vec4, pos: Vec2) -> bool {
+    let max = bounds_max(bounds);
+
+    pos.x >= bounds.x && pos.x <= max.x && pos.y >= bounds.y && pos.y <= max.y
+}
+
+/// The coordinate a guide on axis measures.
+fn along(axis: GuideAxis, pos: Vec2) -> f32 {
+    match axis {
+        GuideAxis::Vertical => pos.x,
+        GuideAxis::Horizontal => pos.y,
+    }
+}
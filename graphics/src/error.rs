@@ -32,11 +32,69 @@ pub enum AscendingError {
     #[error(transparent)]
     Device(#[from] wgpu::RequestDeviceError),
     #[error(transparent)]
+    CreateSurface(#[from] wgpu::CreateSurfaceError),
+    #[error("No graphics adapter matches the requested options.")]
+    NoAdapter,
+    #[error("Invalid renderer config: {0}")]
+    InvalidRendererConfig(String),
+    #[error(transparent)]
+    Window(#[from] winit::error::ExternalError),
+    #[error(transparent)]
     ImageError(#[from] image::ImageError),
+    #[error(transparent)]
+    BufferMap(#[from] wgpu::BufferAsyncError),
+    #[error("Texture can't be read back: {0}")]
+    InvalidReadback(&'static str),
+    #[error("Texture readback ended before it finished.")]
+    ReadbackCanceled,
     #[error("Image atlas has no more space.")]
     AtlasFull,
+    #[cfg(feature = "shapes")]
     #[error(transparent)]
     LyonTessellation(#[from] lyon::lyon_tessellation::TessellationError),
+    #[cfg(feature = "maps")]
+    #[error(transparent)]
+    Decompress(#[from] lz4_flex::block::DecompressError),
+    #[error("Invalid map data: {0}")]
+    InvalidMapData(&'static str),
+    #[error("Invalid layer {name}: {message}")]
+    InvalidLayer { name: String, message: String },
+    #[error("Invalid catalog {name} line {line}: {message}")]
+    InvalidCatalog {
+        name: String,
+        line: usize,
+        message: String,
+    },
+    #[error("Invalid manifest {name} line {line}: {message}")]
+    InvalidManifest {
+        name: String,
+        line: usize,
+        message: String,
+    },
+    #[cfg(any(feature = "software", feature = "dialogue"))]
+    #[error(transparent)]
+    Ron(#[from] ron::error::SpannedError),
+    #[cfg(feature = "dialogue")]
+    #[error("Invalid dialogue {name}: {message}")]
+    InvalidDialogue { name: String, message: String },
+    #[cfg(feature = "frame_graph")]
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "scripting")]
+    #[error("Script {name} failed: {message}")]
+    Script { name: String, message: String },
+    #[cfg(feature = "golden")]
+    #[error("Golden image {name} differs in {diff_pixels} of {total_pixels} pixels, see {}", output.display())]
+    GoldenMismatch {
+        name: String,
+        diff_pixels: usize,
+        total_pixels: usize,
+        /// Folder the actual and diff images were written to.
+        output: std::path::PathBuf,
+    },
+    #[cfg(feature = "golden")]
+    #[error("Golden image {} is missing, run with GOLDEN_BLESS set to create it", .0.display())]
+    GoldenMissing(std::path::PathBuf),
     #[error(transparent)]
     Other(#[from] OtherError),
 }
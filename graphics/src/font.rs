@@ -1,9 +1,17 @@
-mod pipeline;
+mod layout;
+mod measure;
+pub(crate) mod pipeline;
 mod render;
+mod shaping;
 mod text;
-mod vertex;
+mod transform;
+pub(crate) mod vertex;
 
-pub use pipeline::TextRenderPipeline;
+pub use layout::*;
+pub use measure::*;
+pub(crate) use pipeline::TextRenderPipeline;
 pub use render::*;
+pub use shaping::*;
 pub use text::*;
-pub use vertex::*;
+pub use transform::*;
+pub(crate) use vertex::*;
@@ -0,0 +1,430 @@
+use crate::{measure_text, AscendingError, GpuRenderer, Text, Vec2};
+use cosmic_text::{Align, Attrs, Buffer, FontSystem, Metrics, Wrap};
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+const SOFT_HYPHEN: char = '\u{AD}';
+/// Layouts redone to turn soft hyphens at line ends into visible ones.
+/// Each can move a later break onto another soft hyphen.
+const HYPHEN_PASSES: usize = 3;
+
+/// Horizontal alignment of every line within the text's width.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextAlign {
+    /// Or right for right to left text.
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Stretches the spaces of wrapped lines to fill the width. The last
+    /// line of a paragraph stays left aligned.
+    Justify,
+}
+
+impl TextAlign {
+    fn to_cosmic(self) -> Option<Align> {
+        match self {
+            TextAlign::Left => None,
+            TextAlign::Center => Some(Align::Center),
+            TextAlign::Right => Some(Align::Right),
+            TextAlign::Justify => Some(Align::Justified),
+        }
+    }
+}
+
+/// Which way glyphs follow each other.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TextDirection {
+    #[default]
+    Horizontal,
+    /// Top to bottom in columns going right to left, like CJK signage.
+    /// Each paragraph starts a column and continues in the next one past
+    /// the text's height. Glyphs stay upright, one line height apart.
+    Vertical,
+}
+
+/// Places glyphs of vertical text, one column per line height from the
+/// right.
+pub(crate) struct VerticalPen {
+    line_height: f32,
+    /// Of the first row, from the top.
+    first_baseline: f32,
+    size: Vec2,
+    column: usize,
+    row: usize,
+    line: Option<usize>,
+}
+
+impl VerticalPen {
+    pub(crate) fn new(
+        metrics: Metrics,
+        first_baseline: f32,
+        size: Vec2,
+    ) -> Self {
+        Self {
+            line_height: metrics.line_height,
+            first_baseline,
+            size,
+            column: 0,
+            row: 0,
+            line: None,
+        }
+    }
+
+    /// Starts a column for each new paragraph.
+    pub(crate) fn start_line(&mut self, line: usize) {
+        if self.line.is_some_and(|last| last != line) {
+            self.column += 1;
+            self.row = 0;
+        }
+
+        self.line = Some(line);
+    }
+
+    /// Left of the next glyph of width and its baseline from the top.
+    pub(crate) fn place(&mut self, width: f32) -> (f32, f32) {
+        if self.row > 0
+            && (self.row + 1) as f32 * self.line_height > self.size.y
+        {
+            self.column += 1;
+            self.row = 0;
+        }
+
+        let center =
+            self.size.x - (self.column as f32 + 0.5) * self.line_height;
+        let baseline = self.first_baseline + self.row as f32 * self.line_height;
+
+        self.row += 1;
+        (center - width * 0.5, baseline)
+    }
+}
+
+/// Where tabs advance to, in pixels from the left of the text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TabStops {
+    /// Explicit stops, in order.
+    stops: Vec<f32>,
+    /// Spacing of the stops after the last explicit one.
+    every: f32,
+}
+
+impl TabStops {
+    pub fn every(width: f32) -> Self {
+        Self {
+            stops: Vec::new(),
+            every: width.max(1.0),
+        }
+    }
+
+    /// stops, then every width past the last of them.
+    pub fn at(mut stops: Vec<f32>, every: f32) -> Self {
+        stops.sort_by(f32::total_cmp);
+
+        Self {
+            stops,
+            every: every.max(1.0),
+        }
+    }
+
+    /// The first stop past x.
+    pub fn next(&self, x: f32) -> f32 {
+        if let Some(stop) = self.stops.iter().find(|stop| **stop > x) {
+            return *stop;
+        }
+
+        let base = self.stops.last().copied().unwrap_or(0.0);
+
+        base + (((x - base) / self.every).floor() + 1.0) * self.every
+    }
+}
+
+impl Default for TabStops {
+    fn default() -> Self {
+        Self::every(64.0)
+    }
+}
+
+/// Break points of words for hyphenating wrapped text, as no dictionary
+/// for any language comes built in. Words are matched ignoring case.
+#[derive(Clone, Debug, Default)]
+pub struct Hyphenation {
+    /// Lowercase words and the char indexes they can break before.
+    words: HashMap<String, Vec<usize>>,
+}
+
+impl Hyphenation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a word with its break points marked by hyphens, like
+    /// "hy-phen-a-tion".
+    pub fn add(&mut self, marked: &str) {
+        let mut word = String::new();
+        let mut breaks = Vec::new();
+
+        for c in marked.trim().chars() {
+            if c == '-' {
+                breaks.push(word.chars().count());
+            } else {
+                word.extend(c.to_lowercase());
+            }
+        }
+
+        if !word.is_empty() && !breaks.is_empty() {
+            self.words.insert(word, breaks);
+        }
+    }
+
+    /// One marked word per line. Blank lines and lines starting with #
+    /// are skipped.
+    pub fn from_list(list: &str) -> Self {
+        let mut hyphenation = Self::new();
+
+        list.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .for_each(|line| hyphenation.add(line));
+
+        hyphenation
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, AscendingError> {
+        Ok(Self::from_list(&std::fs::read_to_string(path)?))
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Puts soft hyphens at the break points of the words it knows, which
+    /// wrapping may break at.
+    pub fn insert_soft_hyphens(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut word = String::new();
+
+        for c in text.chars().chain(std::iter::once(' ')) {
+            if c.is_alphabetic() {
+                word.push(c);
+                continue;
+            }
+
+            self.push_word(&mut out, &word);
+            word.clear();
+            out.push(c);
+        }
+
+        // The space chained on to end the last word.
+        out.pop();
+        out
+    }
+
+    fn push_word(&self, out: &mut String, word: &str) {
+        let breaks = self.words.get(&word.to_lowercase());
+
+        for (i, c) in word.chars().enumerate() {
+            if breaks.is_some_and(|breaks| breaks.contains(&i)) {
+                out.push(SOFT_HYPHEN);
+            }
+
+            out.push(c);
+        }
+    }
+}
+
+/// Options for [`Text::set_text_layout`].
+#[derive(Clone, Debug, Default)]
+pub struct TextLayout {
+    pub align: TextAlign,
+    /// Tabs are kept as is without stops.
+    pub tabs: Option<TabStops>,
+    pub hyphenation: Option<Arc<Hyphenation>>,
+}
+
+/// Width of content on one line, trailing spaces included.
+fn line_width(
+    font_sys: &mut FontSystem,
+    content: &str,
+    attrs: Attrs,
+    metrics: Metrics,
+) -> f32 {
+    // A closing glyph keeps trailing spaces from being dropped.
+    let with_end = format!("{content}|");
+
+    measure_text(font_sys, &with_end, attrs, metrics, None)
+        .size
+        .x
+        - measure_text(font_sys, "|", attrs, metrics, None).size.x
+}
+
+/// Replaces the tabs of line with spaces reaching the next stops.
+fn expand_tabs(
+    font_sys: &mut FontSystem,
+    line: &str,
+    attrs: Attrs,
+    metrics: Metrics,
+    tabs: &TabStops,
+) -> String {
+    if !line.contains('\t') {
+        return line.to_owned();
+    }
+
+    let space =
+        line_width(font_sys, " ", attrs, metrics).max(metrics.font_size * 0.25);
+    let mut out = String::with_capacity(line.len());
+
+    for (i, segment) in line.split('\t').enumerate() {
+        if i > 0 {
+            let x = line_width(font_sys, &out, attrs, metrics);
+            let count = ((tabs.next(x) - x) / space).round().max(1.0);
+
+            out.extend(std::iter::repeat(' ').take(count as usize));
+        }
+
+        out.push_str(segment);
+    }
+
+    out
+}
+
+/// Byte positions, per buffer line, of soft hyphens lines were wrapped at.
+fn hyphen_breaks(buffer: &Buffer) -> Vec<(usize, usize)> {
+    let runs: Vec<(usize, &str, usize)> = buffer
+        .layout_runs()
+        .map(|run| {
+            let end = run.glyphs.iter().map(|glyph| glyph.end).max();
+
+            (run.line_i, run.text, end.unwrap_or(0))
+        })
+        .collect();
+    let mut breaks = Vec::new();
+
+    for (i, (line, text, end)) in runs.iter().enumerate() {
+        // The last run of a paragraph did not wrap.
+        if runs.get(i + 1).map(|next| next.0) != Some(*line) {
+            continue;
+        }
+
+        // The soft hyphen ends the run or starts the one after.
+        if text[..*end].ends_with(SOFT_HYPHEN) {
+            breaks.push((*line, end - SOFT_HYPHEN.len_utf8()));
+        } else if text[*end..].starts_with(SOFT_HYPHEN) {
+            breaks.push((*line, *end));
+        }
+    }
+
+    breaks
+}
+
+impl Text {
+    /// Aligns every line, including after later [`Text::set_text`] calls.
+    pub fn set_align(&mut self, align: TextAlign) -> &mut Self {
+        self.align = align;
+        self.apply_align();
+        self
+    }
+
+    pub(crate) fn apply_align(&mut self) {
+        let align = self.align.to_cosmic();
+
+        self.shaped = None;
+
+        for line in &mut self.buffer.lines {
+            line.set_align(align);
+        }
+
+        self.buffer.set_redraw(true);
+        self.changed = true;
+    }
+
+    /// Vertical text does not wrap by width, so this also sets the wrap
+    /// to none for it and back to word for horizontal.
+    pub fn set_direction(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        direction: TextDirection,
+    ) -> &mut Self {
+        if self.direction != direction {
+            self.direction = direction;
+            self.set_wrap(
+                renderer,
+                match direction {
+                    TextDirection::Horizontal => Wrap::Word,
+                    TextDirection::Vertical => Wrap::None,
+                },
+            );
+            self.changed = true;
+        }
+
+        self
+    }
+
+    /// Sets the text with layout's alignment, tab stops and hyphenation.
+    /// Wrapping breaks lines by the Unicode line breaking rules, UAX #14,
+    /// and with hyphenation also within the words it knows, adding a
+    /// hyphen where it does. Set the size, metrics and wrap first.
+    pub fn set_text_layout(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        content: &str,
+        attrs: Attrs,
+        layout: &TextLayout,
+    ) -> &mut Self {
+        let metrics = self.buffer.metrics();
+        let mut text = match &layout.tabs {
+            Some(tabs) => content
+                .split('\n')
+                .map(|line| {
+                    expand_tabs(
+                        &mut renderer.font_sys,
+                        line,
+                        attrs,
+                        metrics,
+                        tabs,
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => content.to_owned(),
+        };
+
+        if let Some(hyphenation) = &layout.hyphenation {
+            text = hyphenation.insert_soft_hyphens(&text);
+        }
+
+        self.align = layout.align;
+        self.set_text(renderer, &text, attrs);
+
+        if layout.hyphenation.is_none() {
+            return self;
+        }
+
+        for _ in 0..HYPHEN_PASSES {
+            self.buffer.shape_until(&mut renderer.font_sys, i32::MAX);
+
+            let breaks = hyphen_breaks(&self.buffer);
+
+            if breaks.is_empty() {
+                break;
+            }
+
+            let mut lines: Vec<String> =
+                text.split('\n').map(str::to_owned).collect();
+
+            // Back to front so earlier positions stay valid.
+            for (line, pos) in breaks.into_iter().rev() {
+                if let Some(line) = lines.get_mut(line) {
+                    line.replace_range(pos..pos + SOFT_HYPHEN.len_utf8(), "-");
+                }
+            }
+
+            text = lines.join("\n");
+            self.set_text(renderer, &text, attrs);
+        }
+
+        self
+    }
+}
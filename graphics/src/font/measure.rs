@@ -0,0 +1,91 @@
+use crate::{GpuRenderer, Text, Vec2};
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping, Wrap};
+
+/// One laid out line. y values are from the top of the text, going down.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct LineMetrics {
+    pub width: f32,
+    pub top: f32,
+    pub baseline: f32,
+    pub height: f32,
+}
+
+/// Size of a text and its wrapped lines.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextMeasurement {
+    pub size: Vec2,
+    pub lines: Vec<LineMetrics>,
+}
+
+impl TextMeasurement {
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Measures what was laid out in a buffer, only the shaped lines count.
+    pub fn from_buffer(buffer: &Buffer) -> Self {
+        let height = buffer.metrics().line_height;
+        let lines: Vec<LineMetrics> = buffer
+            .layout_runs()
+            .map(|run| LineMetrics {
+                width: run.line_w,
+                top: run.line_top,
+                baseline: run.line_y,
+                height,
+            })
+            .collect();
+
+        let width = lines.iter().fold(0.0f32, |w, line| w.max(line.width));
+        let bottom = lines.last().map_or(0.0, |line| line.top + line.height);
+
+        Self {
+            size: Vec2::new(width, bottom),
+            lines,
+        }
+    }
+}
+
+/// Lays out content without making any GPU resources, to size widgets
+/// before they are drawn. Lines wrap at max_width when given, at words
+/// like [`Text`] does by default.
+pub fn measure_text(
+    font_sys: &mut FontSystem,
+    content: &str,
+    attrs: Attrs,
+    metrics: Metrics,
+    max_width: Option<f32>,
+) -> TextMeasurement {
+    let mut buffer = Buffer::new(font_sys, metrics);
+    let wrap = if max_width.is_some() {
+        Wrap::Word
+    } else {
+        Wrap::None
+    };
+
+    buffer.set_wrap(font_sys, wrap);
+    buffer.set_size(font_sys, max_width.unwrap_or(f32::MAX), f32::MAX);
+    buffer.set_text(font_sys, content, attrs, Shaping::Advanced);
+    buffer.shape_until_scroll(font_sys);
+
+    TextMeasurement::from_buffer(&buffer)
+}
+
+impl GpuRenderer {
+    /// See [`measure_text`].
+    pub fn measure_text(
+        &mut self,
+        content: &str,
+        attrs: Attrs,
+        metrics: Metrics,
+        max_width: Option<f32>,
+    ) -> TextMeasurement {
+        measure_text(&mut self.font_sys, content, attrs, metrics, max_width)
+    }
+}
+
+impl Text {
+    /// Size of the text as currently shaped.
+    pub fn measure(&self) -> TextMeasurement {
+        TextMeasurement::from_buffer(&self.buffer)
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    BufferLayout, GpuDevice, LayoutStorage, PipeLineLayout, StaticBufferObject,
-    SystemLayout, TextVertex, TextureLayout,
+    shaders, BufferLayout, GpuDevice, LayoutStorage, PipeLineLayout,
+    StaticBufferObject, SystemLayout, TextVertex, TextureLayout,
 };
 use bytemuck::{Pod, Zeroable};
 
@@ -18,9 +18,7 @@ impl PipeLineLayout for TextRenderPipeline {
         let shader = gpu_device.device().create_shader_module(
             wgpu::ShaderModuleDescriptor {
                 label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("../shaders/textshader.wgsl").into(),
-                ),
+                source: wgpu::ShaderSource::Wgsl(shaders::TEXT_SHADER.into()),
             },
         );
 
@@ -72,11 +70,11 @@ impl PipeLineLayout for TextRenderPipeline {
                 depth_stencil: Some(wgpu::DepthStencilState {
                     format: wgpu::TextureFormat::Depth32Float,
                     depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::LessEqual,
+                    depth_compare: gpu_device.depth_compare(),
                     stencil: wgpu::StencilState::default(),
                     bias: wgpu::DepthBiasState::default(),
                 }),
-                multisample: wgpu::MultisampleState::default(),
+                multisample: gpu_device.multisample_state(),
                 fragment: Some(wgpu::FragmentState {
                     module: &shader,
                     entry_point: "fragment",
@@ -1,13 +1,32 @@
 use crate::{
-    AsBufferPass, AscendingError, AtlasGroup, GpuRenderer, InstanceBuffer,
-    OrderedIndex, SetBuffers, StaticBufferObject, Text, TextRenderPipeline,
-    TextVertex, Vec2,
+    Allocation, AsBufferPass, AscendingError, AtlasGroup, DrawCache,
+    GpuRenderer, InstanceBuffer, OrderedIndex, RenderCommands, SetBuffers,
+    SortPolicy, StaticBufferObject, Text, TextRenderPipeline, TextVertex, Vec2,
 };
 use cosmic_text::{CacheKey, SwashCache};
+use std::sync::{Arc, Weak};
+
+/// A glyph with space in the atlas waiting for its bitmap to be uploaded.
+struct PendingGlyph {
+    key: CacheKey,
+    is_color: bool,
+    allocation: Allocation<Vec2>,
+    bitmap: Vec<u8>,
+}
+
+/// Glyphs a text's quads point into, with if each is in the emoji atlas.
+/// The atlas keeps them while the text holds this.
+pub(crate) struct GlyphRefs {
+    keys: Vec<(CacheKey, bool)>,
+}
 
 pub struct TextAtlas {
     pub(crate) text: AtlasGroup<CacheKey, Vec2>,
     pub(crate) emoji: AtlasGroup<CacheKey, Vec2>,
+    batch_uploads: bool,
+    pending: Vec<PendingGlyph>,
+    /// Glyphs of texts, dropped with the texts.
+    refs: Vec<Weak<GlyphRefs>>,
 }
 
 impl TextAtlas {
@@ -18,12 +37,177 @@ impl TextAtlas {
                 renderer,
                 wgpu::TextureFormat::Rgba8UnormSrgb,
             ),
+            batch_uploads: false,
+            pending: Vec::new(),
+            refs: Vec::new(),
         })
     }
 
+    /// Lets glyphs no live text uses be evicted to make room for new ones.
+    /// Glyphs of live texts count as used, even for texts not updated
+    /// since, and stay until the texts are changed or dropped.
     pub fn trim(&mut self) {
         self.emoji.trim();
         self.text.trim();
+
+        self.refs.retain(|refs| {
+            let Some(refs) = refs.upgrade() else {
+                return false;
+            };
+
+            for (key, is_color) in &refs.keys {
+                let atlas = if *is_color {
+                    &mut self.emoji.atlas
+                } else {
+                    &mut self.text.atlas
+                };
+
+                // Promoted past the glyphs free to evict.
+                if atlas.contains(key) {
+                    atlas.promote(*key);
+                }
+            }
+
+            true
+        });
+    }
+
+    /// Texts whose glyphs are kept from eviction, counted at the last trim.
+    pub fn live_texts(&self) -> usize {
+        self.refs.len()
+    }
+
+    pub(crate) fn track(
+        &mut self,
+        keys: Vec<(CacheKey, bool)>,
+    ) -> Arc<GlyphRefs> {
+        let refs = Arc::new(GlyphRefs { keys });
+
+        self.refs.push(Arc::downgrade(&refs));
+        refs
+    }
+
+    /// Evictions of the text and emoji atlases.
+    pub(crate) fn evictions(&self) -> (u64, u64) {
+        (self.text.atlas.evictions(), self.emoji.atlas.evictions())
+    }
+
+    /// Repacks of the text and emoji atlases.
+    pub(crate) fn generations(&self) -> (u32, u32) {
+        (self.text.atlas.generation(), self.emoji.atlas.generation())
+    }
+
+    /// Repacks both glyph atlases, see [`crate::Atlas::repack`]. Pending
+    /// glyphs are flushed first. Texts rebuild their quads on their next
+    /// update. Returns false if either no longer fit, that one is left
+    /// as it was.
+    pub fn repack(&mut self, renderer: &mut GpuRenderer) -> bool {
+        self.flush(renderer);
+
+        let text = self.text.repack(renderer);
+
+        self.emoji.repack(renderer) && text
+    }
+
+    /// If none of the glyphs was evicted.
+    pub(crate) fn has_glyphs(&mut self, refs: &GlyphRefs) -> bool {
+        refs.keys.iter().all(|(key, is_color)| {
+            if *is_color {
+                self.emoji.atlas.contains(key)
+            } else {
+                self.text.atlas.contains(key)
+            }
+        })
+    }
+
+    /// Holds new glyphs until [`TextAtlas::flush`] uploads them together,
+    /// instead of writing each as it is rasterized. Call flush once per
+    /// frame after the texts update and before rendering. Turning it off
+    /// leaves anything pending for the next flush.
+    pub fn set_batch_uploads(&mut self, batch: bool) -> &mut Self {
+        self.batch_uploads = batch;
+        self
+    }
+
+    pub fn batch_uploads(&self) -> bool {
+        self.batch_uploads
+    }
+
+    /// Glyphs waiting for the next flush.
+    pub fn pending_glyphs(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Uploads the pending glyphs, one staging upload per atlas.
+    pub fn flush(&mut self, renderer: &GpuRenderer) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let pending = std::mem::take(&mut self.pending);
+
+        for is_color in [false, true] {
+            let group = if is_color {
+                &mut self.emoji
+            } else {
+                &mut self.text
+            };
+            // Glyphs evicted since they were reserved lost their space.
+            let uploads: Vec<(&[u8], Allocation<Vec2>)> = pending
+                .iter()
+                .filter(|glyph| {
+                    glyph.is_color == is_color
+                        && group.atlas.get(&glyph.key).is_some_and(|current| {
+                            current.layer == glyph.allocation.layer
+                                && current.rect() == glyph.allocation.rect()
+                        })
+                })
+                .map(|glyph| (glyph.bitmap.as_slice(), glyph.allocation))
+                .collect();
+
+            group.atlas.upload_allocations(&uploads, renderer);
+        }
+    }
+
+    /// Space for a new glyph, with its bitmap uploaded now or held for
+    /// the next flush.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn add_glyph(
+        &mut self,
+        key: CacheKey,
+        is_color: bool,
+        bitmap: Vec<u8>,
+        width: u32,
+        height: u32,
+        placement: Vec2,
+        renderer: &GpuRenderer,
+    ) -> Result<Allocation<Vec2>, AscendingError> {
+        let group = if is_color {
+            &mut self.emoji
+        } else {
+            &mut self.text
+        };
+
+        if !self.batch_uploads {
+            return group
+                .atlas
+                .upload(key, &bitmap, width, height, placement, renderer)
+                .ok_or(AscendingError::AtlasFull);
+        }
+
+        let allocation = group
+            .atlas
+            .reserve(key, width, height, placement, renderer)
+            .ok_or(AscendingError::AtlasFull)?;
+
+        self.pending.push(PendingGlyph {
+            key,
+            is_color,
+            allocation,
+            bitmap,
+        });
+
+        Ok(allocation)
     }
 }
 
@@ -48,10 +232,38 @@ impl TextRenderer {
         self.buffer.add_buffer_store(renderer, index);
     }
 
+    /// See [`InstanceBuffer::set_sort_policy`].
+    pub fn set_sort_policy(&mut self, policy: SortPolicy) {
+        self.buffer.set_sort_policy(policy);
+    }
+
+    pub fn set_layer_sort_policy(&mut self, layer: u32, policy: SortPolicy) {
+        self.buffer.set_layer_sort_policy(layer, policy);
+    }
+
     pub fn finalize(&mut self, renderer: &mut GpuRenderer) {
         self.buffer.finalize(renderer)
     }
 
+    /// See [`crate::RectRenderer::cached_update`]. A failed draw leaves
+    /// cache dirty.
+    pub fn cached_update(
+        &mut self,
+        cache: &mut DrawCache,
+        renderer: &mut GpuRenderer,
+        draw: impl FnOnce(&mut Self, &mut GpuRenderer) -> Result<(), AscendingError>,
+    ) -> Result<(), AscendingError> {
+        if self.buffer.add_cached(renderer, cache) {
+            return Ok(());
+        }
+
+        let start = self.buffer.added();
+
+        draw(self, renderer)?;
+        self.buffer.record_cache(start, cache);
+        Ok(())
+    }
+
     pub fn text_update(
         &mut self,
         text: &mut Text,
@@ -77,9 +289,10 @@ where
     );
 }
 
-impl<'a, 'b> RenderText<'a, 'b> for wgpu::RenderPass<'a>
+impl<'a, 'b, P> RenderText<'a, 'b> for P
 where
     'b: 'a,
+    P: RenderCommands<'a>,
 {
     fn render_text(
         &mut self,
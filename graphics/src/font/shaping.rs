@@ -0,0 +1,200 @@
+use crate::{GpuRenderer, Text, TextAlign};
+use cosmic_text::{
+    Attrs, AttrsList, AttrsOwned, BidiParagraphs, BufferLine, LayoutGlyph,
+    LayoutRun, Shaping, Wrap,
+};
+use lru::LruCache;
+use std::{num::NonZeroUsize, sync::Arc};
+
+/// A laid out line of a text, owned so it outlives the buffer it came
+/// from. cosmic-text's lines and glyphs can't be cloned or put back into a
+/// buffer, so cached texts draw from these instead.
+pub(crate) struct ShapedRun {
+    pub line_i: usize,
+    pub line_y: f32,
+    pub glyphs: Vec<LayoutGlyph>,
+}
+
+impl ShapedRun {
+    pub fn from_runs<'a>(
+        runs: impl Iterator<Item = LayoutRun<'a>>,
+    ) -> Arc<[ShapedRun]> {
+        runs.map(|run| ShapedRun {
+            line_i: run.line_i,
+            line_y: run.line_y,
+            glyphs: run.glyphs.iter().map(copy_glyph).collect(),
+        })
+        .collect()
+    }
+}
+
+fn copy_glyph(glyph: &LayoutGlyph) -> LayoutGlyph {
+    LayoutGlyph {
+        start: glyph.start,
+        end: glyph.end,
+        font_size: glyph.font_size,
+        font_id: glyph.font_id,
+        glyph_id: glyph.glyph_id,
+        x: glyph.x,
+        y: glyph.y,
+        w: glyph.w,
+        level: glyph.level,
+        x_offset: glyph.x_offset,
+        y_offset: glyph.y_offset,
+        color_opt: glyph.color_opt,
+        metadata: glyph.metadata,
+    }
+}
+
+/// What a text's shaping and layout depend on.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct ShapeKey {
+    content: String,
+    attrs: AttrsOwned,
+    /// Bits of the font size and line height.
+    metrics: (u32, u32),
+    /// Bits of the wrap width and height.
+    size: (u32, u32),
+    wrap: u8,
+    align: TextAlign,
+}
+
+impl ShapeKey {
+    fn new(text: &Text, content: &str, attrs: Attrs) -> Self {
+        let metrics = text.buffer.metrics();
+
+        Self {
+            content: content.to_owned(),
+            attrs: AttrsOwned::new(attrs),
+            metrics: (
+                metrics.font_size.to_bits(),
+                metrics.line_height.to_bits(),
+            ),
+            size: (
+                text.buffer.size().0.to_bits(),
+                text.buffer.size().1.to_bits(),
+            ),
+            wrap: match text.wrap {
+                Wrap::None => 0,
+                Wrap::Glyph => 1,
+                Wrap::Word => 2,
+            },
+            align: text.align,
+        }
+    }
+}
+
+/// Shaped and laid out lines of texts, keyed by content, attributes, font
+/// size, size, wrap and alignment, so texts set to the same thing again, like labels
+/// rebuilt every frame, skip shaping. The least recently used are evicted
+/// past the capacity.
+///
+/// Clear it after fonts are added or removed, as the cached lines were
+/// shaped with the fonts there were.
+pub struct ShapingCache {
+    lines: LruCache<ShapeKey, Arc<[ShapedRun]>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl ShapingCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: LruCache::new(
+                NonZeroUsize::new(capacity.max(1)).unwrap_or(NonZeroUsize::MIN),
+            ),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Drops every entry of content, whatever its style.
+    pub fn invalidate(&mut self, content: &str) {
+        let keys: Vec<ShapeKey> = self
+            .lines
+            .iter()
+            .filter(|(key, _)| key.content == content)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in keys {
+            self.lines.pop(&key);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Sets of lines reused and shaped since it was made.
+    pub fn stats(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+}
+
+impl Default for ShapingCache {
+    fn default() -> Self {
+        Self::new(512)
+    }
+}
+
+impl Text {
+    /// Like [`Text::set_text`] but takes the shaped lines from cache when
+    /// the same content and style was shaped at this size and wrap before,
+    /// and caches them otherwise. Set the size, metrics, wrap and alignment
+    /// first. On a hit the buffer has the text but no layout of its own.
+    pub fn set_text_cached(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        cache: &mut ShapingCache,
+        content: &str,
+        attrs: Attrs,
+    ) -> &mut Self {
+        let key = ShapeKey::new(self, content, attrs);
+
+        if let Some(runs) = cache.lines.get(&key) {
+            // Buffer::set_text shapes, so the lines are made unshaped the
+            // same way it splits them. The cached runs are drawn instead
+            // until something changes the layout.
+            let attrs_list = AttrsList::new(attrs);
+
+            self.buffer.lines = BidiParagraphs::new(content)
+                .map(|line| {
+                    BufferLine::new(line, attrs_list.clone(), Shaping::Advanced)
+                })
+                .collect();
+
+            if self.buffer.lines.is_empty() {
+                self.buffer.lines.push(BufferLine::new(
+                    "",
+                    attrs_list,
+                    Shaping::Advanced,
+                ));
+            }
+
+            self.buffer.set_scroll(0);
+            self.apply_align();
+            self.shaped = Some(runs.clone());
+            cache.hits += 1;
+            return self;
+        }
+
+        // Shapes up to the scroll, which it resets like the cached lines.
+        self.set_text(renderer, content, attrs);
+
+        let runs = ShapedRun::from_runs(self.buffer.layout_runs());
+
+        cache.lines.put(key, runs.clone());
+        self.shaped = Some(runs);
+        cache.misses += 1;
+        self
+    }
+}
@@ -1,10 +1,58 @@
 use crate::{
-    AscendingError, Bounds, Color, DrawOrder, GpuRenderer, Index, OrderedIndex,
-    TextAtlas, TextVertex, Vec2, Vec3,
+    AscendingError, Bounds, Color, CoordinateSystem, DrawLayer, DrawOrder,
+    GlyphInfo, GlyphRefs, GlyphTransform, GlyphTransformFn, GpuRenderer, Index,
+    OrderedIndex, ShapedRun, TextAlign, TextAtlas, TextDirection, TextVertex,
+    Vec2, Vec3, VerticalPen,
 };
 use cosmic_text::{
-    Attrs, Buffer, Cursor, Metrics, SwashCache, SwashContent, Wrap,
+    Attrs, Buffer, Cursor, LayoutGlyph, Metrics, PhysicalGlyph, SubpixelBin,
+    SwashCache, SwashContent, Wrap,
 };
+use std::{collections::HashSet, sync::Arc};
+
+/// Which fractions of a pixel glyphs are positioned at. Each phase a glyph
+/// is drawn at is rasterized and cached on its own, so finer positioning
+/// takes more atlas space for smoother spacing.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SubpixelMode {
+    /// Glyphs snap to whole pixels, the crispest at small sizes.
+    Off,
+    /// Four phases across, whole pixels down. Even spacing while rows stay
+    /// sharp.
+    Horizontal,
+    /// Four phases along both axes.
+    #[default]
+    Full,
+}
+
+impl SubpixelMode {
+    fn physical(
+        self,
+        glyph: &LayoutGlyph,
+        offset: (f32, f32),
+    ) -> PhysicalGlyph {
+        let mut physical = glyph.physical(offset, 1.0);
+
+        if self != SubpixelMode::Full {
+            snap(&mut physical.y, &mut physical.cache_key.y_bin);
+        }
+
+        if self == SubpixelMode::Off {
+            snap(&mut physical.x, &mut physical.cache_key.x_bin);
+        }
+
+        physical
+    }
+}
+
+/// Rounds to the nearest whole pixel.
+fn snap(pos: &mut i32, bin: &mut SubpixelBin) {
+    if matches!(bin, SubpixelBin::Two | SubpixelBin::Three) {
+        *pos += 1;
+    }
+
+    *bin = SubpixelBin::Zero;
+}
 
 pub struct Text {
     pub buffer: Buffer,
@@ -15,6 +63,9 @@ pub struct Text {
     pub bounds: Option<Bounds>,
     pub store_id: Index,
     pub order: DrawOrder,
+    /// Orders it within its layer under [`crate::SortPolicy::Key`].
+    pub sort_key: u64,
+    pub render_layer: u32,
     /// Cursor the shaping is set too.
     pub cursor: Cursor,
     /// line the shaping is set too.
@@ -23,10 +74,28 @@ pub struct Text {
     pub scroll: i32,
     /// Word Wrap Type. Default is Wrap::Word.
     pub wrap: Wrap,
+    /// Alignment of its lines. Set with [`Text::set_align`].
+    pub align: TextAlign,
+    /// Set with [`Text::set_direction`].
+    pub direction: TextDirection,
+    /// Applied to each glyph when the quads are made.
+    pub glyph_transform: Option<GlyphTransformFn>,
+    /// Given to the glyph transform.
+    pub time: f32,
     /// if the shader should render with the camera's view.
     pub use_camera: bool,
+    pub subpixel: SubpixelMode,
     /// if anything got updated we need to update the buffers too.
     pub changed: bool,
+    /// Keeps the glyphs of the quads in the atlas.
+    glyph_refs: Option<Arc<GlyphRefs>>,
+    /// Of the atlas when the quads were made.
+    evictions: (u64, u64),
+    /// Repacks of the atlas when the quads were made.
+    generations: (u32, u32),
+    /// Lines from the [`crate::ShapingCache`], drawn instead of the
+    /// buffer's until something changes its layout.
+    pub(crate) shaped: Option<Arc<[ShapedRun]>>,
 }
 
 impl Text {
@@ -39,10 +108,35 @@ impl Text {
         let count: usize =
             self.buffer.lines.iter().map(|line| line.text().len()).sum();
         let mut text_buf = Vec::with_capacity(count);
+        let runs = match &self.shaped {
+            Some(runs) => runs.clone(),
+            None => ShapedRun::from_runs(self.buffer.layout_runs()),
+        };
+        let first_baseline =
+            runs.first().map(|run| run.line_y).unwrap_or_default();
+        let mut pen =
+            VerticalPen::new(self.buffer.metrics(), first_baseline, self.size);
+        let mut index = 0;
+        let mut glyphs = HashSet::new();
+
+        for run in runs.iter() {
+            pen.start_line(run.line_i);
 
-        for run in self.buffer.layout_runs() {
             for glyph in run.glyphs.iter() {
-                let physical_glyph = glyph.physical((0., 0.), 1.0);
+                // Placed before the bitmap so spaces still take a row.
+                let (physical_glyph, line_y) = match self.direction {
+                    TextDirection::Horizontal => {
+                        (self.subpixel.physical(glyph, (0., 0.)), run.line_y)
+                    }
+                    TextDirection::Vertical => {
+                        let (x, baseline) = pen.place(glyph.w);
+
+                        (
+                            self.subpixel.physical(glyph, (x - glyph.x, 0.)),
+                            baseline,
+                        )
+                    }
+                };
 
                 let (allocation, is_color) = if let Some(allocation) =
                     atlas.text.atlas.get(&physical_glyph.cache_key)
@@ -70,46 +164,27 @@ impl Text {
                     let height = image.placement.height;
 
                     if width > 0 && height > 0 {
-                        if is_color {
-                            let allocation = atlas
-                                .emoji
-                                .atlas
-                                .upload(
-                                    physical_glyph.cache_key,
-                                    &bitmap,
-                                    width,
-                                    height,
-                                    Vec2::new(
-                                        image.placement.left as f32,
-                                        image.placement.top as f32,
-                                    ),
-                                    renderer,
-                                )
-                                .ok_or(AscendingError::AtlasFull)?;
-                            (allocation, is_color)
-                        } else {
-                            let allocation = atlas
-                                .text
-                                .atlas
-                                .upload(
-                                    physical_glyph.cache_key,
-                                    &bitmap,
-                                    width,
-                                    height,
-                                    Vec2::new(
-                                        image.placement.left as f32,
-                                        image.placement.top as f32,
-                                    ),
-                                    renderer,
-                                )
-                                .ok_or(AscendingError::AtlasFull)?;
-                            (allocation, is_color)
-                        }
+                        let allocation = atlas.add_glyph(
+                            physical_glyph.cache_key,
+                            is_color,
+                            bitmap,
+                            width,
+                            height,
+                            Vec2::new(
+                                image.placement.left as f32,
+                                image.placement.top as f32,
+                            ),
+                            renderer,
+                        )?;
+
+                        (allocation, is_color)
                     } else {
                         continue;
                     }
                 };
 
+                glyphs.insert((physical_glyph.cache_key, is_color));
+
                 let position = allocation.data;
                 let (u, v, width, height) = allocation.rect();
                 let (mut u, mut v, mut width, mut height) =
@@ -124,7 +199,7 @@ impl Text {
                         + self.offsets.y
                         + self.size.y
                         + physical_glyph.y as f32
-                        - run.line_y),
+                        - line_y),
                 );
 
                 let color = is_color
@@ -184,14 +259,33 @@ impl Text {
                     }
                 }
 
+                let transform = match &self.glyph_transform {
+                    Some(transform) => transform(&GlyphInfo {
+                        index,
+                        line: run.line_i,
+                        start: glyph.start,
+                        position: Vec2::new(x - self.pos.x, y - self.pos.y),
+                        time: self.time,
+                    }),
+                    None => GlyphTransform::default(),
+                };
+
+                index += 1;
+
                 let default = TextVertex {
-                    position: [x, y, self.pos.z],
+                    position: [
+                        x + transform.offset.x,
+                        y + transform.offset.y,
+                        self.pos.z,
+                    ],
                     hw: [width, height],
                     tex_coord: [u, v],
                     layer: allocation.layer as u32,
                     color: color.0,
                     use_camera: u32::from(self.use_camera),
                     is_color: is_color as u32,
+                    rotation: transform.rotation,
+                    scale: transform.scale,
                 };
 
                 text_buf.push(default);
@@ -203,7 +297,12 @@ impl Text {
             store.changed = true;
         }
 
-        self.order = DrawOrder::new(false, &self.pos, 1);
+        // Replacing the old refs lets the glyphs only they used go.
+        self.glyph_refs = Some(atlas.track(glyphs.into_iter().collect()));
+        self.evictions = atlas.evictions();
+        self.generations = atlas.generations();
+        self.order = DrawOrder::new(false, &self.pos, self.render_layer)
+            .with_sort_key(self.sort_key);
         self.changed = false;
         self.buffer.set_redraw(false);
         Ok(())
@@ -226,11 +325,22 @@ impl Text {
             bounds: None,
             store_id: renderer.new_buffer(),
             order: DrawOrder::new(false, &pos, 1),
+            sort_key: 0,
+            render_layer: 1,
             changed: true,
             default_color: Color::rgba(0, 0, 0, 255),
             use_camera: false,
+            subpixel: SubpixelMode::default(),
             cursor: Cursor::default(),
             wrap: Wrap::Word,
+            align: TextAlign::default(),
+            direction: TextDirection::default(),
+            glyph_transform: None,
+            time: 0.0,
+            glyph_refs: None,
+            evictions: (0, 0),
+            generations: (0, 0),
+            shaped: None,
             line: 0,
             scroll: 0,
         }
@@ -243,12 +353,18 @@ impl Text {
         text: &str,
         attrs: Attrs,
     ) -> &mut Self {
+        self.shaped = None;
         self.buffer.set_text(
             &mut renderer.font_sys,
             text,
             attrs,
             cosmic_text::Shaping::Advanced,
         );
+
+        if self.align != TextAlign::Left {
+            self.apply_align();
+        }
+
         self.changed = true;
         self
     }
@@ -256,6 +372,7 @@ impl Text {
     /// For more advanced shaping and usage. Use set_changed() to set if you need it to make changes or not.
     /// This will not set the change to true. when changes are made you must set changed to true.
     pub fn get_text_buffer(&mut self) -> &mut Buffer {
+        self.shaped = None;
         &mut self.buffer
     }
 
@@ -269,6 +386,7 @@ impl Text {
             self.cursor = cursor;
             self.line = 0;
             self.changed = true;
+            self.shaped = None;
             self.buffer
                 .shape_until_cursor(&mut renderer.font_sys, cursor);
             self.scroll = self.buffer.scroll();
@@ -287,6 +405,7 @@ impl Text {
             self.cursor = Cursor::default();
             self.line = line;
             self.changed = true;
+            self.shaped = None;
             self.buffer.shape_until(&mut renderer.font_sys, line);
         }
 
@@ -294,11 +413,12 @@ impl Text {
     }
 
     /// Does not use cursor or line but will use the last set scroll.
+    /// Cached lines are already shaped, so it does nothing for them.
     pub fn shape_until_scroll(
         &mut self,
         renderer: &mut GpuRenderer,
     ) -> &mut Self {
-        if self.changed {
+        if self.changed && self.shaped.is_none() {
             self.buffer.shape_until_scroll(&mut renderer.font_sys);
         }
 
@@ -314,6 +434,7 @@ impl Text {
             self.scroll = scroll;
             self.buffer.set_scroll(scroll);
             self.changed = true;
+            self.shaped = None;
             self.buffer.shape_until_scroll(&mut renderer.font_sys);
         }
 
@@ -334,11 +455,33 @@ impl Text {
             self.wrap = wrap;
             self.buffer.set_wrap(&mut renderer.font_sys, wrap);
             self.changed = true;
+            self.shaped = None;
         }
 
         self
     }
 
+    /// Moves the text into the layer at a depth within it, 0.0 at the
+    /// front and 1.0 at the back.
+    pub fn set_layer(&mut self, layer: DrawLayer, depth: f32) -> &mut Self {
+        self.pos.z = layer.z(depth);
+        self.render_layer = layer.index();
+        self.changed = true;
+        self
+    }
+
+    pub fn set_subpixel(&mut self, subpixel: SubpixelMode) -> &mut Self {
+        self.subpixel = subpixel;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_sort_key(&mut self, sort_key: u64) -> &mut Self {
+        self.sort_key = sort_key;
+        self.changed = true;
+        self
+    }
+
     pub fn set_bounds(&mut self, bounds: Option<Bounds>) -> &mut Self {
         self.bounds = bounds;
         self.changed = true;
@@ -351,6 +494,23 @@ impl Text {
         self
     }
 
+    /// Places the text by its box's corner given in a coordinate system,
+    /// the top left one when y is down.
+    pub fn set_position_in(
+        &mut self,
+        position: Vec3,
+        coordinate_system: CoordinateSystem,
+        screen_height: f32,
+    ) -> &mut Self {
+        let pos = coordinate_system.to_render_rect(
+            position.truncate(),
+            self.size,
+            screen_height,
+        );
+
+        self.set_position(pos.extend(position.z))
+    }
+
     pub fn set_default_color(&mut self, color: Color) -> &mut Self {
         self.default_color = color;
         self.changed = true;
@@ -375,11 +535,13 @@ impl Text {
             height as f32,
         );
         self.changed = true;
+        self.shaped = None;
         self
     }
 
     /// resets the TextRender bytes to empty for new bytes
     pub fn clear(&mut self, renderer: &mut GpuRenderer) -> &mut Self {
+        self.shaped = None;
         self.buffer.set_text(
             &mut renderer.font_sys,
             "",
@@ -398,6 +560,21 @@ impl Text {
         atlas: &mut TextAtlas,
         renderer: &mut GpuRenderer,
     ) -> Result<OrderedIndex, AscendingError> {
+        // A glyph evicted anyway, like by a clear, is rasterized again.
+        if !self.changed && self.evictions != atlas.evictions() {
+            self.evictions = atlas.evictions();
+            self.changed = self
+                .glyph_refs
+                .as_ref()
+                .is_some_and(|refs| !atlas.has_glyphs(refs));
+        }
+
+        // The glyphs moved.
+        if self.generations != atlas.generations() {
+            self.generations = atlas.generations();
+            self.changed |= self.glyph_refs.is_some();
+        }
+
         if self.changed {
             self.create_quad(cache, atlas, renderer)?;
         }
@@ -405,6 +582,11 @@ impl Text {
         Ok(OrderedIndex::new(self.order, self.store_id, 0))
     }
 
+    /// Frees the text's buffer. Use when it won't be rendered again.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        renderer.remove_buffer(self.store_id);
+    }
+
     pub fn check_mouse_bounds(&self, mouse_pos: Vec2) -> bool {
         mouse_pos[0] > self.pos.x
             && mouse_pos[0] < self.pos.x + self.size.x
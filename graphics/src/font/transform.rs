@@ -0,0 +1,87 @@
+use crate::{Text, Vec2};
+
+/// Gives each glyph's transform, like a wave for enchanted text.
+pub type GlyphTransformFn = Box<dyn Fn(&GlyphInfo) -> GlyphTransform>;
+
+/// A glyph handed to the [`GlyphTransformFn`] of its text.
+#[derive(Copy, Clone, Debug)]
+pub struct GlyphInfo {
+    /// Of the glyph among the drawn glyphs of the text, in order.
+    pub index: usize,
+    /// Paragraph the glyph is in.
+    pub line: usize,
+    /// Byte offset of the glyph's text in its paragraph.
+    pub start: usize,
+    /// Of the glyph's quad from the text's bottom left, before the
+    /// transform.
+    pub position: Vec2,
+    /// As last set with [`Text::set_time`].
+    pub time: f32,
+}
+
+/// Moves, turns and scales a glyph around its center, without changing
+/// the layout of the rest.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GlyphTransform {
+    pub offset: Vec2,
+    /// In radians, counterclockwise.
+    pub rotation: f32,
+    pub scale: f32,
+}
+
+impl Default for GlyphTransform {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            rotation: 0.0,
+            scale: 1.0,
+        }
+    }
+}
+
+impl GlyphTransform {
+    pub fn offset(offset: Vec2) -> Self {
+        Self {
+            offset,
+            ..Default::default()
+        }
+    }
+
+    pub fn rotation(rotation: f32) -> Self {
+        Self {
+            rotation,
+            ..Default::default()
+        }
+    }
+
+    pub fn scale(scale: f32) -> Self {
+        Self {
+            scale,
+            ..Default::default()
+        }
+    }
+}
+
+impl Text {
+    /// Sets the transform applied to every glyph, or clears it. Glyphs
+    /// clipped by the bounds are clipped before they are transformed.
+    pub fn set_glyph_transform(
+        &mut self,
+        transform: Option<GlyphTransformFn>,
+    ) -> &mut Self {
+        self.glyph_transform = transform;
+        self.changed = true;
+        self
+    }
+
+    /// Time the glyph transform is given, like the seconds since the text
+    /// appeared. Rebuilds the glyphs only when there is a transform.
+    pub fn set_time(&mut self, time: f32) -> &mut Self {
+        if self.time != time {
+            self.time = time;
+            self.changed |= self.glyph_transform.is_some();
+        }
+
+        self
+    }
+}
@@ -11,6 +11,10 @@ pub struct TextVertex {
     pub color: u32,
     pub use_camera: u32,
     pub is_color: u32,
+    /// Of the quad around its center, in radians.
+    pub rotation: f32,
+    /// Of the quad around its center.
+    pub scale: f32,
 }
 
 impl Default for TextVertex {
@@ -23,13 +27,15 @@ impl Default for TextVertex {
             color: 0,
             use_camera: 0,
             is_color: 0,
+            rotation: 0.0,
+            scale: 1.0,
         }
     }
 }
 
 impl BufferLayout for TextVertex {
     fn attributes() -> Vec<wgpu::VertexAttribute> {
-        wgpu::vertex_attr_array![1 => Float32x3, 2 => Float32x2, 3 => Float32x2, 4 => Uint32, 5 => Uint32, 6 => Uint32, 7 => Uint32]
+        wgpu::vertex_attr_array![1 => Float32x3, 2 => Float32x2, 3 => Float32x2, 4 => Uint32, 5 => Uint32, 6 => Uint32, 7 => Uint32, 8 => Float32, 9 => Float32]
             .to_vec()
     }
 
@@ -53,6 +59,6 @@ impl BufferLayout for TextVertex {
     }
 
     fn stride() -> usize {
-        std::mem::size_of::<[f32; 11]>()
+        std::mem::size_of::<[f32; 13]>()
     }
 }
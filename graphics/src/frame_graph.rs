@@ -0,0 +1,5 @@
+mod graph;
+mod timer;
+
+pub use graph::*;
+pub use timer::*;
@@ -0,0 +1,393 @@
+use crate::{AscendingError, DrawBatch, GpuRenderer, RenderStats};
+use serde::Serialize;
+use std::fmt::Write;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum FrameResourceKind {
+    Texture {
+        format: String,
+        width: u32,
+        height: u32,
+        samples: u32,
+    },
+    Buffer {
+        bytes: u64,
+        usage: String,
+    },
+}
+
+/// A texture or buffer passes read or write.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FrameResource {
+    pub name: String,
+    pub kind: FrameResourceKind,
+}
+
+impl FrameResource {
+    pub fn texture(name: impl Into<String>, texture: &wgpu::Texture) -> Self {
+        Self {
+            name: name.into(),
+            kind: FrameResourceKind::Texture {
+                format: format!("{:?}", texture.format()),
+                width: texture.width(),
+                height: texture.height(),
+                samples: texture.sample_count(),
+            },
+        }
+    }
+
+    pub fn buffer(name: impl Into<String>, buffer: &wgpu::Buffer) -> Self {
+        Self {
+            name: name.into(),
+            kind: FrameResourceKind::Buffer {
+                bytes: buffer.size(),
+                usage: format!("{:?}", buffer.usage()),
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum ResourceAccess {
+    Read,
+    Write,
+    /// Cleared then written, so it doesn't depend on earlier passes.
+    Clear,
+}
+
+/// Draws made with one pipeline in a pass.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PipelineDraws {
+    pub pipeline: String,
+    pub draws: u32,
+    pub instances: u32,
+}
+
+/// A render or compute pass and what it touches.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct FramePass {
+    pub name: String,
+    /// Resources by name, in the order given.
+    pub access: Vec<(String, ResourceAccess)>,
+    pub pipelines: Vec<PipelineDraws>,
+    /// Vertex and index buffers the draws bound.
+    pub buffers: u32,
+    pub stats: Option<RenderStats>,
+    /// Milliseconds the GPU spent on it, see [`crate::PassTimer`].
+    pub gpu_ms: Option<f32>,
+}
+
+impl FramePass {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn reads(&mut self, resource: &str) -> &mut Self {
+        self.access
+            .push((resource.to_owned(), ResourceAccess::Read));
+        self
+    }
+
+    pub fn writes(&mut self, resource: &str) -> &mut Self {
+        self.access
+            .push((resource.to_owned(), ResourceAccess::Write));
+        self
+    }
+
+    pub fn clears(&mut self, resource: &str) -> &mut Self {
+        self.access
+            .push((resource.to_owned(), ResourceAccess::Clear));
+        self
+    }
+
+    /// Adds the batch's draws by pipeline. Call before it's submitted.
+    pub fn record_batch(
+        &mut self,
+        renderer: &GpuRenderer,
+        batch: &DrawBatch,
+    ) -> &mut Self {
+        let (pipelines, buffers) = batch.summary();
+
+        for (pipeline, draws, instances) in pipelines {
+            let name = pipeline
+                .map(|p| renderer.pipeline_name(p).unwrap_or("unknown"))
+                .unwrap_or("none");
+
+            match self.pipelines.iter_mut().find(|p| p.pipeline == name) {
+                Some(existing) => {
+                    existing.draws += draws;
+                    existing.instances += instances;
+                }
+                None => self.pipelines.push(PipelineDraws {
+                    pipeline: name.to_owned(),
+                    draws,
+                    instances,
+                }),
+            }
+        }
+
+        self.buffers += buffers;
+        self
+    }
+
+    /// What [`DrawBatch::submit`] returned for the pass.
+    pub fn set_stats(&mut self, stats: RenderStats) -> &mut Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    fn uses(&self, resource: &str, writes: bool) -> bool {
+        self.access.iter().any(|(name, access)| {
+            name == resource && (!writes || *access != ResourceAccess::Read)
+        })
+    }
+}
+
+/// Pass `to` needs what pass `from` last wrote to resource.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FrameDependency {
+    pub from: usize,
+    pub to: usize,
+    pub resource: String,
+}
+
+/// A description of what the engine did in a frame: the passes in order,
+/// the textures and buffers they touch and the dependencies between them.
+/// Filled in by the app as it renders, since passes are its own, and
+/// exported for Graphviz with [`FrameGraph::to_dot`] or as JSON.
+///
+/// ```ignore
+/// let mut graph = FrameGraph::from_renderer(&renderer);
+/// let pass = graph.add_pass("render pass");
+///
+/// pass.clears("frame").clears("depth").reads("static buffers");
+/// pass.record_batch(&renderer, &batch);
+/// std::fs::write("frame.dot", graph.to_dot())?;
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct FrameGraph {
+    pub resources: Vec<FrameResource>,
+    pub passes: Vec<FramePass>,
+}
+
+impl FrameGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// With the renderer's own targets and buffers: "frame", "msaa" when
+    /// multisampling, "depth" and "static buffers".
+    pub fn from_renderer(renderer: &GpuRenderer) -> Self {
+        let size = renderer.size();
+        let target =
+            |format: wgpu::TextureFormat, samples| FrameResourceKind::Texture {
+                format: format!("{format:?}"),
+                width: size.width as u32,
+                height: size.height as u32,
+                samples,
+            };
+        let mut graph = Self::new();
+
+        graph.add_resource(FrameResource {
+            name: "frame".into(),
+            kind: target(renderer.surface_format(), 1),
+        });
+
+        if renderer.sample_count() > 1 {
+            graph.add_resource(FrameResource {
+                name: "msaa".into(),
+                kind: target(
+                    renderer.surface_format(),
+                    renderer.sample_count(),
+                ),
+            });
+        }
+
+        graph.add_resource(FrameResource {
+            name: "depth".into(),
+            kind: target(
+                wgpu::TextureFormat::Depth32Float,
+                renderer.sample_count(),
+            ),
+        });
+
+        let statics = &renderer.buffer_object;
+
+        graph.add_resource(FrameResource {
+            name: "static buffers".into(),
+            kind: FrameResourceKind::Buffer {
+                bytes: statics.vbo.size() + statics.ibo.size(),
+                usage: format!(
+                    "{:?}",
+                    statics.vbo.usage() | statics.ibo.usage()
+                ),
+            },
+        });
+        graph
+    }
+
+    /// Replaces a resource with the same name.
+    pub fn add_resource(&mut self, resource: FrameResource) -> &mut Self {
+        match self.resources.iter_mut().find(|r| r.name == resource.name) {
+            Some(existing) => *existing = resource,
+            None => self.resources.push(resource),
+        }
+
+        self
+    }
+
+    /// Adds a pass after the ones added so far.
+    pub fn add_pass(&mut self, name: impl Into<String>) -> &mut FramePass {
+        self.passes.push(FramePass::new(name));
+
+        let last = self.passes.len() - 1;
+
+        &mut self.passes[last]
+    }
+
+    pub fn pass_mut(&mut self, name: &str) -> Option<&mut FramePass> {
+        self.passes.iter_mut().find(|pass| pass.name == name)
+    }
+
+    /// Sets the GPU times of the passes by name, as
+    /// [`crate::PassTimer::read`] returns them.
+    pub fn set_gpu_times(&mut self, times: &[(String, f32)]) {
+        for (name, ms) in times {
+            if let Some(pass) = self.pass_mut(name) {
+                pass.gpu_ms = Some(*ms);
+            }
+        }
+    }
+
+    /// Each pass's link to the last earlier pass that wrote a resource it
+    /// reads or writes, unless it clears it.
+    pub fn dependencies(&self) -> Vec<FrameDependency> {
+        let mut dependencies = Vec::new();
+
+        for (to, pass) in self.passes.iter().enumerate() {
+            for (resource, access) in &pass.access {
+                if *access == ResourceAccess::Clear {
+                    continue;
+                }
+
+                let from = self.passes[..to]
+                    .iter()
+                    .rposition(|earlier| earlier.uses(resource, true));
+                let dependency = from.map(|from| FrameDependency {
+                    from,
+                    to,
+                    resource: resource.clone(),
+                });
+
+                if let Some(dependency) =
+                    dependency.filter(|d| !dependencies.contains(d))
+                {
+                    dependencies.push(dependency);
+                }
+            }
+        }
+
+        dependencies
+    }
+
+    /// Graphviz source with passes as boxes in order, resources as
+    /// ellipses and an edge for each read and write.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph frame {\n    rankdir=LR;\n");
+        let quote =
+            |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+
+        for resource in &self.resources {
+            let detail = match &resource.kind {
+                FrameResourceKind::Texture {
+                    format,
+                    width,
+                    height,
+                    samples,
+                } => format!("{format} {width}x{height} x{samples}"),
+                FrameResourceKind::Buffer { bytes, .. } => {
+                    format!("{bytes} bytes")
+                }
+            };
+
+            let _ = writeln!(
+                dot,
+                "    \"res:{0}\" [shape=ellipse, label=\"{0}\\n{1}\"];",
+                quote(&resource.name),
+                quote(&detail),
+            );
+        }
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            let mut label = quote(&pass.name);
+
+            if let Some(ms) = pass.gpu_ms {
+                let _ = write!(label, "\\n{ms:.3} ms");
+            }
+
+            for draws in &pass.pipelines {
+                let _ = write!(
+                    label,
+                    "\\n{}: {} draws, {} instances",
+                    quote(&draws.pipeline),
+                    draws.draws,
+                    draws.instances
+                );
+            }
+
+            let _ = writeln!(
+                dot,
+                "    \"pass:{index}\" [shape=box, label=\"{label}\"];"
+            );
+
+            for (resource, access) in &pass.access {
+                let resource = quote(resource);
+                let _ = match access {
+                    ResourceAccess::Read => writeln!(
+                        dot,
+                        "    \"res:{resource}\" -> \"pass:{index}\";"
+                    ),
+                    ResourceAccess::Write => writeln!(
+                        dot,
+                        "    \"pass:{index}\" -> \"res:{resource}\";"
+                    ),
+                    ResourceAccess::Clear => writeln!(
+                        dot,
+                        "    \"pass:{index}\" -> \"res:{resource}\" [label=\"clear\"];"
+                    ),
+                };
+            }
+        }
+
+        // keeps passes in submission order.
+        for index in 1..self.passes.len() {
+            let _ = writeln!(
+                dot,
+                "    \"pass:{}\" -> \"pass:{index}\" [style=invis];",
+                index - 1
+            );
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The resources, passes and [`FrameGraph::dependencies`] as JSON.
+    pub fn to_json(&self) -> Result<String, AscendingError> {
+        #[derive(Serialize)]
+        struct Export<'a> {
+            resources: &'a [FrameResource],
+            passes: &'a [FramePass],
+            dependencies: Vec<FrameDependency>,
+        }
+
+        Ok(serde_json::to_string_pretty(&Export {
+            resources: &self.resources,
+            passes: &self.passes,
+            dependencies: self.dependencies(),
+        })?)
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{AscendingError, GpuRenderer};
+use std::sync::mpsc;
+
+/// Times render passes on the GPU with timestamp queries, for
+/// [`crate::FrameGraph::set_gpu_times`]. Needs the renderer built with
+/// [`wgpu::Features::TIMESTAMP_QUERY`], see
+/// [`crate::RendererBuilder::with_features`].
+///
+/// Each frame, put [`PassTimer::timestamp_writes`] in the descriptor of
+/// the passes to time, [`PassTimer::resolve`] into the same encoder after
+/// the last of them and [`PassTimer::read`] once it's submitted.
+pub struct PassTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback: wgpu::Buffer,
+    /// Passes it can time per frame.
+    capacity: u32,
+    /// Passes timed this frame, in query order.
+    passes: Vec<String>,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+}
+
+impl PassTimer {
+    /// None if the device can't write timestamps.
+    pub fn new(renderer: &GpuRenderer, capacity: u32) -> Option<Self> {
+        let device = renderer.device();
+
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let count = capacity.max(1) * 2;
+        let bytes = count as u64 * std::mem::size_of::<u64>() as u64;
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Pass Timer Queries"),
+            ty: wgpu::QueryType::Timestamp,
+            count,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pass Timer Resolve Buffer"),
+            size: bytes,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE
+                | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pass Timer Readback Buffer"),
+            size: bytes,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback,
+            capacity: capacity.max(1),
+            passes: Vec::new(),
+            period: renderer.queue().get_timestamp_period(),
+        })
+    }
+
+    /// Timestamp writes for the pass called name. None once as many passes
+    /// as it has room for are timed this frame.
+    pub fn timestamp_writes(
+        &mut self,
+        name: &str,
+    ) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        let index = self.passes.len() as u32;
+
+        if index >= self.capacity {
+            return None;
+        }
+
+        self.passes.push(name.to_owned());
+
+        Some(wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(index * 2),
+            end_of_pass_write_index: Some(index * 2 + 1),
+        })
+    }
+
+    /// Copies this frame's timestamps to where [`PassTimer::read`] reads
+    /// them.
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        let count = self.passes.len() as u32 * 2;
+
+        if count == 0 {
+            return;
+        }
+
+        encoder.resolve_query_set(
+            &self.query_set,
+            0..count,
+            &self.resolve_buffer,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback,
+            0,
+            count as u64 * std::mem::size_of::<u64>() as u64,
+        );
+    }
+
+    /// Waits for the GPU and returns each timed pass's name and
+    /// milliseconds, then starts over for the next frame.
+    pub fn read(
+        &mut self,
+        renderer: &GpuRenderer,
+    ) -> Result<Vec<(String, f32)>, AscendingError> {
+        let passes = std::mem::take(&mut self.passes);
+
+        if passes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let bytes = passes.len() as u64 * 2 * std::mem::size_of::<u64>() as u64;
+        let slice = self.readback.slice(..bytes);
+        let (sender, receiver) = mpsc::channel();
+
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        renderer.device().poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|_| AscendingError::ReadbackCanceled)??;
+
+        let data = slice.get_mapped_range();
+        let ticks: Vec<u64> = data
+            .chunks_exact(8)
+            .map(|chunk| {
+                u64::from_le_bytes(chunk.try_into().unwrap_or_default())
+            })
+            .collect();
+
+        drop(data);
+        self.readback.unmap();
+
+        Ok(passes
+            .into_iter()
+            .zip(ticks.chunks_exact(2))
+            .map(|(name, pair)| {
+                let ticks = pair[1].saturating_sub(pair[0]);
+
+                (name, ticks as f32 * self.period / 1_000_000.0)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,568 @@
+#[cfg(feature = "sprites")]
+use crate::Image;
+#[cfg(all(feature = "maps", feature = "text"))]
+use crate::Snapping;
+use crate::{
+    AscendingError, Color, DrawMode, GpuRenderer, Mesh2D, Mesh2DBuilder,
+    Mesh2DRenderer, Rect, Vec2, Vec3, Vec4,
+};
+
+/// Mouse distance in pixels that still counts as over a handle.
+const HIT_TOLERANCE: f32 = 6.0;
+/// Smallest scale a drag can reach, so objects never flip or vanish.
+const MIN_SCALE: f32 = 0.01;
+
+/// An object a [`Gizmo`] can move, rotate and scale.
+pub trait GizmoTarget {
+    /// Where the gizmo is drawn and what rotation and scale pivot around.
+    fn gizmo_position(&self) -> Vec2;
+
+    fn set_gizmo_position(&mut self, position: Vec2);
+
+    /// Size or scale, the scale handles multiply it.
+    fn gizmo_scale(&self) -> Vec2;
+
+    fn set_gizmo_scale(&mut self, scale: Vec2);
+
+    /// Degrees counter clockwise. Objects that can't rotate keep 0.0.
+    fn gizmo_rotation(&self) -> f32 {
+        0.0
+    }
+
+    fn set_gizmo_rotation(&mut self, _rotation: f32) {}
+}
+
+impl GizmoTarget for Rect {
+    fn gizmo_position(&self) -> Vec2 {
+        self.position.truncate()
+    }
+
+    fn set_gizmo_position(&mut self, position: Vec2) {
+        self.set_position(position.extend(self.position.z));
+    }
+
+    fn gizmo_scale(&self) -> Vec2 {
+        self.size
+    }
+
+    fn set_gizmo_scale(&mut self, scale: Vec2) {
+        self.set_size(scale);
+    }
+
+    fn gizmo_rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    fn set_gizmo_rotation(&mut self, rotation: f32) {
+        self.set_rotation(rotation);
+    }
+}
+
+#[cfg(feature = "sprites")]
+impl GizmoTarget for Image {
+    fn gizmo_position(&self) -> Vec2 {
+        self.pos.truncate()
+    }
+
+    fn set_gizmo_position(&mut self, position: Vec2) {
+        self.pos = position.extend(self.pos.z);
+        self.changed = true;
+    }
+
+    fn gizmo_scale(&self) -> Vec2 {
+        self.hw
+    }
+
+    fn set_gizmo_scale(&mut self, scale: Vec2) {
+        self.hw = scale;
+        self.changed = true;
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum GizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GizmoHandle {
+    MoveX,
+    MoveY,
+    /// The center square, moves on both axes.
+    MoveFree,
+    Rotate,
+    ScaleX,
+    ScaleY,
+    /// The center square, scales both axes evenly.
+    ScaleUniform,
+}
+
+/// Steps values round to while snapping.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GizmoSnap {
+    pub translate: f32,
+    /// Degrees.
+    pub rotate: f32,
+    pub scale: f32,
+}
+
+impl Default for GizmoSnap {
+    fn default() -> Self {
+        Self {
+            translate: 8.0,
+            rotate: 15.0,
+            scale: 0.1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GizmoEvent {
+    /// A handle was grabbed. Save the target's transform here to undo.
+    Started(GizmoHandle),
+    /// The target was changed by a drag.
+    Changed,
+    /// The handle was let go.
+    Finished,
+}
+
+#[derive(Copy, Clone)]
+struct GizmoDrag {
+    handle: GizmoHandle,
+    start_mouse: Vec2,
+    start_position: Vec2,
+    start_scale: Vec2,
+    start_rotation: f32,
+}
+
+/// Translate, rotate and scale handles for the editor, drawn as a
+/// [`Mesh2D`]. Positions and the mouse are in the space the target is in,
+/// render space unless use_camera is set, then world space.
+pub struct Gizmo {
+    mode: GizmoMode,
+    /// Length of the axis handles.
+    pub size: f32,
+    pub z: f32,
+    pub use_camera: bool,
+    pub snap: GizmoSnap,
+    pub x_color: Color,
+    pub y_color: Color,
+    pub rotate_color: Color,
+    /// Color of the handle under the mouse or being dragged.
+    pub active_color: Color,
+    pub mesh: Mesh2D,
+    position: Vec2,
+    hovered: Option<GizmoHandle>,
+    drag: Option<GizmoDrag>,
+    was_down: bool,
+    visible: bool,
+    /// if the mesh needs building again.
+    changed: bool,
+}
+
+impl Gizmo {
+    pub fn new(renderer: &mut GpuRenderer, z: f32) -> Self {
+        Self {
+            mode: GizmoMode::default(),
+            size: 60.0,
+            z,
+            use_camera: false,
+            snap: GizmoSnap::default(),
+            x_color: Color::rgba(220, 60, 60, 255),
+            y_color: Color::rgba(60, 200, 60, 255),
+            rotate_color: Color::rgba(70, 120, 230, 255),
+            active_color: Color::rgba(250, 210, 50, 255),
+            mesh: Mesh2D::new(renderer),
+            position: Vec2::ZERO,
+            hovered: None,
+            drag: None,
+            was_down: false,
+            visible: true,
+            changed: true,
+        }
+    }
+
+    pub fn mode(&self) -> GizmoMode {
+        self.mode
+    }
+
+    /// Ignored while dragging.
+    pub fn set_mode(&mut self, mode: GizmoMode) -> &mut Self {
+        if self.drag.is_none() && mode != self.mode {
+            self.mode = mode;
+            self.hovered = None;
+            self.changed = true;
+        }
+
+        self
+    }
+
+    /// Hide it while nothing is selected.
+    pub fn set_visible(&mut self, visible: bool) -> &mut Self {
+        self.visible = visible;
+
+        if !visible {
+            self.drag = None;
+            self.hovered = None;
+        }
+
+        self
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Position of the pivot with the gizmo's z.
+    pub fn position(&self) -> Vec3 {
+        self.position.extend(self.z)
+    }
+
+    pub fn hovered(&self) -> Option<GizmoHandle> {
+        self.hovered
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// The handle of the current mode under pos.
+    pub fn hit_test(&self, pos: Vec2) -> Option<GizmoHandle> {
+        if !self.visible {
+            return None;
+        }
+
+        let d = pos - self.position;
+        let center = self.size * 0.1 + HIT_TOLERANCE;
+        let on_axis = |along: f32, across: f32| {
+            across.abs() <= HIT_TOLERANCE
+                && along >= center
+                && along <= self.size + HIT_TOLERANCE
+        };
+
+        match self.mode {
+            GizmoMode::Rotate => {
+                let radius = self.size * 0.8;
+
+                ((d.length() - radius).abs() <= HIT_TOLERANCE)
+                    .then_some(GizmoHandle::Rotate)
+            }
+            mode => {
+                let scale = mode == GizmoMode::Scale;
+
+                if d.x.abs() <= center && d.y.abs() <= center {
+                    Some(if scale {
+                        GizmoHandle::ScaleUniform
+                    } else {
+                        GizmoHandle::MoveFree
+                    })
+                } else if on_axis(d.x, d.y) {
+                    Some(if scale {
+                        GizmoHandle::ScaleX
+                    } else {
+                        GizmoHandle::MoveX
+                    })
+                } else if on_axis(d.y, d.x) {
+                    Some(if scale {
+                        GizmoHandle::ScaleY
+                    } else {
+                        GizmoHandle::MoveY
+                    })
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Follows the target, hovers and drags handles. snap rounds the
+    /// values to [`GizmoSnap`] steps, usually while ctrl is held.
+    pub fn update<T: GizmoTarget>(
+        &mut self,
+        target: &mut T,
+        mouse_pos: Option<Vec2>,
+        mouse_down: bool,
+        snap: bool,
+    ) -> Option<GizmoEvent> {
+        let step = self.snap.translate;
+        let snap_position = |position: Vec2| {
+            if snap && step > 0.0 {
+                (position / step).round() * step
+            } else {
+                position
+            }
+        };
+
+        self.drag_update(target, mouse_pos, mouse_down, snap, snap_position)
+    }
+
+    /// Like update but moves snap through the editor's grid and guides.
+    /// snap still rounds rotation and scale to [`GizmoSnap`] steps.
+    #[cfg(all(feature = "maps", feature = "text"))]
+    pub fn update_snapped<T: GizmoTarget>(
+        &mut self,
+        target: &mut T,
+        mouse_pos: Option<Vec2>,
+        mouse_down: bool,
+        snap: bool,
+        snapping: &Snapping,
+    ) -> Option<GizmoEvent> {
+        self.drag_update(target, mouse_pos, mouse_down, snap, |position| {
+            snapping.snap(position)
+        })
+    }
+
+    fn drag_update<T: GizmoTarget>(
+        &mut self,
+        target: &mut T,
+        mouse_pos: Option<Vec2>,
+        mouse_down: bool,
+        snap: bool,
+        snap_position: impl Fn(Vec2) -> Vec2,
+    ) -> Option<GizmoEvent> {
+        let pressed = mouse_down && !self.was_down;
+
+        self.was_down = mouse_down;
+        self.set_pivot(target.gizmo_position());
+
+        if !self.visible {
+            return None;
+        }
+
+        let mouse = mouse_pos?;
+
+        let Some(drag) = self.drag else {
+            self.set_hovered(self.hit_test(mouse));
+
+            let handle = self.hovered.filter(|_| pressed)?;
+
+            self.drag = Some(GizmoDrag {
+                handle,
+                start_mouse: mouse,
+                start_position: target.gizmo_position(),
+                start_scale: target.gizmo_scale(),
+                start_rotation: target.gizmo_rotation(),
+            });
+            return Some(GizmoEvent::Started(handle));
+        };
+
+        if !mouse_down {
+            self.drag = None;
+            self.set_hovered(self.hit_test(mouse));
+            return Some(GizmoEvent::Finished);
+        }
+
+        let changed = match drag.handle {
+            GizmoHandle::MoveX | GizmoHandle::MoveY | GizmoHandle::MoveFree => {
+                let mut position = snap_position(
+                    drag.start_position + mouse - drag.start_mouse,
+                );
+
+                // Snapping must not move the locked axis.
+                match drag.handle {
+                    GizmoHandle::MoveX => position.y = drag.start_position.y,
+                    GizmoHandle::MoveY => position.x = drag.start_position.x,
+                    _ => {}
+                }
+
+                let changed = position != target.gizmo_position();
+
+                target.set_gizmo_position(position);
+                changed
+            }
+            GizmoHandle::Rotate => {
+                let from = drag.start_mouse - drag.start_position;
+                let to = mouse - drag.start_position;
+                let mut rotation =
+                    drag.start_rotation + from.angle_between(to).to_degrees();
+
+                if snap && self.snap.rotate > 0.0 {
+                    rotation = (rotation / self.snap.rotate).round()
+                        * self.snap.rotate;
+                }
+
+                let changed = rotation != target.gizmo_rotation();
+
+                target.set_gizmo_rotation(rotation);
+                changed
+            }
+            GizmoHandle::ScaleX
+            | GizmoHandle::ScaleY
+            | GizmoHandle::ScaleUniform => {
+                let from = drag.start_mouse - drag.start_position;
+                let to = mouse - drag.start_position;
+                let ratio = |to: f32, from: f32| {
+                    if from.abs() > f32::EPSILON {
+                        to / from
+                    } else {
+                        1.0
+                    }
+                };
+                let mut factor = match drag.handle {
+                    GizmoHandle::ScaleX => Vec2::new(ratio(to.x, from.x), 1.0),
+                    GizmoHandle::ScaleY => Vec2::new(1.0, ratio(to.y, from.y)),
+                    _ => Vec2::splat(ratio(to.length(), from.length())),
+                };
+
+                if snap && self.snap.scale > 0.0 {
+                    factor =
+                        (factor / self.snap.scale).round() * self.snap.scale;
+                }
+
+                let scale =
+                    (drag.start_scale * factor).max(Vec2::splat(MIN_SCALE));
+                let changed = scale != target.gizmo_scale();
+
+                target.set_gizmo_scale(scale);
+                changed
+            }
+        };
+
+        self.set_pivot(target.gizmo_position());
+        changed.then_some(GizmoEvent::Changed)
+    }
+
+    /// Frees the gizmo's mesh.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        renderer.remove_buffer(self.mesh.vbo_store_id);
+    }
+
+    fn set_pivot(&mut self, position: Vec2) {
+        if position != self.position {
+            self.position = position;
+            self.changed = true;
+        }
+    }
+
+    fn set_hovered(&mut self, hovered: Option<GizmoHandle>) {
+        if hovered != self.hovered {
+            self.hovered = hovered;
+            self.changed = true;
+        }
+    }
+
+    fn handle_color(&self, handle: GizmoHandle, color: Color) -> Color {
+        let active = self.drag.map(|drag| drag.handle).or(self.hovered);
+
+        if active == Some(handle) {
+            self.active_color
+        } else {
+            color
+        }
+    }
+
+    fn build(&mut self) -> Result<(), AscendingError> {
+        let mut builder = if self.use_camera {
+            Mesh2DBuilder::with_camera()
+        } else {
+            Mesh2DBuilder::default()
+        };
+        let (p, size, z) = (self.position, self.size, self.z);
+        let center = size * 0.1;
+
+        match self.mode {
+            GizmoMode::Rotate => {
+                let color =
+                    self.handle_color(GizmoHandle::Rotate, self.rotate_color);
+
+                builder.circle(
+                    DrawMode::stroke(2.0),
+                    p,
+                    size * 0.8,
+                    0.5,
+                    z,
+                    color,
+                )?;
+            }
+            mode => {
+                let scale = mode == GizmoMode::Scale;
+                let (x, y, both) = if scale {
+                    (
+                        GizmoHandle::ScaleX,
+                        GizmoHandle::ScaleY,
+                        GizmoHandle::ScaleUniform,
+                    )
+                } else {
+                    (
+                        GizmoHandle::MoveX,
+                        GizmoHandle::MoveY,
+                        GizmoHandle::MoveFree,
+                    )
+                };
+                let axes = [
+                    (Vec2::X, self.handle_color(x, self.x_color)),
+                    (Vec2::Y, self.handle_color(y, self.y_color)),
+                ];
+
+                for (dir, color) in axes {
+                    let end = p + dir * size;
+                    // Perpendicular, for the arrow head's width.
+                    let side = dir.perp() * 5.0;
+
+                    builder.line(&[p + dir * center, end], z, 2.0, color)?;
+
+                    if scale {
+                        builder.rectangle(
+                            DrawMode::fill(),
+                            Vec4::new(end.x - 4.0, end.y - 4.0, 8.0, 8.0),
+                            z,
+                            color,
+                        )?;
+                    } else {
+                        builder.polygon(
+                            DrawMode::fill(),
+                            &[end + side, end + dir * 10.0, end - side],
+                            z,
+                            color,
+                        )?;
+                    }
+                }
+
+                let color =
+                    self.handle_color(both, Color::rgba(220, 220, 220, 255));
+
+                builder.rectangle(
+                    DrawMode::stroke(2.0),
+                    Vec4::new(
+                        p.x - center,
+                        p.y - center,
+                        center * 2.0,
+                        center * 2.0,
+                    ),
+                    z,
+                    color,
+                )?;
+            }
+        }
+
+        self.mesh.vertices.clear();
+        self.mesh.indices.clear();
+        self.mesh.from_builder(builder.finalize());
+        self.mesh.changed = true;
+        self.changed = false;
+        Ok(())
+    }
+}
+
+impl Mesh2DRenderer {
+    /// Adds the gizmo's handles while it is visible.
+    pub fn gizmo_update(
+        &mut self,
+        gizmo: &mut Gizmo,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        if !gizmo.visible {
+            return Ok(());
+        }
+
+        if gizmo.changed {
+            gizmo.build()?;
+        }
+
+        self.mesh_update(&mut gizmo.mesh, renderer);
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+mod compare;
+mod harness;
+mod scene;
+
+pub use compare::*;
+pub use harness::*;
+pub use scene::*;
@@ -0,0 +1,108 @@
+use image::{Rgba, RgbaImage};
+
+/// Largest possible YIQ difference between two colors.
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+/// How far a render may stray from its golden and still pass.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tolerance {
+    /// Perceptual difference from 0.0 to 1.0 at which a pixel counts as
+    /// changed. Small enough to catch a wrong color, large enough to let
+    /// through rounding and filtering differences between drivers.
+    pub threshold: f32,
+    /// Share of changed pixels from 0.0 to 1.0 that still passes, for edges
+    /// that rasterize a little differently.
+    pub max_diff_ratio: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            threshold: 0.1,
+            max_diff_ratio: 0.001,
+        }
+    }
+}
+
+/// Result of comparing a render with its golden.
+pub struct Comparison {
+    pub diff_pixels: usize,
+    pub total_pixels: usize,
+    /// The golden faded to gray with the changed pixels in red. None when
+    /// the sizes differ.
+    pub diff: Option<RgbaImage>,
+}
+
+impl Comparison {
+    pub fn passed(&self, tolerance: &Tolerance) -> bool {
+        self.diff.is_some()
+            && self.diff_pixels as f32
+                <= self.total_pixels as f32 * tolerance.max_diff_ratio
+    }
+}
+
+/// Compares two images by perceived color, so differences the eye can't
+/// see don't count. Images of different sizes differ in every pixel.
+pub fn compare_images(
+    expected: &RgbaImage,
+    actual: &RgbaImage,
+    tolerance: &Tolerance,
+) -> Comparison {
+    if expected.dimensions() != actual.dimensions() {
+        let total_pixels = expected.len().max(actual.len()) / 4;
+
+        return Comparison {
+            diff_pixels: total_pixels,
+            total_pixels,
+            diff: None,
+        };
+    }
+
+    let max_delta = MAX_YIQ_DELTA * tolerance.threshold * tolerance.threshold;
+    let mut diff = RgbaImage::new(expected.width(), expected.height());
+    let mut diff_pixels = 0;
+
+    for ((a, b), out) in expected
+        .pixels()
+        .zip(actual.pixels())
+        .zip(diff.pixels_mut())
+    {
+        *out = if color_delta(a, b) > max_delta {
+            diff_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let gray = (255.0 - (255.0 - luma(a)) * 0.1) as u8;
+            Rgba([gray, gray, gray, 255])
+        };
+    }
+
+    Comparison {
+        diff_pixels,
+        total_pixels: expected.len() / 4,
+        diff: Some(diff),
+    }
+}
+
+/// Squared YIQ distance of two colors blended over white.
+fn color_delta(a: &Rgba<u8>, b: &Rgba<u8>) -> f32 {
+    let (a, b) = (yiq(a), yiq(b));
+    let (y, i, q) = (a.0 - b.0, a.1 - b.1, a.2 - b.2);
+
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+fn yiq(color: &Rgba<u8>) -> (f32, f32, f32) {
+    let alpha = color[3] as f32 / 255.0;
+    let [r, g, b] = [color[0], color[1], color[2]]
+        .map(|c| 255.0 + (c as f32 - 255.0) * alpha);
+
+    (
+        r * 0.2988953 + g * 0.5866225 + b * 0.11448223,
+        r * 0.59597799 - g * 0.2741761 - b * 0.3218019,
+        r * 0.21147017 - g * 0.5226171 + b * 0.31114694,
+    )
+}
+
+fn luma(color: &Rgba<u8>) -> f32 {
+    yiq(color).0
+}
@@ -0,0 +1,138 @@
+use crate::{
+    compare_images, AscendingError, Comparison, GpuRenderer, Pass, Texture,
+    TextureRegion, Tolerance,
+};
+use futures::executor::block_on;
+use image::RgbaImage;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Set to anything to save renders as the new goldens.
+pub const BLESS_VAR: &str = "GOLDEN_BLESS";
+/// Where the actual and diff images of failed checks go, in the golden dir.
+const FAILED_DIR: &str = "failed";
+
+pub enum GoldenOutcome {
+    /// Close enough to the golden.
+    Matched(Comparison),
+    /// Blessing is on, so the render became the golden.
+    Saved,
+}
+
+/// Renders scenes with a headless renderer and compares them against
+/// golden images stored as `<name>.png` in a folder.
+///
+/// A missing golden returns [`AscendingError::GoldenMissing`] unless
+/// blessing, which is the only time goldens are written. A failed check
+/// writes `<name>.actual.png` and `<name>.diff.png` into
+/// the folder's `failed` folder and returns
+/// [`AscendingError::GoldenMismatch`]. Render on the fallback adapter to
+/// keep goldens the same between machines.
+pub struct GoldenHarness {
+    dir: PathBuf,
+    pub tolerance: Tolerance,
+    /// Saves renders as the goldens instead of comparing them. Starts set
+    /// if [`BLESS_VAR`] is.
+    pub bless: bool,
+}
+
+impl GoldenHarness {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            tolerance: Tolerance::default(),
+            bless: std::env::var_os(BLESS_VAR).is_some(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Renders scene into the renderer's frame and checks it against the
+    /// golden called name. Prepare the scene's buffers first.
+    pub fn check<P: Pass>(
+        &self,
+        renderer: &GpuRenderer,
+        name: &str,
+        scene: &mut P,
+    ) -> Result<GoldenOutcome, AscendingError> {
+        let actual = render_image(renderer, scene)?;
+
+        self.check_image(name, &actual)
+    }
+
+    /// Checks an image that was already rendered.
+    pub fn check_image(
+        &self,
+        name: &str,
+        actual: &RgbaImage,
+    ) -> Result<GoldenOutcome, AscendingError> {
+        let golden = self.dir.join(format!("{name}.png"));
+
+        if self.bless {
+            fs::create_dir_all(&self.dir)?;
+            actual.save(golden)?;
+            return Ok(GoldenOutcome::Saved);
+        }
+
+        // A missing golden fails, so one never committed can't pass.
+        if !golden.exists() {
+            return Err(AscendingError::GoldenMissing(golden));
+        }
+
+        let expected = image::open(golden)?.into_rgba8();
+        let comparison = compare_images(&expected, actual, &self.tolerance);
+
+        if comparison.passed(&self.tolerance) {
+            return Ok(GoldenOutcome::Matched(comparison));
+        }
+
+        let output = self.dir.join(FAILED_DIR);
+
+        fs::create_dir_all(&output)?;
+        actual.save(output.join(format!("{name}.actual.png")))?;
+
+        if let Some(diff) = &comparison.diff {
+            diff.save(output.join(format!("{name}.diff.png")))?;
+        }
+
+        Err(AscendingError::GoldenMismatch {
+            name: name.to_owned(),
+            diff_pixels: comparison.diff_pixels,
+            total_pixels: comparison.total_pixels,
+            output,
+        })
+    }
+}
+
+/// Renders scene into the frame of a headless renderer and reads it back.
+pub fn render_image<P: Pass>(
+    renderer: &GpuRenderer,
+    scene: &mut P,
+) -> Result<RgbaImage, AscendingError> {
+    let texture = renderer
+        .frame_texture()
+        .ok_or(AscendingError::InvalidReadback("there is no frame"))?;
+    let mut encoder = renderer.device().create_command_encoder(
+        &wgpu::CommandEncoderDescriptor {
+            label: Some("golden encoder"),
+        },
+    );
+
+    scene.render(renderer, &mut encoder);
+    renderer.queue().submit(Some(encoder.finish()));
+
+    let image = block_on(Texture::read_back(
+        String::new(),
+        renderer,
+        texture,
+        TextureRegion::whole(texture),
+    ))?;
+    let (width, height) = image.size();
+
+    RgbaImage::from_raw(width, height, image.bytes().to_vec())
+        .ok_or(AscendingError::InvalidReadback("frame size did not match"))
+}
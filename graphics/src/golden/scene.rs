@@ -0,0 +1,362 @@
+use crate::{
+    AnimationClock, AreaLight, AsBufferPass, AscendingError, AtlasGroup,
+    Bounds, Color, DirectionalLight, DrawBatch, DrawMode, GpuRenderer, Image,
+    ImageRenderer, LightAnimation, LightRenderer, Lights, Map, MapRenderer,
+    Mesh2D, Mesh2DBuilder, Mesh2DRenderer, OtherError, Pass, Rect,
+    RectRenderer, RenderCommands, RenderImage, RenderLights, RenderMap,
+    RenderMesh2D, RenderRects, RenderText, RngService, RngStream, SceneDesc,
+    SetBuffers, System, Text, TextAtlas, TextRenderer, Texture, TileData, Vec2,
+    Vec3, Vec4,
+};
+use camera::{
+    controls::{FlatControls, FlatSettings},
+    Projection,
+};
+use cosmic_text::{Attrs, Family, Metrics};
+
+fn color([r, g, b, a]: [u8; 4]) -> Color {
+    Color::rgba(r, g, b, a)
+}
+
+impl SceneDesc {
+    /// Uploads the scene's textures and creates what it draws.
+    pub fn build(
+        &self,
+        renderer: &mut GpuRenderer,
+    ) -> Result<GoldenScene, AscendingError> {
+        let size = renderer.size();
+        let system = System::new(
+            renderer,
+            Projection::orthographic_layers(
+                0.0,
+                size.width,
+                0.0,
+                size.height,
+                100,
+            ),
+            FlatControls::new(FlatSettings::default()),
+            [size.width, size.height],
+        );
+        let mut image_atlas =
+            AtlasGroup::new(renderer, wgpu::TextureFormat::Rgba8UnormSrgb);
+        let mut map_atlas =
+            AtlasGroup::new(renderer, wgpu::TextureFormat::Rgba8UnormSrgb);
+        let mut sprites = Vec::with_capacity(self.sprites.len());
+        let mut texts = Vec::with_capacity(self.texts.len());
+
+        for font in &self.fonts {
+            renderer.font_sys.db_mut().load_font_file(font)?;
+        }
+
+        let allocations = self
+            .textures
+            .iter()
+            .map(|path| {
+                Texture::from_file(path)?
+                    .group_upload(&mut image_atlas, renderer)
+                    .ok_or(AscendingError::AtlasFull)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for desc in &self.sprites {
+            let allocation =
+                *allocations.get(desc.texture).ok_or_else(|| {
+                    AscendingError::Other(OtherError::new(&format!(
+                        "sprite texture {} does not exist",
+                        desc.texture
+                    )))
+                })?;
+            let (width, height) = allocation.size();
+            let mut image = Image::new(Some(allocation), renderer, 0);
+
+            image.pos = Vec3::from_array(desc.pos);
+            image.hw = Vec2::from_array(desc.size);
+            image.uv = Vec4::from_array(desc.uv.unwrap_or([
+                0.0,
+                0.0,
+                width as f32,
+                height as f32,
+            ]));
+            image.color = color(desc.color);
+            sprites.push(image);
+        }
+
+        let rects = self
+            .rects
+            .iter()
+            .map(|desc| {
+                let mut rect = Rect::new(renderer, 0);
+
+                rect.set_position(Vec3::from_array(desc.pos))
+                    .set_size(Vec2::from_array(desc.size))
+                    .set_color(color(desc.color))
+                    .set_border_width(desc.border_width)
+                    .set_border_color(color(desc.border_color))
+                    .set_radius(desc.radius);
+                rect
+            })
+            .collect();
+
+        let meshes = self
+            .circles
+            .iter()
+            .map(|desc| {
+                let mode = match desc.stroke {
+                    Some(width) => DrawMode::stroke(width),
+                    None => DrawMode::fill(),
+                };
+                let mut builder = Mesh2DBuilder::default();
+                let mut mesh = Mesh2D::new(renderer);
+
+                builder.circle(
+                    mode,
+                    Vec2::from_array(desc.center),
+                    desc.radius,
+                    0.5,
+                    desc.z,
+                    color(desc.color),
+                )?;
+                mesh.from_builder(builder.finalize());
+                Ok(mesh)
+            })
+            .collect::<Result<Vec<_>, AscendingError>>()?;
+
+        let map = match &self.map {
+            Some(desc) => {
+                Texture::from_file(&desc.tilesheet)?
+                    .new_tilesheet(&mut map_atlas, renderer, desc.tilesize)
+                    .ok_or(AscendingError::AtlasFull)?;
+
+                let mut map = Map::new(renderer, desc.tilesize);
+
+                for tile in &desc.tiles {
+                    map.set_tile(
+                        (tile.pos[0], tile.pos[1], tile.pos[2]),
+                        TileData {
+                            texture_id: tile.id,
+                            texture_layer: 0,
+                            color: color(tile.color),
+                        },
+                    );
+                }
+
+                map.pos = Vec2::from_array(desc.pos);
+                map.can_render = true;
+                Some(map)
+            }
+            None => None,
+        };
+
+        let mut rng = RngService::new(self.seed);
+        let lights = match &self.lights {
+            Some(desc) => {
+                let mut lights = Lights::new(renderer, 0);
+
+                lights.set_seed(rng.stream(RngStream::Lights).next_u32());
+
+                lights.world_color = Vec4::from_array(desc.world_color);
+                lights.enable_lights = true;
+
+                for area in &desc.areas {
+                    lights.insert_area_light(AreaLight {
+                        pos: Vec2::from_array(area.pos),
+                        color: color(area.color),
+                        max_distance: area.max_distance,
+                        anim_speed: 0.0,
+                        dither: 0.0,
+                        animate: false,
+                        animation: LightAnimation::None,
+                        cookie: None,
+                    });
+                }
+
+                for dir in &desc.directionals {
+                    lights.insert_directional_light(DirectionalLight {
+                        pos: Vec2::from_array(dir.pos),
+                        color: color(dir.color),
+                        max_distance: dir.max_distance,
+                        max_width: dir.max_width,
+                        anim_speed: 0.0,
+                        angle: dir.angle,
+                        dither: 0.0,
+                        fade_distance: dir.fade_distance,
+                        edge_fade_distance: dir.edge_fade_distance,
+                        animate: false,
+                        animation: LightAnimation::None,
+                        cookie: None,
+                    });
+                }
+
+                Some(lights)
+            }
+            None => None,
+        };
+
+        for desc in &self.texts {
+            let (pos, size) =
+                (Vec3::from_array(desc.pos), Vec2::from_array(desc.size));
+            let mut text = Text::new(
+                renderer,
+                Some(Metrics::new(desc.font_size, desc.font_size)),
+                pos,
+                size,
+            );
+            let attrs = match &desc.family {
+                Some(family) => Attrs::new().family(Family::Name(family)),
+                None => Attrs::new(),
+            };
+
+            text.set_buffer_size(renderer, size.x as i32, size.y as i32)
+                .set_bounds(Some(Bounds::new(
+                    pos.x,
+                    pos.y,
+                    pos.x + size.x,
+                    pos.y + size.y,
+                )))
+                .set_default_color(color(desc.color))
+                .set_text(renderer, &desc.text, attrs);
+            texts.push(text);
+        }
+
+        let [r, g, b, a] = self.clear.map(|c| c as f64 / 255.0);
+
+        Ok(GoldenScene {
+            clear: wgpu::Color { r, g, b, a },
+            clock: AnimationClock::new(),
+            system,
+            image_renderer: ImageRenderer::new(renderer)?,
+            rect_renderer: RectRenderer::new(renderer)?,
+            mesh_renderer: Mesh2DRenderer::new(renderer)?,
+            map_renderer: MapRenderer::new(renderer, 1)?,
+            light_renderer: LightRenderer::new(renderer)?,
+            text_renderer: TextRenderer::new(renderer)?,
+            text_atlas: TextAtlas::new(renderer)?,
+            image_atlas,
+            map_atlas,
+            sprites,
+            rects,
+            meshes,
+            map,
+            lights,
+            texts,
+        })
+    }
+}
+
+/// A built [`SceneDesc`]. Call prepare before checking it so its buffers
+/// are filled.
+pub struct GoldenScene {
+    clear: wgpu::Color,
+    /// Never advanced, so animated shaders draw the same every time.
+    clock: AnimationClock,
+    pub system: System<FlatControls>,
+    pub image_renderer: ImageRenderer,
+    pub rect_renderer: RectRenderer,
+    pub mesh_renderer: Mesh2DRenderer,
+    pub map_renderer: MapRenderer,
+    pub light_renderer: LightRenderer,
+    pub text_renderer: TextRenderer,
+    pub text_atlas: TextAtlas,
+    pub image_atlas: AtlasGroup,
+    pub map_atlas: AtlasGroup,
+    pub sprites: Vec<Image>,
+    pub rects: Vec<Rect>,
+    pub meshes: Vec<Mesh2D>,
+    pub map: Option<Map>,
+    pub lights: Option<Lights>,
+    pub texts: Vec<Text>,
+}
+
+impl GoldenScene {
+    /// Updates the camera and uploads everything to the renderers.
+    pub fn prepare(
+        &mut self,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        let size = renderer.size();
+
+        self.system.update(renderer, &self.clock);
+        self.system
+            .update_screen(renderer, [size.width, size.height]);
+
+        for sprite in &mut self.sprites {
+            self.image_renderer.image_update(sprite, renderer);
+        }
+
+        for rect in &mut self.rects {
+            self.rect_renderer.rect_update(rect, renderer);
+        }
+
+        for mesh in &mut self.meshes {
+            self.mesh_renderer.mesh_update(mesh, renderer);
+        }
+
+        if let Some(map) = &mut self.map {
+            self.map_renderer.map_update(map, renderer);
+        }
+
+        if let Some(lights) = &mut self.lights {
+            self.light_renderer.lights_update(lights, renderer);
+        }
+
+        for text in &mut self.texts {
+            self.text_renderer.text_update(
+                text,
+                &mut self.text_atlas,
+                renderer,
+            )?;
+        }
+
+        self.image_renderer.finalize(renderer);
+        self.rect_renderer.finalize(renderer);
+        self.mesh_renderer.finalize(renderer);
+        self.map_renderer.finalize(renderer);
+        self.light_renderer.finalize(renderer);
+        self.text_renderer.finalize(renderer);
+        Ok(())
+    }
+}
+
+impl Pass for GoldenScene {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("golden pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: renderer.render_target().expect("no frame view?"),
+                resolve_target: renderer.resolve_target(),
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(self.clear),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(
+                wgpu::RenderPassDepthStencilAttachment {
+                    view: renderer.depth_buffer(),
+                    depth_ops: Some(wgpu::Operations {
+                        load: renderer.gpu_device().depth_clear(),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                },
+            ),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        let mut batch = DrawBatch::new();
+
+        batch.set_bind_group(0, self.system.bind_group(), &[]);
+        batch.set_buffers(renderer.buffer_object.as_buffer_pass());
+        batch.render_lower_maps(renderer, &self.map_renderer, &self.map_atlas);
+        batch.render_image(renderer, &self.image_renderer, &self.image_atlas);
+        batch.render_upper_maps(renderer, &self.map_renderer, &self.map_atlas);
+        batch.render_lights(renderer, &self.light_renderer);
+        batch.render_rects(renderer, &self.rect_renderer);
+        batch.render_2dmeshs(renderer, &self.mesh_renderer);
+        batch.render_text(renderer, &self.text_renderer, &self.text_atlas);
+        batch.submit(&mut pass);
+    }
+}
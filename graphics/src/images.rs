@@ -1,9 +1,13 @@
+mod batch;
+mod clip;
 mod image;
-mod pipeline;
+pub(crate) mod pipeline;
 mod render;
-mod vertex;
+pub(crate) mod vertex;
 
 pub use self::image::*;
-pub use pipeline::*;
+pub use batch::*;
+pub use clip::*;
+pub(crate) use pipeline::*;
 pub use render::*;
-pub use vertex::*;
+pub(crate) use vertex::*;
@@ -0,0 +1,194 @@
+use crate::{
+    Atlas, DrawOrder, GpuRenderer, Image, ImageRenderer, ImageVertex, Index,
+    OrderedIndex, PoolHandle, SpritePool, Vec3,
+};
+use std::hash::Hash;
+
+/// Owns many sprites drawn as one buffer store, so thousands of them cost
+/// one entry in the [`ImageRenderer`] instead of one each. The instances
+/// are only rebuilt when a sprite was added, removed or changed, sorted
+/// back to front and then by atlas layer, so the batch renders in the
+/// single instanced draw of its atlas.
+///
+/// Every sprite uses the batch's render layer. Clip fades and
+/// silhouettes are not drawn, use [`ImageRenderer::image_update`] for
+/// sprites that need them.
+pub struct SpriteBatch {
+    sprites: SpritePool,
+    store_id: Index,
+    order: DrawOrder,
+    render_layer: u32,
+    /// Scratch for the sorted instances.
+    instances: Vec<(DrawOrder, ImageVertex)>,
+    /// if a sprite was added or removed.
+    changed: bool,
+}
+
+impl SpriteBatch {
+    pub fn new(renderer: &mut GpuRenderer, render_layer: u32) -> Self {
+        Self::with_capacity(renderer, render_layer, 0)
+    }
+
+    pub fn with_capacity(
+        renderer: &mut GpuRenderer,
+        render_layer: u32,
+        capacity: usize,
+    ) -> Self {
+        Self {
+            sprites: SpritePool::with_capacity(capacity),
+            store_id: renderer.new_buffer(),
+            order: DrawOrder::default(),
+            render_layer,
+            instances: Vec::with_capacity(capacity),
+            changed: true,
+        }
+    }
+
+    pub fn insert(&mut self, sprite: Image) -> PoolHandle {
+        self.changed = true;
+        self.sprites.insert(sprite)
+    }
+
+    /// Removes the sprite and frees its buffers. Returns false if the
+    /// handle was already removed.
+    pub fn remove(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        handle: PoolHandle,
+    ) -> bool {
+        let removed = self.sprites.remove(renderer, handle);
+
+        self.changed |= removed;
+        removed
+    }
+
+    pub fn get(&self, handle: PoolHandle) -> Option<&Image> {
+        self.sprites.get(handle)
+    }
+
+    /// Set the sprite's changed flag after editing it, like for a lone
+    /// [`Image`], so the batch gets rebuilt.
+    pub fn get_mut(&mut self, handle: PoolHandle) -> Option<&mut Image> {
+        self.sprites.get_mut(handle)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (PoolHandle, &Image)> {
+        self.sprites.iter()
+    }
+
+    /// Set the changed flag of every sprite edited.
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (PoolHandle, &mut Image)> {
+        self.sprites.iter_mut()
+    }
+
+    /// Moves every sprite's texture to where the last [`Atlas::repack`]
+    /// put it.
+    pub fn relocate<U: Hash + Eq + Clone>(&mut self, atlas: &Atlas<U>) {
+        for (_, sprite) in self.sprites.iter_mut() {
+            sprite.relocate(atlas);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    /// if the instances will be rebuilt on the next update.
+    pub fn is_changed(&self) -> bool {
+        self.changed || self.sprites.iter().any(|(_, sprite)| sprite.changed)
+    }
+
+    fn rebuild(&mut self, renderer: &mut GpuRenderer) {
+        let render_layer = self.render_layer;
+        let instances = &mut self.instances;
+        let mut back = f32::MIN;
+        let mut alpha = false;
+
+        instances.clear();
+
+        for (_, sprite) in self.sprites.iter_mut() {
+            sprite.changed = false;
+
+            let Some(allocation) = sprite.texture else {
+                continue;
+            };
+            let order = DrawOrder::new(
+                sprite.color.a() < 255 || sprite.fade.is_some(),
+                &sprite.pos,
+                render_layer,
+            )
+            .with_texture(allocation.layer as u32);
+
+            back = back.max(sprite.pos.z);
+            alpha |= order.alpha;
+            instances.push((order, sprite.vertex(&allocation)));
+        }
+
+        // Back to front, then grouped by atlas layer.
+        instances.sort_unstable_by(|(a, _), (b, _)| {
+            a.alpha
+                .cmp(&b.alpha)
+                .then(a.z.cmp(&b.z).reverse())
+                .then(a.texture.cmp(&b.texture))
+                .then(a.y.cmp(&b.y).reverse())
+                .then(a.x.cmp(&b.x))
+        });
+
+        if let Some(store) = renderer.get_buffer_mut(&self.store_id) {
+            store.store.clear();
+
+            for (_, instance) in instances.iter() {
+                store.store.extend_from_slice(bytemuck::bytes_of(instance));
+            }
+
+            store.changed = true;
+        }
+
+        // Sorts the whole batch by its farthest sprite.
+        self.order = DrawOrder::new(
+            alpha,
+            &Vec3::new(0.0, 0.0, if back == f32::MIN { 0.0 } else { back }),
+            render_layer,
+        );
+        self.changed = false;
+    }
+
+    /// Rebuilds the instances if anything changed. Returns None while the
+    /// batch has no sprites.
+    pub fn update(
+        &mut self,
+        renderer: &mut GpuRenderer,
+    ) -> Option<OrderedIndex> {
+        if self.is_changed() {
+            self.rebuild(renderer);
+        }
+
+        (!self.instances.is_empty())
+            .then(|| OrderedIndex::new(self.order, self.store_id, 0))
+    }
+
+    /// Frees the batch's buffer and every sprite's.
+    pub fn unload(mut self, renderer: &mut GpuRenderer) {
+        self.sprites.clear(renderer);
+        renderer.remove_buffer(self.store_id);
+    }
+}
+
+impl ImageRenderer {
+    /// Adds the whole batch to this frame's images.
+    pub fn batch_update(
+        &mut self,
+        batch: &mut SpriteBatch,
+        renderer: &mut GpuRenderer,
+    ) {
+        if let Some(index) = batch.update(renderer) {
+            self.add_buffer_store(renderer, index);
+        }
+    }
+}
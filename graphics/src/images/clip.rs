@@ -0,0 +1,51 @@
+use crate::{Image, Vec2, Vec4};
+
+/// One animation of an image, like walking or idle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AnimationClip {
+    /// Start of the first frame and the frame size within the texture.
+    pub uv: Vec4,
+    /// frames, frames_per_row.
+    pub frames: Vec2,
+    /// in millsecs 1000 = 1sec
+    pub switch_time: u32,
+    pub animate: bool,
+}
+
+impl AnimationClip {
+    pub fn new(uv: Vec4, frames: Vec2, switch_time: u32) -> Self {
+        Self {
+            uv,
+            frames,
+            switch_time,
+            animate: true,
+        }
+    }
+
+    /// The clip the image is currently set to.
+    pub fn from_image(image: &Image) -> Self {
+        Self {
+            uv: image.uv,
+            frames: image.frames,
+            switch_time: image.switch_time,
+            animate: image.animate,
+        }
+    }
+}
+
+/// The clip being faded out after [`Image::play_clip`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClipFade {
+    pub from: AnimationClip,
+    /// Seconds since the fade started.
+    pub elapsed: f32,
+    /// Seconds the fade takes.
+    pub duration: f32,
+}
+
+impl ClipFade {
+    /// How far the new clip has faded in. 0.0 - 1.0.
+    pub fn progress(&self) -> f32 {
+        (self.elapsed / self.duration).clamp(0.0, 1.0)
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{
-    Allocation, Color, DrawOrder, GpuRenderer, ImageVertex, Index,
-    OrderedIndex, Vec2, Vec3, Vec4,
+    Allocation, AlphaMask, AnimationClip, AnimationClock, Atlas, ClipFade,
+    Color, DrawLayer, DrawOrder, GpuRenderer, ImageVertex, Index, OrderedIndex,
+    Vec2, Vec3, Vec4,
 };
+use std::{hash::Hash, sync::Arc};
 
 /// rendering data for all images.
 pub struct Image {
@@ -18,12 +20,37 @@ pub struct Image {
     pub switch_time: u32,
     /// turn on animation if set.
     pub animate: bool,
+    /// Speed of this image's animation on top of the clock's. 0.0 stops
+    /// it on the first frame. Changing it can skip frames.
+    pub time_scale: f32,
     pub use_camera: bool,
+    /// Stands the image up facing the camera from its bottom left, for
+    /// sprites in a perspective view. Needs use_camera.
+    pub billboard: bool,
     /// Texture area location in Atlas.
     pub texture: Option<Allocation>,
+    /// Transparent areas of the texture for hit testing. Get it from the
+    /// atlas the texture was uploaded to.
+    pub alpha_mask: Option<Arc<AlphaMask>>,
     pub store_id: Index,
     pub order: DrawOrder,
+    /// Orders it within its layer under [`crate::SortPolicy::Key`].
+    pub sort_key: u64,
     pub render_layer: u32,
+    /// Id written to a [`crate::PickBuffer`]. 0 leaves the image out.
+    pub pick_id: u32,
+    /// Data for custom pipelines, like a dissolve threshold or team color.
+    /// The built in shaders ignore it.
+    pub user_data: [f32; 4],
+    /// Color to draw the image in where map layers or other objects cover
+    /// it. None turns the silhouette off.
+    pub silhouette: Option<Color>,
+    /// Created the first time a silhouette is set.
+    pub silhouette_store_id: Option<Index>,
+    /// The previous clip while crossfading to a new one.
+    pub fade: Option<ClipFade>,
+    /// Created the first time a clip is faded.
+    pub fade_store_id: Option<Index>,
     /// if anything got updated we need to update the buffers too.
     pub changed: bool,
 }
@@ -41,49 +68,135 @@ impl Image {
             frames: Vec2::default(),
             switch_time: 0,
             animate: false,
+            time_scale: 1.0,
             use_camera: true,
+            billboard: false,
             color: Color::rgba(255, 255, 255, 255),
             texture,
+            alpha_mask: None,
             store_id: renderer.new_buffer(),
             order: DrawOrder::default(),
+            sort_key: 0,
             render_layer,
+            pick_id: 0,
+            user_data: [0.0; 4],
+            silhouette: None,
+            silhouette_store_id: None,
+            fade: None,
+            fade_store_id: None,
             changed: true,
         }
     }
-    pub fn create_quad(&mut self, renderer: &mut GpuRenderer) {
-        let allocation = match &self.texture {
-            Some(allocation) => allocation,
-            None => return,
-        };
 
+    fn tex_data(uv: Vec4, allocation: &Allocation) -> [f32; 4] {
         let (u, v, width, height) = allocation.rect();
-        let (u, v, width, height) = (
-            self.uv.x + u as f32,
-            self.uv.y + v as f32,
-            self.uv.z.min(width as f32),
-            self.uv.w.min(height as f32),
+
+        [
+            uv.x + u as f32,
+            uv.y + v as f32,
+            uv.z.min(width as f32),
+            uv.w.min(height as f32),
+        ]
+    }
+
+    /// The shader animates off the clock so a slower image just switches
+    /// frames less often.
+    fn scaled_switch_time(&self, switch_time: u32) -> u32 {
+        if self.time_scale > 0.0 {
+            (switch_time as f32 / self.time_scale) as u32
+        } else {
+            switch_time
+        }
+    }
+
+    /// The image's main quad, without the fading clip or silhouette.
+    pub(crate) fn vertex(&self, allocation: &Allocation) -> ImageVertex {
+        // While fading the new clip fades in over the old one.
+        let fade = self.fade.map(|fade| fade.progress()).unwrap_or(1.0);
+        let color = Color::rgba(
+            self.color.r(),
+            self.color.g(),
+            self.color.b(),
+            (self.color.a() as f32 * fade) as u8,
         );
 
-        let instance = ImageVertex {
+        ImageVertex {
             position: self.pos.to_array(),
             hw: self.hw.to_array(),
-            #[allow(clippy::tuple_array_conversions)]
-            tex_data: [u, v, width, height],
-            color: self.color.0,
+            tex_data: Self::tex_data(self.uv, allocation),
+            color: color.0,
             frames: self.frames.to_array(),
-            animate: u32::from(self.animate),
-            use_camera: u32::from(self.use_camera),
-            time: self.switch_time,
+            animate: u32::from(self.animate && self.time_scale > 0.0),
+            use_camera: u32::from(self.use_camera)
+                | (u32::from(self.billboard) << 1),
+            time: self.scaled_switch_time(self.switch_time),
             layer: allocation.layer as i32,
+            pick_id: self.pick_id,
+            user_data: self.user_data,
+        }
+    }
+
+    pub fn create_quad(&mut self, renderer: &mut GpuRenderer) {
+        let allocation = match &self.texture {
+            Some(allocation) => *allocation,
+            None => return,
         };
 
+        let fade = self.fade.map(|fade| fade.progress()).unwrap_or(1.0);
+        let instance = self.vertex(&allocation);
+
         if let Some(store) = renderer.get_buffer_mut(&self.store_id) {
             store.store = bytemuck::bytes_of(&instance).to_vec();
             store.changed = true;
         }
 
-        self.order =
-            DrawOrder::new(self.color.a() < 255, &self.pos, self.render_layer);
+        if let Some(ClipFade { from, .. }) = self.fade {
+            let store_id = *self
+                .fade_store_id
+                .get_or_insert_with(|| renderer.new_buffer());
+            let instance = ImageVertex {
+                tex_data: Self::tex_data(from.uv, &allocation),
+                color: Color::rgba(
+                    self.color.r(),
+                    self.color.g(),
+                    self.color.b(),
+                    (self.color.a() as f32 * (1.0 - fade)) as u8,
+                )
+                .0,
+                frames: from.frames.to_array(),
+                animate: u32::from(from.animate && self.time_scale > 0.0),
+                time: self.scaled_switch_time(from.switch_time),
+                ..instance
+            };
+
+            if let Some(store) = renderer.get_buffer_mut(&store_id) {
+                store.store = bytemuck::bytes_of(&instance).to_vec();
+                store.changed = true;
+            }
+        }
+
+        if let Some(color) = self.silhouette {
+            let store_id = *self
+                .silhouette_store_id
+                .get_or_insert_with(|| renderer.new_buffer());
+            let instance = ImageVertex {
+                color: color.0,
+                ..instance
+            };
+
+            if let Some(store) = renderer.get_buffer_mut(&store_id) {
+                store.store = bytemuck::bytes_of(&instance).to_vec();
+                store.changed = true;
+            }
+        }
+
+        self.order = DrawOrder::new(
+            self.color.a() < 255 || self.fade.is_some(),
+            &self.pos,
+            self.render_layer,
+        )
+        .with_sort_key(self.sort_key)
+        .with_texture(self.texture.map_or(0, |texture| texture.layer as u32));
         self.changed = false;
     }
 
@@ -96,4 +209,160 @@ impl Image {
 
         OrderedIndex::new(self.order, self.store_id, 0)
     }
+
+    /// Frees the image's buffers. Use when it won't be rendered again.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        renderer.remove_buffer(self.store_id);
+
+        if let Some(store_id) = self.silhouette_store_id {
+            renderer.remove_buffer(store_id);
+        }
+
+        if let Some(store_id) = self.fade_store_id {
+            renderer.remove_buffer(store_id);
+        }
+    }
+
+    /// if pos lands on the image. pos is in world space when the image uses
+    /// the camera, otherwise screen space. With an alpha mask transparent
+    /// pixels miss. Animated images are tested against their first frame.
+    pub fn hit_test(&self, pos: Vec2) -> bool {
+        let allocation = match &self.texture {
+            Some(allocation) => allocation,
+            None => return false,
+        };
+
+        let local = pos - self.pos.truncate();
+
+        if local.x < 0.0
+            || local.y < 0.0
+            || local.x >= self.hw.x
+            || local.y >= self.hw.y
+        {
+            return false;
+        }
+
+        let mask = match &self.alpha_mask {
+            Some(mask) => mask,
+            None => return true,
+        };
+
+        let (_, _, width, height) = allocation.rect();
+        let (width, height) =
+            (self.uv.z.min(width as f32), self.uv.w.min(height as f32));
+        // Textures are stored top down while y goes up on screen.
+        let x = self.uv.x + local.x / self.hw.x * width;
+        let y = self.uv.y + (1.0 - local.y / self.hw.y) * height;
+
+        mask.is_opaque(x as u32, y as u32)
+    }
+
+    /// Switches to the clip. With a fade above 0.0 seconds the old clip
+    /// fades out while the new one fades in. Call
+    /// [`Image::update_fade`] every frame while fading.
+    pub fn play_clip(&mut self, clip: &AnimationClip, fade: f32) {
+        let current = AnimationClip::from_image(self);
+
+        if current == *clip {
+            return;
+        }
+
+        self.fade =
+            (fade > 0.0 && self.texture.is_some()).then_some(ClipFade {
+                from: current,
+                elapsed: 0.0,
+                duration: fade,
+            });
+        self.uv = clip.uv;
+        self.frames = clip.frames;
+        self.switch_time = clip.switch_time;
+        self.animate = clip.animate;
+        self.changed = true;
+    }
+
+    /// Advances a crossfade by the clock's delta and the image's time scale.
+    pub fn update_fade(&mut self, clock: &AnimationClock) {
+        let delta = clock.scaled_delta(self.time_scale);
+
+        if let Some(fade) = &mut self.fade {
+            fade.elapsed += delta;
+
+            if fade.elapsed >= fade.duration {
+                self.fade = None;
+            }
+
+            self.changed = true;
+        }
+    }
+
+    /// The outgoing clip while crossfading.
+    pub fn fade_index(&self) -> Option<OrderedIndex> {
+        match (self.fade, self.fade_store_id) {
+            (Some(_), Some(store_id)) => {
+                Some(OrderedIndex::new(self.order, store_id, 0))
+            }
+            _ => None,
+        }
+    }
+
+    /// Moves the image into the layer at a depth within it, 0.0 at the
+    /// front and 1.0 at the back. Sets its z and render layer.
+    pub fn set_layer(&mut self, layer: DrawLayer, depth: f32) -> &mut Self {
+        self.pos.z = layer.z(depth);
+        self.render_layer = layer.index();
+        self.changed = true;
+        self
+    }
+
+    /// Moves the texture to where the last [`Atlas::repack`] put it.
+    /// Returns true if it moved. Call it on every image using the atlas
+    /// after a repack.
+    pub fn relocate<U: Hash + Eq + Clone>(&mut self, atlas: &Atlas<U>) -> bool {
+        let Some(texture) = self.texture else {
+            return false;
+        };
+
+        match atlas.current(&texture) {
+            Some(current) if current.generation != texture.generation => {
+                self.texture = Some(current);
+                self.changed = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn set_sort_key(&mut self, sort_key: u64) -> &mut Self {
+        self.sort_key = sort_key;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_user_data(&mut self, user_data: [f32; 4]) -> &mut Self {
+        self.user_data = user_data;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_pick_id(&mut self, pick_id: u32) -> &mut Self {
+        self.pick_id = pick_id;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_silhouette(&mut self, silhouette: Option<Color>) {
+        self.silhouette = silhouette;
+        self.changed = true;
+    }
+
+    /// The silhouette to draw after the upper map layers. None if the image
+    /// has no silhouette.
+    pub fn silhouette_index(&self) -> Option<OrderedIndex> {
+        match (self.silhouette, self.silhouette_store_id) {
+            (Some(_), Some(store_id)) => {
+                Some(OrderedIndex::new(self.order, store_id, 0))
+            }
+            _ => None,
+        }
+    }
 }
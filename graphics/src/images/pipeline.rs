@@ -1,6 +1,7 @@
 use crate::{
-    BufferLayout, GpuDevice, ImageVertex, LayoutStorage, PipeLineLayout,
-    StaticBufferObject, SystemLayout, TextureLayout,
+    shaders, BufferLayout, GpuDevice, ImageVertex, LayoutStorage,
+    PipeLineLayout, StaticBufferObject, SystemLayout, TextureLayout,
+    PICK_FORMAT,
 };
 use bytemuck::{Pod, Zeroable};
 
@@ -15,75 +16,151 @@ impl PipeLineLayout for ImageRenderPipeline {
         layouts: &mut LayoutStorage,
         surface_format: wgpu::TextureFormat,
     ) -> wgpu::RenderPipeline {
-        let shader = gpu_device.device().create_shader_module(
-            wgpu::ShaderModuleDescriptor {
-                label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("../shaders/imageshader.wgsl").into(),
-                ),
-            },
-        );
+        create_image_pipeline(
+            gpu_device,
+            layouts,
+            surface_format,
+            ImagePass::Color,
+        )
+    }
+}
 
-        let system_layout = layouts.create_layout(gpu_device, SystemLayout);
-        let texture_layout = layouts.create_layout(gpu_device, TextureLayout);
+/// Draws images in a flat color only where something closer already
+/// covers them.
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct ImageSilhouetteRenderPipeline;
 
-        // Create the render pipeline.
-        gpu_device.device().create_render_pipeline(
-            &wgpu::RenderPipelineDescriptor {
-                label: Some("Image render pipeline"),
-                layout: Some(&gpu_device.device().create_pipeline_layout(
-                    &wgpu::PipelineLayoutDescriptor {
-                        label: Some("render_pipeline_layout"),
-                        bind_group_layouts: &[&system_layout, &texture_layout],
-                        push_constant_ranges: &[],
-                    },
-                )),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vertex",
-                    buffers: &[
-                        wgpu::VertexBufferLayout {
-                            array_stride: StaticBufferObject::stride(),
-                            step_mode: wgpu::VertexStepMode::Vertex,
-                            attributes: &[
-                                StaticBufferObject::vertex_attribute(),
-                            ],
-                        },
-                        wgpu::VertexBufferLayout {
-                            array_stride: ImageVertex::stride() as u64,
-                            step_mode: wgpu::VertexStepMode::Instance,
-                            attributes: &ImageVertex::attributes(),
-                        },
-                    ],
-                },
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: None,
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
-                },
-                depth_stencil: Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::LessEqual,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default(),
-                }),
-                multisample: wgpu::MultisampleState::default(),
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: "fragment",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: surface_format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                multiview: None,
-            },
+impl PipeLineLayout for ImageSilhouetteRenderPipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        create_image_pipeline(
+            gpu_device,
+            layouts,
+            surface_format,
+            ImagePass::Silhouette,
         )
     }
 }
+
+/// Writes the images' pick ids into a [`crate::PickBuffer`].
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct ImagePickRenderPipeline;
+
+impl PipeLineLayout for ImagePickRenderPipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        _surface_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        create_image_pipeline(gpu_device, layouts, PICK_FORMAT, ImagePass::Pick)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum ImagePass {
+    Color,
+    Silhouette,
+    Pick,
+}
+
+fn create_image_pipeline(
+    gpu_device: &mut GpuDevice,
+    layouts: &mut LayoutStorage,
+    format: wgpu::TextureFormat,
+    pass: ImagePass,
+) -> wgpu::RenderPipeline {
+    let shader = gpu_device.device().create_shader_module(
+        wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::IMAGE_SHADER.into()),
+        },
+    );
+
+    let system_layout = layouts.create_layout(gpu_device, SystemLayout);
+    let texture_layout = layouts.create_layout(gpu_device, TextureLayout);
+
+    // Silhouettes only pass behind what is already drawn and leave the
+    // depth alone so they never hide anything.
+    let (depth_write_enabled, depth_compare, entry_point) = match pass {
+        ImagePass::Silhouette => (
+            false,
+            gpu_device.depth_compare_behind(),
+            "silhouette_fragment",
+        ),
+        ImagePass::Color => (true, gpu_device.depth_compare(), "fragment"),
+        ImagePass::Pick => (true, gpu_device.depth_compare(), "pick_fragment"),
+    };
+    // Ids can't be blended or multisampled.
+    let (blend, multisample) = if pass == ImagePass::Pick {
+        (None, wgpu::MultisampleState::default())
+    } else {
+        (
+            Some(wgpu::BlendState::ALPHA_BLENDING),
+            gpu_device.multisample_state(),
+        )
+    };
+
+    // Create the render pipeline.
+    gpu_device.device().create_render_pipeline(
+        &wgpu::RenderPipelineDescriptor {
+            label: Some("Image render pipeline"),
+            layout: Some(&gpu_device.device().create_pipeline_layout(
+                &wgpu::PipelineLayoutDescriptor {
+                    label: Some("render_pipeline_layout"),
+                    bind_group_layouts: &[&system_layout, &texture_layout],
+                    push_constant_ranges: &[],
+                },
+            )),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vertex",
+                buffers: &[
+                    wgpu::VertexBufferLayout {
+                        array_stride: StaticBufferObject::stride(),
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &[StaticBufferObject::vertex_attribute()],
+                    },
+                    wgpu::VertexBufferLayout {
+                        array_stride: ImageVertex::stride() as u64,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &ImageVertex::attributes(),
+                    },
+                ],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled,
+                depth_compare,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample,
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        },
+    )
+}
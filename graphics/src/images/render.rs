@@ -1,16 +1,22 @@
 use crate::{
-    AscendingError, AtlasGroup, GpuRenderer, Image, ImageRenderPipeline,
-    ImageVertex, InstanceBuffer, OrderedIndex, StaticBufferObject,
+    AscendingError, AtlasGroup, GpuRenderer, Image, ImagePickRenderPipeline,
+    ImageRenderPipeline, ImageSilhouetteRenderPipeline, ImageVertex,
+    InstanceBuffer, OrderedIndex, RenderCommands, StaticBufferObject,
 };
 
 pub struct ImageRenderer {
     pub buffer: InstanceBuffer<ImageVertex>,
+    pub silhouette_buffer: InstanceBuffer<ImageVertex>,
 }
 
 impl ImageRenderer {
     pub fn new(renderer: &GpuRenderer) -> Result<Self, AscendingError> {
         Ok(Self {
             buffer: InstanceBuffer::new(renderer.gpu_device()),
+            silhouette_buffer: InstanceBuffer::with_capacity(
+                renderer.gpu_device(),
+                32,
+            ),
         })
     }
 
@@ -23,7 +29,8 @@ impl ImageRenderer {
     }
 
     pub fn finalize(&mut self, renderer: &mut GpuRenderer) {
-        self.buffer.finalize(renderer)
+        self.buffer.finalize(renderer);
+        self.silhouette_buffer.finalize(renderer);
     }
 
     pub fn image_update(
@@ -34,6 +41,14 @@ impl ImageRenderer {
         let index = image.update(renderer);
 
         self.add_buffer_store(renderer, index);
+
+        if let Some(index) = image.fade_index() {
+            self.add_buffer_store(renderer, index);
+        }
+
+        if let Some(index) = image.silhouette_index() {
+            self.silhouette_buffer.add_buffer_store(renderer, index);
+        }
     }
 }
 
@@ -47,11 +62,40 @@ where
         buffer: &'b ImageRenderer,
         atlas: &'b AtlasGroup,
     );
+
+    /// Like render_image but with a custom pipeline made for
+    /// [`ImageVertex`], which can read each image's
+    /// [`crate::Image::user_data`].
+    fn render_image_with(
+        &mut self,
+        buffer: &'b ImageRenderer,
+        atlas: &'b AtlasGroup,
+        pipeline: &'b wgpu::RenderPipeline,
+    );
+
+    /// Draws the silhouettes of covered images. Render after everything
+    /// that can cover them but before the lights, which fill the depth.
+    fn render_silhouettes(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b ImageRenderer,
+        atlas: &'b AtlasGroup,
+    );
+
+    /// Draws the images' pick ids. Render into a [`crate::PickBuffer`]
+    /// pass.
+    fn render_image_picks(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b ImageRenderer,
+        atlas: &'b AtlasGroup,
+    );
 }
 
-impl<'a, 'b> RenderImage<'a, 'b> for wgpu::RenderPass<'a>
+impl<'a, 'b, P> RenderImage<'a, 'b> for P
 where
     'b: 'a,
+    P: RenderCommands<'a>,
 {
     fn render_image(
         &mut self,
@@ -73,4 +117,70 @@ where
             );
         }
     }
+
+    fn render_image_with(
+        &mut self,
+        buffer: &'b ImageRenderer,
+        atlas: &'b AtlasGroup,
+        pipeline: &'b wgpu::RenderPipeline,
+    ) {
+        if buffer.buffer.count() > 0 {
+            self.set_bind_group(1, &atlas.texture.bind_group, &[]);
+            self.set_vertex_buffer(1, buffer.buffer.instances(None));
+            self.set_pipeline(pipeline);
+
+            self.draw_indexed(
+                0..StaticBufferObject::index_count(),
+                0,
+                0..buffer.buffer.count(),
+            );
+        }
+    }
+
+    fn render_silhouettes(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b ImageRenderer,
+        atlas: &'b AtlasGroup,
+    ) {
+        if buffer.silhouette_buffer.count() > 0 {
+            self.set_bind_group(1, &atlas.texture.bind_group, &[]);
+            self.set_vertex_buffer(1, buffer.silhouette_buffer.instances(None));
+            self.set_pipeline(
+                renderer
+                    .get_pipelines(ImageSilhouetteRenderPipeline)
+                    .unwrap(),
+            );
+
+            self.draw_indexed(
+                0..StaticBufferObject::index_count(),
+                0,
+                0..buffer.silhouette_buffer.count(),
+            );
+        }
+    }
+
+    fn render_image_picks(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b ImageRenderer,
+        atlas: &'b AtlasGroup,
+    ) {
+        let Some(pipeline) = renderer.get_pipelines(ImagePickRenderPipeline)
+        else {
+            return;
+        };
+
+        if buffer.buffer.count() > 0 {
+            self.set_bind_group(1, &atlas.texture.bind_group, &[]);
+            self.set_vertex_buffer(1, buffer.buffer.instances(None));
+            self.set_pipeline(pipeline);
+
+            self.draw_indexed(
+                0..StaticBufferObject::index_count(),
+                0,
+                0..buffer.buffer.count(),
+            );
+        }
+    }
 }
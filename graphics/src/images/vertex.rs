@@ -10,9 +10,13 @@ pub struct ImageVertex {
     pub color: u32,
     pub frames: [f32; 2],
     pub animate: u32,
+    /// Bit 0 uses the camera and bit 1 billboards.
     pub use_camera: u32,
     pub time: u32,
     pub layer: i32,
+    pub pick_id: u32,
+    /// Free for custom pipelines, read as `@location(11) vec4<f32>`.
+    pub user_data: [f32; 4],
 }
 
 impl Default for ImageVertex {
@@ -27,13 +31,15 @@ impl Default for ImageVertex {
             use_camera: 1,
             time: 0,
             layer: 0,
+            pick_id: 0,
+            user_data: [0.0; 4],
         }
     }
 }
 
 impl BufferLayout for ImageVertex {
     fn attributes() -> Vec<wgpu::VertexAttribute> {
-        wgpu::vertex_attr_array![1 => Float32x3, 2 => Float32x2, 3 => Float32x4, 4 => Uint32, 5 => Float32x2, 6 => Uint32, 7 => Uint32,8 => Uint32, 9 => Sint32 ]
+        wgpu::vertex_attr_array![1 => Float32x3, 2 => Float32x2, 3 => Float32x4, 4 => Uint32, 5 => Float32x2, 6 => Uint32, 7 => Uint32,8 => Uint32, 9 => Sint32, 10 => Uint32, 11 => Float32x4 ]
             .to_vec()
     }
 
@@ -58,6 +64,6 @@ impl BufferLayout for ImageVertex {
     }
 
     fn stride() -> usize {
-        std::mem::size_of::<[f32; 16]>()
+        std::mem::size_of::<[f32; 21]>()
     }
 }
@@ -0,0 +1,663 @@
+use crate::{
+    Allocation, AscendingError, Bounds, Color, GpuRenderer, Image,
+    ImageRenderer, ItemGridWidget, MessageRouter, Rect, RectRenderer, Text,
+    TextAtlas, TextRenderer, UiSound, Vec2, Vec3, Vec4, WidgetHandle,
+    WidgetInput, WidgetSounds,
+};
+use cosmic_text::{Attrs, Metrics};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Grid ids are unique so drags between grids know where they came from.
+static NEXT_GRID_ID: AtomicU32 = AtomicU32::new(0);
+
+/// An id for a widget's [`SlotRef`]s, unique among everything dragging
+/// through an [`ItemDrag`].
+pub(crate) fn next_grid_id() -> u32 {
+    NEXT_GRID_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Pixels the mouse has to move before a press becomes a drag.
+const DRAG_THRESHOLD: f32 = 3.0;
+
+/// A stack of one kind of item in a cell.
+#[derive(Clone, Debug)]
+pub struct GridItem {
+    /// The app's id for the kind of item. Stacks only merge with the same
+    /// id.
+    pub item: u32,
+    pub icon: Allocation,
+    pub count: u32,
+    /// Most that fit in one cell.
+    pub max_stack: u32,
+    pub tooltip: String,
+}
+
+/// A cell of some grid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SlotRef {
+    pub grid: u32,
+    pub slot: usize,
+}
+
+/// An item dragged from one cell onto another, possibly in another grid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ItemMove {
+    pub from: SlotRef,
+    pub to: SlotRef,
+    pub item: u32,
+    pub count: u32,
+}
+
+/// What an [`ItemGrid`] emits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ItemGridEvent {
+    /// A drop the target grid accepted. Apply it with [`ItemGrid::take`]
+    /// and [`ItemGrid::put`], both grids are left as they were.
+    Moved(ItemMove),
+    /// Shift clicked, usually to send the stack to another open grid.
+    QuickMove(SlotRef),
+}
+
+/// Decides if a grid accepts a drop, like an equipment slot only taking
+/// weapons.
+pub type DropValidator = Box<dyn Fn(&ItemMove, &GridItem) -> bool>;
+
+/// The item being dragged, shared by every grid it can move between.
+/// Owns the icon following the cursor.
+pub struct ItemDrag {
+    pub ghost: Image,
+    from: Option<SlotRef>,
+    item: Option<GridItem>,
+    start: Vec2,
+    moved: bool,
+}
+
+impl ItemDrag {
+    /// z should be in front of every grid.
+    pub fn new(renderer: &mut GpuRenderer, render_layer: u32, z: f32) -> Self {
+        let mut ghost = Image::new(None, renderer, render_layer);
+
+        ghost.use_camera = false;
+        ghost.pos.z = z;
+        ghost.color = Color::rgba(255, 255, 255, 200);
+
+        Self {
+            ghost,
+            from: None,
+            item: None,
+            start: Vec2::ZERO,
+            moved: false,
+        }
+    }
+
+    /// The cell the drag started from once the mouse moved far enough.
+    pub fn source(&self) -> Option<SlotRef> {
+        self.from.filter(|_| self.moved)
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.source().is_some()
+    }
+
+    /// Picks item up from a cell at the press position. It only counts as
+    /// a drag once the mouse moves.
+    pub(crate) fn begin(&mut self, from: SlotRef, item: GridItem, start: Vec2) {
+        self.from = Some(from);
+        self.item = Some(item);
+        self.start = start;
+        self.moved = false;
+    }
+
+    /// Ends the drag, returning where it came from and what was dragged if
+    /// it got far enough.
+    pub(crate) fn drop_item(&mut self) -> Option<(SlotRef, GridItem)> {
+        let from = self.source();
+        let item = self.item.take();
+
+        self.cancel();
+        from.zip(item)
+    }
+
+    pub fn cancel(&mut self) {
+        self.from = None;
+        self.item = None;
+        self.moved = false;
+    }
+
+    /// Call after every grid's input. Ends drags dropped outside any grid
+    /// and moves the icon to the cursor.
+    pub fn update(&mut self, input: &WidgetInput, cell_size: Vec2) {
+        if !input.mouse_down {
+            self.cancel();
+            return;
+        }
+
+        let (Some(pos), Some(item)) = (input.mouse_pos, &self.item) else {
+            return;
+        };
+
+        if !self.moved && pos.distance(self.start) > DRAG_THRESHOLD {
+            self.moved = true;
+        }
+
+        let (width, height) = item.icon.size();
+
+        self.ghost.texture = Some(item.icon);
+        self.ghost.uv = Vec4::new(0.0, 0.0, width as f32, height as f32);
+        self.ghost.hw = cell_size;
+        self.ghost.pos = (pos - cell_size / 2.0).extend(self.ghost.pos.z);
+        self.ghost.changed = true;
+    }
+
+    /// Frees the icon's buffer.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        self.ghost.unload(renderer);
+    }
+}
+
+struct Cell {
+    background: Rect,
+    icon: Image,
+    count: Text,
+}
+
+/// A fixed grid of item cells for inventories, chests and shops. Shows each
+/// stack's icon from the atlas and count, a tooltip over the hovered cell,
+/// and lets items be dragged between grids sharing an [`ItemDrag`].
+///
+/// Cells go left to right from the top row. Placed in render space like
+/// the [`crate::Spinner`]. Events are emitted through the router when a
+/// handle is set.
+pub struct ItemGrid {
+    pub handle: Option<WidgetHandle<ItemGridWidget>>,
+    pub sounds: WidgetSounds,
+    /// Called before accepting a drop onto this grid. None accepts all.
+    pub validator: Option<DropValidator>,
+    pub cell_color: Color,
+    pub hover_color: Color,
+    pub border_color: Color,
+    pub count_color: Color,
+    pub tooltip_background: Rect,
+    pub tooltip: Text,
+    id: u32,
+    columns: usize,
+    rows: usize,
+    cell_size: Vec2,
+    spacing: f32,
+    position: Vec3,
+    slots: Vec<Option<GridItem>>,
+    cells: Vec<Cell>,
+    hovered: Option<usize>,
+    was_down: bool,
+    pending_sounds: Vec<UiSound>,
+    changed: bool,
+}
+
+impl ItemGrid {
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        columns: usize,
+        rows: usize,
+        render_layer: u32,
+    ) -> Self {
+        let cells = (0..columns * rows)
+            .map(|_| {
+                let count = Text::new(
+                    renderer,
+                    Some(Metrics::new(12.0, 14.0)),
+                    Vec3::ZERO,
+                    Vec2::ZERO,
+                );
+
+                Cell {
+                    background: Rect::new(renderer, render_layer),
+                    icon: Image::new(None, renderer, render_layer),
+                    count,
+                }
+            })
+            .collect();
+        let mut tooltip_background = Rect::new(renderer, render_layer);
+        let mut tooltip = Text::new(
+            renderer,
+            Some(Metrics::new(14.0, 16.0)),
+            Vec3::ZERO,
+            Vec2::ZERO,
+        );
+
+        tooltip_background
+            .set_color(Color::rgba(20, 20, 20, 235))
+            .set_border_width(1.0)
+            .set_border_color(Color::rgba(120, 120, 120, 255))
+            .set_radius(3.0);
+        tooltip.set_default_color(Color::rgba(230, 230, 230, 255));
+
+        Self {
+            handle: None,
+            sounds: WidgetSounds::default(),
+            validator: None,
+            cell_color: Color::rgba(35, 35, 35, 230),
+            hover_color: Color::rgba(65, 65, 65, 240),
+            border_color: Color::rgba(90, 90, 90, 255),
+            count_color: Color::rgba(255, 255, 255, 255),
+            tooltip_background,
+            tooltip,
+            id: next_grid_id(),
+            columns,
+            rows,
+            cell_size: Vec2::splat(40.0),
+            spacing: 4.0,
+            position: Vec3::ZERO,
+            slots: vec![None; columns * rows],
+            cells,
+            hovered: None,
+            was_down: false,
+            pending_sounds: Vec::new(),
+            changed: true,
+        }
+    }
+
+    /// The id in this grid's [`SlotRef`]s.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cell_size(&self) -> Vec2 {
+        self.cell_size
+    }
+
+    /// Bottom left corner and z.
+    pub fn set_position(&mut self, position: Vec3) -> &mut Self {
+        self.position = position;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_cell_size(&mut self, size: Vec2, spacing: f32) -> &mut Self {
+        self.cell_size = size;
+        self.spacing = spacing;
+        self.changed = true;
+        self
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let step = self.cell_size + self.spacing;
+        let size =
+            Vec2::new(step.x * self.columns as f32, step.y * self.rows as f32)
+                - self.spacing;
+
+        Bounds::new(
+            self.position.x,
+            self.position.y,
+            self.position.x + size.x,
+            self.position.y + size.y,
+        )
+    }
+
+    pub fn slot(&self, slot: usize) -> Option<&GridItem> {
+        self.slots.get(slot).and_then(Option::as_ref)
+    }
+
+    pub fn slots(&self) -> &[Option<GridItem>] {
+        &self.slots
+    }
+
+    /// Replaces the cell's stack and returns the old one.
+    pub fn set_slot(
+        &mut self,
+        slot: usize,
+        item: Option<GridItem>,
+    ) -> Option<GridItem> {
+        let cell = self.slots.get_mut(slot)?;
+
+        self.changed = true;
+        std::mem::replace(cell, item)
+    }
+
+    pub fn take(&mut self, slot: usize) -> Option<GridItem> {
+        self.set_slot(slot, None)
+    }
+
+    /// Puts a stack in a cell. Merges into a stack of the same item as far
+    /// as it fits, otherwise swaps. Returns what is left over or was
+    /// swapped out.
+    pub fn put(&mut self, slot: usize, mut item: GridItem) -> Option<GridItem> {
+        let Some(cell) = self.slots.get_mut(slot) else {
+            return Some(item);
+        };
+
+        self.changed = true;
+
+        match cell {
+            Some(existing) if existing.item == item.item => {
+                let moved = item
+                    .count
+                    .min(existing.max_stack.saturating_sub(existing.count));
+
+                existing.count += moved;
+                item.count -= moved;
+                (item.count > 0).then_some(item)
+            }
+            _ => cell.replace(item),
+        }
+    }
+
+    /// First empty cell.
+    pub fn first_free(&self) -> Option<usize> {
+        self.slots.iter().position(Option::is_none)
+    }
+
+    /// The cell under pos.
+    pub fn slot_at(&self, pos: Vec2) -> Option<usize> {
+        (0..self.slots.len()).find(|slot| self.cell_bounds(*slot).contains(pos))
+    }
+
+    pub fn hovered(&self) -> Option<usize> {
+        self.hovered
+    }
+
+    /// Applies a frame of input. Call for every grid sharing drag, then
+    /// [`ItemDrag::update`]. Returns the event, which is also emitted if a
+    /// handle is set.
+    pub fn handle_input<M: 'static>(
+        &mut self,
+        input: &WidgetInput,
+        drag: &mut ItemDrag,
+        router: &mut MessageRouter<M>,
+    ) -> Option<ItemGridEvent> {
+        let pressed = input.mouse_down && !self.was_down;
+        let released = !input.mouse_down && self.was_down;
+        let hovered = input.mouse_pos.and_then(|pos| self.slot_at(pos));
+
+        self.was_down = input.mouse_down;
+
+        if hovered != self.hovered {
+            if hovered.is_some_and(|slot| self.slot(slot).is_some()) {
+                self.pending_sounds.push(UiSound::Hover);
+            }
+
+            self.hovered = hovered;
+            self.changed = true;
+        }
+
+        let mut event = None;
+
+        match hovered {
+            Some(slot) if pressed && self.slot(slot).is_some() => {
+                let from = SlotRef {
+                    grid: self.id,
+                    slot,
+                };
+
+                self.pending_sounds.push(UiSound::Click);
+
+                if input.shift {
+                    event = Some(ItemGridEvent::QuickMove(from));
+                } else if let Some(item) = self.slot(slot).cloned() {
+                    drag.begin(from, item, input.mouse_pos.unwrap_or_default());
+                }
+            }
+            Some(slot) if released => {
+                event = self.drop_onto(slot, drag).map(ItemGridEvent::Moved);
+            }
+            _ => {}
+        }
+
+        for sound in self.pending_sounds.drain(..) {
+            router.play_sound(self.sounds.cue(sound));
+        }
+
+        if let (Some(event), Some(handle)) = (event, self.handle) {
+            router.emit(handle, event);
+        }
+
+        event
+    }
+
+    /// The move for dropping the dragged item on slot, if it's allowed.
+    fn drop_onto(
+        &mut self,
+        slot: usize,
+        drag: &mut ItemDrag,
+    ) -> Option<ItemMove> {
+        let (from, item) = drag.drop_item()?;
+        let to = SlotRef {
+            grid: self.id,
+            slot,
+        };
+
+        if from == to {
+            return None;
+        }
+
+        let movement = ItemMove {
+            from,
+            to,
+            item: item.item,
+            count: item.count,
+        };
+        let accepted = self
+            .validator
+            .as_ref()
+            .is_none_or(|validator| validator(&movement, &item));
+
+        if accepted {
+            self.pending_sounds.push(UiSound::Close);
+            Some(movement)
+        } else {
+            self.pending_sounds.push(UiSound::Error);
+            None
+        }
+    }
+
+    fn cell_bounds(&self, slot: usize) -> Bounds {
+        let step = self.cell_size + self.spacing;
+        let column = (slot % self.columns) as f32;
+        // Rows go down from the top, render space goes up.
+        let row = (self.rows - 1 - slot / self.columns) as f32;
+        let x = self.position.x + column * step.x;
+        let y = self.position.y + row * step.y;
+
+        Bounds::new(x, y, x + self.cell_size.x, y + self.cell_size.y)
+    }
+
+    /// Lays out the cells and tooltip if anything changed.
+    fn refresh(&mut self, renderer: &mut GpuRenderer) {
+        if !self.changed {
+            return;
+        }
+
+        let z = self.position.z;
+        let size = self.cell_size;
+
+        for slot in 0..self.cells.len() {
+            let bounds = self.cell_bounds(slot);
+            let corner = Vec2::new(bounds.left, bounds.bottom);
+            let color = if self.hovered == Some(slot) {
+                self.hover_color
+            } else {
+                self.cell_color
+            };
+            let item = self.slots[slot].as_ref();
+            let cell = &mut self.cells[slot];
+
+            cell.background
+                .set_position(corner.extend(z))
+                .set_size(size)
+                .set_color(color)
+                .set_border_width(1.0)
+                .set_border_color(self.border_color);
+
+            // lower Z renders over the cell.
+            cell.icon.texture = item.map(|item| item.icon);
+            cell.icon.use_camera = false;
+            cell.icon.pos = (corner + 2.0).extend(z - 0.01);
+            cell.icon.hw = size - 4.0;
+
+            if let Some(icon) = item.map(|item| item.icon) {
+                let (width, height) = icon.size();
+
+                cell.icon.uv = Vec4::new(0.0, 0.0, width as f32, height as f32);
+            }
+
+            cell.icon.changed = true;
+
+            let count = match item {
+                Some(item) if item.count > 1 => item.count.to_string(),
+                _ => String::new(),
+            };
+            let line_height = cell.count.buffer.metrics().line_height;
+
+            cell.count
+                .set_buffer_size(renderer, size.x as i32, line_height as i32)
+                .set_default_color(self.count_color)
+                .set_text(renderer, &count, Attrs::new())
+                .set_position(Vec3::new(
+                    corner.x + 3.0,
+                    corner.y + 2.0,
+                    z - 0.02,
+                ));
+            cell.count.size = Vec2::new(size.x, line_height);
+        }
+
+        let tooltip = self.hovered.and_then(|slot| {
+            let item = self.slot(slot)?;
+
+            Some((self.cell_bounds(slot), item.tooltip.clone()))
+        });
+
+        if let Some((bounds, tooltip)) = tooltip {
+            let line_height = self.tooltip.buffer.metrics().line_height;
+            let lines = tooltip.lines().count().max(1) as f32;
+            let text_size = Vec2::new(200.0, line_height * lines);
+            let corner =
+                Vec2::new(bounds.right + 6.0, bounds.top - text_size.y - 8.0);
+
+            self.tooltip_background
+                .set_position(corner.extend(z - 0.05))
+                .set_size(text_size + 8.0);
+            self.tooltip
+                .set_buffer_size(
+                    renderer,
+                    text_size.x as i32,
+                    text_size.y as i32,
+                )
+                .set_text(renderer, &tooltip, Attrs::new())
+                .set_position((corner + 4.0).extend(z - 0.06));
+            self.tooltip.size = text_size;
+        }
+
+        self.changed = false;
+    }
+
+    fn tooltip_shown(&self) -> bool {
+        self.hovered
+            .and_then(|slot| self.slot(slot))
+            .is_some_and(|item| !item.tooltip.is_empty())
+    }
+
+    /// Frees the cells' and tooltip's buffers.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        for cell in self.cells {
+            cell.background.unload(renderer);
+            cell.icon.unload(renderer);
+            cell.count.unload(renderer);
+        }
+
+        self.tooltip_background.unload(renderer);
+        self.tooltip.unload(renderer);
+    }
+}
+
+impl RectRenderer {
+    pub fn item_grid_update(
+        &mut self,
+        grid: &mut ItemGrid,
+        drag: &ItemDrag,
+        renderer: &mut GpuRenderer,
+    ) {
+        grid.refresh(renderer);
+
+        for cell in &mut grid.cells {
+            self.rect_update(&mut cell.background, renderer);
+        }
+
+        if grid.tooltip_shown() && !drag.is_dragging() {
+            self.rect_update(&mut grid.tooltip_background, renderer);
+        }
+    }
+}
+
+impl ImageRenderer {
+    /// The dragged stack stays dimmed in its cell until dropped.
+    pub fn item_grid_update(
+        &mut self,
+        grid: &mut ItemGrid,
+        drag: &ItemDrag,
+        renderer: &mut GpuRenderer,
+    ) {
+        grid.refresh(renderer);
+
+        let dragged = drag
+            .source()
+            .filter(|source| source.grid == grid.id)
+            .map(|source| source.slot);
+
+        for (slot, cell) in grid.cells.iter_mut().enumerate() {
+            if cell.icon.texture.is_none() {
+                continue;
+            }
+
+            let alpha = if dragged == Some(slot) { 90 } else { 255 };
+
+            if cell.icon.color.a() != alpha {
+                cell.icon.color = Color::rgba(255, 255, 255, alpha);
+                cell.icon.changed = true;
+            }
+
+            self.image_update(&mut cell.icon, renderer);
+        }
+    }
+
+    /// Adds the icon following the cursor while dragging.
+    pub fn item_drag_update(
+        &mut self,
+        drag: &mut ItemDrag,
+        renderer: &mut GpuRenderer,
+    ) {
+        if drag.is_dragging() {
+            self.image_update(&mut drag.ghost, renderer);
+        }
+    }
+}
+
+impl TextRenderer {
+    pub fn item_grid_update(
+        &mut self,
+        grid: &mut ItemGrid,
+        drag: &ItemDrag,
+        atlas: &mut TextAtlas,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        grid.refresh(renderer);
+
+        for (slot, cell) in grid.cells.iter_mut().enumerate() {
+            if grid.slots[slot].as_ref().is_some_and(|item| item.count > 1) {
+                self.text_update(&mut cell.count, atlas, renderer)?;
+            }
+        }
+
+        if grid.tooltip_shown() && !drag.is_dragging() {
+            self.text_update(&mut grid.tooltip, atlas, renderer)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,28 +1,214 @@
 #![allow(clippy::extra_unused_type_parameters)]
+// Lets derived code name the crate the same way from inside and out.
+extern crate self as graphics;
+
+#[cfg(all(feature = "sprites", feature = "text", feature = "shapes"))]
+mod action_bar;
+pub mod advanced;
 mod atlas;
+mod autosave;
+mod background;
+#[cfg(all(feature = "text", feature = "shapes"))]
+mod canvas;
+#[cfg(feature = "shapes")]
+mod cast_bar;
+#[cfg(all(feature = "text", feature = "shapes"))]
+mod chat_box;
+mod clipboard;
+#[cfg(all(feature = "text", feature = "shapes"))]
+mod console;
+#[cfg(feature = "shapes")]
+mod cooldown_overlay;
+mod damage;
+#[cfg(feature = "dialogs")]
+mod dialogs;
+#[cfg(feature = "dialogue")]
+mod dialogue;
+mod display;
+#[cfg(feature = "shapes")]
+mod drop_targets;
+#[cfg(all(feature = "maps", feature = "text", feature = "shapes"))]
+mod editor;
 mod error;
+#[cfg(feature = "text")]
 mod font;
+#[cfg(feature = "frame_graph")]
+mod frame_graph;
+#[cfg(feature = "shapes")]
+mod gizmo;
+#[cfg(feature = "golden")]
+mod golden;
+#[cfg(feature = "sprites")]
 mod images;
+#[cfg(all(feature = "sprites", feature = "text", feature = "shapes"))]
+mod item_grid;
+#[cfg(feature = "lights")]
 mod lights;
+mod loading;
+#[cfg(all(feature = "text", feature = "shapes"))]
+mod localization;
+#[cfg(feature = "maps")]
 mod maps;
+#[cfg(feature = "shapes")]
 mod mesh2d;
+mod messages;
+#[cfg(all(feature = "sprites", feature = "shapes"))]
+mod minimap;
+#[cfg(feature = "sprites")]
+mod panel_cache;
+#[cfg(feature = "sprites")]
+mod pick_buffer;
+#[cfg(any(
+    feature = "sprites",
+    feature = "maps",
+    all(feature = "text", feature = "shapes")
+))]
+mod picking;
+mod plugins;
+mod pools;
+pub mod prelude;
+#[cfg(all(feature = "text", feature = "shapes"))]
+mod radial_menu;
+#[cfg(all(feature = "text", feature = "shapes"))]
+mod reconcile;
+#[cfg(feature = "shapes")]
+mod rects;
+mod render_target;
+mod rng;
+mod runner;
+#[cfg(feature = "scripting")]
+mod scripting;
+#[cfg(all(feature = "sprites", feature = "shapes"))]
+mod selection;
+pub mod shaders;
+#[cfg(feature = "software")]
+mod software;
+#[cfg(all(feature = "text", feature = "shapes"))]
+mod spinner;
+mod streaming;
 mod systems;
 mod textures;
 mod tilesheet;
+mod timeline;
+mod transitions;
+mod ui_sounds;
+mod upscale;
+#[cfg(all(feature = "text", feature = "shapes"))]
+mod vec_editor;
+#[cfg(feature = "video")]
+mod video;
+#[cfg(feature = "maps")]
+mod water;
+mod widget_input;
+#[cfg(all(feature = "text", feature = "shapes"))]
+mod world_text;
+#[cfg(all(feature = "text", feature = "shapes"))]
+mod world_ui;
 
+#[cfg(all(feature = "sprites", feature = "text", feature = "shapes"))]
+pub use action_bar::*;
 pub use atlas::*;
+pub use autosave::*;
+pub use background::*;
+#[cfg(all(feature = "text", feature = "shapes"))]
+pub use canvas::*;
+#[cfg(feature = "shapes")]
+pub use cast_bar::*;
+#[cfg(all(feature = "text", feature = "shapes"))]
+pub use chat_box::*;
+pub use clipboard::*;
+#[cfg(all(feature = "text", feature = "shapes"))]
+pub use console::*;
+#[cfg(feature = "shapes")]
+pub use cooldown_overlay::*;
 pub use cosmic_text::Color;
+pub use damage::*;
+#[cfg(feature = "dialogs")]
+pub use dialogs::*;
+#[cfg(feature = "dialogue")]
+pub use dialogue::*;
+pub use display::*;
+#[cfg(feature = "shapes")]
+pub use drop_targets::*;
+#[cfg(all(feature = "maps", feature = "text", feature = "shapes"))]
+pub use editor::*;
 pub use error::*;
+#[cfg(feature = "text")]
 pub use font::*;
+#[cfg(feature = "frame_graph")]
+pub use frame_graph::*;
+#[cfg(feature = "shapes")]
+pub use gizmo::*;
+#[cfg(feature = "golden")]
+pub use golden::*;
+#[cfg(feature = "sprites")]
 pub use images::*;
+#[cfg(all(feature = "sprites", feature = "text", feature = "shapes"))]
+pub use item_grid::*;
+#[cfg(feature = "lights")]
 pub use lights::*;
+pub use loading::*;
+#[cfg(all(feature = "text", feature = "shapes"))]
+pub use localization::*;
+#[cfg(feature = "maps")]
 pub use maps::*;
+#[cfg(feature = "shapes")]
 pub use mesh2d::*;
+pub use messages::*;
+#[cfg(all(feature = "sprites", feature = "shapes"))]
+pub use minimap::*;
+#[cfg(feature = "sprites")]
+pub use panel_cache::*;
+#[cfg(feature = "sprites")]
+pub use pick_buffer::*;
+#[cfg(any(
+    feature = "sprites",
+    feature = "maps",
+    all(feature = "text", feature = "shapes")
+))]
+pub use picking::*;
+pub use plugins::*;
+pub use pools::*;
+#[cfg(all(feature = "text", feature = "shapes"))]
+pub use radial_menu::*;
+#[cfg(all(feature = "text", feature = "shapes"))]
+pub use reconcile::*;
+#[cfg(feature = "shapes")]
+pub use rects::*;
+pub use render_target::*;
+pub use rng::*;
+pub use runner::*;
+#[cfg(feature = "scripting")]
+pub use scripting::*;
+#[cfg(all(feature = "sprites", feature = "shapes"))]
+pub use selection::*;
+#[cfg(feature = "software")]
+pub use software::*;
+#[cfg(all(feature = "text", feature = "shapes"))]
+pub use spinner::*;
+pub use streaming::*;
 pub use systems::*;
 pub use textures::*;
 pub use tilesheet::*;
+pub use timeline::*;
+pub use transitions::*;
+pub use ui_sounds::*;
+pub use upscale::*;
+#[cfg(all(feature = "text", feature = "shapes"))]
+pub use vec_editor::*;
+#[cfg(feature = "video")]
+pub use video::*;
+#[cfg(feature = "maps")]
+pub use water::*;
+pub use widget_input::*;
+#[cfg(all(feature = "text", feature = "shapes"))]
+pub use world_text::*;
+#[cfg(all(feature = "text", feature = "shapes"))]
+pub use world_ui::*;
 
+pub use derive::EngineUniform;
 pub use glam::{Vec2, Vec3, Vec4};
+pub use wgpu;
 
 #[cfg(feature = "iced")]
 pub use iced_renderer;
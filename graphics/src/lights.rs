@@ -1,11 +1,13 @@
+mod clusters;
 mod lights;
-mod pipeline;
+pub(crate) mod pipeline;
 mod render;
-mod uniforms;
-mod vertex;
+pub(crate) mod uniforms;
+pub(crate) mod vertex;
 
+pub use clusters::*;
 pub use lights::*;
-pub use pipeline::*;
+pub(crate) use pipeline::*;
 pub use render::*;
-pub use uniforms::*;
-pub use vertex::*;
+pub(crate) use uniforms::*;
+pub(crate) use vertex::*;
@@ -0,0 +1,172 @@
+use crate::{GpuRenderer, Lights, Vec2};
+
+/// Size in pixels of the screen tiles lights are binned into.
+pub const LIGHT_TILE_SIZE: u32 = 32;
+
+/// Bins lights into screen tiles on the CPU so each pixel only checks the
+/// lights that can reach it. Only used with storage buffers.
+pub(crate) struct LightClusters {
+    /// tile size, columns and rows followed by a [offset, area count,
+    /// directional count, 0] entry per tile.
+    pub(crate) tiles: wgpu::Buffer,
+    /// Light indices referenced by the tiles.
+    pub(crate) indices: wgpu::Buffer,
+    tiles_capacity: usize,
+    indices_capacity: usize,
+    size: [u32; 2],
+    /// Reused between builds to save on allocations.
+    area_bins: Vec<Vec<u32>>,
+    dir_bins: Vec<Vec<u32>>,
+}
+
+impl LightClusters {
+    pub(crate) fn new(renderer: &GpuRenderer) -> Self {
+        Self {
+            tiles: Self::create_buffer(renderer, "Light tiles buffer", 64),
+            indices: Self::create_buffer(renderer, "Light indices buffer", 64),
+            tiles_capacity: 64,
+            indices_capacity: 64,
+            size: [0; 2],
+            area_bins: Vec::new(),
+            dir_bins: Vec::new(),
+        }
+    }
+
+    fn create_buffer(
+        renderer: &GpuRenderer,
+        label: &str,
+        capacity: usize,
+    ) -> wgpu::Buffer {
+        renderer.device().create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: (capacity * 16) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// if the screen size changed since the last build.
+    pub(crate) fn resized(&self, renderer: &GpuRenderer) -> bool {
+        self.size
+            != [renderer.size().width as u32, renderer.size().height as u32]
+    }
+
+    /// Rebuilds the tile lists. Returns true if the buffers had to be
+    /// recreated and the bind group needs rebuilding.
+    pub(crate) fn build(
+        &mut self,
+        lights: &Lights,
+        renderer: &GpuRenderer,
+    ) -> bool {
+        self.size =
+            [renderer.size().width as u32, renderer.size().height as u32];
+
+        let columns = self.size[0].div_ceil(LIGHT_TILE_SIZE).max(1);
+        let rows = self.size[1].div_ceil(LIGHT_TILE_SIZE).max(1);
+        let count = (columns * rows) as usize;
+
+        self.area_bins.resize_with(count, Vec::new);
+        self.dir_bins.resize_with(count, Vec::new);
+        self.area_bins.iter_mut().for_each(Vec::clear);
+        self.dir_bins.iter_mut().for_each(Vec::clear);
+
+        // Same order the lights are written to the buffers in.
+        for (i, (_key, light)) in lights.area_lights.iter().enumerate() {
+            // Animation moves the edge by 1 and dither fades out past it.
+            let radius = light.max_distance + 1.0 + light.dither.abs() * 8.0;
+
+            Self::bin(&mut self.area_bins, columns, rows, light.pos, radius, i);
+        }
+
+        for (i, (_key, light)) in lights.directional_lights.iter().enumerate() {
+            let radius = light.max_distance + 1.0 + light.dither.abs() * 8.0;
+
+            Self::bin(&mut self.dir_bins, columns, rows, light.pos, radius, i);
+        }
+
+        let mut tiles: Vec<u32> = Vec::with_capacity(4 + count * 4);
+        let mut indices: Vec<u32> = Vec::new();
+
+        tiles.extend_from_slice(&[LIGHT_TILE_SIZE, columns, rows, 0]);
+
+        for (areas, dirs) in self.area_bins.iter().zip(&self.dir_bins) {
+            tiles.extend_from_slice(&[
+                indices.len() as u32,
+                areas.len() as u32,
+                dirs.len() as u32,
+                0,
+            ]);
+            indices.extend_from_slice(areas);
+            indices.extend_from_slice(dirs);
+        }
+
+        let mut recreated = false;
+
+        // Capacities are in 16 byte entries.
+        if tiles.len() / 4 > self.tiles_capacity {
+            self.tiles_capacity = (tiles.len() / 4).next_power_of_two();
+            self.tiles = Self::create_buffer(
+                renderer,
+                "Light tiles buffer",
+                self.tiles_capacity,
+            );
+            recreated = true;
+        }
+
+        let index_entries = indices.len().div_ceil(4);
+
+        if index_entries > self.indices_capacity {
+            self.indices_capacity = index_entries.next_power_of_two();
+            self.indices = Self::create_buffer(
+                renderer,
+                "Light indices buffer",
+                self.indices_capacity,
+            );
+            recreated = true;
+        }
+
+        renderer.queue().write_buffer(
+            &self.tiles,
+            0,
+            bytemuck::cast_slice(&tiles),
+        );
+
+        if !indices.is_empty() {
+            renderer.queue().write_buffer(
+                &self.indices,
+                0,
+                bytemuck::cast_slice(&indices),
+            );
+        }
+
+        recreated
+    }
+
+    fn bin(
+        bins: &mut [Vec<u32>],
+        columns: u32,
+        rows: u32,
+        pos: Vec2,
+        radius: f32,
+        index: usize,
+    ) {
+        let tile = LIGHT_TILE_SIZE as f32;
+        let min_x = ((pos.x - radius) / tile).floor().max(0.0) as u32;
+        let min_y = ((pos.y - radius) / tile).floor().max(0.0) as u32;
+        let max_x = ((pos.x + radius) / tile).floor();
+        let max_y = ((pos.y + radius) / tile).floor();
+
+        if max_x < 0.0 || max_y < 0.0 {
+            return;
+        }
+
+        let max_x = (max_x as u32).min(columns - 1);
+        let max_y = (max_y as u32).min(rows - 1);
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                bins[(y * columns + x) as usize].push(index as u32);
+            }
+        }
+    }
+}
@@ -1,15 +1,130 @@
 use std::mem;
 
 use crate::{
-    AreaLightRaw, Color, DirectionalLightRaw, DrawOrder, GpuRenderer, Index,
-    LightsVertex, OrderedIndex, Vec2, Vec3, Vec4,
+    hash_u32, Allocation, AreaLightRaw, Color, DirectionalLightRaw, DrawOrder,
+    GpuRenderer, Index, LightCookieRaw, LightsVertex, OrderedIndex, Vec2, Vec3,
+    Vec4,
 };
 use slab::Slab;
 use wgpu::util::align_to;
 
+/// Light limits when storage buffers are not supported and the lights are
+/// stored in uniform arrays instead.
 pub const MAX_AREA_LIGHTS: usize = 2_000;
 pub const MAX_DIR_LIGHTS: usize = 1_365;
 
+/// A texture from an atlas that masks a light's shape, like a flashlight
+/// cone, a window frame or a flicker pattern. White lets the light through
+/// and black blocks it. Area lights stretch it over the light's radius and
+/// directional lights project it along their cone.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct LightCookie {
+    /// x, y, width, height within the atlas layer.
+    pub rect: (u32, u32, u32, u32),
+    pub layer: u32,
+    /// In degrees. Only used by area lights.
+    pub rotation: f32,
+}
+
+impl LightCookie {
+    pub fn from_allocation<Data: Copy + Default>(
+        allocation: &Allocation<Data>,
+    ) -> Self {
+        Self {
+            rect: allocation.rect(),
+            layer: allocation.layer as u32,
+            rotation: 0.0,
+        }
+    }
+
+    pub fn set_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    fn to_raw(cookie: Option<&LightCookie>) -> LightCookieRaw {
+        match cookie {
+            Some(cookie) => LightCookieRaw {
+                rect: [
+                    cookie.rect.0 | (cookie.rect.1 << 16),
+                    cookie.rect.2 | (cookie.rect.3 << 16),
+                ],
+                layer: cookie.layer as i32,
+                rotation: cookie.rotation,
+            },
+            None => LightCookieRaw {
+                layer: -1,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Brightness animation evaluated on the GPU from the system time, so the
+/// light buffers don't need updating every frame. The speed is the light's
+/// anim_speed.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum LightAnimation {
+    #[default]
+    None,
+    /// Smoothly dims by up to amount (0.0..=1.0) and back.
+    Pulse { amount: f32 },
+    /// Randomly dims by up to amount (0.0..=1.0), like a torch.
+    Flicker { amount: f32 },
+    /// On for duty (0.0..=1.0) of each cycle and off for the rest.
+    Strobe { duty: f32 },
+}
+
+impl LightAnimation {
+    /// Packs the animation in with the wobble flag and the light's seed.
+    fn to_raw(self, animate: bool, seed: u32) -> (u32, f32) {
+        let (kind, amount) = match self {
+            LightAnimation::None => (0, 0.0),
+            LightAnimation::Pulse { amount } => (1, amount),
+            LightAnimation::Flicker { amount } => (2, amount),
+            LightAnimation::Strobe { duty } => (3, duty),
+        };
+
+        (
+            u32::from(animate) | (kind << 8) | (seed << 16),
+            amount.clamp(0.0, 1.0),
+        )
+    }
+}
+
+/// Approximate color of a black body at kelvin, for warm torch light
+/// (~1900K) through daylight (~6500K) to cold blue (~10000K).
+pub fn color_temperature(kelvin: f32, alpha: u8) -> Color {
+    let temp = kelvin.clamp(1000.0, 40000.0) / 100.0;
+
+    let red = if temp <= 66.0 {
+        255.0
+    } else {
+        329.69873 * (temp - 60.0).powf(-0.133_204_76)
+    };
+
+    let green = if temp <= 66.0 {
+        99.4708 * temp.ln() - 161.11957
+    } else {
+        288.12216 * (temp - 60.0).powf(-0.075_514_85)
+    };
+
+    let blue = if temp >= 66.0 {
+        255.0
+    } else if temp <= 19.0 {
+        0.0
+    } else {
+        138.51773 * (temp - 10.0).ln() - 305.0448
+    };
+
+    Color::rgba(
+        red.clamp(0.0, 255.0) as u8,
+        green.clamp(0.0, 255.0) as u8,
+        blue.clamp(0.0, 255.0) as u8,
+        alpha,
+    )
+}
+
 pub struct AreaLight {
     pub pos: Vec2,
     pub color: Color,
@@ -17,17 +132,22 @@ pub struct AreaLight {
     pub anim_speed: f32,
     pub dither: f32,
     pub animate: bool,
+    pub animation: LightAnimation,
+    pub cookie: Option<LightCookie>,
 }
 
 impl AreaLight {
-    fn to_raw(&self) -> AreaLightRaw {
+    fn to_raw(&self, seed: u32) -> AreaLightRaw {
+        let (animate, anim_amount) = self.animation.to_raw(self.animate, seed);
+
         AreaLightRaw {
             pos: self.pos.to_array(),
             color: self.color.0,
             max_distance: self.max_distance,
             dither: self.dither,
             anim_speed: self.anim_speed,
-            animate: u32::from(self.animate),
+            animate,
+            anim_amount,
         }
     }
 }
@@ -43,15 +163,20 @@ pub struct DirectionalLight {
     pub fade_distance: f32,
     pub edge_fade_distance: f32,
     pub animate: bool,
+    pub animation: LightAnimation,
+    pub cookie: Option<LightCookie>,
 }
 
 impl DirectionalLight {
-    fn to_raw(&self) -> DirectionalLightRaw {
+    fn to_raw(&self, seed: u32) -> DirectionalLightRaw {
+        let (animate, anim_amount) = self.animation.to_raw(self.animate, seed);
+
         DirectionalLightRaw {
             pos: self.pos.to_array(),
             color: self.color.0,
             max_distance: self.max_distance,
-            animate: u32::from(self.animate),
+            animate,
+            anim_amount,
             max_width: self.max_width,
             anim_speed: self.anim_speed,
             dither: self.dither,
@@ -73,30 +198,63 @@ pub struct Lights {
     pub directional_lights: Slab<DirectionalLight>,
     pub area_count: u32,
     pub dir_count: u32,
+    /// Most lights that can be inserted. Only limited when storage
+    /// buffers are not supported.
+    pub max_area_lights: usize,
+    pub max_dir_lights: usize,
     /// if anything got updated we need to update the buffers too.
     pub changed: bool,
     pub directionals_changed: bool,
     pub areas_changed: bool,
+    /// Mixed with each light's key so their flickers stay out of step.
+    seed: u32,
 }
 
 impl Lights {
     pub fn new(renderer: &mut GpuRenderer, render_layer: u32) -> Self {
+        let (max_area_lights, max_dir_lights) = if renderer.storage_supported()
+        {
+            (usize::MAX, usize::MAX)
+        } else {
+            (MAX_AREA_LIGHTS, MAX_DIR_LIGHTS)
+        };
+
         Self {
             world_color: Vec4::new(1.0, 1.0, 1.0, 0.0),
             enable_lights: false,
             store_id: renderer.new_buffer(),
             order: DrawOrder::default(),
             render_layer,
-            area_lights: Slab::with_capacity(MAX_AREA_LIGHTS),
-            directional_lights: Slab::with_capacity(MAX_DIR_LIGHTS),
+            area_lights: Slab::new(),
+            directional_lights: Slab::new(),
             area_count: 0,
             dir_count: 0,
+            max_area_lights,
+            max_dir_lights,
             changed: true,
             directionals_changed: true,
             areas_changed: true,
+            seed: 0,
         }
     }
 
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Sets the seed of the lights' animations, like one drawn from
+    /// [`crate::RngStream::Lights`].
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+        self.areas_changed = true;
+        self.directionals_changed = true;
+    }
+
+    /// 16 bit animation seed of a light, which fits above its flags.
+    fn light_seed(&self, key: usize, directional: bool) -> u32 {
+        hash_u32(&[key as u32, u32::from(directional)], self.seed) >> 16
+    }
+
     pub fn create_quad(&mut self, renderer: &mut GpuRenderer) {
         let instance = LightsVertex {
             world_color: self.world_color.to_array(),
@@ -115,7 +273,7 @@ impl Lights {
     }
 
     pub fn insert_area_light(&mut self, light: AreaLight) -> Option<usize> {
-        if self.area_lights.len() + 1 >= MAX_AREA_LIGHTS {
+        if self.area_lights.len() + 1 >= self.max_area_lights {
             return None;
         }
 
@@ -139,7 +297,7 @@ impl Lights {
         &mut self,
         light: DirectionalLight,
     ) -> Option<usize> {
-        if self.directional_lights.len() + 1 >= MAX_DIR_LIGHTS {
+        if self.directional_lights.len() + 1 >= self.max_dir_lights {
             return None;
         }
 
@@ -168,6 +326,8 @@ impl Lights {
         renderer: &mut GpuRenderer,
         areas: &mut wgpu::Buffer,
         dirs: &mut wgpu::Buffer,
+        area_cookies: &mut wgpu::Buffer,
+        dir_cookies: &mut wgpu::Buffer,
     ) -> OrderedIndex {
         // if pos or tex_pos or color changed.
         if self.changed {
@@ -177,11 +337,21 @@ impl Lights {
         if self.areas_changed {
             let area_alignment: usize =
                 align_to(mem::size_of::<AreaLightRaw>(), 32) as usize;
-            for (i, (_key, light)) in self.area_lights.iter().enumerate() {
+            for (i, (key, light)) in self.area_lights.iter().enumerate() {
                 renderer.queue().write_buffer(
                     areas,
                     (i * area_alignment) as wgpu::BufferAddress,
-                    bytemuck::bytes_of(&light.to_raw()),
+                    bytemuck::bytes_of(
+                        &light.to_raw(self.light_seed(key, false)),
+                    ),
+                );
+                renderer.queue().write_buffer(
+                    area_cookies,
+                    (i * mem::size_of::<LightCookieRaw>())
+                        as wgpu::BufferAddress,
+                    bytemuck::bytes_of(&LightCookie::to_raw(
+                        light.cookie.as_ref(),
+                    )),
                 );
             }
 
@@ -191,11 +361,19 @@ impl Lights {
         if self.directionals_changed {
             let dir_alignment: usize =
                 align_to(mem::size_of::<DirectionalLightRaw>(), 48) as usize;
-            for (i, (_key, dir)) in self.directional_lights.iter().enumerate() {
+            for (i, (key, dir)) in self.directional_lights.iter().enumerate() {
                 renderer.queue().write_buffer(
                     dirs,
                     (i * dir_alignment) as wgpu::BufferAddress,
-                    bytemuck::bytes_of(&dir.to_raw()),
+                    bytemuck::bytes_of(&dir.to_raw(self.light_seed(key, true))),
+                );
+                renderer.queue().write_buffer(
+                    dir_cookies,
+                    (i * mem::size_of::<LightCookieRaw>())
+                        as wgpu::BufferAddress,
+                    bytemuck::bytes_of(&LightCookie::to_raw(
+                        dir.cookie.as_ref(),
+                    )),
                 );
             }
 
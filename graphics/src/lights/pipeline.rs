@@ -1,9 +1,13 @@
 use crate::{
-    AreaLightLayout, BufferLayout, DirLightLayout, GpuDevice, LayoutStorage,
+    shaders, AreaLightLayout, AreaLightStorageLayout, BufferLayout,
+    DirLightLayout, DirLightStorageLayout, GpuDevice, LayoutStorage,
     LightsVertex, PipeLineLayout, StaticBufferObject, SystemLayout,
+    TextureLayout,
 };
 use bytemuck::{Pod, Zeroable};
 
+/// Lights read from fixed size uniform arrays. Used when the device can not
+/// read storage buffers from the fragment shader.
 #[repr(C)]
 #[derive(Clone, Copy, Hash, Pod, Zeroable)]
 pub struct LightRenderPipeline;
@@ -15,81 +19,115 @@ impl PipeLineLayout for LightRenderPipeline {
         layouts: &mut LayoutStorage,
         surface_format: wgpu::TextureFormat,
     ) -> wgpu::RenderPipeline {
-        let shader = gpu_device.device().create_shader_module(
-            wgpu::ShaderModuleDescriptor {
-                label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("../shaders/lightshader.wgsl").into(),
-                ),
-            },
-        );
+        create_light_pipeline(gpu_device, layouts, surface_format, false)
+    }
+}
 
-        let system_layout = layouts.create_layout(gpu_device, SystemLayout);
-        let area_light_layout =
-            layouts.create_layout(gpu_device, AreaLightLayout);
-        let dir_light_layout =
-            layouts.create_layout(gpu_device, DirLightLayout);
-        // Create the render pipeline.
-        gpu_device.device().create_render_pipeline(
-            &wgpu::RenderPipelineDescriptor {
-                label: Some("Lights render pipeline"),
-                layout: Some(&gpu_device.device().create_pipeline_layout(
-                    &wgpu::PipelineLayoutDescriptor {
-                        label: Some("render_pipeline_layout"),
-                        bind_group_layouts: &[
-                            &system_layout,
-                            &area_light_layout,
-                            &dir_light_layout,
-                        ],
-                        push_constant_ranges: &[],
-                    },
-                )),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vertex",
-                    buffers: &[
-                        wgpu::VertexBufferLayout {
-                            array_stride: StaticBufferObject::stride(),
-                            step_mode: wgpu::VertexStepMode::Vertex,
-                            attributes: &[
-                                StaticBufferObject::vertex_attribute(),
-                            ],
-                        },
-                        wgpu::VertexBufferLayout {
-                            array_stride: LightsVertex::stride() as u64,
-                            step_mode: wgpu::VertexStepMode::Instance,
-                            attributes: &LightsVertex::attributes(),
-                        },
+/// Lights read from storage buffers that grow with the light count.
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct LightStorageRenderPipeline;
+
+impl PipeLineLayout for LightStorageRenderPipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        create_light_pipeline(gpu_device, layouts, surface_format, true)
+    }
+}
+
+fn create_light_pipeline(
+    gpu_device: &mut GpuDevice,
+    layouts: &mut LayoutStorage,
+    surface_format: wgpu::TextureFormat,
+    storage: bool,
+) -> wgpu::RenderPipeline {
+    // The light arrays are declared separately so the rest of the shader
+    // is shared between the uniform and storage versions.
+    let shader = gpu_device.device().create_shader_module(
+        wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                shaders::light_shader(storage).into(),
+            ),
+        },
+    );
+
+    let system_layout = layouts.create_layout(gpu_device, SystemLayout);
+    let (area_light_layout, dir_light_layout) = if storage {
+        (
+            layouts.create_layout(gpu_device, AreaLightStorageLayout),
+            layouts.create_layout(gpu_device, DirLightStorageLayout),
+        )
+    } else {
+        (
+            layouts.create_layout(gpu_device, AreaLightLayout),
+            layouts.create_layout(gpu_device, DirLightLayout),
+        )
+    };
+    let texture_layout = layouts.create_layout(gpu_device, TextureLayout);
+    // Create the render pipeline.
+    gpu_device.device().create_render_pipeline(
+        &wgpu::RenderPipelineDescriptor {
+            label: Some("Lights render pipeline"),
+            layout: Some(&gpu_device.device().create_pipeline_layout(
+                &wgpu::PipelineLayoutDescriptor {
+                    label: Some("render_pipeline_layout"),
+                    bind_group_layouts: &[
+                        &system_layout,
+                        &area_light_layout,
+                        &dir_light_layout,
+                        &texture_layout,
                     ],
+                    push_constant_ranges: &[],
                 },
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: None,
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
-                },
-                depth_stencil: Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::LessEqual,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default(),
-                }),
-                multisample: wgpu::MultisampleState::default(),
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: "fragment",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: surface_format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                multiview: None,
+            )),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vertex",
+                buffers: &[
+                    wgpu::VertexBufferLayout {
+                        array_stride: StaticBufferObject::stride(),
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &[StaticBufferObject::vertex_attribute()],
+                    },
+                    wgpu::VertexBufferLayout {
+                        array_stride: LightsVertex::stride() as u64,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &LightsVertex::attributes(),
+                    },
+                ],
             },
-        )
-    }
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: gpu_device.depth_compare(),
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: gpu_device.multisample_state(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fragment",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        },
+    )
 }
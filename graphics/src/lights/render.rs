@@ -1,96 +1,297 @@
 use std::{iter, mem};
 
 use crate::{
-    AreaLightLayout, AreaLightRaw, AscendingError, DirLightLayout,
-    DirectionalLightRaw, GpuRenderer, InstanceBuffer, LightRenderPipeline,
-    Lights, LightsVertex, OrderedIndex, StaticBufferObject, MAX_AREA_LIGHTS,
-    MAX_DIR_LIGHTS,
+    AreaLightLayout, AreaLightRaw, AreaLightStorageLayout, AscendingError,
+    AtlasGroup, DirLightLayout, DirLightStorageLayout, DirectionalLightRaw,
+    GpuRenderer, InstanceBuffer, LightClusters, LightCookieRaw,
+    LightRenderPipeline, LightStorageRenderPipeline, Lights, LightsVertex,
+    OrderedIndex, RenderCommands, StaticBufferObject, TextureGroup,
+    TextureLayout, MAX_AREA_LIGHTS, MAX_DIR_LIGHTS,
 };
 
 use wgpu::util::{align_to, DeviceExt};
 
+/// Starting light capacity of the storage buffers. They double as needed.
+const STORAGE_START_CAPACITY: usize = 64;
+
+/// A light type's data and cookies.
+struct LightBuffers {
+    data: wgpu::Buffer,
+    cookies: wgpu::Buffer,
+    /// How many lights fit in the buffers.
+    capacity: usize,
+}
+
+impl LightBuffers {
+    fn new(
+        renderer: &GpuRenderer,
+        label: &str,
+        stride: usize,
+        capacity: usize,
+        storage: bool,
+    ) -> Self {
+        let usage = if storage {
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST
+        } else {
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST
+        };
+
+        let data: Vec<u8> = iter::repeat(0u8).take(capacity * stride).collect();
+
+        let data = renderer.device().create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{label} Light buffer")),
+                contents: &data,
+                usage,
+            },
+        );
+
+        let cookies: Vec<u8> = iter::repeat(LightCookieRaw {
+            layer: -1,
+            ..Default::default()
+        })
+        .take(capacity)
+        .flat_map(|cookie| bytemuck::bytes_of(&cookie).to_vec())
+        .collect();
+
+        let cookies = renderer.device().create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{label} Light cookie buffer")),
+                contents: &cookies,
+                usage,
+            },
+        );
+
+        Self {
+            data,
+            cookies,
+            capacity,
+        }
+    }
+}
+
 pub struct LightRenderer {
     pub buffer: InstanceBuffer<LightsVertex>,
-    area_buffer: wgpu::Buffer,
-    dir_buffer: wgpu::Buffer,
+    areas: LightBuffers,
+    dirs: LightBuffers,
     area_bind_group: wgpu::BindGroup,
     dir_bind_group: wgpu::BindGroup,
+    /// Screen tile light lists. Only used with storage buffers.
+    clusters: Option<LightClusters>,
+    /// Bound in place of a cookie atlas when none is given.
+    blank_cookies: TextureGroup,
 }
 
 impl LightRenderer {
     pub fn new(renderer: &mut GpuRenderer) -> Result<Self, AscendingError> {
+        let storage = renderer.storage_supported();
+        let (area_capacity, dir_capacity) = if storage {
+            (STORAGE_START_CAPACITY, STORAGE_START_CAPACITY)
+        } else {
+            (MAX_AREA_LIGHTS, MAX_DIR_LIGHTS)
+        };
+
+        let areas = Self::create_areas(renderer, area_capacity, storage);
+        let dirs = Self::create_dirs(renderer, dir_capacity, storage);
+        let clusters = storage.then(|| LightClusters::new(renderer));
+        let (area_bind_group, dir_bind_group) =
+            Self::create_bind_groups(renderer, &areas, &dirs, &clusters);
+        let blank_cookies = Self::create_blank_cookies(renderer);
+
+        Ok(Self {
+            buffer: InstanceBuffer::new(renderer.gpu_device()),
+            areas,
+            dirs,
+            area_bind_group,
+            dir_bind_group,
+            clusters,
+            blank_cookies,
+        })
+    }
+
+    fn create_areas(
+        renderer: &GpuRenderer,
+        capacity: usize,
+        storage: bool,
+    ) -> LightBuffers {
         // The size + Padding == 32.
         let area_alignment: usize =
             align_to(mem::size_of::<AreaLightRaw>(), 32) as usize;
+
+        LightBuffers::new(renderer, "Area", area_alignment, capacity, storage)
+    }
+
+    fn create_dirs(
+        renderer: &GpuRenderer,
+        capacity: usize,
+        storage: bool,
+    ) -> LightBuffers {
         // The size + Padding == 48.
         let dir_alignment: usize =
             align_to(mem::size_of::<DirectionalLightRaw>(), 48) as usize;
 
-        let area: Vec<u8> = iter::repeat(0u8)
-            .take(MAX_AREA_LIGHTS * area_alignment)
-            .collect();
+        LightBuffers::new(
+            renderer,
+            "Directional",
+            dir_alignment,
+            capacity,
+            storage,
+        )
+    }
 
-        let area_buffer = renderer.device().create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("Area Light buffer"),
-                contents: &area, //2000
-                usage: wgpu::BufferUsages::UNIFORM
-                    | wgpu::BufferUsages::COPY_DST,
+    fn create_bind_groups(
+        renderer: &mut GpuRenderer,
+        areas: &LightBuffers,
+        dirs: &LightBuffers,
+        clusters: &Option<LightClusters>,
+    ) -> (wgpu::BindGroup, wgpu::BindGroup) {
+        let mut area_entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: areas.data.as_entire_binding(),
             },
-        );
-
-        let dirs: Vec<u8> = iter::repeat(0u8)
-            .take(MAX_DIR_LIGHTS * dir_alignment)
-            .collect();
-
-        let dir_buffer = renderer.device().create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("Directional Light buffer"),
-                contents: &dirs, //2000
-                usage: wgpu::BufferUsages::UNIFORM
-                    | wgpu::BufferUsages::COPY_DST,
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: areas.cookies.as_entire_binding(),
             },
-        );
+        ];
+
+        let (area_layout, dir_layout) = match clusters {
+            Some(clusters) => {
+                area_entries.push(wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: clusters.tiles.as_entire_binding(),
+                });
+                area_entries.push(wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: clusters.indices.as_entire_binding(),
+                });
 
-        // Create the bind group layout for the area lights.
-        let layout = renderer.create_layout(AreaLightLayout);
+                (
+                    renderer.create_layout(AreaLightStorageLayout),
+                    renderer.create_layout(DirLightStorageLayout),
+                )
+            }
+            None => (
+                renderer.create_layout(AreaLightLayout),
+                renderer.create_layout(DirLightLayout),
+            ),
+        };
 
-        // Create the bind group.
         let area_bind_group =
             renderer
                 .device()
                 .create_bind_group(&wgpu::BindGroupDescriptor {
-                    layout: &layout,
-                    entries: &[wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: area_buffer.as_entire_binding(),
-                    }],
+                    layout: &area_layout,
+                    entries: &area_entries,
                     label: Some("area_lights_bind_group"),
                 });
 
-        // Create the bind group layout for the directional lights.
-        let layout = renderer.create_layout(DirLightLayout);
-
-        // Create the bind group.
         let dir_bind_group =
             renderer
                 .device()
                 .create_bind_group(&wgpu::BindGroupDescriptor {
-                    layout: &layout,
-                    entries: &[wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: dir_buffer.as_entire_binding(),
-                    }],
+                    layout: &dir_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: dirs.data.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: dirs.cookies.as_entire_binding(),
+                        },
+                    ],
                     label: Some("dir_lights_bind_group"),
                 });
 
-        Ok(Self {
-            buffer: InstanceBuffer::new(renderer.gpu_device()),
-            dir_buffer,
-            area_buffer,
-            area_bind_group,
-            dir_bind_group,
-        })
+        (area_bind_group, dir_bind_group)
+    }
+
+    /// Grows the storage buffers to fit the lights and rebuilds the screen
+    /// tile lists. Uniform arrays are fixed size and [`Lights`] stops
+    /// inserts before they fill up.
+    fn reserve(&mut self, lights: &mut Lights, renderer: &mut GpuRenderer) {
+        let clusters = match &mut self.clusters {
+            Some(clusters) => clusters,
+            None => return,
+        };
+
+        let mut recreated = false;
+        let count = lights.area_lights.len();
+
+        if count > self.areas.capacity {
+            self.areas =
+                Self::create_areas(renderer, count.next_power_of_two(), true);
+            lights.areas_changed = true;
+            recreated = true;
+        }
+
+        let count = lights.directional_lights.len();
+
+        if count > self.dirs.capacity {
+            self.dirs =
+                Self::create_dirs(renderer, count.next_power_of_two(), true);
+            lights.directionals_changed = true;
+            recreated = true;
+        }
+
+        if lights.areas_changed
+            || lights.directionals_changed
+            || clusters.resized(renderer)
+        {
+            recreated |= clusters.build(lights, renderer);
+        }
+
+        if recreated {
+            (self.area_bind_group, self.dir_bind_group) =
+                Self::create_bind_groups(
+                    renderer,
+                    &self.areas,
+                    &self.dirs,
+                    &self.clusters,
+                );
+        }
+    }
+
+    /// A single white texel so the pipeline always has a cookie atlas bound.
+    fn create_blank_cookies(renderer: &mut GpuRenderer) -> TextureGroup {
+        let extent = wgpu::Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        };
+
+        let texture =
+            renderer.device().create_texture(&wgpu::TextureDescriptor {
+                label: Some("Blank cookie texture"),
+                size: extent,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+
+        renderer.queue().write_texture(
+            texture.as_image_copy(),
+            &[255; 4],
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4),
+                rows_per_image: Some(1),
+            },
+            extent,
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+
+        TextureGroup::from_view(renderer, &view, TextureLayout)
     }
 
     pub fn add_buffer_store(
@@ -110,10 +311,14 @@ impl LightRenderer {
         lights: &mut Lights,
         renderer: &mut GpuRenderer,
     ) {
+        self.reserve(lights, renderer);
+
         let index = lights.update(
             renderer,
-            &mut self.area_buffer,
-            &mut self.dir_buffer,
+            &mut self.areas.data,
+            &mut self.dirs.data,
+            &mut self.areas.cookies,
+            &mut self.dirs.cookies,
         );
 
         self.add_buffer_store(renderer, index);
@@ -129,11 +334,20 @@ where
         renderer: &'b GpuRenderer,
         buffer: &'b LightRenderer,
     );
+
+    /// Renders the lights with cookies sampled from the atlas.
+    fn render_lights_with_cookies(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b LightRenderer,
+        atlas_group: &'b AtlasGroup,
+    );
 }
 
-impl<'a, 'b> RenderLights<'a, 'b> for wgpu::RenderPass<'a>
+impl<'a, 'b, P> RenderLights<'a, 'b> for P
 where
     'b: 'a,
+    P: RenderCommands<'a>,
 {
     fn render_lights(
         &mut self,
@@ -141,18 +355,47 @@ where
         buffer: &'b LightRenderer,
     ) {
         if buffer.buffer.count() > 0 {
-            self.set_bind_group(1, &buffer.area_bind_group, &[]);
-            self.set_bind_group(2, &buffer.dir_bind_group, &[]);
-            self.set_vertex_buffer(1, buffer.buffer.instances(None));
-            self.set_pipeline(
-                renderer.get_pipelines(LightRenderPipeline).unwrap(),
-            );
-
-            self.draw_indexed(
-                0..StaticBufferObject::index_count(),
-                0,
-                0..buffer.buffer.count(),
-            );
+            self.set_bind_group(3, &buffer.blank_cookies.bind_group, &[]);
+            draw_lights(self, renderer, buffer);
         }
     }
+
+    fn render_lights_with_cookies(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b LightRenderer,
+        atlas_group: &'b AtlasGroup,
+    ) {
+        if buffer.buffer.count() > 0 {
+            self.set_bind_group(3, &atlas_group.texture.bind_group, &[]);
+            draw_lights(self, renderer, buffer);
+        }
+    }
+}
+
+/// Expects the cookie texture group to already be set.
+fn draw_lights<'a, 'b>(
+    pass: &mut impl RenderCommands<'a>,
+    renderer: &'b GpuRenderer,
+    buffer: &'b LightRenderer,
+) where
+    'b: 'a,
+{
+    pass.set_bind_group(1, &buffer.area_bind_group, &[]);
+    pass.set_bind_group(2, &buffer.dir_bind_group, &[]);
+    pass.set_vertex_buffer(1, buffer.buffer.instances(None));
+
+    if buffer.clusters.is_some() {
+        pass.set_pipeline(
+            renderer.get_pipelines(LightStorageRenderPipeline).unwrap(),
+        );
+    } else {
+        pass.set_pipeline(renderer.get_pipelines(LightRenderPipeline).unwrap());
+    }
+
+    pass.draw_indexed(
+        0..StaticBufferObject::index_count(),
+        0,
+        0..buffer.buffer.count(),
+    );
 }
@@ -1,4 +1,7 @@
-use crate::{GpuDevice, Layout};
+use crate::{
+    shaders::{self, ShaderReflection},
+    GpuDevice, Layout,
+};
 use bytemuck::{Pod, Zeroable};
 
 #[repr(C)]
@@ -9,7 +12,11 @@ pub struct AreaLightRaw {
     pub max_distance: f32,
     pub anim_speed: f32,
     pub dither: f32,
+    /// Bit 0 is the radius wobble, bits 8..16 the [`crate::LightAnimation`]
+    /// and bits 16.. the light's animation seed.
     pub animate: u32,
+    /// Pulse or flicker amount or the strobe duty.
+    pub anim_amount: f32,
 }
 
 #[repr(C)]
@@ -24,9 +31,40 @@ pub struct DirectionalLightRaw {
     pub dither: f32,
     pub fade_distance: f32,
     pub edge_fade_distance: f32,
+    /// Bit 0 is the size wobble, bits 8..16 the [`crate::LightAnimation`]
+    /// and bits 16.. the light's animation seed.
     pub animate: u32,
+    /// Pulse or flicker amount or the strobe duty.
+    pub anim_amount: f32,
 }
 
+/// Where a light's cookie is within the atlas. Kept in its own array
+/// since the light arrays already fill the uniform buffer size limit.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LightCookieRaw {
+    /// x | y << 16 and width | height << 16 in atlas pixels.
+    pub rect: [u32; 2],
+    /// Atlas layer. -1 when the light has no cookie.
+    pub layer: i32,
+    pub rotation: f32,
+}
+
+/// Builds a light bind group layout from the light shader, so it always
+/// matches what the shader declares.
+fn light_layout(
+    gpu_device: &GpuDevice,
+    storage: bool,
+    group: u32,
+) -> wgpu::BindGroupLayout {
+    ShaderReflection::new("lightshader", &shaders::light_shader(storage))
+        .and_then(|reflection| {
+            reflection.create_bind_group_layout(gpu_device, group)
+        })
+        .unwrap_or_else(|e| panic!("embedded light shader: {e}"))
+}
+
+/// Area light data and cookies as uniform buffers.
 #[repr(C)]
 #[derive(Clone, Copy, Hash, Pod, Zeroable)]
 pub struct AreaLightLayout;
@@ -36,24 +74,11 @@ impl Layout for AreaLightLayout {
         &self,
         gpu_device: &mut GpuDevice,
     ) -> wgpu::BindGroupLayout {
-        gpu_device.device().create_bind_group_layout(
-            &wgpu::BindGroupLayoutDescriptor {
-                label: Some("area_light_bind_group_layout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }],
-            },
-        )
+        light_layout(gpu_device, false, 1)
     }
 }
 
+/// Directional light data and cookies as uniform buffers.
 #[repr(C)]
 #[derive(Clone, Copy, Hash, Pod, Zeroable)]
 pub struct DirLightLayout;
@@ -63,20 +88,35 @@ impl Layout for DirLightLayout {
         &self,
         gpu_device: &mut GpuDevice,
     ) -> wgpu::BindGroupLayout {
-        gpu_device.device().create_bind_group_layout(
-            &wgpu::BindGroupLayoutDescriptor {
-                label: Some("dir_light_bind_group_layout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }],
-            },
-        )
+        light_layout(gpu_device, false, 2)
+    }
+}
+
+/// Area light data, cookies and the screen tile light lists as read only
+/// storage buffers.
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct AreaLightStorageLayout;
+
+impl Layout for AreaLightStorageLayout {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+    ) -> wgpu::BindGroupLayout {
+        light_layout(gpu_device, true, 1)
+    }
+}
+
+/// Directional light data and cookies as read only storage buffers.
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct DirLightStorageLayout;
+
+impl Layout for DirLightStorageLayout {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+    ) -> wgpu::BindGroupLayout {
+        light_layout(gpu_device, true, 2)
     }
 }
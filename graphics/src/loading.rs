@@ -0,0 +1,33 @@
+mod preload;
+#[cfg(all(feature = "sprites", feature = "shapes"))]
+mod screen;
+mod stream;
+
+pub use preload::*;
+#[cfg(all(feature = "sprites", feature = "shapes"))]
+pub use screen::*;
+pub use stream::*;
+
+use crate::{AnimationClock, GpuRenderer};
+
+/// What the [`crate::Runner`] shows instead of the app while its
+/// [`AssetStream`] has loads left. It should only use pipelines the
+/// renderer made up front, as making more would stall the frames it is
+/// there to fill.
+pub trait LoadingScene: 'static {
+    /// Called every loading frame with the progress so far.
+    fn update(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        clock: &AnimationClock,
+        progress: &LoadProgress,
+    );
+
+    /// Records the loading frame's passes. The runner submits and
+    /// presents.
+    fn draw(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    );
+}
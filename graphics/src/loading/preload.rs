@@ -0,0 +1,324 @@
+use crate::shaders::{ShaderSource, ShaderValidator};
+use crate::{
+    Allocation, AscendingError, AssetStream, AtlasGroup, GpuRenderer,
+    LoadProgress, OtherError, PipeLineLayout, RenderTarget, Texture,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+};
+
+/// A file of a [`PreloadManifest`] and the key its result is kept under.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreloadEntry {
+    pub key: String,
+    pub path: PathBuf,
+}
+
+/// Files to load before gameplay. Textures become [`Texture`]s, fonts
+/// their bytes and shaders their validated WGSL source, each under its
+/// key in the [`AssetStream`].
+///
+/// As text, one entry per line with `#` comments:
+///
+/// ```text
+/// texture player images/player.png
+/// font ui fonts/ui.ttf
+/// shader water shaders/water.wgsl
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreloadManifest {
+    pub textures: Vec<PreloadEntry>,
+    pub fonts: Vec<PreloadEntry>,
+    pub shaders: Vec<PreloadEntry>,
+}
+
+impl PreloadManifest {
+    /// Parses the text form. Relative paths are taken from root. name is
+    /// only used in errors.
+    pub fn parse(
+        name: &str,
+        source: &str,
+        root: &Path,
+    ) -> Result<Self, AscendingError> {
+        let mut manifest = Self::default();
+
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| AscendingError::InvalidManifest {
+                name: name.to_owned(),
+                line: index + 1,
+                message,
+            };
+            let mut parts = line.splitn(3, char::is_whitespace);
+            let kind = parts.next().unwrap_or_default();
+            let (Some(key), Some(path)) = (parts.next(), parts.next()) else {
+                return Err(error(format!("expected `{kind} <key> <path>`")));
+            };
+            let entry = PreloadEntry {
+                key: key.to_owned(),
+                path: root.join(path.trim()),
+            };
+
+            match kind {
+                "texture" => manifest.textures.push(entry),
+                "font" => manifest.fonts.push(entry),
+                "shader" => manifest.shaders.push(entry),
+                _ => return Err(error(format!("unknown entry `{kind}`"))),
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    /// Paths are relative to the manifest's folder.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, AscendingError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+
+        Self::parse(
+            &path.to_string_lossy(),
+            &source,
+            path.parent().unwrap_or(Path::new("")),
+        )
+    }
+
+    /// Entries of every kind.
+    pub fn len(&self) -> usize {
+        self.textures.len() + self.fonts.len() + self.shaders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Uploads the textures from the finished stream into atlas,
+    /// returning their allocations by key. Textures that failed or were
+    /// already taken are left out.
+    pub fn upload_textures(
+        &self,
+        assets: &mut AssetStream,
+        atlas: &mut AtlasGroup,
+        renderer: &GpuRenderer,
+    ) -> HashMap<String, Allocation> {
+        self.textures
+            .iter()
+            .filter_map(|entry| {
+                let texture = assets.take::<Texture>(&entry.key)?;
+                let allocation = texture.group_upload(atlas, renderer)?;
+
+                Some((entry.key.clone(), allocation))
+            })
+            .collect()
+    }
+
+    /// Loads the fonts from the finished stream into font_system.
+    #[cfg(feature = "text")]
+    pub fn install_fonts(
+        &self,
+        assets: &mut AssetStream,
+        font_system: &mut cosmic_text::FontSystem,
+    ) {
+        for entry in &self.fonts {
+            if let Some(bytes) = assets.take::<Vec<u8>>(&entry.key) {
+                font_system.db_mut().load_font_data(bytes);
+            }
+        }
+    }
+}
+
+type CreatePipeline = Box<dyn FnOnce(&mut GpuRenderer)>;
+type WarmUp =
+    Box<dyn FnOnce(&GpuRenderer, &mut wgpu::CommandEncoder, &RenderTarget)>;
+/// Given to [`Preloader::on_progress`].
+pub type ProgressCallback = Box<dyn FnMut(&LoadProgress)>;
+
+/// Runs a [`PreloadManifest`] on an [`AssetStream`], then creates the
+/// pipelines and records the warm-up draws given to it on the main
+/// thread, one per frame so a loading screen keeps drawing. Every step
+/// counts in the stream's progress, in "textures", "fonts", "shaders",
+/// "pipelines" and "warm up".
+///
+/// Hand it to [`crate::Runner::preload`] to have it run behind the
+/// loading scene.
+pub struct Preloader {
+    manifest: PreloadManifest,
+    pipelines: VecDeque<(String, CreatePipeline)>,
+    warm_ups: VecDeque<(String, WarmUp)>,
+    /// Warm-ups draw into it. Shares the format and MSAA of the frame so
+    /// drivers build the pipeline variants the game uses.
+    target: Option<RenderTarget>,
+    on_progress: Option<ProgressCallback>,
+    /// Loads done when the callback was last called.
+    reported: Option<usize>,
+    started: bool,
+}
+
+impl Preloader {
+    pub fn new(manifest: PreloadManifest) -> Self {
+        Self {
+            manifest,
+            pipelines: VecDeque::new(),
+            warm_ups: VecDeque::new(),
+            target: None,
+            on_progress: None,
+            reported: None,
+            started: false,
+        }
+    }
+
+    pub fn manifest(&self) -> &PreloadManifest {
+        &self.manifest
+    }
+
+    /// Creates pipeline before gameplay instead of on first use.
+    pub fn add_pipeline<K: PipeLineLayout>(
+        &mut self,
+        pipeline: K,
+    ) -> &mut Self {
+        let name = std::any::type_name::<K>();
+        let name = name.rsplit("::").next().unwrap_or(name);
+
+        self.pipelines.push_back((
+            name.to_owned(),
+            Box::new(move |renderer: &mut GpuRenderer| {
+                renderer.create_pipeline(pipeline)
+            }),
+        ));
+        self
+    }
+
+    /// Records draw after the pipelines are made, into a small offscreen
+    /// target with the frame's format and MSAA. Draw one of each kind of
+    /// thing with its own renderers so the drivers finish compiling the
+    /// pipelines now rather than on the first frame using them.
+    pub fn add_warm_up<F>(
+        &mut self,
+        name: impl Into<String>,
+        draw: F,
+    ) -> &mut Self
+    where
+        F: FnOnce(&GpuRenderer, &mut wgpu::CommandEncoder, &RenderTarget)
+            + 'static,
+    {
+        self.warm_ups.push_back((name.into(), Box::new(draw)));
+        self
+    }
+
+    /// Called with the stream's progress whenever a step of it finishes.
+    pub fn on_progress<F>(&mut self, callback: F) -> &mut Self
+    where
+        F: FnMut(&LoadProgress) + 'static,
+    {
+        let callback: ProgressCallback = Box::new(callback);
+
+        self.on_progress = Some(callback);
+        self
+    }
+
+    /// Queues the manifest's files on the stream and counts the pipelines
+    /// and warm-ups in its progress. Shaders are validated against the
+    /// renderer's device on the workers.
+    pub fn start(&mut self, renderer: &GpuRenderer, assets: &mut AssetStream) {
+        if self.started {
+            return;
+        }
+
+        self.started = true;
+
+        for entry in &self.manifest.textures {
+            assets.load_texture(entry.key.clone(), entry.path.clone());
+        }
+
+        for entry in &self.manifest.fonts {
+            assets.load_bytes("fonts", entry.key.clone(), entry.path.clone());
+        }
+
+        let device = renderer.device();
+
+        for entry in &self.manifest.shaders {
+            let validator =
+                ShaderValidator::new(device.limits(), device.features());
+            let (key, path) = (entry.key.clone(), entry.path.clone());
+
+            assets.load("shaders", key.clone(), move || {
+                let source = std::fs::read_to_string(path)?;
+                let errors = validator
+                    .validate_source(&ShaderSource::new(key, source.clone()));
+
+                if !errors.is_empty() {
+                    return Err(AscendingError::Other(OtherError::new(
+                        &errors
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    )));
+                }
+
+                Ok(source)
+            });
+        }
+
+        assets.add_steps("pipelines", self.pipelines.len());
+        assets.add_steps("warm up", self.warm_ups.len());
+    }
+
+    /// Call once per frame after [`AssetStream::poll`]. Creates the next
+    /// pipeline, or once they are all made records the next warm-up.
+    /// Returns true once every step is done.
+    pub fn update(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        assets: &mut AssetStream,
+    ) -> bool {
+        if !self.started {
+            self.start(renderer, assets);
+        }
+
+        if let Some((name, create)) = self.pipelines.pop_front() {
+            create(renderer);
+            assets.step_done("pipelines", name, Ok(()));
+        } else if let Some((name, draw)) = self.warm_ups.pop_front() {
+            let target = self
+                .target
+                .get_or_insert_with(|| RenderTarget::new(renderer, 4, 4));
+            let mut encoder = renderer.device().create_command_encoder(
+                &wgpu::CommandEncoderDescriptor {
+                    label: Some("warm up encoder"),
+                },
+            );
+
+            draw(renderer, &mut encoder, target);
+            renderer.queue().submit(std::iter::once(encoder.finish()));
+            assets.step_done("warm up", name, Ok(()));
+        }
+
+        let progress = assets.progress();
+
+        if self.reported != Some(progress.done()) {
+            self.reported = Some(progress.done());
+
+            if let Some(callback) = &mut self.on_progress {
+                callback(progress);
+            }
+        }
+
+        let done = self.pipelines.is_empty()
+            && self.warm_ups.is_empty()
+            && !assets.is_loading();
+
+        if done {
+            self.target = None;
+        }
+
+        done
+    }
+}
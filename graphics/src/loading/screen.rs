@@ -0,0 +1,217 @@
+use crate::{
+    AnimationClock, AsBufferPass, AscendingError, AtlasGroup, Color,
+    GpuRenderer, Image, ImageRenderer, LoadProgress, LoadingScene, OtherError,
+    Rect, RectRenderer, RenderImage, RenderRects, SetBuffers, System, Texture,
+    Vec2, Vec3, Vec4,
+};
+use camera::{
+    controls::{FlatControls, FlatSettings},
+    Projection,
+};
+
+/// Size of the progress bar in pixels.
+const BAR_SIZE: Vec2 = Vec2::new(320.0, 14.0);
+/// Space between the logo and the bar.
+const BAR_GAP: f32 = 24.0;
+
+/// A [`LoadingScene`] with a logo over a progress bar, centered in the
+/// window. Only uses the image and rect pipelines.
+pub struct LoadingScreen {
+    system: System<FlatControls>,
+    atlas: AtlasGroup,
+    image_renderer: ImageRenderer,
+    rect_renderer: RectRenderer,
+    logo: Option<Image>,
+    background: Rect,
+    fill: Rect,
+    pub clear: wgpu::Color,
+    /// Window size the layout was made for.
+    size: [f32; 2],
+    /// Fill shown, eased towards the progress so it doesn't jump.
+    shown: f32,
+}
+
+impl LoadingScreen {
+    /// logo is uploaded right away, so load it before the stream starts.
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        logo: Option<Texture>,
+    ) -> Result<Self, AscendingError> {
+        let size = renderer.size();
+        let system = System::new(
+            renderer,
+            Projection::orthographic_layers(
+                0.0,
+                size.width,
+                0.0,
+                size.height,
+                1,
+            ),
+            FlatControls::new(FlatSettings::default()),
+            [size.width, size.height],
+        );
+        let mut atlas =
+            AtlasGroup::new(renderer, wgpu::TextureFormat::Rgba8UnormSrgb);
+        let logo = match logo {
+            Some(texture) => {
+                let (width, height) = texture.size();
+                let allocation =
+                    texture.group_upload(&mut atlas, renderer).ok_or_else(
+                        || OtherError::new("failed to upload loading logo"),
+                    )?;
+                let mut image = Image::new(Some(allocation), renderer, 0);
+
+                image.hw = Vec2::new(width as f32, height as f32);
+                image.uv = Vec4::new(0.0, 0.0, width as f32, height as f32);
+                Some(image)
+            }
+            None => None,
+        };
+        let mut background = Rect::new(renderer, 0);
+        let mut fill = Rect::new(renderer, 0);
+
+        background
+            .set_color(Color::rgba(20, 20, 20, 255))
+            .set_border_width(1.0)
+            .set_border_color(Color::rgba(90, 90, 90, 255));
+        fill.set_color(Color::rgba(220, 180, 60, 255));
+
+        Ok(Self {
+            system,
+            atlas,
+            image_renderer: ImageRenderer::new(renderer)?,
+            rect_renderer: RectRenderer::new(renderer)?,
+            logo,
+            background,
+            fill,
+            clear: wgpu::Color::BLACK,
+            size: [0.0; 2],
+            shown: 0.0,
+        })
+    }
+
+    pub fn set_bar_colors(
+        &mut self,
+        background: Color,
+        fill: Color,
+    ) -> &mut Self {
+        self.background.set_color(background);
+        self.fill.set_color(fill);
+        self
+    }
+
+    /// Centers the logo and the bar under it in the window.
+    fn layout(&mut self, renderer: &GpuRenderer) {
+        let [width, height] = self.size;
+        let logo_height = self.logo.as_ref().map_or(0.0, |logo| logo.hw.y);
+        let total = logo_height + BAR_GAP + BAR_SIZE.y;
+        let bar_y = ((height - total) * 0.5).round();
+
+        if let Some(logo) = &mut self.logo {
+            logo.pos = Vec3::new(
+                ((width - logo.hw.x) * 0.5).round(),
+                bar_y + BAR_SIZE.y + BAR_GAP,
+                0.5,
+            );
+            logo.changed = true;
+        }
+
+        self.background
+            .set_position(Vec3::new(
+                ((width - BAR_SIZE.x) * 0.5).round(),
+                bar_y,
+                0.6,
+            ))
+            .set_size(BAR_SIZE);
+        self.system.set_projection(Projection::orthographic_layers(
+            0.0, width, 0.0, height, 1,
+        ));
+        self.system.update_screen(renderer, self.size);
+    }
+}
+
+impl LoadingScene for LoadingScreen {
+    fn update(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        clock: &AnimationClock,
+        progress: &LoadProgress,
+    ) {
+        let size = renderer.size();
+
+        if self.size != [size.width, size.height] {
+            self.size = [size.width, size.height];
+            self.layout(renderer);
+        }
+
+        let target = progress.fraction();
+
+        self.shown = if target < self.shown {
+            target
+        } else {
+            let step = clock.real_delta_seconds() * 4.0;
+
+            self.shown + (target - self.shown) * step.min(1.0)
+        };
+
+        let position = self.background.position;
+
+        self.fill
+            .set_position(Vec3::new(position.x, position.y, 0.5))
+            .set_size(Vec2::new(BAR_SIZE.x * self.shown, BAR_SIZE.y));
+        self.system.update(renderer, clock);
+
+        if let Some(logo) = &mut self.logo {
+            self.image_renderer.image_update(logo, renderer);
+        }
+
+        self.rect_renderer
+            .rect_update(&mut self.background, renderer);
+
+        if self.fill.size.x > 0.0 {
+            self.rect_renderer.rect_update(&mut self.fill, renderer);
+        }
+
+        self.image_renderer.finalize(renderer);
+        self.rect_renderer.finalize(renderer);
+    }
+
+    fn draw(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let Some(view) = renderer.render_target() else {
+            return;
+        };
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("loading screen pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: renderer.resolve_target(),
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(self.clear),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(
+                wgpu::RenderPassDepthStencilAttachment {
+                    view: renderer.depth_buffer(),
+                    depth_ops: Some(wgpu::Operations {
+                        load: renderer.gpu_device().depth_clear(),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                },
+            ),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        pass.set_bind_group(0, self.system.bind_group(), &[]);
+        pass.set_buffers(renderer.buffer_object.as_buffer_pass());
+        pass.render_image(renderer, &self.image_renderer, &self.atlas);
+        pass.render_rects(renderer, &self.rect_renderer);
+    }
+}
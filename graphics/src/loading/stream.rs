@@ -0,0 +1,343 @@
+use crate::{AscendingError, Texture};
+use std::{
+    any::Any,
+    collections::HashMap,
+    path::PathBuf,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+/// Workers spawned when the machine doesn't say how many cores it has.
+const DEFAULT_WORKERS: usize = 2;
+/// Most workers spawned, leaving cores for the render thread.
+const MAX_WORKERS: usize = 4;
+
+type Loaded = Result<Box<dyn Any + Send>, AscendingError>;
+type Job = Box<dyn FnOnce() -> Loaded + Send>;
+
+/// Loads of one category, like "textures" or "maps".
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CategoryProgress {
+    pub name: String,
+    pub total: usize,
+    pub done: usize,
+    /// Of done, how many failed.
+    pub failed: usize,
+}
+
+impl CategoryProgress {
+    /// 0.0 to 1.0. Empty categories are complete.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.done >= self.total
+    }
+}
+
+/// Progress of every category since the stream was last idle.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadProgress {
+    categories: Vec<CategoryProgress>,
+}
+
+impl LoadProgress {
+    /// In the order they were first loaded into.
+    pub fn categories(&self) -> &[CategoryProgress] {
+        &self.categories
+    }
+
+    pub fn category(&self, name: &str) -> Option<&CategoryProgress> {
+        self.categories
+            .iter()
+            .find(|category| category.name == name)
+    }
+
+    pub fn total(&self) -> usize {
+        self.categories.iter().map(|category| category.total).sum()
+    }
+
+    pub fn done(&self) -> usize {
+        self.categories.iter().map(|category| category.done).sum()
+    }
+
+    /// Of every load, 0.0 to 1.0.
+    pub fn fraction(&self) -> f32 {
+        match self.total() {
+            0 => 1.0,
+            total => self.done() as f32 / total as f32,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.done() >= self.total()
+    }
+
+    fn category_mut(&mut self, name: &str) -> &mut CategoryProgress {
+        let index = match self
+            .categories
+            .iter()
+            .position(|category| category.name == name)
+        {
+            Some(index) => index,
+            None => {
+                self.categories.push(CategoryProgress {
+                    name: name.to_owned(),
+                    ..Default::default()
+                });
+                self.categories.len() - 1
+            }
+        };
+
+        &mut self.categories[index]
+    }
+}
+
+struct Pending {
+    category: String,
+    key: String,
+}
+
+/// Runs asset loads on worker threads and collects what they return by
+/// key, with progress per category for a loading screen.
+///
+/// Loads do the file reading and decoding. Anything touching the GPU, like
+/// uploading into an atlas, happens on the main thread after
+/// [`AssetStream::take`].
+pub struct AssetStream {
+    /// None until the first load spawns the workers.
+    jobs: Option<mpsc::Sender<(u64, Job)>>,
+    results: mpsc::Receiver<(u64, Loaded)>,
+    results_sender: mpsc::Sender<(u64, Loaded)>,
+    next_id: u64,
+    pending: HashMap<u64, Pending>,
+    /// Main thread steps counted in the progress but not done yet.
+    steps: usize,
+    loaded: HashMap<String, Box<dyn Any + Send>>,
+    errors: Vec<(String, AscendingError)>,
+    progress: LoadProgress,
+}
+
+impl AssetStream {
+    pub fn new() -> Self {
+        let (results_sender, results) = mpsc::channel();
+
+        Self {
+            jobs: None,
+            results,
+            results_sender,
+            next_id: 0,
+            pending: HashMap::new(),
+            steps: 0,
+            loaded: HashMap::new(),
+            errors: Vec::new(),
+            progress: LoadProgress::default(),
+        }
+    }
+
+    fn spawn_workers(&mut self) -> &mpsc::Sender<(u64, Job)> {
+        self.jobs.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel::<(u64, Job)>();
+            let receiver = Arc::new(Mutex::new(receiver));
+            let workers = thread::available_parallelism()
+                .map_or(DEFAULT_WORKERS, |cores| cores.get().saturating_sub(1))
+                .clamp(1, MAX_WORKERS);
+
+            for index in 0..workers {
+                let receiver = receiver.clone();
+                let results = self.results_sender.clone();
+
+                // The workers end once the stream drops the job sender.
+                let spawned = thread::Builder::new()
+                    .name(format!("asset worker {index}"))
+                    .spawn(move || loop {
+                        let job = match receiver.lock() {
+                            Ok(receiver) => receiver.recv(),
+                            Err(_) => return,
+                        };
+
+                        let Ok((id, job)) = job else {
+                            return;
+                        };
+
+                        if results.send((id, job())).is_err() {
+                            return;
+                        }
+                    });
+
+                if let Err(e) = spawned {
+                    log::error!("failed to spawn asset worker: {e}");
+                }
+            }
+
+            sender
+        })
+    }
+
+    /// Runs load on a worker and keeps what it returns under key, counted
+    /// in category. A key loaded again replaces the earlier result.
+    pub fn load<T, F>(
+        &mut self,
+        category: impl Into<String>,
+        key: impl Into<String>,
+        load: F,
+    ) where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T, AscendingError> + Send + 'static,
+    {
+        let category = category.into();
+        let id = self.next_id;
+
+        self.restart_progress();
+
+        self.next_id += 1;
+        self.progress.category_mut(&category).total += 1;
+        self.pending.insert(
+            id,
+            Pending {
+                category,
+                key: key.into(),
+            },
+        );
+
+        let job: Job = Box::new(move || {
+            load().map(|asset| Box::new(asset) as Box<dyn Any + Send>)
+        });
+
+        if self.spawn_workers().send((id, job)).is_err() {
+            log::error!("asset workers are gone, load {id} is lost");
+        }
+    }
+
+    /// The finished progress stays readable until more is queued.
+    fn restart_progress(&mut self) {
+        if !self.is_loading() {
+            self.progress = LoadProgress::default();
+        }
+    }
+
+    /// Counts count steps done on the main thread, like pipelines to
+    /// create, in category. Finish each with [`AssetStream::step_done`].
+    pub fn add_steps(&mut self, category: &str, count: usize) {
+        if count == 0 {
+            return;
+        }
+
+        self.restart_progress();
+        self.steps += count;
+        self.progress.category_mut(category).total += count;
+    }
+
+    /// Finishes one of the steps of category. A failed step is counted
+    /// and kept under key with the load errors.
+    pub fn step_done(
+        &mut self,
+        category: &str,
+        key: impl Into<String>,
+        result: Result<(), AscendingError>,
+    ) {
+        if self.steps == 0 {
+            return;
+        }
+
+        let progress = self.progress.category_mut(category);
+
+        self.steps -= 1;
+        progress.done += 1;
+
+        if let Err(e) = result {
+            let key = key.into();
+
+            progress.failed += 1;
+            log::warn!("failed to preload {key}: {e}");
+            self.errors.push((key, e));
+        }
+    }
+
+    /// Decodes the image at path into a [`Texture`] under key, counted in
+    /// "textures".
+    pub fn load_texture(&mut self, key: impl Into<String>, path: PathBuf) {
+        self.load("textures", key, move || Texture::from_file(path));
+    }
+
+    /// Reads the file at path into bytes under key, counted in category.
+    pub fn load_bytes(
+        &mut self,
+        category: impl Into<String>,
+        key: impl Into<String>,
+        path: PathBuf,
+    ) {
+        self.load(category, key, move || Ok(std::fs::read(path)?));
+    }
+
+    /// Collects the loads that finished. Call once per frame, the
+    /// [`crate::Runner`] does this for its own stream. Progress starts
+    /// over at the first load after everything finished.
+    pub fn poll(&mut self) -> &LoadProgress {
+        while let Ok((id, result)) = self.results.try_recv() {
+            let Some(pending) = self.pending.remove(&id) else {
+                continue;
+            };
+            let category = self.progress.category_mut(&pending.category);
+
+            category.done += 1;
+
+            match result {
+                Ok(asset) => {
+                    self.loaded.insert(pending.key, asset);
+                }
+                Err(e) => {
+                    category.failed += 1;
+                    log::warn!("failed to load {}: {e}", pending.key);
+                    self.errors.push((pending.key, e));
+                }
+            }
+        }
+
+        &self.progress
+    }
+
+    pub fn progress(&self) -> &LoadProgress {
+        &self.progress
+    }
+
+    /// if any load hasn't finished.
+    pub fn is_loading(&self) -> bool {
+        !self.pending.is_empty() || self.steps > 0
+    }
+
+    /// Takes the finished result under key. None if it isn't done, failed
+    /// or is another type, in which case it stays.
+    pub fn take<T: 'static>(&mut self, key: &str) -> Option<T> {
+        let asset = self.loaded.remove(key)?;
+
+        match asset.downcast::<T>() {
+            Ok(asset) => Some(*asset),
+            Err(asset) => {
+                self.loaded.insert(key.to_owned(), asset);
+                None
+            }
+        }
+    }
+
+    /// if a finished result is waiting under key.
+    pub fn contains(&self, key: &str) -> bool {
+        self.loaded.contains_key(key)
+    }
+
+    /// Keys and errors of the loads that failed since last taken.
+    pub fn take_errors(&mut self) -> Vec<(String, AscendingError)> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+impl Default for AssetStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
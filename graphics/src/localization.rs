@@ -0,0 +1,7 @@
+mod catalog;
+mod localizer;
+mod plural;
+
+pub use catalog::*;
+pub use localizer::*;
+pub use plural::*;
@@ -0,0 +1,538 @@
+use crate::{plural_forms, AscendingError};
+use std::collections::HashMap;
+
+/// A parsed message value.
+pub(crate) type Pattern = Vec<Element>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Element {
+    Text(String),
+    /// `{ $name }`, filled in from the arguments.
+    Variable(String),
+    /// `{ other-message }` or `{ -term }`.
+    Reference(String),
+    /// `{ $name -> [key] value *[other] value }`.
+    Select {
+        selector: String,
+        variants: Vec<(String, Pattern)>,
+        default: usize,
+    },
+}
+
+/// Translated messages of one locale, keyed by message id.
+///
+/// Fluent files support messages, terms, attributes (as `id.attr`),
+/// variables, references, string literals and select expressions on one
+/// variable. Gettext files use msgid as the key, `context\u{4}msgid` with a
+/// msgctxt, and plural forms become a select on `$count`. Placeables in
+/// gettext strings use the Fluent syntax.
+#[derive(Clone, Debug, Default)]
+pub struct Catalog {
+    messages: HashMap<String, Pattern>,
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a Fluent (.ftl) file. name is only used in errors.
+    pub fn from_ftl(name: &str, source: &str) -> Result<Self, AscendingError> {
+        let mut catalog = Self::new();
+        // id, first line, value so far.
+        let mut entry: Option<(String, usize, String)> = None;
+        let mut message = String::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let trimmed = line.trim_start();
+            // The `}` closing a select is usually not indented.
+            let indented = trimmed.len() != line.len() || line.starts_with('}');
+
+            if line.starts_with('#') || trimmed.is_empty() {
+                continue;
+            }
+
+            // Attributes are indented `.attr = value` lines of a message.
+            let start = if !indented {
+                message.clear();
+                Some(line)
+            } else if trimmed.starts_with('.') && !message.is_empty() {
+                Some(trimmed)
+            } else {
+                None
+            };
+
+            if let Some(start) = start {
+                catalog.finish_entry(name, entry.take())?;
+
+                let Some((id, value)) = start.split_once('=') else {
+                    return Err(catalog_error(
+                        name,
+                        line_number,
+                        "expected `id = value`",
+                    ));
+                };
+                let id = id.trim();
+
+                if id.is_empty() {
+                    return Err(catalog_error(name, line_number, "missing id"));
+                }
+
+                let id = if let Some(attr) = id.strip_prefix('.') {
+                    format!("{message}.{attr}")
+                } else {
+                    message = id.to_owned();
+                    id.to_owned()
+                };
+
+                entry = Some((id, line_number, value.trim().to_owned()));
+            } else if let Some((_, _, value)) = &mut entry {
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+
+                value.push_str(trimmed.trim_end());
+            } else {
+                return Err(catalog_error(
+                    name,
+                    line_number,
+                    "indented line outside of a message",
+                ));
+            }
+        }
+
+        catalog.finish_entry(name, entry)?;
+        Ok(catalog)
+    }
+
+    /// Parses a gettext (.po) file. locale picks which plural category each
+    /// `msgstr[n]` is.
+    pub fn from_po(
+        name: &str,
+        locale: &str,
+        source: &str,
+    ) -> Result<Self, AscendingError> {
+        let mut catalog = Self::new();
+        let mut entry = PoEntry::default();
+        // The string continuation lines get appended to.
+        let mut field: Option<PoField> = None;
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('"') {
+                let text = po_string(name, line_number, line)?;
+
+                match field {
+                    Some(PoField::Context) => entry.context.push_str(&text),
+                    Some(PoField::Id) => entry.id.push_str(&text),
+                    Some(PoField::Plural) => entry.plural = true,
+                    Some(PoField::Str(n)) => entry.strs[n].1.push_str(&text),
+                    None => {
+                        return Err(catalog_error(
+                            name,
+                            line_number,
+                            "string outside of an entry",
+                        ))
+                    }
+                }
+
+                continue;
+            }
+
+            let (keyword, rest) =
+                line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let text = po_string(name, line_number, rest.trim())?;
+
+            field = Some(match keyword {
+                "msgctxt" | "msgid" if field.is_some_and(PoField::is_str) => {
+                    catalog.finish_po_entry(
+                        name,
+                        locale,
+                        std::mem::take(&mut entry),
+                    )?;
+                    entry.line = line_number;
+                    po_start(&mut entry, keyword, text)
+                }
+                "msgctxt" | "msgid" => {
+                    entry.line = line_number;
+                    po_start(&mut entry, keyword, text)
+                }
+                "msgid_plural" => {
+                    entry.plural = true;
+                    PoField::Plural
+                }
+                "msgstr" => {
+                    entry.strs.push((0, text));
+                    PoField::Str(entry.strs.len() - 1)
+                }
+                _ => {
+                    let n = keyword
+                        .strip_prefix("msgstr[")
+                        .and_then(|n| n.strip_suffix(']'))
+                        .and_then(|n| n.parse().ok())
+                        .ok_or_else(|| {
+                            catalog_error(
+                                name,
+                                line_number,
+                                &format!("unknown keyword `{keyword}`"),
+                            )
+                        })?;
+
+                    entry.strs.push((n, text));
+                    PoField::Str(entry.strs.len() - 1)
+                }
+            });
+        }
+
+        catalog.finish_po_entry(name, locale, entry)?;
+        Ok(catalog)
+    }
+
+    /// Adds other's messages, replacing ones with the same id.
+    pub fn merge(&mut self, other: Catalog) {
+        self.messages.extend(other.messages);
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.messages.contains_key(id)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.messages.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub(crate) fn message(&self, id: &str) -> Option<&Pattern> {
+        self.messages.get(id)
+    }
+
+    fn finish_entry(
+        &mut self,
+        name: &str,
+        entry: Option<(String, usize, String)>,
+    ) -> Result<(), AscendingError> {
+        if let Some((id, line, value)) = entry {
+            let pattern = parse_pattern(&value)
+                .map_err(|message| catalog_error(name, line, &message))?;
+
+            self.messages.insert(id, pattern);
+        }
+
+        Ok(())
+    }
+
+    fn finish_po_entry(
+        &mut self,
+        name: &str,
+        locale: &str,
+        entry: PoEntry,
+    ) -> Result<(), AscendingError> {
+        // The header and untranslated entries.
+        if entry.id.is_empty() || entry.strs.iter().all(|(_, s)| s.is_empty()) {
+            return Ok(());
+        }
+
+        let id = if entry.context.is_empty() {
+            entry.id
+        } else {
+            format!("{}\u{4}{}", entry.context, entry.id)
+        };
+        let error = |message: String| catalog_error(name, entry.line, &message);
+
+        let pattern = if entry.plural {
+            let forms = plural_forms(locale);
+            let mut variants = Vec::with_capacity(entry.strs.len());
+
+            for (n, text) in &entry.strs {
+                let category = forms.get(*n).ok_or_else(|| {
+                    error(format!("{locale} has no plural form {n}"))
+                })?;
+
+                variants.push((
+                    category.name().to_owned(),
+                    parse_pattern(text).map_err(error)?,
+                ));
+            }
+
+            vec![Element::Select {
+                selector: "count".to_owned(),
+                default: variants.len() - 1,
+                variants,
+            }]
+        } else {
+            parse_pattern(&entry.strs[0].1).map_err(error)?
+        };
+
+        self.messages.insert(id, pattern);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct PoEntry {
+    line: usize,
+    context: String,
+    id: String,
+    plural: bool,
+    /// Form number and translation.
+    strs: Vec<(usize, String)>,
+}
+
+#[derive(Copy, Clone)]
+enum PoField {
+    Context,
+    Id,
+    Plural,
+    Str(usize),
+}
+
+impl PoField {
+    fn is_str(self) -> bool {
+        matches!(self, PoField::Str(_))
+    }
+}
+
+fn po_start(entry: &mut PoEntry, keyword: &str, text: String) -> PoField {
+    if keyword == "msgctxt" {
+        entry.context = text;
+        PoField::Context
+    } else {
+        entry.id = text;
+        PoField::Id
+    }
+}
+
+/// Unquotes and unescapes a gettext string.
+fn po_string(
+    name: &str,
+    line: usize,
+    quoted: &str,
+) -> Result<String, AscendingError> {
+    let inner = quoted
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .ok_or_else(|| catalog_error(name, line, "expected a quoted string"))?;
+    let mut text = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            Some(c @ ('"' | '\\')) => text.push(c),
+            _ => return Err(catalog_error(name, line, "invalid escape")),
+        }
+    }
+
+    Ok(text)
+}
+
+fn catalog_error(name: &str, line: usize, message: &str) -> AscendingError {
+    AscendingError::InvalidCatalog {
+        name: name.to_owned(),
+        line,
+        message: message.to_owned(),
+    }
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+fn parse_pattern(source: &str) -> Result<Pattern, String> {
+    let mut parser = Parser {
+        chars: source.chars().peekable(),
+    };
+    let pattern = parser.pattern(false)?;
+
+    match parser.chars.next() {
+        Some(c) => Err(format!("unexpected `{c}`")),
+        None => Ok(pattern),
+    }
+}
+
+impl Parser<'_> {
+    /// Text and placeables until a `}`, or a new line in a variant.
+    fn pattern(&mut self, variant: bool) -> Result<Pattern, String> {
+        let mut pattern = Vec::new();
+        let mut text = String::new();
+
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                '}' => break,
+                '\n' if variant => break,
+                '{' => {
+                    self.chars.next();
+
+                    if !text.is_empty() {
+                        pattern.push(Element::Text(std::mem::take(&mut text)));
+                    }
+
+                    pattern.push(self.placeable()?);
+                }
+                _ => {
+                    self.chars.next();
+                    text.push(c);
+                }
+            }
+        }
+
+        if variant {
+            text.truncate(text.trim_end().len());
+        }
+
+        if !text.is_empty() {
+            pattern.push(Element::Text(text));
+        }
+
+        Ok(pattern)
+    }
+
+    /// After the `{`, up to and including the `}`.
+    fn placeable(&mut self) -> Result<Element, String> {
+        self.skip_whitespace();
+
+        let element = match self.chars.peek() {
+            Some('"') => {
+                self.chars.next();
+                Element::Text(self.string_literal()?)
+            }
+            Some('$') => {
+                self.chars.next();
+                Element::Variable(self.identifier()?)
+            }
+            Some('-') => {
+                self.chars.next();
+                Element::Reference(format!("-{}", self.identifier()?))
+            }
+            _ => Element::Reference(self.identifier()?),
+        };
+
+        self.skip_whitespace();
+
+        if self.chars.peek() == Some(&'-') {
+            self.chars.next();
+
+            if self.chars.next() != Some('>') {
+                return Err("expected `->`".to_owned());
+            }
+
+            let Element::Variable(selector) = element else {
+                return Err("only variables can be selected on".to_owned());
+            };
+
+            return self.select(selector);
+        }
+
+        self.expect('}')?;
+        Ok(element)
+    }
+
+    fn select(&mut self, selector: String) -> Result<Element, String> {
+        let mut variants = Vec::new();
+        let mut default = None;
+
+        loop {
+            self.skip_whitespace();
+
+            match self.chars.next() {
+                Some('}') => break,
+                Some('*') if default.is_none() => {
+                    default = Some(variants.len());
+                    self.expect('[')?;
+                }
+                Some('[') => {}
+                _ => return Err("expected a variant".to_owned()),
+            }
+
+            let mut key = String::new();
+
+            for c in self.chars.by_ref() {
+                if c == ']' {
+                    break;
+                }
+
+                key.push(c);
+            }
+
+            while self.chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+
+            variants.push((key.trim().to_owned(), self.pattern(true)?));
+        }
+
+        let default = default.ok_or("select needs a `*[default]` variant")?;
+
+        Ok(Element::Select {
+            selector,
+            variants,
+            default,
+        })
+    }
+
+    fn string_literal(&mut self) -> Result<String, String> {
+        let mut text = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => break,
+                Some('\\') => match self.chars.next() {
+                    Some(c @ ('"' | '\\')) => text.push(c),
+                    _ => return Err("invalid escape".to_owned()),
+                },
+                Some('\n') | None => {
+                    return Err("unterminated string".to_owned())
+                }
+                Some(c) => text.push(c),
+            }
+        }
+
+        self.skip_whitespace();
+        Ok(text)
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        let mut id = String::new();
+
+        while let Some(c) = self.chars.next_if(|c| {
+            c.is_alphanumeric() || *c == '-' || *c == '_' || *c == '.'
+        }) {
+            id.push(c);
+        }
+
+        if id.is_empty() {
+            return Err("expected an identifier".to_owned());
+        }
+
+        Ok(id)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(format!("expected `{expected}`")),
+        }
+    }
+}
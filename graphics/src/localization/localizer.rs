@@ -0,0 +1,414 @@
+use crate::{
+    locale_language, plural_category, AscendingError, Catalog, Element,
+    GpuRenderer, OtherError, Pattern, PluralCategory, Text, WorldUi,
+};
+use cosmic_text::{Attrs, Family, FontSystem};
+use std::{collections::HashMap, fmt, path::Path};
+
+/// References deeper than this are treated as cycles.
+const MAX_DEPTH: usize = 8;
+
+/// A value passed into a message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LocaleValue {
+    String(String),
+    Number(f64),
+}
+
+impl fmt::Display for LocaleValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocaleValue::String(s) => f.write_str(s),
+            // Whole numbers without the trailing .0.
+            LocaleValue::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => {
+                write!(f, "{}", *n as i64)
+            }
+            LocaleValue::Number(n) => write!(f, "{n}"),
+        }
+    }
+}
+
+impl From<&str> for LocaleValue {
+    fn from(value: &str) -> Self {
+        LocaleValue::String(value.to_owned())
+    }
+}
+
+impl From<String> for LocaleValue {
+    fn from(value: String) -> Self {
+        LocaleValue::String(value)
+    }
+}
+
+macro_rules! locale_number {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for LocaleValue {
+                fn from(value: $ty) -> Self {
+                    LocaleValue::Number(value as f64)
+                }
+            }
+        )*
+    };
+}
+
+locale_number!(i32, u32, i64, u64, usize, f32, f64);
+
+/// Named values for a message's `{ $name }` placeables.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LocaleArgs {
+    values: HashMap<String, LocaleValue>,
+}
+
+impl LocaleArgs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<LocaleValue>,
+    ) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    pub fn set(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Into<LocaleValue>,
+    ) -> &mut Self {
+        self.values.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LocaleValue> {
+        self.values.get(name)
+    }
+}
+
+/// A message id and its arguments, kept with a widget so its text can be
+/// looked up again when the language changes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LocalizedString {
+    pub id: String,
+    pub args: LocaleArgs,
+}
+
+impl LocalizedString {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            args: LocaleArgs::new(),
+        }
+    }
+
+    pub fn with_arg(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<LocaleValue>,
+    ) -> Self {
+        self.args.set(name, value);
+        self
+    }
+
+    pub fn resolve(&self, localizer: &Localizer) -> String {
+        localizer.format(&self.id, &self.args)
+    }
+}
+
+/// Loaded catalogs and the current language.
+///
+/// Messages are looked up in the current locale, then its language without
+/// the region ("pt" for "pt-BR"), then the fallbacks in order. Missing
+/// messages come back as their id so they are easy to spot.
+pub struct Localizer {
+    catalogs: HashMap<String, Catalog>,
+    locale: String,
+    fallbacks: Vec<String>,
+    /// Font families per locale or language, most preferred first.
+    fonts: HashMap<String, Vec<String>>,
+    /// Bumped on every language change so widgets know to update.
+    generation: u64,
+}
+
+impl Localizer {
+    pub fn new(locale: impl Into<String>) -> Self {
+        Self {
+            catalogs: HashMap::new(),
+            locale: locale.into(),
+            fallbacks: Vec::new(),
+            fonts: HashMap::new(),
+            generation: 0,
+        }
+    }
+
+    /// Adds messages to a locale, replacing ones with the same id.
+    pub fn add_catalog(&mut self, locale: impl Into<String>, catalog: Catalog) {
+        self.catalogs
+            .entry(locale.into())
+            .or_default()
+            .merge(catalog);
+    }
+
+    /// Loads a .ftl or .po file for a locale.
+    pub fn load_file(
+        &mut self,
+        locale: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<(), AscendingError> {
+        let path = path.as_ref();
+        let name = path.display().to_string();
+        let source = std::fs::read_to_string(path)?;
+        let catalog = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ftl") => Catalog::from_ftl(&name, &source)?,
+            Some("po") => Catalog::from_po(&name, locale, &source)?,
+            _ => {
+                return Err(AscendingError::Other(OtherError::new(&format!(
+                    "{name} is not a .ftl or .po catalog"
+                ))))
+            }
+        };
+
+        self.add_catalog(locale, catalog);
+        Ok(())
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Locales with a loaded catalog.
+    pub fn locales(&self) -> impl Iterator<Item = &str> {
+        self.catalogs.keys().map(String::as_str)
+    }
+
+    /// Switches the language. Returns false if it was already set.
+    pub fn set_locale(&mut self, locale: impl Into<String>) -> bool {
+        let locale = locale.into();
+
+        if locale == self.locale {
+            return false;
+        }
+
+        self.locale = locale;
+        self.generation += 1;
+        true
+    }
+
+    /// Locales to look in when the current one lacks a message.
+    pub fn set_fallbacks(&mut self, fallbacks: Vec<String>) {
+        self.fallbacks = fallbacks;
+        self.generation += 1;
+    }
+
+    /// Font families to use for a locale or a language, most preferred
+    /// first. The first one installed is used.
+    pub fn set_fonts(
+        &mut self,
+        locale: impl Into<String>,
+        families: Vec<String>,
+    ) {
+        self.fonts.insert(locale.into(), families);
+        self.generation += 1;
+    }
+
+    /// Changes every time the language, fallbacks or fonts change.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Font families configured for the current locale.
+    pub fn fonts(&self) -> &[String] {
+        self.fonts
+            .get(&self.locale)
+            .or_else(|| self.fonts.get(&locale_language(&self.locale)))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Text attributes with the first installed font of the current
+    /// locale, or the default font if none of them are.
+    pub fn attrs(&self, font_sys: &FontSystem) -> Attrs<'_> {
+        let installed = self.fonts().iter().find(|family| {
+            font_sys.db().faces().any(|face| {
+                face.families.iter().any(|(name, _)| name == *family)
+            })
+        });
+
+        match installed {
+            Some(family) => Attrs::new().family(Family::Name(family)),
+            None => Attrs::new(),
+        }
+    }
+
+    pub fn has(&self, id: &str) -> bool {
+        self.lookup(id).is_some()
+    }
+
+    /// The message without arguments.
+    pub fn get(&self, id: &str) -> String {
+        self.format(id, &LocaleArgs::default())
+    }
+
+    pub fn format(&self, id: &str, args: &LocaleArgs) -> String {
+        let mut out = String::new();
+
+        match self.lookup(id) {
+            Some((locale, pattern)) => {
+                self.write_pattern(&mut out, pattern, args, locale, 0)
+            }
+            None => {
+                log::debug!("missing message {id} for {}", self.locale);
+                out.push_str(id);
+            }
+        }
+
+        out
+    }
+
+    /// Formats a message that selects on `$count`.
+    pub fn plural(&self, id: &str, count: impl Into<f64>) -> String {
+        self.format(id, &LocaleArgs::new().with("count", count.into()))
+    }
+
+    pub fn plural_category(&self, n: f64) -> PluralCategory {
+        plural_category(&self.locale, n)
+    }
+
+    /// The message and the locale it was found in.
+    fn lookup(&self, id: &str) -> Option<(&str, &Pattern)> {
+        let language = locale_language(&self.locale);
+
+        std::iter::once(&self.locale)
+            .chain(std::iter::once(&language))
+            .chain(&self.fallbacks)
+            .find_map(|locale| {
+                let (locale, catalog) = self.catalogs.get_key_value(locale)?;
+
+                Some((locale.as_str(), catalog.message(id)?))
+            })
+    }
+
+    fn write_pattern(
+        &self,
+        out: &mut String,
+        pattern: &Pattern,
+        args: &LocaleArgs,
+        locale: &str,
+        depth: usize,
+    ) {
+        for element in pattern {
+            match element {
+                Element::Text(text) => out.push_str(text),
+                Element::Variable(name) => match args.get(name) {
+                    Some(value) => out.push_str(&value.to_string()),
+                    None => {
+                        out.push_str("{$");
+                        out.push_str(name);
+                        out.push('}');
+                    }
+                },
+                Element::Reference(id) => match self.lookup(id) {
+                    Some((locale, pattern)) if depth < MAX_DEPTH => self
+                        .write_pattern(out, pattern, args, locale, depth + 1),
+                    _ => out.push_str(id),
+                },
+                Element::Select {
+                    selector,
+                    variants,
+                    default,
+                } => {
+                    let key = |value: &str| {
+                        variants.iter().position(|(key, _)| key == value)
+                    };
+                    let index = match args.get(selector) {
+                        Some(LocaleValue::Number(n)) => variants
+                            .iter()
+                            .position(|(key, _)| key.parse() == Ok(*n))
+                            .or_else(
+                                || key(plural_category(locale, *n).name()),
+                            ),
+                        Some(LocaleValue::String(s)) => key(s),
+                        None => None,
+                    };
+                    let (_, pattern) = &variants[index.unwrap_or(*default)];
+
+                    self.write_pattern(out, pattern, args, locale, depth);
+                }
+            }
+        }
+    }
+}
+
+impl Text {
+    /// Sets the text to a message in the localizer's font.
+    pub fn set_localized(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        localizer: &Localizer,
+        message: &LocalizedString,
+    ) -> &mut Self {
+        let content = message.resolve(localizer);
+        let attrs = localizer.attrs(&renderer.font_sys);
+
+        self.buffer.set_text(
+            &mut renderer.font_sys,
+            &content,
+            attrs,
+            cosmic_text::Shaping::Advanced,
+        );
+        self.changed = true;
+        self
+    }
+}
+
+impl WorldUi {
+    /// Gives a text a message and sets it. It is looked up again by
+    /// [`WorldUi::localize`] when the language changes.
+    pub fn set_localized(
+        &mut self,
+        index: usize,
+        message: LocalizedString,
+        renderer: &mut GpuRenderer,
+        localizer: &Localizer,
+    ) {
+        if let Some(text) = self.texts.get_mut(index) {
+            text.text.set_localized(renderer, localizer, &message);
+            text.content = message.resolve(localizer);
+            text.localized = Some(message);
+            self.mark_changed();
+        }
+    }
+
+    /// Looks up the localized texts again after a language change, so they
+    /// are shaped with the new strings and font. Does nothing if the
+    /// localizer hasn't changed since the last call.
+    pub fn localize(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        localizer: &Localizer,
+    ) -> bool {
+        if self.locale_generation == Some(localizer.generation()) {
+            return false;
+        }
+
+        self.locale_generation = Some(localizer.generation());
+
+        let mut updated = false;
+
+        for text in &mut self.texts {
+            if let Some(message) = &text.localized {
+                text.text.set_localized(renderer, localizer, message);
+                text.content = message.resolve(localizer);
+                updated = true;
+            }
+        }
+
+        if updated {
+            self.mark_changed();
+        }
+
+        updated
+    }
+}
@@ -0,0 +1,120 @@
+/// CLDR plural category of a number. Select variants in catalogs are
+/// matched by these names.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn name(self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/// Language part of a locale, "pt-BR" and "pt_BR" give "pt".
+pub fn locale_language(locale: &str) -> String {
+    locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Cardinal plural category of n in the locale's language. Covers the
+/// common languages, others use the English rules.
+pub fn plural_category(locale: &str, n: f64) -> PluralCategory {
+    use PluralCategory::*;
+
+    let n = n.abs();
+    let integer = n.fract() == 0.0;
+    let i = n.trunc() as u64;
+    let (mod10, mod100) = (i % 10, i % 100);
+
+    let language = locale_language(locale);
+
+    match language.as_str() {
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" => Other,
+        "fr" | "pt" => {
+            if i <= 1 {
+                One
+            } else {
+                Other
+            }
+        }
+        "ru" | "uk" | "be" | "pl" => {
+            // Polish only uses one for 1 itself, not 21, 31 and so on.
+            let one = if language == "pl" {
+                i == 1
+            } else {
+                mod10 == 1 && mod100 != 11
+            };
+            let few = (2..=4).contains(&mod10) && !(12..=14).contains(&mod100);
+
+            if !integer {
+                Other
+            } else if one {
+                One
+            } else if few {
+                Few
+            } else {
+                Many
+            }
+        }
+        "cs" | "sk" => {
+            if !integer {
+                Many
+            } else if i == 1 {
+                One
+            } else if (2..=4).contains(&i) {
+                Few
+            } else {
+                Other
+            }
+        }
+        "ar" => {
+            if !integer {
+                Other
+            } else if i <= 2 {
+                [Zero, One, Two][i as usize]
+            } else if (3..=10).contains(&mod100) {
+                Few
+            } else if (11..=99).contains(&mod100) {
+                Many
+            } else {
+                Other
+            }
+        }
+        _ => {
+            if integer && i == 1 {
+                One
+            } else {
+                Other
+            }
+        }
+    }
+}
+
+/// Categories in the order gettext numbers the `msgstr[n]` forms.
+pub fn plural_forms(locale: &str) -> &'static [PluralCategory] {
+    use PluralCategory::*;
+
+    match locale_language(locale).as_str() {
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" => &[Other],
+        "ru" | "uk" | "be" | "pl" => &[One, Few, Many],
+        "cs" | "sk" => &[One, Few, Other],
+        "ar" => &[Zero, One, Two, Few, Many, Other],
+        _ => &[One, Other],
+    }
+}
@@ -1,9 +1,35 @@
+mod background;
+mod format;
+#[cfg(feature = "lights")]
+mod light_bake;
+#[cfg(feature = "sprites")]
+mod lod;
 mod map;
-mod pipeline;
+mod materials;
+mod metadata;
+mod overlay;
+pub(crate) mod pipeline;
+#[cfg(feature = "shapes")]
+mod region_debug;
+mod regions;
 mod render;
-mod vertex;
+mod transition;
+mod variants;
+pub(crate) mod vertex;
 
+pub use background::*;
+pub use format::*;
+#[cfg(feature = "sprites")]
+pub use lod::*;
 pub use map::*;
-pub use pipeline::*;
+pub use materials::*;
+pub use metadata::*;
+pub use overlay::*;
+pub(crate) use pipeline::*;
+#[cfg(feature = "shapes")]
+pub use region_debug::*;
+pub use regions::*;
 pub use render::*;
-pub use vertex::*;
+pub use transition::*;
+pub use variants::*;
+pub(crate) use vertex::*;
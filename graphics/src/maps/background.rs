@@ -0,0 +1,135 @@
+use crate::{
+    DrawOrder, GpuRenderer, Index, MapVertex, OrderedIndex, TileData, Vec2,
+    Vec3,
+};
+
+/// Z the background renders at. Behind the Ground layer of every map.
+pub const MAP_BACKGROUND_Z: f32 = 11.0;
+
+/// A tile layer drawn behind the maps with its own parallax factor, for
+/// skies, distant mountains and the like. Rendered with the lower maps.
+pub struct MapBackground {
+    /// its render position.
+    pub pos: Vec2,
+    /// Width and Height in tiles.
+    pub size: (u32, u32),
+    pub tiles: Vec<TileData>,
+    pub tilesize: u32,
+    /// Scales the camera translation applied to the background.
+    /// Defaults to 0.5 so it scrolls at half the speed of the maps.
+    pub parallax: f32,
+    pub store_id: Index,
+    /// the draw order of the background. created when update is called.
+    pub order: DrawOrder,
+    /// Used to deturmine if the background can be rendered.
+    pub can_render: bool,
+    /// if the position or a tile gets changed.
+    pub changed: bool,
+}
+
+impl MapBackground {
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        size: (u32, u32),
+        tilesize: u32,
+    ) -> Self {
+        Self {
+            pos: Vec2::default(),
+            size,
+            tiles: vec![TileData::default(); (size.0 * size.1) as usize],
+            tilesize,
+            parallax: 0.5,
+            store_id: renderer.new_buffer(),
+            order: DrawOrder::default(),
+            can_render: false,
+            changed: true,
+        }
+    }
+
+    pub fn create_quad(&mut self, renderer: &mut GpuRenderer) {
+        let mut buffer = Vec::with_capacity(self.tiles.len());
+
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                let tile = &self.tiles[(x + y * self.size.0) as usize];
+
+                if tile.texture_id == 0 && tile.color.a() == 0 {
+                    continue;
+                }
+
+                buffer.push(MapVertex {
+                    position: [
+                        self.pos.x + (x * self.tilesize) as f32,
+                        self.pos.y + (y * self.tilesize) as f32,
+                        MAP_BACKGROUND_Z,
+                    ],
+                    tilesize: self.tilesize as f32,
+                    texture_id: tile.texture_id as f32,
+                    texture_layer: tile.texture_layer as f32,
+                    color: tile.color.0,
+                    parallax: self.parallax,
+                    ao: 0,
+                    light: [u32::MAX; 4],
+                });
+            }
+        }
+
+        if let Some(store) = renderer.get_buffer_mut(&self.store_id) {
+            store.store = bytemuck::cast_slice(&buffer).to_vec();
+            store.changed = true;
+        }
+
+        // Layer 0 so it sorts before the maps.
+        self.order = DrawOrder::new(
+            false,
+            &Vec3::new(self.pos.x, self.pos.y, MAP_BACKGROUND_Z),
+            0,
+        );
+        self.changed = false;
+    }
+
+    pub fn get_tile(&self, pos: (u32, u32)) -> Option<TileData> {
+        if pos.0 >= self.size.0 || pos.1 >= self.size.1 {
+            return None;
+        }
+
+        Some(self.tiles[(pos.0 + pos.1 * self.size.0) as usize])
+    }
+
+    pub fn set_tile(&mut self, pos: (u32, u32), tile: TileData) {
+        if pos.0 >= self.size.0 || pos.1 >= self.size.1 {
+            return;
+        }
+
+        self.tiles[(pos.0 + pos.1 * self.size.0) as usize] = tile;
+        self.changed = true;
+    }
+
+    pub fn set_parallax(&mut self, parallax: f32) -> &mut Self {
+        self.parallax = parallax;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_pos(&mut self, pos: Vec2) -> &mut Self {
+        self.pos = pos;
+        self.changed = true;
+        self
+    }
+
+    /// used to check and update the vertex array.
+    pub fn update(
+        &mut self,
+        renderer: &mut GpuRenderer,
+    ) -> Option<OrderedIndex> {
+        if self.can_render {
+            if self.changed {
+                self.create_quad(renderer);
+            }
+
+            Some(OrderedIndex::new(self.order, self.store_id, 0))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use crate::{
+    AscendingError, CollisionFlags, GpuRenderer, Map, MapLayers, MapRegion,
+    RegionShape, TileData, TileMeta, Vec2, Vec4,
+};
+use cosmic_text::Color;
+
+/// First bytes of every map file.
+pub const MAP_MAGIC: [u8; 4] = *b"AMAP";
+/// Bumped when the layout changes. Older versions are still readable.
+pub const MAP_FORMAT_VERSION: u16 = 1;
+
+const CHUNK_TILES: u8 = 0;
+const CHUNK_METADATA: u8 = 1;
+const CHUNK_PARALLAX: u8 = 2;
+const CHUNK_WATER: u8 = 3;
+const CHUNK_REGIONS: u8 = 4;
+
+// Largest uncompressed size of each chunk kind. Sizes are read from the
+// file, so they are checked before anything is allocated for them.
+const TILES_SIZE: usize = 1024 * 6;
+const METADATA_SIZE: usize = 1024 * 6;
+const PARALLAX_SIZE: usize = MapLayers::Count as usize * 4;
+const WATER_SIZE: usize = 128;
+/// Regions have no fixed size, this is far past what a map would use.
+const REGIONS_SIZE: usize = 16 * 1024 * 1024;
+
+const REGION_RECT: u8 = 0;
+const REGION_POLYGON: u8 = 1;
+
+/// How each chunk's data is compressed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum MapCompression {
+    None,
+    #[default]
+    Lz4,
+}
+
+impl MapCompression {
+    fn id(self) -> u8 {
+        match self {
+            MapCompression::None => 0,
+            MapCompression::Lz4 => 1,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, AscendingError> {
+        match id {
+            0 => Ok(MapCompression::None),
+            1 => Ok(MapCompression::Lz4),
+            _ => Err(AscendingError::InvalidMapData("unknown compression")),
+        }
+    }
+
+    fn compress(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            MapCompression::None => data.to_vec(),
+            MapCompression::Lz4 => lz4_flex::block::compress(data),
+        }
+    }
+
+    /// Errors instead of decompressing past max, the largest size the
+    /// chunk can be.
+    fn decompress(
+        self,
+        data: &[u8],
+        size: usize,
+        max: usize,
+    ) -> Result<Vec<u8>, AscendingError> {
+        let size = match self {
+            MapCompression::None => data.len(),
+            MapCompression::Lz4 => size,
+        };
+
+        if size > max {
+            return Err(AscendingError::InvalidMapData("chunk is too large"));
+        }
+
+        match self {
+            MapCompression::None => Ok(data.to_vec()),
+            MapCompression::Lz4 => Ok(lz4_flex::block::decompress(data, size)?),
+        }
+    }
+}
+
+/// Reads little endian values out of a byte slice.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], AscendingError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(AscendingError::InvalidMapData("unexpected end of data"))?;
+        let bytes = &self.data[self.pos..end];
+
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, AscendingError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, AscendingError> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, AscendingError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, AscendingError> {
+        Ok(f32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+}
+
+fn write_chunk(
+    out: &mut Vec<u8>,
+    kind: u8,
+    index: u8,
+    compression: MapCompression,
+    data: &[u8],
+) {
+    let compressed = compression.compress(data);
+
+    out.push(kind);
+    out.push(index);
+    out.push(compression.id());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    out.extend_from_slice(&compressed);
+}
+
+impl Map {
+    /// Writes the map into the binary map format.
+    ///
+    /// Layout is a header, a palette of the tilesheet references used and
+    /// then chunks. Each chunk is a single layer, the metadata, the
+    /// parallax factors, the water tiles or the regions, and is compressed
+    /// on its own so a loader can stream or skip them. Empty layers and
+    /// maps without water or regions don't write those chunks.
+    pub fn to_bytes(&self, compression: MapCompression) -> Vec<u8> {
+        let mut palette: Vec<(u32, u8)> = Vec::new();
+        let mut chunks = Vec::new();
+        let mut chunk_count: u16 = 0;
+
+        for layer in 0..MapLayers::Count as usize {
+            if self.filled_tiles[layer] == 0 {
+                continue;
+            }
+
+            let mut data = Vec::with_capacity(TILES_SIZE);
+
+            for tile in &self.tiles[layer * 1024..(layer + 1) * 1024] {
+                let key = (tile.texture_id, tile.texture_layer);
+                let index = match palette.iter().position(|v| *v == key) {
+                    Some(index) => index,
+                    None => {
+                        palette.push(key);
+                        palette.len() - 1
+                    }
+                };
+
+                data.extend_from_slice(&(index as u16).to_le_bytes());
+                data.extend_from_slice(&tile.color.0.to_le_bytes());
+            }
+
+            write_chunk(
+                &mut chunks,
+                CHUNK_TILES,
+                layer as u8,
+                compression,
+                &data,
+            );
+            chunk_count += 1;
+        }
+
+        let mut data = Vec::with_capacity(METADATA_SIZE);
+
+        for meta in &self.metadata.tiles {
+            data.push(meta.collision.0);
+            data.extend_from_slice(&meta.trigger.to_le_bytes());
+            data.push(meta.move_cost);
+        }
+
+        write_chunk(&mut chunks, CHUNK_METADATA, 0, compression, &data);
+        chunk_count += 1;
+
+        let data: Vec<u8> = self
+            .parallax
+            .iter()
+            .flat_map(|parallax| parallax.to_le_bytes())
+            .collect();
+
+        write_chunk(&mut chunks, CHUNK_PARALLAX, 0, compression, &data);
+        chunk_count += 1;
+
+        if self.water.iter().any(|water| *water) {
+            // One bit per tile.
+            let mut data = vec![0u8; WATER_SIZE];
+
+            for (i, water) in self.water.iter().enumerate() {
+                if *water {
+                    data[i / 8] |= 1 << (i % 8);
+                }
+            }
+
+            write_chunk(&mut chunks, CHUNK_WATER, 0, compression, &data);
+            chunk_count += 1;
+        }
+
+        if !self.regions.is_empty() {
+            let mut data = Vec::new();
+
+            data.extend_from_slice(&(self.regions.len() as u16).to_le_bytes());
+
+            for region in &self.regions {
+                data.extend_from_slice(
+                    &(region.name.len() as u16).to_le_bytes(),
+                );
+                data.extend_from_slice(region.name.as_bytes());
+                data.extend_from_slice(&region.trigger.to_le_bytes());
+                data.push(region.enabled as u8);
+
+                match &region.shape {
+                    RegionShape::Rect(rect) => {
+                        data.push(REGION_RECT);
+
+                        for value in rect.to_array() {
+                            data.extend_from_slice(&value.to_le_bytes());
+                        }
+                    }
+                    RegionShape::Polygon(points) => {
+                        data.push(REGION_POLYGON);
+                        data.extend_from_slice(
+                            &(points.len() as u16).to_le_bytes(),
+                        );
+
+                        for point in points {
+                            data.extend_from_slice(&point.x.to_le_bytes());
+                            data.extend_from_slice(&point.y.to_le_bytes());
+                        }
+                    }
+                }
+            }
+
+            write_chunk(&mut chunks, CHUNK_REGIONS, 0, compression, &data);
+            chunk_count += 1;
+        }
+
+        let mut out = Vec::with_capacity(32 + palette.len() * 5 + chunks.len());
+
+        out.extend_from_slice(&MAP_MAGIC);
+        out.extend_from_slice(&MAP_FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&self.tilesize.to_le_bytes());
+        out.extend_from_slice(&self.pos.x.to_le_bytes());
+        out.extend_from_slice(&self.pos.y.to_le_bytes());
+        out.extend_from_slice(&(palette.len() as u16).to_le_bytes());
+
+        for (texture_id, texture_layer) in &palette {
+            out.extend_from_slice(&texture_id.to_le_bytes());
+            out.push(*texture_layer);
+        }
+
+        out.extend_from_slice(&chunk_count.to_le_bytes());
+        out.extend_from_slice(&chunks);
+        out
+    }
+
+    /// Reads a map written by [`Map::to_bytes`]. Unknown chunk kinds are
+    /// skipped so newer files still load what this version understands.
+    pub fn from_bytes(
+        renderer: &mut GpuRenderer,
+        bytes: &[u8],
+    ) -> Result<Self, AscendingError> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.bytes(4)? != MAP_MAGIC {
+            return Err(AscendingError::InvalidMapData("not a map file"));
+        }
+
+        let version = reader.u16()?;
+
+        if version == 0 || version > MAP_FORMAT_VERSION {
+            return Err(AscendingError::InvalidMapData(
+                "unsupported map format version",
+            ));
+        }
+
+        let tilesize = reader.u32()?;
+        let pos = Vec2::new(reader.f32()?, reader.f32()?);
+        let palette_count = reader.u16()?;
+        let mut palette = Vec::with_capacity(palette_count as usize);
+
+        for _ in 0..palette_count {
+            palette.push((reader.u32()?, reader.u8()?));
+        }
+
+        let mut map = Map::new(renderer, tilesize);
+        map.pos = pos;
+
+        let chunk_count = reader.u16()?;
+
+        for _ in 0..chunk_count {
+            let kind = reader.u8()?;
+            let index = reader.u8()? as usize;
+            let compression = MapCompression::from_id(reader.u8()?)?;
+            let size = reader.u32()? as usize;
+            let compressed_size = reader.u32()? as usize;
+            let compressed = reader.bytes(compressed_size)?;
+
+            match kind {
+                CHUNK_TILES => {
+                    if index >= MapLayers::Count as usize {
+                        return Err(AscendingError::InvalidMapData(
+                            "tile layer out of range",
+                        ));
+                    }
+
+                    let data =
+                        compression.decompress(compressed, size, TILES_SIZE)?;
+                    let mut chunk = Reader::new(&data);
+
+                    for i in 0..1024 {
+                        let entry = chunk.u16()? as usize;
+                        let color = Color(chunk.u32()?);
+                        let (texture_id, texture_layer) =
+                            *palette.get(entry).ok_or(
+                                AscendingError::InvalidMapData(
+                                    "palette index out of range",
+                                ),
+                            )?;
+
+                        let tile = TileData {
+                            texture_id,
+                            texture_layer,
+                            color,
+                        };
+
+                        map.set_tile((i % 32, i / 32, index as u32), tile);
+                    }
+                }
+                CHUNK_METADATA => {
+                    let data = compression.decompress(
+                        compressed,
+                        size,
+                        METADATA_SIZE,
+                    )?;
+                    let mut chunk = Reader::new(&data);
+
+                    for meta in map.metadata.tiles.iter_mut() {
+                        *meta = TileMeta {
+                            collision: CollisionFlags(chunk.u8()?),
+                            trigger: chunk.u32()?,
+                            move_cost: chunk.u8()?,
+                        };
+                    }
+                }
+                CHUNK_PARALLAX => {
+                    let data = compression.decompress(
+                        compressed,
+                        size,
+                        PARALLAX_SIZE,
+                    )?;
+                    let mut chunk = Reader::new(&data);
+
+                    for parallax in map.parallax.iter_mut() {
+                        if chunk.is_empty() {
+                            break;
+                        }
+
+                        *parallax = chunk.f32()?;
+                    }
+                }
+                CHUNK_WATER => {
+                    let data =
+                        compression.decompress(compressed, size, WATER_SIZE)?;
+
+                    if data.len() < WATER_SIZE {
+                        return Err(AscendingError::InvalidMapData(
+                            "water chunk too small",
+                        ));
+                    }
+
+                    for (i, water) in map.water.iter_mut().enumerate() {
+                        *water = data[i / 8] & (1 << (i % 8)) != 0;
+                    }
+                }
+                CHUNK_REGIONS => {
+                    let data = compression.decompress(
+                        compressed,
+                        size,
+                        REGIONS_SIZE,
+                    )?;
+                    let mut chunk = Reader::new(&data);
+                    let count = chunk.u16()?;
+
+                    for _ in 0..count {
+                        let len = chunk.u16()? as usize;
+                        let name =
+                            String::from_utf8(chunk.bytes(len)?.to_vec())
+                                .map_err(|_| {
+                                    AscendingError::InvalidMapData(
+                                        "region name is not utf8",
+                                    )
+                                })?;
+                        let trigger = chunk.u32()?;
+                        let enabled = chunk.u8()? != 0;
+                        let shape = match chunk.u8()? {
+                            REGION_RECT => RegionShape::Rect(Vec4::new(
+                                chunk.f32()?,
+                                chunk.f32()?,
+                                chunk.f32()?,
+                                chunk.f32()?,
+                            )),
+                            REGION_POLYGON => {
+                                let count = chunk.u16()?;
+                                let mut points =
+                                    Vec::with_capacity(count as usize);
+
+                                for _ in 0..count {
+                                    points.push(Vec2::new(
+                                        chunk.f32()?,
+                                        chunk.f32()?,
+                                    ));
+                                }
+
+                                RegionShape::Polygon(points)
+                            }
+                            _ => {
+                                return Err(AscendingError::InvalidMapData(
+                                    "unknown region shape",
+                                ))
+                            }
+                        };
+
+                        map.set_region(MapRegion {
+                            name,
+                            shape,
+                            trigger,
+                            enabled,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(map)
+    }
+}
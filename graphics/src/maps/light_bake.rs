@@ -0,0 +1,137 @@
+use crate::{AreaLight, Color, Map, Vec2, Vec4, LIGHT_CORNERS};
+
+/// Softness widths past a light's radius where its falloff is still seen.
+const FALLOFF_REACH: f32 = 8.0;
+
+/// The light shader's falloff, 1.0 near the center to 0.0 past c.
+fn fade(d: f32, c: f32, w: f32) -> f32 {
+    let w = w.max(0.000001);
+
+    1.0 / (1.0 + (-(c - d) / w).exp())
+}
+
+fn mix(a: Vec4, b: Vec4, t: f32) -> Vec4 {
+    a + (b - a) * t
+}
+
+fn color_to_vec4(color: Color) -> Vec4 {
+    Vec4::new(
+        color.r() as f32,
+        color.g() as f32,
+        color.b() as f32,
+        color.a() as f32,
+    ) / 255.0
+}
+
+fn vec4_to_color(color: Vec4) -> Color {
+    let color = (color.clamp(Vec4::ZERO, Vec4::ONE) * 255.0).round();
+
+    Color::rgba(color.x as u8, color.y as u8, color.z as u8, color.w as u8)
+}
+
+impl Map {
+    /// Bakes static area lights into the tiles, so the light pass only
+    /// needs the dynamic ones. world_color is the ambient overlay the
+    /// lights would cut through, which gets baked in too, so set the
+    /// light pass's world color to transparent after.
+    ///
+    /// The light overlay is baked as a tint on the tiles' colors per
+    /// corner. Dark overlays match the light pass, colored ones are close.
+    /// Light animations and cookies are left out, keep those lights
+    /// dynamic. Sprites on the map can be tinted to match with
+    /// [`Map::baked_light_at`].
+    pub fn bake_lights<'a>(
+        &mut self,
+        world_color: Vec4,
+        lights: impl IntoIterator<Item = &'a AreaLight>,
+    ) {
+        let size = (LIGHT_CORNERS - 1) as f32 * self.tilesize as f32;
+        let min = self.pos;
+        let max = self.pos + Vec2::splat(size);
+        // Lights too far off the map to reach any corner are skipped.
+        let lights: Vec<(Vec2, Vec4, f32, f32)> = lights
+            .into_iter()
+            .filter_map(|light| {
+                let cutoff = light.max_distance.max(0.1);
+                let reach = cutoff + light.dither.max(0.0) * FALLOFF_REACH;
+                let nearest = light.pos.clamp(min, max);
+
+                (nearest.distance(light.pos) <= reach).then_some((
+                    light.pos,
+                    color_to_vec4(light.color),
+                    cutoff,
+                    light.dither,
+                ))
+            })
+            .collect();
+        let mut baked =
+            Vec::with_capacity((LIGHT_CORNERS * LIGHT_CORNERS) as usize);
+
+        for y in 0..LIGHT_CORNERS {
+            for x in 0..LIGHT_CORNERS {
+                let corner = self.pos
+                    + Vec2::new(x as f32, y as f32) * self.tilesize as f32;
+                // Lights mix the overlay toward their color, in order.
+                let overlay = lights.iter().fold(
+                    world_color,
+                    |overlay, (pos, color, cutoff, dither)| {
+                        let value =
+                            fade(corner.distance(*pos), *cutoff, *dither);
+
+                        mix(overlay, *color, value)
+                    },
+                );
+                // The overlay over a white tile, as a multiplier.
+                let tint = Vec4::ONE * (1.0 - overlay.w) + overlay * overlay.w;
+
+                baked.push(vec4_to_color(tint.truncate().extend(1.0)).0);
+            }
+        }
+
+        self.baked_light = baked;
+        self.changed = true;
+    }
+
+    /// Goes back to unlit tiles.
+    pub fn clear_baked_lights(&mut self) {
+        if !self.baked_light.is_empty() {
+            self.baked_light.clear();
+            self.changed = true;
+        }
+    }
+
+    pub fn has_baked_lights(&self) -> bool {
+        !self.baked_light.is_empty()
+    }
+
+    /// The baked tint at a render space position, blended between the
+    /// nearest corners like on the tiles. White off the map or without a
+    /// bake.
+    pub fn baked_light_at(&self, pos: Vec2) -> Color {
+        let white = Color::rgba(255, 255, 255, 255);
+
+        if self.baked_light.is_empty() || self.tilesize == 0 {
+            return white;
+        }
+
+        let local = (pos - self.pos) / self.tilesize as f32;
+        let last = (LIGHT_CORNERS - 1) as f32;
+
+        if local.x < 0.0 || local.y < 0.0 || local.x > last || local.y > last {
+            return white;
+        }
+
+        let x0 = (local.x.floor() as u32).min(LIGHT_CORNERS - 2);
+        let y0 = (local.y.floor() as u32).min(LIGHT_CORNERS - 2);
+        let t = local - Vec2::new(x0 as f32, y0 as f32);
+        let corner = |x: u32, y: u32| {
+            color_to_vec4(Color(
+                self.baked_light[(x + y * LIGHT_CORNERS) as usize],
+            ))
+        };
+        let bottom = mix(corner(x0, y0), corner(x0 + 1, y0), t.x);
+        let top = mix(corner(x0, y0 + 1), corner(x0 + 1, y0 + 1), t.x);
+
+        vec4_to_color(mix(bottom, top, t.y))
+    }
+}
@@ -0,0 +1,297 @@
+use crate::{
+    AtlasGroup, Color, GpuRenderer, Image, ImageRenderer, Index, Map,
+    MapRenderer, OrderedIndex, TileData, TileSheet, Vec2, Vec3, Vec4,
+};
+use std::collections::HashMap;
+
+/// Average colors of a tileset's tiles, keyed by the texture_id and
+/// texture_layer their [`TileData`] uses.
+#[derive(Clone, Debug, Default)]
+pub struct TileColors {
+    colors: HashMap<(u32, u8), Color>,
+}
+
+impl TileColors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_tilesheet(sheet: &TileSheet) -> Self {
+        let mut colors = Self::new();
+
+        colors.add_tilesheet(sheet);
+        colors
+    }
+
+    pub fn add_tilesheet(&mut self, sheet: &TileSheet) {
+        for tile in &sheet.tiles {
+            self.colors
+                .insert((tile.id, tile.allocation.layer as u8), tile.average);
+        }
+    }
+
+    pub fn set(&mut self, texture_id: u32, texture_layer: u8, color: Color) {
+        self.colors.insert((texture_id, texture_layer), color);
+    }
+
+    /// The tile's average color tinted by its color. Transparent for
+    /// unknown tiles.
+    pub fn get(&self, tile: &TileData) -> Color {
+        let Some(average) =
+            self.colors.get(&(tile.texture_id, tile.texture_layer))
+        else {
+            return Color::rgba(0, 0, 0, 0);
+        };
+        let tint = |a: u8, b: u8| ((a as u32 * b as u32) / 255) as u8;
+
+        Color::rgba(
+            tint(average.r(), tile.color.r()),
+            tint(average.g(), tile.color.g()),
+            tint(average.b(), tile.color.b()),
+            tint(average.a(), tile.color.a()),
+        )
+    }
+}
+
+/// A map's downsampled texture, one texel per tile.
+struct LodChunk {
+    map: Index,
+    image: Image,
+    key: String,
+    /// Of the map's tiles when the texture was made.
+    tile_version: u64,
+}
+
+/// Draws maps from a texture with one texel per tile once the camera is
+/// zoomed far out, instead of every tile. The textures are made from the
+/// tiles' average colors and only remade after tiles change.
+///
+/// Switching uses two zooms so hovering around one does not flicker
+/// between the tiles and the textures. Water is still drawn by the
+/// [`crate::WaterRenderer`].
+pub struct MapLod {
+    pub colors: TileColors,
+    /// Camera zoom at or below which maps switch to their textures.
+    pub enter_zoom: f32,
+    /// Camera zoom at or above which they switch back to tiles. Keep it
+    /// above enter_zoom.
+    pub exit_zoom: f32,
+    active: bool,
+    chunks: Vec<LodChunk>,
+    next_key: u64,
+}
+
+impl MapLod {
+    pub fn new(colors: TileColors) -> Self {
+        Self {
+            colors,
+            enter_zoom: 0.35,
+            exit_zoom: 0.45,
+            active: false,
+            chunks: Vec::new(),
+            next_key: 0,
+        }
+    }
+
+    /// Call with the camera's zoom each frame. Returns true if it switched
+    /// between tiles and textures.
+    pub fn set_zoom(&mut self, zoom: f32) -> bool {
+        let active = if self.active {
+            zoom < self.exit_zoom
+        } else {
+            zoom <= self.enter_zoom
+        };
+        let switched = active != self.active;
+
+        self.active = active;
+        switched
+    }
+
+    /// if maps draw from their textures.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Maps with a texture made.
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// The map's texels, rows from the top like the texture. Layers are
+    /// blended over each other by their tiles' alpha.
+    fn texels(&self, map: &Map) -> Vec<u8> {
+        let mut texels = Vec::with_capacity(32 * 32 * 4);
+
+        for y in (0..32).rev() {
+            for x in 0..32 {
+                let mut texel = Vec4::ZERO;
+
+                for layer in 0..8 {
+                    let tile = map.get_tile((x, y, layer));
+
+                    if tile.is_empty() {
+                        continue;
+                    }
+
+                    let color = self.colors.get(&tile);
+                    let alpha = color.a() as f32 / 255.0;
+                    let rgb = Vec4::new(
+                        color.r() as f32,
+                        color.g() as f32,
+                        color.b() as f32,
+                        255.0,
+                    ) / 255.0;
+
+                    texel = rgb * alpha + texel * (1.0 - alpha);
+                }
+
+                // Blended premultiplied, the texture is not.
+                if texel.w > 0.0 {
+                    texel = (texel.truncate() / texel.w).extend(texel.w);
+                }
+
+                let texel =
+                    (texel.clamp(Vec4::ZERO, Vec4::ONE) * 255.0).round();
+
+                texels.extend_from_slice(&[
+                    texel.x as u8,
+                    texel.y as u8,
+                    texel.z as u8,
+                    texel.w as u8,
+                ]);
+            }
+        }
+
+        texels
+    }
+
+    /// Makes or remakes the map's texture if its tiles changed and updates
+    /// its image. atlas is the one images draw from.
+    pub fn chunk_update(
+        &mut self,
+        map: &Map,
+        atlas: &mut AtlasGroup,
+        renderer: &mut GpuRenderer,
+    ) -> Option<OrderedIndex> {
+        let index = match self
+            .chunks
+            .iter()
+            .position(|chunk| chunk.map == map.lowerstore_id)
+        {
+            Some(index) => index,
+            None => {
+                let key = format!("map_lod_{}", self.next_key);
+                let render_layer =
+                    map.layers.map_or(1, |(ground, _)| ground.index());
+
+                self.next_key += 1;
+                self.chunks.push(LodChunk {
+                    map: map.lowerstore_id,
+                    image: Image::new(None, renderer, render_layer),
+                    key,
+                    // Never matches, so the texture gets made.
+                    tile_version: u64::MAX,
+                });
+                self.chunks.len() - 1
+            }
+        };
+
+        let evicted = !atlas.contains(&self.chunks[index].key);
+
+        if self.chunks[index].tile_version != map.tile_version || evicted {
+            let texels = self.texels(map);
+            let chunk = &mut self.chunks[index];
+
+            // Same size each time, so it is written over in place.
+            let allocation = match atlas.get(&chunk.key) {
+                Some(allocation) => {
+                    atlas.atlas.upload_allocation(
+                        &texels,
+                        &allocation,
+                        renderer,
+                    );
+                    allocation
+                }
+                None => atlas.upload(
+                    chunk.key.clone(),
+                    &texels,
+                    32,
+                    32,
+                    0,
+                    renderer,
+                )?,
+            };
+
+            chunk.image.texture = Some(allocation);
+            chunk.image.uv = Vec4::new(0.0, 0.0, 32.0, 32.0);
+            chunk.image.changed = true;
+            chunk.tile_version = map.tile_version;
+        } else {
+            // Keeps it from eviction.
+            atlas.get(&self.chunks[index].key);
+        }
+
+        let chunk = &mut self.chunks[index];
+        let pos = map.pos + map.offset;
+        let pos = Vec3::new(pos.x, pos.y, map.tile_z(0, 0.0));
+        let hw = Vec2::splat(32.0 * map.tilesize as f32);
+
+        chunk.image.relocate(&atlas.atlas);
+
+        if chunk.image.pos != pos || chunk.image.hw != hw {
+            chunk.image.pos = pos;
+            chunk.image.hw = hw;
+            chunk.image.changed = true;
+        }
+
+        Some(chunk.image.update(renderer))
+    }
+
+    /// Adds the map to this frame, as tiles or as its texture.
+    pub fn map_update(
+        &mut self,
+        map: &mut Map,
+        map_renderer: &mut MapRenderer,
+        image_renderer: &mut ImageRenderer,
+        atlas: &mut AtlasGroup,
+        renderer: &mut GpuRenderer,
+    ) {
+        if !self.active {
+            map_renderer.map_update(map, renderer);
+        } else if let Some(index) = self.chunk_update(map, atlas, renderer) {
+            image_renderer.add_buffer_store(renderer, index);
+        }
+    }
+
+    /// Frees the map's texture, like when the map is unloaded.
+    pub fn remove(
+        &mut self,
+        map: &Map,
+        atlas: &mut AtlasGroup,
+        renderer: &mut GpuRenderer,
+    ) {
+        if let Some(index) = self
+            .chunks
+            .iter()
+            .position(|chunk| chunk.map == map.lowerstore_id)
+        {
+            let chunk = self.chunks.swap_remove(index);
+
+            atlas.atlas.remove(&chunk.key);
+            chunk.image.unload(renderer);
+        }
+    }
+}
+
+impl Map {
+    /// Call after changing the tiles directly instead of through
+    /// [`Map::set_tile`], so copies of them like the [`MapLod`] update.
+    pub fn mark_tiles_changed(&mut self) {
+        self.tile_version += 1;
+        self.changed = true;
+    }
+}
@@ -1,7 +1,13 @@
 use crate::{
-    DrawOrder, GpuRenderer, Index, MapVertex, OrderedIndex, Vec2, Vec3,
+    CollisionFlags, DrawLayer, DrawOrder, GpuRenderer, Index, MapMetadata,
+    MapOverlay, MapRegion, MapVertex, MaterialTable, OrderedIndex, TileMeta,
+    TileVariantGroup, Vec2, Vec3, WaterVertex, WATER_Z,
 };
 use cosmic_text::Color;
+use std::sync::Arc;
+
+/// Tile corners per side of a map, for baked light.
+pub(crate) const LIGHT_CORNERS: u32 = 33;
 
 #[allow(dead_code)]
 #[derive(Copy, Clone)]
@@ -55,6 +61,13 @@ pub struct TileData {
     pub color: Color,
 }
 
+impl TileData {
+    /// if the tile draws nothing.
+    pub fn is_empty(&self) -> bool {
+        self.texture_id == 0 || self.color.a() == 0
+    }
+}
+
 impl Default for TileData {
     fn default() -> Self {
         Self {
@@ -77,13 +90,48 @@ pub struct Map {
     pub lowerstore_id: Index,
     /// vertex array in bytes for fringe layers.
     pub upperstore_id: Index,
+    /// vertex array in bytes for the water tiles.
+    pub waterstore_id: Index,
     /// the draw order of the maps. created when update is called.
     pub order: DrawOrder,
     /// count if any Filled Tiles Exist. this is to optimize out empty maps in rendering.
     pub filled_tiles: [u16; MapLayers::Count as usize],
+    /// Parallax factor per layer. Scales the camera translation applied to
+    /// the layer. 1.0 scrolls with the camera, lower scrolls slower.
+    pub parallax: [f32; MapLayers::Count as usize],
+    /// Opacity per layer, for fading maps in and out. Layers at 0.0 are
+    /// not drawn.
+    pub opacity: [f32; MapLayers::Count as usize],
+    /// Added to the position of every tile without moving the map, like
+    /// for sliding it in.
+    pub offset: Vec2,
+    /// Ground and overhead layers the lower and fringe tiles are spread
+    /// over. None keeps the fixed z of [`MapLayers::layerz`].
+    pub layers: Option<(DrawLayer, DrawLayer)>,
     // The size of the Tile to render. for spacing tiles out upon
     // vertex creation. Default will be 20.
     pub tilesize: u32,
+    /// Collision, triggers and movement costs. Not rendered.
+    pub metadata: MapMetadata,
+    /// Named trigger areas, see [`crate::RegionTracker`]. Not rendered.
+    pub regions: Vec<MapRegion>,
+    /// Materials of the tileset's tiles, shared by the maps using it.
+    pub materials: Option<Arc<MaterialTable>>,
+    /// Bumped by every tile change, so copies of the tiles like the
+    /// [`crate::MapLod`] know when to update.
+    pub(crate) tile_version: u64,
+    /// Tiles drawn as reflective water by the WaterRenderer.
+    pub water: [bool; 1024],
+    /// How dark the corners get next to blocked tiles. 0.0 disables the
+    /// baked ambient occlusion.
+    pub ao_strength: f32,
+    /// Baked light tint per tile corner, row by row from the bottom left.
+    /// Empty when nothing is baked.
+    pub(crate) baked_light: Vec<u32>,
+    /// Edits previewed over the tiles until applied or discarded.
+    overlay: MapOverlay,
+    /// How strongly the overlay shows over the tiles under it.
+    pub overlay_opacity: f32,
     // Used to deturmine if the map can be rendered or if its just a preload.
     pub can_render: bool,
     /// if the position or a tile gets changed.
@@ -91,33 +139,182 @@ pub struct Map {
 }
 
 impl Map {
+    /// Bakes the corner darkening of every tile from its blocked neighbours.
+    /// Each corner checks its two side tiles and the diagonal between them.
+    /// Blocked tiles themselves are not darkened.
+    fn bake_ao(&self) -> [u32; 1024] {
+        let mut ao = [0u32; 1024];
+
+        if self.ao_strength <= 0.0 {
+            return ao;
+        }
+
+        let solid = |x: i32, y: i32| -> bool {
+            if !(0..32).contains(&x) || !(0..32).contains(&y) {
+                return false;
+            }
+
+            self.metadata
+                .get(x as u32, y as u32)
+                .map(|meta| meta.collision.contains(CollisionFlags::BLOCKED))
+                .unwrap_or(false)
+        };
+        let strength = self.ao_strength.clamp(0.0, 1.0);
+
+        for x in 0..32 {
+            for y in 0..32 {
+                if solid(x, y) {
+                    continue;
+                }
+
+                let mut packed = 0;
+
+                // Same corner order as the vertices in the shader.
+                for (corner, (dx, dy)) in
+                    [(-1, -1), (1, -1), (1, 1), (-1, 1)].iter().enumerate()
+                {
+                    let side1 = solid(x + dx, y);
+                    let side2 = solid(x, y + dy);
+                    let occlusion = if side1 && side2 {
+                        3
+                    } else {
+                        side1 as u32
+                            + side2 as u32
+                            + solid(x + dx, y + dy) as u32
+                    };
+                    let value =
+                        (occlusion as f32 / 3.0 * strength * 255.0) as u32;
+
+                    packed |= value.min(255) << (corner * 8);
+                }
+
+                ao[(x + y * 32) as usize] = packed;
+            }
+        }
+
+        ao
+    }
+
+    /// z of a tile layer, moved ahead by a share of the gap to the next.
+    pub(crate) fn tile_z(&self, layer: u32, ahead: f32) -> f32 {
+        match self.layers {
+            Some((ground, _)) if layer < 6 => {
+                ground.z((6.0 - layer as f32 - ahead) / 6.0)
+            }
+            Some((_, overhead)) => {
+                overhead.z((8.0 - layer as f32 - ahead) / 2.0)
+            }
+            None => MapLayers::indexed_layerz(layer) - ahead,
+        }
+    }
+
+    /// Baked light of a tile's corners in vertex order.
+    fn corner_lights(&self, x: u32, y: u32) -> [u32; 4] {
+        if self.baked_light.is_empty() {
+            return [u32::MAX; 4];
+        }
+
+        let corner =
+            |x: u32, y: u32| self.baked_light[(x + y * LIGHT_CORNERS) as usize];
+
+        [
+            corner(x, y),
+            corner(x + 1, y),
+            corner(x + 1, y + 1),
+            corner(x, y + 1),
+        ]
+    }
+
+    fn create_water(&self) -> Vec<WaterVertex> {
+        let mut buffer = Vec::new();
+        let pos = self.pos + self.offset;
+        // Between the ground and the first mask layer.
+        let z = match self.layers {
+            Some(_) => self.tile_z(0, 0.5),
+            None => WATER_Z,
+        };
+
+        for x in 0..32 {
+            for y in 0..32 {
+                if !self.water[(x + y * 32) as usize] {
+                    continue;
+                }
+
+                // The reflection mirrors around the top of this column of water.
+                let mut top = y;
+
+                while top < 31 && self.water[(x + (top + 1) * 32) as usize] {
+                    top += 1;
+                }
+
+                buffer.push(WaterVertex {
+                    position: [
+                        pos.x + (x * self.tilesize) as f32,
+                        pos.y + (y * self.tilesize) as f32,
+                        z,
+                    ],
+                    tilesize: self.tilesize as f32,
+                    surface: pos.y + ((top + 1) * self.tilesize) as f32,
+                    parallax: self.parallax[MapLayers::Ground as usize],
+                });
+            }
+        }
+
+        buffer
+    }
+
     pub fn create_quad(&mut self, renderer: &mut GpuRenderer) {
         let mut lowerbuffer = Vec::new();
         let mut upperbuffer = Vec::new();
+        let ao = self.bake_ao();
+        let opacity = self.overlay_opacity.clamp(0.0, 1.0);
+        let pos = self.pos + self.offset;
+        let tile_vertex = |x: u32, y: u32, i: u32, tile: &TileData, z: f32| {
+            let layer_opacity = self.layer_opacity(i);
+
+            MapVertex {
+                position: [
+                    pos.x + (x * self.tilesize) as f32,
+                    pos.y + (y * self.tilesize) as f32,
+                    z,
+                ],
+                tilesize: self.tilesize as f32,
+                texture_id: tile.texture_id as f32,
+                texture_layer: tile.texture_layer as f32,
+                color: if layer_opacity < 1.0 {
+                    scale_alpha(tile.color, layer_opacity).0
+                } else {
+                    tile.color.0
+                },
+                parallax: self.parallax[i as usize],
+                // Fringe layers sit above the walls and stay unshaded.
+                ao: if i >= 6 { 0 } else { ao[(x + y * 32) as usize] },
+                light: self.corner_lights(x, y),
+            }
+        };
 
         for i in 0..8 {
-            let z = MapLayers::indexed_layerz(i);
+            let z = self.tile_z(i, 0.0);
 
-            if self.filled_tiles[i as usize] == 0 {
+            if (self.filled_tiles[i as usize] == 0
+                && !self.overlay.has_layer(i))
+                || self.layer_opacity(i) <= 0.0
+            {
                 continue;
             }
 
             for x in 0..32 {
                 for y in 0..32 {
-                    let tile =
-                        &self.tiles[(x + (y * 32) + (i * 1024)) as usize];
-
-                    let map_vertex = MapVertex {
-                        position: [
-                            self.pos.x + (x * self.tilesize) as f32,
-                            self.pos.y + (y * self.tilesize) as f32,
-                            z,
-                        ],
-                        tilesize: self.tilesize as f32,
-                        texture_id: tile.texture_id as f32,
-                        texture_layer: tile.texture_layer as f32,
-                        color: tile.color.0,
-                    };
+                    let mut tile =
+                        self.tiles[(x + (y * 32) + (i * 1024)) as usize];
+
+                    // Fade tiles the overlay is erasing.
+                    if self.overlay.get((x, y, i)).is_some_and(|t| t.is_empty())
+                    {
+                        tile.color = scale_alpha(tile.color, 1.0 - opacity);
+                    }
+
+                    let map_vertex = tile_vertex(x, y, i, &tile, z);
 
                     if i >= 6 {
                         upperbuffer.push(map_vertex);
@@ -128,6 +325,24 @@ impl Map {
             }
         }
 
+        // Drawn last and slightly in front so they blend over their layer.
+        for ((x, y, i), mut tile) in self.overlay.iter() {
+            if tile.is_empty() || self.layer_opacity(i) <= 0.0 {
+                continue;
+            }
+
+            tile.color = scale_alpha(tile.color, opacity);
+
+            let z = self.tile_z(i, 0.5);
+            let map_vertex = tile_vertex(x, y, i, &tile, z);
+
+            if i >= 6 {
+                upperbuffer.push(map_vertex);
+            } else {
+                lowerbuffer.push(map_vertex);
+            }
+        }
+
         if let Some(store) = renderer.get_buffer_mut(&self.lowerstore_id) {
             store.store = bytemuck::cast_slice(&lowerbuffer).to_vec();
             store.changed = true;
@@ -138,8 +353,20 @@ impl Map {
             store.changed = true;
         }
 
-        self.order =
-            DrawOrder::new(false, &Vec3::new(self.pos.x, self.pos.y, 1.0), 1);
+        let waterbuffer = self.create_water();
+
+        if let Some(store) = renderer.get_buffer_mut(&self.waterstore_id) {
+            store.store = bytemuck::cast_slice(&waterbuffer).to_vec();
+            store.changed = true;
+        }
+
+        let render_layer = self.layers.map_or(1, |(ground, _)| ground.index());
+
+        self.order = DrawOrder::new(
+            false,
+            &Vec3::new(self.pos.x, self.pos.y, 1.0),
+            render_layer,
+        );
         self.changed = false;
     }
 
@@ -149,9 +376,23 @@ impl Map {
             pos: Vec2::default(),
             lowerstore_id: renderer.new_buffer(),
             upperstore_id: renderer.new_buffer(),
+            waterstore_id: renderer.new_buffer(),
             filled_tiles: [0; MapLayers::Count as usize],
+            parallax: [1.0; MapLayers::Count as usize],
+            opacity: [1.0; MapLayers::Count as usize],
+            offset: Vec2::ZERO,
+            layers: None,
             order: DrawOrder::default(),
             tilesize,
+            metadata: MapMetadata::default(),
+            regions: Vec::new(),
+            materials: None,
+            tile_version: 0,
+            water: [false; 1024],
+            ao_strength: 0.5,
+            baked_light: Vec::new(),
+            overlay: MapOverlay::default(),
+            overlay_opacity: 0.6,
             can_render: false,
             changed: true,
         }
@@ -187,9 +428,251 @@ impl Map {
         }
 
         self.tiles[tilepos] = tile;
+        self.tile_version += 1;
+        self.changed = true;
+    }
+
+    /// Sets the tile to a variant picked from the group. The pick is seeded
+    /// by the tile's position in the world so neighbouring maps don't repeat.
+    pub fn set_tile_variant(
+        &mut self,
+        pos: (u32, u32, u32),
+        group: &TileVariantGroup,
+    ) {
+        let x = (self.pos.x / self.tilesize as f32) as i32 + pos.0 as i32;
+        let y = (self.pos.y / self.tilesize as f32) as i32 + pos.1 as i32;
+
+        if let Some(tile) = group.pick(x, y, pos.2) {
+            self.set_tile(pos, tile);
+        }
+    }
+
+    /// Fills a whole layer from the group. For scattered decorations add an
+    /// empty [`TileData`] with alpha 0 as one of the variants, its weight
+    /// controls how sparse the decorations are.
+    pub fn fill_layer_variants(
+        &mut self,
+        layer: u32,
+        group: &TileVariantGroup,
+    ) {
+        if layer >= 8 {
+            return;
+        }
+
+        for x in 0..32 {
+            for y in 0..32 {
+                self.set_tile_variant((x, y, layer), group);
+            }
+        }
+    }
+
+    pub fn overlay(&self) -> &MapOverlay {
+        &self.overlay
+    }
+
+    /// Previews a tile over the committed one without changing it.
+    pub fn set_overlay_tile(&mut self, pos: (u32, u32, u32), tile: TileData) {
+        self.overlay.set(pos, tile);
+        self.changed = true;
+    }
+
+    pub fn remove_overlay_tile(&mut self, pos: (u32, u32, u32)) {
+        if self.overlay.remove(pos).is_some() {
+            self.changed = true;
+        }
+    }
+
+    /// The overlay tile at pos if there is one, otherwise the committed tile.
+    pub fn preview_tile(&self, pos: (u32, u32, u32)) -> TileData {
+        self.overlay.get(pos).unwrap_or_else(|| self.get_tile(pos))
+    }
+
+    pub fn set_overlay_opacity(&mut self, opacity: f32) {
+        if self.overlay_opacity != opacity {
+            self.overlay_opacity = opacity;
+
+            if !self.overlay.is_empty() {
+                self.changed = true;
+            }
+        }
+    }
+
+    /// Drops every overlay tile, leaving the committed tiles as they were.
+    pub fn discard_overlay(&mut self) {
+        self.take_overlay();
+    }
+
+    /// Removes the overlay to commit it some other way, like through
+    /// [`crate::SetTile::from_overlay`] so it can be undone.
+    pub fn take_overlay(&mut self) -> MapOverlay {
+        if !self.overlay.is_empty() {
+            self.changed = true;
+        }
+
+        std::mem::take(&mut self.overlay)
+    }
+
+    /// Commits every overlay tile to the map at once and clears the overlay.
+    pub fn apply_overlay(&mut self) {
+        for (pos, tile) in self.take_overlay().iter() {
+            self.set_tile(pos, tile);
+        }
+    }
+
+    /// Spreads the ground tile layers and water over ground and the fringe
+    /// layers over overhead, so sprites in the layers between draw in
+    /// between.
+    pub fn set_layers(&mut self, ground: DrawLayer, overhead: DrawLayer) {
+        self.layers = Some((ground, overhead));
         self.changed = true;
     }
 
+    pub fn set_layer_opacity(&mut self, layer: MapLayers, opacity: f32) {
+        if let Some(current) = self.opacity.get_mut(layer as usize) {
+            if *current != opacity {
+                *current = opacity;
+                self.changed = true;
+            }
+        }
+    }
+
+    /// Sets the opacity of every layer.
+    pub fn set_opacity(&mut self, opacity: f32) {
+        if self.opacity.iter().any(|current| *current != opacity) {
+            self.opacity = [opacity; MapLayers::Count as usize];
+            self.changed = true;
+        }
+    }
+
+    pub fn set_offset(&mut self, offset: Vec2) {
+        if self.offset != offset {
+            self.offset = offset;
+            self.changed = true;
+        }
+    }
+
+    fn layer_opacity(&self, layer: u32) -> f32 {
+        self.opacity
+            .get(layer as usize)
+            .copied()
+            .unwrap_or(1.0)
+            .clamp(0.0, 1.0)
+    }
+
+    pub fn set_layer_parallax(&mut self, layer: MapLayers, parallax: f32) {
+        if let Some(current) = self.parallax.get_mut(layer as usize) {
+            if *current != parallax {
+                *current = parallax;
+                self.changed = true;
+            }
+        }
+    }
+
+    pub fn set_ao_strength(&mut self, strength: f32) {
+        if self.ao_strength != strength {
+            self.ao_strength = strength;
+            self.changed = true;
+        }
+    }
+
+    pub fn is_water(&self, pos: (u32, u32)) -> bool {
+        if pos.0 >= 32 || pos.1 >= 32 {
+            return false;
+        }
+
+        self.water[(pos.0 + pos.1 * 32) as usize]
+    }
+
+    pub fn set_water(&mut self, pos: (u32, u32), water: bool) {
+        if pos.0 >= 32 || pos.1 >= 32 {
+            return;
+        }
+
+        let tilepos = (pos.0 + pos.1 * 32) as usize;
+
+        if self.water[tilepos] != water {
+            self.water[tilepos] = water;
+            self.changed = true;
+        }
+    }
+
+    /// The water tiles to give the WaterRenderer. None if the map can't be
+    /// rendered or has no water.
+    pub fn water_index(&self) -> Option<OrderedIndex> {
+        if self.can_render && self.water.iter().any(|water| *water) {
+            Some(OrderedIndex::new(self.order, self.waterstore_id, 0))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_meta(&self, pos: (u32, u32)) -> Option<TileMeta> {
+        self.metadata.get(pos.0, pos.1).copied()
+    }
+
+    pub fn set_meta(&mut self, pos: (u32, u32), meta: TileMeta) {
+        if let Some(current) = self.metadata.get_mut(pos.0, pos.1) {
+            // collision feeds the baked ambient occlusion.
+            if current.collision != meta.collision {
+                self.changed = true;
+            }
+
+            *current = meta;
+        }
+    }
+
+    /// Out of bounds positions count as blocked.
+    pub fn get_collision(&self, pos: (u32, u32)) -> CollisionFlags {
+        self.metadata
+            .get(pos.0, pos.1)
+            .map(|meta| meta.collision)
+            .unwrap_or(CollisionFlags::BLOCKED)
+    }
+
+    pub fn set_collision(
+        &mut self,
+        pos: (u32, u32),
+        collision: CollisionFlags,
+    ) {
+        if let Some(meta) = self.metadata.get_mut(pos.0, pos.1) {
+            if meta.collision != collision {
+                meta.collision = collision;
+                self.changed = true;
+            }
+        }
+    }
+
+    pub fn is_blocked(&self, pos: (u32, u32)) -> bool {
+        self.get_collision(pos).contains(CollisionFlags::BLOCKED)
+    }
+
+    /// Returns 0 if there is no trigger or pos is out of bounds.
+    pub fn get_trigger(&self, pos: (u32, u32)) -> u32 {
+        self.metadata
+            .get(pos.0, pos.1)
+            .map(|meta| meta.trigger)
+            .unwrap_or(0)
+    }
+
+    pub fn set_trigger(&mut self, pos: (u32, u32), trigger: u32) {
+        if let Some(meta) = self.metadata.get_mut(pos.0, pos.1) {
+            meta.trigger = trigger;
+        }
+    }
+
+    pub fn get_move_cost(&self, pos: (u32, u32)) -> u8 {
+        self.metadata
+            .get(pos.0, pos.1)
+            .map(|meta| meta.move_cost)
+            .unwrap_or(0)
+    }
+
+    pub fn set_move_cost(&mut self, pos: (u32, u32), move_cost: u8) {
+        if let Some(meta) = self.metadata.get_mut(pos.0, pos.1) {
+            meta.move_cost = move_cost;
+        }
+    }
+
     /// used to check and update the vertex array or Texture witht he image buffer.
     pub fn update(
         &mut self,
@@ -209,3 +692,9 @@ impl Map {
         }
     }
 }
+
+fn scale_alpha(color: Color, scale: f32) -> Color {
+    let alpha = (color.a() as f32 * scale).round() as u8;
+
+    Color::rgba(color.r(), color.g(), color.b(), alpha)
+}
@@ -0,0 +1,197 @@
+use crate::{Map, MapLayers, TileData, Vec2};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+
+/// What a tile is made of for gameplay, like grass or wood, so footsteps,
+/// dust and movement can follow the ground under a sprite.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TileMaterial {
+    pub name: String,
+    /// Sound played for each step, by name like the other sounds.
+    pub footstep_sound: Option<String>,
+    /// Particle effect spawned while walking, by name.
+    pub particle_effect: Option<String>,
+    /// Scales movement speed. 1.0 is normal, lower is slippery or slow.
+    pub friction: f32,
+    /// Extra cost to move onto the tile, added to the tile's own.
+    pub move_cost: u8,
+}
+
+impl Default for TileMaterial {
+    fn default() -> Self {
+        Self {
+            name: "Default".to_owned(),
+            footstep_sound: None,
+            particle_effect: None,
+            friction: 1.0,
+            move_cost: 0,
+        }
+    }
+}
+
+impl TileMaterial {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_footstep_sound(mut self, sound: impl Into<String>) -> Self {
+        self.footstep_sound = Some(sound.into());
+        self
+    }
+
+    pub fn with_particle_effect(mut self, effect: impl Into<String>) -> Self {
+        self.particle_effect = Some(effect.into());
+        self
+    }
+
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_move_cost(mut self, move_cost: u8) -> Self {
+        self.move_cost = move_cost;
+        self
+    }
+}
+
+/// The materials of a tileset's tiles. Tiles are keyed by the
+/// texture_id and texture_layer their [`TileData`] uses. Tiles without
+/// one use the first material, the default.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MaterialTable {
+    materials: Vec<TileMaterial>,
+    tiles: HashMap<(u32, u8), u16>,
+}
+
+impl Default for MaterialTable {
+    fn default() -> Self {
+        Self::new(TileMaterial::default())
+    }
+}
+
+impl MaterialTable {
+    pub fn new(default: TileMaterial) -> Self {
+        Self {
+            materials: vec![default],
+            tiles: HashMap::new(),
+        }
+    }
+
+    /// Adds the material, or replaces the one with the same name. Returns
+    /// its id.
+    pub fn add(&mut self, material: TileMaterial) -> u16 {
+        if let Some(id) = self.find(&material.name) {
+            self.materials[id as usize] = material;
+            return id;
+        }
+
+        self.materials.push(material);
+        (self.materials.len() - 1) as u16
+    }
+
+    pub fn find(&self, name: &str) -> Option<u16> {
+        self.materials
+            .iter()
+            .position(|material| material.name == name)
+            .map(|id| id as u16)
+    }
+
+    pub fn get(&self, id: u16) -> Option<&TileMaterial> {
+        self.materials.get(id as usize)
+    }
+
+    pub fn get_mut(&mut self, id: u16) -> Option<&mut TileMaterial> {
+        self.materials.get_mut(id as usize)
+    }
+
+    pub fn default_material(&self) -> &TileMaterial {
+        &self.materials[0]
+    }
+
+    pub fn materials(&self) -> &[TileMaterial] {
+        &self.materials
+    }
+
+    /// Gives the tile the material. Unknown ids are ignored.
+    pub fn set_tile(&mut self, tile: &TileData, material: u16) {
+        if (material as usize) < self.materials.len() {
+            self.tiles
+                .insert((tile.texture_id, tile.texture_layer), material);
+        }
+    }
+
+    /// The tile goes back to the default material.
+    pub fn clear_tile(&mut self, tile: &TileData) {
+        self.tiles.remove(&(tile.texture_id, tile.texture_layer));
+    }
+
+    /// Id of the tile's own material, None if it uses the default.
+    pub fn tile_material_id(&self, tile: &TileData) -> Option<u16> {
+        self.tiles
+            .get(&(tile.texture_id, tile.texture_layer))
+            .copied()
+    }
+
+    pub fn material_of(&self, tile: &TileData) -> &TileMaterial {
+        self.tile_material_id(tile)
+            .and_then(|id| self.get(id))
+            .unwrap_or_else(|| self.default_material())
+    }
+}
+
+impl Map {
+    /// Shares the tileset's materials with the map.
+    pub fn set_materials(&mut self, materials: Option<Arc<MaterialTable>>) {
+        self.materials = materials;
+    }
+
+    /// Material of the tile at a tile position. The highest layer under the
+    /// fringe with a tile that has its own material decides, else the
+    /// default. None without a table or outside the map.
+    pub fn material_at_tile(&self, pos: (u32, u32)) -> Option<&TileMaterial> {
+        let materials = self.materials.as_deref()?;
+
+        if pos.0 >= 32 || pos.1 >= 32 {
+            return None;
+        }
+
+        let material = (0..MapLayers::Fringe as u32)
+            .rev()
+            .map(|layer| self.get_tile((pos.0, pos.1, layer)))
+            .filter(|tile| !tile.is_empty())
+            .find_map(|tile| materials.tile_material_id(&tile))
+            .and_then(|id| materials.get(id))
+            .unwrap_or_else(|| materials.default_material());
+
+        Some(material)
+    }
+
+    /// Material under pos, which is in the same space as the map's
+    /// position, like where a sprite's feet are.
+    pub fn material_at(&self, pos: Vec2) -> Option<&TileMaterial> {
+        if self.tilesize == 0 {
+            return None;
+        }
+
+        let local = (pos - self.pos) / self.tilesize as f32;
+
+        if local.x < 0.0 || local.y < 0.0 {
+            return None;
+        }
+
+        self.material_at_tile((local.x as u32, local.y as u32))
+    }
+
+    /// Cost to move onto the tile, its own plus its material's.
+    pub fn total_move_cost(&self, pos: (u32, u32)) -> u8 {
+        let material = self
+            .material_at_tile(pos)
+            .map_or(0, |material| material.move_cost);
+
+        self.get_move_cost(pos).saturating_add(material)
+    }
+}
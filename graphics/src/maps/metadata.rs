@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+/// Collision bits for a tile. Directional bits block movement leaving
+/// the tile on that side.
+#[derive(
+    Copy, Clone, Debug, Default, Hash, Eq, PartialEq, Serialize, Deserialize,
+)]
+pub struct CollisionFlags(pub u8);
+
+impl CollisionFlags {
+    pub const NONE: Self = Self(0);
+    /// Fully solid. Nothing can enter the tile.
+    pub const BLOCKED: Self = Self(1);
+    pub const NORTH: Self = Self(1 << 1);
+    pub const SOUTH: Self = Self(1 << 2);
+    pub const EAST: Self = Self(1 << 3);
+    pub const WEST: Self = Self(1 << 4);
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl std::ops::BitOr for CollisionFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Non rendered data for a single tile position.
+#[derive(
+    Copy, Clone, Debug, Default, Hash, Eq, PartialEq, Serialize, Deserialize,
+)]
+pub struct TileMeta {
+    pub collision: CollisionFlags,
+    /// Game defined trigger id. 0 means no trigger.
+    pub trigger: u32,
+    /// Extra cost to move onto the tile. 0 is the normal cost.
+    pub move_cost: u8,
+}
+
+/// Per tile gameplay data for a 32x32 map. Stored with the map so the
+/// renderer, gameplay systems and the pathfinder all query the same map.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MapMetadata {
+    pub tiles: Vec<TileMeta>,
+}
+
+impl Default for MapMetadata {
+    fn default() -> Self {
+        Self {
+            tiles: vec![TileMeta::default(); 1024],
+        }
+    }
+}
+
+impl MapMetadata {
+    pub fn get(&self, x: u32, y: u32) -> Option<&TileMeta> {
+        if x >= 32 || y >= 32 {
+            return None;
+        }
+
+        self.tiles.get((x + y * 32) as usize)
+    }
+
+    pub fn get_mut(&mut self, x: u32, y: u32) -> Option<&mut TileMeta> {
+        if x >= 32 || y >= 32 {
+            return None;
+        }
+
+        self.tiles.get_mut((x + y * 32) as usize)
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.fill(TileMeta::default());
+    }
+}
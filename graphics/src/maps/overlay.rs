@@ -0,0 +1,54 @@
+use crate::TileData;
+use std::collections::BTreeMap;
+
+/// Pending tiles stacked over a [`crate::Map`]'s committed tiles, like a
+/// brush preview or unapplied edits. They render blended over the map but
+/// don't change it until applied.
+#[derive(Clone, Default)]
+pub struct MapOverlay {
+    tiles: BTreeMap<(u32, u32, u32), TileData>,
+}
+
+impl MapOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, pos: (u32, u32, u32)) -> Option<TileData> {
+        self.tiles.get(&pos).copied()
+    }
+
+    /// An empty tile previews erasing the tile under it.
+    pub fn set(&mut self, pos: (u32, u32, u32), tile: TileData) {
+        if pos.0 < 32 && pos.1 < 32 && pos.2 < 8 {
+            self.tiles.insert(pos, tile);
+        }
+    }
+
+    pub fn remove(&mut self, pos: (u32, u32, u32)) -> Option<TileData> {
+        self.tiles.remove(&pos)
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    pub fn has_layer(&self, layer: u32) -> bool {
+        self.tiles.keys().any(|pos| pos.2 == layer)
+    }
+
+    /// Tiles with their positions.
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = ((u32, u32, u32), TileData)> + '_ {
+        self.tiles.iter().map(|(pos, tile)| (*pos, *tile))
+    }
+}
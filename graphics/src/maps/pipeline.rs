@@ -1,5 +1,5 @@
 use crate::{
-    BufferLayout, GpuDevice, LayoutStorage, MapVertex, PipeLineLayout,
+    shaders, BufferLayout, GpuDevice, LayoutStorage, MapVertex, PipeLineLayout,
     StaticBufferObject, SystemLayout, TextureLayout,
 };
 use bytemuck::{Pod, Zeroable};
@@ -15,75 +15,97 @@ impl PipeLineLayout for MapRenderPipeline {
         layouts: &mut LayoutStorage,
         surface_format: wgpu::TextureFormat,
     ) -> wgpu::RenderPipeline {
-        let shader = gpu_device.device().create_shader_module(
-            wgpu::ShaderModuleDescriptor {
-                label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("../shaders/mapshader.wgsl").into(),
-                ),
-            },
-        );
+        create_map_pipeline(gpu_device, layouts, surface_format, true)
+    }
+}
 
-        let system_layout = layouts.create_layout(gpu_device, SystemLayout);
-        let texture_layout = layouts.create_layout(gpu_device, TextureLayout);
+/// Draws maps without writing depth, so maps fading in over them are
+/// never hidden by their layers in front.
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct MapFadeRenderPipeline;
 
-        // Create the render pipeline.
-        gpu_device.device().create_render_pipeline(
-            &wgpu::RenderPipelineDescriptor {
-                label: Some("Map render pipeline"),
-                layout: Some(&gpu_device.device().create_pipeline_layout(
-                    &wgpu::PipelineLayoutDescriptor {
-                        label: Some("Map_render_pipeline_layout"),
-                        bind_group_layouts: &[&system_layout, &texture_layout],
-                        push_constant_ranges: &[],
-                    },
-                )),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vertex",
-                    buffers: &[
-                        wgpu::VertexBufferLayout {
-                            array_stride: StaticBufferObject::stride(),
-                            step_mode: wgpu::VertexStepMode::Vertex,
-                            attributes: &[
-                                StaticBufferObject::vertex_attribute(),
-                            ],
-                        },
-                        wgpu::VertexBufferLayout {
-                            array_stride: MapVertex::stride() as u64,
-                            step_mode: wgpu::VertexStepMode::Instance,
-                            attributes: &MapVertex::attributes(),
-                        },
-                    ],
-                },
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: None,
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
+impl PipeLineLayout for MapFadeRenderPipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        create_map_pipeline(gpu_device, layouts, surface_format, false)
+    }
+}
+
+fn create_map_pipeline(
+    gpu_device: &mut GpuDevice,
+    layouts: &mut LayoutStorage,
+    surface_format: wgpu::TextureFormat,
+    depth_write: bool,
+) -> wgpu::RenderPipeline {
+    let shader = gpu_device.device().create_shader_module(
+        wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::MAP_SHADER.into()),
+        },
+    );
+
+    let system_layout = layouts.create_layout(gpu_device, SystemLayout);
+    let texture_layout = layouts.create_layout(gpu_device, TextureLayout);
+
+    // Create the render pipeline.
+    gpu_device.device().create_render_pipeline(
+        &wgpu::RenderPipelineDescriptor {
+            label: Some("Map render pipeline"),
+            layout: Some(&gpu_device.device().create_pipeline_layout(
+                &wgpu::PipelineLayoutDescriptor {
+                    label: Some("Map_render_pipeline_layout"),
+                    bind_group_layouts: &[&system_layout, &texture_layout],
+                    push_constant_ranges: &[],
                 },
-                depth_stencil: Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::LessEqual,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default(),
-                }),
-                multisample: wgpu::MultisampleState::default(),
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: "fragment",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: surface_format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                multiview: None,
+            )),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vertex",
+                buffers: &[
+                    wgpu::VertexBufferLayout {
+                        array_stride: StaticBufferObject::stride(),
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &[StaticBufferObject::vertex_attribute()],
+                    },
+                    wgpu::VertexBufferLayout {
+                        array_stride: MapVertex::stride() as u64,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &MapVertex::attributes(),
+                    },
+                ],
             },
-        )
-    }
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: depth_write,
+                depth_compare: gpu_device.depth_compare(),
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: gpu_device.multisample_state(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fragment",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        },
+    )
 }
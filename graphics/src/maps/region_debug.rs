@@ -0,0 +1,165 @@
+use crate::{
+    AscendingError, Color, DrawMode, GpuRenderer, Map, Mesh2D, Mesh2DBuilder,
+    Mesh2DRenderer, RegionShape, RegionTracker,
+};
+
+/// Draws the regions of maps as outlined areas, for checking where the
+/// triggers are. Occupied regions use the active colors.
+pub struct RegionDebug {
+    pub z: f32,
+    /// Maps are usually drawn with the camera, so this defaults to true.
+    pub use_camera: bool,
+    pub fill: Color,
+    pub outline: Color,
+    /// Fill and outline of regions a sensor is inside.
+    pub active_fill: Color,
+    pub active_outline: Color,
+    pub disabled_outline: Color,
+    pub outline_width: f32,
+    pub mesh: Mesh2D,
+    /// Shapes moved to the maps and their fill and outline colors.
+    shapes: Vec<(RegionShape, Option<Color>, Color)>,
+    visible: bool,
+    /// if the mesh needs building again.
+    changed: bool,
+}
+
+impl RegionDebug {
+    pub fn new(renderer: &mut GpuRenderer, z: f32) -> Self {
+        Self {
+            z,
+            use_camera: true,
+            fill: Color::rgba(60, 140, 255, 50),
+            outline: Color::rgba(60, 140, 255, 220),
+            active_fill: Color::rgba(250, 200, 40, 80),
+            active_outline: Color::rgba(250, 200, 40, 255),
+            disabled_outline: Color::rgba(140, 140, 140, 160),
+            outline_width: 2.0,
+            mesh: Mesh2D::new(renderer),
+            shapes: Vec::new(),
+            visible: true,
+            changed: true,
+        }
+    }
+
+    pub fn set_visible(&mut self, visible: bool) -> &mut Self {
+        self.visible = visible;
+        self
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Collects the regions to draw, coloring the ones a sensor of the
+    /// tracker is inside. The mesh is only rebuilt if they changed.
+    pub fn set_regions<'a>(
+        &mut self,
+        maps: impl IntoIterator<Item = &'a Map>,
+        tracker: Option<&RegionTracker>,
+    ) -> &mut Self {
+        let mut shapes = Vec::with_capacity(self.shapes.len());
+
+        for map in maps {
+            for region in &map.regions {
+                let (fill, outline) = if !region.enabled {
+                    (None, self.disabled_outline)
+                } else if tracker
+                    .map(|tracker| tracker.is_occupied(&region.name))
+                    .unwrap_or(false)
+                {
+                    (Some(self.active_fill), self.active_outline)
+                } else {
+                    (Some(self.fill), self.outline)
+                };
+
+                shapes.push((
+                    region.shape.translated(map.pos + map.offset),
+                    fill,
+                    outline,
+                ));
+            }
+        }
+
+        if shapes != self.shapes {
+            self.shapes = shapes;
+            self.changed = true;
+        }
+
+        self
+    }
+
+    /// Frees the mesh.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        renderer.remove_buffer(self.mesh.vbo_store_id);
+    }
+
+    fn build(&mut self) -> Result<(), AscendingError> {
+        let mut builder = if self.use_camera {
+            Mesh2DBuilder::with_camera()
+        } else {
+            Mesh2DBuilder::default()
+        };
+        let stroke = DrawMode::stroke(self.outline_width);
+
+        for (shape, fill, outline) in &self.shapes {
+            match shape {
+                RegionShape::Rect(rect) => {
+                    if let Some(fill) = fill {
+                        builder.rectangle(
+                            DrawMode::fill(),
+                            *rect,
+                            self.z,
+                            *fill,
+                        )?;
+                    }
+
+                    builder.rectangle(stroke, *rect, self.z, *outline)?;
+                }
+                RegionShape::Polygon(points) => {
+                    if points.len() < 3 {
+                        continue;
+                    }
+
+                    if let Some(fill) = fill {
+                        builder.polygon(
+                            DrawMode::fill(),
+                            points,
+                            self.z,
+                            *fill,
+                        )?;
+                    }
+
+                    builder.polygon(stroke, points, self.z, *outline)?;
+                }
+            }
+        }
+
+        self.mesh.vertices.clear();
+        self.mesh.indices.clear();
+        self.mesh.from_builder(builder.finalize());
+        self.mesh.changed = true;
+        self.changed = false;
+        Ok(())
+    }
+}
+
+impl Mesh2DRenderer {
+    /// Adds the region outlines while visible and there are any.
+    pub fn region_debug_update(
+        &mut self,
+        debug: &mut RegionDebug,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        if !debug.visible || debug.shapes.is_empty() {
+            return Ok(());
+        }
+
+        if debug.changed {
+            debug.build()?;
+        }
+
+        self.mesh_update(&mut debug.mesh, renderer);
+        Ok(())
+    }
+}
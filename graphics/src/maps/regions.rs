@@ -0,0 +1,290 @@
+use crate::{Map, Vec2, Vec4};
+use std::collections::HashSet;
+
+/// Area of a [`MapRegion`], in pixels from the map's bottom left.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegionShape {
+    /// X, Y, width and height.
+    Rect(Vec4),
+    /// Closed by joining the last point to the first. Needs at least 3
+    /// points to contain anything.
+    Polygon(Vec<Vec2>),
+}
+
+impl RegionShape {
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            RegionShape::Rect(rect) => {
+                point.x >= rect.x
+                    && point.y >= rect.y
+                    && point.x < rect.x + rect.z
+                    && point.y < rect.y + rect.w
+            }
+            RegionShape::Polygon(points) => {
+                if points.len() < 3 {
+                    return false;
+                }
+
+                // Even odd rule, counting the edges a ray to the right
+                // crosses.
+                let mut inside = false;
+                let mut j = points.len() - 1;
+
+                for i in 0..points.len() {
+                    let (a, b) = (points[i], points[j]);
+
+                    if (a.y > point.y) != (b.y > point.y)
+                        && point.x
+                            < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x
+                    {
+                        inside = !inside;
+                    }
+
+                    j = i;
+                }
+
+                inside
+            }
+        }
+    }
+
+    /// X, Y, width and height of the smallest rect around the shape.
+    pub fn bounds(&self) -> Vec4 {
+        match self {
+            RegionShape::Rect(rect) => *rect,
+            RegionShape::Polygon(points) => {
+                if points.is_empty() {
+                    return Vec4::ZERO;
+                }
+
+                let (min, max) = points.iter().fold(
+                    (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+                    |(min, max), point| (min.min(*point), max.max(*point)),
+                );
+
+                Vec4::new(min.x, min.y, max.x - min.x, max.y - min.y)
+            }
+        }
+    }
+
+    /// The same shape moved by offset.
+    pub fn translated(&self, offset: Vec2) -> Self {
+        match self {
+            RegionShape::Rect(rect) => RegionShape::Rect(Vec4::new(
+                rect.x + offset.x,
+                rect.y + offset.y,
+                rect.z,
+                rect.w,
+            )),
+            RegionShape::Polygon(points) => RegionShape::Polygon(
+                points.iter().map(|point| *point + offset).collect(),
+            ),
+        }
+    }
+}
+
+/// A named area of a map that fires events as sensors enter and leave
+/// it, like a door, a zone border or a cutscene start. Saved with the map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapRegion {
+    /// Unique within the map.
+    pub name: String,
+    pub shape: RegionShape,
+    /// Game defined id of what the region does, like the tile triggers.
+    pub trigger: u32,
+    /// Disabled regions fire nothing, sensors inside them leave.
+    pub enabled: bool,
+}
+
+impl MapRegion {
+    pub fn new(
+        name: impl Into<String>,
+        shape: RegionShape,
+        trigger: u32,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            shape,
+            trigger,
+            enabled: true,
+        }
+    }
+
+    pub fn rect(name: impl Into<String>, rect: Vec4, trigger: u32) -> Self {
+        Self::new(name, RegionShape::Rect(rect), trigger)
+    }
+
+    pub fn polygon(
+        name: impl Into<String>,
+        points: Vec<Vec2>,
+        trigger: u32,
+    ) -> Self {
+        Self::new(name, RegionShape::Polygon(points), trigger)
+    }
+}
+
+impl Map {
+    /// Adds the region, replacing the one with the same name.
+    pub fn set_region(&mut self, region: MapRegion) {
+        if let Some(current) =
+            self.regions.iter_mut().find(|r| r.name == region.name)
+        {
+            *current = region;
+        } else {
+            self.regions.push(region);
+        }
+    }
+
+    pub fn remove_region(&mut self, name: &str) -> Option<MapRegion> {
+        let index = self.regions.iter().position(|r| r.name == name)?;
+
+        Some(self.regions.remove(index))
+    }
+
+    pub fn region(&self, name: &str) -> Option<&MapRegion> {
+        self.regions.iter().find(|r| r.name == name)
+    }
+
+    pub fn region_mut(&mut self, name: &str) -> Option<&mut MapRegion> {
+        self.regions.iter_mut().find(|r| r.name == name)
+    }
+
+    /// Enabled regions containing pos, which is in the same space as the
+    /// map's position.
+    pub fn regions_at(&self, pos: Vec2) -> impl Iterator<Item = &MapRegion> {
+        let local = pos - self.pos;
+
+        self.regions
+            .iter()
+            .filter(move |r| r.enabled && r.shape.contains(local))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RegionEventKind {
+    Enter,
+    Leave,
+}
+
+/// A sensor crossing into or out of a region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionEvent {
+    pub kind: RegionEventKind,
+    pub sensor: usize,
+    pub region: String,
+    pub trigger: u32,
+}
+
+/// Fires [`RegionEvent`]s as the positions of registered sensors, like
+/// the player's and npcs' sprites, cross the regions of the maps given to
+/// [`RegionTracker::update`]. Regions are told apart by name, so a region
+/// spanning maps should use the same name on each.
+#[derive(Debug, Default)]
+pub struct RegionTracker {
+    sensors: Vec<(usize, Vec2)>,
+    /// Sensor, region name and its trigger, for each sensor inside.
+    inside: HashSet<(usize, String, u32)>,
+    events: Vec<RegionEvent>,
+}
+
+impl RegionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the sensor or moves it if the id is already used. Pos
+    /// is in the same space as the maps' positions.
+    pub fn set_sensor(&mut self, id: usize, pos: Vec2) -> &mut Self {
+        if let Some(sensor) = self.sensors.iter_mut().find(|(i, _)| *i == id) {
+            sensor.1 = pos;
+        } else {
+            self.sensors.push((id, pos));
+        }
+
+        self
+    }
+
+    /// The sensor leaves its regions on the next update.
+    pub fn remove_sensor(&mut self, id: usize) {
+        self.sensors.retain(|(i, _)| *i != id);
+    }
+
+    pub fn sensor(&self, id: usize) -> Option<Vec2> {
+        self.sensors
+            .iter()
+            .find(|(i, _)| *i == id)
+            .map(|(_, pos)| *pos)
+    }
+
+    /// if the sensor was inside the region at the last update.
+    pub fn is_inside(&self, sensor: usize, region: &str) -> bool {
+        self.inside
+            .iter()
+            .any(|(id, name, _)| *id == sensor && name == region)
+    }
+
+    /// Sensors inside the region at the last update.
+    pub fn sensors_in<'a>(
+        &'a self,
+        region: &'a str,
+    ) -> impl Iterator<Item = usize> + 'a {
+        self.inside
+            .iter()
+            .filter(move |(_, name, _)| name == region)
+            .map(|(id, _, _)| *id)
+    }
+
+    /// if any sensor was inside the region at the last update.
+    pub fn is_occupied(&self, region: &str) -> bool {
+        self.sensors_in(region).next().is_some()
+    }
+
+    /// Checks every sensor against the maps' regions and returns what
+    /// changed since the last update. Leaves come before enters, so
+    /// walking from one region into a touching one reads in order.
+    pub fn update<'a>(
+        &mut self,
+        maps: impl IntoIterator<Item = &'a Map>,
+    ) -> &[RegionEvent] {
+        let mut inside = HashSet::with_capacity(self.inside.len());
+
+        for map in maps {
+            for (id, pos) in &self.sensors {
+                for region in map.regions_at(*pos) {
+                    inside.insert((*id, region.name.clone(), region.trigger));
+                }
+            }
+        }
+
+        self.events.clear();
+
+        for (sensor, region, trigger) in self.inside.difference(&inside) {
+            self.events.push(RegionEvent {
+                kind: RegionEventKind::Leave,
+                sensor: *sensor,
+                region: region.clone(),
+                trigger: *trigger,
+            });
+        }
+
+        for (sensor, region, trigger) in inside.difference(&self.inside) {
+            self.events.push(RegionEvent {
+                kind: RegionEventKind::Enter,
+                sensor: *sensor,
+                region: region.clone(),
+                trigger: *trigger,
+            });
+        }
+
+        self.inside = inside;
+        &self.events
+    }
+
+    /// Forgets every sensor and which regions they were in, without
+    /// firing leaves.
+    pub fn clear(&mut self) {
+        self.sensors.clear();
+        self.inside.clear();
+        self.events.clear();
+    }
+}
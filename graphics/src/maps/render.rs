@@ -1,11 +1,14 @@
 use crate::{
     AsBufferPass, AscendingError, AtlasGroup, GpuRenderer, InstanceBuffer, Map,
-    MapRenderPipeline, MapVertex, OrderedIndex, SetBuffers, StaticBufferObject,
+    MapBackground, MapFadeRenderPipeline, MapRenderPipeline, MapVertex,
+    OrderedIndex, RenderCommands, SetBuffers, StaticBufferObject,
 };
 
 pub struct MapRenderer {
     pub maplower_buffer: InstanceBuffer<MapVertex>,
     pub mapupper_buffer: InstanceBuffer<MapVertex>,
+    /// if its maps are drawn without writing depth.
+    fading: bool,
 }
 
 impl MapRenderer {
@@ -22,9 +25,32 @@ impl MapRenderer {
                 renderer.gpu_device(),
                 2_048 * map_count as usize,
             ),
+            fading: false,
         })
     }
 
+    /// Draws the maps without writing depth, for the outgoing maps of a
+    /// [`crate::MapTransition`] so the incoming maps drawn after them
+    /// always show over them.
+    pub fn set_fading(&mut self, fading: bool) {
+        self.fading = fading;
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.fading
+    }
+
+    fn pipeline<'a>(
+        &self,
+        renderer: &'a GpuRenderer,
+    ) -> &'a wgpu::RenderPipeline {
+        if self.fading {
+            renderer.get_pipelines(MapFadeRenderPipeline).unwrap()
+        } else {
+            renderer.get_pipelines(MapRenderPipeline).unwrap()
+        }
+    }
+
     pub fn add_buffer_store(
         &mut self,
         renderer: &GpuRenderer,
@@ -44,6 +70,17 @@ impl MapRenderer {
             self.add_buffer_store(renderer, index);
         }
     }
+
+    /// Backgrounds render with the lower maps.
+    pub fn background_update(
+        &mut self,
+        background: &mut MapBackground,
+        renderer: &mut GpuRenderer,
+    ) {
+        if let Some(index) = background.update(renderer) {
+            self.maplower_buffer.add_buffer_store(renderer, index);
+        }
+    }
 }
 
 pub trait RenderMap<'a, 'b>
@@ -65,9 +102,10 @@ where
     );
 }
 
-impl<'a, 'b> RenderMap<'a, 'b> for wgpu::RenderPass<'a>
+impl<'a, 'b, P> RenderMap<'a, 'b> for P
 where
     'b: 'a,
+    P: RenderCommands<'a>,
 {
     fn render_lower_maps(
         &mut self,
@@ -79,9 +117,7 @@ where
             self.set_buffers(renderer.buffer_object.as_buffer_pass());
             self.set_bind_group(1, &atlas_group.texture.bind_group, &[]);
             self.set_vertex_buffer(1, buffer.maplower_buffer.instances(None));
-            self.set_pipeline(
-                renderer.get_pipelines(MapRenderPipeline).unwrap(),
-            );
+            self.set_pipeline(buffer.pipeline(renderer));
             self.draw_indexed(
                 0..StaticBufferObject::index_count(),
                 0,
@@ -100,9 +136,7 @@ where
             self.set_buffers(renderer.buffer_object.as_buffer_pass());
             self.set_bind_group(1, &atlas_group.texture.bind_group, &[]);
             self.set_vertex_buffer(1, buffer.mapupper_buffer.instances(None));
-            self.set_pipeline(
-                renderer.get_pipelines(MapRenderPipeline).unwrap(),
-            );
+            self.set_pipeline(buffer.pipeline(renderer));
             self.draw_indexed(
                 0..StaticBufferObject::index_count(),
                 0,
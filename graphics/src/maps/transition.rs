@@ -0,0 +1,158 @@
+use crate::{AnimationClock, Easing, Map, MapLayers, Vec2, WipeDirection};
+
+/// How the incoming maps replace the outgoing ones.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum MapTransitionKind {
+    /// The incoming maps fade in over the outgoing ones, whose overhead
+    /// layers fade out.
+    #[default]
+    CrossFade,
+    /// The incoming maps slide in from the side and push the outgoing
+    /// ones out the other.
+    Slide(WipeDirection),
+}
+
+/// Blends between two sets of maps while walking between areas, so the
+/// new area does not pop in. Both sets render while it runs, the outgoing
+/// one through a [`crate::MapRenderer`] set to fading and drawn first.
+///
+/// Start it, then each frame call [`MapTransition::update`] with whether
+/// the incoming maps and their textures are loaded, and apply it to both
+/// sets before their updates. It holds at the start until they are, so
+/// streaming never shows half a map. Unload the outgoing maps once
+/// [`MapTransition::take_finished`] returns true.
+pub struct MapTransition {
+    pub kind: MapTransitionKind,
+    pub easing: Easing,
+    /// How far slides move the maps, like the width of the view.
+    pub slide_distance: f32,
+    /// In seconds.
+    duration: f32,
+    elapsed: f32,
+    active: bool,
+    /// if it ran to the end.
+    complete: bool,
+    /// Set when it finishes and cleared when taken.
+    finished: bool,
+}
+
+impl Default for MapTransition {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MapTransition {
+    pub fn new() -> Self {
+        Self {
+            kind: MapTransitionKind::CrossFade,
+            easing: Easing::EaseInOut,
+            slide_distance: 640.0,
+            duration: 0.0,
+            elapsed: 0.0,
+            active: false,
+            complete: false,
+            finished: false,
+        }
+    }
+
+    /// Duration is in seconds.
+    pub fn start(&mut self, kind: MapTransitionKind, duration: f32) {
+        self.kind = kind;
+        self.duration = duration.max(0.0);
+        self.elapsed = 0.0;
+        self.active = true;
+        self.complete = false;
+        self.finished = false;
+    }
+
+    /// Advances by the clock's scaled delta once incoming_ready, so it
+    /// pauses with the game.
+    pub fn update(&mut self, clock: &AnimationClock, incoming_ready: bool) {
+        if !self.active || !incoming_ready {
+            return;
+        }
+
+        self.elapsed += clock.delta_seconds();
+
+        if self.elapsed >= self.duration {
+            self.elapsed = self.duration;
+            self.active = false;
+            self.complete = true;
+            self.finished = true;
+        }
+    }
+
+    /// Eased, from 0.0 showing the outgoing maps to 1.0 the incoming.
+    pub fn progress(&self) -> f32 {
+        if self.complete {
+            1.0
+        } else if self.duration > 0.0 {
+            self.easing.apply(self.elapsed / self.duration)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns true once after it finishes.
+    pub fn take_finished(&mut self) -> bool {
+        std::mem::take(&mut self.finished)
+    }
+
+    /// Unit step the incoming maps come in along.
+    fn direction(direction: WipeDirection) -> Vec2 {
+        match direction {
+            WipeDirection::Left => Vec2::new(1.0, 0.0),
+            WipeDirection::Right => Vec2::new(-1.0, 0.0),
+            // Render space is from the bottom.
+            WipeDirection::Top => Vec2::new(0.0, -1.0),
+            WipeDirection::Bottom => Vec2::new(0.0, 1.0),
+        }
+    }
+
+    /// Sets an outgoing map's opacity and offset for the progress.
+    pub fn apply_outgoing(&self, map: &mut Map) {
+        let progress = self.progress();
+
+        match self.kind {
+            MapTransitionKind::CrossFade => {
+                // The lower layers stay so nothing behind the maps shows
+                // through while the incoming ones fade in over them.
+                map.set_opacity(1.0);
+                map.set_layer_opacity(MapLayers::Fringe, 1.0 - progress);
+                map.set_layer_opacity(MapLayers::Fringe2, 1.0 - progress);
+                map.set_offset(Vec2::ZERO);
+            }
+            MapTransitionKind::Slide(direction) => {
+                map.set_opacity(1.0);
+                map.set_offset(
+                    Self::direction(direction) * self.slide_distance * progress,
+                );
+            }
+        }
+    }
+
+    /// Sets an incoming map's opacity and offset for the progress.
+    pub fn apply_incoming(&self, map: &mut Map) {
+        let progress = self.progress();
+
+        match self.kind {
+            MapTransitionKind::CrossFade => {
+                map.set_opacity(progress);
+                map.set_offset(Vec2::ZERO);
+            }
+            MapTransitionKind::Slide(direction) => {
+                map.set_opacity(1.0);
+                map.set_offset(
+                    Self::direction(direction)
+                        * self.slide_distance
+                        * (progress - 1.0),
+                );
+            }
+        }
+    }
+}
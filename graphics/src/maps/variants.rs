@@ -0,0 +1,96 @@
+use crate::{hash_u32, RngService, RngStream, TileData};
+
+/// A group of weighted alternative tiles. When placed the tile is picked
+/// deterministically from the tile's position so the same spot always gets
+/// the same variant without storing which one was chosen.
+#[derive(Clone, Default)]
+pub struct TileVariantGroup {
+    /// Tiles and their weights, private so total_weight stays in sync.
+    variants: Vec<(TileData, u32)>,
+    /// Sum of every variant's weight.
+    total_weight: u32,
+    /// Mixed into the hash so groups placed on the same tiles differ.
+    pub seed: u32,
+}
+
+impl TileVariantGroup {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            variants: Vec::new(),
+            total_weight: 0,
+            seed,
+        }
+    }
+
+    /// Seeded from the tile variant stream, so each group differs but the
+    /// same seed places the same variants.
+    pub fn from_rng(rng: &mut RngService) -> Self {
+        Self::new(rng.stream(RngStream::TileVariants).next_u32())
+    }
+
+    /// Adds a variant. Weights of 0 are never picked.
+    pub fn add(&mut self, tile: TileData, weight: u32) -> &mut Self {
+        self.variants.push((tile, weight));
+        self.total_weight = self.total_weight.saturating_add(weight);
+        self
+    }
+
+    /// Removes the variant at index, returning it and its weight.
+    pub fn remove(&mut self, index: usize) -> Option<(TileData, u32)> {
+        if index >= self.variants.len() {
+            return None;
+        }
+
+        let variant = self.variants.remove(index);
+
+        // Summed again as the total may have saturated.
+        self.total_weight = self
+            .variants
+            .iter()
+            .fold(0u32, |total, (_, weight)| total.saturating_add(*weight));
+        Some(variant)
+    }
+
+    pub fn clear(&mut self) {
+        self.variants.clear();
+        self.total_weight = 0;
+    }
+
+    /// The variants and their weights in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &(TileData, u32)> {
+        self.variants.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.variants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.variants.is_empty()
+    }
+
+    pub fn total_weight(&self) -> u32 {
+        self.total_weight
+    }
+
+    /// Picks a variant for the given tile coordinates. Returns None if the
+    /// group has no weighted variants.
+    pub fn pick(&self, x: i32, y: i32, layer: u32) -> Option<TileData> {
+        if self.total_weight == 0 {
+            return None;
+        }
+
+        let mut roll = hash_u32(&[x as u32, y as u32, layer], self.seed)
+            % self.total_weight;
+
+        for (tile, weight) in &self.variants {
+            if roll < *weight {
+                return Some(*tile);
+            }
+
+            roll -= weight;
+        }
+
+        None
+    }
+}
@@ -10,6 +10,15 @@ pub struct MapVertex {
     pub texture_id: f32,
     pub texture_layer: f32,
     pub color: u32,
+    /// How much of the camera's translation is applied. 1.0 moves with
+    /// the camera and 0.0 stays fixed on screen.
+    pub parallax: f32,
+    /// Baked corner darkening. One byte per corner in vertex order,
+    /// 0 is unshaded and 255 fully dark.
+    pub ao: u32,
+    /// Baked light tint per corner in vertex order, multiplied into the
+    /// tile's color. White is unlit.
+    pub light: [u32; 4],
 }
 
 impl Default for MapVertex {
@@ -20,13 +29,16 @@ impl Default for MapVertex {
             texture_id: 0.0,
             texture_layer: 0.0,
             color: 0,
+            parallax: 1.0,
+            ao: 0,
+            light: [u32::MAX; 4],
         }
     }
 }
 
 impl BufferLayout for MapVertex {
     fn attributes() -> Vec<wgpu::VertexAttribute> {
-        wgpu::vertex_attr_array![1 => Float32x3, 2 => Float32, 3 => Float32, 4 => Float32, 5 => Uint32]
+        wgpu::vertex_attr_array![1 => Float32x3, 2 => Float32, 3 => Float32, 4 => Float32, 5 => Uint32, 6 => Float32, 7 => Uint32, 8 => Uint32x4]
             .to_vec()
     }
 
@@ -50,6 +62,6 @@ impl BufferLayout for MapVertex {
     }
 
     fn stride() -> usize {
-        std::mem::size_of::<[f32; 7]>()
+        std::mem::size_of::<[f32; 13]>()
     }
 }
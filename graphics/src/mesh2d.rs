@@ -1,10 +1,10 @@
 mod meshs;
-mod pipeline;
+pub(crate) mod pipeline;
 mod render;
 mod vertex;
 
 pub use lyon::tessellation::{FillOptions, StrokeOptions};
 pub use meshs::*;
-pub use pipeline::*;
+pub(crate) use pipeline::*;
 pub use render::*;
 pub use vertex::*;
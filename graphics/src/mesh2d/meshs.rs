@@ -1,6 +1,7 @@
 use crate::{
-    AscendingError, BufferLayout, DrawOrder, GpuRenderer, Index, Mesh2DVertex,
-    OrderedIndex, OtherError, Vec2, Vec3, Vec4, VertexBuilder,
+    ArenaHandle, AscendingError, BufferArena, BufferLayout, DrawLayer,
+    DrawOrder, GpuRenderer, Index, Mesh2DVertex, OrderedIndex, OtherError,
+    Vec2, Vec3, Vec4, VertexBuilder,
 };
 use cosmic_text::Color;
 use lyon::{
@@ -33,7 +34,12 @@ pub struct Mesh2D {
     pub vertices: Vec<Mesh2DVertex>,
     pub indices: Vec<u32>,
     pub vbo_store_id: Index,
+    /// Where the mesh lives when drawn from a [`BufferArena`].
+    pub arena_handle: Option<ArenaHandle>,
     pub order: DrawOrder,
+    /// Orders it within its layer under [`crate::SortPolicy::Key`].
+    pub sort_key: u64,
+    pub render_layer: u32,
     pub high_index: u32,
     // if anything got updated we need to update the buffers too.
     pub changed: bool,
@@ -46,7 +52,10 @@ impl Mesh2D {
             size: Vec2::default(),
             color: Color::rgba(255, 255, 255, 255),
             vbo_store_id: renderer.new_buffer(),
+            arena_handle: None,
             order: DrawOrder::default(),
+            sort_key: 0,
+            render_layer: 1,
             changed: true,
             vertices: Vec::new(),
             indices: Vec::new(),
@@ -84,6 +93,29 @@ impl Mesh2D {
         self
     }
 
+    /// Moves the mesh into the layer with its front at a depth within it,
+    /// 0.0 at the front and 1.0 at the back. Vertices keep their z
+    /// offsets from the front.
+    pub fn set_layer(&mut self, layer: DrawLayer, depth: f32) -> &mut Self {
+        let z = layer.z(depth);
+        let offset = z - self.position.z;
+
+        for vertex in &mut self.vertices {
+            vertex.position[2] += offset;
+        }
+
+        self.position.z = z;
+        self.render_layer = layer.index();
+        self.changed = true;
+        self
+    }
+
+    pub fn set_sort_key(&mut self, sort_key: u64) -> &mut Self {
+        self.sort_key = sort_key;
+        self.changed = true;
+        self
+    }
+
     pub fn create_quad(&mut self, renderer: &mut GpuRenderer) {
         if let Some(store) = renderer.get_buffer_mut(&self.vbo_store_id) {
             let mut vertex_bytes = Vec::with_capacity(
@@ -104,7 +136,8 @@ impl Mesh2D {
             store.changed = true;
         }
 
-        self.order = DrawOrder::new(false, &self.position, 1);
+        self.order = DrawOrder::new(false, &self.position, self.render_layer)
+            .with_sort_key(self.sort_key);
     }
 
     // used to check and update the ShapeVertex array.
@@ -118,6 +151,49 @@ impl Mesh2D {
         OrderedIndex::new(self.order, self.vbo_store_id, self.high_index)
     }
 
+    /// Like update, but writes the mesh into an arena shared with other
+    /// meshes instead of its own buffer store.
+    pub fn arena_update(
+        &mut self,
+        renderer: &GpuRenderer,
+        arena: &mut BufferArena<Mesh2DVertex>,
+    ) -> ArenaHandle {
+        let vertices = bytemuck::cast_slice(&self.vertices);
+        let handle = match self.arena_handle {
+            Some(handle) if !self.changed && arena.contains(handle) => {
+                return handle;
+            }
+            Some(handle)
+                if arena.update(
+                    renderer.gpu_device(),
+                    handle,
+                    vertices,
+                    &self.indices,
+                ) =>
+            {
+                handle
+            }
+            _ => arena.alloc(renderer.gpu_device(), vertices, &self.indices),
+        };
+
+        self.order = DrawOrder::new(false, &self.position, self.render_layer)
+            .with_sort_key(self.sort_key);
+        self.arena_handle = Some(handle);
+        self.changed = false;
+        handle
+    }
+
+    /// Frees the mesh's space in the arena it was drawn from.
+    pub fn arena_unload(
+        &mut self,
+        renderer: &GpuRenderer,
+        arena: &mut BufferArena<Mesh2DVertex>,
+    ) {
+        if let Some(handle) = self.arena_handle.take() {
+            arena.free(renderer.gpu_device(), handle);
+        }
+    }
+
     pub fn check_mouse_bounds(&self, mouse_pos: Vec2) -> bool {
         mouse_pos[0] > self.position.x
             && mouse_pos[0] < self.position.x + self.size.x
@@ -1,6 +1,6 @@
 use crate::{
-    BufferLayout, GpuDevice, LayoutStorage, Mesh2DVertex, PipeLineLayout,
-    SystemLayout,
+    shaders, BufferLayout, GpuDevice, LayoutStorage, Mesh2DVertex,
+    PipeLineLayout, SystemLayout,
 };
 use bytemuck::{Pod, Zeroable};
 
@@ -18,9 +18,7 @@ impl PipeLineLayout for Mesh2DRenderPipeline {
         let shader = gpu_device.device().create_shader_module(
             wgpu::ShaderModuleDescriptor {
                 label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("../shaders/2dmeshshader.wgsl").into(),
-                ),
+                source: wgpu::ShaderSource::Wgsl(shaders::MESH2D_SHADER.into()),
             },
         );
 
@@ -58,11 +56,11 @@ impl PipeLineLayout for Mesh2DRenderPipeline {
                 depth_stencil: Some(wgpu::DepthStencilState {
                     format: wgpu::TextureFormat::Depth32Float,
                     depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::LessEqual,
+                    depth_compare: gpu_device.depth_compare(),
                     stencil: wgpu::StencilState::default(),
                     bias: wgpu::DepthBiasState::default(),
                 }),
-                multisample: wgpu::MultisampleState::default(),
+                multisample: gpu_device.multisample_state(),
                 fragment: Some(wgpu::FragmentState {
                     module: &shader,
                     entry_point: "fragment",
@@ -1,10 +1,16 @@
 use crate::{
-    AsBufferPass, AscendingError, GpuBuffer, GpuRenderer, Mesh2D,
-    Mesh2DRenderPipeline, Mesh2DVertex, OrderedIndex, SetBuffers,
+    ArenaHandle, AsBufferPass, AscendingError, BufferArena, GpuBuffer,
+    GpuRenderer, Mesh2D, Mesh2DRenderPipeline, Mesh2DVertex, OrderedIndex,
+    RenderCommands, SetBuffers,
 };
+use std::ops::Range;
 
 pub struct Mesh2DRenderer {
     pub vbos: GpuBuffer<Mesh2DVertex>,
+    /// Shared buffers for meshes updated with mesh_arena_update.
+    pub arena: BufferArena<Mesh2DVertex>,
+    arena_queue: Vec<ArenaHandle>,
+    arena_ranges: Vec<Range<u32>>,
 }
 
 //TODO: Update this to take in instance buffer index too.
@@ -12,6 +18,9 @@ impl Mesh2DRenderer {
     pub fn new(renderer: &GpuRenderer) -> Result<Self, AscendingError> {
         Ok(Self {
             vbos: GpuBuffer::new(renderer.gpu_device()),
+            arena: BufferArena::new(renderer.gpu_device()),
+            arena_queue: Vec::new(),
+            arena_ranges: Vec::new(),
         })
     }
 
@@ -25,6 +34,10 @@ impl Mesh2DRenderer {
 
     pub fn finalize(&mut self, renderer: &mut GpuRenderer) {
         self.vbos.finalize(renderer);
+
+        self.arena.maintain(renderer.gpu_device());
+        self.arena_ranges = self.arena.index_ranges(&self.arena_queue);
+        self.arena_queue.clear();
     }
 
     pub fn mesh_update(
@@ -36,6 +49,23 @@ impl Mesh2DRenderer {
 
         self.add_buffer_store(renderer, index);
     }
+
+    /// Draws the mesh from the shared arena this frame. Suits many small
+    /// meshes, which then draw with a handful of calls.
+    pub fn mesh_arena_update(
+        &mut self,
+        mesh: &mut Mesh2D,
+        renderer: &mut GpuRenderer,
+    ) {
+        let handle = mesh.arena_update(renderer, &mut self.arena);
+
+        self.arena_queue.push(handle);
+    }
+
+    /// Frees a mesh drawn with mesh_arena_update that is no longer used.
+    pub fn arena_unload(&mut self, mesh: &mut Mesh2D, renderer: &GpuRenderer) {
+        mesh.arena_unload(renderer, &mut self.arena);
+    }
 }
 
 pub trait RenderMesh2D<'a, 'b>
@@ -49,9 +79,10 @@ where
     );
 }
 
-impl<'a, 'b> RenderMesh2D<'a, 'b> for wgpu::RenderPass<'a>
+impl<'a, 'b, P> RenderMesh2D<'a, 'b> for P
 where
     'b: 'a,
+    P: RenderCommands<'a>,
 {
     fn render_2dmeshs(
         &mut self,
@@ -79,5 +110,17 @@ where
                 index_pos += details.count;
             }
         }
+
+        if !buffer.arena_ranges.is_empty() {
+            self.set_buffers(buffer.arena.as_buffer_pass());
+            self.set_pipeline(
+                renderer.get_pipelines(Mesh2DRenderPipeline).unwrap(),
+            );
+
+            // Arena indices already point at their own vertices.
+            for range in &buffer.arena_ranges {
+                self.draw_indexed(range.clone(), 0, 0..1);
+            }
+        }
     }
 }
@@ -0,0 +1,287 @@
+use crate::UiSoundCue;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+
+/// Handle ids are unique across routers so a child's events never match a
+/// parent's subscription.
+static NEXT_WIDGET_ID: AtomicU32 = AtomicU32::new(0);
+
+/// A kind of widget and the events it emits.
+pub trait WidgetKind: 'static {
+    type Event: Send + Sync + 'static;
+}
+
+/// Pressed.
+pub struct ButtonWidget;
+/// The new value.
+pub struct SliderWidget;
+/// The new text.
+pub struct TextInputWidget;
+/// The new checked state.
+pub struct CheckboxWidget;
+/// The new value.
+pub struct SpinnerWidget<T>(PhantomData<fn() -> T>);
+/// The new values of every component.
+pub struct VecEditorWidget;
+/// The index of the picked item.
+pub struct RadialMenuWidget;
+/// A sent line or a clicked link.
+#[cfg(all(feature = "text", feature = "shapes"))]
+pub struct ChatBoxWidget;
+/// The world position clicked or dragged to.
+#[cfg(all(feature = "sprites", feature = "shapes"))]
+pub struct MinimapWidget;
+/// A move or quick move.
+#[cfg(all(feature = "sprites", feature = "text", feature = "shapes"))]
+pub struct ItemGridWidget;
+/// A dialogue moving on, a picked choice or its end.
+#[cfg(feature = "dialogue")]
+pub struct DialogueWidget;
+/// A used or assigned slot.
+#[cfg(all(feature = "sprites", feature = "text", feature = "shapes"))]
+pub struct ActionBarWidget;
+
+impl WidgetKind for ButtonWidget {
+    type Event = ();
+}
+
+impl WidgetKind for SliderWidget {
+    type Event = f32;
+}
+
+impl WidgetKind for TextInputWidget {
+    type Event = String;
+}
+
+impl WidgetKind for CheckboxWidget {
+    type Event = bool;
+}
+
+impl<T: Send + Sync + 'static> WidgetKind for SpinnerWidget<T> {
+    type Event = T;
+}
+
+impl WidgetKind for VecEditorWidget {
+    type Event = Vec<f32>;
+}
+
+impl WidgetKind for RadialMenuWidget {
+    type Event = usize;
+}
+
+#[cfg(all(feature = "text", feature = "shapes"))]
+impl WidgetKind for ChatBoxWidget {
+    type Event = crate::ChatEvent;
+}
+
+#[cfg(all(feature = "sprites", feature = "shapes"))]
+impl WidgetKind for MinimapWidget {
+    type Event = crate::Vec2;
+}
+
+#[cfg(all(feature = "sprites", feature = "text", feature = "shapes"))]
+impl WidgetKind for ItemGridWidget {
+    type Event = crate::ItemGridEvent;
+}
+
+#[cfg(feature = "dialogue")]
+impl WidgetKind for DialogueWidget {
+    type Event = crate::DialogueEvent;
+}
+
+#[cfg(all(feature = "sprites", feature = "text", feature = "shapes"))]
+impl WidgetKind for ActionBarWidget {
+    type Event = crate::ActionBarEvent;
+}
+
+/// Typed reference to a widget registered with a [`MessageRouter`].
+pub struct WidgetHandle<W: WidgetKind> {
+    id: u32,
+    kind: PhantomData<fn() -> W>,
+}
+
+impl<W: WidgetKind> Clone for WidgetHandle<W> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<W: WidgetKind> Copy for WidgetHandle<W> {}
+
+impl<W: WidgetKind> PartialEq for WidgetHandle<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<W: WidgetKind> Eq for WidgetHandle<W> {}
+
+impl<W: WidgetKind> fmt::Debug for WidgetHandle<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WidgetHandle").field(&self.id).finish()
+    }
+}
+
+impl<W: WidgetKind> WidgetHandle<W> {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Wraps an event of this widget so it can be sent as the GUI's one
+    /// message type, like from an iced `on_press`, and routed back.
+    pub fn event(&self, event: W::Event) -> WidgetEvent {
+        WidgetEvent {
+            id: self.id,
+            kind: TypeId::of::<W>(),
+            event: Arc::new(event),
+        }
+    }
+}
+
+/// An event from some widget. The single message type the GUI sees, which
+/// [`MessageRouter::dispatch`] turns into the app's typed messages.
+#[derive(Clone)]
+pub struct WidgetEvent {
+    id: u32,
+    kind: TypeId,
+    event: Arc<dyn Any + Send + Sync>,
+}
+
+impl fmt::Debug for WidgetEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WidgetEvent").field("id", &self.id).finish()
+    }
+}
+
+type Mapper<M> = Box<dyn Fn(&(dyn Any + Send + Sync)) -> Option<M>>;
+
+/// Maps widget events into messages of type M. Nested components get their
+/// own router of their own message type and are forwarded into their
+/// parent's, Elm style.
+pub struct MessageRouter<M> {
+    subscriptions: HashMap<(TypeId, u32), Mapper<M>>,
+    queue: Vec<M>,
+    sounds: Vec<UiSoundCue>,
+}
+
+impl<M> Default for MessageRouter<M> {
+    fn default() -> Self {
+        Self {
+            subscriptions: HashMap::new(),
+            queue: Vec::new(),
+            sounds: Vec::new(),
+        }
+    }
+}
+
+impl<M: 'static> MessageRouter<M> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A new handle with no subscription yet.
+    pub fn handle<W: WidgetKind>(&mut self) -> WidgetHandle<W> {
+        WidgetHandle {
+            id: NEXT_WIDGET_ID.fetch_add(1, Ordering::Relaxed),
+            kind: PhantomData,
+        }
+    }
+
+    /// A new handle whose events are mapped into messages by map.
+    pub fn widget<W, F>(&mut self, map: F) -> WidgetHandle<W>
+    where
+        W: WidgetKind,
+        F: Fn(&W::Event) -> M + 'static,
+    {
+        let handle = self.handle();
+
+        self.subscribe(handle, map);
+        handle
+    }
+
+    /// Replaces the handle's mapping.
+    pub fn subscribe<W, F>(&mut self, handle: WidgetHandle<W>, map: F)
+    where
+        W: WidgetKind,
+        F: Fn(&W::Event) -> M + 'static,
+    {
+        self.subscriptions.insert(
+            (TypeId::of::<W>(), handle.id),
+            Box::new(move |event| event.downcast_ref::<W::Event>().map(&map)),
+        );
+    }
+
+    pub fn unsubscribe<W: WidgetKind>(&mut self, handle: WidgetHandle<W>) {
+        self.subscriptions.remove(&(TypeId::of::<W>(), handle.id));
+    }
+
+    /// The message for an event, if its widget is subscribed here.
+    pub fn dispatch(&self, event: &WidgetEvent) -> Option<M> {
+        self.subscriptions
+            .get(&(event.kind, event.id))
+            .and_then(|map| map(event.event.as_ref()))
+    }
+
+    /// Sends an event directly, queueing its message.
+    pub fn emit<W: WidgetKind>(
+        &mut self,
+        handle: WidgetHandle<W>,
+        event: W::Event,
+    ) {
+        self.route(&handle.event(event));
+    }
+
+    /// Queues the message for an event. Returns false if nothing here is
+    /// subscribed to it, so it can be tried on a child router.
+    pub fn route(&mut self, event: &WidgetEvent) -> bool {
+        match self.dispatch(event) {
+            Some(message) => {
+                self.queue.push(message);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn push(&mut self, message: M) {
+        self.queue.push(message);
+    }
+
+    /// Moves a child component's queued messages into this router as
+    /// parent messages.
+    pub fn forward<C, F>(&mut self, child: &mut MessageRouter<C>, map: F)
+    where
+        C: 'static,
+        F: Fn(C) -> M,
+    {
+        self.queue.extend(child.drain().map(map));
+        self.sounds.append(&mut child.sounds);
+    }
+
+    /// Takes the queued messages in the order they were routed.
+    pub fn drain(&mut self) -> std::vec::Drain<'_, M> {
+        self.queue.drain(..)
+    }
+
+    /// Queues a widget's sound, see [`crate::UiSounds`].
+    pub fn play_sound(&mut self, cue: Option<UiSoundCue>) {
+        self.sounds.extend(cue);
+    }
+
+    /// Takes the queued sounds in the order they were played.
+    pub fn drain_sounds(&mut self) -> std::vec::Drain<'_, UiSoundCue> {
+        self.sounds.drain(..)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
@@ -0,0 +1,414 @@
+use crate::{
+    Allocation, Bounds, Color, Easing, GpuRenderer, Image, ImageRenderer,
+    MessageRouter, MinimapWidget, Rect, RectRenderer, System, UiSound, Vec2,
+    Vec3, Vec4, WidgetHandle, WidgetInput, WidgetSounds,
+};
+use camera::controls::CameraFocus;
+
+/// An icon on a [`Minimap`] at a world position, like a player or quest
+/// giver.
+pub struct MinimapMarker {
+    /// World position the icon is centered on.
+    pub pos: Vec2,
+    /// Pixels on the minimap, not scaled by its zoom.
+    pub size: Vec2,
+    pub image: Image,
+}
+
+/// A small overview of the world: a prerendered texture of it, the
+/// camera's view as a rectangle and marker icons. Clicking or dragging on
+/// it gives the world position under the cursor, see
+/// [`Minimap::center_camera`]. Zoomed in it follows the camera.
+///
+/// Placed in render space like the [`crate::Spinner`]. World positions are
+/// emitted through the router when a handle is set.
+pub struct Minimap {
+    pub handle: Option<WidgetHandle<MinimapWidget>>,
+    pub sounds: WidgetSounds,
+    pub map: Image,
+    pub frame: Rect,
+    pub viewport: Rect,
+    pub markers: Vec<MinimapMarker>,
+    /// How far each zoom level magnifies, 1.0 shows the whole world.
+    pub zoom_levels: Vec<f32>,
+    /// Keeps the camera's view centered when zoomed in.
+    pub follow_camera: bool,
+    render_layer: u32,
+    /// World area the texture covers.
+    world: Bounds,
+    /// Size of the texture in pixels.
+    texture_size: Vec2,
+    zoom_index: usize,
+    /// World position in the middle of the minimap.
+    center: Vec2,
+    /// Camera view in world space, from the last sync.
+    view: Bounds,
+    position: Vec3,
+    size: Vec2,
+    dragging: bool,
+    was_down: bool,
+    last_emitted: Option<Vec2>,
+    changed: bool,
+}
+
+impl Minimap {
+    /// texture shows world, as rendered ahead of time by the app.
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        texture: Allocation,
+        world: Bounds,
+        render_layer: u32,
+    ) -> Self {
+        let (width, height) = texture.size();
+        let mut map = Image::new(Some(texture), renderer, render_layer);
+        let mut frame = Rect::new(renderer, render_layer);
+        let mut viewport = Rect::new(renderer, render_layer);
+
+        map.use_camera = false;
+        frame
+            .set_color(Color::rgba(15, 15, 15, 255))
+            .set_border_width(2.0)
+            .set_border_color(Color::rgba(90, 90, 90, 255));
+        viewport
+            .set_color(Color::rgba(0, 0, 0, 0))
+            .set_border_width(1.0)
+            .set_border_color(Color::rgba(255, 255, 255, 220));
+
+        Self {
+            handle: None,
+            sounds: WidgetSounds::default(),
+            map,
+            frame,
+            viewport,
+            markers: Vec::new(),
+            zoom_levels: vec![1.0, 2.0, 4.0],
+            follow_camera: true,
+            render_layer,
+            world,
+            texture_size: Vec2::new(width as f32, height as f32),
+            zoom_index: 0,
+            center: Vec2::new(
+                (world.left + world.right) / 2.0,
+                (world.bottom + world.top) / 2.0,
+            ),
+            view: world,
+            position: Vec3::ZERO,
+            size: Vec2::splat(160.0),
+            dragging: false,
+            was_down: false,
+            last_emitted: None,
+            changed: true,
+        }
+    }
+
+    /// Bottom left corner and z.
+    pub fn set_position(&mut self, position: Vec3) -> &mut Self {
+        self.position = position;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_size(&mut self, size: Vec2) -> &mut Self {
+        self.size = size;
+        self.changed = true;
+        self
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(
+            self.position.x,
+            self.position.y,
+            self.position.x + self.size.x,
+            self.position.y + self.size.y,
+        )
+    }
+
+    /// Swaps the texture, like after the world was rerendered.
+    pub fn set_texture(&mut self, texture: Allocation, world: Bounds) {
+        let (width, height) = texture.size();
+
+        self.map.texture = Some(texture);
+        self.texture_size = Vec2::new(width as f32, height as f32);
+        self.world = world;
+        self.changed = true;
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom_levels
+            .get(self.zoom_index)
+            .copied()
+            .unwrap_or(1.0)
+            .max(1.0)
+    }
+
+    pub fn zoom_index(&self) -> usize {
+        self.zoom_index
+    }
+
+    pub fn set_zoom_index(&mut self, index: usize) -> &mut Self {
+        self.zoom_index = index.min(self.zoom_levels.len().saturating_sub(1));
+        self.changed = true;
+        self
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.set_zoom_index(self.zoom_index + 1);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.set_zoom_index(self.zoom_index.saturating_sub(1));
+    }
+
+    /// Returns the marker's index.
+    pub fn add_marker(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        icon: Allocation,
+        pos: Vec2,
+        size: Vec2,
+    ) -> usize {
+        let (width, height) = icon.size();
+        let mut image = Image::new(Some(icon), renderer, self.render_layer);
+
+        image.use_camera = false;
+        image.uv = Vec4::new(0.0, 0.0, width as f32, height as f32);
+        self.markers.push(MinimapMarker { pos, size, image });
+        self.changed = true;
+        self.markers.len() - 1
+    }
+
+    pub fn set_marker_pos(&mut self, index: usize, pos: Vec2) {
+        if let Some(marker) = self.markers.get_mut(index) {
+            marker.pos = pos;
+            self.changed = true;
+        }
+    }
+
+    /// Takes the marker out. Later markers move down an index.
+    pub fn remove_marker(&mut self, renderer: &mut GpuRenderer, index: usize) {
+        if index < self.markers.len() {
+            self.markers.remove(index).image.unload(renderer);
+            self.changed = true;
+        }
+    }
+
+    /// The world area shown, clamped inside the texture's.
+    pub fn shown_world(&self) -> Bounds {
+        let world_size = Vec2::new(
+            self.world.right - self.world.left,
+            self.world.top - self.world.bottom,
+        );
+        let half = world_size / self.zoom() / 2.0;
+        let min = Vec2::new(self.world.left, self.world.bottom) + half;
+        let max = Vec2::new(self.world.right, self.world.top) - half;
+        let center = self.center.clamp(min, max.max(min));
+
+        Bounds::new(
+            center.x - half.x,
+            center.y - half.y,
+            center.x + half.x,
+            center.y + half.y,
+        )
+    }
+
+    pub fn world_to_minimap(&self, pos: Vec2) -> Vec2 {
+        let shown = self.shown_world();
+        let t = (pos - Vec2::new(shown.left, shown.bottom))
+            / Vec2::new(shown.right - shown.left, shown.top - shown.bottom);
+
+        self.position.truncate() + t * self.size
+    }
+
+    pub fn minimap_to_world(&self, pos: Vec2) -> Vec2 {
+        let shown = self.shown_world();
+        let t = (pos - self.position.truncate()) / self.size;
+
+        Vec2::new(shown.left, shown.bottom)
+            + t * Vec2::new(shown.right - shown.left, shown.top - shown.bottom)
+    }
+
+    /// Takes the camera's view for the viewport rectangle. Call after the
+    /// system's update each frame.
+    pub fn sync<Controls>(&mut self, system: &System<Controls>)
+    where
+        Controls: camera::controls::Controls,
+    {
+        let view = system.visible_world_bounds();
+
+        if view != self.view {
+            self.view = view;
+
+            if self.follow_camera {
+                self.center = Vec2::new(
+                    (view.left + view.right) / 2.0,
+                    (view.bottom + view.top) / 2.0,
+                );
+            }
+
+            self.changed = true;
+        }
+    }
+
+    /// Moves the camera over a world position, keeping its zoom.
+    pub fn center_camera<Controls>(
+        system: &mut System<Controls>,
+        pos: Vec2,
+        duration: f32,
+    ) where
+        Controls: camera::controls::Controls,
+    {
+        if let Some(focus) = system.camera_focus() {
+            let center = CameraFocus::new([pos.x, pos.y, 0.0], focus.zoom);
+
+            system.transition_to(center, duration, Easing::EaseOut);
+        }
+    }
+
+    /// Applies a frame of input. Returns the world position clicked or
+    /// dragged to, which is also emitted if a handle is set.
+    pub fn handle_input<M: 'static>(
+        &mut self,
+        input: &WidgetInput,
+        router: &mut MessageRouter<M>,
+    ) -> Option<Vec2> {
+        let pressed = input.mouse_down && !self.was_down;
+
+        self.was_down = input.mouse_down;
+
+        let pos = input.mouse_pos?;
+
+        if pressed && self.bounds().contains(pos) {
+            self.dragging = true;
+            self.last_emitted = None;
+            router.play_sound(self.sounds.cue(UiSound::Click));
+        } else if !input.mouse_down {
+            self.dragging = false;
+        }
+
+        if !self.dragging {
+            return None;
+        }
+
+        let bounds = self.bounds();
+        let pos = pos.clamp(
+            Vec2::new(bounds.left, bounds.bottom),
+            Vec2::new(bounds.right, bounds.top),
+        );
+        let world = self.minimap_to_world(pos);
+
+        if self.last_emitted == Some(world) {
+            return None;
+        }
+
+        self.last_emitted = Some(world);
+
+        if let Some(handle) = self.handle {
+            router.emit(handle, world);
+        }
+
+        Some(world)
+    }
+
+    /// Lays out the map, viewport and markers if anything changed.
+    fn refresh(&mut self) {
+        if !self.changed {
+            return;
+        }
+
+        let (pos, size) = (self.position, self.size);
+        let shown = self.shown_world();
+        let world_size = Vec2::new(
+            self.world.right - self.world.left,
+            self.world.top - self.world.bottom,
+        );
+        // Texture rows go down from the top of the world.
+        let uv_min = Vec2::new(
+            (shown.left - self.world.left) / world_size.x,
+            (self.world.top - shown.top) / world_size.y,
+        ) * self.texture_size;
+        let uv_size = Vec2::new(
+            (shown.right - shown.left) / world_size.x,
+            (shown.top - shown.bottom) / world_size.y,
+        ) * self.texture_size;
+
+        self.frame
+            .set_position(pos - Vec3::new(2.0, 2.0, 0.0))
+            .set_size(size + 4.0);
+
+        // lower Z renders over the frame.
+        self.map.pos = pos.truncate().extend(pos.z - 0.01);
+        self.map.hw = size;
+        self.map.uv = Vec4::new(uv_min.x, uv_min.y, uv_size.x, uv_size.y);
+        self.map.changed = true;
+
+        let bounds = self.bounds();
+        let min = self
+            .world_to_minimap(Vec2::new(self.view.left, self.view.bottom))
+            .max(Vec2::new(bounds.left, bounds.bottom));
+        let max = self
+            .world_to_minimap(Vec2::new(self.view.right, self.view.top))
+            .min(Vec2::new(bounds.right, bounds.top));
+
+        self.viewport
+            .set_position(min.extend(pos.z - 0.03))
+            .set_size((max - min).max(Vec2::ZERO));
+
+        for index in 0..self.markers.len() {
+            let center = self.world_to_minimap(self.markers[index].pos);
+            let marker = &mut self.markers[index];
+
+            marker.image.pos =
+                (center - marker.size / 2.0).extend(pos.z - 0.02);
+            marker.image.hw = marker.size;
+            marker.image.changed = true;
+        }
+
+        self.changed = false;
+    }
+
+    /// Frees the minimap's images and rects.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        self.map.unload(renderer);
+        self.frame.unload(renderer);
+        self.viewport.unload(renderer);
+
+        for marker in self.markers {
+            marker.image.unload(renderer);
+        }
+    }
+}
+
+impl ImageRenderer {
+    /// Markers outside the shown area are left out.
+    pub fn minimap_update(
+        &mut self,
+        minimap: &mut Minimap,
+        renderer: &mut GpuRenderer,
+    ) {
+        minimap.refresh();
+        self.image_update(&mut minimap.map, renderer);
+
+        let shown = minimap.shown_world();
+
+        for marker in &mut minimap.markers {
+            if shown.contains(marker.pos) {
+                self.image_update(&mut marker.image, renderer);
+            }
+        }
+    }
+}
+
+impl RectRenderer {
+    pub fn minimap_update(
+        &mut self,
+        minimap: &mut Minimap,
+        renderer: &mut GpuRenderer,
+    ) {
+        minimap.refresh();
+        self.rect_update(&mut minimap.frame, renderer);
+
+        if minimap.viewport.size.x > 0.0 && minimap.viewport.size.y > 0.0 {
+            self.rect_update(&mut minimap.viewport, renderer);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{
+    AscendingError, AtlasGroup, GpuRenderer, Image, RenderTarget, System, Vec2,
+    Vec3, Vec4,
+};
+use camera::{
+    controls::{FlatControls, FlatSettings},
+    Projection,
+};
+
+/// Caches a complex panel that rarely changes, like a character sheet or
+/// an options window, as a texture. Its widgets are drawn once into an
+/// offscreen target and then shown as a single image until the panel is
+/// marked dirty or resized, trading the texture's memory for the draws.
+///
+/// The image lives in the atlas given to [`PanelCache::update`], which
+/// must share the surface format like for [`RenderTarget::snapshot_into`].
+pub struct PanelCache {
+    /// The cached panel, drawn with the image renderer of the atlas.
+    pub image: Image,
+    /// The panel's background where no widget draws.
+    pub clear: wgpu::Color,
+    key: String,
+    system: System<FlatControls>,
+    target: RenderTarget,
+    size: Vec2,
+    dirty: bool,
+}
+
+impl PanelCache {
+    /// key names the panel's allocation in the atlas and must not be used
+    /// by anything else in it.
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        key: impl Into<String>,
+        position: Vec3,
+        size: Vec2,
+        render_layer: u32,
+    ) -> Self {
+        let mut image = Image::new(None, renderer, render_layer);
+
+        image.pos = position;
+        image.use_camera = false;
+
+        Self {
+            image,
+            clear: wgpu::Color::TRANSPARENT,
+            key: key.into(),
+            system: Self::create_system(renderer, size),
+            target: RenderTarget::new(renderer, size.x as u32, size.y as u32),
+            size,
+            dirty: true,
+        }
+    }
+
+    fn create_system(
+        renderer: &mut GpuRenderer,
+        size: Vec2,
+    ) -> System<FlatControls> {
+        System::new(
+            renderer,
+            Projection::orthographic_layers(0.0, size.x, 0.0, size.y, 100),
+            FlatControls::new(FlatSettings::default()),
+            [size.x, size.y],
+        )
+    }
+
+    /// Render space of the panel, from its bottom left, to draw its
+    /// widgets with.
+    pub fn system(&self) -> &System<FlatControls> {
+        &self.system
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Moves the image. The panel is not drawn again.
+    pub fn set_position(&mut self, position: Vec3) -> &mut Self {
+        self.image.pos = position;
+        self.image.changed = true;
+        self
+    }
+
+    /// Remakes the target at size and marks it dirty.
+    pub fn set_size(&mut self, renderer: &mut GpuRenderer, size: Vec2) {
+        if self.size == size {
+            return;
+        }
+
+        self.size = size;
+        self.system = Self::create_system(renderer, size);
+        self.target = RenderTarget::new(renderer, size.x as u32, size.y as u32);
+        self.dirty = true;
+    }
+
+    /// Draws the panel again on the next update, after any of its widgets
+    /// changed.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Bytes the target and the allocation take, at 4 per pixel and
+    /// counting the multisampled and depth textures.
+    pub fn memory_bytes(&self, renderer: &GpuRenderer) -> u64 {
+        let (width, height) = self.target.size();
+        let pixels = width as u64 * height as u64 * 4;
+        let samples = renderer.sample_count() as u64;
+        let msaa = if samples > 1 { samples } else { 0 };
+
+        // Target, its msaa texture, depth and the atlas copy.
+        pixels * (2 + msaa + samples)
+    }
+
+    /// Draws the panel into the target if it is dirty and copies it into
+    /// the atlas for the image. redraw gets the panel's system and target
+    /// and must update, finalize and render the panel's own renderers into
+    /// a pass of the target, which is submitted after. Returns if it was
+    /// drawn.
+    pub fn update<F>(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        atlas: &mut AtlasGroup,
+        redraw: F,
+    ) -> Result<bool, AscendingError>
+    where
+        F: FnOnce(
+            &mut GpuRenderer,
+            &System<FlatControls>,
+            &RenderTarget,
+            &mut wgpu::CommandEncoder,
+            wgpu::Color,
+        ) -> Result<(), AscendingError>,
+    {
+        if !self.dirty {
+            // The atlas may have dropped it since.
+            match atlas.atlas.get(&self.key) {
+                Some(allocation) => {
+                    self.image.texture = Some(allocation);
+                    return Ok(false);
+                }
+                None => self.dirty = true,
+            }
+        }
+
+        let mut encoder = renderer.device().create_command_encoder(
+            &wgpu::CommandEncoderDescriptor {
+                label: Some("panel cache encoder"),
+            },
+        );
+
+        redraw(
+            renderer,
+            &self.system,
+            &self.target,
+            &mut encoder,
+            self.clear,
+        )?;
+        renderer.queue().submit(std::iter::once(encoder.finish()));
+
+        // A new size needs a new allocation.
+        if let Some(old) = atlas.atlas.get(&self.key) {
+            if old.size() != self.target.size() {
+                atlas.atlas.remove(&self.key);
+            }
+        }
+
+        let allocation =
+            self.target
+                .snapshot_into(renderer, atlas, self.key.clone(), 0)?;
+        let (width, height) = self.target.size();
+
+        self.image.texture = Some(allocation);
+        self.image.hw = self.size;
+        self.image.uv = Vec4::new(0.0, 0.0, width as f32, height as f32);
+        self.image.changed = true;
+        self.dirty = false;
+        Ok(true)
+    }
+
+    /// Frees the image and the panel's allocation.
+    pub fn unload(self, renderer: &mut GpuRenderer, atlas: &mut AtlasGroup) {
+        atlas.atlas.remove(&self.key);
+        self.image.unload(renderer);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{
+    read_back, read_back_with, AscendingError, GpuRenderer,
+    ImagePickRenderPipeline, TextureRegion, Vec2,
+};
+use std::future::Future;
+
+/// Format of the id target.
+pub const PICK_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+
+/// An object id render target for pixel exact picking in dense scenes.
+/// Images with a pick id write it wherever they are visible, so reading
+/// the id under the cursor back finds the front most one.
+///
+/// Render into it in its own pass each frame picking is needed, setting
+/// the system bind group and quad buffers as for the main pass and then
+/// calling [`crate::RenderImage::render_image_picks`]. Only images write
+/// ids, so what other renderers draw over them doesn't hide them.
+pub struct PickBuffer {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    depth_view: wgpu::TextureView,
+    depth_clear: wgpu::LoadOp<f32>,
+}
+
+impl PickBuffer {
+    /// Sized to the renderer. Creates the pick pipelines the first time.
+    pub fn new(renderer: &mut GpuRenderer) -> Self {
+        if renderer.get_pipelines(ImagePickRenderPipeline).is_none() {
+            renderer.create_pipeline(ImagePickRenderPipeline);
+        }
+
+        let (texture, view, depth_view) = create_targets(renderer);
+
+        Self {
+            texture,
+            view,
+            depth_view,
+            depth_clear: renderer.gpu_device().depth_clear(),
+        }
+    }
+
+    /// Call after the window resizes. Ids drawn before are lost.
+    pub fn resize(&mut self, renderer: &GpuRenderer) {
+        let size = renderer.size();
+
+        if self.texture.width() != size.width as u32
+            || self.texture.height() != size.height as u32
+        {
+            (self.texture, self.view, self.depth_view) =
+                create_targets(renderer);
+        }
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// Starts a pass that clears the ids to 0, which picks nothing.
+    pub fn begin_pass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+    ) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pick pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(
+                wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: self.depth_clear,
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                },
+            ),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+
+    /// The id under a screen position, in render space from the bottom
+    /// left. None where no pickable image was drawn. Submit the pick pass
+    /// first.
+    pub fn pick_gpu<'a>(
+        &self,
+        renderer: &'a GpuRenderer,
+        screen_pos: Vec2,
+    ) -> impl Future<Output = Result<Option<u32>, AscendingError>> + 'a {
+        let bytes = self
+            .region(screen_pos)
+            .map(|region| read_back(renderer, &self.texture, region));
+
+        async move {
+            match bytes {
+                Some(bytes) => Ok(to_id(&bytes.await?)),
+                None => Ok(None),
+            }
+        }
+    }
+
+    /// Like pick_gpu but calls callback once the id is read instead of
+    /// waiting. See [`read_back_with`] for when it runs.
+    pub fn pick_gpu_with<F>(
+        &self,
+        renderer: &GpuRenderer,
+        screen_pos: Vec2,
+        callback: F,
+    ) -> Result<(), AscendingError>
+    where
+        F: FnOnce(Result<Option<u32>, AscendingError>) + Send + 'static,
+    {
+        match self.region(screen_pos) {
+            Some(region) => {
+                read_back_with(renderer, &self.texture, region, move |bytes| {
+                    callback(bytes.map(|bytes| to_id(&bytes)));
+                })
+            }
+            None => {
+                callback(Ok(None));
+                Ok(())
+            }
+        }
+    }
+
+    /// The texel under a position, flipped as textures start at the top.
+    fn region(&self, screen_pos: Vec2) -> Option<TextureRegion> {
+        let (width, height) = (self.texture.width(), self.texture.height());
+
+        if screen_pos.x < 0.0
+            || screen_pos.y < 0.0
+            || screen_pos.x >= width as f32
+            || screen_pos.y >= height as f32
+        {
+            return None;
+        }
+
+        let y = height - 1 - screen_pos.y as u32;
+
+        Some(TextureRegion::new(screen_pos.x as u32, y, 1, 1))
+    }
+}
+
+fn to_id(bytes: &[u8]) -> Option<u32> {
+    let id = u32::from_ne_bytes(bytes.get(..4)?.try_into().ok()?);
+
+    (id != 0).then_some(id)
+}
+
+fn create_targets(
+    renderer: &GpuRenderer,
+) -> (wgpu::Texture, wgpu::TextureView, wgpu::TextureView) {
+    let size = wgpu::Extent3d {
+        width: (renderer.size().width as u32).max(1),
+        height: (renderer.size().height as u32).max(1),
+        depth_or_array_layers: 1,
+    };
+    let texture = renderer.device().create_texture(&wgpu::TextureDescriptor {
+        label: Some("pick texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: PICK_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let depth = renderer.device().create_texture(&wgpu::TextureDescriptor {
+        label: Some("pick depth texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Depth32Float,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+
+    (texture, view, depth_view)
+}
@@ -0,0 +1,196 @@
+#[cfg(feature = "sprites")]
+use crate::Image;
+#[cfg(all(feature = "text", feature = "shapes"))]
+use crate::WorldUi;
+#[cfg(feature = "maps")]
+use crate::{Map, MapLayers};
+use crate::{System, Vec2};
+
+/// What was under the cursor. Ids are the ones given to the [`Picker`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PickTarget {
+    /// An element of a [`WorldUi`] group.
+    Widget {
+        id: usize,
+        element: usize,
+    },
+    Sprite {
+        id: usize,
+    },
+    Tile {
+        id: usize,
+        x: u32,
+        y: u32,
+        layer: u32,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PickResult {
+    pub target: PickTarget,
+    /// Z the target renders at. Lower is in front.
+    pub z: f32,
+    /// Cursor position relative to the target's bottom left corner.
+    pub local: Vec2,
+}
+
+/// Collects the objects to test for one [`Picker::pick`] call. Objects are
+/// borrowed so build it when needed rather than keeping it around.
+#[derive(Default)]
+pub struct Picker<'a> {
+    #[cfg(all(feature = "text", feature = "shapes"))]
+    widgets: Vec<(usize, &'a WorldUi)>,
+    #[cfg(feature = "sprites")]
+    sprites: Vec<(usize, &'a Image)>,
+    #[cfg(feature = "maps")]
+    maps: Vec<(usize, &'a Map)>,
+}
+
+impl<'a> Picker<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(all(feature = "text", feature = "shapes"))]
+    pub fn add_widgets(
+        &mut self,
+        id: usize,
+        widgets: &'a WorldUi,
+    ) -> &mut Self {
+        self.widgets.push((id, widgets));
+        self
+    }
+
+    #[cfg(feature = "sprites")]
+    pub fn add_sprite(&mut self, id: usize, sprite: &'a Image) -> &mut Self {
+        self.sprites.push((id, sprite));
+        self
+    }
+
+    #[cfg(feature = "maps")]
+    pub fn add_map(&mut self, id: usize, map: &'a Map) -> &mut Self {
+        self.maps.push((id, map));
+        self
+    }
+
+    /// Everything under screen_pos sorted front to back. screen_pos and
+    /// the results' local positions use the system's coordinate system.
+    /// Sprites with an alpha mask ignore their transparent pixels.
+    pub fn pick<Controls>(
+        &self,
+        system: &System<Controls>,
+        screen_pos: Vec2,
+    ) -> Vec<PickResult>
+    where
+        Controls: camera::controls::Controls,
+    {
+        let mut results = Vec::new();
+        let coords = system.coordinate_system();
+        // Widgets and sprites without the camera are placed in render space.
+        #[cfg(any(
+            feature = "sprites",
+            all(feature = "text", feature = "shapes")
+        ))]
+        let render_pos = system.to_render_pos(screen_pos);
+
+        #[cfg(all(feature = "text", feature = "shapes"))]
+        for (id, widgets) in &self.widgets {
+            if !widgets.is_on_screen() {
+                continue;
+            }
+
+            for (element, widget) in widgets.elements.iter().enumerate() {
+                let rect = &widget.rect;
+                let local = render_pos - rect.position.truncate();
+
+                if contains(local, rect.size) {
+                    results.push(PickResult {
+                        target: PickTarget::Widget { id: *id, element },
+                        z: rect.position.z,
+                        local: coords.from_render_local(local, rect.size),
+                    });
+                }
+            }
+        }
+
+        #[cfg(feature = "sprites")]
+        let world_pos = system.screen_to_world_pos(screen_pos);
+
+        #[cfg(feature = "sprites")]
+        for (id, sprite) in &self.sprites {
+            let pos = if sprite.use_camera {
+                world_pos
+            } else {
+                render_pos
+            };
+
+            if sprite.hit_test(pos) {
+                results.push(PickResult {
+                    target: PickTarget::Sprite { id: *id },
+                    z: sprite.pos.z,
+                    local: coords.from_render_local(
+                        pos - sprite.pos.truncate(),
+                        sprite.hw,
+                    ),
+                });
+            }
+        }
+
+        #[cfg(feature = "maps")]
+        for (id, map) in &self.maps {
+            if !map.can_render {
+                continue;
+            }
+
+            let tilesize = map.tilesize as f32;
+
+            for layer in 0..MapLayers::Count as u32 {
+                if map.filled_tiles[layer as usize] == 0 {
+                    continue;
+                }
+
+                let pos = system.screen_to_layer_pos(
+                    screen_pos,
+                    map.parallax[layer as usize],
+                );
+                let offset = pos - map.pos;
+
+                if !contains(offset, Vec2::splat(tilesize * 32.0)) {
+                    continue;
+                }
+
+                let (x, y) = (
+                    (offset.x / tilesize) as u32,
+                    (offset.y / tilesize) as u32,
+                );
+                let tile = map.get_tile((x, y, layer));
+
+                if tile.texture_id == 0 && tile.color.a() == 0 {
+                    continue;
+                }
+
+                results.push(PickResult {
+                    target: PickTarget::Tile {
+                        id: *id,
+                        x,
+                        y,
+                        layer,
+                    },
+                    z: MapLayers::indexed_layerz(layer),
+                    local: coords.from_render_local(
+                        offset - Vec2::new(x as f32, y as f32) * tilesize,
+                        Vec2::splat(tilesize),
+                    ),
+                });
+            }
+        }
+
+        results.sort_by(|a, b| a.z.total_cmp(&b.z));
+        results
+    }
+}
+
+#[cfg(any(feature = "maps", all(feature = "text", feature = "shapes")))]
+fn contains(local: Vec2, size: Vec2) -> bool {
+    local.x >= 0.0 && local.y >= 0.0 && local.x < size.x && local.y < size.y
+}
@@ -0,0 +1,185 @@
+use crate::{
+    AppContext, AscendingError, GpuRenderer, OtherError, PipeLineLayout,
+    WindowActivity,
+};
+#[cfg(all(feature = "text", feature = "shapes"))]
+use crate::{Vec3, WorldUi};
+use std::{any::Any, collections::HashMap, hash::Hash, path::Path};
+
+/// An optional engine feature like particles or audio added to a
+/// [`crate::Runner`]. Every hook has a default so plugins only implement
+/// what they need. Hooks run in the order plugins were added.
+pub trait Plugin<ActionId, AxisId>: 'static
+where
+    ActionId: Clone + Eq + Hash + Send + Sync,
+    AxisId: Clone + Eq + Hash + Send + Sync,
+{
+    fn name(&self) -> &'static str;
+
+    /// Called once when added. Register pipelines, loaders and widgets
+    /// here.
+    fn setup(&mut self, _setup: &mut PluginSetup) {}
+
+    /// Called every frame before the app's update.
+    fn pre_update(
+        &mut self,
+        _ctx: &mut AppContext<ActionId, AxisId>,
+        _delta: f32,
+    ) {
+    }
+
+    /// Called every frame after the app's update.
+    fn post_update(
+        &mut self,
+        _ctx: &mut AppContext<ActionId, AxisId>,
+        _delta: f32,
+    ) {
+    }
+
+    /// Called when the window gains or loses focus or gets covered. Audio
+    /// plugins check [`crate::BackgroundState::is_audio_muted`] here.
+    fn window_activity(
+        &mut self,
+        _ctx: &mut AppContext<ActionId, AxisId>,
+        _activity: WindowActivity,
+    ) {
+    }
+
+    /// Called after all updates to build and upload this frame's buffers.
+    fn extract(&mut self, _renderer: &mut GpuRenderer) {}
+
+    /// Records the plugin's passes after the app drew.
+    fn render(
+        &mut self,
+        _renderer: &GpuRenderer,
+        _encoder: &mut wgpu::CommandEncoder,
+    ) {
+    }
+}
+
+/// Loads files of some extensions into any type. Registered by plugins and
+/// used through [`PluginRegistry::load_asset`].
+pub trait AssetLoader: 'static {
+    /// Extensions without the dot, lowercase.
+    fn extensions(&self) -> &[&'static str];
+
+    fn load(
+        &self,
+        renderer: &mut GpuRenderer,
+        path: &Path,
+    ) -> Result<Box<dyn Any>, AscendingError>;
+}
+
+/// Creates a [`WorldUi`] group at an anchor.
+#[cfg(all(feature = "text", feature = "shapes"))]
+pub type WidgetFactory = Box<dyn Fn(&mut GpuRenderer, Vec3) -> WorldUi>;
+
+/// Loaders and widgets registered by plugins.
+#[derive(Default)]
+pub struct PluginRegistry {
+    loaders: Vec<Box<dyn AssetLoader>>,
+    /// Extension to index into loaders.
+    extensions: HashMap<&'static str, usize>,
+    #[cfg(all(feature = "text", feature = "shapes"))]
+    widgets: HashMap<&'static str, WidgetFactory>,
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A later loader replaces an earlier one for the same extension.
+    pub fn register_loader<L: AssetLoader>(&mut self, loader: L) {
+        let index = self.loaders.len();
+
+        for extension in loader.extensions() {
+            self.extensions.insert(extension, index);
+        }
+
+        self.loaders.push(Box::new(loader));
+    }
+
+    #[cfg(all(feature = "text", feature = "shapes"))]
+    pub fn register_widget<F>(&mut self, name: &'static str, factory: F)
+    where
+        F: Fn(&mut GpuRenderer, Vec3) -> WorldUi + 'static,
+    {
+        self.widgets.insert(name, Box::new(factory));
+    }
+
+    pub fn has_loader(&self, extension: &str) -> bool {
+        self.extensions.contains_key(extension)
+    }
+
+    /// Loads the file with the loader registered for its extension.
+    pub fn load_asset<T: 'static>(
+        &self,
+        renderer: &mut GpuRenderer,
+        path: impl AsRef<Path>,
+    ) -> Result<T, AscendingError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .unwrap_or_default();
+
+        let loader = self
+            .extensions
+            .get(extension.as_str())
+            .map(|index| &self.loaders[*index])
+            .ok_or_else(|| {
+                OtherError::new(&format!("no loader for {}", path.display()))
+            })?;
+
+        loader
+            .load(renderer, path)?
+            .downcast()
+            .map(|asset| *asset)
+            .map_err(|_| {
+                OtherError::new(&format!(
+                    "loader for {} returned another type",
+                    path.display()
+                ))
+                .into()
+            })
+    }
+
+    /// None if no widget has that name.
+    #[cfg(all(feature = "text", feature = "shapes"))]
+    pub fn create_widget(
+        &self,
+        name: &str,
+        renderer: &mut GpuRenderer,
+        anchor: Vec3,
+    ) -> Option<WorldUi> {
+        self.widgets
+            .get(name)
+            .map(|factory| factory(renderer, anchor))
+    }
+}
+
+/// What [`Plugin::setup`] can register into.
+pub struct PluginSetup<'a> {
+    pub renderer: &'a mut GpuRenderer,
+    pub registry: &'a mut PluginRegistry,
+}
+
+impl<'a> PluginSetup<'a> {
+    pub fn register_pipeline<K: PipeLineLayout>(&mut self, pipeline: K) {
+        self.renderer.create_pipeline(pipeline);
+    }
+
+    pub fn register_loader<L: AssetLoader>(&mut self, loader: L) {
+        self.registry.register_loader(loader);
+    }
+
+    #[cfg(all(feature = "text", feature = "shapes"))]
+    pub fn register_widget<F>(&mut self, name: &'static str, factory: F)
+    where
+        F: Fn(&mut GpuRenderer, Vec3) -> WorldUi + 'static,
+    {
+        self.registry.register_widget(name, factory);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::GpuRenderer;
+#[cfg(feature = "sprites")]
+use crate::{Image, ImageRenderer};
+#[cfg(feature = "shapes")]
+use crate::{Rect, RectRenderer};
+
+/// Refers to an object in a [`Pool`]. Handles of removed objects stay
+/// invalid even after their slot is reused.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PoolHandle {
+    index: u32,
+    generation: u32,
+}
+
+/// Objects a [`Pool`] can free the GPU buffers of when they are removed.
+pub trait Poolable {
+    fn unload(self, renderer: &mut GpuRenderer);
+}
+
+#[cfg(feature = "sprites")]
+impl Poolable for Image {
+    fn unload(self, renderer: &mut GpuRenderer) {
+        Image::unload(self, renderer);
+    }
+}
+
+#[cfg(feature = "shapes")]
+impl Poolable for Rect {
+    fn unload(self, renderer: &mut GpuRenderer) {
+        Rect::unload(self, renderer);
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Owns short lived objects like bullets or pickups and hands out
+/// [`PoolHandle`]s to them so they can be referenced without borrowing.
+pub struct Pool<T: Poolable> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+#[cfg(feature = "sprites")]
+pub type SpritePool = Pool<Image>;
+#[cfg(feature = "shapes")]
+pub type ShapePool = Pool<Rect>;
+
+impl<T: Poolable> Default for Pool<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T: Poolable> Pool<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> PoolHandle {
+        self.len += 1;
+
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+
+            slot.value = Some(value);
+            return PoolHandle {
+                index,
+                generation: slot.generation,
+            };
+        }
+
+        self.slots.push(Slot {
+            generation: 0,
+            value: Some(value),
+        });
+
+        PoolHandle {
+            index: self.slots.len() as u32 - 1,
+            generation: 0,
+        }
+    }
+
+    fn slot(&self, handle: PoolHandle) -> Option<&Slot<T>> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+    }
+
+    /// Removes the object and frees its buffers. Returns false if the
+    /// handle was already removed.
+    pub fn remove(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        handle: PoolHandle,
+    ) -> bool {
+        match self.take(handle) {
+            Some(value) => {
+                value.unload(renderer);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the object without freeing its buffers so it can be used
+    /// outside the pool.
+    pub fn take(&mut self, handle: PoolHandle) -> Option<T> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+
+        if slot.generation != handle.generation {
+            return None;
+        }
+
+        let value = slot.value.take()?;
+
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn get(&self, handle: PoolHandle) -> Option<&T> {
+        self.slot(handle)?.value.as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle: PoolHandle) -> Option<&mut T> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+
+        if slot.generation != handle.generation {
+            return None;
+        }
+
+        slot.value.as_mut()
+    }
+
+    pub fn contains(&self, handle: PoolHandle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (PoolHandle, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.value.as_ref().map(|value| {
+                (
+                    PoolHandle {
+                        index: index as u32,
+                        generation: slot.generation,
+                    },
+                    value,
+                )
+            })
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (PoolHandle, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let generation = slot.generation;
+
+                slot.value.as_mut().map(|value| {
+                    (
+                        PoolHandle {
+                            index: index as u32,
+                            generation,
+                        },
+                        value,
+                    )
+                })
+            })
+    }
+
+    /// Removes every object f returns false for, freeing their buffers.
+    pub fn retain<F>(&mut self, renderer: &mut GpuRenderer, mut f: F)
+    where
+        F: FnMut(PoolHandle, &mut T) -> bool,
+    {
+        let mut removed = Vec::new();
+
+        for (handle, value) in self.iter_mut() {
+            if !f(handle, value) {
+                removed.push(handle);
+            }
+        }
+
+        for handle in removed {
+            self.remove(renderer, handle);
+        }
+    }
+
+    /// Removes everything. Old handles stay invalid.
+    pub fn clear(&mut self, renderer: &mut GpuRenderer) {
+        let handles: Vec<PoolHandle> =
+            self.iter().map(|(handle, _)| handle).collect();
+
+        for handle in handles {
+            self.remove(renderer, handle);
+        }
+    }
+}
+
+#[cfg(feature = "sprites")]
+impl SpritePool {
+    /// Adds every sprite to the renderer's buffer for this frame.
+    pub fn update(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        image_renderer: &mut ImageRenderer,
+    ) {
+        for (_, sprite) in self.iter_mut() {
+            image_renderer.image_update(sprite, renderer);
+        }
+    }
+}
+
+#[cfg(feature = "shapes")]
+impl ShapePool {
+    /// Adds every shape to the renderer's buffer for this frame.
+    pub fn update(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        rect_renderer: &mut RectRenderer,
+    ) {
+        for (_, shape) in self.iter_mut() {
+            rect_renderer.rect_update(shape, renderer);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! The types most apps need, as one stable import:
+//! `use graphics::prelude::*;`. Everything else is still at the crate
+//! root, and the raw GPU types are in [`crate::advanced`].
+
+pub use crate::{
+    AnimationClock, App, AppContext, AsBufferPass, AscendingError, AssetStream,
+    Atlas, AtlasGroup, BackgroundSettings, Bounds, BufferPass, CameraGoal,
+    Color, ColorblindMode, CoordinateSystem, Damage, DamageRect,
+    DamageRenderer, DamageTracker, DisplayRenderer, DisplaySettings, DrawBatch,
+    DrawCache, DrawLayer, DrawOrder, Easing, FramePacing, GpuDevice,
+    GpuRenderer, GpuWindow, Index, Layers, LoadProgress, LoadingScene,
+    OrderedIndex, Pass, Plugin, PluginRegistry, PreloadManifest, Preloader,
+    RenderCommands, RenderStats, RendererBuilder, ResolutionScale, Runner,
+    SetBuffers, SortPolicy, StreamingSettings, System, Texture, TextureGroup,
+    TextureStreamer, Transition, TransitionKind, TransitionRenderer,
+    UpscaleFilter, UpscaleRenderer, Vec2, Vec3, Vec4, WindowActivity,
+    WipeDirection, WorldBounds,
+};
+
+#[cfg(feature = "sprites")]
+pub use crate::{
+    AnimationClip, Image, ImageRenderer, PanelCache, RenderImage, SpriteBatch,
+};
+#[cfg(feature = "lights")]
+pub use crate::{AreaLight, DirectionalLight, LightRenderer, RenderLights};
+#[cfg(feature = "shapes")]
+pub use crate::{
+    DrawMode, Mesh2D, Mesh2DBuilder, Mesh2DRenderer, Rect, RectRenderer,
+    RenderMesh2D, RenderRects,
+};
+#[cfg(feature = "text")]
+pub use crate::{
+    GlyphTransform, RenderText, Text, TextAlign, TextAtlas, TextDirection,
+    TextLayout, TextRenderer,
+};
+#[cfg(feature = "maps")]
+pub use crate::{
+    Map, MapLayers, MapRegion, MapRenderer, MapTransition, MapTransitionKind,
+    MaterialTable, RegionEvent, RegionEventKind, RegionShape, RegionTracker,
+    RenderMap, TileData, TileMaterial,
+};
+#[cfg(all(feature = "maps", feature = "sprites"))]
+pub use crate::{MapLod, TileColors};
@@ -0,0 +1,402 @@
+use crate::{
+    AscendingError, Color, Easing, GpuRenderer, MessageRouter,
+    RadialMenuWidget, Rect, RectRenderer, Text, TextAtlas, TextRenderer,
+    UiSound, Vec2, Vec3, WidgetHandle, WidgetInput, WidgetSounds,
+};
+use cosmic_text::{Attrs, Metrics};
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+/// How the trigger button opens a [`RadialMenu`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RadialTrigger {
+    /// Opens while held and picks the highlighted item on release.
+    #[default]
+    Hold,
+    /// A press opens it. A click or another press picks the highlighted
+    /// item, or closes it if nothing is highlighted.
+    Toggle,
+}
+
+/// This frame's input for a [`RadialMenu`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RadialInput<'a> {
+    pub widget: WidgetInput<'a>,
+    /// if the button opening the menu is down.
+    pub trigger: bool,
+    /// Gamepad stick, -1.0 to 1.0 with up positive. Used over the mouse
+    /// when pushed past the stick dead zone.
+    pub stick: Vec2,
+}
+
+pub struct RadialItem {
+    pub label: String,
+    pub rect: Rect,
+    pub text: Text,
+}
+
+/// Items laid out in a ring around a center, picked by pointing in their
+/// direction with the mouse or a stick. The first item is at the top and
+/// the rest go clockwise. Opening and closing grow and fade the ring over
+/// duration.
+///
+/// Placed in render space like the [`crate::Spinner`]. Picks are emitted
+/// through the router when a handle is set.
+pub struct RadialMenu {
+    pub items: Vec<RadialItem>,
+    pub handle: Option<WidgetHandle<RadialMenuWidget>>,
+    pub sounds: WidgetSounds,
+    pub trigger: RadialTrigger,
+    /// Opens around the cursor instead of the anchor when there is one.
+    pub open_at_cursor: bool,
+    /// Distance from the center to the items' centers.
+    pub radius: f32,
+    pub item_size: Vec2,
+    /// Pixels from the center the mouse has to move to highlight an item.
+    pub dead_zone: f32,
+    pub stick_dead_zone: f32,
+    /// Seconds opening or closing takes.
+    pub duration: f32,
+    pub easing: Easing,
+    pub color: Color,
+    pub highlight_color: Color,
+    pub text_color: Color,
+    render_layer: u32,
+    anchor: Vec3,
+    center: Vec2,
+    open: bool,
+    /// 0.0 closed to 1.0 open, before easing.
+    progress: f32,
+    highlighted: Option<usize>,
+    was_trigger: bool,
+    was_down: bool,
+    pending_sounds: Vec<UiSound>,
+    changed: bool,
+}
+
+impl RadialMenu {
+    pub fn new(render_layer: u32) -> Self {
+        Self {
+            items: Vec::new(),
+            handle: None,
+            sounds: WidgetSounds::default(),
+            trigger: RadialTrigger::default(),
+            open_at_cursor: true,
+            radius: 96.0,
+            item_size: Vec2::new(96.0, 28.0),
+            dead_zone: 16.0,
+            stick_dead_zone: 0.35,
+            duration: 0.15,
+            easing: Easing::EaseOut,
+            color: Color::rgba(40, 40, 40, 230),
+            highlight_color: Color::rgba(90, 120, 200, 240),
+            text_color: Color::rgba(230, 230, 230, 255),
+            render_layer,
+            anchor: Vec3::ZERO,
+            center: Vec2::ZERO,
+            open: false,
+            progress: 0.0,
+            highlighted: None,
+            was_trigger: false,
+            was_down: false,
+            pending_sounds: Vec::new(),
+            changed: true,
+        }
+    }
+
+    /// Returns the item's index.
+    pub fn add_item(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        label: &str,
+    ) -> usize {
+        let mut rect = Rect::new(renderer, self.render_layer);
+        let mut text = Text::new(
+            renderer,
+            Some(Metrics::new(14.0, 16.0)),
+            Vec3::ZERO,
+            Vec2::ZERO,
+        );
+
+        rect.set_radius(6.0).set_border_width(1.0);
+        text.set_text(renderer, label, Attrs::new());
+
+        self.items.push(RadialItem {
+            label: label.to_owned(),
+            rect,
+            text,
+        });
+        self.changed = true;
+        self.items.len() - 1
+    }
+
+    /// Center and z to open at when not opening at the cursor.
+    pub fn set_anchor(&mut self, anchor: Vec3) -> &mut Self {
+        self.anchor = anchor;
+
+        if !self.open_at_cursor {
+            self.center = anchor.truncate();
+        }
+
+        self.changed = true;
+        self
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// if anything is on screen, including while closing.
+    pub fn is_visible(&self) -> bool {
+        self.progress > 0.0
+    }
+
+    pub fn highlighted(&self) -> Option<usize> {
+        self.highlighted
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.center
+    }
+
+    /// Opens around center.
+    pub fn open(&mut self, center: Vec2) {
+        if self.open {
+            return;
+        }
+
+        self.open = true;
+        self.center = center;
+        self.highlighted = None;
+        self.changed = true;
+        self.pending_sounds.push(UiSound::Open);
+    }
+
+    pub fn close(&mut self) {
+        if !self.open {
+            return;
+        }
+
+        self.open = false;
+        self.changed = true;
+        self.pending_sounds.push(UiSound::Close);
+    }
+
+    /// Applies a frame of input. Returns the picked item, which is also
+    /// emitted if a handle is set.
+    pub fn handle_input<M: 'static>(
+        &mut self,
+        input: &RadialInput,
+        router: &mut MessageRouter<M>,
+    ) -> Option<usize> {
+        let trigger_pressed = input.trigger && !self.was_trigger;
+        let trigger_released = !input.trigger && self.was_trigger;
+        let clicked = input.widget.mouse_down && !self.was_down;
+        let center = match (self.open_at_cursor, input.widget.mouse_pos) {
+            (true, Some(pos)) => pos,
+            _ => self.anchor.truncate(),
+        };
+
+        self.was_trigger = input.trigger;
+        self.was_down = input.widget.mouse_down;
+
+        let mut picked = None;
+
+        if !self.open {
+            if trigger_pressed {
+                self.open(center);
+            }
+        } else {
+            self.highlight(input);
+
+            let confirm = match self.trigger {
+                RadialTrigger::Hold => trigger_released,
+                RadialTrigger::Toggle => trigger_pressed || clicked,
+            };
+
+            if confirm {
+                picked = self.highlighted;
+
+                if picked.is_some() {
+                    self.pending_sounds.push(UiSound::Click);
+                }
+
+                self.close();
+            }
+        }
+
+        for sound in self.pending_sounds.drain(..) {
+            router.play_sound(self.sounds.cue(sound));
+        }
+
+        if let (Some(index), Some(handle)) = (picked, self.handle) {
+            router.emit(handle, index);
+        }
+
+        picked
+    }
+
+    /// Moves the open and close animation along.
+    pub fn advance(&mut self, delta: f32) {
+        let target = if self.open { 1.0 } else { 0.0 };
+
+        if self.progress == target {
+            return;
+        }
+
+        let step = if self.duration > 0.0 {
+            delta / self.duration
+        } else {
+            1.0
+        };
+
+        self.progress = if self.open {
+            (self.progress + step).min(1.0)
+        } else {
+            (self.progress - step).max(0.0)
+        };
+        self.changed = true;
+    }
+
+    fn highlight(&mut self, input: &RadialInput) {
+        let direction = if input.stick.length() > self.stick_dead_zone {
+            Some(input.stick)
+        } else {
+            input
+                .widget
+                .mouse_pos
+                .map(|pos| pos - self.center)
+                .filter(|offset| offset.length() > self.dead_zone)
+        };
+
+        let highlighted = direction
+            .filter(|_| !self.items.is_empty())
+            .map(|direction| self.item_at(direction));
+
+        if highlighted != self.highlighted {
+            if highlighted.is_some() {
+                self.pending_sounds.push(UiSound::Hover);
+            }
+
+            self.highlighted = highlighted;
+            self.changed = true;
+        }
+    }
+
+    /// The item whose slice of the ring direction points into.
+    fn item_at(&self, direction: Vec2) -> usize {
+        let count = self.items.len() as f32;
+        let slice = TAU / count;
+        // Clockwise from the top.
+        let angle =
+            (FRAC_PI_2 - direction.y.atan2(direction.x)).rem_euclid(TAU);
+
+        (((angle + slice / 2.0) / slice) as usize) % self.items.len()
+    }
+
+    fn item_direction(&self, index: usize) -> Vec2 {
+        let angle = FRAC_PI_2 - TAU * index as f32 / self.items.len() as f32;
+
+        Vec2::new(angle.cos(), angle.sin())
+    }
+
+    /// Lays out the items if anything changed.
+    fn refresh(&mut self, renderer: &mut GpuRenderer) {
+        if !self.changed {
+            return;
+        }
+
+        let eased = self.easing.apply(self.progress);
+        let fade = |color: Color| {
+            Color::rgba(
+                color.r(),
+                color.g(),
+                color.b(),
+                (color.a() as f32 * eased) as u8,
+            )
+        };
+        let size = self.item_size;
+        let z = self.anchor.z;
+
+        for index in 0..self.items.len() {
+            let center =
+                self.center + self.item_direction(index) * self.radius * eased;
+            let corner = center - size / 2.0;
+            let color = if self.highlighted == Some(index) {
+                self.highlight_color
+            } else {
+                self.color
+            };
+            let line_height =
+                self.items[index].text.buffer.metrics().line_height;
+            let item = &mut self.items[index];
+
+            item.rect
+                .set_position(corner.extend(z))
+                .set_size(size)
+                .set_color(fade(color))
+                .set_border_color(fade(self.highlight_color));
+
+            // lower Z renders over the rect.
+            item.text
+                .set_buffer_size(renderer, (size.x - 8.0) as i32, size.y as i32)
+                .set_default_color(fade(self.text_color))
+                .set_position(Vec3::new(
+                    corner.x + 4.0,
+                    corner.y + (size.y - line_height) / 2.0,
+                    z - 0.01,
+                ));
+            item.text.size = Vec2::new(size.x - 8.0, line_height);
+        }
+
+        self.changed = false;
+    }
+
+    /// Frees the items' rects and text.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        for item in self.items {
+            item.rect.unload(renderer);
+            item.text.unload(renderer);
+        }
+    }
+}
+
+impl RectRenderer {
+    /// Adds nothing while the menu is fully closed.
+    pub fn radial_menu_update(
+        &mut self,
+        menu: &mut RadialMenu,
+        renderer: &mut GpuRenderer,
+    ) {
+        if !menu.is_visible() {
+            return;
+        }
+
+        menu.refresh(renderer);
+
+        for item in &mut menu.items {
+            self.rect_update(&mut item.rect, renderer);
+        }
+    }
+}
+
+impl TextRenderer {
+    /// Adds nothing while the menu is fully closed.
+    pub fn radial_menu_update(
+        &mut self,
+        menu: &mut RadialMenu,
+        atlas: &mut TextAtlas,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        if !menu.is_visible() {
+            return Ok(());
+        }
+
+        menu.refresh(renderer);
+
+        for item in &mut menu.items {
+            self.text_update(&mut item.text, atlas, renderer)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,356 @@
+use crate::{
+    Color, GpuRenderer, Rect, Text, Vec2, Vec3, WorldUi, WorldUiElement,
+    WorldUiKind, WorldUiText,
+};
+use cosmic_text::Attrs;
+use std::collections::HashMap;
+
+/// Stable id of a described widget. Widgets keep their state, like text
+/// scroll or focus, for as long as the same key is described.
+pub type UiKey = u64;
+
+/// A widget a rebuild based UI wants to exist this frame.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UiNode {
+    Rect {
+        key: UiKey,
+        offset: Vec2,
+        size: Vec2,
+        color: Color,
+    },
+    /// percent is 0.0..=1.0 of the width to fill.
+    Bar {
+        key: UiKey,
+        offset: Vec2,
+        size: Vec2,
+        background: Color,
+        fill: Color,
+        percent: f32,
+    },
+    Text {
+        key: UiKey,
+        offset: Vec2,
+        size: Vec2,
+        text: String,
+        color: Color,
+    },
+}
+
+impl UiNode {
+    pub fn key(&self) -> UiKey {
+        match self {
+            UiNode::Rect { key, .. }
+            | UiNode::Bar { key, .. }
+            | UiNode::Text { key, .. } => *key,
+        }
+    }
+}
+
+/// What a [`WorldUi::reconcile`] did.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReconcileStats {
+    pub created: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+impl ReconcileStats {
+    pub fn is_unchanged(&self) -> bool {
+        self.created == 0 && self.updated == 0 && self.removed == 0
+    }
+}
+
+impl WorldUi {
+    /// Makes the group match nodes, which can be rebuilt from the app's
+    /// state every frame. Widgets are matched by key: existing ones are
+    /// updated in place, new keys are created and keys no longer described
+    /// are unloaded along with any widgets added without a key. Elements
+    /// and texts end up in the order they are described.
+    pub fn reconcile(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        nodes: &[UiNode],
+    ) -> ReconcileStats {
+        let mut stats = ReconcileStats::default();
+        let mut old_elements: HashMap<UiKey, WorldUiElement> = HashMap::new();
+        let mut old_texts: HashMap<UiKey, WorldUiText> = HashMap::new();
+
+        for element in self.elements.drain(..) {
+            match element.key {
+                Some(key) if !old_elements.contains_key(&key) => {
+                    old_elements.insert(key, element);
+                }
+                _ => {
+                    element.unload(renderer);
+                    stats.removed += 1;
+                }
+            }
+        }
+
+        for text in self.texts.drain(..) {
+            match text.key {
+                Some(key) if !old_texts.contains_key(&key) => {
+                    old_texts.insert(key, text);
+                }
+                _ => {
+                    text.unload(renderer);
+                    stats.removed += 1;
+                }
+            }
+        }
+
+        for node in nodes {
+            match node {
+                UiNode::Text {
+                    key,
+                    offset,
+                    size,
+                    text,
+                    color,
+                } => {
+                    let widget = match old_texts.remove(key) {
+                        Some(mut widget) => {
+                            if update_text(
+                                &mut widget,
+                                renderer,
+                                *offset,
+                                *size,
+                                text,
+                                *color,
+                            ) {
+                                stats.updated += 1;
+                            }
+
+                            widget
+                        }
+                        None => {
+                            stats.created += 1;
+                            create_text(
+                                renderer, *key, *offset, *size, text, *color,
+                            )
+                        }
+                    };
+
+                    self.texts.push(widget);
+                }
+                _ => {
+                    let element = match old_elements.remove(&node.key()) {
+                        Some(mut element) => {
+                            match update_element(&mut element, node) {
+                                Some(true) => stats.updated += 1,
+                                Some(false) => {}
+                                // A different kind under the same key.
+                                None => {
+                                    element.unload(renderer);
+                                    stats.removed += 1;
+                                    stats.created += 1;
+                                    element = create_element(renderer, node);
+                                }
+                            }
+
+                            element
+                        }
+                        None => {
+                            stats.created += 1;
+                            create_element(renderer, node)
+                        }
+                    };
+
+                    self.elements.push(element);
+                }
+            }
+        }
+
+        stats.removed += old_elements.len() + old_texts.len();
+
+        for (_, element) in old_elements {
+            element.unload(renderer);
+        }
+
+        for (_, text) in old_texts {
+            text.unload(renderer);
+        }
+
+        if let Some(focused) = self.focused {
+            if !nodes.iter().any(|node| node.key() == focused) {
+                self.focused = None;
+            }
+        }
+
+        if !stats.is_unchanged() {
+            self.mark_changed();
+        }
+
+        stats
+    }
+}
+
+fn create_element(renderer: &mut GpuRenderer, node: &UiNode) -> WorldUiElement {
+    let mut rect = Rect::new(renderer, 0);
+
+    let (key, offset, size, kind) = match node {
+        UiNode::Rect {
+            key,
+            offset,
+            size,
+            color,
+        } => {
+            rect.set_color(*color);
+            (*key, *offset, *size, WorldUiKind::Rect)
+        }
+        UiNode::Bar {
+            key,
+            offset,
+            size,
+            background,
+            fill: fill_color,
+            percent,
+        } => {
+            rect.set_color(*background);
+
+            let mut fill = Rect::new(renderer, 0);
+            fill.set_color(*fill_color);
+
+            let kind = WorldUiKind::Bar {
+                fill,
+                percent: percent.clamp(0.0, 1.0),
+            };
+
+            (*key, *offset, *size, kind)
+        }
+        UiNode::Text { .. } => unreachable!("texts are not elements"),
+    };
+
+    WorldUiElement {
+        key: Some(key),
+        offset,
+        size,
+        rect,
+        kind,
+    }
+}
+
+/// Some(true) if anything changed, None if the element is of another kind.
+fn update_element(element: &mut WorldUiElement, node: &UiNode) -> Option<bool> {
+    let mut changed = false;
+
+    let (offset, size) = match (node, &mut element.kind) {
+        (
+            UiNode::Rect {
+                offset,
+                size,
+                color,
+                ..
+            },
+            WorldUiKind::Rect,
+        ) => {
+            if element.rect.color != *color {
+                element.rect.set_color(*color);
+                changed = true;
+            }
+
+            (*offset, *size)
+        }
+        (
+            UiNode::Bar {
+                offset,
+                size,
+                background,
+                fill: fill_color,
+                percent: new_percent,
+                ..
+            },
+            WorldUiKind::Bar { fill, percent },
+        ) => {
+            let new_percent = new_percent.clamp(0.0, 1.0);
+
+            if element.rect.color != *background {
+                element.rect.set_color(*background);
+                changed = true;
+            }
+
+            if fill.color != *fill_color {
+                fill.set_color(*fill_color);
+                changed = true;
+            }
+
+            if *percent != new_percent {
+                *percent = new_percent;
+                changed = true;
+            }
+
+            (*offset, *size)
+        }
+        _ => return None,
+    };
+
+    if element.offset != offset || element.size != size {
+        element.offset = offset;
+        element.size = size;
+        changed = true;
+    }
+
+    Some(changed)
+}
+
+fn create_text(
+    renderer: &mut GpuRenderer,
+    key: UiKey,
+    offset: Vec2,
+    size: Vec2,
+    content: &str,
+    color: Color,
+) -> WorldUiText {
+    let mut text = Text::new(renderer, None, Vec3::ZERO, size);
+
+    text.set_buffer_size(renderer, size.x as i32, size.y as i32)
+        .set_default_color(color)
+        .set_text(renderer, content, Attrs::new());
+    text.use_camera = false;
+
+    WorldUiText {
+        key: Some(key),
+        offset,
+        text,
+        content: content.to_owned(),
+        localized: None,
+    }
+}
+
+/// Only touches what differs so the scroll and cursor of the text stay.
+fn update_text(
+    widget: &mut WorldUiText,
+    renderer: &mut GpuRenderer,
+    offset: Vec2,
+    size: Vec2,
+    content: &str,
+    color: Color,
+) -> bool {
+    let mut changed = false;
+
+    if widget.offset != offset {
+        widget.offset = offset;
+        changed = true;
+    }
+
+    if widget.text.size != size {
+        widget.text.size = size;
+        widget
+            .text
+            .set_buffer_size(renderer, size.x as i32, size.y as i32);
+        changed = true;
+    }
+
+    if widget.text.default_color != color {
+        widget.text.set_default_color(color);
+        changed = true;
+    }
+
+    if widget.content != content {
+        widget.text.set_text(renderer, content, Attrs::new());
+        widget.content = content.to_owned();
+        changed = true;
+    }
+
+    changed
+}
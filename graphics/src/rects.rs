@@ -0,0 +1,9 @@
+pub(crate) mod pipeline;
+mod rect;
+mod render;
+pub(crate) mod vertex;
+
+pub(crate) use pipeline::*;
+pub use rect::*;
+pub use render::*;
+pub(crate) use vertex::*;
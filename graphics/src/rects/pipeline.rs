@@ -0,0 +1,86 @@
+use crate::{
+    shaders, BufferLayout, GpuDevice, LayoutStorage, PipeLineLayout,
+    RectVertex, StaticBufferObject, SystemLayout,
+};
+use bytemuck::{Pod, Zeroable};
+
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct RectRenderPipeline;
+
+impl PipeLineLayout for RectRenderPipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let shader = gpu_device.device().create_shader_module(
+            wgpu::ShaderModuleDescriptor {
+                label: Some("Shader"),
+                source: wgpu::ShaderSource::Wgsl(shaders::RECT_SHADER.into()),
+            },
+        );
+
+        let system_layout = layouts.create_layout(gpu_device, SystemLayout);
+
+        // Create the render pipeline.
+        gpu_device.device().create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: Some("Rect render pipeline"),
+                layout: Some(&gpu_device.device().create_pipeline_layout(
+                    &wgpu::PipelineLayoutDescriptor {
+                        label: Some("rect_render_pipeline_layout"),
+                        bind_group_layouts: &[&system_layout],
+                        push_constant_ranges: &[],
+                    },
+                )),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vertex",
+                    buffers: &[
+                        wgpu::VertexBufferLayout {
+                            array_stride: StaticBufferObject::stride(),
+                            step_mode: wgpu::VertexStepMode::Vertex,
+                            attributes: &[
+                                StaticBufferObject::vertex_attribute(),
+                            ],
+                        },
+                        wgpu::VertexBufferLayout {
+                            array_stride: RectVertex::stride() as u64,
+                            step_mode: wgpu::VertexStepMode::Instance,
+                            attributes: &RectVertex::attributes(),
+                        },
+                    ],
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    unclipped_depth: false,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    conservative: false,
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: true,
+                    depth_compare: gpu_device.depth_compare(),
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: gpu_device.multisample_state(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fragment",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: surface_format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview: None,
+            },
+        )
+    }
+}
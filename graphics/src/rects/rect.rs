@@ -0,0 +1,178 @@
+use crate::{
+    Color, DrawLayer, DrawOrder, GpuRenderer, Index, OrderedIndex, RectVertex,
+    Vec2, Vec3,
+};
+
+/// A flat colored rectangle with an optional border and rounded corners.
+pub struct Rect {
+    pub position: Vec3,
+    pub size: Vec2,
+    pub color: Color,
+    pub border_width: f32,
+    pub border_color: Color,
+    /// corner radius in pixels. 0.0 is a hard corner.
+    pub radius: f32,
+    /// Degrees counter clockwise around position.
+    pub rotation: f32,
+    /// Share drawn clockwise from the top, 0.0 to 1.0. Less than 1.0 cuts
+    /// out a pie slice, like a cooldown sweep.
+    pub sweep: f32,
+    /// if the shader should render with the camera's view.
+    pub use_camera: bool,
+    pub store_id: Index,
+    pub order: DrawOrder,
+    /// Orders it within its layer under [`crate::SortPolicy::Key`].
+    pub sort_key: u64,
+    pub render_layer: u32,
+    /// Data for custom pipelines, like a dissolve threshold or team color.
+    /// The built in shaders ignore it.
+    pub user_data: [f32; 4],
+    /// if anything got updated we need to update the buffers too.
+    pub changed: bool,
+}
+
+impl Rect {
+    pub fn new(renderer: &mut GpuRenderer, render_layer: u32) -> Self {
+        Self {
+            position: Vec3::default(),
+            size: Vec2::default(),
+            color: Color::rgba(255, 255, 255, 255),
+            border_width: 0.0,
+            border_color: Color::rgba(0, 0, 0, 0),
+            radius: 0.0,
+            rotation: 0.0,
+            sweep: 1.0,
+            use_camera: false,
+            store_id: renderer.new_buffer(),
+            order: DrawOrder::default(),
+            sort_key: 0,
+            render_layer,
+            user_data: [0.0; 4],
+            changed: true,
+        }
+    }
+
+    pub fn set_position(&mut self, position: Vec3) -> &mut Self {
+        self.position = position;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_size(&mut self, size: Vec2) -> &mut Self {
+        self.size = size;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_color(&mut self, color: Color) -> &mut Self {
+        self.color = color;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_border_width(&mut self, width: f32) -> &mut Self {
+        self.border_width = width;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_border_color(&mut self, color: Color) -> &mut Self {
+        self.border_color = color;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_radius(&mut self, radius: f32) -> &mut Self {
+        self.radius = radius;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32) -> &mut Self {
+        self.rotation = rotation;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_sweep(&mut self, sweep: f32) -> &mut Self {
+        self.sweep = sweep.clamp(0.0, 1.0);
+        self.changed = true;
+        self
+    }
+
+    /// Moves the rect into the layer at a depth within it, 0.0 at the
+    /// front and 1.0 at the back.
+    pub fn set_layer(&mut self, layer: DrawLayer, depth: f32) -> &mut Self {
+        self.position.z = layer.z(depth);
+        self.render_layer = layer.index();
+        self.changed = true;
+        self
+    }
+
+    pub fn set_sort_key(&mut self, sort_key: u64) -> &mut Self {
+        self.sort_key = sort_key;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_user_data(&mut self, user_data: [f32; 4]) -> &mut Self {
+        self.user_data = user_data;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_use_camera(&mut self, use_camera: bool) -> &mut Self {
+        self.use_camera = use_camera;
+        self.changed = true;
+        self
+    }
+
+    pub fn create_quad(&mut self, renderer: &mut GpuRenderer) {
+        let instance = RectVertex {
+            position: self.position.to_array(),
+            size: self.size.to_array(),
+            color: self.color.0,
+            border_width: self.border_width,
+            border_color: self.border_color.0,
+            radius: self.radius,
+            use_camera: u32::from(self.use_camera),
+            rotation: self.rotation,
+            sweep: self.sweep,
+            user_data: self.user_data,
+        };
+
+        if let Some(store) = renderer.get_buffer_mut(&self.store_id) {
+            store.store = bytemuck::bytes_of(&instance).to_vec();
+            store.changed = true;
+        }
+
+        self.order = DrawOrder::new(
+            self.color.a() < 255 || self.border_color.a() < 255,
+            &self.position,
+            self.render_layer,
+        )
+        .with_sort_key(self.sort_key);
+        self.changed = false;
+    }
+
+    /// used to check and update the vertex array.
+    pub fn update(&mut self, renderer: &mut GpuRenderer) -> OrderedIndex {
+        if self.changed {
+            self.create_quad(renderer);
+        }
+
+        OrderedIndex::new(self.order, self.store_id, 0)
+    }
+
+    /// Frees the rect's buffer. Use when it won't be rendered again.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        renderer.remove_buffer(self.store_id);
+    }
+
+    pub fn check_mouse_bounds(&self, mouse_pos: Vec2) -> bool {
+        mouse_pos[0] > self.position.x
+            && mouse_pos[0] < self.position.x + self.size.x
+            && mouse_pos[1] > self.position.y
+            && mouse_pos[1] < self.position.y + self.size.y
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{
+    AscendingError, DrawCache, GpuRenderer, InstanceBuffer, OrderedIndex, Rect,
+    RectRenderPipeline, RectVertex, RenderCommands, StaticBufferObject,
+};
+
+pub struct RectRenderer {
+    pub buffer: InstanceBuffer<RectVertex>,
+}
+
+impl RectRenderer {
+    pub fn new(renderer: &GpuRenderer) -> Result<Self, AscendingError> {
+        Ok(Self {
+            buffer: InstanceBuffer::new(renderer.gpu_device()),
+        })
+    }
+
+    pub fn add_buffer_store(
+        &mut self,
+        renderer: &GpuRenderer,
+        index: OrderedIndex,
+    ) {
+        self.buffer.add_buffer_store(renderer, index);
+    }
+
+    pub fn finalize(&mut self, renderer: &mut GpuRenderer) {
+        self.buffer.finalize(renderer)
+    }
+
+    pub fn rect_update(&mut self, rect: &mut Rect, renderer: &mut GpuRenderer) {
+        let index = rect.update(renderer);
+
+        self.add_buffer_store(renderer, index);
+    }
+
+    /// Runs draw, a widget's updates, only while cache is dirty and
+    /// otherwise adds the rects it added last time, so a static UI costs
+    /// next to nothing per frame.
+    pub fn cached_update(
+        &mut self,
+        cache: &mut DrawCache,
+        renderer: &mut GpuRenderer,
+        draw: impl FnOnce(&mut Self, &mut GpuRenderer),
+    ) {
+        if self.buffer.add_cached(renderer, cache) {
+            return;
+        }
+
+        let start = self.buffer.added();
+
+        draw(self, renderer);
+        self.buffer.record_cache(start, cache);
+    }
+}
+
+pub trait RenderRects<'a, 'b>
+where
+    'b: 'a,
+{
+    fn render_rects(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b RectRenderer,
+    );
+
+    /// Like render_rects but with a custom pipeline made for
+    /// [`RectVertex`], which can read each rect's
+    /// [`crate::Rect::user_data`].
+    fn render_rects_with(
+        &mut self,
+        buffer: &'b RectRenderer,
+        pipeline: &'b wgpu::RenderPipeline,
+    );
+}
+
+impl<'a, 'b, P> RenderRects<'a, 'b> for P
+where
+    'b: 'a,
+    P: RenderCommands<'a>,
+{
+    fn render_rects(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b RectRenderer,
+    ) {
+        if buffer.buffer.count() > 0 {
+            self.set_vertex_buffer(1, buffer.buffer.instances(None));
+            self.set_pipeline(
+                renderer.get_pipelines(RectRenderPipeline).unwrap(),
+            );
+
+            self.draw_indexed(
+                0..StaticBufferObject::index_count(),
+                0,
+                0..buffer.buffer.count(),
+            );
+        }
+    }
+
+    fn render_rects_with(
+        &mut self,
+        buffer: &'b RectRenderer,
+        pipeline: &'b wgpu::RenderPipeline,
+    ) {
+        if buffer.buffer.count() > 0 {
+            self.set_vertex_buffer(1, buffer.buffer.instances(None));
+            self.set_pipeline(pipeline);
+
+            self.draw_indexed(
+                0..StaticBufferObject::index_count(),
+                0,
+                0..buffer.buffer.count(),
+            );
+        }
+    }
+}
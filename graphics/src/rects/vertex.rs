@@ -0,0 +1,67 @@
+use crate::{BufferData, BufferLayout};
+use std::iter;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct RectVertex {
+    pub position: [f32; 3],
+    pub size: [f32; 2],
+    pub color: u32,
+    pub border_width: f32,
+    pub border_color: u32,
+    pub radius: f32,
+    pub use_camera: u32,
+    /// Degrees counter clockwise around position.
+    pub rotation: f32,
+    /// Share drawn clockwise from the top, 1.0 is the whole rect.
+    pub sweep: f32,
+    /// Free for custom pipelines, read as `@location(10) vec4<f32>`.
+    pub user_data: [f32; 4],
+}
+
+impl Default for RectVertex {
+    fn default() -> Self {
+        Self {
+            position: [0.0; 3],
+            size: [0.0; 2],
+            color: 0,
+            border_width: 0.0,
+            border_color: 0,
+            radius: 0.0,
+            use_camera: 1,
+            rotation: 0.0,
+            sweep: 1.0,
+            user_data: [0.0; 4],
+        }
+    }
+}
+
+impl BufferLayout for RectVertex {
+    fn attributes() -> Vec<wgpu::VertexAttribute> {
+        wgpu::vertex_attr_array![1 => Float32x3, 2 => Float32x2, 3 => Uint32, 4 => Float32, 5 => Uint32, 6 => Float32, 7 => Uint32, 8 => Float32, 9 => Float32, 10 => Float32x4]
+            .to_vec()
+    }
+
+    ///default set as large enough to contain 2_000 rects.
+    fn default_buffer() -> BufferData {
+        Self::with_capacity(2_000, 0)
+    }
+
+    fn with_capacity(
+        vertex_capacity: usize,
+        _index_capacity: usize,
+    ) -> BufferData {
+        let instance_arr: Vec<RectVertex> = iter::repeat(RectVertex::default())
+            .take(vertex_capacity)
+            .collect();
+
+        BufferData {
+            vertexs: bytemuck::cast_slice(&instance_arr).to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn stride() -> usize {
+        std::mem::size_of::<[f32; 16]>()
+    }
+}
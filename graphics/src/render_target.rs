@@ -0,0 +1,205 @@
+use crate::{Allocation, AscendingError, AtlasGroup, GpuRenderer, OtherError};
+use std::hash::Hash;
+
+/// An offscreen color target the renderer's pipelines can draw into, for
+/// dynamic thumbnails like character or map previews.
+///
+/// Render into it in its own pass with a [`crate::System`] sized to it,
+/// then copy it into an atlas with [`RenderTarget::snapshot_into`] to draw
+/// it as a regular image. It uses the surface format and MSAA of the
+/// renderer, since the pipelines are built for those.
+pub struct RenderTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    /// Multisampled target resolved into texture. None without MSAA.
+    msaa_view: Option<wgpu::TextureView>,
+    depth_view: wgpu::TextureView,
+    depth_clear: wgpu::LoadOp<f32>,
+}
+
+impl RenderTarget {
+    pub fn new(renderer: &GpuRenderer, width: u32, height: u32) -> Self {
+        let size = wgpu::Extent3d {
+            width: width.max(1),
+            height: height.max(1),
+            depth_or_array_layers: 1,
+        };
+        let format = renderer.surface_format();
+        let sample_count = renderer.sample_count();
+        let create = |label, sample_count, format, usage| {
+            renderer.device().create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size,
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+                view_formats: &[],
+            })
+        };
+        let texture = create(
+            "render target texture",
+            1,
+            format,
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        );
+        let msaa_view = (sample_count > 1).then(|| {
+            create(
+                "render target msaa texture",
+                sample_count,
+                format,
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+            )
+            .create_view(&wgpu::TextureViewDescriptor::default())
+        });
+        let depth = create(
+            "render target depth texture",
+            sample_count,
+            wgpu::TextureFormat::Depth32Float,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        );
+
+        Self {
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            texture,
+            msaa_view,
+            depth_view: depth
+                .create_view(&wgpu::TextureViewDescriptor::default()),
+            depth_clear: renderer.gpu_device().depth_clear(),
+        }
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// View of the resolved contents, for sampling it directly.
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.texture.width(), self.texture.height())
+    }
+
+    /// Starts a pass that clears the target to clear.
+    pub fn begin_pass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        clear: wgpu::Color,
+    ) -> wgpu::RenderPass<'a> {
+        self.pass(encoder, wgpu::LoadOp::Clear(clear))
+    }
+
+    /// Starts a pass that keeps what the target holds, for drawing over
+    /// part of it. Depth is still cleared.
+    pub fn begin_load_pass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+    ) -> wgpu::RenderPass<'a> {
+        self.pass(encoder, wgpu::LoadOp::Load)
+    }
+
+    fn pass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPass<'a> {
+        let (view, resolve_target) = match &self.msaa_view {
+            Some(msaa_view) => (msaa_view, Some(&self.view)),
+            None => (&self.view, None),
+        };
+
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("render target pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(
+                wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: self.depth_clear,
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                },
+            ),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+
+    /// Copies the contents into the atlas under key on the GPU. The first
+    /// snapshot of a key allocates it, later ones overwrite it in place so
+    /// images drawing the allocation show the new contents. Submit the
+    /// pass rendering into the target first.
+    ///
+    /// The atlas must share the target's format, ignoring srgb. No alpha
+    /// mask is made as the pixels never reach the CPU.
+    pub fn snapshot_into<U: Hash + Eq + Clone, Data: Copy + Default>(
+        &self,
+        renderer: &GpuRenderer,
+        atlas: &mut AtlasGroup<U, Data>,
+        key: U,
+        data: Data,
+    ) -> Result<Allocation<Data>, AscendingError> {
+        let format = self.texture.format();
+
+        if format.remove_srgb_suffix()
+            != atlas.atlas.format.remove_srgb_suffix()
+        {
+            return Err(AscendingError::Other(OtherError::new(&format!(
+                "render target is {format:?} but the atlas is {:?}",
+                atlas.atlas.format
+            ))));
+        }
+
+        let (width, height) = self.size();
+        let allocation = atlas
+            .atlas
+            .reserve(key, width, height, data, renderer)
+            .ok_or(AscendingError::AtlasFull)?;
+        let (x, y) = allocation.position();
+        let mut encoder = renderer.device().create_command_encoder(
+            &wgpu::CommandEncoderDescriptor {
+                label: Some("render target snapshot encoder"),
+            },
+        );
+
+        encoder.copy_texture_to_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyTexture {
+                texture: &atlas.atlas.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x,
+                    y,
+                    z: allocation.layer as u32,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        renderer.queue().submit(std::iter::once(encoder.finish()));
+
+        Ok(allocation)
+    }
+}
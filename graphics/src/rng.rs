@@ -0,0 +1,183 @@
+use crate::FxHashMap;
+
+const PCG_MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+
+/// Small PCG32 generator. The same seed gives the same numbers on every
+/// platform, so effects drawing from it replay exactly.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+    increment: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self::with_stream(seed, 0)
+    }
+
+    /// Generators with the same seed but different streams give unrelated
+    /// numbers.
+    pub fn with_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+
+        self.state = old
+            .wrapping_mul(PCG_MULTIPLIER)
+            .wrapping_add(self.increment);
+
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        ((self.next_u32() as u64) << 32) | self.next_u32() as u64
+    }
+
+    /// From 0.0 up to but not including 1.0.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// From min up to but not including max.
+    pub fn range_f32(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// Below bound, with every value equally likely. 0 if bound is 0.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        if bound == 0 {
+            return 0;
+        }
+
+        // Rerolls the few values that would favour the low numbers.
+        let threshold = bound.wrapping_neg() % bound;
+
+        loop {
+            let roll = self.next_u32();
+
+            if roll >= threshold {
+                return roll % bound;
+            }
+        }
+    }
+
+    /// From min up to but not including max.
+    pub fn range_i32(&mut self, min: i32, max: i32) -> i32 {
+        if max <= min {
+            return min;
+        }
+
+        min.wrapping_add(self.below(max.abs_diff(min)) as i32)
+    }
+
+    /// if a roll comes in under probability, from 0.0 to 1.0.
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        items.get(self.below(items.len() as u32) as usize)
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i as u32 + 1) as usize);
+        }
+    }
+
+    /// A new generator drawn from this one, like one per particle emitter.
+    pub fn fork(&mut self) -> Rng {
+        Rng::with_stream(self.next_u64(), self.next_u64())
+    }
+}
+
+/// The parts of the engine that draw random numbers. Each gets its own
+/// stream so drawing more in one doesn't change what the others get.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RngStream {
+    Particles,
+    TileVariants,
+    /// Light flicker seeds.
+    Lights,
+    CameraShake,
+    /// For apps and plugins.
+    Custom(u32),
+}
+
+impl RngStream {
+    fn id(self) -> u64 {
+        match self {
+            RngStream::Particles => 1,
+            RngStream::TileVariants => 2,
+            RngStream::Lights => 3,
+            RngStream::CameraShake => 4,
+            RngStream::Custom(id) => (1 << 32) | id as u64,
+        }
+    }
+}
+
+/// The engine's seeded randomness. Visual effects draw from it instead of
+/// an unseeded source, so a replay or golden image test with the same seed
+/// looks the same every run.
+#[derive(Clone, Debug, Default)]
+pub struct RngService {
+    seed: u64,
+    streams: FxHashMap<RngStream, Rng>,
+}
+
+impl RngService {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: FxHashMap::default(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restarts every stream from a new seed, like when a replay starts.
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.streams.clear();
+    }
+
+    /// The generator of a stream. Started from the seed the first time.
+    pub fn stream(&mut self, stream: RngStream) -> &mut Rng {
+        let seed = self.seed;
+
+        self.streams
+            .entry(stream)
+            .or_insert_with(|| Rng::with_stream(seed, stream.id()))
+    }
+}
+
+/// Integer hash of values, for picks made from positions instead of drawn
+/// in order. Stable across runs and platforms.
+pub fn hash_u32(values: &[u32], seed: u32) -> u32 {
+    let mut h = seed ^ 0x9E37_79B9;
+
+    for v in values {
+        h ^= v.wrapping_mul(0x85EB_CA6B);
+        h = h.rotate_left(13).wrapping_mul(0xC2B2_AE35);
+    }
+
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^ (h >> 16)
+}
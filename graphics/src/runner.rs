@@ -0,0 +1,554 @@
+use crate::{
+    AnimationClock, AscendingError, AssetStream, BackgroundSettings,
+    BackgroundState, GpuRenderer, LoadingScene, Plugin, PluginRegistry,
+    PluginSetup, Preloader, RngService, UiSounds, WindowActivity,
+};
+use input::{Bindings, FrameTime, InputHandler};
+use std::{
+    hash::Hash,
+    time::{Duration, Instant},
+};
+use winit::{
+    dpi::PhysicalSize,
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+};
+
+/// What an [`App`] gets to work with each call.
+pub struct AppContext<'a, ActionId, AxisId>
+where
+    ActionId: Clone + Eq + Hash + Send + Sync,
+    AxisId: Clone + Eq + Hash + Send + Sync,
+{
+    pub renderer: &'a mut GpuRenderer,
+    pub input: &'a InputHandler<ActionId, AxisId>,
+    pub frame_time: &'a FrameTime,
+    pub clock: &'a mut AnimationClock,
+    /// Seeded randomness for effects, so replays come out the same.
+    pub rng: &'a mut RngService,
+    /// Asset loaders and widgets added by plugins.
+    pub registry: &'a PluginRegistry,
+    /// Focus and occlusion, and what to do about them.
+    pub background: &'a mut BackgroundState,
+    /// The UI sound theme and the widget sounds waiting to be played.
+    pub ui_sounds: &'a mut UiSounds,
+    /// Loads files on worker threads. While it has loads left the
+    /// runner's loading scene is shown instead of the app.
+    pub assets: &'a mut AssetStream,
+    exit: &'a mut bool,
+}
+
+impl<'a, ActionId, AxisId> AppContext<'a, ActionId, AxisId>
+where
+    ActionId: Clone + Eq + Hash + Send + Sync,
+    AxisId: Clone + Eq + Hash + Send + Sync,
+{
+    /// Stops the runner after the current event.
+    pub fn exit(&mut self) {
+        *self.exit = true;
+    }
+}
+
+/// A game or tool driven by a [`Runner`]. Only update and draw are
+/// required.
+pub trait App<ActionId, AxisId>: 'static
+where
+    ActionId: Clone + Eq + Hash + Send + Sync,
+    AxisId: Clone + Eq + Hash + Send + Sync,
+{
+    /// Called once before the first event.
+    fn init(&mut self, _ctx: &mut AppContext<ActionId, AxisId>) {}
+
+    /// Called with every window event before the runner handles it.
+    fn on_event(
+        &mut self,
+        _ctx: &mut AppContext<ActionId, AxisId>,
+        _event: &Event<()>,
+    ) {
+    }
+
+    /// Called when the window gains or loses focus or gets covered, after
+    /// the clock was paused or resumed for it.
+    fn window_activity(
+        &mut self,
+        _ctx: &mut AppContext<ActionId, AxisId>,
+        _activity: WindowActivity,
+    ) {
+    }
+
+    /// Called when the window size changed. The renderer's targets are
+    /// already resized.
+    fn resize(
+        &mut self,
+        _ctx: &mut AppContext<ActionId, AxisId>,
+        _size: PhysicalSize<f32>,
+    ) {
+    }
+
+    /// Called when the loads queued on [`AppContext::assets`] have all
+    /// finished, before the first update after. Take the results with
+    /// [`AssetStream::take`] here.
+    fn loaded(&mut self, _ctx: &mut AppContext<ActionId, AxisId>) {}
+
+    /// Called once per frame with the clock's scaled delta seconds.
+    fn update(&mut self, ctx: &mut AppContext<ActionId, AxisId>, delta: f32);
+
+    /// Records the frame's render passes. The runner submits and presents.
+    fn draw(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    );
+
+    /// Called after the frame was presented.
+    fn end_frame(&mut self, _ctx: &mut AppContext<ActionId, AxisId>) {}
+}
+
+/// How the [`Runner`] spaces frames out.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum FramePacing {
+    /// Left to the present mode.
+    #[default]
+    PresentMode,
+    /// At most this many frames a second.
+    Fixed(f32),
+    /// One frame per refresh of the monitor the window is on.
+    Refresh,
+    /// One frame every other refresh, for a steady rate on machines that
+    /// can't keep up with the monitor.
+    HalfRefresh,
+}
+
+/// Owns the event loop plumbing every game otherwise rewrites: input,
+/// resizing, lost surfaces, timing and frame pacing.
+pub struct Runner<ActionId, AxisId>
+where
+    ActionId: Clone + Eq + Hash + Send + Sync,
+    AxisId: Clone + Eq + Hash + Send + Sync,
+{
+    renderer: GpuRenderer,
+    input: InputHandler<ActionId, AxisId>,
+    frame_time: FrameTime,
+    clock: AnimationClock,
+    pacing: FramePacing,
+    /// Of the monitor the window is on, in Hz.
+    refresh_rate: Option<f32>,
+    size: PhysicalSize<f32>,
+    plugins: Vec<Box<dyn Plugin<ActionId, AxisId>>>,
+    registry: PluginRegistry,
+    rng: RngService,
+    background: BackgroundState,
+    ui_sounds: UiSounds,
+    assets: AssetStream,
+    loading_scene: Option<Box<dyn LoadingScene>>,
+    preloader: Option<Preloader>,
+    /// if the assets were loading last frame.
+    was_loading: bool,
+    exit: bool,
+}
+
+impl<ActionId, AxisId> Runner<ActionId, AxisId>
+where
+    ActionId: Clone + Eq + Hash + Send + Sync + 'static,
+    AxisId: Clone + Eq + Hash + Send + Sync + 'static,
+{
+    /// The renderer must have a window.
+    pub fn new(
+        renderer: GpuRenderer,
+        bindings: Bindings<ActionId, AxisId>,
+    ) -> Self {
+        Self {
+            size: renderer.size(),
+            refresh_rate: renderer.refresh_rate(),
+            renderer,
+            input: InputHandler::new(bindings),
+            frame_time: FrameTime::new(),
+            clock: AnimationClock::new(),
+            pacing: FramePacing::PresentMode,
+            plugins: Vec::new(),
+            registry: PluginRegistry::new(),
+            rng: RngService::new(0),
+            background: BackgroundState::default(),
+            ui_sounds: UiSounds::default(),
+            assets: AssetStream::new(),
+            loading_scene: None,
+            preloader: None,
+            was_loading: false,
+            exit: false,
+        }
+    }
+
+    /// Restarts the rng streams from seed, like for a replay.
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng.reseed(seed);
+        self
+    }
+
+    /// Frame cap. None leaves the pacing to the present mode.
+    pub fn set_target_fps(&mut self, fps: Option<f32>) -> &mut Self {
+        let pacing = match fps.filter(|fps| *fps > 0.0) {
+            Some(fps) => FramePacing::Fixed(fps),
+            None => FramePacing::PresentMode,
+        };
+
+        self.set_frame_pacing(pacing)
+    }
+
+    /// The refresh modes also snap the [`FrameTime`] deltas to the
+    /// refresh interval and follow the window between monitors.
+    pub fn set_frame_pacing(&mut self, pacing: FramePacing) -> &mut Self {
+        self.pacing = pacing;
+        self.update_refresh_rate();
+        self
+    }
+
+    pub fn frame_pacing(&self) -> FramePacing {
+        self.pacing
+    }
+
+    /// Of the monitor the window is on, in Hz. None if the platform
+    /// doesn't report it, in which case the refresh modes don't cap.
+    pub fn refresh_rate(&self) -> Option<f32> {
+        self.refresh_rate
+    }
+
+    /// Frames the refresh modes show each frame for.
+    fn refresh_divisor(&self) -> Option<f32> {
+        match self.pacing {
+            FramePacing::Refresh => Some(1.0),
+            FramePacing::HalfRefresh => Some(2.0),
+            _ => None,
+        }
+    }
+
+    fn update_refresh_rate(&mut self) {
+        self.refresh_rate = self.renderer.refresh_rate();
+
+        let interval = self
+            .refresh_divisor()
+            .zip(self.refresh_rate)
+            .map(|(divisor, hz)| divisor / hz);
+
+        if interval != self.frame_time.frame_interval() {
+            self.frame_time.set_frame_interval(interval);
+        }
+    }
+
+    /// Frame cap of the pacing, before the background's.
+    fn target_fps(&self) -> Option<f32> {
+        match self.pacing {
+            FramePacing::PresentMode => None,
+            FramePacing::Fixed(fps) => Some(fps),
+            FramePacing::Refresh | FramePacing::HalfRefresh => self
+                .refresh_divisor()
+                .zip(self.refresh_rate)
+                .map(|(divisor, hz)| hz / divisor),
+        }
+    }
+
+    /// What to do while the window is unfocused or covered.
+    pub fn set_background_settings(
+        &mut self,
+        settings: BackgroundSettings,
+    ) -> &mut Self {
+        self.background.set_settings(settings);
+        self
+    }
+
+    /// Sets the plugin up right away so what it registers is there for
+    /// the app's init.
+    pub fn add_plugin<P>(&mut self, mut plugin: P) -> &mut Self
+    where
+        P: Plugin<ActionId, AxisId>,
+    {
+        plugin.setup(&mut PluginSetup {
+            renderer: &mut self.renderer,
+            registry: &mut self.registry,
+        });
+        self.plugins.push(Box::new(plugin));
+        self
+    }
+
+    pub fn has_plugin(&self, name: &str) -> bool {
+        self.plugins.iter().any(|plugin| plugin.name() == name)
+    }
+
+    pub fn registry(&self) -> &PluginRegistry {
+        &self.registry
+    }
+
+    pub fn renderer(&self) -> &GpuRenderer {
+        &self.renderer
+    }
+
+    pub fn renderer_mut(&mut self) -> &mut GpuRenderer {
+        &mut self.renderer
+    }
+
+    pub fn frame_time_mut(&mut self) -> &mut FrameTime {
+        &mut self.frame_time
+    }
+
+    pub fn clock_mut(&mut self) -> &mut AnimationClock {
+        &mut self.clock
+    }
+
+    pub fn rng_mut(&mut self) -> &mut RngService {
+        &mut self.rng
+    }
+
+    pub fn ui_sounds_mut(&mut self) -> &mut UiSounds {
+        &mut self.ui_sounds
+    }
+
+    pub fn assets_mut(&mut self) -> &mut AssetStream {
+        &mut self.assets
+    }
+
+    /// Shown instead of the app while the assets load, like a
+    /// [`crate::LoadingScreen`]. Without one the app runs as the loads
+    /// come in.
+    pub fn set_loading_scene<L: LoadingScene>(
+        &mut self,
+        scene: L,
+    ) -> &mut Self {
+        self.loading_scene = Some(Box::new(scene));
+        self
+    }
+
+    /// Runs preloader on the runner's assets, behind the loading scene if
+    /// there is one. [`App::loaded`] is called once it and every other
+    /// load finished.
+    pub fn preload(&mut self, mut preloader: Preloader) -> &mut Self {
+        preloader.start(&self.renderer, &mut self.assets);
+        self.preloader = Some(preloader);
+        self
+    }
+
+    /// Frees the loading scene once nothing else will be loaded behind
+    /// it.
+    pub fn clear_loading_scene(&mut self) {
+        self.loading_scene = None;
+    }
+
+    fn context(&mut self) -> AppContext<'_, ActionId, AxisId> {
+        AppContext {
+            renderer: &mut self.renderer,
+            input: &self.input,
+            frame_time: &self.frame_time,
+            clock: &mut self.clock,
+            rng: &mut self.rng,
+            registry: &self.registry,
+            background: &mut self.background,
+            ui_sounds: &mut self.ui_sounds,
+            assets: &mut self.assets,
+            exit: &mut self.exit,
+        }
+    }
+
+    /// Runs the app until it exits or the window is closed.
+    pub fn run<A>(mut self, event_loop: EventLoop<()>, mut app: A) -> !
+    where
+        A: App<ActionId, AxisId>,
+    {
+        app.init(&mut self.context());
+
+        #[allow(deprecated)]
+        event_loop.run(move |event, _, control_flow| {
+            self.handle_event(&mut app, event);
+
+            if self.exit {
+                *control_flow = ControlFlow::Exit;
+            }
+        })
+    }
+
+    fn handle_activity<A>(&mut self, app: &mut A, activity: WindowActivity)
+    where
+        A: App<ActionId, AxisId>,
+    {
+        self.background.handle_activity(activity);
+        self.background.apply(&mut self.clock);
+
+        let mut plugins = std::mem::take(&mut self.plugins);
+
+        for plugin in &mut plugins {
+            plugin.window_activity(&mut self.context(), activity);
+        }
+
+        self.plugins = plugins;
+        app.window_activity(&mut self.context(), activity);
+    }
+
+    fn handle_event<A>(&mut self, app: &mut A, event: Event<()>)
+    where
+        A: App<ActionId, AxisId>,
+    {
+        app.on_event(&mut self.context(), &event);
+
+        if let Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            window_id,
+        } = &event
+        {
+            if *window_id == self.renderer.window().id() {
+                self.exit = true;
+                return;
+            }
+        }
+
+        self.input.update(self.renderer.window(), &event, 1.0);
+
+        if let Event::WindowEvent { event, window_id } = &event {
+            if *window_id == self.renderer.window().id() {
+                if let Some(activity) = WindowActivity::from_window_event(event)
+                {
+                    self.handle_activity(app, activity);
+                }
+
+                // The window may be on another monitor now.
+                if matches!(
+                    event,
+                    WindowEvent::Moved(_)
+                        | WindowEvent::ScaleFactorChanged { .. }
+                ) {
+                    self.update_refresh_rate();
+                }
+            }
+        }
+
+        // Minimized windows have nothing to render to.
+        let inner_size = self.renderer.window().inner_size();
+
+        if inner_size.width == 0 || inner_size.height == 0 {
+            return;
+        }
+
+        // Lost surfaces are reconfigured by the renderer, other errors
+        // skip the frame unless the device is out of memory.
+        match self.renderer.update(&event) {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(AscendingError::Surface(wgpu::SurfaceError::OutOfMemory)) => {
+                log::error!("surface out of memory, exiting");
+                self.exit = true;
+                return;
+            }
+            Err(e) => {
+                log::warn!("skipping frame: {e}");
+                return;
+            }
+        }
+
+        let frame_start = Instant::now();
+
+        // The app may have overridden the background state.
+        self.background.apply(&mut self.clock);
+
+        if self.size != self.renderer.size() {
+            self.size = self.renderer.size();
+            self.renderer.update_depth_texture();
+
+            let size = self.size;
+            app.resize(&mut self.context(), size);
+        }
+
+        self.clock.update(&self.frame_time);
+        self.assets.poll();
+
+        if let Some(preloader) = &mut self.preloader {
+            if preloader.update(&mut self.renderer, &mut self.assets) {
+                self.preloader = None;
+            }
+        }
+
+        let loading = self.assets.is_loading();
+
+        if self.was_loading && !loading {
+            app.loaded(&mut self.context());
+        }
+
+        self.was_loading = loading;
+
+        // Shown instead of the app and plugins until the loads finish.
+        let mut loading_scene = match loading {
+            true => self.loading_scene.take(),
+            false => None,
+        };
+        let delta = self.clock.delta_seconds();
+        // Taken out so the plugins can get a context of the runner.
+        let mut plugins = std::mem::take(&mut self.plugins);
+
+        if let Some(scene) = &mut loading_scene {
+            scene.update(
+                &mut self.renderer,
+                &self.clock,
+                self.assets.progress(),
+            );
+        } else {
+            for plugin in &mut plugins {
+                plugin.pre_update(&mut self.context(), delta);
+            }
+
+            app.update(&mut self.context(), delta);
+
+            for plugin in &mut plugins {
+                plugin.post_update(&mut self.context(), delta);
+            }
+
+            for plugin in &mut plugins {
+                plugin.extract(&mut self.renderer);
+            }
+        }
+
+        let mut encoder = self.renderer.device().create_command_encoder(
+            &wgpu::CommandEncoderDescriptor {
+                label: Some("command encoder"),
+            },
+        );
+
+        if let Some(scene) = &mut loading_scene {
+            scene.draw(&self.renderer, &mut encoder);
+        } else {
+            app.draw(&self.renderer, &mut encoder);
+
+            for plugin in &mut plugins {
+                plugin.render(&self.renderer, &mut encoder);
+            }
+        }
+
+        self.plugins = plugins;
+
+        if loading_scene.is_some() {
+            self.loading_scene = loading_scene;
+        }
+        self.renderer
+            .queue()
+            .submit(std::iter::once(encoder.finish()));
+
+        self.input.end_frame();
+        self.frame_time.update();
+
+        if let Err(e) = self.renderer.present() {
+            log::warn!("{e}");
+        }
+
+        app.end_frame(&mut self.context());
+
+        if let Some(fps) = self.background.target_fps(self.target_fps()) {
+            let mut frame = Duration::from_secs_f32(1.0 / fps);
+
+            // The present waits out the rest, lining it up with the
+            // refresh instead of landing just after one.
+            if self.refresh_divisor().is_some() {
+                frame = frame.saturating_sub(Duration::from_millis(1));
+            }
+
+            let elapsed = frame_start.elapsed();
+
+            if elapsed < frame {
+                std::thread::sleep(frame - elapsed);
+            }
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod commands;
+mod host;
+
+pub use commands::*;
+pub use host::*;
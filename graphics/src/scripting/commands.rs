@@ -0,0 +1,156 @@
+use crate::{Color, Vec2, Vec3};
+use rhai::Dynamic;
+
+/// What a script asked the engine to do. Scripts never touch the renderer
+/// or the app's objects, they queue these by name for the app to run
+/// between frames, see [`crate::ScriptHost::take_commands`].
+#[derive(Clone, Debug)]
+pub enum ScriptCommand {
+    /// Build a widget of kind, like "button" or "label", under name.
+    CreateWidget {
+        kind: String,
+        name: String,
+        props: rhai::Map,
+    },
+    SetWidget {
+        name: String,
+        props: rhai::Map,
+    },
+    RemoveWidget {
+        name: String,
+    },
+    MoveSprite {
+        name: String,
+        pos: Vec3,
+    },
+    SetSpriteColor {
+        name: String,
+        color: Color,
+    },
+    /// Starts or stops a sprite's frame animation.
+    SetSpriteAnimate {
+        name: String,
+        animate: bool,
+    },
+    MoveLight {
+        name: String,
+        pos: Vec2,
+    },
+    SetLightColor {
+        name: String,
+        color: Color,
+    },
+    SetLightRadius {
+        name: String,
+        radius: f32,
+    },
+    /// pos is x, y and map layer.
+    SetTile {
+        map: String,
+        pos: (u32, u32, u32),
+        texture_id: u32,
+        texture_layer: u8,
+        color: Color,
+    },
+    /// A custom event for the app.
+    Emit {
+        event: String,
+        data: Dynamic,
+    },
+}
+
+impl ScriptCommand {
+    /// Name of the widget, sprite, light or map it's for.
+    pub fn target(&self) -> Option<&str> {
+        match self {
+            ScriptCommand::CreateWidget { name, .. }
+            | ScriptCommand::SetWidget { name, .. }
+            | ScriptCommand::RemoveWidget { name }
+            | ScriptCommand::MoveSprite { name, .. }
+            | ScriptCommand::SetSpriteColor { name, .. }
+            | ScriptCommand::SetSpriteAnimate { name, .. }
+            | ScriptCommand::MoveLight { name, .. }
+            | ScriptCommand::SetLightColor { name, .. }
+            | ScriptCommand::SetLightRadius { name, .. } => Some(name),
+            ScriptCommand::SetTile { map, .. } => Some(map),
+            ScriptCommand::Emit { .. } => None,
+        }
+    }
+
+    /// Runs the sprite commands on image. Returns false for the rest.
+    #[cfg(feature = "sprites")]
+    pub fn apply_image(&self, image: &mut crate::Image) -> bool {
+        match self {
+            ScriptCommand::MoveSprite { pos, .. } => image.pos = *pos,
+            ScriptCommand::SetSpriteColor { color, .. } => image.color = *color,
+            ScriptCommand::SetSpriteAnimate { animate, .. } => {
+                image.animate = *animate
+            }
+            _ => return false,
+        }
+
+        image.changed = true;
+        true
+    }
+
+    /// Runs the light commands on light, get it with
+    /// [`crate::Lights::get_mut_area_light`] so the change is uploaded.
+    /// Returns false for the rest.
+    #[cfg(feature = "lights")]
+    pub fn apply_area_light(&self, light: &mut crate::AreaLight) -> bool {
+        match self {
+            ScriptCommand::MoveLight { pos, .. } => light.pos = *pos,
+            ScriptCommand::SetLightColor { color, .. } => light.color = *color,
+            ScriptCommand::SetLightRadius { radius, .. } => {
+                light.max_distance = *radius
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Like [`ScriptCommand::apply_area_light`], with the radius setting
+    /// the light's reach.
+    #[cfg(feature = "lights")]
+    pub fn apply_directional_light(
+        &self,
+        light: &mut crate::DirectionalLight,
+    ) -> bool {
+        match self {
+            ScriptCommand::MoveLight { pos, .. } => light.pos = *pos,
+            ScriptCommand::SetLightColor { color, .. } => light.color = *color,
+            ScriptCommand::SetLightRadius { radius, .. } => {
+                light.max_distance = *radius
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Runs tile commands on map. Returns false for the rest.
+    #[cfg(feature = "maps")]
+    pub fn apply_map(&self, map: &mut crate::Map) -> bool {
+        let ScriptCommand::SetTile {
+            pos,
+            texture_id,
+            texture_layer,
+            color,
+            ..
+        } = self
+        else {
+            return false;
+        };
+
+        map.set_tile(
+            *pos,
+            crate::TileData {
+                texture_id: *texture_id,
+                texture_layer: *texture_layer,
+                color: *color,
+            },
+        );
+        true
+    }
+}
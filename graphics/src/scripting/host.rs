@@ -0,0 +1,474 @@
+use crate::{AscendingError, Color, ScriptCommand, Vec2, Vec3};
+use rhai::{
+    CallFnOptions, Dynamic, Engine, EvalAltResult, FnPtr, Scope, AST, FLOAT,
+    INT,
+};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTime,
+};
+
+/// What the bound functions share with the host while a script runs.
+#[derive(Default)]
+struct ScriptShared {
+    commands: Vec<ScriptCommand>,
+    /// Subscriptions made by the running script, as event and handler.
+    subscribed: Vec<(String, String)>,
+    unsubscribed: Vec<String>,
+}
+
+struct Script {
+    name: String,
+    /// Set for scripts loaded from a file, which are reloaded on change.
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    ast: AST,
+    scope: Scope<'static>,
+    /// Event and the script function it calls.
+    subscriptions: Vec<(String, String)>,
+}
+
+/// Runs [rhai](https://rhai.rs) scripts for UI flows and scene logic.
+///
+/// Scripts queue [`ScriptCommand`]s through the bound functions below and
+/// the app runs them with [`ScriptHost::take_commands`], so a script can't
+/// reach past what the app lets it change. The engine is limited in
+/// operations, call depth and sizes so a bad script errors instead of
+/// hanging the frame.
+///
+/// | Function | Queues |
+/// | --- | --- |
+/// | `create_widget(kind, name[, props])` | [`ScriptCommand::CreateWidget`] |
+/// | `set_widget(name, props)` | [`ScriptCommand::SetWidget`] |
+/// | `remove_widget(name)` | [`ScriptCommand::RemoveWidget`] |
+/// | `move_sprite(name, x, y[, z])` | [`ScriptCommand::MoveSprite`] |
+/// | `set_sprite_color(name, r, g, b, a)` | [`ScriptCommand::SetSpriteColor`] |
+/// | `set_sprite_animate(name, on)` | [`ScriptCommand::SetSpriteAnimate`] |
+/// | `move_light(name, x, y)` | [`ScriptCommand::MoveLight`] |
+/// | `set_light_color(name, r, g, b, a)` | [`ScriptCommand::SetLightColor`] |
+/// | `set_light_radius(name, radius)` | [`ScriptCommand::SetLightRadius`] |
+/// | `set_tile(map, x, y, layer, texture_id, texture_layer)` | [`ScriptCommand::SetTile`] |
+/// | `emit(event[, data])` | [`ScriptCommand::Emit`] |
+///
+/// `subscribe(event, handler)` calls the script function handler, by name
+/// or `Fn("name")`, with one argument whenever the app passes the event to
+/// [`ScriptHost::dispatch`]. `print` and `debug` go to the log.
+pub struct ScriptHost {
+    engine: Engine,
+    shared: Rc<RefCell<ScriptShared>>,
+    scripts: Vec<Script>,
+}
+
+impl ScriptHost {
+    pub fn new() -> Self {
+        let shared = Rc::new(RefCell::new(ScriptShared::default()));
+        let mut engine = Engine::new();
+
+        engine
+            .set_max_operations(1_000_000)
+            .set_max_call_levels(64)
+            .set_max_string_size(1 << 20)
+            .set_max_array_size(100_000)
+            .set_max_map_size(10_000)
+            .on_print(|text| log::info!("{text}"))
+            .on_debug(|text, source, pos| {
+                log::debug!("{} {pos}: {text}", source.unwrap_or("script"))
+            });
+
+        register_api(&mut engine, &shared);
+
+        Self {
+            engine,
+            shared,
+            scripts: Vec::new(),
+        }
+    }
+
+    /// For binding more functions or changing the limits.
+    pub fn engine_mut(&mut self) -> &mut Engine {
+        &mut self.engine
+    }
+
+    /// Loads and runs a script file, which [`ScriptHost::reload_changed`]
+    /// reloads when it's saved. Named by its path.
+    pub fn load(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), AscendingError> {
+        let path = path.as_ref();
+        let modified = std::fs::metadata(path)?.modified().ok();
+        let source = std::fs::read_to_string(path)?;
+
+        self.insert(
+            path.display().to_string(),
+            Some(path.to_path_buf()),
+            modified,
+            &source,
+        )
+    }
+
+    /// Compiles and runs source as the script name, replacing one loaded
+    /// under the same name.
+    pub fn load_str(
+        &mut self,
+        name: &str,
+        source: &str,
+    ) -> Result<(), AscendingError> {
+        self.insert(name.to_owned(), None, None, source)
+    }
+
+    /// Drops a script and its subscriptions.
+    pub fn unload(&mut self, name: &str) -> bool {
+        let len = self.scripts.len();
+
+        self.scripts.retain(|script| script.name != name);
+        self.scripts.len() != len
+    }
+
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.scripts.iter().any(|script| script.name == name)
+    }
+
+    /// Reloads the script files that changed since they were loaded,
+    /// returning each one's name or the error it failed with. A script
+    /// that fails to compile keeps its old version until it's saved again.
+    pub fn reload_changed(&mut self) -> Vec<Result<String, AscendingError>> {
+        let mut changed = Vec::new();
+
+        for script in &mut self.scripts {
+            let Some(path) = &script.path else {
+                continue;
+            };
+            let modified = std::fs::metadata(path)
+                .and_then(|meta| meta.modified())
+                .ok();
+
+            if modified.is_some() && modified != script.modified {
+                script.modified = modified;
+                changed.push((script.name.clone(), path.clone(), modified));
+            }
+        }
+
+        changed
+            .into_iter()
+            .map(|(name, path, modified)| {
+                let source = std::fs::read_to_string(&path)?;
+
+                self.insert(name.clone(), Some(path), modified, &source)?;
+                Ok(name)
+            })
+            .collect()
+    }
+
+    /// if any script has a handler for event.
+    pub fn is_subscribed(&self, event: &str) -> bool {
+        self.scripts
+            .iter()
+            .flat_map(|script| &script.subscriptions)
+            .any(|(name, _)| name == event)
+    }
+
+    /// Calls every handler subscribed to event with data, like a widget's
+    /// events from the router or a [`crate::TimelineEvent`]. Every handler
+    /// runs even if one fails, returning the first error.
+    pub fn dispatch(
+        &mut self,
+        event: &str,
+        data: impl Into<Dynamic>,
+    ) -> Result<(), AscendingError> {
+        let data = data.into();
+        let mut result = Ok(());
+
+        for index in 0..self.scripts.len() {
+            let handlers: Vec<String> = self.scripts[index]
+                .subscriptions
+                .iter()
+                .filter(|(name, _)| name == event)
+                .map(|(_, handler)| handler.clone())
+                .collect();
+
+            for handler in handlers {
+                let script = &mut self.scripts[index];
+                let called = self.engine.call_fn_with_options::<Dynamic>(
+                    CallFnOptions::new().eval_ast(false).rewind_scope(false),
+                    &mut script.scope,
+                    &script.ast,
+                    &handler,
+                    (data.clone(),),
+                );
+                let error =
+                    called.err().map(|error| script_error(script, &error));
+
+                self.collect_subscriptions(index);
+
+                if let (Some(error), Ok(())) = (error, &result) {
+                    result = Err(error);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Takes what the scripts queued since the last call.
+    pub fn take_commands(&mut self) -> Vec<ScriptCommand> {
+        std::mem::take(&mut self.shared.borrow_mut().commands)
+    }
+
+    fn insert(
+        &mut self,
+        name: String,
+        path: Option<PathBuf>,
+        modified: Option<SystemTime>,
+        source: &str,
+    ) -> Result<(), AscendingError> {
+        let mut ast = self.engine.compile(source).map_err(|error| {
+            AscendingError::Script {
+                name: name.clone(),
+                message: error.to_string(),
+            }
+        })?;
+
+        ast.set_source(name.clone());
+
+        let mut script = Script {
+            name,
+            path,
+            modified,
+            ast,
+            scope: Scope::new(),
+            subscriptions: Vec::new(),
+        };
+        let ran = self
+            .engine
+            .run_ast_with_scope(&mut script.scope, &script.ast);
+        let index =
+            match self.scripts.iter().position(|s| s.name == script.name) {
+                Some(index) => {
+                    self.scripts[index] = script;
+                    index
+                }
+                None => {
+                    self.scripts.push(script);
+                    self.scripts.len() - 1
+                }
+            };
+
+        self.collect_subscriptions(index);
+        ran.map_err(|error| script_error(&self.scripts[index], &error))
+    }
+
+    /// Moves subscriptions made while the script ran onto it.
+    fn collect_subscriptions(&mut self, index: usize) {
+        let mut shared = self.shared.borrow_mut();
+        let script = &mut self.scripts[index];
+
+        for event in shared.unsubscribed.drain(..) {
+            script.subscriptions.retain(|(name, _)| *name != event);
+        }
+
+        for subscription in shared.subscribed.drain(..) {
+            if !script.subscriptions.contains(&subscription) {
+                script.subscriptions.push(subscription);
+            }
+        }
+    }
+}
+
+impl Default for ScriptHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn script_error(script: &Script, error: &EvalAltResult) -> AscendingError {
+    AscendingError::Script {
+        name: script.name.clone(),
+        message: error.to_string(),
+    }
+}
+
+fn color(r: INT, g: INT, b: INT, a: INT) -> Color {
+    let channel = |c: INT| c.clamp(0, 255) as u8;
+
+    Color::rgba(channel(r), channel(g), channel(b), channel(a))
+}
+
+/// Binds the functions listed on [`ScriptHost`].
+fn register_api(engine: &mut Engine, shared: &Rc<RefCell<ScriptShared>>) {
+    let queue = |shared: &Rc<RefCell<ScriptShared>>| {
+        let shared = shared.clone();
+
+        move |command| shared.borrow_mut().commands.push(command)
+    };
+
+    let push = queue(shared);
+    engine.register_fn("create_widget", move |kind: &str, name: &str| {
+        push(ScriptCommand::CreateWidget {
+            kind: kind.to_owned(),
+            name: name.to_owned(),
+            props: rhai::Map::new(),
+        })
+    });
+    let push = queue(shared);
+    engine.register_fn(
+        "create_widget",
+        move |kind: &str, name: &str, props: rhai::Map| {
+            push(ScriptCommand::CreateWidget {
+                kind: kind.to_owned(),
+                name: name.to_owned(),
+                props,
+            })
+        },
+    );
+    let push = queue(shared);
+    engine.register_fn("set_widget", move |name: &str, props: rhai::Map| {
+        push(ScriptCommand::SetWidget {
+            name: name.to_owned(),
+            props,
+        })
+    });
+    let push = queue(shared);
+    engine.register_fn("remove_widget", move |name: &str| {
+        push(ScriptCommand::RemoveWidget {
+            name: name.to_owned(),
+        })
+    });
+
+    let push = queue(shared);
+    engine.register_fn("move_sprite", move |name: &str, x: FLOAT, y: FLOAT| {
+        push(ScriptCommand::MoveSprite {
+            name: name.to_owned(),
+            pos: Vec3::new(x as f32, y as f32, 0.0),
+        })
+    });
+    let push = queue(shared);
+    engine.register_fn(
+        "move_sprite",
+        move |name: &str, x: FLOAT, y: FLOAT, z: FLOAT| {
+            push(ScriptCommand::MoveSprite {
+                name: name.to_owned(),
+                pos: Vec3::new(x as f32, y as f32, z as f32),
+            })
+        },
+    );
+    let push = queue(shared);
+    engine.register_fn(
+        "set_sprite_color",
+        move |name: &str, r: INT, g: INT, b: INT, a: INT| {
+            push(ScriptCommand::SetSpriteColor {
+                name: name.to_owned(),
+                color: color(r, g, b, a),
+            })
+        },
+    );
+    let push = queue(shared);
+    engine.register_fn(
+        "set_sprite_animate",
+        move |name: &str, animate: bool| {
+            push(ScriptCommand::SetSpriteAnimate {
+                name: name.to_owned(),
+                animate,
+            })
+        },
+    );
+
+    let push = queue(shared);
+    engine.register_fn("move_light", move |name: &str, x: FLOAT, y: FLOAT| {
+        push(ScriptCommand::MoveLight {
+            name: name.to_owned(),
+            pos: Vec2::new(x as f32, y as f32),
+        })
+    });
+    let push = queue(shared);
+    engine.register_fn(
+        "set_light_color",
+        move |name: &str, r: INT, g: INT, b: INT, a: INT| {
+            push(ScriptCommand::SetLightColor {
+                name: name.to_owned(),
+                color: color(r, g, b, a),
+            })
+        },
+    );
+    let push = queue(shared);
+    engine.register_fn("set_light_radius", move |name: &str, radius: FLOAT| {
+        push(ScriptCommand::SetLightRadius {
+            name: name.to_owned(),
+            radius: radius as f32,
+        })
+    });
+
+    let push = queue(shared);
+    engine.register_fn(
+        "set_tile",
+        move |map: &str,
+              x: INT,
+              y: INT,
+              layer: INT,
+              texture_id: INT,
+              texture_layer: INT|
+              -> Result<(), Box<EvalAltResult>> {
+            let (Ok(x), Ok(y), Ok(layer), Ok(texture_id), Ok(texture_layer)) = (
+                u32::try_from(x),
+                u32::try_from(y),
+                u32::try_from(layer),
+                u32::try_from(texture_id),
+                u8::try_from(texture_layer),
+            ) else {
+                return Err("set_tile arguments out of range".into());
+            };
+
+            push(ScriptCommand::SetTile {
+                map: map.to_owned(),
+                pos: (x, y, layer),
+                texture_id,
+                texture_layer,
+                color: Color::rgba(255, 255, 255, 255),
+            });
+            Ok(())
+        },
+    );
+
+    let push = queue(shared);
+    engine.register_fn("emit", move |event: &str| {
+        push(ScriptCommand::Emit {
+            event: event.to_owned(),
+            data: Dynamic::UNIT,
+        })
+    });
+    let push = queue(shared);
+    engine.register_fn("emit", move |event: &str, data: Dynamic| {
+        push(ScriptCommand::Emit {
+            event: event.to_owned(),
+            data,
+        })
+    });
+
+    let subs = shared.clone();
+    engine.register_fn("subscribe", move |event: &str, handler: &str| {
+        subs.borrow_mut()
+            .subscribed
+            .push((event.to_owned(), handler.to_owned()));
+    });
+    let subs = shared.clone();
+    engine.register_fn(
+        "subscribe",
+        move |event: &str, handler: FnPtr| -> Result<(), Box<EvalAltResult>> {
+            // curried values live outside the script's functions.
+            if handler.is_curried() {
+                return Err("subscribe handlers can't capture variables".into());
+            }
+
+            subs.borrow_mut()
+                .subscribed
+                .push((event.to_owned(), handler.fn_name().to_owned()));
+            Ok(())
+        },
+    );
+    let subs = shared.clone();
+    engine.register_fn("unsubscribe", move |event: &str| {
+        subs.borrow_mut().unsubscribed.push(event.to_owned());
+    });
+}
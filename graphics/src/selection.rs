@@ -0,0 +1,9 @@
+pub(crate) mod pipeline;
+mod render;
+mod selection;
+pub(crate) mod vertex;
+
+pub use self::selection::*;
+pub(crate) use pipeline::*;
+pub use render::*;
+pub(crate) use vertex::*;
@@ -0,0 +1,162 @@
+use crate::{
+    AscendingError, AtlasGroup, Color, DrawOrder, GpuRenderer, Image, Index,
+    InstanceBuffer, OrderedIndex, Rect, RenderCommands, Selection,
+    SelectionKind, SelectionRenderPipeline, SelectionStyle, SelectionVertex,
+    StaticBufferObject, Vec3,
+};
+
+/// Draws outlines or tints over the objects in a [`Selection`]. Objects are
+/// added every frame, only the selected or hovered ones get drawn.
+pub struct SelectionRenderer {
+    pub buffer: InstanceBuffer<SelectionVertex>,
+    pub style: SelectionStyle,
+    instances: Vec<SelectionVertex>,
+    store_id: Index,
+}
+
+impl SelectionRenderer {
+    pub fn new(renderer: &mut GpuRenderer) -> Result<Self, AscendingError> {
+        Ok(Self {
+            buffer: InstanceBuffer::new(renderer.gpu_device()),
+            style: SelectionStyle::default(),
+            instances: Vec::new(),
+            store_id: renderer.new_buffer(),
+        })
+    }
+
+    /// Selected wins over hovered.
+    fn color(&self, id: usize, selection: &Selection) -> Option<Color> {
+        if selection.is_selected(id) {
+            Some(self.style.selected_color)
+        } else if selection.is_hovered(id) {
+            Some(self.style.hover_color)
+        } else {
+            None
+        }
+    }
+
+    fn kind(&self) -> u32 {
+        match self.style.kind {
+            SelectionKind::Outline => 0,
+            SelectionKind::Tint => 1,
+        }
+    }
+
+    /// Outlines follow the image's alpha. Animated images use their first
+    /// frame.
+    pub fn add_image(
+        &mut self,
+        id: usize,
+        image: &Image,
+        selection: &Selection,
+    ) {
+        let color = match self.color(id, selection) {
+            Some(color) => color,
+            None => return,
+        };
+
+        let allocation = match &image.texture {
+            Some(allocation) => allocation,
+            None => return,
+        };
+
+        let (u, v, width, height) = allocation.rect();
+
+        self.instances.push(SelectionVertex {
+            position: image.pos.to_array(),
+            hw: image.hw.to_array(),
+            tex_data: [
+                image.uv.x + u as f32,
+                image.uv.y + v as f32,
+                image.uv.z.min(width as f32),
+                image.uv.w.min(height as f32),
+            ],
+            color: color.0,
+            width: self.style.width,
+            layer: allocation.layer as i32,
+            use_camera: u32::from(image.use_camera),
+            kind: self.kind(),
+            pulse_speed: self.style.pulse_speed,
+        });
+    }
+
+    /// Rects are treated as solid quads.
+    pub fn add_rect(&mut self, id: usize, rect: &Rect, selection: &Selection) {
+        let color = match self.color(id, selection) {
+            Some(color) => color,
+            None => return,
+        };
+
+        self.instances.push(SelectionVertex {
+            position: rect.position.to_array(),
+            hw: rect.size.to_array(),
+            tex_data: [0.0; 4],
+            color: color.0,
+            width: self.style.width,
+            layer: -1,
+            use_camera: u32::from(rect.use_camera),
+            kind: self.kind(),
+            pulse_speed: self.style.pulse_speed,
+        });
+    }
+
+    /// Uploads this frame's selections and clears them for the next.
+    pub fn finalize(&mut self, renderer: &mut GpuRenderer) {
+        if !self.instances.is_empty() {
+            if let Some(store) = renderer.get_buffer_mut(&self.store_id) {
+                store.store = bytemuck::cast_slice(&self.instances).to_vec();
+                store.changed = true;
+            }
+
+            let order = DrawOrder::new(true, &Vec3::ZERO, 0);
+
+            self.buffer.add_buffer_store(
+                renderer,
+                OrderedIndex::new(order, self.store_id, 0),
+            );
+            self.instances.clear();
+        }
+
+        self.buffer.finalize(renderer);
+    }
+}
+
+pub trait RenderSelection<'a, 'b>
+where
+    'b: 'a,
+{
+    /// atlas must be the one the selected images were uploaded to.
+    fn render_selection(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b SelectionRenderer,
+        atlas: &'b AtlasGroup,
+    );
+}
+
+impl<'a, 'b, P> RenderSelection<'a, 'b> for P
+where
+    'b: 'a,
+    P: RenderCommands<'a>,
+{
+    fn render_selection(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b SelectionRenderer,
+        atlas: &'b AtlasGroup,
+    ) {
+        if buffer.buffer.count() > 0 {
+            self.set_bind_group(1, &atlas.texture.bind_group, &[]);
+            self.set_vertex_buffer(1, buffer.buffer.instances(None));
+            self.set_pipeline(
+                renderer.get_pipelines(SelectionRenderPipeline).unwrap(),
+            );
+
+            self.draw_indexed(
+                0..StaticBufferObject::index_count(),
+                0,
+                0..buffer.buffer.count(),
+            );
+        }
+    }
+}
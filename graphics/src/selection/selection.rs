@@ -0,0 +1,91 @@
+use crate::Color;
+use std::collections::HashSet;
+
+/// Which objects are selected or hovered. Ids are chosen by the
+/// application and passed along with the objects to the
+/// [`crate::SelectionRenderer`].
+#[derive(Clone, Debug, Default)]
+pub struct Selection {
+    selected: HashSet<usize>,
+    hovered: Option<usize>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn select(&mut self, id: usize) {
+        self.selected.insert(id);
+    }
+
+    pub fn deselect(&mut self, id: usize) {
+        self.selected.remove(&id);
+    }
+
+    pub fn toggle(&mut self, id: usize) {
+        if !self.selected.remove(&id) {
+            self.selected.insert(id);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.selected.clear();
+    }
+
+    pub fn set_hovered(&mut self, hovered: Option<usize>) {
+        self.hovered = hovered;
+    }
+
+    pub fn hovered(&self) -> Option<usize> {
+        self.hovered
+    }
+
+    pub fn is_selected(&self, id: usize) -> bool {
+        self.selected.contains(&id)
+    }
+
+    pub fn is_hovered(&self, id: usize) -> bool {
+        self.hovered == Some(id)
+    }
+
+    pub fn selected(&self) -> impl Iterator<Item = &usize> {
+        self.selected.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selected.is_empty() && self.hovered.is_none()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SelectionKind {
+    /// A line around the object's shape.
+    Outline,
+    /// The object's shape filled with the color.
+    Tint,
+}
+
+/// How selected and hovered objects are drawn.
+#[derive(Copy, Clone, Debug)]
+pub struct SelectionStyle {
+    pub kind: SelectionKind,
+    pub selected_color: Color,
+    pub hover_color: Color,
+    /// Outline width in pixels.
+    pub width: f32,
+    /// How fast the alpha pulses. 0.0 disables pulsing.
+    pub pulse_speed: f32,
+}
+
+impl Default for SelectionStyle {
+    fn default() -> Self {
+        Self {
+            kind: SelectionKind::Outline,
+            selected_color: Color::rgba(255, 220, 0, 255),
+            hover_color: Color::rgba(255, 255, 255, 160),
+            width: 2.0,
+            pulse_speed: 4.0,
+        }
+    }
+}
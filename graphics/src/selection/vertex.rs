@@ -0,0 +1,65 @@
+use crate::{BufferData, BufferLayout};
+use std::iter;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SelectionVertex {
+    pub position: [f32; 3],
+    pub hw: [f32; 2],
+    /// Texture rect in the atlas. Unused when layer is -1.
+    pub tex_data: [f32; 4],
+    pub color: u32,
+    pub width: f32,
+    /// Atlas layer or -1 for a solid quad.
+    pub layer: i32,
+    pub use_camera: u32,
+    /// 0 for an outline, 1 for a tint.
+    pub kind: u32,
+    pub pulse_speed: f32,
+}
+
+impl Default for SelectionVertex {
+    fn default() -> Self {
+        Self {
+            position: [0.0; 3],
+            hw: [0.0; 2],
+            tex_data: [0.0; 4],
+            color: 0,
+            width: 0.0,
+            layer: -1,
+            use_camera: 1,
+            kind: 0,
+            pulse_speed: 0.0,
+        }
+    }
+}
+
+impl BufferLayout for SelectionVertex {
+    fn attributes() -> Vec<wgpu::VertexAttribute> {
+        wgpu::vertex_attr_array![1 => Float32x3, 2 => Float32x2, 3 => Float32x4, 4 => Uint32, 5 => Float32, 6 => Sint32, 7 => Uint32, 8 => Uint32, 9 => Float32]
+            .to_vec()
+    }
+
+    fn default_buffer() -> BufferData {
+        Self::with_capacity(256, 0)
+    }
+
+    fn with_capacity(
+        vertex_capacity: usize,
+        _index_capacity: usize,
+    ) -> BufferData {
+        let instance_arr: Vec<SelectionVertex> =
+            iter::repeat(SelectionVertex::default())
+                .take(vertex_capacity)
+                .collect();
+
+        BufferData {
+            vertexs: bytemuck::cast_slice(&instance_arr).to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn stride() -> usize {
+        std::mem::size_of::<[f32; 16]>()
+    }
+}
@@ -0,0 +1,456 @@
+//! The WGSL sources the pipelines are built from, and offline validation of
+//! them and of any custom shaders against a device's limits and features.
+
+mod reflect;
+
+pub use reflect::*;
+
+use crate::GpuDevice;
+use naga::{
+    front::wgsl,
+    valid::{Capabilities, ValidationFlags, Validator},
+    AddressSpace, Binding, ImageClass, Module, ShaderStage, TypeInner,
+};
+use std::borrow::Cow;
+use thiserror::Error;
+
+pub const DAMAGE_SHADER: &str = include_str!("shaders/damageshader.wgsl");
+pub const DISPLAY_SHADER: &str = include_str!("shaders/displayshader.wgsl");
+/// The `Global` struct and its binding at group 0 binding 0, which every
+/// pipeline drawn with a [`crate::System`] starts with. Matches
+/// [`crate::advanced::GlobalUniform`].
+pub const GLOBAL_SHADER: &str = include_str!("shaders/global.wgsl");
+#[cfg(feature = "sprites")]
+pub const IMAGE_SHADER: &str = concat!(
+    include_str!("shaders/global.wgsl"),
+    include_str!("shaders/imageshader.wgsl")
+);
+#[cfg(feature = "maps")]
+pub const MAP_SHADER: &str = concat!(
+    include_str!("shaders/global.wgsl"),
+    include_str!("shaders/mapshader.wgsl")
+);
+#[cfg(feature = "shapes")]
+pub const MESH2D_SHADER: &str = concat!(
+    include_str!("shaders/global.wgsl"),
+    include_str!("shaders/2dmeshshader.wgsl")
+);
+#[cfg(feature = "shapes")]
+pub const RECT_SHADER: &str = concat!(
+    include_str!("shaders/global.wgsl"),
+    include_str!("shaders/rectshader.wgsl")
+);
+#[cfg(all(feature = "sprites", feature = "shapes"))]
+pub const SELECTION_SHADER: &str = concat!(
+    include_str!("shaders/global.wgsl"),
+    include_str!("shaders/selectionshader.wgsl")
+);
+#[cfg(feature = "text")]
+pub const TEXT_SHADER: &str = concat!(
+    include_str!("shaders/global.wgsl"),
+    include_str!("shaders/textshader.wgsl")
+);
+pub const TRANSITION_SHADER: &str =
+    include_str!("shaders/transitionshader.wgsl");
+pub const UPSCALE_SHADER: &str = include_str!("shaders/upscaleshader.wgsl");
+#[cfg(feature = "maps")]
+pub const WATER_SHADER: &str = concat!(
+    include_str!("shaders/global.wgsl"),
+    include_str!("shaders/watershader.wgsl")
+);
+/// Needs the light arrays appended, see [`light_shader`].
+#[cfg(feature = "lights")]
+pub const LIGHT_SHADER: &str = concat!(
+    include_str!("shaders/global.wgsl"),
+    include_str!("shaders/lightshader.wgsl")
+);
+#[cfg(feature = "lights")]
+pub const LIGHT_UNIFORM_BINDINGS: &str =
+    include_str!("shaders/lightuniform.wgsl");
+#[cfg(feature = "lights")]
+pub const LIGHT_STORAGE_BINDINGS: &str =
+    include_str!("shaders/lightstorage.wgsl");
+
+/// A custom shader with [`GLOBAL_SHADER`] in front, so it can use
+/// `global.seconds`, `global.wind` and the rest without declaring them.
+pub fn with_globals(source: &str) -> String {
+    format!("{GLOBAL_SHADER}\n{source}")
+}
+
+/// The light shader with its arrays in storage or uniform buffers.
+#[cfg(feature = "lights")]
+pub fn light_shader(storage: bool) -> String {
+    let bindings = if storage {
+        LIGHT_STORAGE_BINDINGS
+    } else {
+        LIGHT_UNIFORM_BINDINGS
+    };
+
+    format!("{LIGHT_SHADER}\n{bindings}")
+}
+
+/// A named WGSL source to validate.
+#[derive(Clone, Debug)]
+pub struct ShaderSource {
+    pub name: Cow<'static, str>,
+    pub source: Cow<'static, str>,
+}
+
+impl ShaderSource {
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        source: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            source: source.into(),
+        }
+    }
+}
+
+/// Every shader the crate's pipelines use, as they are handed to wgpu.
+/// The storage light shader is only included with storage_lights.
+pub fn embedded_shaders(storage_lights: bool) -> Vec<ShaderSource> {
+    // The rest belong to features, which may all be off.
+    #[allow(unused_mut)]
+    let mut shaders = vec![
+        ShaderSource::new("damageshader", DAMAGE_SHADER),
+        ShaderSource::new("displayshader", DISPLAY_SHADER),
+        ShaderSource::new("transitionshader", TRANSITION_SHADER),
+        ShaderSource::new("upscaleshader", UPSCALE_SHADER),
+    ];
+
+    #[cfg(feature = "sprites")]
+    shaders.push(ShaderSource::new("imageshader", IMAGE_SHADER));
+    #[cfg(feature = "maps")]
+    shaders.extend([
+        ShaderSource::new("mapshader", MAP_SHADER),
+        ShaderSource::new("watershader", WATER_SHADER),
+    ]);
+    #[cfg(feature = "shapes")]
+    shaders.extend([
+        ShaderSource::new("2dmeshshader", MESH2D_SHADER),
+        ShaderSource::new("rectshader", RECT_SHADER),
+    ]);
+    #[cfg(all(feature = "sprites", feature = "shapes"))]
+    shaders.push(ShaderSource::new("selectionshader", SELECTION_SHADER));
+    #[cfg(feature = "text")]
+    shaders.push(ShaderSource::new("textshader", TEXT_SHADER));
+    #[cfg(feature = "lights")]
+    {
+        shaders.push(ShaderSource::new(
+            "lightshader (uniform)",
+            light_shader(false),
+        ));
+
+        if storage_lights {
+            shaders.push(ShaderSource::new(
+                "lightshader (storage)",
+                light_shader(true),
+            ));
+        }
+    }
+    #[cfg(not(feature = "lights"))]
+    let _ = storage_lights;
+
+    shaders
+}
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ShaderError {
+    /// message is the formatted WGSL error with the source snippet.
+    #[error("{name} failed to parse:\n{message}")]
+    Parse { name: String, message: String },
+    #[error("{name} is invalid:\n{message}")]
+    Validation { name: String, message: String },
+    /// The shader doesn't match the Rust side layouts it is used with.
+    #[error("{name}: {message}")]
+    Mismatch { name: String, message: String },
+    /// The shader is valid WGSL but uses more than the device allows.
+    #[error("{name}: entry point {entry_point} {message}")]
+    Limit {
+        name: String,
+        entry_point: String,
+        message: String,
+    },
+}
+
+/// Validates shaders without creating pipelines, so a broken shader shows
+/// up as an error in a test instead of a panic when the pipeline is built.
+pub struct ShaderValidator {
+    limits: wgpu::Limits,
+    features: wgpu::Features,
+    sources: Vec<ShaderSource>,
+}
+
+impl ShaderValidator {
+    /// Starts with the embedded shaders. The storage light shader is only
+    /// checked when the limits have room for its buffers, as the renderer
+    /// only uses it then.
+    pub fn new(limits: wgpu::Limits, features: wgpu::Features) -> Self {
+        let sources =
+            embedded_shaders(limits.max_storage_buffers_per_shader_stage >= 6);
+
+        Self {
+            limits,
+            features,
+            sources,
+        }
+    }
+
+    /// Uses the limits and features the device was created with.
+    pub fn for_device(gpu_device: &GpuDevice) -> Self {
+        Self::new(gpu_device.device().limits(), gpu_device.device().features())
+    }
+
+    /// Adds a custom shader, like one of a plugin's pipelines.
+    pub fn register(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        source: impl Into<Cow<'static, str>>,
+    ) -> &mut Self {
+        self.sources.push(ShaderSource::new(name, source));
+        self
+    }
+
+    pub fn sources(&self) -> &[ShaderSource] {
+        &self.sources
+    }
+
+    /// Checks every source and collects all the errors.
+    pub fn validate(&self) -> Result<(), Vec<ShaderError>> {
+        let errors: Vec<ShaderError> = self
+            .sources
+            .iter()
+            .flat_map(|shader| self.validate_source(shader))
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub(crate) fn validate_source(
+        &self,
+        shader: &ShaderSource,
+    ) -> Vec<ShaderError> {
+        let name = shader.name.to_string();
+        let module = match wgsl::parse_str(&shader.source) {
+            Ok(module) => module,
+            Err(e) => {
+                return vec![ShaderError::Parse {
+                    message: e.emit_to_string_with_path(&shader.source, &name),
+                    name,
+                }]
+            }
+        };
+
+        let mut validator =
+            Validator::new(ValidationFlags::all(), self.capabilities());
+
+        let info = match validator.validate(&module) {
+            Ok(info) => info,
+            Err(e) => {
+                return vec![ShaderError::Validation {
+                    message: e.emit_to_string_with_path(&shader.source, &name),
+                    name,
+                }]
+            }
+        };
+
+        let mut errors = Vec::new();
+
+        for (index, entry_point) in module.entry_points.iter().enumerate() {
+            let usage = info.get_entry_point(index);
+            let used: Vec<_> = module
+                .global_variables
+                .iter()
+                .filter(|(handle, _)| !usage[*handle].is_empty())
+                .map(|(_, var)| var)
+                .collect();
+
+            let mut limit = |message: String| {
+                errors.push(ShaderError::Limit {
+                    name: name.clone(),
+                    entry_point: entry_point.name.clone(),
+                    message,
+                })
+            };
+
+            let mut counts = BindingCounts::default();
+
+            for var in &used {
+                if let Some(binding) = &var.binding {
+                    if binding.group >= self.limits.max_bind_groups {
+                        limit(format!(
+                            "uses bind group {} but only {} are allowed",
+                            binding.group, self.limits.max_bind_groups
+                        ));
+                    }
+                }
+
+                counts.add(&module, var.space, var.ty);
+            }
+
+            for (count, max, what) in [
+                (
+                    counts.uniform_buffers,
+                    self.limits.max_uniform_buffers_per_shader_stage,
+                    "uniform buffers",
+                ),
+                (
+                    counts.storage_buffers,
+                    self.limits.max_storage_buffers_per_shader_stage,
+                    "storage buffers",
+                ),
+                (
+                    counts.sampled_textures,
+                    self.limits.max_sampled_textures_per_shader_stage,
+                    "sampled textures",
+                ),
+                (
+                    counts.storage_textures,
+                    self.limits.max_storage_textures_per_shader_stage,
+                    "storage textures",
+                ),
+                (
+                    counts.samplers,
+                    self.limits.max_samplers_per_shader_stage,
+                    "samplers",
+                ),
+            ] {
+                if count > max {
+                    limit(format!(
+                        "uses {count} {what} but only {max} are allowed"
+                    ));
+                }
+            }
+
+            if entry_point.stage == ShaderStage::Vertex {
+                let inputs = vertex_inputs(&module, entry_point);
+
+                if inputs > self.limits.max_vertex_attributes {
+                    limit(format!(
+                        "has {inputs} vertex inputs but only {} are allowed",
+                        self.limits.max_vertex_attributes
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// What naga may accept given the device's features.
+    fn capabilities(&self) -> Capabilities {
+        let mut capabilities = Capabilities::empty();
+
+        for (feature, capability) in [
+            (wgpu::Features::PUSH_CONSTANTS, Capabilities::PUSH_CONSTANT),
+            (wgpu::Features::SHADER_F64, Capabilities::FLOAT64),
+            (
+                wgpu::Features::SHADER_PRIMITIVE_INDEX,
+                Capabilities::PRIMITIVE_INDEX,
+            ),
+            (
+                wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
+                Capabilities::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
+                    | Capabilities::SAMPLER_NON_UNIFORM_INDEXING,
+            ),
+            (
+                wgpu::Features::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
+                Capabilities::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
+            ),
+            (wgpu::Features::MULTIVIEW, Capabilities::MULTIVIEW),
+            (
+                wgpu::Features::SHADER_EARLY_DEPTH_TEST,
+                Capabilities::EARLY_DEPTH_TEST,
+            ),
+            (
+                wgpu::Features::DUAL_SOURCE_BLENDING,
+                Capabilities::DUAL_SOURCE_BLENDING,
+            ),
+        ] {
+            if self.features.contains(feature) {
+                capabilities |= capability;
+            }
+        }
+
+        capabilities
+    }
+}
+
+/// Validates the embedded shaders against the given limits and features.
+/// Use [`wgpu::Limits::downlevel_webgl2_defaults`] and no features to check
+/// the lowest targets without a device.
+pub fn validate_all(
+    limits: wgpu::Limits,
+    features: wgpu::Features,
+) -> Result<(), Vec<ShaderError>> {
+    ShaderValidator::new(limits, features).validate()
+}
+
+#[derive(Default)]
+struct BindingCounts {
+    uniform_buffers: u32,
+    storage_buffers: u32,
+    sampled_textures: u32,
+    storage_textures: u32,
+    samplers: u32,
+}
+
+impl BindingCounts {
+    fn add(
+        &mut self,
+        module: &Module,
+        space: AddressSpace,
+        ty: naga::Handle<naga::Type>,
+    ) {
+        match space {
+            AddressSpace::Uniform => self.uniform_buffers += 1,
+            AddressSpace::Storage { .. } => self.storage_buffers += 1,
+            AddressSpace::Handle => match module.types[ty].inner {
+                TypeInner::Image {
+                    class: ImageClass::Storage { .. },
+                    ..
+                } => self.storage_textures += 1,
+                TypeInner::Image { .. } => self.sampled_textures += 1,
+                TypeInner::Sampler { .. } => self.samplers += 1,
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Location bound arguments of a vertex entry point, including the
+/// members of struct arguments.
+fn vertex_inputs(module: &Module, entry_point: &naga::EntryPoint) -> u32 {
+    let mut count = 0;
+
+    for argument in &entry_point.function.arguments {
+        match &argument.binding {
+            Some(Binding::Location { .. }) => count += 1,
+            Some(Binding::BuiltIn(_)) => {}
+            None => {
+                if let TypeInner::Struct { members, .. } =
+                    &module.types[argument.ty].inner
+                {
+                    count += members
+                        .iter()
+                        .filter(|member| {
+                            matches!(
+                                member.binding,
+                                Some(Binding::Location { .. })
+                            )
+                        })
+                        .count() as u32;
+                }
+            }
+        }
+    }
+
+    count
+}
@@ -1,18 +1,3 @@
-struct Global {
-    view: mat4x4<f32>, //64
-    proj: mat4x4<f32>,
-    inverse_proj: mat4x4<f32>,
-    eye: vec3<f32>,
-    scale: f32,
-    size: vec2<f32>,
-    seconds: f32,
-};
-
-@group(0)
-@binding(0)
-var<uniform> global: Global;
-
-
 struct VertexInput {
     @builtin(vertex_index) vertex_idx: u32,
     @location(0) position: vec3<f32>,
@@ -42,7 +27,7 @@ fn vertex(
     var pos = vertex.position;
 
     if (vertex.use_camera == 1u) {
-        result.clip_position = (global.proj * global.view) * vec4<f32>(pos, 1.0);
+        result.clip_position = (global.proj * camera_view(1.0)) * vec4<f32>(pos, 1.0);
     } else {
         result.clip_position = global.proj * vec4<f32>(pos, 1.0);
     }
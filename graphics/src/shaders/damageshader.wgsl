@@ -0,0 +1,24 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+@vertex
+fn vertex(
+    @builtin(vertex_index) vertex_idx: u32,
+) -> VertexOutput {
+    var result: VertexOutput;
+    // One triangle that covers the whole screen, the scissor limits it to
+    // the damaged region.
+    let x = f32((vertex_idx << 1u) & 2u);
+    let y = f32(vertex_idx & 2u);
+
+    result.clip_position = vec4<f32>(x * 2.0 - 1.0, y * 2.0 - 1.0, 0.0, 1.0);
+    return result;
+}
+
+// Fragment shader
+// The blend replaces this with the pass's blend constant, the clear color.
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
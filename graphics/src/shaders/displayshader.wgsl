@@ -0,0 +1,131 @@
+struct Display {
+    size: vec2<f32>,
+    brightness: f32,
+    contrast: f32,
+    gamma: f32,
+    colorblind: u32,
+    strength: f32,
+    linear: u32,
+};
+
+@group(0)
+@binding(0)
+var<uniform> display: Display;
+@group(0)
+@binding(1)
+var frame_tex: texture_2d<f32>;
+@group(0)
+@binding(2)
+var frame_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+@vertex
+fn vertex(
+    @builtin(vertex_index) vertex_idx: u32,
+) -> VertexOutput {
+    var result: VertexOutput;
+    // One triangle that covers the whole screen.
+    let x = f32((vertex_idx << 1u) & 2u);
+    let y = f32(vertex_idx & 2u);
+
+    result.clip_position = vec4<f32>(x * 2.0 - 1.0, y * 2.0 - 1.0, 0.0, 1.0);
+    return result;
+}
+
+fn to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+fn to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, color <= vec3<f32>(0.04045));
+}
+
+// Daltonization. Simulates what the player sees in LMS space and moves
+// the difference into the channels they can still tell apart.
+fn daltonize(color: vec3<f32>, mode: u32) -> vec3<f32> {
+    let rgb_to_lms = mat3x3<f32>(
+        vec3<f32>(17.8824, 3.45565, 0.0299566),
+        vec3<f32>(43.5161, 27.1554, 0.184309),
+        vec3<f32>(4.11935, 3.86714, 1.46709),
+    );
+    let lms_to_rgb = mat3x3<f32>(
+        vec3<f32>(0.0809444479, -0.0102485335, -0.000365296938),
+        vec3<f32>(-0.130504409, 0.0540193266, -0.00412161469),
+        vec3<f32>(0.116721066, -0.113614708, 0.693511405),
+    );
+
+    var sim: mat3x3<f32>;
+
+    switch mode {
+        // Protanopia
+        case 1u: {
+            sim = mat3x3<f32>(
+                vec3<f32>(0.0, 0.0, 0.0),
+                vec3<f32>(2.02344, 1.0, 0.0),
+                vec3<f32>(-2.52581, 0.0, 1.0),
+            );
+        }
+        // Deuteranopia
+        case 2u: {
+            sim = mat3x3<f32>(
+                vec3<f32>(1.0, 0.494207, 0.0),
+                vec3<f32>(0.0, 0.0, 0.0),
+                vec3<f32>(0.0, 1.24827, 1.0),
+            );
+        }
+        // Tritanopia
+        default: {
+            sim = mat3x3<f32>(
+                vec3<f32>(1.0, 0.0, -0.395913),
+                vec3<f32>(0.0, 1.0, 0.801109),
+                vec3<f32>(0.0, 0.0, 0.0),
+            );
+        }
+    }
+
+    let seen = lms_to_rgb * (sim * (rgb_to_lms * color));
+    let error = color - seen;
+    let shift = vec3<f32>(
+        0.0,
+        error.r * 0.7 + error.g,
+        error.r * 0.7 + error.b,
+    );
+
+    return color + shift;
+}
+
+// Fragment shader
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let uv = vertex.clip_position.xy / display.size;
+    let frame = textureSampleLevel(frame_tex, frame_sampler, uv, 0.0);
+    var color = frame.rgb;
+
+    if (display.linear == 1u) {
+        color = to_srgb(color);
+    }
+
+    color = pow(max(color, vec3<f32>(0.0)), vec3<f32>(1.0 / display.gamma));
+    color = (color - 0.5) * display.contrast + 0.5;
+    color = color * display.brightness;
+
+    if (display.colorblind != 0u) {
+        let corrected = daltonize(clamp(color, vec3<f32>(0.0), vec3<f32>(1.0)), display.colorblind);
+        color = mix(color, corrected, display.strength);
+    }
+
+    color = clamp(color, vec3<f32>(0.0), vec3<f32>(1.0));
+
+    if (display.linear == 1u) {
+        color = to_linear(color);
+    }
+
+    return vec4<f32>(color, frame.a);
+}
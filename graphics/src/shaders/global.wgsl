@@ -0,0 +1,51 @@
+// The engine globals, put in front of every shader drawn with a System.
+// Custom shaders get them from shaders::with_globals.
+struct Global {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+    inverse_proj: mat4x4<f32>,
+    eye: vec3<f32>,
+    scale: f32,
+    size: vec2<f32>,
+    // Seconds since the start of the program, given by the clock.
+    seconds: f32,
+    // 0.0 at midnight, 0.5 at noon.
+    time_of_day: f32,
+    // Direction and strength of the wind.
+    wind: vec2<f32>,
+    // Changes whenever the app reseeds.
+    seed: u32,
+    ambient: vec4<f32>,
+    // 1 when positions get rounded to whole screen pixels.
+    pixel_snap: u32,
+};
+
+@group(0)
+@binding(0)
+var<uniform> global: Global;
+
+// The camera's view with its translation scaled by parallax, and rounded
+// to whole screen pixels when pixel snapping is on.
+fn camera_view(parallax: f32) -> mat4x4<f32> {
+    var view = global.view;
+    var offset = view[3].xy * parallax;
+
+    if (global.pixel_snap == 1u) {
+        offset = round(offset);
+    }
+
+    view[3] = vec4<f32>(offset, view[3].z * parallax, view[3].w);
+    return view;
+}
+
+// Rounds an instance's position to the screen pixel it lands on, at the
+// camera's zoom if bit 0 of use_camera is set, when pixel snapping is on.
+fn snap_instance(pos: vec3<f32>, use_camera: u32) -> vec3<f32> {
+    if (global.pixel_snap != 1u) {
+        return pos;
+    }
+
+    let scale = select(1.0, global.scale, (use_camera & 1u) == 1u);
+    return vec3<f32>(round(pos.xy * scale) / scale, pos.z);
+}
+
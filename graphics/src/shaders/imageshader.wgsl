@@ -1,17 +1,3 @@
-struct Global {
-    view: mat4x4<f32>,
-    proj: mat4x4<f32>,
-    inverse_proj: mat4x4<f32>,
-    eye: vec3<f32>,
-    scale: f32,
-    size: vec2<f32>,
-    seconds: f32,
-};
-
-@group(0)
-@binding(0)
-var<uniform> global: Global;
-
 struct VertexInput {
     @builtin(vertex_index) vertex_idx: u32,
     @location(0) v_pos: vec2<f32>,
@@ -24,6 +10,9 @@ struct VertexInput {
     @location(7) use_camera: u32,
     @location(8) time: u32,
     @location(9) layer: i32,
+    @location(10) pick_id: u32,
+    // Unused here, declared so custom pipelines can share the layout.
+    @location(11) user_data: vec4<f32>,
 };
 
 struct VertexOutput {
@@ -36,6 +25,7 @@ struct VertexOutput {
     @location(5) layer: i32,
     @location(6) time: u32,
     @location(7) animate: u32,
+    @location(8) pick_id: u32,
 };
 
 @group(1)
@@ -72,7 +62,8 @@ fn vertex(
     let size = textureDimensions(tex);
     let fsize = vec2<f32> (f32(size.x), f32(size.y));
     let tex_data = vertex.tex_data;
-    var pos = vertex.position;
+    let base = snap_instance(vertex.position, vertex.use_camera);
+    var pos = base;
 
     switch v {
         case 1u: {
@@ -93,8 +84,15 @@ fn vertex(
         }
     }
 
-    if (vertex.use_camera == 1u) {
-        result.clip_position = (global.proj * global.view) * vec4<f32>(pos, 1.0);
+    if ((vertex.use_camera & 3u) == 3u) {
+        // Builds the quad in view space so it always faces the camera.
+        let view = camera_view(1.0);
+        let corner = (pos.xy - base.xy) * length(view[0].xyz);
+
+        result.clip_position = global.proj
+            * (view * vec4<f32>(base, 1.0) + vec4<f32>(corner, 0.0, 0.0));
+    } else if ((vertex.use_camera & 1u) == 1u) {
+        result.clip_position = (global.proj * camera_view(1.0)) * vec4<f32>(pos, 1.0);
     } else {
         result.clip_position = global.proj * vec4<f32>(pos, 1.0);
     }
@@ -106,12 +104,12 @@ fn vertex(
     result.size = fsize;
     result.animate = vertex.animate;
     result.time = vertex.time;
+    result.pick_id = vertex.pick_id;
     return result;
 }
 
-// Fragment shader
-@fragment
-fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+// Samples the current frame of the image.
+fn sample_image(vertex: VertexOutput) -> vec4<f32> {
     var coords = vec2<f32>(0.0, 0.0);
     let xframes = vertex.frames[0];
     var yframes = vertex.frames[0];
@@ -151,11 +149,41 @@ fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
     c3 = c3 * (frac.x * (1.0 - frac.y));
     c4 = c4 *((1.0 - frac.x) * (1.0 - frac.y));
 
-    let object_color = (c1 + c2 + c3 + c4) * vertex.col;
+    return c1 + c2 + c3 + c4;
+}
+
+// Fragment shader
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let object_color = sample_image(vertex) * vertex.col;
 
     if (object_color.a <= 0.0) {
         discard;
     }
 
     return object_color;
-}
\ No newline at end of file
+}
+
+// Flat color in the image's shape. col holds the silhouette color.
+@fragment
+fn silhouette_fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let alpha = sample_image(vertex).a * vertex.col.a;
+
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    return vec4<f32>(vertex.col.rgb, alpha);
+}
+
+// Object id for the pick buffer where the image is visible.
+@fragment
+fn pick_fragment(vertex: VertexOutput,) -> @location(0) u32 {
+    let alpha = sample_image(vertex).a * vertex.col.a;
+
+    if (alpha <= 0.0 || vertex.pick_id == 0u) {
+        discard;
+    }
+
+    return vertex.pick_id;
+}
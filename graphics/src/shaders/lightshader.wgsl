@@ -1,13 +1,3 @@
-struct Global {
-    view: mat4x4<f32>,
-    proj: mat4x4<f32>,
-    inverse_proj: mat4x4<f32>,
-    eye: vec3<f32>,
-    scale: f32,
-    size: vec2<f32>,
-    seconds: f32,
-};
-
 struct AreaLights {
     pos: vec2<f32>,
     color: u32,
@@ -15,6 +5,7 @@ struct AreaLights {
     anim_speed: f32,
     dither: f32,
     animate: u32,
+    anim_amount: f32,
 };
 
 struct RangeReturn {
@@ -33,11 +24,14 @@ struct DirLights {
     fade_distance: f32,
     edge_fade_distance: f32,
     animate: u32,
+    anim_amount: f32,
 };
 
-@group(0)
-@binding(0)
-var<uniform> global: Global;
+struct LightCookie {
+    rect: vec2<u32>,
+    layer: i32,
+    rotation: f32,
+};
 
 struct VertexInput {
     @builtin(vertex_index) vertex_idx: u32,
@@ -57,15 +51,15 @@ struct VertexOutput {
     @location(4) area_count: u32,
 };
 
-const c_area_lights: u32 = 2000u;
-const c_dir_lights: u32 = 1365u;
-
-@group(1)
+// u_areas, u_area_cookies, u_dirs, u_dir_cookies and the area/dir range and
+// index functions are declared in lightuniform.wgsl or lightstorage.wgsl.
+// A range is the start and count of the lights that can reach a pixel.
+@group(3)
 @binding(0)
-var<uniform> u_areas: array<AreaLights, c_area_lights>;
-@group(2)
-@binding(0)
-var<uniform> u_dirs: array<DirLights, c_dir_lights>;
+var cookie_tex: texture_2d_array<f32>;
+@group(3)
+@binding(1)
+var cookie_sample: sampler;
 
 fn unpack_color(color: u32) -> vec4<f32> {
     return vec4<f32>(
@@ -187,34 +181,124 @@ fn flash_light(light_pos: vec2<f32>, pixel_pos: vec2<f32>, dir: f32, w_angle: f3
     return 0.0;
 }
 
+// uv is 0.0..1.0 within the cookie. Returns how much light gets through.
+fn sample_cookie(cookie: LightCookie, uv: vec2<f32>) -> f32 {
+    if (cookie.layer < 0) {
+        return 1.0;
+    }
+
+    if (any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0))) {
+        return 0.0;
+    }
+
+    let size = textureDimensions(cookie_tex);
+    let fsize = vec2<f32>(f32(size.x), f32(size.y));
+    let pos = vec2<f32>(f32(cookie.rect.x & 0xffffu), f32(cookie.rect.x >> 16u));
+    let rect_size = vec2<f32>(f32(cookie.rect.y & 0xffffu), f32(cookie.rect.y >> 16u));
+    let color = textureSampleLevel(cookie_tex, cookie_sample, (pos + uv * rect_size) / fsize, cookie.layer, 0.0);
+
+    return dot(color.rgb, vec3<f32>(0.299, 0.587, 0.114)) * color.a;
+}
+
+// Stretches the cookie over the light's radius, rotated around its center.
+fn area_cookie(cookie: LightCookie, light_pos: vec2<f32>, pixel_pos: vec2<f32>, radius: f32) -> f32 {
+    let r = radians(cookie.rotation);
+    let offset = pixel_pos - light_pos;
+    let local = vec2<f32>(
+        offset.x * cos(r) + offset.y * sin(r),
+        offset.y * cos(r) - offset.x * sin(r),
+    );
+
+    return sample_cookie(cookie, local / (radius * 2.0) + 0.5);
+}
+
+// Projects the cookie along the cone. u runs along the distance
+// and v across the width of the cone.
+fn dir_cookie(cookie: LightCookie, light_pos: vec2<f32>, pixel_pos: vec2<f32>, dir: f32, w_angle: f32, range: f32) -> f32 {
+    let deg = atan2(pixel_pos.y - light_pos.y, pixel_pos.x - light_pos.x) * 180.0 / 3.14159265;
+    let across = normalize_180(deg - dir) / w_angle + 0.5;
+    let along = distance(light_pos, pixel_pos) / range;
+
+    return sample_cookie(cookie, vec2<f32>(along, across));
+}
+
+// Integer hash to 0.0..1.0, so every GPU gets the same values.
+fn hash(n: f32) -> f32 {
+    var h = bitcast<u32>(i32(n)) * 747796405u + 2891336453u;
+    h = ((h >> ((h >> 28u) + 4u)) ^ h) * 277803737u;
+    h = (h >> 22u) ^ h;
+    return f32(h >> 8u) / 16777216.0;
+}
+
+// Smooth random value 0.0..1.0 over time.
+fn noise(t: f32) -> f32 {
+    let i = floor(t);
+    let f = fract(t);
+    return mix(hash(i), hash(i + 1.0), f * f * (3.0 - 2.0 * f));
+}
+
+// Brightness multiplier for the light's animation. The seed in the upper
+// bits keeps lights flickering out of step with each other.
+fn light_animation(animate: u32, speed: f32, amount: f32) -> f32 {
+    let t = global.seconds * speed;
+    let seed = f32(animate >> 16u) / 65536.0;
+
+    switch ((animate >> 8u) & 0xffu) {
+        // Pulse
+        case 1u: {
+            return 1.0 - amount * (0.5 + 0.5 * sin(t));
+        }
+        // Flicker
+        case 2u: {
+            let n = noise(t + seed * 512.0) * 0.7 + noise(t * 2.7 + seed * 1024.0) * 0.3;
+            return 1.0 - amount * n;
+        }
+        // Strobe
+        case 3u: {
+            return select(0.0, 1.0, fract(t + seed) < amount);
+        }
+        default: {
+            return 1.0;
+        }
+    }
+}
+
 // Fragment shader
 @fragment
 fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
     var col = vertex.col;
 
     if (vertex.enable_lights > 0u) {
-        for(var i = 0u; i < min(vertex.area_count, c_area_lights); i += 1u) {
+        let areas = area_range(vertex.area_count, vertex.tex_coords.xy);
+
+        for(var n = 0u; n < areas.y; n += 1u) {
+            let i = area_index(areas.x + n);
             let light = u_areas[i];
             let light_color = unpack_color(light.color);
             let pos = vec2<f32>(light.pos.x, light.pos.y);
-            let max_distance = light.max_distance - (f32(light.animate) *(1.0 * sin(global.seconds * light.anim_speed)));
+            let max_distance = light.max_distance - (f32(light.animate & 1u) *(1.0 * sin(global.seconds * light.anim_speed)));
             let dist = distance(pos.xy, vertex.tex_coords.xy);
             let cutoff = max(0.1, max_distance);
-            let value = fade(dist, 0.0, 1.0, cutoff, light.dither);
+            let anim = light_animation(light.animate, light.anim_speed, light.anim_amount);
+            let value = anim * fade(dist, 0.0, 1.0, cutoff, light.dither) * area_cookie(u_area_cookies[i], pos, vertex.tex_coords.xy, cutoff);
             var color2 = col; 
             let alpha = mix(color2.a, light_color.a, value);
             color2.a = alpha;
             col = mix(color2, light_color, vec4<f32>(value));
         }
 
-        for(var i = 0u; i < min(vertex.dir_count, c_dir_lights); i += 1u) {
+        let dirs = dir_range(vertex.dir_count, vertex.tex_coords.xy);
+
+        for(var n = 0u; n < dirs.y; n += 1u) {
+            let i = dir_index(dirs.x + n);
             let light = u_dirs[i];
             let light_color = unpack_color(light.color);
-            let max_distance = light.max_distance - (f32(light.animate) *(1.0 * sin(global.seconds * light.anim_speed)));
+            let max_distance = light.max_distance - (f32(light.animate & 1u) *(1.0 * sin(global.seconds * light.anim_speed)));
             let dist_cutoff = max(0.1, max_distance);
-            let max_width = light.max_width - (f32(light.animate) *(1.0 * sin(global.seconds * light.anim_speed)));
+            let max_width = light.max_width - (f32(light.animate & 1u) *(1.0 * sin(global.seconds * light.anim_speed)));
             let width_cutoff = max(0.1, max_width);
-            let value = flash_light(light.pos, vertex.tex_coords.xy, light.angle, width_cutoff, dist_cutoff, light.dither, light.edge_fade_distance, light.fade_distance);
+            let anim = light_animation(light.animate, light.anim_speed, light.anim_amount);
+            let value = anim * flash_light(light.pos, vertex.tex_coords.xy, light.angle, width_cutoff, dist_cutoff, light.dither, light.edge_fade_distance, light.fade_distance) * dir_cookie(u_dir_cookies[i], light.pos, vertex.tex_coords.xy, light.angle, width_cutoff, dist_cutoff);
             var color2 = col; 
             let alpha = mix(color2.a, light_color.a, value);
             color2.a = alpha;
@@ -0,0 +1,57 @@
+// Lights binned into screen tiles. Each tile is the offset into
+// u_tile_lights, the area light count then the directional light count.
+// The area light indices come first followed by the directional ones.
+struct LightTiles {
+    tile_size: u32,
+    columns: u32,
+    rows: u32,
+    padding: u32,
+    tiles: array<vec4<u32>>,
+};
+
+@group(1)
+@binding(0)
+var<storage, read> u_areas: array<AreaLights>;
+@group(1)
+@binding(1)
+var<storage, read> u_area_cookies: array<LightCookie>;
+@group(1)
+@binding(2)
+var<storage, read> u_light_tiles: LightTiles;
+@group(1)
+@binding(3)
+var<storage, read> u_tile_lights: array<u32>;
+@group(2)
+@binding(0)
+var<storage, read> u_dirs: array<DirLights>;
+@group(2)
+@binding(1)
+var<storage, read> u_dir_cookies: array<LightCookie>;
+
+fn light_tile(pixel: vec2<f32>) -> vec4<u32> {
+    let tile = vec2<i32>(floor(pixel / f32(max(u_light_tiles.tile_size, 1u))));
+
+    if (tile.x < 0 || tile.y < 0 || u32(tile.x) >= u_light_tiles.columns || u32(tile.y) >= u_light_tiles.rows) {
+        return vec4<u32>(0u);
+    }
+
+    return u_light_tiles.tiles[u32(tile.y) * u_light_tiles.columns + u32(tile.x)];
+}
+
+fn area_range(count: u32, pixel: vec2<f32>) -> vec2<u32> {
+    let tile = light_tile(pixel);
+    return vec2<u32>(tile.x, tile.y);
+}
+
+fn dir_range(count: u32, pixel: vec2<f32>) -> vec2<u32> {
+    let tile = light_tile(pixel);
+    return vec2<u32>(tile.x + tile.y, tile.z);
+}
+
+fn area_index(i: u32) -> u32 {
+    return min(u_tile_lights[i], arrayLength(&u_areas) - 1u);
+}
+
+fn dir_index(i: u32) -> u32 {
+    return min(u_tile_lights[i], arrayLength(&u_dirs) - 1u);
+}
@@ -0,0 +1,32 @@
+const c_area_lights: u32 = 2000u;
+const c_dir_lights: u32 = 1365u;
+
+@group(1)
+@binding(0)
+var<uniform> u_areas: array<AreaLights, c_area_lights>;
+@group(1)
+@binding(1)
+var<uniform> u_area_cookies: array<LightCookie, c_area_lights>;
+@group(2)
+@binding(0)
+var<uniform> u_dirs: array<DirLights, c_dir_lights>;
+@group(2)
+@binding(1)
+var<uniform> u_dir_cookies: array<LightCookie, c_dir_lights>;
+
+// Every light is checked without clustering.
+fn area_range(count: u32, pixel: vec2<f32>) -> vec2<u32> {
+    return vec2<u32>(0u, min(count, c_area_lights));
+}
+
+fn dir_range(count: u32, pixel: vec2<f32>) -> vec2<u32> {
+    return vec2<u32>(0u, min(count, c_dir_lights));
+}
+
+fn area_index(i: u32) -> u32 {
+    return i;
+}
+
+fn dir_index(i: u32) -> u32 {
+    return i;
+}
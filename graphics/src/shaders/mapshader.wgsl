@@ -1,17 +1,3 @@
-struct Global {
-    view: mat4x4<f32>,
-    proj: mat4x4<f32>,
-    inverse_proj: mat4x4<f32>,
-    eye: vec3<f32>,
-    scale: f32,
-    size: vec2<f32>,
-    seconds: f32,
-};
-
-@group(0)
-@binding(0)
-var<uniform> global: Global;
-
 struct VertexInput {
     @builtin(vertex_index) vertex_idx: u32,
     @location(0) v_pos: vec2<f32>,
@@ -20,6 +6,9 @@ struct VertexInput {
     @location(3) texture_id: f32,
     @location(4) texture_layer: f32,
     @location(5) color: u32,
+    @location(6) parallax: f32,
+    @location(7) ao: u32,
+    @location(8) light: vec4<u32>,
 };
 
 struct VertexOutput {
@@ -27,6 +16,8 @@ struct VertexOutput {
     @location(0) uv: vec2<f32>,
     @location(1) uv_layer: i32,
     @location(2) color: vec4<f32>,
+    @location(3) ao: f32,
+    @location(4) light: vec3<f32>,
 };
 
 @group(1)
@@ -77,9 +68,16 @@ fn vertex(
         }
     }
 
-    result.clip_position =  (global.proj * global.view) * vec4<f32>(pos, 1.0);
+    // Scale only the camera's translation so distant layers scroll slower.
+    // Every corner snaps rather than the tile, so neighbours never gap.
+    result.clip_position = (global.proj * camera_view(vertex.parallax))
+        * vec4<f32>(snap_instance(pos, 1u), 1.0);
     result.color = unpack_color(vertex.color);
     result.uv_layer = i32(vertex.texture_layer);
+    // One byte of baked darkening per corner, blended across the tile.
+    result.ao = f32((vertex.ao >> (v * 8u)) & 0xffu) / 255.0;
+    // Baked static lights, blended across the tile like the darkening.
+    result.light = unpack_color(vertex.light[v]).rgb;
     return result;
 }
 
@@ -88,7 +86,9 @@ fn vertex(
 fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
     let object_color = textureSampleLevel(tex, tex_sample, vertex.uv, vertex.uv_layer, 1.0);
 
-    let color = object_color * vertex.color;
+    var color = object_color * vertex.color;
+    color = vec4<f32>(color.rgb * vertex.light, color.a);
+    color = vec4<f32>(color.rgb * (1.0 - vertex.ao), color.a);
 
     if (color.a <= 0.0) {
         discard;
@@ -0,0 +1,124 @@
+struct VertexInput {
+    @builtin(vertex_index) vertex_idx: u32,
+    @location(0) v_pos: vec2<f32>,
+    @location(1) position: vec3<f32>,
+    @location(2) size: vec2<f32>,
+    @location(3) color: u32,
+    @location(4) border_width: f32,
+    @location(5) border_color: u32,
+    @location(6) radius: f32,
+    @location(7) use_camera: u32,
+    @location(8) rotation: f32,
+    @location(9) sweep: f32,
+    // Unused here, declared so custom pipelines can share the layout.
+    @location(10) user_data: vec4<f32>,
+};
+
+struct VertexOutput {
+    @invariant @builtin(position) clip_position: vec4<f32>,
+    @location(0) local_pos: vec2<f32>,
+    @location(1) size: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) border_color: vec4<f32>,
+    @location(4) border_width: f32,
+    @location(5) radius: f32,
+    @location(6) sweep: f32,
+};
+
+fn unpack_color(color: u32) -> vec4<f32> {
+    return vec4<f32>(
+        f32((color & 0xff0000u) >> 16u),
+        f32((color & 0xff00u) >> 8u),
+        f32((color & 0xffu)),
+        f32((color & 0xff000000u) >> 24u),
+    ) / 255.0;
+}
+
+@vertex
+fn vertex(
+    vertex: VertexInput,
+) -> VertexOutput {
+    var result: VertexOutput;
+    var pos = snap_instance(vertex.position, vertex.use_camera);
+    let v = vertex.vertex_idx % 4u;
+
+    switch v {
+        case 1u: {
+            result.local_pos = vec2<f32>(vertex.size.x, 0.0);
+            pos.x += vertex.size.x;
+        }
+        case 2u: {
+            result.local_pos = vertex.size;
+            pos.x += vertex.size.x;
+            pos.y += vertex.size.y;
+        }
+        case 3u: {
+            result.local_pos = vec2<f32>(0.0, vertex.size.y);
+            pos.y += vertex.size.y;
+        }
+        default: {
+            result.local_pos = vec2<f32>(0.0, 0.0);
+        }
+    }
+
+    let r = radians(vertex.rotation);
+    let offset = pos.xy - vertex.position.xy;
+    pos = vec3<f32>(
+        vertex.position.x + offset.x * cos(r) - offset.y * sin(r),
+        vertex.position.y + offset.x * sin(r) + offset.y * cos(r),
+        pos.z,
+    );
+
+    if (vertex.use_camera == 1u) {
+        result.clip_position = (global.proj * camera_view(1.0)) * vec4<f32>(pos, 1.0);
+    } else {
+        result.clip_position = global.proj * vec4<f32>(pos, 1.0);
+    }
+
+    result.size = vertex.size;
+    result.color = unpack_color(vertex.color);
+    result.border_color = unpack_color(vertex.border_color);
+    result.border_width = vertex.border_width;
+    result.radius = min(vertex.radius, min(vertex.size.x, vertex.size.y) * 0.5);
+    result.sweep = vertex.sweep;
+    return result;
+}
+
+// Signed distance from the edge of a rounded box centered on the origin.
+fn rounded_box(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + vec2<f32>(radius, radius);
+    return length(max(q, vec2<f32>(0.0, 0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+// Fragment shader
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let half_size = vertex.size * 0.5;
+    let dist = rounded_box(vertex.local_pos - half_size, half_size, vertex.radius);
+
+    if (dist > 0.0) {
+        discard;
+    }
+
+    // Turns clockwise from the top, 0.0 to 1.0.
+    if (vertex.sweep < 1.0) {
+        let p = vertex.local_pos - half_size;
+        let turn = fract(atan2(p.x, p.y) / 6.2831853 + 1.0);
+
+        if (turn >= vertex.sweep) {
+            discard;
+        }
+    }
+
+    var color = vertex.color;
+
+    if (vertex.border_width > 0.0 && dist > -vertex.border_width) {
+        color = vertex.border_color;
+    }
+
+    if (color.a <= 0.0) {
+        discard;
+    }
+
+    return color;
+}
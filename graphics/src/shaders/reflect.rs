@@ -0,0 +1,661 @@
+use super::ShaderError;
+use crate::{BufferLayout, EngineUniform, GpuDevice};
+use crevice::std140::AsStd140;
+use naga::{
+    front::wgsl,
+    valid::{Capabilities, GlobalUse, ModuleInfo, ValidationFlags, Validator},
+    AddressSpace, ArraySize, Binding, GlobalVariable, ImageClass,
+    ImageDimension, Module, ScalarKind, ShaderStage, StorageAccess,
+    StorageFormat, Type, TypeInner, VectorSize,
+};
+use std::{collections::BTreeMap, num::NonZeroU64};
+
+/// Bind group and vertex layouts read from a WGSL source, so they can be
+/// built from the shader instead of by hand and the Rust side types can be
+/// checked against what the shader declares. The light layouts are built
+/// this way. Layouts shared between shaders, like the system and texture
+/// ones, stay written out since one shader's reflection only sees the
+/// stages that shader uses.
+pub struct ShaderReflection {
+    name: String,
+    module: Module,
+    info: ModuleInfo,
+}
+
+impl ShaderReflection {
+    pub fn new(name: &str, source: &str) -> Result<Self, ShaderError> {
+        let module =
+            wgsl::parse_str(source).map_err(|e| ShaderError::Parse {
+                name: name.to_owned(),
+                message: e.emit_to_string_with_path(source, name),
+            })?;
+
+        // Only the analysis is needed here, device support is checked by
+        // the ShaderValidator.
+        let info = Validator::new(ValidationFlags::all(), Capabilities::all())
+            .validate(&module)
+            .map_err(|e| ShaderError::Validation {
+                name: name.to_owned(),
+                message: e.emit_to_string_with_path(source, name),
+            })?;
+
+        Ok(Self {
+            name: name.to_owned(),
+            module,
+            info,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn mismatch(&self, message: String) -> ShaderError {
+        ShaderError::Mismatch {
+            name: self.name.clone(),
+            message,
+        }
+    }
+
+    /// Bind group indices the shader declares resources in.
+    pub fn groups(&self) -> Vec<u32> {
+        let mut groups: Vec<u32> = self
+            .module
+            .global_variables
+            .iter()
+            .filter_map(|(_, var)| var.binding.as_ref().map(|b| b.group))
+            .collect();
+
+        groups.sort_unstable();
+        groups.dedup();
+        groups
+    }
+
+    fn global(&self, group: u32, binding: u32) -> Option<&GlobalVariable> {
+        self.module.global_variables.iter().find_map(|(_, var)| {
+            var.binding
+                .as_ref()
+                .filter(|b| b.group == group && b.binding == binding)
+                .map(|_| var)
+        })
+    }
+
+    /// Stages of the entry points that use the variable.
+    fn visibility(
+        &self,
+        handle: naga::Handle<GlobalVariable>,
+    ) -> wgpu::ShaderStages {
+        let mut stages = wgpu::ShaderStages::NONE;
+
+        for (index, entry_point) in self.module.entry_points.iter().enumerate()
+        {
+            if self.info.get_entry_point(index)[handle] == GlobalUse::empty() {
+                continue;
+            }
+
+            stages |= match entry_point.stage {
+                ShaderStage::Vertex => wgpu::ShaderStages::VERTEX,
+                ShaderStage::Fragment => wgpu::ShaderStages::FRAGMENT,
+                ShaderStage::Compute => wgpu::ShaderStages::COMPUTE,
+            };
+        }
+
+        stages
+    }
+
+    /// The layout entries of a bind group, in binding order. Resources no
+    /// entry point uses are still included, visible to no stage.
+    pub fn bind_group_entries(
+        &self,
+        group: u32,
+    ) -> Result<Vec<wgpu::BindGroupLayoutEntry>, ShaderError> {
+        let mut entries = BTreeMap::new();
+
+        for (handle, var) in self.module.global_variables.iter() {
+            let binding = match &var.binding {
+                Some(binding) if binding.group == group => binding.binding,
+                _ => continue,
+            };
+
+            let (ty, count) = match &self.module.types[var.ty].inner {
+                TypeInner::BindingArray { base, size } => {
+                    let count = match size {
+                        ArraySize::Constant(count) => Some(*count),
+                        ArraySize::Dynamic => {
+                            return Err(self.mismatch(format!(
+                                "binding {group}:{binding} is an unsized \
+                                 binding array"
+                            )))
+                        }
+                    };
+
+                    (*base, count)
+                }
+                _ => (var.ty, None),
+            };
+
+            entries.insert(
+                binding,
+                wgpu::BindGroupLayoutEntry {
+                    binding,
+                    visibility: self.visibility(handle),
+                    ty: self.binding_type(var.space, ty, group, binding)?,
+                    count,
+                },
+            );
+        }
+
+        Ok(entries.into_values().collect())
+    }
+
+    fn binding_type(
+        &self,
+        space: AddressSpace,
+        ty: naga::Handle<Type>,
+        group: u32,
+        binding: u32,
+    ) -> Result<wgpu::BindingType, ShaderError> {
+        let min_binding_size = NonZeroU64::new(
+            self.module.types[ty].inner.size(self.module.to_ctx()) as u64,
+        );
+
+        Ok(match (space, &self.module.types[ty].inner) {
+            (AddressSpace::Uniform, _) => wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size,
+            },
+            (AddressSpace::Storage { access }, inner) => {
+                // Runtime sized arrays have no fixed size to require.
+                let sized = !matches!(
+                    inner,
+                    TypeInner::Array {
+                        size: ArraySize::Dynamic,
+                        ..
+                    }
+                );
+
+                wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage {
+                        read_only: !access.contains(StorageAccess::STORE),
+                    },
+                    has_dynamic_offset: false,
+                    min_binding_size: min_binding_size.filter(|_| sized),
+                }
+            }
+            (AddressSpace::Handle, TypeInner::Sampler { comparison }) => {
+                wgpu::BindingType::Sampler(if *comparison {
+                    wgpu::SamplerBindingType::Comparison
+                } else {
+                    wgpu::SamplerBindingType::Filtering
+                })
+            }
+            (
+                AddressSpace::Handle,
+                TypeInner::Image {
+                    dim,
+                    arrayed,
+                    class,
+                },
+            ) => {
+                let view_dimension = view_dimension(*dim, *arrayed);
+
+                match class {
+                    ImageClass::Sampled { kind, multi } => {
+                        wgpu::BindingType::Texture {
+                            sample_type: match kind {
+                                ScalarKind::Sint => {
+                                    wgpu::TextureSampleType::Sint
+                                }
+                                ScalarKind::Uint => {
+                                    wgpu::TextureSampleType::Uint
+                                }
+                                _ => wgpu::TextureSampleType::Float {
+                                    filterable: !multi,
+                                },
+                            },
+                            view_dimension,
+                            multisampled: *multi,
+                        }
+                    }
+                    ImageClass::Depth { multi } => wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension,
+                        multisampled: *multi,
+                    },
+                    ImageClass::Storage { format, access } => {
+                        wgpu::BindingType::StorageTexture {
+                            access: if !access.contains(StorageAccess::LOAD) {
+                                wgpu::StorageTextureAccess::WriteOnly
+                            } else if !access.contains(StorageAccess::STORE) {
+                                wgpu::StorageTextureAccess::ReadOnly
+                            } else {
+                                wgpu::StorageTextureAccess::ReadWrite
+                            },
+                            format: texture_format(*format),
+                            view_dimension,
+                        }
+                    }
+                }
+            }
+            _ => {
+                return Err(self.mismatch(format!(
+                    "binding {group}:{binding} has a type that can't be bound"
+                )))
+            }
+        })
+    }
+
+    /// Builds the layout of a bind group straight from the shader.
+    pub fn create_bind_group_layout(
+        &self,
+        gpu_device: &GpuDevice,
+        group: u32,
+    ) -> Result<wgpu::BindGroupLayout, ShaderError> {
+        let entries = self.bind_group_entries(group)?;
+
+        Ok(gpu_device.device().create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: Some(&format!("{} group {group}", self.name)),
+                entries: &entries,
+            },
+        ))
+    }
+
+    /// Size in bytes the shader's uniform at group and binding reads.
+    pub fn uniform_size(&self, group: u32, binding: u32) -> Option<u64> {
+        self.global(group, binding)
+            .filter(|var| var.space == AddressSpace::Uniform)
+            .map(|var| {
+                self.module.types[var.ty].inner.size(self.module.to_ctx())
+                    as u64
+            })
+    }
+
+    /// Checks a buffer of size bytes covers the uniform, like one packed
+    /// from several structs.
+    pub fn check_uniform_size(
+        &self,
+        group: u32,
+        binding: u32,
+        size: u64,
+    ) -> Result<(), ShaderError> {
+        match self.uniform_size(group, binding) {
+            Some(expected) if size >= expected => Ok(()),
+            Some(expected) => Err(self.mismatch(format!(
+                "uniform {group}:{binding} is {expected} bytes but the buffer \
+                 is only {size}"
+            ))),
+            None => {
+                Err(self.mismatch(format!("no uniform at {group}:{binding}")))
+            }
+        }
+    }
+
+    /// Checks the std140 layout of T is exactly the size of the uniform.
+    pub fn check_uniform<T: AsStd140>(
+        &self,
+        group: u32,
+        binding: u32,
+    ) -> Result<(), ShaderError> {
+        let size = T::std140_size_static() as u64;
+
+        match self.uniform_size(group, binding) {
+            Some(expected) if size == expected => Ok(()),
+            Some(expected) => Err(self.mismatch(format!(
+                "uniform {group}:{binding} is {expected} bytes but {} is {size}",
+                std::any::type_name::<T>()
+            ))),
+            None => Err(self.mismatch(format!(
+                "no uniform at {group}:{binding}"
+            ))),
+        }
+    }
+
+    /// Checks the derived layout of T is exactly the size of the uniform.
+    pub fn check_engine_uniform<T: EngineUniform>(
+        &self,
+        group: u32,
+        binding: u32,
+    ) -> Result<(), ShaderError> {
+        let size = T::SIZE as u64;
+
+        match self.uniform_size(group, binding) {
+            Some(expected) if size == expected => Ok(()),
+            Some(expected) => Err(self.mismatch(format!(
+                "uniform {group}:{binding} is {expected} bytes but {} is {size}",
+                std::any::type_name::<T>()
+            ))),
+            None => Err(self.mismatch(format!(
+                "no uniform at {group}:{binding}"
+            ))),
+        }
+    }
+
+    /// The vertex inputs of an entry point as tightly packed attributes in
+    /// location order, for a vertex struct laid out the same way.
+    pub fn vertex_attributes(
+        &self,
+        entry_point: &str,
+    ) -> Result<Vec<wgpu::VertexAttribute>, ShaderError> {
+        let mut offset = 0;
+
+        self.vertex_inputs(entry_point)?
+            .into_iter()
+            .map(|(location, format)| {
+                let attribute = wgpu::VertexAttribute {
+                    format,
+                    offset,
+                    shader_location: location,
+                };
+
+                offset += format.size();
+                Ok(attribute)
+            })
+            .collect()
+    }
+
+    /// Location and format of every vertex input, sorted by location.
+    fn vertex_inputs(
+        &self,
+        entry_point: &str,
+    ) -> Result<Vec<(u32, wgpu::VertexFormat)>, ShaderError> {
+        let entry = self
+            .module
+            .entry_points
+            .iter()
+            .find(|e| e.name == entry_point && e.stage == ShaderStage::Vertex)
+            .ok_or_else(|| {
+                self.mismatch(format!("no vertex entry point {entry_point}"))
+            })?;
+
+        let mut inputs = Vec::new();
+        let mut add = |binding: &Option<Binding>, ty: naga::Handle<Type>| {
+            if let Some(Binding::Location { location, .. }) = binding {
+                let format = vertex_format(&self.module.types[ty].inner)
+                    .ok_or_else(|| {
+                        self.mismatch(format!(
+                            "vertex input {location} has no vertex format"
+                        ))
+                    })?;
+
+                inputs.push((*location, format));
+            }
+
+            Ok(())
+        };
+
+        for argument in &entry.function.arguments {
+            match &self.module.types[argument.ty].inner {
+                TypeInner::Struct { members, .. }
+                    if argument.binding.is_none() =>
+                {
+                    for member in members {
+                        add(&member.binding, member.ty)?;
+                    }
+                }
+                _ => add(&argument.binding, argument.ty)?,
+            }
+        }
+
+        inputs.sort_by_key(|(location, _)| *location);
+        Ok(inputs)
+    }
+
+    /// Checks every vertex input of the entry point is fed by one of the
+    /// attributes with a format of the same kind and size, and that no
+    /// attribute goes unread.
+    pub fn check_vertex_attributes(
+        &self,
+        entry_point: &str,
+        attributes: &[wgpu::VertexAttribute],
+    ) -> Result<(), ShaderError> {
+        let inputs = self.vertex_inputs(entry_point)?;
+
+        for (location, format) in &inputs {
+            let attribute = attributes
+                .iter()
+                .find(|a| a.shader_location == *location)
+                .ok_or_else(|| {
+                    self.mismatch(format!(
+                        "{entry_point} reads vertex input {location} but no \
+                         attribute provides it"
+                    ))
+                })?;
+
+            if shader_type(attribute.format) != shader_type(*format) {
+                return Err(self.mismatch(format!(
+                    "{entry_point} vertex input {location} is {format:?} \
+                     but the attribute is {:?}",
+                    attribute.format
+                )));
+            }
+        }
+
+        for attribute in attributes {
+            if !inputs.iter().any(|(l, _)| *l == attribute.shader_location) {
+                return Err(self.mismatch(format!(
+                    "attribute {} is not read by {entry_point}",
+                    attribute.shader_location
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks V's attributes, together with shared ones from other vertex
+    /// buffers like the static quad's, against the entry point, and that
+    /// they fit V's stride.
+    pub fn check_vertex_layout<V: BufferLayout>(
+        &self,
+        entry_point: &str,
+        shared: &[wgpu::VertexAttribute],
+    ) -> Result<(), ShaderError> {
+        let own = V::attributes();
+        let stride = V::stride() as u64;
+
+        if let Some(attribute) =
+            own.iter().find(|a| a.offset + a.format.size() > stride)
+        {
+            return Err(self.mismatch(format!(
+                "attribute {} of {} ends past its stride of {stride}",
+                attribute.shader_location,
+                std::any::type_name::<V>()
+            )));
+        }
+
+        let attributes: Vec<_> = own.iter().chain(shared).copied().collect();
+
+        self.check_vertex_attributes(entry_point, &attributes)
+    }
+}
+
+fn view_dimension(
+    dim: ImageDimension,
+    arrayed: bool,
+) -> wgpu::TextureViewDimension {
+    match (dim, arrayed) {
+        (ImageDimension::D1, _) => wgpu::TextureViewDimension::D1,
+        (ImageDimension::D2, false) => wgpu::TextureViewDimension::D2,
+        (ImageDimension::D2, true) => wgpu::TextureViewDimension::D2Array,
+        (ImageDimension::D3, _) => wgpu::TextureViewDimension::D3,
+        (ImageDimension::Cube, false) => wgpu::TextureViewDimension::Cube,
+        (ImageDimension::Cube, true) => wgpu::TextureViewDimension::CubeArray,
+    }
+}
+
+fn vertex_format(inner: &TypeInner) -> Option<wgpu::VertexFormat> {
+    use wgpu::VertexFormat::*;
+
+    let (kind, size) = match *inner {
+        TypeInner::Scalar { kind, width: 4 } => (kind, 1),
+        TypeInner::Vector {
+            size,
+            kind,
+            width: 4,
+        } => (
+            kind,
+            match size {
+                VectorSize::Bi => 2,
+                VectorSize::Tri => 3,
+                VectorSize::Quad => 4,
+            },
+        ),
+        _ => return None,
+    };
+
+    Some(match (kind, size) {
+        (ScalarKind::Float, 1) => Float32,
+        (ScalarKind::Float, 2) => Float32x2,
+        (ScalarKind::Float, 3) => Float32x3,
+        (ScalarKind::Float, 4) => Float32x4,
+        (ScalarKind::Uint, 1) => Uint32,
+        (ScalarKind::Uint, 2) => Uint32x2,
+        (ScalarKind::Uint, 3) => Uint32x3,
+        (ScalarKind::Uint, 4) => Uint32x4,
+        (ScalarKind::Sint, 1) => Sint32,
+        (ScalarKind::Sint, 2) => Sint32x2,
+        (ScalarKind::Sint, 3) => Sint32x3,
+        (ScalarKind::Sint, 4) => Sint32x4,
+        _ => return None,
+    })
+}
+
+/// The scalar kind and component count a vertex format is read as.
+fn shader_type(format: wgpu::VertexFormat) -> (ScalarKind, u64) {
+    use wgpu::VertexFormat::*;
+
+    let kind = match format {
+        Uint8x2 | Uint8x4 | Uint16x2 | Uint16x4 | Uint32 | Uint32x2
+        | Uint32x3 | Uint32x4 => ScalarKind::Uint,
+        Sint8x2 | Sint8x4 | Sint16x2 | Sint16x4 | Sint32 | Sint32x2
+        | Sint32x3 | Sint32x4 => ScalarKind::Sint,
+        _ => ScalarKind::Float,
+    };
+
+    let components = match format {
+        Float64 | Float32 | Uint32 | Sint32 => 1,
+        Float64x3 | Float32x3 | Uint32x3 | Sint32x3 => 3,
+        Uint8x4 | Sint8x4 | Unorm8x4 | Snorm8x4 | Uint16x4 | Sint16x4
+        | Unorm16x4 | Snorm16x4 | Float16x4 | Float32x4 | Uint32x4
+        | Sint32x4 | Float64x4 => 4,
+        _ => 2,
+    };
+
+    (kind, components)
+}
+
+fn texture_format(format: StorageFormat) -> wgpu::TextureFormat {
+    use wgpu::TextureFormat as Tf;
+
+    match format {
+        StorageFormat::R8Unorm => Tf::R8Unorm,
+        StorageFormat::R8Snorm => Tf::R8Snorm,
+        StorageFormat::R8Uint => Tf::R8Uint,
+        StorageFormat::R8Sint => Tf::R8Sint,
+        StorageFormat::R16Uint => Tf::R16Uint,
+        StorageFormat::R16Sint => Tf::R16Sint,
+        StorageFormat::R16Float => Tf::R16Float,
+        StorageFormat::Rg8Unorm => Tf::Rg8Unorm,
+        StorageFormat::Rg8Snorm => Tf::Rg8Snorm,
+        StorageFormat::Rg8Uint => Tf::Rg8Uint,
+        StorageFormat::Rg8Sint => Tf::Rg8Sint,
+        StorageFormat::R32Uint => Tf::R32Uint,
+        StorageFormat::R32Sint => Tf::R32Sint,
+        StorageFormat::R32Float => Tf::R32Float,
+        StorageFormat::Rg16Uint => Tf::Rg16Uint,
+        StorageFormat::Rg16Sint => Tf::Rg16Sint,
+        StorageFormat::Rg16Float => Tf::Rg16Float,
+        StorageFormat::Rgba8Unorm => Tf::Rgba8Unorm,
+        StorageFormat::Rgba8Snorm => Tf::Rgba8Snorm,
+        StorageFormat::Rgba8Uint => Tf::Rgba8Uint,
+        StorageFormat::Rgba8Sint => Tf::Rgba8Sint,
+        StorageFormat::Bgra8Unorm => Tf::Bgra8Unorm,
+        StorageFormat::Rgb10a2Uint => Tf::Rgb10a2Uint,
+        StorageFormat::Rgb10a2Unorm => Tf::Rgb10a2Unorm,
+        StorageFormat::Rg11b10Float => Tf::Rg11b10Float,
+        StorageFormat::Rg32Uint => Tf::Rg32Uint,
+        StorageFormat::Rg32Sint => Tf::Rg32Sint,
+        StorageFormat::Rg32Float => Tf::Rg32Float,
+        StorageFormat::Rgba16Uint => Tf::Rgba16Uint,
+        StorageFormat::Rgba16Sint => Tf::Rgba16Sint,
+        StorageFormat::Rgba16Float => Tf::Rgba16Float,
+        StorageFormat::Rgba32Uint => Tf::Rgba32Uint,
+        StorageFormat::Rgba32Sint => Tf::Rgba32Sint,
+        StorageFormat::Rgba32Float => Tf::Rgba32Float,
+        StorageFormat::R16Unorm => Tf::R16Unorm,
+        StorageFormat::R16Snorm => Tf::R16Snorm,
+        StorageFormat::Rg16Unorm => Tf::Rg16Unorm,
+        StorageFormat::Rg16Snorm => Tf::Rg16Snorm,
+        StorageFormat::Rgba16Unorm => Tf::Rgba16Unorm,
+        StorageFormat::Rgba16Snorm => Tf::Rgba16Snorm,
+    }
+}
+
+type LayoutCheck =
+    fn(&ShaderReflection, &[wgpu::VertexAttribute]) -> Result<(), ShaderError>;
+
+/// Checks the crate's vertex types and the system uniform against the
+/// embedded shaders. Run at startup in debug builds.
+pub fn validate_layouts() -> Result<(), Vec<ShaderError>> {
+    use crate::{shaders::*, StaticBufferObject};
+
+    let quad = [StaticBufferObject::vertex_attribute()];
+    let mut errors = Vec::new();
+    // Each check belongs to a feature, so there may be none.
+    #[allow(unused_mut)]
+    let mut checks: Vec<(&str, &str, LayoutCheck)> = Vec::new();
+
+    #[cfg(feature = "sprites")]
+    checks.push(("imageshader", IMAGE_SHADER, |r, q| {
+        r.check_vertex_layout::<crate::ImageVertex>("vertex", q)
+    }));
+    #[cfg(feature = "maps")]
+    checks.push(("mapshader", MAP_SHADER, |r, q| {
+        r.check_vertex_layout::<crate::MapVertex>("vertex", q)
+    }));
+    #[cfg(feature = "maps")]
+    checks.push(("watershader", WATER_SHADER, |r, q| {
+        r.check_vertex_layout::<crate::WaterVertex>("vertex", q)
+    }));
+    #[cfg(feature = "shapes")]
+    checks.push(("2dmeshshader", MESH2D_SHADER, |r, _| {
+        r.check_vertex_layout::<crate::Mesh2DVertex>("vertex", &[])
+    }));
+    #[cfg(feature = "shapes")]
+    checks.push(("rectshader", RECT_SHADER, |r, q| {
+        r.check_vertex_layout::<crate::RectVertex>("vertex", q)
+    }));
+    #[cfg(all(feature = "sprites", feature = "shapes"))]
+    checks.push(("selectionshader", SELECTION_SHADER, |r, q| {
+        r.check_vertex_layout::<crate::SelectionVertex>("vertex", q)
+    }));
+    #[cfg(feature = "text")]
+    checks.push(("textshader", TEXT_SHADER, |r, q| {
+        r.check_vertex_layout::<crate::TextVertex>("vertex", q)
+    }));
+
+    for (name, source, check) in checks {
+        let reflection = match ShaderReflection::new(name, source) {
+            Ok(reflection) => reflection,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+
+        errors.extend(check(&reflection, &quad).err());
+        errors.extend(
+            reflection
+                .check_engine_uniform::<crate::GlobalUniform>(0, 0)
+                .err(),
+        );
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
@@ -0,0 +1,142 @@
+struct VertexInput {
+    @builtin(vertex_index) vertex_idx: u32,
+    @location(0) v_pos: vec2<f32>,
+    @location(1) position: vec3<f32>,
+    @location(2) hw: vec2<f32>,
+    @location(3) tex_data: vec4<f32>,
+    @location(4) color: u32,
+    @location(5) width: f32,
+    @location(6) layer: i32,
+    @location(7) use_camera: u32,
+    @location(8) kind: u32,
+    @location(9) pulse_speed: f32,
+};
+
+struct VertexOutput {
+    @invariant @builtin(position) clip_position: vec4<f32>,
+    @location(0) local_pos: vec2<f32>,
+    @location(1) hw: vec2<f32>,
+    @location(2) tex_data: vec4<f32>,
+    @location(3) color: vec4<f32>,
+    @location(4) width: f32,
+    @location(5) layer: i32,
+    @location(6) kind: u32,
+    @location(7) pulse_speed: f32,
+};
+
+@group(1)
+@binding(0)
+var tex: texture_2d_array<f32>;
+@group(1)
+@binding(1)
+var tex_sample: sampler;
+
+fn unpack_color(color: u32) -> vec4<f32> {
+    return vec4<f32>(
+        f32((color & 0xff0000u) >> 16u),
+        f32((color & 0xff00u) >> 8u),
+        f32((color & 0xffu)),
+        f32((color & 0xff000000u) >> 24u),
+    ) / 255.0;
+}
+
+@vertex
+fn vertex(
+    vertex: VertexInput,
+) -> VertexOutput {
+    var result: VertexOutput;
+    let v = vertex.vertex_idx % 4u;
+    // Grow the quad so the outline has room around the object.
+    let grow = select(0.0, vertex.width, vertex.kind == 0u);
+    var pos = snap_instance(vertex.position, vertex.use_camera)
+        - vec3<f32>(grow, grow, 0.0);
+    let size = vertex.hw + vec2<f32>(grow * 2.0);
+
+    switch v {
+        case 1u: {
+            pos.x += size.x;
+        }
+        case 2u: {
+            pos.x += size.x;
+            pos.y += size.y;
+        }
+        case 3u: {
+            pos.y += size.y;
+        }
+        default: {}
+    }
+
+    if (vertex.use_camera == 1u) {
+        result.clip_position = (global.proj * camera_view(1.0)) * vec4<f32>(pos, 1.0);
+    } else {
+        result.clip_position = global.proj * vec4<f32>(pos, 1.0);
+    }
+
+    result.local_pos = pos.xy - vertex.position.xy;
+    result.hw = vertex.hw;
+    result.tex_data = vertex.tex_data;
+    result.color = unpack_color(vertex.color);
+    result.width = vertex.width;
+    result.layer = vertex.layer;
+    result.kind = vertex.kind;
+    result.pulse_speed = vertex.pulse_speed;
+    return result;
+}
+
+// Alpha of the object at a position relative to its bottom left corner.
+fn shape_alpha(vertex: VertexOutput, local: vec2<f32>) -> f32 {
+    if (any(local < vec2<f32>(0.0)) || any(local >= vertex.hw)) {
+        return 0.0;
+    }
+
+    if (vertex.layer < 0) {
+        return 1.0;
+    }
+
+    let size = textureDimensions(tex);
+    let fsize = vec2<f32>(f32(size.x), f32(size.y));
+    // Textures are stored top down while y goes up on screen.
+    let texel = vec2<f32>(
+        vertex.tex_data.x + local.x / vertex.hw.x * vertex.tex_data.z,
+        vertex.tex_data.y + (1.0 - local.y / vertex.hw.y) * vertex.tex_data.w,
+    );
+
+    return textureSampleLevel(tex, tex_sample, texel / fsize, vertex.layer, 0.0).a;
+}
+
+// Fragment shader
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let own = shape_alpha(vertex, vertex.local_pos);
+    var alpha = own;
+
+    if (vertex.kind == 0u) {
+        if (own > 0.5) {
+            discard;
+        }
+
+        var near = 0.0;
+
+        for (var i = 0u; i < 8u; i += 1u) {
+            let angle = f32(i) * 0.785398;
+            let dir = vec2<f32>(cos(angle), sin(angle));
+
+            near = max(near, shape_alpha(vertex, vertex.local_pos + dir * vertex.width));
+            near = max(near, shape_alpha(vertex, vertex.local_pos + dir * vertex.width * 0.5));
+        }
+
+        alpha = step(0.5, near);
+    }
+
+    if (vertex.pulse_speed > 0.0) {
+        alpha *= 0.65 + 0.35 * sin(global.seconds * vertex.pulse_speed);
+    }
+
+    let color = vec4<f32>(vertex.color.rgb, vertex.color.a * alpha);
+
+    if (color.a <= 0.0) {
+        discard;
+    }
+
+    return color;
+}
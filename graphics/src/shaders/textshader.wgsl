@@ -1,17 +1,3 @@
-struct Global {
-    view: mat4x4<f32>,
-    proj: mat4x4<f32>,
-    inverse_proj: mat4x4<f32>,
-    eye: vec3<f32>,
-    scale: f32,
-    size: vec2<f32>,
-    seconds: f32,
-};
-
-@group(0)
-@binding(0)
-var<uniform> global: Global;
-
 struct VertexInput {
     @builtin(vertex_index) vertex_idx: u32,
     @location(0) v_pos: vec2<f32>,
@@ -22,6 +8,8 @@ struct VertexInput {
     @location(5) color: u32,
     @location(6) use_camera: u32,
     @location(7) is_color: u32,
+    @location(8) rotation: f32,
+    @location(9) scale: f32,
 };
 
 struct VertexOutput {
@@ -60,7 +48,7 @@ fn vertex(
     vertex: VertexInput,
 ) -> VertexOutput {
     var result: VertexOutput;
-    var pos = vertex.pos;
+    var pos = snap_instance(vertex.pos, vertex.use_camera);
     var size = vec2<u32>(0u);
 
     if vertex.is_color == 1u {
@@ -71,28 +59,37 @@ fn vertex(
 
     let fsize = vec2<f32> (f32(size.x), f32(size.y));
     let v = vertex.vertex_idx % 4u;
+    var corner = vec2<f32>(0.0);
 
     switch v {
         case 1u: {
             result.uv = vec2<f32>(vertex.uv.x + vertex.hw.x, vertex.uv.y + vertex.hw.y) /  fsize;
-            pos.x += vertex.hw.x;
+            corner.x = vertex.hw.x;
         }
         case 2u: {
             result.uv = vec2<f32>(vertex.uv.x + vertex.hw.x, vertex.uv.y) /  fsize;
-            pos.x += vertex.hw.x;
-            pos.y += vertex.hw.y;
+            corner = vertex.hw;
         }
         case 3u: {
             result.uv = vec2<f32>(vertex.uv.x, vertex.uv.y) /  fsize;
-            pos.y += vertex.hw.y;
+            corner.y = vertex.hw.y;
         }
         default: {
             result.uv = vec2<f32>(vertex.uv.x, vertex.uv.y + vertex.hw.y) /  fsize;
         }
     }
 
+    // Per glyph transform around the quad's center.
+    let center = vertex.hw * 0.5;
+    let local = (corner - center) * vertex.scale;
+    let c = cos(vertex.rotation);
+    let s = sin(vertex.rotation);
+
+    pos.x += center.x + local.x * c - local.y * s;
+    pos.y += center.y + local.x * s + local.y * c;
+
     if (vertex.use_camera == 1u) {
-        result.position = (global.proj * global.view) * vec4<f32>(pos.xyz, 1.0);
+        result.position = (global.proj * camera_view(1.0)) * vec4<f32>(pos.xyz, 1.0);
     } else {
         result.position = global.proj * vec4<f32>(pos.xyz, 1.0);
     }
@@ -0,0 +1,104 @@
+struct Transition {
+    center: vec2<f32>,
+    size: vec2<f32>,
+    color: u32,
+    coverage: f32,
+    kind: u32,
+    direction: u32,
+    max_pixel_size: f32,
+    softness: f32,
+};
+
+@group(0)
+@binding(0)
+var<uniform> transition: Transition;
+@group(0)
+@binding(1)
+var frame_tex: texture_2d<f32>;
+@group(0)
+@binding(2)
+var frame_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+fn unpack_color(color: u32) -> vec4<f32> {
+    return vec4<f32>(
+        f32((color & 0xff0000u) >> 16u),
+        f32((color & 0xff00u) >> 8u),
+        f32((color & 0xffu)),
+        f32((color & 0xff000000u) >> 24u),
+    ) / 255.0;
+}
+
+@vertex
+fn vertex(
+    @builtin(vertex_index) vertex_idx: u32,
+) -> VertexOutput {
+    var result: VertexOutput;
+    // One triangle that covers the whole screen.
+    let x = f32((vertex_idx << 1u) & 2u);
+    let y = f32(vertex_idx & 2u);
+
+    result.clip_position = vec4<f32>(x * 2.0 - 1.0, y * 2.0 - 1.0, 0.0, 1.0);
+    return result;
+}
+
+// Fragment shader
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let color = unpack_color(transition.color);
+    let pos = vertex.clip_position.xy;
+    let size = transition.size;
+    let soft = max(transition.softness, 1.0);
+    let coverage = transition.coverage;
+
+    switch transition.kind {
+        // Wipe
+        case 1u: {
+            var along = pos.x;
+            var span = size.x;
+
+            switch transition.direction {
+                case 1u: {
+                    along = size.x - pos.x;
+                }
+                case 2u: {
+                    along = pos.y;
+                    span = size.y;
+                }
+                case 3u: {
+                    along = size.y - pos.y;
+                    span = size.y;
+                }
+                default: {}
+            }
+
+            let edge = coverage * (span + soft);
+            let alpha = clamp((edge - along) / soft, 0.0, 1.0);
+            return vec4<f32>(color.rgb, color.a * alpha);
+        }
+        // Iris
+        case 2u: {
+            let corner = max(transition.center, size - transition.center);
+            let radius = (1.0 - coverage) * (length(corner) + soft) - soft;
+            let dist = distance(pos, transition.center);
+            let alpha = clamp((dist - radius) / soft, 0.0, 1.0);
+            return vec4<f32>(color.rgb, color.a * alpha);
+        }
+        // Pixelate
+        case 3u: {
+            let block = mix(1.0, transition.max_pixel_size, coverage);
+            let uv = (floor(pos / block) + 0.5) * block / size;
+            let frame = textureSampleLevel(frame_tex, frame_sampler, uv, 0.0);
+            // Fade to the color at the end so the screen is fully covered.
+            let fade = smoothstep(0.5, 1.0, coverage) * color.a;
+            return vec4<f32>(mix(frame.rgb, color.rgb, fade), 1.0);
+        }
+        // Fade
+        default: {
+            return vec4<f32>(color.rgb, color.a * coverage);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+struct Upscale {
+    source_size: vec2<f32>,
+    output_size: vec2<f32>,
+    sharpness: f32,
+};
+
+@group(0)
+@binding(0)
+var<uniform> upscale: Upscale;
+@group(0)
+@binding(1)
+var source_tex: texture_2d<f32>;
+@group(0)
+@binding(2)
+var source_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+@vertex
+fn vertex(
+    @builtin(vertex_index) vertex_idx: u32,
+) -> VertexOutput {
+    var result: VertexOutput;
+    // One triangle that covers the whole screen.
+    let x = f32((vertex_idx << 1u) & 2u);
+    let y = f32(vertex_idx & 2u);
+
+    result.clip_position = vec4<f32>(x * 2.0 - 1.0, y * 2.0 - 1.0, 0.0, 1.0);
+    return result;
+}
+
+fn fetch(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(source_tex, source_sampler, uv, 0.0).rgb;
+}
+
+// Fragment shader
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let uv = vertex.clip_position.xy / upscale.output_size;
+    let center = textureSampleLevel(source_tex, source_sampler, uv, 0.0);
+
+    if (upscale.sharpness <= 0.0) {
+        return center;
+    }
+
+    // Contrast adaptive sharpening over the source texels around it, so
+    // edges softened by the bilinear upscale get some of their detail back
+    // without ringing where the contrast is already high.
+    let texel = 1.0 / upscale.source_size;
+    let up = fetch(uv - vec2<f32>(0.0, texel.y));
+    let down = fetch(uv + vec2<f32>(0.0, texel.y));
+    let left = fetch(uv - vec2<f32>(texel.x, 0.0));
+    let right = fetch(uv + vec2<f32>(texel.x, 0.0));
+
+    let low = min(center.rgb, min(min(up, down), min(left, right)));
+    let high = max(center.rgb, max(max(up, down), max(left, right)));
+    let headroom = min(low, 1.0 - high) / max(high, vec3<f32>(0.0001));
+    let amount = sqrt(clamp(headroom, vec3<f32>(0.0), vec3<f32>(1.0)));
+    let weight = -amount * mix(0.125, 0.2, upscale.sharpness);
+    let color = (center.rgb + (up + down + left + right) * weight)
+        / (1.0 + 4.0 * weight);
+
+    return vec4<f32>(clamp(color, vec3<f32>(0.0), vec3<f32>(1.0)), center.a);
+}
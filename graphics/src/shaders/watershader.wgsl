@@ -0,0 +1,106 @@
+struct Water {
+    tint: u32,
+    ripple_speed: f32,
+    ripple_strength: f32,
+    ripple_scale: f32,
+    reflectivity: f32,
+    reflection_depth: f32,
+    reflect: u32,
+    padding: f32,
+};
+
+@group(1)
+@binding(0)
+var<uniform> water: Water;
+@group(1)
+@binding(1)
+var reflection_tex: texture_2d<f32>;
+@group(1)
+@binding(2)
+var reflection_sample: sampler;
+
+struct VertexInput {
+    @builtin(vertex_index) vertex_idx: u32,
+    @location(0) v_pos: vec2<f32>,
+    @location(1) position: vec3<f32>,
+    @location(2) tilesize: f32,
+    @location(3) surface: f32,
+    @location(4) parallax: f32,
+};
+
+struct VertexOutput {
+    @invariant @builtin(position) clip_position: vec4<f32>,
+    @location(0) world: vec3<f32>,
+    @location(1) surface: f32,
+    @location(2) parallax: f32,
+};
+
+fn unpack_color(color: u32) -> vec4<f32> {
+    return vec4<f32>(
+        f32((color & 0xff0000u) >> 16u),
+        f32((color & 0xff00u) >> 8u),
+        f32((color & 0xffu)),
+        f32((color & 0xff000000u) >> 24u),
+    ) / 255.0;
+}
+
+@vertex
+fn vertex(
+    vertex: VertexInput,
+) -> VertexOutput {
+    var result: VertexOutput;
+    var pos = vertex.position;
+    let v = vertex.vertex_idx % 4u;
+
+    switch v {
+        case 1u: {
+            pos.x += vertex.tilesize;
+        }
+        case 2u: {
+            pos.x += vertex.tilesize;
+            pos.y += vertex.tilesize;
+        }
+        case 3u: {
+            pos.y += vertex.tilesize;
+        }
+        default: {}
+    }
+
+    result.clip_position = (global.proj * camera_view(vertex.parallax))
+        * vec4<f32>(snap_instance(pos, 1u), 1.0);
+    result.world = pos;
+    result.surface = vertex.surface;
+    result.parallax = vertex.parallax;
+    return result;
+}
+
+// Fragment shader
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let tint = unpack_color(water.tint);
+
+    if (water.reflect == 0u) {
+        return tint;
+    }
+
+    let t = global.seconds * water.ripple_speed;
+    let ripple = vec2<f32>(
+        sin(vertex.world.y * water.ripple_scale + t),
+        cos(vertex.world.x * water.ripple_scale * 0.8 + t * 1.3),
+    ) * water.ripple_strength;
+
+    // Mirror around the water's top edge and find it on the screen.
+    let mirrored = vec2<f32>(vertex.world.x, 2.0 * vertex.surface - vertex.world.y) + ripple;
+    let clip = (global.proj * camera_view(vertex.parallax)) * vec4<f32>(mirrored, vertex.world.z, 1.0);
+    let uv = vec2<f32>(clip.x / clip.w * 0.5 + 0.5, 0.5 - clip.y / clip.w * 0.5);
+    let reflected = textureSampleLevel(reflection_tex, reflection_sample, uv, 0.0);
+
+    // Off screen there is nothing to reflect.
+    let on_screen = select(0.0, 1.0, all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0)));
+
+    // Strongest at the edge, fading out the deeper into the water.
+    let depth = clamp((vertex.surface - vertex.world.y) / water.reflection_depth, 0.0, 1.0);
+    let fresnel = water.reflectivity * (1.0 - depth) * (1.0 - depth) * on_screen;
+
+    return vec4<f32>(mix(tint.rgb, reflected.rgb, fresnel), max(tint.a, fresnel));
+}
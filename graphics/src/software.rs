@@ -0,0 +1,4 @@
+mod raster;
+mod scene;
+
+pub use scene::*;
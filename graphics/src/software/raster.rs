@@ -0,0 +1,374 @@
+use crate::{
+    AscendingError, CircleDesc, MapDesc, OtherError, RectDesc, SceneDesc,
+    SpriteDesc, TextDesc,
+};
+use cosmic_text::{
+    Attrs, Buffer, Color, Family, FontSystem, Metrics, Shaping, SwashCache,
+};
+use image::{Rgba, RgbaImage};
+
+/// Pass the GPU scene draws each kind in, which breaks ties in z the same
+/// way as its depth test.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum DrawPass {
+    LowerMap,
+    Sprite,
+    UpperMap,
+    Rect,
+    Circle,
+    Text,
+}
+
+enum Item<'a> {
+    Tile { map: &'a MapDesc, tile: usize },
+    Sprite(&'a SpriteDesc),
+    Rect(&'a RectDesc),
+    Circle(&'a CircleDesc),
+    Text(&'a TextDesc),
+}
+
+/// Render space of the GPU scene, from the bottom left, over an image
+/// stored from the top left.
+struct Canvas {
+    image: RgbaImage,
+}
+
+impl Canvas {
+    fn width(&self) -> i32 {
+        self.image.width() as i32
+    }
+
+    fn height(&self) -> i32 {
+        self.image.height() as i32
+    }
+
+    /// Alpha blends color over the pixel at x and y from the bottom left.
+    fn blend(&mut self, x: i32, y: i32, color: [u8; 4]) {
+        if color[3] == 0
+            || x < 0
+            || y < 0
+            || x >= self.width()
+            || y >= self.height()
+        {
+            return;
+        }
+
+        let row = (self.height() - 1 - y) as u32;
+        let Rgba(dst) = self.image.get_pixel_mut(x as u32, row);
+        let alpha = color[3] as f32 / 255.0;
+
+        for i in 0..3 {
+            dst[i] = (color[i] as f32 * alpha + dst[i] as f32 * (1.0 - alpha))
+                .round() as u8;
+        }
+
+        dst[3] =
+            (color[3] as f32 + dst[3] as f32 * (1.0 - alpha)).round() as u8;
+    }
+
+    /// Pixels whose centers are within the rectangle, as x and y from the
+    /// bottom left with their centers.
+    fn pixels(
+        &self,
+        min: [f32; 2],
+        max: [f32; 2],
+    ) -> impl Iterator<Item = (i32, i32, f32, f32)> {
+        let x0 = (min[0] - 0.5).ceil().max(0.0) as i32;
+        let y0 = (min[1] - 0.5).ceil().max(0.0) as i32;
+        let x1 = ((max[0] - 0.5).ceil() as i32).min(self.width());
+        let y1 = ((max[1] - 0.5).ceil() as i32).min(self.height());
+
+        (y0..y1).flat_map(move |y| {
+            (x0..x1).map(move |x| (x, y, x as f32 + 0.5, y as f32 + 0.5))
+        })
+    }
+}
+
+/// Z of a map layer, the same as `MapLayers::indexed_layerz` which is only
+/// there with the maps feature.
+fn tile_z(layer: u32) -> f32 {
+    match layer {
+        0 => 10.0,
+        1 => 9.0,
+        2 => 8.0,
+        3 => 7.0,
+        4 => 6.0,
+        5 => 5.0,
+        6 => 3.0,
+        _ => 2.0,
+    }
+}
+
+fn tint(texel: [u8; 4], color: [u8; 4]) -> [u8; 4] {
+    [0, 1, 2, 3].map(|i| ((texel[i] as u32 * color[i] as u32) / 255) as u8)
+}
+
+/// Nearest texel of the area for a pixel center u and v across it, 0.0 to
+/// 1.0 from the top left.
+fn sample(texture: &RgbaImage, area: [f32; 4], u: f32, v: f32) -> [u8; 4] {
+    let x = (area[0] + u * area[2]).floor() as i64;
+    let y = (area[1] + v * area[3]).floor() as i64;
+
+    if x < 0
+        || y < 0
+        || x >= texture.width() as i64
+        || y >= texture.height() as i64
+    {
+        return [0; 4];
+    }
+
+    texture.get_pixel(x as u32, y as u32).0
+}
+
+/// Same distance as the rect shader, negative inside.
+fn rounded_box(p: [f32; 2], half_size: [f32; 2], radius: f32) -> f32 {
+    let q = [
+        p[0].abs() - half_size[0] + radius,
+        p[1].abs() - half_size[1] + radius,
+    ];
+
+    (q[0].max(0.0).powi(2) + q[1].max(0.0).powi(2)).sqrt()
+        + q[0].max(q[1]).min(0.0)
+        - radius
+}
+
+impl SceneDesc {
+    /// Draws the sprites, map, rects, circles and text on the CPU, for
+    /// previews where there is no graphics adapter. Lights are left out.
+    ///
+    /// Matches the GPU render closely but not exactly: there is no
+    /// multisampling, blending is done in sRGB and glyphs are rasterized
+    /// by swash directly, so compare against goldens made the same way.
+    pub fn rasterize(
+        &self,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, AscendingError> {
+        let mut canvas = Canvas {
+            image: RgbaImage::from_pixel(width, height, Rgba(self.clear)),
+        };
+        let textures = self
+            .textures
+            .iter()
+            .map(|path| Ok(image::open(path)?.into_rgba8()))
+            .collect::<Result<Vec<_>, AscendingError>>()?;
+        let tilesheet = match &self.map {
+            Some(map) => Some(image::open(&map.tilesheet)?.into_rgba8()),
+            None => None,
+        };
+        let mut font_system = FontSystem::new();
+        let mut cache = SwashCache::new();
+
+        for font in &self.fonts {
+            font_system.db_mut().load_font_file(font)?;
+        }
+
+        let mut items = Vec::new();
+
+        if let Some(map) = &self.map {
+            for (index, tile) in map.tiles.iter().enumerate() {
+                let pass = if tile.pos[2] >= 6 {
+                    DrawPass::UpperMap
+                } else {
+                    DrawPass::LowerMap
+                };
+
+                items.push((
+                    tile_z(tile.pos[2]),
+                    pass,
+                    Item::Tile { map, tile: index },
+                ));
+            }
+        }
+
+        items.extend(
+            self.sprites.iter().map(|desc| {
+                (desc.pos[2], DrawPass::Sprite, Item::Sprite(desc))
+            }),
+        );
+        items.extend(
+            self.rects
+                .iter()
+                .map(|desc| (desc.pos[2], DrawPass::Rect, Item::Rect(desc))),
+        );
+        items.extend(
+            self.circles
+                .iter()
+                .map(|desc| (desc.z, DrawPass::Circle, Item::Circle(desc))),
+        );
+        items.extend(
+            self.texts
+                .iter()
+                .map(|desc| (desc.pos[2], DrawPass::Text, Item::Text(desc))),
+        );
+
+        // Back to front, lower z is in front. The sort is stable so equal
+        // ones keep their order within a pass.
+        items.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        for (_, _, item) in items {
+            match item {
+                Item::Tile { map, tile } => {
+                    if let Some(sheet) = &tilesheet {
+                        draw_tile(&mut canvas, sheet, map, tile);
+                    }
+                }
+                Item::Sprite(desc) => {
+                    let texture =
+                        textures.get(desc.texture).ok_or_else(|| {
+                            AscendingError::Other(OtherError::new(&format!(
+                                "sprite texture {} does not exist",
+                                desc.texture
+                            )))
+                        })?;
+
+                    draw_sprite(&mut canvas, texture, desc);
+                }
+                Item::Rect(desc) => draw_rect(&mut canvas, desc),
+                Item::Circle(desc) => draw_circle(&mut canvas, desc),
+                Item::Text(desc) => {
+                    draw_text(&mut canvas, &mut font_system, &mut cache, desc)
+                }
+            }
+        }
+
+        Ok(canvas.image)
+    }
+}
+
+fn draw_tile(
+    canvas: &mut Canvas,
+    sheet: &RgbaImage,
+    map: &MapDesc,
+    index: usize,
+) {
+    let tile = &map.tiles[index];
+    let size = map.tilesize as f32;
+    let per_row = (sheet.width() / map.tilesize).max(1);
+
+    // Id 0 is the empty tile the tilesheet uploads first.
+    let Some(id) = tile.id.checked_sub(1) else {
+        return;
+    };
+
+    let area = [
+        ((id % per_row) * map.tilesize) as f32,
+        ((id / per_row) * map.tilesize) as f32,
+        size,
+        size,
+    ];
+    let min = [
+        map.pos[0] + tile.pos[0] as f32 * size,
+        map.pos[1] + tile.pos[1] as f32 * size,
+    ];
+
+    for (x, y, cx, cy) in canvas.pixels(min, [min[0] + size, min[1] + size]) {
+        let u = (cx - min[0]) / size;
+        let v = 1.0 - (cy - min[1]) / size;
+
+        canvas.blend(x, y, tint(sample(sheet, area, u, v), tile.color));
+    }
+}
+
+fn draw_sprite(canvas: &mut Canvas, texture: &RgbaImage, desc: &SpriteDesc) {
+    let area = desc.uv.unwrap_or([
+        0.0,
+        0.0,
+        texture.width() as f32,
+        texture.height() as f32,
+    ]);
+    let min = [desc.pos[0], desc.pos[1]];
+    let max = [min[0] + desc.size[0], min[1] + desc.size[1]];
+
+    for (x, y, cx, cy) in canvas.pixels(min, max) {
+        let u = (cx - min[0]) / desc.size[0];
+        let v = 1.0 - (cy - min[1]) / desc.size[1];
+
+        canvas.blend(x, y, tint(sample(texture, area, u, v), desc.color));
+    }
+}
+
+fn draw_rect(canvas: &mut Canvas, desc: &RectDesc) {
+    let half_size = [desc.size[0] * 0.5, desc.size[1] * 0.5];
+    let radius = desc.radius.min(half_size[0].min(half_size[1]));
+    let min = [desc.pos[0], desc.pos[1]];
+    let max = [min[0] + desc.size[0], min[1] + desc.size[1]];
+
+    for (x, y, cx, cy) in canvas.pixels(min, max) {
+        let p = [cx - min[0] - half_size[0], cy - min[1] - half_size[1]];
+        let dist = rounded_box(p, half_size, radius);
+
+        if dist > 0.0 {
+            continue;
+        }
+
+        let color = if desc.border_width > 0.0 && dist > -desc.border_width {
+            desc.border_color
+        } else {
+            desc.color
+        };
+
+        canvas.blend(x, y, color);
+    }
+}
+
+fn draw_circle(canvas: &mut Canvas, desc: &CircleDesc) {
+    let [cx0, cy0] = desc.center;
+    // Strokes are centered on the radius like the tessellated mesh.
+    let (inner, outer) = match desc.stroke {
+        Some(width) => (desc.radius - width * 0.5, desc.radius + width * 0.5),
+        None => (0.0, desc.radius),
+    };
+
+    for (x, y, cx, cy) in
+        canvas.pixels([cx0 - outer, cy0 - outer], [cx0 + outer, cy0 + outer])
+    {
+        let dist = ((cx - cx0).powi(2) + (cy - cy0).powi(2)).sqrt();
+
+        if dist <= outer && (desc.stroke.is_none() || dist >= inner) {
+            canvas.blend(x, y, desc.color);
+        }
+    }
+}
+
+fn draw_text(
+    canvas: &mut Canvas,
+    font_system: &mut FontSystem,
+    cache: &mut SwashCache,
+    desc: &TextDesc,
+) {
+    let mut buffer =
+        Buffer::new(font_system, Metrics::new(desc.font_size, desc.font_size));
+    let attrs = match &desc.family {
+        Some(family) => Attrs::new().family(Family::Name(family)),
+        None => Attrs::new(),
+    };
+    let [r, g, b, a] = desc.color;
+    // Lines run down from the top of the text's area, clipped to it.
+    let top = (desc.pos[1] + desc.size[1]).round() as i32;
+    let left = desc.pos[0].round() as i32;
+    let (width, height) = (desc.size[0] as i32, desc.size[1] as i32);
+
+    buffer.set_size(font_system, desc.size[0], desc.size[1]);
+    buffer.set_text(font_system, &desc.text, attrs, Shaping::Advanced);
+    buffer.draw(
+        font_system,
+        cache,
+        Color::rgba(r, g, b, a),
+        |x, y, w, h, color| {
+            for py in y..y + h as i32 {
+                for px in x..x + w as i32 {
+                    if px < 0 || py < 0 || px >= width || py >= height {
+                        continue;
+                    }
+
+                    canvas.blend(
+                        left + px,
+                        top - 1 - py,
+                        [color.r(), color.g(), color.b(), color.a()],
+                    );
+                }
+            }
+        },
+    );
+}
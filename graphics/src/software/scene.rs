@@ -0,0 +1,199 @@
+use crate::AscendingError;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// A scene for golden tests and software previews, usually loaded from
+/// RON. Paths are relative to the scene file when loaded with
+/// [`SceneDesc::load`].
+///
+/// ```ron
+/// (
+///     textures: ["../../images/Female_1.png"],
+///     sprites: [(texture: 0, pos: (16, 16, 5), size: (48, 48))],
+///     rects: [(pos: (8, 8, 6), size: (32, 16), color: (255, 0, 0, 255))],
+/// )
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SceneDesc {
+    /// Background as RGBA from 0 to 255.
+    pub clear: [u8; 4],
+    /// Seed of the scene's randomness, like the light flicker.
+    pub seed: u64,
+    /// Font files loaded before any text, so text doesn't depend on the
+    /// fonts installed.
+    pub fonts: Vec<PathBuf>,
+    /// Images the sprites draw from, by index.
+    pub textures: Vec<PathBuf>,
+    pub sprites: Vec<SpriteDesc>,
+    pub rects: Vec<RectDesc>,
+    pub circles: Vec<CircleDesc>,
+    pub map: Option<MapDesc>,
+    pub lights: Option<LightsDesc>,
+    pub texts: Vec<TextDesc>,
+}
+
+impl Default for SceneDesc {
+    fn default() -> Self {
+        Self {
+            clear: [0, 0, 0, 255],
+            seed: 0,
+            fonts: Vec::new(),
+            textures: Vec::new(),
+            sprites: Vec::new(),
+            rects: Vec::new(),
+            circles: Vec::new(),
+            map: None,
+            lights: None,
+            texts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SpriteDesc {
+    pub texture: usize,
+    pub pos: [f32; 3],
+    pub size: [f32; 2],
+    /// Area of the texture in pixels. The whole texture if not set.
+    #[serde(default)]
+    pub uv: Option<[f32; 4]>,
+    #[serde(default = "white")]
+    pub color: [u8; 4],
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RectDesc {
+    pub pos: [f32; 3],
+    pub size: [f32; 2],
+    #[serde(default = "white")]
+    pub color: [u8; 4],
+    #[serde(default)]
+    pub border_width: f32,
+    #[serde(default)]
+    pub border_color: [u8; 4],
+    #[serde(default)]
+    pub radius: f32,
+}
+
+/// A 2d mesh circle.
+#[derive(Clone, Debug, Deserialize)]
+pub struct CircleDesc {
+    pub center: [f32; 2],
+    pub radius: f32,
+    #[serde(default = "default_z")]
+    pub z: f32,
+    #[serde(default = "white")]
+    pub color: [u8; 4],
+    /// Outline width. Filled if not set.
+    #[serde(default)]
+    pub stroke: Option<f32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MapDesc {
+    pub tilesheet: PathBuf,
+    #[serde(default = "default_tilesize")]
+    pub tilesize: u32,
+    #[serde(default)]
+    pub pos: [f32; 2],
+    pub tiles: Vec<TileDesc>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TileDesc {
+    /// x, y and layer.
+    pub pos: [u32; 3],
+    /// Tile in the map atlas. 1 is the first tile of the sheet.
+    pub id: u32,
+    #[serde(default = "white")]
+    pub color: [u8; 4],
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LightsDesc {
+    /// RGB and how dark the unlit world gets.
+    pub world_color: [f32; 4],
+    #[serde(default)]
+    pub areas: Vec<AreaLightDesc>,
+    #[serde(default)]
+    pub directionals: Vec<DirLightDesc>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AreaLightDesc {
+    pub pos: [f32; 2],
+    pub color: [u8; 4],
+    pub max_distance: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DirLightDesc {
+    pub pos: [f32; 2],
+    pub color: [u8; 4],
+    pub max_distance: f32,
+    pub max_width: f32,
+    /// Degrees.
+    pub angle: f32,
+    #[serde(default)]
+    pub fade_distance: f32,
+    #[serde(default)]
+    pub edge_fade_distance: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TextDesc {
+    pub text: String,
+    pub pos: [f32; 3],
+    pub size: [f32; 2],
+    #[serde(default = "default_font_size")]
+    pub font_size: f32,
+    /// Font family name, like one of the loaded fonts.
+    #[serde(default)]
+    pub family: Option<String>,
+    #[serde(default = "white")]
+    pub color: [u8; 4],
+}
+
+fn white() -> [u8; 4] {
+    [255; 4]
+}
+
+fn default_z() -> f32 {
+    1.0
+}
+
+fn default_tilesize() -> u32 {
+    20
+}
+
+fn default_font_size() -> f32 {
+    16.0
+}
+
+impl SceneDesc {
+    pub fn from_ron(source: &str) -> Result<Self, AscendingError> {
+        Ok(ron::from_str(source)?)
+    }
+
+    /// Loads a scene file, making its paths relative to it.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AscendingError> {
+        let path = path.as_ref();
+        let mut desc = Self::from_ron(&std::fs::read_to_string(path)?)?;
+
+        if let Some(dir) = path.parent() {
+            desc.fonts
+                .iter_mut()
+                .for_each(|font| *font = dir.join(&*font));
+            desc.textures
+                .iter_mut()
+                .for_each(|texture| *texture = dir.join(&*texture));
+
+            if let Some(map) = &mut desc.map {
+                map.tilesheet = dir.join(&map.tilesheet);
+            }
+        }
+
+        Ok(desc)
+    }
+}
@@ -0,0 +1,535 @@
+use crate::{
+    AscendingError, Bounds, Color, GpuRenderer, MessageRouter, Rect,
+    RectRenderer, SpinnerWidget, Text, TextAtlas, TextRenderer, UiSound, Vec2,
+    Vec3, WidgetHandle, WidgetInput, WidgetSounds,
+};
+use cosmic_text::{Attrs, Metrics};
+
+/// Pixels the mouse has to move before a press becomes a drag.
+const DRAG_THRESHOLD: f32 = 3.0;
+
+/// A number a [`Spinner`] can edit.
+pub trait SpinnerValue: Copy + PartialOrd + Send + Sync + 'static {
+    fn to_f64(self) -> f64;
+
+    fn from_f64(value: f64) -> Self;
+
+    /// Text shown for the value.
+    fn format(self, precision: usize) -> String;
+
+    fn parse(text: &str) -> Option<Self>;
+
+    /// if c can be typed into the field.
+    fn accepts(c: char) -> bool;
+}
+
+impl SpinnerValue for f32 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn format(self, precision: usize) -> String {
+        format!("{self:.precision$}")
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        text.trim()
+            .parse()
+            .ok()
+            .filter(|value: &f32| value.is_finite())
+    }
+
+    fn accepts(c: char) -> bool {
+        c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')
+    }
+}
+
+impl SpinnerValue for i32 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value.round() as i32
+    }
+
+    fn format(self, _precision: usize) -> String {
+        self.to_string()
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        text.trim().parse().ok()
+    }
+
+    fn accepts(c: char) -> bool {
+        c.is_ascii_digit() || matches!(c, '-' | '+')
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Drag<T> {
+    start_x: f32,
+    start_value: T,
+    /// Set once past the threshold, a press that never moves edits instead.
+    moved: bool,
+}
+
+/// A number field. Drag it sideways to adjust the value, click the arrows
+/// to step it, or click it to type an exact value. Typed values that don't
+/// parse are not applied and the field shows as invalid until fixed or
+/// cancelled with escape.
+///
+/// Placed in render space like rects that don't use the camera. Changes are
+/// emitted through the router when a handle is set, and so are its sounds.
+pub struct Spinner<T: SpinnerValue> {
+    value: T,
+    pub min: T,
+    pub max: T,
+    /// Amount the arrows change the value by. Dragged values snap to it.
+    pub step: T,
+    /// Value change per pixel dragged.
+    pub drag_speed: f32,
+    /// Decimals shown for floats.
+    pub precision: usize,
+    pub handle: Option<WidgetHandle<SpinnerWidget<T>>>,
+    /// Border color when the typed value is invalid.
+    pub invalid_color: Color,
+    pub sounds: WidgetSounds,
+    pub background: Rect,
+    pub up: Rect,
+    pub down: Rect,
+    pub text: Text,
+    position: Vec3,
+    size: Vec2,
+    border_color: Color,
+    /// Typed text while editing.
+    editing: Option<String>,
+    invalid: bool,
+    drag: Option<Drag<T>>,
+    /// Arrows step once per press.
+    was_down: bool,
+    hovered: bool,
+    /// Played through the router on the next input.
+    pending_sounds: Vec<UiSound>,
+    /// if the text or layout needs updating.
+    changed: bool,
+}
+
+impl<T: SpinnerValue> Spinner<T> {
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        value: T,
+        min: T,
+        max: T,
+        step: T,
+        render_layer: u32,
+    ) -> Self {
+        let border_color = Color::rgba(90, 90, 90, 255);
+        let mut background = Rect::new(renderer, render_layer);
+        let mut up = Rect::new(renderer, render_layer);
+        let mut down = Rect::new(renderer, render_layer);
+
+        background
+            .set_color(Color::rgba(30, 30, 30, 255))
+            .set_border_width(1.0)
+            .set_border_color(border_color);
+        up.set_color(Color::rgba(70, 70, 70, 255));
+        down.set_color(Color::rgba(70, 70, 70, 255));
+
+        let mut text = Text::new(
+            renderer,
+            Some(Metrics::new(14.0, 16.0)),
+            Vec3::ZERO,
+            Vec2::ZERO,
+        );
+
+        text.set_default_color(Color::rgba(230, 230, 230, 255));
+
+        Self {
+            value: clamp(value, min, max),
+            min,
+            max,
+            step,
+            drag_speed: step.to_f64() as f32,
+            precision: 2,
+            handle: None,
+            invalid_color: Color::rgba(200, 60, 60, 255),
+            sounds: WidgetSounds::default(),
+            background,
+            up,
+            down,
+            text,
+            position: Vec3::ZERO,
+            size: Vec2::new(80.0, 20.0),
+            border_color,
+            editing: None,
+            invalid: false,
+            drag: None,
+            was_down: false,
+            hovered: false,
+            pending_sounds: Vec::new(),
+            changed: true,
+        }
+    }
+
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// Sets the value without emitting a change.
+    pub fn set_value(&mut self, value: T) -> &mut Self {
+        let value = clamp(value, self.min, self.max);
+
+        if value != self.value {
+            self.value = value;
+            self.changed = true;
+        }
+
+        self
+    }
+
+    /// Bottom left corner and z.
+    pub fn set_position(&mut self, position: Vec3) -> &mut Self {
+        self.position = position;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_size(&mut self, size: Vec2) -> &mut Self {
+        self.size = size;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_border_color(&mut self, color: Color) -> &mut Self {
+        self.border_color = color;
+        self.changed = true;
+        self
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(
+            self.position.x,
+            self.position.y,
+            self.position.x + self.size.x,
+            self.position.y + self.size.y,
+        )
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some_and(|drag| drag.moved)
+    }
+
+    /// if the typed text doesn't parse.
+    pub fn is_invalid(&self) -> bool {
+        self.invalid
+    }
+
+    /// Applies a frame of input. Returns the new value when it changed,
+    /// which is also emitted if a handle is set.
+    pub fn handle_input<M: 'static>(
+        &mut self,
+        input: &WidgetInput,
+        router: &mut MessageRouter<M>,
+    ) -> Option<T> {
+        let old = self.value;
+        let pressed = input.mouse_down && !self.was_down;
+
+        self.was_down = input.mouse_down;
+
+        let hovered = input
+            .mouse_pos
+            .is_some_and(|pos| self.bounds().contains(pos));
+
+        if hovered && !self.hovered {
+            self.pending_sounds.push(UiSound::Hover);
+        }
+
+        self.hovered = hovered;
+
+        if let Some(pos) = input.mouse_pos {
+            self.handle_mouse(pos, input.mouse_down, pressed);
+        }
+
+        if self.editing.is_some() {
+            for c in input.typed.chars() {
+                self.type_char(c);
+            }
+        }
+
+        for sound in self.pending_sounds.drain(..) {
+            router.play_sound(self.sounds.cue(sound));
+        }
+
+        if self.value == old {
+            return None;
+        }
+
+        if let Some(handle) = self.handle {
+            router.emit(handle, self.value);
+        }
+
+        Some(self.value)
+    }
+
+    /// Starts typing a value, with the current one as the text.
+    pub fn begin_edit(&mut self) {
+        self.editing = Some(self.value.format(self.precision));
+        self.invalid = false;
+        self.changed = true;
+        self.pending_sounds.push(UiSound::Open);
+    }
+
+    /// Applies the typed value. Stays editing and marks the field invalid
+    /// if it doesn't parse.
+    pub fn commit_edit(&mut self) {
+        let Some(typed) = &self.editing else {
+            return;
+        };
+
+        match T::parse(typed) {
+            Some(value) => {
+                self.editing = None;
+                self.invalid = false;
+                self.value = clamp(value, self.min, self.max);
+                self.pending_sounds.push(UiSound::Close);
+            }
+            None => {
+                self.invalid = true;
+                self.pending_sounds.push(UiSound::Error);
+            }
+        }
+
+        self.changed = true;
+    }
+
+    pub fn cancel_edit(&mut self) {
+        if self.editing.is_some() {
+            self.pending_sounds.push(UiSound::Close);
+        }
+
+        self.editing = None;
+        self.invalid = false;
+        self.changed = true;
+    }
+
+    /// Adds steps to the value, negative to go down.
+    pub fn step_by(&mut self, steps: i32) {
+        let value = self.value.to_f64() + self.step.to_f64() * steps as f64;
+
+        self.value = clamp(T::from_f64(value), self.min, self.max);
+        self.changed = true;
+    }
+
+    fn handle_mouse(&mut self, pos: Vec2, down: bool, pressed: bool) {
+        if pressed {
+            let on_arrows = self.arrow_bounds(true).contains(pos)
+                || self.arrow_bounds(false).contains(pos);
+
+            // Clicking anywhere but the typed text finishes the edit.
+            if self.editing.is_some()
+                && (on_arrows || !self.bounds().contains(pos))
+            {
+                self.commit_edit();
+            }
+
+            if self.editing.is_some() {
+                return;
+            }
+
+            if self.bounds().contains(pos) {
+                self.pending_sounds.push(UiSound::Click);
+            }
+
+            if self.arrow_bounds(true).contains(pos) {
+                self.step_by(1);
+            } else if self.arrow_bounds(false).contains(pos) {
+                self.step_by(-1);
+            } else if self.bounds().contains(pos) {
+                self.drag = Some(Drag {
+                    start_x: pos.x,
+                    start_value: self.value,
+                    moved: false,
+                });
+            }
+
+            return;
+        }
+
+        let Some(drag) = &mut self.drag else {
+            return;
+        };
+
+        if !down {
+            let clicked = !drag.moved;
+
+            self.drag = None;
+
+            if clicked {
+                self.begin_edit();
+            }
+
+            return;
+        }
+
+        let dx = pos.x - drag.start_x;
+
+        if dx.abs() > DRAG_THRESHOLD {
+            drag.moved = true;
+        }
+
+        if drag.moved {
+            let step = self.step.to_f64();
+            let mut value =
+                drag.start_value.to_f64() + (dx * self.drag_speed) as f64;
+
+            if step > 0.0 {
+                value = (value / step).round() * step;
+            }
+
+            let value = clamp(T::from_f64(value), self.min, self.max);
+
+            if value != self.value {
+                self.value = value;
+                self.changed = true;
+            }
+        }
+    }
+
+    fn type_char(&mut self, c: char) {
+        let Some(typed) = &mut self.editing else {
+            return;
+        };
+
+        match c {
+            '\r' | '\n' => self.commit_edit(),
+            '\u{1b}' => self.cancel_edit(),
+            '\u{8}' => {
+                typed.pop();
+                self.invalid = false;
+                self.changed = true;
+            }
+            c if T::accepts(c) => {
+                typed.push(c);
+                self.invalid = false;
+                self.changed = true;
+            }
+            _ => {}
+        }
+    }
+
+    fn arrow_width(&self) -> f32 {
+        (self.size.y * 0.75).min(self.size.x / 3.0)
+    }
+
+    fn arrow_bounds(&self, up: bool) -> Bounds {
+        let half = self.size.y / 2.0;
+        let right = self.position.x + self.size.x;
+        let bottom = if up {
+            self.position.y + half
+        } else {
+            self.position.y
+        };
+
+        Bounds::new(right - self.arrow_width(), bottom, right, bottom + half)
+    }
+
+    /// Lays out the rects and sets the shown text if anything changed.
+    fn refresh(&mut self, renderer: &mut GpuRenderer) {
+        if !self.changed {
+            return;
+        }
+
+        let (pos, size) = (self.position, self.size);
+        let arrow = self.arrow_width();
+        let border = if self.invalid {
+            self.invalid_color
+        } else {
+            self.border_color
+        };
+
+        self.background.set_position(pos).set_size(size);
+        self.background.set_border_color(border);
+
+        // lower Z renders over the background.
+        for (rect, up) in [(&mut self.up, true), (&mut self.down, false)] {
+            let y = if up { pos.y + size.y / 2.0 } else { pos.y };
+
+            rect.set_position(Vec3::new(
+                pos.x + size.x - arrow + 1.0,
+                y + 1.0,
+                pos.z - 0.01,
+            ))
+            .set_size(Vec2::new(arrow - 2.0, size.y / 2.0 - 2.0));
+        }
+
+        let content = match &self.editing {
+            Some(typed) => format!("{typed}|"),
+            None => self.value.format(self.precision),
+        };
+        let line_height = self.text.buffer.metrics().line_height;
+        let text_width = (size.x - arrow - 8.0).max(0.0);
+
+        self.text
+            .set_buffer_size(renderer, text_width as i32, size.y as i32)
+            .set_text(renderer, &content, Attrs::new())
+            .set_position(Vec3::new(
+                pos.x + 4.0,
+                pos.y + (size.y - line_height) / 2.0,
+                pos.z - 0.01,
+            ));
+        self.text.size = Vec2::new(text_width, line_height);
+        self.changed = false;
+    }
+
+    /// Frees the spinner's rects and text.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        self.background.unload(renderer);
+        self.up.unload(renderer);
+        self.down.unload(renderer);
+        self.text.unload(renderer);
+    }
+}
+
+fn clamp<T: SpinnerValue>(value: T, min: T, max: T) -> T {
+    if value < min {
+        min
+    } else if value > max {
+        max
+    } else {
+        value
+    }
+}
+
+impl RectRenderer {
+    pub fn spinner_update<T: SpinnerValue>(
+        &mut self,
+        spinner: &mut Spinner<T>,
+        renderer: &mut GpuRenderer,
+    ) {
+        spinner.refresh(renderer);
+        self.rect_update(&mut spinner.background, renderer);
+        self.rect_update(&mut spinner.up, renderer);
+        self.rect_update(&mut spinner.down, renderer);
+    }
+}
+
+impl TextRenderer {
+    pub fn spinner_update<T: SpinnerValue>(
+        &mut self,
+        spinner: &mut Spinner<T>,
+        atlas: &mut TextAtlas,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        spinner.refresh(renderer);
+        self.text_update(&mut spinner.text, atlas, renderer)
+    }
+}
@@ -0,0 +1,349 @@
+use crate::{Allocation, AssetStream, AtlasGroup, GpuRenderer, Texture, Vec2};
+use image::imageops::FilterType;
+use std::{collections::HashMap, path::PathBuf};
+
+/// Distances and budget for a [`TextureStreamer`], in world units and
+/// bytes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StreamingSettings {
+    /// Textures with an anchor nearer than this load at full resolution.
+    pub full_radius: f32,
+    /// Textures with an anchor nearer than this load, past full_radius at
+    /// far_lod.
+    pub load_radius: f32,
+    /// Textures with every anchor further than this are evicted. Keep it
+    /// above load_radius so textures at the edge don't load and evict in
+    /// turn.
+    pub evict_radius: f32,
+    /// Times the resolution halves for textures loaded past full_radius.
+    pub far_lod: u32,
+    /// Most atlas bytes the streamed textures may take. The furthest are
+    /// evicted past it.
+    pub budget_bytes: u64,
+    /// Most loads running at once. The nearest waiting go first.
+    pub max_in_flight: usize,
+}
+
+impl Default for StreamingSettings {
+    fn default() -> Self {
+        Self {
+            full_radius: 1024.0,
+            load_radius: 2048.0,
+            evict_radius: 3072.0,
+            far_lod: 1,
+            budget_bytes: 256 * 1024 * 1024,
+            max_in_flight: 4,
+        }
+    }
+}
+
+/// What a [`TextureStreamer`] holds, for a memory overlay.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreamingStats {
+    pub tracked: usize,
+    pub resident: usize,
+    /// Of resident, how many are at reduced resolution.
+    pub reduced: usize,
+    pub resident_bytes: u64,
+    pub budget_bytes: u64,
+    pub in_flight: usize,
+    pub loaded_total: u64,
+    pub evicted_total: u64,
+}
+
+/// A streamed texture in the atlas. lod is how many times its resolution
+/// was halved, so UVs made for the full texture scale by
+/// [`StreamedTexture::uv_scale`].
+#[derive(Copy, Clone, Debug)]
+pub struct StreamedTexture {
+    pub allocation: Allocation,
+    pub lod: u32,
+}
+
+impl StreamedTexture {
+    pub fn uv_scale(&self) -> f32 {
+        1.0 / (1u32 << self.lod) as f32
+    }
+}
+
+struct Tracked {
+    path: PathBuf,
+    /// Where it is used, like the centers of the chunks drawing it.
+    anchors: Vec<Vec2>,
+    /// Distance of the nearest anchor at the last update.
+    distance: f32,
+    /// lod and bytes in the atlas.
+    resident: Option<(u32, u64)>,
+    /// lod being loaded.
+    loading: Option<u32>,
+}
+
+/// Loads textures of large worlds into an atlas as the camera nears them
+/// and evicts them once it is far, nearest first and within a byte
+/// budget. Textures past [`StreamingSettings::full_radius`] load at a
+/// reduced resolution and are reloaded at full size when the camera gets
+/// close.
+///
+/// Decoding runs on the streamer's own [`AssetStream`] so it never holds
+/// up a loading screen. The atlas keys are the texture keys, so give the
+/// streamer an atlas of its own or keys nothing else uses.
+pub struct TextureStreamer {
+    pub settings: StreamingSettings,
+    stream: AssetStream,
+    textures: HashMap<String, Tracked>,
+    loaded_total: u64,
+    evicted_total: u64,
+}
+
+impl TextureStreamer {
+    pub fn new(settings: StreamingSettings) -> Self {
+        Self {
+            settings,
+            stream: AssetStream::new(),
+            textures: HashMap::new(),
+            loaded_total: 0,
+            evicted_total: 0,
+        }
+    }
+
+    /// Tracks the texture at path under key. Nothing loads until it has an
+    /// anchor in range.
+    pub fn track(&mut self, key: impl Into<String>, path: impl Into<PathBuf>) {
+        self.textures.entry(key.into()).or_insert_with(|| Tracked {
+            path: path.into(),
+            anchors: Vec::new(),
+            distance: f32::INFINITY,
+            resident: None,
+            loading: None,
+        });
+    }
+
+    /// Adds a world position the texture is used at.
+    pub fn add_anchor(&mut self, key: &str, position: Vec2) {
+        if let Some(texture) = self.textures.get_mut(key) {
+            texture.anchors.push(position);
+        }
+    }
+
+    /// Forgets where the texture is used, like when its chunk unloads.
+    pub fn clear_anchors(&mut self, key: &str) {
+        if let Some(texture) = self.textures.get_mut(key) {
+            texture.anchors.clear();
+        }
+    }
+
+    /// Stops tracking the texture and frees it from the atlas.
+    pub fn untrack(&mut self, key: &str, atlas: &mut AtlasGroup) {
+        if self.textures.remove(key).is_some() {
+            atlas.atlas.remove(&key.to_owned());
+        }
+    }
+
+    /// The texture if it is in the atlas, marking it used this frame.
+    pub fn get(
+        &mut self,
+        key: &str,
+        atlas: &mut AtlasGroup,
+    ) -> Option<StreamedTexture> {
+        let (lod, _) = self.textures.get(key)?.resident?;
+
+        atlas
+            .atlas
+            .get(&key.to_owned())
+            .map(|allocation| StreamedTexture { allocation, lod })
+    }
+
+    /// Evicts what is out of range or over budget, uploads finished loads
+    /// and starts the next. Call once per frame with the camera's world
+    /// position.
+    pub fn update(
+        &mut self,
+        camera: Vec2,
+        atlas: &mut AtlasGroup,
+        renderer: &GpuRenderer,
+    ) {
+        let settings = self.settings;
+
+        for texture in self.textures.values_mut() {
+            texture.distance = texture
+                .anchors
+                .iter()
+                .map(|anchor| anchor.distance(camera))
+                .fold(f32::INFINITY, f32::min);
+        }
+
+        self.stream.poll();
+        self.upload_finished(atlas, renderer);
+
+        // Out of range.
+        for (key, texture) in &mut self.textures {
+            if texture.resident.is_some()
+                && texture.distance > settings.evict_radius
+            {
+                atlas.atlas.remove(key);
+                texture.resident = None;
+                self.evicted_total += 1;
+            }
+        }
+
+        self.evict_over_budget(atlas);
+        self.start_loads();
+    }
+
+    fn upload_finished(
+        &mut self,
+        atlas: &mut AtlasGroup,
+        renderer: &GpuRenderer,
+    ) {
+        for (key, e) in self.stream.take_errors() {
+            log::warn!("failed to stream {key}: {e}");
+
+            if let Some(texture) = self.textures.get_mut(&key) {
+                texture.loading = None;
+            }
+        }
+
+        for (key, texture) in &mut self.textures {
+            let Some(lod) = texture.loading else {
+                continue;
+            };
+            let Some(loaded) = self.stream.take::<Texture>(key) else {
+                continue;
+            };
+
+            texture.loading = None;
+
+            // The camera left while it loaded.
+            if texture.distance > self.settings.evict_radius {
+                continue;
+            }
+
+            let (width, height) = loaded.size();
+
+            // Frees the other lod so the upload doesn't reuse it.
+            atlas.atlas.remove(key);
+
+            if atlas
+                .atlas
+                .upload(key.clone(), loaded.bytes(), width, height, 0, renderer)
+                .is_some()
+            {
+                texture.resident =
+                    Some((lod, width as u64 * height as u64 * 4));
+                self.loaded_total += 1;
+            } else {
+                texture.resident = None;
+                log::warn!("no atlas space to stream {key} into");
+            }
+        }
+    }
+
+    fn evict_over_budget(&mut self, atlas: &mut AtlasGroup) {
+        let mut resident: Vec<(&String, &mut Tracked)> = self
+            .textures
+            .iter_mut()
+            .filter(|(_, texture)| texture.resident.is_some())
+            .collect();
+        let mut bytes: u64 = resident
+            .iter()
+            .filter_map(|(_, texture)| texture.resident)
+            .map(|(_, bytes)| bytes)
+            .sum();
+
+        if bytes <= self.settings.budget_bytes {
+            return;
+        }
+
+        // Furthest first.
+        resident.sort_by(|a, b| b.1.distance.total_cmp(&a.1.distance));
+
+        for (key, texture) in resident {
+            if bytes <= self.settings.budget_bytes {
+                break;
+            }
+
+            if let Some((_, size)) = texture.resident.take() {
+                atlas.atlas.remove(key);
+                bytes -= size;
+                self.evicted_total += 1;
+            }
+        }
+    }
+
+    fn start_loads(&mut self) {
+        let settings = self.settings;
+        let in_flight = self
+            .textures
+            .values()
+            .filter(|texture| texture.loading.is_some())
+            .count();
+        let mut wanted: Vec<(&String, &mut Tracked, u32)> = self
+            .textures
+            .iter_mut()
+            .filter(|(_, texture)| {
+                texture.loading.is_none()
+                    && texture.distance <= settings.load_radius
+            })
+            .filter_map(|(key, texture)| {
+                let lod = if texture.distance <= settings.full_radius {
+                    0
+                } else {
+                    settings.far_lod
+                };
+
+                // Only reload to get sharper.
+                match texture.resident {
+                    Some((resident, _)) if resident <= lod => None,
+                    _ => Some((key, texture, lod)),
+                }
+            })
+            .collect();
+
+        wanted.sort_by(|a, b| a.1.distance.total_cmp(&b.1.distance));
+
+        for (key, texture, lod) in wanted
+            .into_iter()
+            .take(settings.max_in_flight.saturating_sub(in_flight))
+        {
+            let path = texture.path.clone();
+            let name = key.clone();
+
+            texture.loading = Some(lod);
+            self.stream.load("textures", key.clone(), move || {
+                let image = image::open(&path)?;
+                let image = match lod {
+                    0 => image,
+                    lod => image.resize(
+                        (image.width() >> lod).max(1),
+                        (image.height() >> lod).max(1),
+                        FilterType::Triangle,
+                    ),
+                };
+
+                Ok(Texture::from_image(name, image))
+            });
+        }
+    }
+
+    pub fn stats(&self) -> StreamingStats {
+        let mut stats = StreamingStats {
+            tracked: self.textures.len(),
+            budget_bytes: self.settings.budget_bytes,
+            loaded_total: self.loaded_total,
+            evicted_total: self.evicted_total,
+            ..Default::default()
+        };
+
+        for texture in self.textures.values() {
+            if let Some((lod, bytes)) = texture.resident {
+                stats.resident += 1;
+                stats.resident_bytes += bytes;
+                stats.reduced += usize::from(lod > 0);
+            }
+
+            stats.in_flight += usize::from(texture.loading.is_some());
+        }
+
+        stats
+    }
+}
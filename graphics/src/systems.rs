@@ -1,30 +1,57 @@
+pub(crate) mod arena;
+mod batch;
 mod bounds;
-mod buffer;
+pub(crate) mod buffer;
+mod builder;
+mod camera_transition;
+mod clock;
+mod coords;
 mod device;
 mod draw_order;
-mod instance_buffer;
-mod layout;
+mod easing;
+pub(crate) mod instance_buffer;
+mod layers;
+pub(crate) mod layout;
 mod pass;
-mod pipelines;
+pub(crate) mod pipelines;
 mod renderer;
-mod static_vbo;
-mod system;
-mod vbo;
+pub(crate) mod static_vbo;
+pub(crate) mod system;
+mod uniform;
+pub(crate) mod vbo;
 
+pub use arena::ArenaHandle;
+// Only used by some of the subsystem features.
+#[allow(unused_imports)]
+pub(crate) use arena::BufferArena;
+pub use batch::*;
 pub use bounds::{Bounds, WorldBounds};
-pub use buffer::{
-    AsBufferPass, Buffer, BufferData, BufferLayout, BufferPass, BufferStore,
-};
+pub use buffer::{AsBufferPass, BufferPass};
+pub(crate) use buffer::{Buffer, BufferData, BufferLayout, BufferStore};
+pub use builder::RendererBuilder;
+pub use camera_transition::CameraGoal;
+pub(crate) use camera_transition::CameraTransition;
+pub use clock::*;
+pub use coords::*;
 pub use device::*;
-pub use draw_order::{DrawOrder, Index, OrderedIndex};
-pub use instance_buffer::*;
-pub use layout::*;
+pub(crate) use draw_order::SortPolicies;
+pub use draw_order::{DrawOrder, Index, OrderedIndex, SortPolicy};
+pub use easing::*;
+pub use instance_buffer::DrawCache;
+#[allow(unused_imports)]
+pub(crate) use instance_buffer::*;
+pub use layers::{DrawLayer, Layers, STANDARD_LAYERS};
+pub(crate) use layout::*;
 pub use pass::*;
-pub use pipelines::*;
+pub(crate) use pipelines::*;
 pub use renderer::*;
-pub use static_vbo::*;
-pub use system::*;
-pub use vbo::*;
+pub(crate) use static_vbo::*;
+pub use system::System;
+#[allow(unused_imports)]
+pub(crate) use system::{GlobalUniform, SystemLayout};
+pub use uniform::*;
+#[allow(unused_imports)]
+pub(crate) use vbo::*;
 
 pub(crate) type FxBuildHasher =
     std::hash::BuildHasherDefault<ritehash::FxHasher>;
@@ -0,0 +1,531 @@
+use crate::{AsBufferPass, Buffer, BufferLayout, BufferPass, GpuDevice};
+use slab::Slab;
+use std::ops::Range;
+
+/// Sorted, non touching free ranges of a buffer, in elements.
+#[derive(Default)]
+struct FreeList {
+    ranges: Vec<Range<u32>>,
+}
+
+impl FreeList {
+    fn new(capacity: u32) -> Self {
+        let mut list = Self::default();
+
+        list.free(0..capacity);
+        list
+    }
+
+    /// Takes the smallest free range that fits, to keep big ones whole.
+    fn alloc(&mut self, size: u32) -> Option<Range<u32>> {
+        if size == 0 {
+            return Some(0..0);
+        }
+
+        let (i, _) = self
+            .ranges
+            .iter()
+            .enumerate()
+            .filter(|(_, range)| range.len() >= size as usize)
+            .min_by_key(|(_, range)| range.len())?;
+        let start = self.ranges[i].start;
+
+        self.ranges[i].start += size;
+
+        if self.ranges[i].is_empty() {
+            self.ranges.remove(i);
+        }
+
+        Some(start..start + size)
+    }
+
+    /// Returns a range, merging it with the free ranges it touches.
+    fn free(&mut self, range: Range<u32>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let i = self.ranges.partition_point(|r| r.start < range.start);
+        let joins_next = self
+            .ranges
+            .get(i)
+            .is_some_and(|next| next.start == range.end);
+        let joins_prev = i > 0 && self.ranges[i - 1].end == range.start;
+
+        match (joins_prev, joins_next) {
+            (true, true) => {
+                self.ranges[i - 1].end = self.ranges[i].end;
+                self.ranges.remove(i);
+            }
+            (true, false) => self.ranges[i - 1].end = range.end,
+            (false, true) => self.ranges[i].start = range.start,
+            (false, false) => self.ranges.insert(i, range),
+        }
+    }
+
+    /// Where the used part of a buffer of capacity ends.
+    fn end(&self, capacity: u32) -> u32 {
+        match self.ranges.last() {
+            Some(last) if last.end == capacity => last.start,
+            _ => capacity,
+        }
+    }
+
+    /// Free space left between allocations.
+    fn holes(&self, capacity: u32) -> u32 {
+        let end = self.end(capacity);
+
+        self.ranges
+            .iter()
+            .filter(|range| range.end <= end)
+            .map(|range| range.len() as u32)
+            .sum()
+    }
+}
+
+/// A range of an arena, given out by [`BufferArena::alloc`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ArenaHandle {
+    key: u32,
+    generation: u32,
+}
+
+struct Allocation {
+    vertices: Range<u32>,
+    indices: Range<u32>,
+    generation: u32,
+}
+
+/// Suballocates one large vertex and index buffer pair among many small
+/// meshes, like shapes, text runs and trails, so they don't each need a
+/// buffer of their own and can be drawn without rebinding.
+///
+/// Indices are stored offset by where their vertices landed, so draws of
+/// the arena use a base vertex of 0 and neighbouring allocations can be
+/// drawn in one call. Freed indices are zeroed so drawing over them only
+/// makes empty triangles.
+///
+/// The buffers grow when full. Freeing leaves holes that later allocations
+/// reuse; compact closes them when they add up.
+pub struct BufferArena<K: BufferLayout> {
+    vertex_buffer: Buffer<K>,
+    index_buffer: Buffer<K>,
+    /// CPU copies, used to regrow and compact the buffers.
+    vertices: Vec<u8>,
+    indices: Vec<u32>,
+    vertex_free: FreeList,
+    index_free: FreeList,
+    allocations: Slab<Allocation>,
+    generation: u32,
+    /// Share of the used buffer space lost to holes before maintain
+    /// compacts.
+    pub compact_threshold: f32,
+}
+
+impl<'a, K: BufferLayout> AsBufferPass<'a> for BufferArena<K> {
+    fn as_buffer_pass(&'a self) -> BufferPass<'a> {
+        BufferPass {
+            vertex_buffer: &self.vertex_buffer.buffer,
+            index_buffer: &self.index_buffer.buffer,
+        }
+    }
+}
+
+impl<K: BufferLayout> BufferArena<K> {
+    pub fn new(gpu_device: &GpuDevice) -> Self {
+        Self::with_capacity(gpu_device, 4_096, 12_288)
+    }
+
+    /// Capacities are counts of vertices and indices.
+    pub fn with_capacity(
+        gpu_device: &GpuDevice,
+        vertex_capacity: u32,
+        index_capacity: u32,
+    ) -> Self {
+        let vertex_capacity = vertex_capacity.max(1);
+        let index_capacity = index_capacity.max(1);
+        let vertices = vec![0; vertex_capacity as usize * K::stride()];
+        let indices = vec![0; index_capacity as usize];
+
+        Self {
+            vertex_buffer: vertex_buffer(gpu_device, &vertices),
+            index_buffer: index_buffer(gpu_device, &indices),
+            vertices,
+            indices,
+            vertex_free: FreeList::new(vertex_capacity),
+            index_free: FreeList::new(index_capacity),
+            allocations: Slab::new(),
+            generation: 0,
+            compact_threshold: 0.25,
+        }
+    }
+
+    /// Stores a mesh. vertices are bytes of K and indices count from the
+    /// mesh's first vertex.
+    pub fn alloc(
+        &mut self,
+        gpu_device: &GpuDevice,
+        vertices: &[u8],
+        indices: &[u32],
+    ) -> ArenaHandle {
+        let vertex_count = (vertices.len() / K::stride()) as u32;
+        let vertex_range = self.alloc_vertices(gpu_device, vertex_count);
+        let index_range = self.alloc_indices(gpu_device, indices.len() as u32);
+
+        self.generation = self.generation.wrapping_add(1);
+
+        let key = self.allocations.insert(Allocation {
+            vertices: vertex_range.clone(),
+            indices: index_range.clone(),
+            generation: self.generation,
+        });
+
+        self.write(gpu_device, vertex_range, index_range, vertices, indices);
+
+        ArenaHandle {
+            key: key as u32,
+            generation: self.generation,
+        }
+    }
+
+    /// Replaces an allocation's mesh, in place when it still fits.
+    /// Returns false if the handle was already freed.
+    pub fn update(
+        &mut self,
+        gpu_device: &GpuDevice,
+        handle: ArenaHandle,
+        vertices: &[u8],
+        indices: &[u32],
+    ) -> bool {
+        let Some(old) = self.get(handle) else {
+            return false;
+        };
+        let (old_vertices, old_indices) =
+            (old.vertices.clone(), old.indices.clone());
+        let vertex_count = (vertices.len() / K::stride()) as u32;
+        let index_count = indices.len() as u32;
+
+        let vertex_range = if vertex_count as usize <= old_vertices.len() {
+            let end = old_vertices.start + vertex_count;
+
+            self.vertex_free.free(end..old_vertices.end);
+            old_vertices.start..end
+        } else {
+            self.vertex_free.free(old_vertices);
+            self.alloc_vertices(gpu_device, vertex_count)
+        };
+
+        let index_range = if index_count as usize <= old_indices.len() {
+            let end = old_indices.start + index_count;
+
+            self.clear_indices(gpu_device, end..old_indices.end);
+            self.index_free.free(end..old_indices.end);
+            old_indices.start..end
+        } else {
+            self.clear_indices(gpu_device, old_indices.clone());
+            self.index_free.free(old_indices);
+            self.alloc_indices(gpu_device, index_count)
+        };
+
+        if let Some(allocation) = self.allocations.get_mut(handle.key as usize)
+        {
+            allocation.vertices = vertex_range.clone();
+            allocation.indices = index_range.clone();
+        }
+
+        self.write(gpu_device, vertex_range, index_range, vertices, indices);
+        true
+    }
+
+    /// Returns false if the handle was already freed.
+    pub fn free(
+        &mut self,
+        gpu_device: &GpuDevice,
+        handle: ArenaHandle,
+    ) -> bool {
+        if self.get(handle).is_none() {
+            return false;
+        }
+
+        let allocation = self.allocations.remove(handle.key as usize);
+
+        self.clear_indices(gpu_device, allocation.indices.clone());
+        self.vertex_free.free(allocation.vertices);
+        self.index_free.free(allocation.indices);
+        true
+    }
+
+    /// Frees everything, keeping the buffers' size.
+    pub fn clear(&mut self, gpu_device: &GpuDevice) {
+        self.allocations.clear();
+        self.indices.fill(0);
+        self.vertex_free = FreeList::new(self.vertex_capacity());
+        self.index_free = FreeList::new(self.index_capacity());
+        self.index_buffer.write(
+            gpu_device,
+            bytemuck::cast_slice(&self.indices),
+            0,
+        );
+    }
+
+    pub fn contains(&self, handle: ArenaHandle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.allocations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allocations.is_empty()
+    }
+
+    /// The index range to draw an allocation with a base vertex of 0.
+    pub fn indices(&self, handle: ArenaHandle) -> Option<Range<u32>> {
+        self.get(handle)
+            .map(|allocation| allocation.indices.clone())
+    }
+
+    /// Index ranges to draw the allocations in order, with ranges that
+    /// follow on from each other merged into one draw.
+    pub fn index_ranges(&self, handles: &[ArenaHandle]) -> Vec<Range<u32>> {
+        let mut ranges: Vec<Range<u32>> = Vec::new();
+
+        for range in handles.iter().filter_map(|handle| self.indices(*handle)) {
+            if range.is_empty() {
+                continue;
+            }
+
+            match ranges.last_mut() {
+                Some(last) if last.end == range.start => last.end = range.end,
+                _ => ranges.push(range),
+            }
+        }
+
+        ranges
+    }
+
+    /// Indices up to the end of the last allocation. Drawing 0..draw_end
+    /// draws every allocation at once.
+    pub fn draw_end(&self) -> u32 {
+        self.index_free.end(self.index_capacity())
+    }
+
+    pub fn vertex_capacity(&self) -> u32 {
+        (self.vertices.len() / K::stride()) as u32
+    }
+
+    pub fn index_capacity(&self) -> u32 {
+        self.indices.len() as u32
+    }
+
+    /// Share of the used space lost to holes left by freed allocations,
+    /// the worse of the vertex and index buffer.
+    pub fn fragmentation(&self) -> f32 {
+        let share = |free: &FreeList, capacity: u32| {
+            let end = free.end(capacity);
+
+            if end == 0 {
+                0.0
+            } else {
+                free.holes(capacity) as f32 / end as f32
+            }
+        };
+
+        share(&self.vertex_free, self.vertex_capacity())
+            .max(share(&self.index_free, self.index_capacity()))
+    }
+
+    /// Compacts once fragmentation passes compact_threshold. Returns true
+    /// if it did. Meant to be called once per frame before drawing.
+    pub fn maintain(&mut self, gpu_device: &GpuDevice) -> bool {
+        if self.fragmentation() <= self.compact_threshold {
+            return false;
+        }
+
+        self.compact(gpu_device);
+        true
+    }
+
+    /// Moves every allocation to the front of the buffers, closing the
+    /// holes between them. Handles stay valid but their ranges change.
+    pub fn compact(&mut self, gpu_device: &GpuDevice) {
+        let stride = K::stride();
+        let mut vertices = vec![0; self.vertices.len()];
+        let mut indices = vec![0; self.indices.len()];
+        let mut order: Vec<usize> =
+            self.allocations.iter().map(|(key, _)| key).collect();
+        let (mut vertex_pos, mut index_pos) = (0, 0);
+
+        order.sort_by_key(|key| self.allocations[*key].indices.start);
+
+        for key in order {
+            let allocation = &mut self.allocations[key];
+            let old_vertices = allocation.vertices.clone();
+            let old_indices = allocation.indices.clone();
+            let new_vertices =
+                vertex_pos..vertex_pos + old_vertices.len() as u32;
+            let new_indices = index_pos..index_pos + old_indices.len() as u32;
+
+            vertices[byte_range(&new_vertices, stride)].copy_from_slice(
+                &self.vertices[byte_range(&old_vertices, stride)],
+            );
+
+            for (to, from) in indices[as_usize(&new_indices)]
+                .iter_mut()
+                .zip(&self.indices[as_usize(&old_indices)])
+            {
+                *to = from - old_vertices.start + new_vertices.start;
+            }
+
+            vertex_pos = new_vertices.end;
+            index_pos = new_indices.end;
+            allocation.vertices = new_vertices;
+            allocation.indices = new_indices;
+        }
+
+        self.vertices = vertices;
+        self.indices = indices;
+        self.vertex_free = FreeList::default();
+        self.vertex_free.free(vertex_pos..self.vertex_capacity());
+        self.index_free = FreeList::default();
+        self.index_free.free(index_pos..self.index_capacity());
+        self.vertex_buffer.write(gpu_device, &self.vertices, 0);
+        self.index_buffer.write(
+            gpu_device,
+            bytemuck::cast_slice(&self.indices),
+            0,
+        );
+    }
+
+    fn get(&self, handle: ArenaHandle) -> Option<&Allocation> {
+        self.allocations
+            .get(handle.key as usize)
+            .filter(|allocation| allocation.generation == handle.generation)
+    }
+
+    fn alloc_vertices(
+        &mut self,
+        gpu_device: &GpuDevice,
+        count: u32,
+    ) -> Range<u32> {
+        if let Some(range) = self.vertex_free.alloc(count) {
+            return range;
+        }
+
+        let capacity = self.vertex_capacity();
+        let grown = (capacity * 2).max(self.vertex_free.end(capacity) + count);
+
+        self.vertices.resize(grown as usize * K::stride(), 0);
+        self.vertex_free.free(capacity..grown);
+        self.vertex_buffer = vertex_buffer(gpu_device, &self.vertices);
+        self.vertex_free
+            .alloc(count)
+            .expect("grown vertex buffer fits the allocation")
+    }
+
+    fn alloc_indices(
+        &mut self,
+        gpu_device: &GpuDevice,
+        count: u32,
+    ) -> Range<u32> {
+        if let Some(range) = self.index_free.alloc(count) {
+            return range;
+        }
+
+        let capacity = self.index_capacity();
+        let grown = (capacity * 2).max(self.index_free.end(capacity) + count);
+
+        self.indices.resize(grown as usize, 0);
+        self.index_free.free(capacity..grown);
+        self.index_buffer = index_buffer(gpu_device, &self.indices);
+        self.index_free
+            .alloc(count)
+            .expect("grown index buffer fits the allocation")
+    }
+
+    fn write(
+        &mut self,
+        gpu_device: &GpuDevice,
+        vertex_range: Range<u32>,
+        index_range: Range<u32>,
+        vertices: &[u8],
+        indices: &[u32],
+    ) {
+        let stride = K::stride();
+        let vertex_bytes = byte_range(&vertex_range, stride);
+        let index_slice = as_usize(&index_range);
+
+        self.vertices[vertex_bytes.clone()]
+            .copy_from_slice(&vertices[..vertex_bytes.len()]);
+
+        for (to, from) in
+            self.indices[index_slice.clone()].iter_mut().zip(indices)
+        {
+            *to = from + vertex_range.start;
+        }
+
+        if !vertex_bytes.is_empty() {
+            self.vertex_buffer.write(
+                gpu_device,
+                &self.vertices[vertex_bytes.clone()],
+                vertex_bytes.start as u64,
+            );
+        }
+
+        if !index_slice.is_empty() {
+            self.index_buffer.write(
+                gpu_device,
+                bytemuck::cast_slice(&self.indices[index_slice]),
+                index_range.start as u64 * 4,
+            );
+        }
+    }
+
+    fn clear_indices(&mut self, gpu_device: &GpuDevice, range: Range<u32>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let slice = as_usize(&range);
+
+        self.indices[slice.clone()].fill(0);
+        self.index_buffer.write(
+            gpu_device,
+            bytemuck::cast_slice(&self.indices[slice]),
+            range.start as u64 * 4,
+        );
+    }
+}
+
+fn vertex_buffer<K: BufferLayout>(
+    gpu_device: &GpuDevice,
+    vertices: &[u8],
+) -> Buffer<K> {
+    Buffer::new(
+        gpu_device,
+        vertices,
+        wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        Some("Arena Vertex Buffer"),
+    )
+}
+
+fn index_buffer<K: BufferLayout>(
+    gpu_device: &GpuDevice,
+    indices: &[u32],
+) -> Buffer<K> {
+    Buffer::new(
+        gpu_device,
+        bytemuck::cast_slice(indices),
+        wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+        Some("Arena Index Buffer"),
+    )
+}
+
+fn as_usize(range: &Range<u32>) -> Range<usize> {
+    range.start as usize..range.end as usize
+}
+
+fn byte_range(range: &Range<u32>, stride: usize) -> Range<usize> {
+    range.start as usize * stride..range.end as usize * stride
+}
@@ -0,0 +1,480 @@
+use serde::Serialize;
+use std::ops::Range;
+
+/// Most bind group slots a pipeline in this crate uses.
+const MAX_BIND_GROUPS: usize = 4;
+/// Most vertex buffer slots a pipeline in this crate uses.
+const MAX_VERTEX_BUFFERS: usize = 2;
+
+/// The render pass calls the renderers make. Implemented by
+/// [`wgpu::RenderPass`] to draw right away and by [`DrawBatch`] to record
+/// the draws and submit them with fewer state changes.
+pub trait RenderCommands<'a> {
+    fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group: &'a wgpu::BindGroup,
+        offsets: &[wgpu::DynamicOffset],
+    );
+
+    fn set_pipeline(&mut self, pipeline: &'a wgpu::RenderPipeline);
+
+    fn set_vertex_buffer(&mut self, slot: u32, buffer: wgpu::BufferSlice<'a>);
+
+    fn set_index_buffer(
+        &mut self,
+        buffer: wgpu::BufferSlice<'a>,
+        format: wgpu::IndexFormat,
+    );
+
+    /// Sets all of a buffer. A [`DrawBatch`] can tell when it is already
+    /// set, which it can't for slices.
+    fn set_whole_vertex_buffer(&mut self, slot: u32, buffer: &'a wgpu::Buffer) {
+        self.set_vertex_buffer(slot, buffer.slice(..));
+    }
+
+    fn set_whole_index_buffer(
+        &mut self,
+        buffer: &'a wgpu::Buffer,
+        format: wgpu::IndexFormat,
+    ) {
+        self.set_index_buffer(buffer.slice(..), format);
+    }
+
+    fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>);
+
+    fn draw_indexed(
+        &mut self,
+        indices: Range<u32>,
+        base_vertex: i32,
+        instances: Range<u32>,
+    );
+}
+
+impl<'a> RenderCommands<'a> for wgpu::RenderPass<'a> {
+    fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group: &'a wgpu::BindGroup,
+        offsets: &[wgpu::DynamicOffset],
+    ) {
+        wgpu::RenderPass::set_bind_group(self, index, bind_group, offsets);
+    }
+
+    fn set_pipeline(&mut self, pipeline: &'a wgpu::RenderPipeline) {
+        wgpu::RenderPass::set_pipeline(self, pipeline);
+    }
+
+    fn set_vertex_buffer(&mut self, slot: u32, buffer: wgpu::BufferSlice<'a>) {
+        wgpu::RenderPass::set_vertex_buffer(self, slot, buffer);
+    }
+
+    fn set_index_buffer(
+        &mut self,
+        buffer: wgpu::BufferSlice<'a>,
+        format: wgpu::IndexFormat,
+    ) {
+        wgpu::RenderPass::set_index_buffer(self, buffer, format);
+    }
+
+    fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        wgpu::RenderPass::draw(self, vertices, instances);
+    }
+
+    fn draw_indexed(
+        &mut self,
+        indices: Range<u32>,
+        base_vertex: i32,
+        instances: Range<u32>,
+    ) {
+        wgpu::RenderPass::draw_indexed(self, indices, base_vertex, instances);
+    }
+}
+
+/// What a submitted [`DrawBatch`] did.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct RenderStats {
+    pub draws: u32,
+    pub instances: u32,
+    /// Bind groups set on the pass.
+    pub bind_group_switches: u32,
+    /// Bind group sets skipped as the group was already bound.
+    pub bind_group_skips: u32,
+    pub pipeline_switches: u32,
+    pub pipeline_skips: u32,
+    /// Vertex and index buffers set on the pass.
+    pub buffer_switches: u32,
+}
+
+impl std::ops::AddAssign for RenderStats {
+    fn add_assign(&mut self, other: Self) {
+        self.draws += other.draws;
+        self.instances += other.instances;
+        self.bind_group_switches += other.bind_group_switches;
+        self.bind_group_skips += other.bind_group_skips;
+        self.pipeline_switches += other.pipeline_switches;
+        self.pipeline_skips += other.pipeline_skips;
+        self.buffer_switches += other.buffer_switches;
+    }
+}
+
+/// A pipeline with the draws and instances made with it.
+#[cfg(feature = "frame_graph")]
+pub(crate) type PipelineUse<'a> = (Option<&'a wgpu::RenderPipeline>, u32, u32);
+
+#[derive(Clone)]
+struct BoundGroup<'a> {
+    group: &'a wgpu::BindGroup,
+    offsets: Vec<wgpu::DynamicOffset>,
+}
+
+impl<'a> BoundGroup<'a> {
+    fn same(&self, other: &BoundGroup<'a>) -> bool {
+        std::ptr::eq(self.group, other.group) && self.offsets == other.offsets
+    }
+}
+
+/// Buffer slices can't be compared, so each set call gets an id and draws
+/// recorded after the same call share it. Whole buffers are known by
+/// address.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum BufferId {
+    Call(u32),
+    Whole(usize),
+}
+
+#[derive(Copy, Clone)]
+struct BoundBuffer<'a> {
+    id: BufferId,
+    slice: wgpu::BufferSlice<'a>,
+}
+
+#[derive(Clone, Default)]
+struct DrawState<'a> {
+    pipeline: Option<&'a wgpu::RenderPipeline>,
+    bind_groups: [Option<BoundGroup<'a>>; MAX_BIND_GROUPS],
+    vertex_buffers: [Option<BoundBuffer<'a>>; MAX_VERTEX_BUFFERS],
+    index_buffer: Option<(BoundBuffer<'a>, wgpu::IndexFormat)>,
+}
+
+enum DrawCall {
+    Draw {
+        vertices: Range<u32>,
+    },
+    Indexed {
+        indices: Range<u32>,
+        base_vertex: i32,
+    },
+}
+
+struct Draw<'a> {
+    state: DrawState<'a>,
+    call: DrawCall,
+    instances: Range<u32>,
+    /// Draws in the same group may be reordered.
+    group: Option<u32>,
+}
+
+impl<'a> Draw<'a> {
+    /// Orders draws so ones sharing a pipeline and bind groups are next to
+    /// each other.
+    fn batch_key(&self) -> [usize; MAX_BIND_GROUPS + 1] {
+        let mut key = [0; MAX_BIND_GROUPS + 1];
+
+        key[0] = self.state.pipeline.map_or(0, |p| p as *const _ as usize);
+
+        for (slot, group) in self.state.bind_groups.iter().enumerate() {
+            key[slot + 1] =
+                group.as_ref().map_or(0, |g| g.group as *const _ as usize);
+        }
+
+        key
+    }
+}
+
+/// Records the draws of a pass and submits them with redundant pipeline and
+/// bind group changes left out. Renderers record into it the same way they
+/// draw into a [`wgpu::RenderPass`].
+///
+/// Draws keep their order, except between begin_reorder and end_reorder
+/// where they are grouped by pipeline and bind groups to switch less. Only
+/// reorder draws whose order doesn't matter, like opaque depth tested ones.
+#[derive(Default)]
+pub struct DrawBatch<'a> {
+    state: DrawState<'a>,
+    draws: Vec<Draw<'a>>,
+    next_buffer_id: u32,
+    group: Option<u32>,
+    next_group: u32,
+}
+
+impl<'a> DrawBatch<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.draws.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.draws.is_empty()
+    }
+
+    /// Draws recorded from here until end_reorder may change order.
+    pub fn begin_reorder(&mut self) {
+        self.group = Some(self.next_group);
+        self.next_group += 1;
+    }
+
+    pub fn end_reorder(&mut self) {
+        self.group = None;
+    }
+
+    /// Draws everything recorded into the pass.
+    pub fn submit(mut self, pass: &mut wgpu::RenderPass<'a>) -> RenderStats {
+        self.sort_groups();
+
+        let mut stats = RenderStats::default();
+        let mut bound = DrawState::default();
+
+        for draw in &self.draws {
+            let state = &draw.state;
+
+            match (state.pipeline, bound.pipeline) {
+                (Some(pipeline), Some(current))
+                    if std::ptr::eq(pipeline, current) =>
+                {
+                    stats.pipeline_skips += 1;
+                }
+                (Some(pipeline), _) => {
+                    pass.set_pipeline(pipeline);
+                    bound.pipeline = Some(pipeline);
+                    stats.pipeline_switches += 1;
+                }
+                (None, _) => {}
+            }
+
+            for (slot, group) in state.bind_groups.iter().enumerate() {
+                let Some(group) = group else {
+                    continue;
+                };
+
+                match &bound.bind_groups[slot] {
+                    Some(current) if current.same(group) => {
+                        stats.bind_group_skips += 1;
+                    }
+                    _ => {
+                        pass.set_bind_group(
+                            slot as u32,
+                            group.group,
+                            &group.offsets,
+                        );
+                        bound.bind_groups[slot] = Some(group.clone());
+                        stats.bind_group_switches += 1;
+                    }
+                }
+            }
+
+            for (slot, buffer) in state.vertex_buffers.iter().enumerate() {
+                let Some(buffer) = buffer else {
+                    continue;
+                };
+
+                if bound.vertex_buffers[slot].map(|b| b.id) != Some(buffer.id) {
+                    pass.set_vertex_buffer(slot as u32, buffer.slice);
+                    bound.vertex_buffers[slot] = Some(*buffer);
+                    stats.buffer_switches += 1;
+                }
+            }
+
+            if let Some((buffer, format)) = state.index_buffer {
+                if bound.index_buffer.map(|(b, _)| b.id) != Some(buffer.id) {
+                    pass.set_index_buffer(buffer.slice, format);
+                    bound.index_buffer = Some((buffer, format));
+                    stats.buffer_switches += 1;
+                }
+            }
+
+            match &draw.call {
+                DrawCall::Draw { vertices } => {
+                    pass.draw(vertices.clone(), draw.instances.clone());
+                }
+                DrawCall::Indexed {
+                    indices,
+                    base_vertex,
+                } => {
+                    pass.draw_indexed(
+                        indices.clone(),
+                        *base_vertex,
+                        draw.instances.clone(),
+                    );
+                }
+            }
+
+            stats.draws += 1;
+            stats.instances += draw.instances.len() as u32;
+        }
+
+        stats
+    }
+
+    /// Draws and instances per pipeline, in the order first used, and the
+    /// vertex and index buffers bound, for [`crate::FramePass`].
+    #[cfg(feature = "frame_graph")]
+    pub(crate) fn summary(&self) -> (Vec<PipelineUse<'a>>, u32) {
+        let mut pipelines: Vec<PipelineUse> = Vec::new();
+        let mut buffers = Vec::new();
+        let address = |p: Option<&wgpu::RenderPipeline>| {
+            p.map_or(0, |p| p as *const _ as usize)
+        };
+
+        for draw in &self.draws {
+            let state = &draw.state;
+            let instances = draw.instances.len() as u32;
+            let found = pipelines
+                .iter_mut()
+                .find(|(p, _, _)| address(*p) == address(state.pipeline));
+
+            match found {
+                Some((_, draws, total)) => {
+                    *draws += 1;
+                    *total += instances;
+                }
+                None => pipelines.push((state.pipeline, 1, instances)),
+            }
+
+            let bound = state
+                .vertex_buffers
+                .iter()
+                .flatten()
+                .chain(state.index_buffer.as_ref().map(|(b, _)| b));
+
+            for buffer in bound {
+                if !buffers.contains(&buffer.id) {
+                    buffers.push(buffer.id);
+                }
+            }
+        }
+
+        (pipelines, buffers.len() as u32)
+    }
+
+    /// Stable sorts each run of draws in the same reorder group.
+    fn sort_groups(&mut self) {
+        let mut start = 0;
+
+        while start < self.draws.len() {
+            let group = self.draws[start].group;
+            let end = self.draws[start..]
+                .iter()
+                .position(|draw| draw.group != group)
+                .map_or(self.draws.len(), |len| start + len);
+
+            if group.is_some() {
+                self.draws[start..end].sort_by_key(Draw::batch_key);
+            }
+
+            start = end;
+        }
+    }
+
+    fn call_id(&mut self) -> BufferId {
+        self.next_buffer_id += 1;
+        BufferId::Call(self.next_buffer_id)
+    }
+
+    fn bind_vertex_buffer(&mut self, slot: u32, buffer: BoundBuffer<'a>) {
+        if let Some(slot) = self.state.vertex_buffers.get_mut(slot as usize) {
+            *slot = Some(buffer);
+        }
+    }
+
+    fn push(&mut self, call: DrawCall, instances: Range<u32>) {
+        self.draws.push(Draw {
+            state: self.state.clone(),
+            call,
+            instances,
+            group: self.group,
+        });
+    }
+}
+
+impl<'a> RenderCommands<'a> for DrawBatch<'a> {
+    fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group: &'a wgpu::BindGroup,
+        offsets: &[wgpu::DynamicOffset],
+    ) {
+        if let Some(slot) = self.state.bind_groups.get_mut(index as usize) {
+            *slot = Some(BoundGroup {
+                group: bind_group,
+                offsets: offsets.to_vec(),
+            });
+        }
+    }
+
+    fn set_pipeline(&mut self, pipeline: &'a wgpu::RenderPipeline) {
+        self.state.pipeline = Some(pipeline);
+    }
+
+    fn set_vertex_buffer(&mut self, slot: u32, buffer: wgpu::BufferSlice<'a>) {
+        let id = self.call_id();
+
+        self.bind_vertex_buffer(slot, BoundBuffer { id, slice: buffer });
+    }
+
+    fn set_index_buffer(
+        &mut self,
+        buffer: wgpu::BufferSlice<'a>,
+        format: wgpu::IndexFormat,
+    ) {
+        let id = self.call_id();
+
+        self.state.index_buffer =
+            Some((BoundBuffer { id, slice: buffer }, format));
+    }
+
+    fn set_whole_vertex_buffer(&mut self, slot: u32, buffer: &'a wgpu::Buffer) {
+        let id = BufferId::Whole(buffer as *const _ as usize);
+
+        self.bind_vertex_buffer(
+            slot,
+            BoundBuffer {
+                id,
+                slice: buffer.slice(..),
+            },
+        );
+    }
+
+    fn set_whole_index_buffer(
+        &mut self,
+        buffer: &'a wgpu::Buffer,
+        format: wgpu::IndexFormat,
+    ) {
+        let id = BufferId::Whole(buffer as *const _ as usize);
+        let slice = buffer.slice(..);
+
+        self.state.index_buffer = Some((BoundBuffer { id, slice }, format));
+    }
+
+    fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        self.push(DrawCall::Draw { vertices }, instances);
+    }
+
+    fn draw_indexed(
+        &mut self,
+        indices: Range<u32>,
+        base_vertex: i32,
+        instances: Range<u32>,
+    ) {
+        self.push(
+            DrawCall::Indexed {
+                indices,
+                base_vertex,
+            },
+            instances,
+        );
+    }
+}
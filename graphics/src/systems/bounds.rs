@@ -1,4 +1,4 @@
-use crate::Vec3;
+use crate::{Vec2, Vec3};
 
 /// This is the location within the World.
 /// Height is needed to map world to the correct mouse coords.
@@ -104,6 +104,14 @@ impl Bounds {
             top,
         }
     }
+
+    /// if pos is within the bounds, edges included.
+    pub fn contains(&self, pos: Vec2) -> bool {
+        pos.x >= self.left
+            && pos.x <= self.right
+            && pos.y >= self.bottom
+            && pos.y <= self.top
+    }
 }
 
 impl Default for Bounds {
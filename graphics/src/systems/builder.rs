@@ -0,0 +1,367 @@
+use crate::{AscendingError, GpuDevice, GpuRenderer, GpuWindow};
+use camera::DepthMode;
+use std::path::PathBuf;
+use wgpu::TextureFormat;
+use winit::{dpi::PhysicalSize, window::Window};
+
+/// Surface formats tried in order when none are given.
+pub(crate) const DEFAULT_SURFACE_FORMATS: [TextureFormat; 2] =
+    [TextureFormat::Rgba8UnormSrgb, TextureFormat::Bgra8UnormSrgb];
+
+/// What the renderer draws into.
+enum RenderTarget {
+    Window(Window),
+    /// Offscreen texture of this size.
+    Headless(PhysicalSize<u32>),
+}
+
+/// Configures and creates a [`GpuRenderer`]. Everything but the target has
+/// a default, so only `with_window` or `headless` is needed.
+pub struct RendererBuilder {
+    target: Option<RenderTarget>,
+    backends: wgpu::Backends,
+    power_preference: wgpu::PowerPreference,
+    force_fallback_adapter: bool,
+    features: wgpu::Features,
+    limits: wgpu::Limits,
+    present_mode: wgpu::PresentMode,
+    sample_count: u32,
+    depth_mode: DepthMode,
+    surface_formats: Vec<TextureFormat>,
+    debug: bool,
+    trace_path: Option<PathBuf>,
+}
+
+impl Default for RendererBuilder {
+    fn default() -> Self {
+        Self {
+            target: None,
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            features: wgpu::Features::default(),
+            limits: wgpu::Limits::default(),
+            present_mode: wgpu::PresentMode::AutoVsync,
+            sample_count: 1,
+            depth_mode: DepthMode::Standard,
+            surface_formats: DEFAULT_SURFACE_FORMATS.to_vec(),
+            debug: false,
+            trace_path: None,
+        }
+    }
+}
+
+impl RendererBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders to the window's surface.
+    pub fn with_window(mut self, window: Window) -> Self {
+        self.target = Some(RenderTarget::Window(window));
+        self
+    }
+
+    /// Renders to an offscreen texture without a window.
+    pub fn headless(mut self, width: u32, height: u32) -> Self {
+        self.target =
+            Some(RenderTarget::Headless(PhysicalSize::new(width, height)));
+        self
+    }
+
+    pub fn with_backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    /// HighPerformance prefers dedicated GPUs, LowPower integrated ones.
+    pub fn with_power_preference(
+        mut self,
+        power_preference: wgpu::PowerPreference,
+    ) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    /// Forces a software adapter. Mostly for testing.
+    pub fn with_fallback_adapter(mut self, force: bool) -> Self {
+        self.force_fallback_adapter = force;
+        self
+    }
+
+    /// Features the adapter must support.
+    pub fn with_features(mut self, features: wgpu::Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Limits the adapter must support.
+    pub fn with_limits(mut self, limits: wgpu::Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Ignored when headless.
+    pub fn with_present_mode(
+        mut self,
+        present_mode: wgpu::PresentMode,
+    ) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// MSAA samples. 1 turns it off. Render passes must then draw into
+    /// [`GpuRenderer::render_target`] and resolve into
+    /// [`GpuRenderer::resolve_target`].
+    pub fn with_msaa(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Depth compare and clear of every pipeline. Cameras must use the
+    /// same mode, see [`camera::Camera::set_depth_mode`], and render
+    /// passes should clear with [`GpuDevice::depth_clear`].
+    pub fn with_depth_mode(mut self, depth_mode: DepthMode) -> Self {
+        self.depth_mode = depth_mode;
+        self
+    }
+
+    /// Formats to pick the surface format from, most wanted first.
+    pub fn with_surface_formats(mut self, formats: &[TextureFormat]) -> Self {
+        self.surface_formats = formats.to_vec();
+        self
+    }
+
+    /// Turns on the backend's validation layers and debug labels.
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Records an API trace into the folder if wgpu was built with tracing.
+    pub fn with_trace_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.trace_path = Some(path.into());
+        self
+    }
+
+    fn validate(&self) -> Result<(), AscendingError> {
+        if !matches!(self.sample_count, 1 | 2 | 4 | 8) {
+            return Err(invalid(format!(
+                "msaa sample count must be 1, 2, 4 or 8, got {}",
+                self.sample_count
+            )));
+        }
+
+        if self.surface_formats.is_empty() {
+            return Err(invalid("no surface formats were given".into()));
+        }
+
+        match &self.target {
+            None => {
+                Err(invalid("a window or headless size is required".into()))
+            }
+            Some(RenderTarget::Headless(size))
+                if size.width == 0 || size.height == 0 =>
+            {
+                Err(invalid("headless size can not be zero".into()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates the renderer and its pipelines.
+    pub async fn build(self) -> Result<GpuRenderer, AscendingError> {
+        self.validate()?;
+
+        let flags = if self.debug {
+            wgpu::InstanceFlags::debugging()
+        } else {
+            wgpu::InstanceFlags::default()
+        };
+
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: self.backends,
+            flags,
+            dx12_shader_compiler: wgpu::Dx12Compiler::default(),
+            gles_minor_version: wgpu::Gles3MinorVersion::Automatic,
+        });
+
+        let (window, size) = match self.target {
+            Some(RenderTarget::Window(window)) => {
+                let surface = unsafe { instance.create_surface(&window)? };
+                let size = window.inner_size();
+
+                (Some((window, surface)), size)
+            }
+            Some(RenderTarget::Headless(size)) => (None, size),
+            None => unreachable!("checked in validate"),
+        };
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: self.power_preference,
+                compatible_surface: window.as_ref().map(|(_, surface)| surface),
+                force_fallback_adapter: self.force_fallback_adapter,
+            })
+            .await
+            .ok_or(AscendingError::NoAdapter)?;
+
+        let missing = self.features - adapter.features();
+
+        if !missing.is_empty() {
+            return Err(invalid(format!(
+                "adapter is missing features {missing:?}"
+            )));
+        }
+
+        if !self.limits.check_limits(&adapter.limits()) {
+            return Err(invalid(
+                "limits are higher than the adapter supports".into(),
+            ));
+        }
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: None,
+                    features: self.features,
+                    limits: self.limits,
+                },
+                self.trace_path.as_deref(),
+            )
+            .await?;
+
+        finish_renderer(
+            adapter,
+            GpuDevice {
+                device,
+                queue,
+                sample_count: self.sample_count,
+                depth_mode: self.depth_mode,
+            },
+            window,
+            size,
+            self.present_mode,
+            &self.surface_formats,
+        )
+    }
+}
+
+fn invalid(msg: String) -> AscendingError {
+    AscendingError::InvalidRendererConfig(msg)
+}
+
+/// Picks the surface format, configures the target and creates the
+/// renderer with its pipelines.
+pub(crate) fn finish_renderer(
+    adapter: wgpu::Adapter,
+    gpu_device: GpuDevice,
+    window: Option<(Window, wgpu::Surface)>,
+    size: PhysicalSize<u32>,
+    present_mode: wgpu::PresentMode,
+    formats: &[TextureFormat],
+) -> Result<GpuRenderer, AscendingError> {
+    let (format, usage) = match &window {
+        Some((_, surface)) => {
+            let caps = surface.get_capabilities(&adapter);
+            let format = formats
+                .iter()
+                .find(|format| caps.formats.contains(format))
+                .copied()
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "surface supports none of {formats:?}, only {:?}",
+                        caps.formats
+                    ))
+                })?;
+
+            // Copying the frame lets transitions sample what was rendered.
+            let usage = if caps.usages.contains(wgpu::TextureUsages::COPY_SRC) {
+                wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::COPY_SRC
+            } else {
+                wgpu::TextureUsages::RENDER_ATTACHMENT
+            };
+
+            (format, usage)
+        }
+        None => {
+            let format = formats
+                .iter()
+                .find(|format| {
+                    adapter
+                        .get_texture_format_features(**format)
+                        .allowed_usages
+                        .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+                })
+                .copied()
+                .ok_or_else(|| {
+                    invalid(format!("adapter can not render to {formats:?}"))
+                })?;
+
+            (
+                format,
+                wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::COPY_SRC,
+            )
+        }
+    };
+
+    log::debug!("surface format: {:?}", format);
+
+    let sample_count = gpu_device.sample_count;
+
+    for format in [format, TextureFormat::Depth32Float] {
+        if !adapter
+            .get_texture_format_features(format)
+            .flags
+            .sample_count_supported(sample_count)
+        {
+            return Err(invalid(format!(
+                "{format:?} does not support {sample_count}x msaa"
+            )));
+        }
+    }
+
+    let surface_config = wgpu::SurfaceConfiguration {
+        usage,
+        format,
+        width: size.width,
+        height: size.height,
+        present_mode,
+        alpha_mode: wgpu::CompositeAlphaMode::Auto,
+        view_formats: vec![format],
+    };
+
+    let (window, surface, offscreen) = match window {
+        Some((window, surface)) => {
+            surface.configure(gpu_device.device(), &surface_config);
+            (Some(window), Some(surface), None)
+        }
+        None => {
+            let texture = GpuWindow::create_offscreen_texture(
+                &gpu_device,
+                &surface_config,
+            );
+
+            (None, None, Some(texture))
+        }
+    };
+
+    let mut renderer = GpuRenderer::new(
+        GpuWindow {
+            adapter,
+            surface,
+            window,
+            surface_format: format,
+            size: PhysicalSize::new(size.width as f32, size.height as f32),
+            surface_config,
+            offscreen,
+        },
+        gpu_device,
+    );
+
+    // Creates the shader rendering pipelines for each renderer.
+    renderer.create_pipelines(renderer.surface_format());
+    Ok(renderer)
+}
@@ -0,0 +1,60 @@
+use crate::Easing;
+use camera::{controls::CameraFocus, Projection};
+
+/// Where a camera transition ends. Parts left as None stay as they are.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CameraGoal {
+    pub focus: Option<CameraFocus>,
+    pub projection: Option<Projection>,
+}
+
+impl CameraGoal {
+    pub fn new(focus: CameraFocus, projection: Projection) -> Self {
+        Self {
+            focus: Some(focus),
+            projection: Some(projection),
+        }
+    }
+}
+
+impl From<CameraFocus> for CameraGoal {
+    fn from(focus: CameraFocus) -> Self {
+        Self {
+            focus: Some(focus),
+            projection: None,
+        }
+    }
+}
+
+impl From<Projection> for CameraGoal {
+    fn from(projection: Projection) -> Self {
+        Self {
+            focus: None,
+            projection: Some(projection),
+        }
+    }
+}
+
+/// A running [`crate::System::transition_to`].
+pub(crate) struct CameraTransition {
+    pub from_focus: Option<CameraFocus>,
+    pub from_projection: Projection,
+    pub goal: CameraGoal,
+    pub easing: Easing,
+    /// In seconds.
+    pub duration: f32,
+    pub elapsed: f32,
+}
+
+impl CameraTransition {
+    /// Moves the transition on and returns the eased progress.
+    pub fn advance(&mut self, delta: f32) -> f32 {
+        self.elapsed += delta;
+
+        if self.duration > 0.0 && self.elapsed < self.duration {
+            self.easing.apply(self.elapsed / self.duration)
+        } else {
+            1.0
+        }
+    }
+}
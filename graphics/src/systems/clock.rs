@@ -0,0 +1,91 @@
+use input::FrameTime;
+
+/// Game time for animations. Follows the frame time but can be slowed down,
+/// sped up or paused. The shaders animate off this clock through
+/// [`crate::System::update`], CPU side animations should take their delta
+/// from it too.
+#[derive(Copy, Clone, Debug)]
+pub struct AnimationClock {
+    /// Scaled seconds since the clock started.
+    seconds: f32,
+    /// Scaled seconds of the last frame.
+    delta_seconds: f32,
+    /// Unscaled seconds of the last frame.
+    real_delta_seconds: f32,
+    /// 1.0 is normal speed, lower is slow motion.
+    time_scale: f32,
+    paused: bool,
+}
+
+impl Default for AnimationClock {
+    fn default() -> Self {
+        Self {
+            seconds: 0.0,
+            delta_seconds: 0.0,
+            real_delta_seconds: 0.0,
+            time_scale: 1.0,
+            paused: false,
+        }
+    }
+}
+
+impl AnimationClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the clock by the frame's delta. Call once per frame after
+    /// the frame time was updated.
+    pub fn update(&mut self, frame_time: &FrameTime) {
+        self.real_delta_seconds = frame_time.delta_seconds();
+        self.delta_seconds = if self.paused {
+            0.0
+        } else {
+            self.real_delta_seconds * self.time_scale
+        };
+        self.seconds += self.delta_seconds;
+    }
+
+    pub fn seconds(&self) -> f32 {
+        self.seconds
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta_seconds
+    }
+
+    /// Delta for an object with its own time scale on top of the clock's.
+    pub fn scaled_delta(&self, time_scale: f32) -> f32 {
+        self.delta_seconds * time_scale.max(0.0)
+    }
+
+    /// Delta ignoring the time scale and pausing. For things like the
+    /// camera that should keep moving in slow motion.
+    pub fn real_delta_seconds(&self) -> f32 {
+        self.real_delta_seconds
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
@@ -0,0 +1,87 @@
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Which corner screen positions start from and which way y points.
+/// Rendering always uses bottom left with y up, this only changes the
+/// positions given to and returned from [`crate::System`], picking and
+/// text placement. Windows, winit and iced use top left with y down.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Hash, Default, Serialize, Deserialize,
+)]
+pub enum CoordinateSystem {
+    #[default]
+    BottomLeftYUp,
+    TopLeftYDown,
+}
+
+impl CoordinateSystem {
+    pub fn is_y_down(self) -> bool {
+        self == CoordinateSystem::TopLeftYDown
+    }
+
+    /// Converts a point in this system into render space.
+    pub fn to_render_pos(self, pos: Vec2, screen_height: f32) -> Vec2 {
+        match self {
+            CoordinateSystem::BottomLeftYUp => pos,
+            CoordinateSystem::TopLeftYDown => {
+                Vec2::new(pos.x, screen_height - pos.y)
+            }
+        }
+    }
+
+    /// Converts a point in render space into this system.
+    pub fn from_render_pos(self, pos: Vec2, screen_height: f32) -> Vec2 {
+        // Flipping is its own inverse.
+        self.to_render_pos(pos, screen_height)
+    }
+
+    /// Converts a rect's corner in this system, the top left one when y
+    /// is down, into its bottom left corner in render space.
+    pub fn to_render_rect(
+        self,
+        pos: Vec2,
+        size: Vec2,
+        screen_height: f32,
+    ) -> Vec2 {
+        match self {
+            CoordinateSystem::BottomLeftYUp => pos,
+            CoordinateSystem::TopLeftYDown => {
+                Vec2::new(pos.x, screen_height - pos.y - size.y)
+            }
+        }
+    }
+
+    /// Reverse of [`CoordinateSystem::to_render_rect`].
+    pub fn from_render_rect(
+        self,
+        pos: Vec2,
+        size: Vec2,
+        screen_height: f32,
+    ) -> Vec2 {
+        self.to_render_rect(pos, size, screen_height)
+    }
+
+    /// Converts a position relative to a rect's bottom left corner in
+    /// render space into one relative to this system's corner.
+    pub fn from_render_local(self, local: Vec2, size: Vec2) -> Vec2 {
+        match self {
+            CoordinateSystem::BottomLeftYUp => local,
+            CoordinateSystem::TopLeftYDown => {
+                Vec2::new(local.x, size.y - local.y)
+            }
+        }
+    }
+
+    /// Converts a point between two systems.
+    pub fn convert(
+        pos: Vec2,
+        from: CoordinateSystem,
+        to: CoordinateSystem,
+        screen_height: f32,
+    ) -> Vec2 {
+        to.from_render_pos(
+            from.to_render_pos(pos, screen_height),
+            screen_height,
+        )
+    }
+}
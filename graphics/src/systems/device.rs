@@ -1,5 +1,7 @@
+use super::builder::{finish_renderer, DEFAULT_SURFACE_FORMATS};
 use crate::{AscendingError, GpuRenderer};
 use async_trait::async_trait;
+use camera::DepthMode;
 use std::path::Path;
 use wgpu::TextureFormat;
 use winit::{
@@ -12,9 +14,52 @@ use winit::{
 pub struct GpuDevice {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
+    /// MSAA samples of the render target. 1 is off.
+    pub(crate) sample_count: u32,
+    /// How pipelines compare depth and the depth buffer is cleared.
+    pub(crate) depth_mode: DepthMode,
 }
 
 impl GpuDevice {
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Multisample state for pipelines drawing into the render target.
+    pub fn multisample_state(&self) -> wgpu::MultisampleState {
+        wgpu::MultisampleState {
+            count: self.sample_count,
+            ..Default::default()
+        }
+    }
+
+    pub fn depth_mode(&self) -> DepthMode {
+        self.depth_mode
+    }
+
+    /// Depth compare for pipelines drawing what is in front, LessEqual or
+    /// GreaterEqual when reversed.
+    pub fn depth_compare(&self) -> wgpu::CompareFunction {
+        match self.depth_mode {
+            DepthMode::Standard => wgpu::CompareFunction::LessEqual,
+            DepthMode::Reversed => wgpu::CompareFunction::GreaterEqual,
+        }
+    }
+
+    /// Depth compare for pipelines drawing only behind what is already
+    /// drawn, Greater or Less when reversed.
+    pub fn depth_compare_behind(&self) -> wgpu::CompareFunction {
+        match self.depth_mode {
+            DepthMode::Standard => wgpu::CompareFunction::Greater,
+            DepthMode::Reversed => wgpu::CompareFunction::Less,
+        }
+    }
+
+    /// Load op clearing the depth buffer to the far plane.
+    pub fn depth_clear(&self) -> wgpu::LoadOp<f32> {
+        wgpu::LoadOp::Clear(self.depth_mode.clear_value())
+    }
+
     pub fn device(&self) -> &wgpu::Device {
         &self.device
     }
@@ -24,14 +69,16 @@ impl GpuDevice {
     }
 }
 
-///Handles the Window, Adapter and Surface information.
+///Handles the Window, Adapter and Surface information. Headless renderers
+///have no window or surface and draw into an offscreen texture instead.
 pub struct GpuWindow {
     pub(crate) adapter: wgpu::Adapter,
-    pub(crate) surface: wgpu::Surface,
-    pub(crate) window: Window,
+    pub(crate) surface: Option<wgpu::Surface>,
+    pub(crate) window: Option<Window>,
     pub(crate) surface_format: wgpu::TextureFormat,
     pub(crate) size: PhysicalSize<f32>,
     pub(crate) surface_config: wgpu::SurfaceConfiguration,
+    pub(crate) offscreen: Option<wgpu::Texture>,
 }
 
 impl GpuWindow {
@@ -50,8 +97,18 @@ impl GpuWindow {
 
         self.surface_config.height = size.height;
         self.surface_config.width = size.width;
-        self.surface
-            .configure(gpu_device.device(), &self.surface_config);
+
+        if let Some(surface) = &self.surface {
+            surface.configure(gpu_device.device(), &self.surface_config);
+        }
+
+        if self.offscreen.is_some() {
+            self.offscreen = Some(Self::create_offscreen_texture(
+                gpu_device,
+                &self.surface_config,
+            ));
+        }
+
         self.size = PhysicalSize::new(size.width as f32, size.height as f32);
 
         Ok(())
@@ -61,8 +118,34 @@ impl GpuWindow {
         self.size
     }
 
-    pub fn surface(&self) -> &wgpu::Surface {
-        &self.surface
+    pub fn surface(&self) -> Option<&wgpu::Surface> {
+        self.surface.as_ref()
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.window.is_none()
+    }
+
+    pub(crate) fn create_offscreen_texture(
+        gpu_device: &GpuDevice,
+        config: &wgpu::SurfaceConfiguration,
+    ) -> wgpu::Texture {
+        gpu_device
+            .device()
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("offscreen texture"),
+                size: wgpu::Extent3d {
+                    width: config.width,
+                    height: config.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: config.format,
+                usage: config.usage,
+                view_formats: &[],
+            })
     }
 
     pub fn surface_format(&self) -> wgpu::TextureFormat {
@@ -74,11 +157,16 @@ impl GpuWindow {
         gpu_device: &GpuDevice,
         event: &Event<()>,
     ) -> Result<Option<wgpu::SurfaceTexture>, AscendingError> {
+        let (window, surface) = match (&self.window, &self.surface) {
+            (Some(window), Some(surface)) => (window, surface),
+            _ => return Ok(None),
+        };
+
         match event {
             Event::WindowEvent {
                 ref event,
                 window_id,
-            } if *window_id == self.window.id() => match event {
+            } if *window_id == window.id() => match event {
                 WindowEvent::Resized(physical_size) => {
                     self.resize(gpu_device, *physical_size)?;
                 }
@@ -87,24 +175,22 @@ impl GpuWindow {
                 }
                 _ => (),
             },
-            Event::RedrawRequested(_) => {
-                match self.surface.get_current_texture() {
-                    Ok(frame) => return Ok(Some(frame)),
-                    Err(wgpu::SurfaceError::Lost) => {
-                        let size = PhysicalSize::new(
-                            self.size.width as u32,
-                            self.size.height as u32,
-                        );
-                        self.resize(gpu_device, size)?;
-                    }
-                    Err(wgpu::SurfaceError::Outdated) => {
-                        return Ok(None);
-                    }
-                    Err(e) => return Err(AscendingError::from(e)),
+            Event::RedrawRequested(_) => match surface.get_current_texture() {
+                Ok(frame) => return Ok(Some(frame)),
+                Err(wgpu::SurfaceError::Lost) => {
+                    let size = PhysicalSize::new(
+                        self.size.width as u32,
+                        self.size.height as u32,
+                    );
+                    self.resize(gpu_device, size)?;
                 }
-            }
+                Err(wgpu::SurfaceError::Outdated) => {
+                    return Ok(None);
+                }
+                Err(e) => return Err(AscendingError::from(e)),
+            },
             Event::MainEventsCleared => {
-                self.window.request_redraw();
+                window.request_redraw();
             }
             _ => (),
         }
@@ -112,12 +198,48 @@ impl GpuWindow {
         Ok(None)
     }
 
+    /// Panics if the renderer is headless.
     pub fn window(&self) -> &Window {
-        &self.window
+        self.window
+            .as_ref()
+            .expect("headless renderer has no window")
     }
 
+    /// Panics if the renderer is headless.
     pub fn window_mut(&mut self) -> &mut Window {
-        &mut self.window
+        self.window
+            .as_mut()
+            .expect("headless renderer has no window")
+    }
+
+    /// Multisampled color target the frame gets resolved from.
+    pub(crate) fn create_msaa_view(
+        &self,
+        gpu_device: &GpuDevice,
+    ) -> Option<wgpu::TextureView> {
+        if gpu_device.sample_count <= 1 {
+            return None;
+        }
+
+        let texture =
+            gpu_device
+                .device()
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("msaa texture"),
+                    size: wgpu::Extent3d {
+                        width: self.surface_config.width,
+                        height: self.surface_config.height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: gpu_device.sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: self.surface_format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                });
+
+        Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
     }
 
     pub fn create_depth_texture(
@@ -129,6 +251,14 @@ impl GpuWindow {
             height: self.size.height as u32,
             depth_or_array_layers: 1,
         };
+        // Multisampled textures can only be render attachments.
+        let usage = if gpu_device.sample_count > 1 {
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+        } else {
+            wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_DST
+        };
 
         let texture =
             gpu_device
@@ -137,12 +267,10 @@ impl GpuWindow {
                     label: Some("depth texture"),
                     size,
                     mip_level_count: 1,
-                    sample_count: 1,
+                    sample_count: gpu_device.sample_count,
                     dimension: wgpu::TextureDimension::D2,
                     format: wgpu::TextureFormat::Depth32Float,
-                    usage: wgpu::TextureUsages::TEXTURE_BINDING
-                        | wgpu::TextureUsages::RENDER_ATTACHMENT
-                        | wgpu::TextureUsages::COPY_DST,
+                    usage,
                     view_formats: &[TextureFormat::Depth32Float],
                 });
 
@@ -173,59 +301,24 @@ impl AdapterExt for wgpu::Adapter {
         present_mode: wgpu::PresentMode,
     ) -> Result<GpuRenderer, AscendingError> {
         let size = window.inner_size();
+        let surface = unsafe { instance.create_surface(&window)? };
 
         let (device, queue) =
             self.request_device(device_descriptor, trace_path).await?;
 
-        let surface = unsafe { instance.create_surface(&window).unwrap() };
-        let caps = surface.get_capabilities(&self);
-
-        println!("{:?}", caps.formats);
-
-        let rgba = caps
-            .formats
-            .iter()
-            .position(|v| *v == TextureFormat::Rgba8UnormSrgb);
-        let bgra = caps
-            .formats
-            .iter()
-            .position(|v| *v == TextureFormat::Bgra8UnormSrgb);
-
-        let format = if let Some(pos) = rgba {
-            caps.formats[pos]
-        } else if let Some(pos) = bgra {
-            caps.formats[pos]
-        } else {
-            panic!("Your Rendering Device does not support Bgra8UnormSrgb or Rgba8UnormSrgb");
-        };
-
-        println!("surface format: {:?}", format);
-        let surface_config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format,
-            width: size.width,
-            height: size.height,
-            present_mode,
-            alpha_mode: wgpu::CompositeAlphaMode::Auto,
-            view_formats: vec![format],
-        };
-
-        surface.configure(&device, &surface_config);
-        let mut renderer = GpuRenderer::new(
-            GpuWindow {
-                adapter: self,
-                surface,
-                window,
-                surface_format: format,
-                size: PhysicalSize::new(size.width as f32, size.height as f32),
-                surface_config,
+        finish_renderer(
+            self,
+            GpuDevice {
+                device,
+                queue,
+                sample_count: 1,
+                depth_mode: DepthMode::Standard,
             },
-            GpuDevice { device, queue },
-        );
-
-        // Creates the shader rendering pipelines for each renderer.
-        renderer.create_pipelines(renderer.surface_format());
-        Ok(renderer)
+            Some((window, surface)),
+            size,
+            present_mode,
+            &DEFAULT_SURFACE_FORMATS,
+        )
     }
 }
 
@@ -11,6 +11,10 @@ pub struct DrawOrder {
     pub x: u32,     // Lower is lower
     pub y: u32,     // higher is lower
     pub z: u32,     // lower is higher
+    /// Drawable's own key, used by [`SortPolicy::Key`].
+    pub sort_key: u64,
+    /// Texture layer, grouped by [`SortPolicy::Texture`].
+    pub texture: u32,
 }
 
 impl PartialOrd for DrawOrder {
@@ -27,6 +31,8 @@ impl Ord for DrawOrder {
             .then(self.x.cmp(&other.x))
             .then(self.y.cmp(&other.y).reverse())
             .then(self.z.cmp(&other.z).reverse())
+            .then(self.sort_key.cmp(&other.sort_key))
+            .then(self.texture.cmp(&other.texture))
     }
 }
 
@@ -38,8 +44,101 @@ impl DrawOrder {
             x: (pos.x * 100.0) as u32,
             y: (pos.y * 100.0) as u32,
             z: (pos.z * 100.0) as u32,
+            sort_key: 0,
+            texture: 0,
         }
     }
+
+    pub fn with_sort_key(mut self, sort_key: u64) -> Self {
+        self.sort_key = sort_key;
+        self
+    }
+
+    pub fn with_texture(mut self, texture: u32) -> Self {
+        self.texture = texture;
+        self
+    }
+}
+
+/// How drawables are ordered within a layer of a buffer. Opaque ones
+/// always draw before transparent ones. Orders other than Position and Z
+/// can blend overlapping transparent drawables in the wrong order, in
+/// exchange for better batching or a custom look.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum SortPolicy {
+    /// By x, then y top to bottom, then z back to front.
+    #[default]
+    Position,
+    /// Back to front by z only.
+    Z,
+    /// Top to bottom by y, then back to front by z. Lower sprites overlap
+    /// higher ones, for top down views.
+    YThenZ,
+    /// Grouped by texture layer, then back to front by z.
+    Texture,
+    /// By each drawable's sort_key, lowest first.
+    Key,
+}
+
+impl SortPolicy {
+    /// Compares two orders of the same layer and alpha.
+    pub fn compare(self, a: &DrawOrder, b: &DrawOrder) -> Ordering {
+        let z = || a.z.cmp(&b.z).reverse();
+        let y = || a.y.cmp(&b.y).reverse();
+
+        match self {
+            SortPolicy::Position => a.cmp(b),
+            SortPolicy::Z => z().then_with(|| a.cmp(b)),
+            SortPolicy::YThenZ => y().then_with(z).then_with(|| a.cmp(b)),
+            SortPolicy::Texture => a
+                .texture
+                .cmp(&b.texture)
+                .then_with(z)
+                .then_with(|| a.cmp(b)),
+            SortPolicy::Key => {
+                a.sort_key.cmp(&b.sort_key).then_with(|| a.cmp(b))
+            }
+        }
+    }
+}
+
+/// The sort policy of each layer in a buffer.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SortPolicies {
+    pub(crate) default: SortPolicy,
+    pub(crate) layers: Vec<(u32, SortPolicy)>,
+}
+
+impl SortPolicies {
+    pub(crate) fn set_layer(&mut self, layer: u32, policy: SortPolicy) {
+        match self.layers.iter_mut().find(|(l, _)| *l == layer) {
+            Some(entry) => entry.1 = policy,
+            None => self.layers.push((layer, policy)),
+        }
+    }
+
+    fn get(&self, layer: u32) -> SortPolicy {
+        self.layers
+            .iter()
+            .find(|(l, _)| *l == layer)
+            .map_or(self.default, |(_, policy)| *policy)
+    }
+
+    pub(crate) fn sort(&self, buffers: &mut [OrderedIndex]) {
+        if self.default == SortPolicy::Position && self.layers.is_empty() {
+            buffers.sort();
+            return;
+        }
+
+        buffers.sort_by(|a, b| {
+            let (a, b) = (&a.order, &b.order);
+
+            a.layer
+                .cmp(&b.layer)
+                .then(a.alpha.cmp(&b.alpha))
+                .then_with(|| self.get(a.layer).compare(a, b))
+        });
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -79,4 +178,8 @@ impl OrderedIndex {
             index_max,
         }
     }
+
+    pub fn sort_key(&self) -> u64 {
+        self.order.sort_key
+    }
 }
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// How a value moves from start to end over a transition.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum Easing {
+    Linear,
+    /// Starts slow and speeds up.
+    EaseIn,
+    /// Starts fast and slows down.
+    EaseOut,
+    /// Slow at both ends.
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps progress t from 0.0..=1.0 onto the curve.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) * 0.5
+                }
+            }
+        }
+    }
+}
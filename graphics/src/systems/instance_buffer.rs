@@ -1,12 +1,54 @@
-use crate::{Buffer, BufferLayout, GpuDevice, GpuRenderer, OrderedIndex};
+use crate::{
+    Buffer, BufferLayout, GpuDevice, GpuRenderer, OrderedIndex, SortPolicies,
+    SortPolicy,
+};
 use std::ops::Range;
 
+/// The stores a widget added to an [`InstanceBuffer`] the last time it
+/// drew, so it can skip drawing while nothing about it changed. Mark it
+/// dirty whenever the widget's state does, a clean cache re-adds what it
+/// recorded even if the widget's rects or text were changed since.
+pub struct DrawCache {
+    indexes: Vec<OrderedIndex>,
+    dirty: bool,
+}
+
+impl DrawCache {
+    /// Dirty, so the first frame draws.
+    pub fn new() -> Self {
+        Self {
+            indexes: Vec::new(),
+            dirty: true,
+        }
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+impl Default for DrawCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 //This Holds onto all the instances Compressed into a byte array.
 pub struct InstanceBuffer<K: BufferLayout> {
     pub buffers: Vec<OrderedIndex>,
     pub buffer: Buffer<K>,
     // this is a calculation of the buffers size when being marked as ready to add into the buffer.
     needed_size: usize,
+    sort_policies: SortPolicies,
+    /// Stores added before the last finalize, in the order they were added.
+    last_buffers: Vec<OrderedIndex>,
+    last_size: usize,
+    /// if the last finalize wrote anything to the GPU.
+    written: bool,
 }
 
 impl<K: BufferLayout> InstanceBuffer<K> {
@@ -22,6 +64,10 @@ impl<K: BufferLayout> InstanceBuffer<K> {
                 Some("Instance Buffer"),
             ),
             needed_size: 0,
+            sort_policies: SortPolicies::default(),
+            last_buffers: Vec::new(),
+            last_size: 0,
+            written: false,
         }
     }
 
@@ -37,10 +83,94 @@ impl<K: BufferLayout> InstanceBuffer<K> {
         }
     }
 
+    /// Adds the stores cache recorded if it is clean and they all still
+    /// exist. Returns false when the widget has to draw instead, record
+    /// what it adds with [`InstanceBuffer::record_cache`].
+    pub fn add_cached(
+        &mut self,
+        renderer: &GpuRenderer,
+        cache: &DrawCache,
+    ) -> bool {
+        if cache.dirty
+            || cache
+                .indexes
+                .iter()
+                .any(|index| renderer.get_buffer(&index.index).is_none())
+        {
+            return false;
+        }
+
+        for index in &cache.indexes {
+            self.add_buffer_store(renderer, *index);
+        }
+
+        true
+    }
+
+    /// Number of stores added since the last finalize, where a widget's
+    /// draw starts for [`InstanceBuffer::record_cache`].
+    pub fn added(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Keeps the stores added from start on in cache and marks it clean.
+    pub fn record_cache(&self, start: usize, cache: &mut DrawCache) {
+        cache.indexes.clear();
+        cache
+            .indexes
+            .extend_from_slice(self.buffers.get(start..).unwrap_or_default());
+        cache.dirty = false;
+    }
+
+    /// How every layer without its own policy is ordered.
+    pub fn set_sort_policy(&mut self, policy: SortPolicy) {
+        self.sort_policies.default = policy;
+        self.last_buffers.clear();
+    }
+
+    pub fn set_layer_sort_policy(&mut self, layer: u32, policy: SortPolicy) {
+        self.sort_policies.set_layer(layer, policy);
+        self.last_buffers.clear();
+    }
+
+    /// if the last finalize wrote anything, false when the same stores
+    /// were added unchanged.
+    pub fn was_written(&self) -> bool {
+        self.written
+    }
+
+    /// if the stores added this frame are the ones of the last frame, in
+    /// the same order and none of them changed, so the GPU buffer already
+    /// holds them.
+    fn is_unchanged(&self, renderer: &GpuRenderer) -> bool {
+        self.needed_size == self.last_size
+            && self.needed_size <= self.buffer.max
+            && self.buffers.len() == self.last_buffers.len()
+            && self.buffers.iter().zip(&self.last_buffers).all(|(a, b)| {
+                a.index == b.index
+                    && a.order == b.order
+                    && renderer
+                        .get_buffer(&a.index)
+                        .is_some_and(|store| !store.changed)
+            })
+    }
+
     pub fn finalize(&mut self, renderer: &mut GpuRenderer) {
         let mut changed = false;
         let mut pos = 0;
 
+        if self.is_unchanged(renderer) {
+            self.written = false;
+            self.needed_size = 0;
+            self.buffers.clear();
+            return;
+        }
+
+        self.written = true;
+        self.last_size = self.needed_size;
+        self.last_buffers.clear();
+        self.last_buffers.extend_from_slice(&self.buffers);
+
         if self.needed_size > self.buffer.max {
             self.resize(renderer.gpu_device(), self.needed_size / K::stride());
             changed = true;
@@ -49,7 +179,7 @@ impl<K: BufferLayout> InstanceBuffer<K> {
         self.buffer.count = self.needed_size / K::stride();
         self.buffer.len = self.needed_size;
 
-        self.buffers.sort();
+        self.sort_policies.sort(&mut self.buffers);
 
         for buf in &self.buffers {
             let mut write_buffer = false;
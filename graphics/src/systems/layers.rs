@@ -0,0 +1,200 @@
+use crate::{AscendingError, SortPolicy};
+
+/// Share of a layer's z range drawables are placed in. The back of the
+/// range is left empty so a layer never touches the one behind it.
+const LAYER_FILL: f32 = 0.9;
+
+/// The usual layers from back to front with their depth in z units. They
+/// fill 100 units, the depth of [`Layers::standard`].
+pub const STANDARD_LAYERS: [(&str, u32); 6] = [
+    ("background", 20),
+    ("ground", 20),
+    ("objects", 15),
+    ("characters", 15),
+    ("overhead", 15),
+    ("ui", 15),
+];
+
+/// Handle to a named layer of a [`Layers`] registry. It holds the layer's
+/// z range so drawables can be placed without the registry.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DrawLayer {
+    index: u32,
+    /// Lower z draws in front.
+    front: f32,
+    back: f32,
+}
+
+impl DrawLayer {
+    /// Render layer drawables in it sort by. Layers added later are
+    /// higher and draw after the ones behind them.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The z range, front first.
+    pub fn range(&self) -> (f32, f32) {
+        (self.front, self.back)
+    }
+
+    /// z for a depth within the layer, 0.0 at its front and 1.0 at its
+    /// back. Depths outside are clamped so nothing leaves the layer.
+    pub fn z(&self, depth: f32) -> f32 {
+        self.front
+            + depth.clamp(0.0, 1.0) * (self.back - self.front) * LAYER_FILL
+    }
+
+    /// If z is within the layer's range.
+    pub fn contains(&self, z: f32) -> bool {
+        z >= self.front && z < self.back
+    }
+}
+
+struct LayerEntry {
+    name: String,
+    layer: DrawLayer,
+    sort_policy: Option<SortPolicy>,
+}
+
+/// Named layers mapping to z ranges, so drawables are placed by layer
+/// rather than by raw z and never z-fight with another layer.
+///
+/// Layers are added back to front, each taking the next range in front of
+/// the previous one, starting from the back of the depth. Handles stay
+/// valid as more are added. Pass [`Layers::depth`] to
+/// [`camera::Projection::orthographic_layers`] so every layer is visible.
+pub struct Layers {
+    depth: u32,
+    /// Front of the last added layer, where the next one's back goes.
+    next_back: u32,
+    layers: Vec<LayerEntry>,
+}
+
+impl Layers {
+    /// An empty registry spanning z 0.0 to depth.
+    pub fn new(depth: u32) -> Self {
+        Self {
+            depth,
+            next_back: depth,
+            layers: Vec::new(),
+        }
+    }
+
+    /// The [`STANDARD_LAYERS`] over a depth of 100.
+    pub fn standard() -> Self {
+        let mut layers = Self::new(100);
+
+        for (name, depth) in STANDARD_LAYERS {
+            layers
+                .add(name, depth)
+                .expect("standard layers fit their depth");
+        }
+
+        layers
+    }
+
+    /// Adds a layer in front of the others taking depth z units. Errors if
+    /// the name is taken or there is not enough depth left.
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        depth: u32,
+    ) -> Result<DrawLayer, AscendingError> {
+        let name = name.into();
+
+        if self.get(&name).is_some() {
+            return Err(AscendingError::InvalidLayer {
+                name,
+                message: "a layer with this name exists".into(),
+            });
+        }
+
+        if depth == 0 || depth > self.next_back {
+            return Err(AscendingError::InvalidLayer {
+                name,
+                message: format!(
+                    "needs a depth of 1 to {}, got {depth}",
+                    self.next_back
+                ),
+            });
+        }
+
+        let layer = DrawLayer {
+            index: self.layers.len() as u32,
+            front: (self.next_back - depth) as f32,
+            back: self.next_back as f32,
+        };
+
+        self.next_back -= depth;
+        self.layers.push(LayerEntry {
+            name,
+            layer,
+            sort_policy: None,
+        });
+
+        Ok(layer)
+    }
+
+    pub fn get(&self, name: &str) -> Option<DrawLayer> {
+        self.layers
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| entry.layer)
+    }
+
+    pub fn name(&self, layer: DrawLayer) -> Option<&str> {
+        self.layers
+            .get(layer.index as usize)
+            .map(|entry| entry.name.as_str())
+    }
+
+    /// The layer z is in. None in front of every layer.
+    pub fn layer_at(&self, z: f32) -> Option<DrawLayer> {
+        self.layers
+            .iter()
+            .map(|entry| entry.layer)
+            .find(|layer| layer.contains(z))
+    }
+
+    /// Orders drawables within the layer, applied to renderers by
+    /// [`Layers::sort_policies`].
+    pub fn set_sort_policy(&mut self, layer: DrawLayer, policy: SortPolicy) {
+        if let Some(entry) = self.layers.get_mut(layer.index as usize) {
+            entry.sort_policy = Some(policy);
+        }
+    }
+
+    /// Render layers and their sort policies to give each renderer's
+    /// `set_layer_sort_policy`.
+    pub fn sort_policies(
+        &self,
+    ) -> impl Iterator<Item = (u32, SortPolicy)> + '_ {
+        self.layers.iter().filter_map(|entry| {
+            entry.sort_policy.map(|policy| (entry.layer.index, policy))
+        })
+    }
+
+    /// Layers from back to front.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, DrawLayer)> {
+        self.layers
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.layer))
+    }
+
+    /// z units the layers can span, for
+    /// [`camera::Projection::orthographic_layers`].
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// z units in front of every layer not yet taken.
+    pub fn remaining(&self) -> u32 {
+        self.next_back
+    }
+}
+
+impl Default for Layers {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
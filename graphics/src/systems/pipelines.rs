@@ -21,12 +21,15 @@ pub trait PipeLineLayout: Pod + Zeroable {
 
 pub struct PipelineStorage {
     pub(crate) map: FxHashMap<(TypeId, Vec<u8>), wgpu::RenderPipeline>,
+    /// Type name of the layout each pipeline was made from.
+    names: FxHashMap<(TypeId, Vec<u8>), &'static str>,
 }
 
 impl PipelineStorage {
     pub fn new() -> Self {
         Self {
             map: FxHashMap::default(),
+            names: FxHashMap::default(),
         }
     }
 
@@ -38,7 +41,10 @@ impl PipelineStorage {
         pipeline: K,
     ) {
         let key = pipeline.layout_key();
+        let name = std::any::type_name::<K>();
 
+        self.names
+            .insert(key.clone(), name.rsplit("::").next().unwrap_or(name));
         self.map.insert(
             key,
             pipeline.create_layout(device, layout_storage, surface_format),
@@ -53,6 +59,18 @@ impl PipelineStorage {
 
         self.map.get(&key)
     }
+
+    /// Name of the layout type pipeline was made from, like
+    /// "ImageRenderPipeline".
+    pub fn name_of(
+        &self,
+        pipeline: &wgpu::RenderPipeline,
+    ) -> Option<&'static str> {
+        self.map
+            .iter()
+            .find(|(_, stored)| std::ptr::eq(*stored, pipeline))
+            .and_then(|(key, _)| self.names.get(key).copied())
+    }
 }
 
 impl Default for PipelineStorage {
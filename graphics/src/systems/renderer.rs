@@ -1,7 +1,7 @@
 use crate::{
     AscendingError, BufferPass, BufferStore, GpuDevice, GpuWindow, Index,
     Layout, LayoutStorage, OtherError, PipeLineLayout, PipelineStorage,
-    StaticBufferObject,
+    RenderCommands, StaticBufferObject,
 };
 use cosmic_text::FontSystem;
 use generational_array::{
@@ -9,7 +9,11 @@ use generational_array::{
 };
 use std::rc::Rc;
 
-use winit::{dpi::PhysicalSize, event::Event, window::Window};
+use winit::{
+    dpi::PhysicalSize,
+    event::Event,
+    window::{CursorGrabMode, Window},
+};
 
 ///Handles the Window, Device and buffer stores.
 pub struct GpuRenderer {
@@ -20,6 +24,8 @@ pub struct GpuRenderer {
     pub(crate) pipeline_storage: PipelineStorage,
     pub(crate) depthbuffer: wgpu::TextureView,
     pub(crate) framebuffer: Option<wgpu::TextureView>,
+    /// Multisampled target drawn into before resolving to the frame.
+    pub(crate) msaa_view: Option<wgpu::TextureView>,
     pub(crate) frame: Option<wgpu::SurfaceTexture>,
     pub font_sys: FontSystem,
     pub buffer_object: StaticBufferObject,
@@ -32,14 +38,15 @@ where
     fn set_buffers(&mut self, buffer: BufferPass<'b>);
 }
 
-impl<'a, 'b> SetBuffers<'a, 'b> for wgpu::RenderPass<'a>
+impl<'a, 'b, P> SetBuffers<'a, 'b> for P
 where
     'b: 'a,
+    P: RenderCommands<'a>,
 {
     fn set_buffers(&mut self, buffer: BufferPass<'b>) {
-        self.set_vertex_buffer(0, buffer.vertex_buffer.slice(..));
-        self.set_index_buffer(
-            buffer.index_buffer.slice(..),
+        self.set_whole_vertex_buffer(0, buffer.vertex_buffer);
+        self.set_whole_index_buffer(
+            buffer.index_buffer,
             wgpu::IndexFormat::Uint32,
         );
     }
@@ -49,6 +56,10 @@ impl GpuRenderer {
     pub fn new(window: GpuWindow, device: GpuDevice) -> Self {
         let buffer_object = StaticBufferObject::create_buffer(&device);
         let depth_buffer = window.create_depth_texture(&device);
+        let msaa_view = window.create_msaa_view(&device);
+        let framebuffer = window.offscreen.as_ref().map(|texture| {
+            texture.create_view(&wgpu::TextureViewDescriptor::default())
+        });
 
         Self {
             window,
@@ -57,7 +68,8 @@ impl GpuRenderer {
             layout_storage: LayoutStorage::new(),
             pipeline_storage: PipelineStorage::new(),
             depthbuffer: depth_buffer,
-            framebuffer: None,
+            framebuffer,
+            msaa_view,
             frame: None,
             font_sys: FontSystem::new(),
             buffer_object,
@@ -72,13 +84,53 @@ impl GpuRenderer {
         &mut self,
         size: PhysicalSize<u32>,
     ) -> Result<(), AscendingError> {
-        self.window.resize(&self.device, size)
+        self.window.resize(&self.device, size)?;
+
+        if let Some(texture) = &self.window.offscreen {
+            self.framebuffer = Some(
+                texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            );
+        }
+
+        Ok(())
     }
 
+    /// View of the current frame. Headless renderers always have one.
     pub fn frame_buffer(&self) -> &Option<wgpu::TextureView> {
         &self.framebuffer
     }
 
+    /// Texture of the current frame for copying from.
+    pub fn frame_texture(&self) -> Option<&wgpu::Texture> {
+        match &self.frame {
+            Some(frame) => Some(&frame.texture),
+            None => self.window.offscreen.as_ref(),
+        }
+    }
+
+    /// Where render passes should draw. The multisampled target with MSAA
+    /// on, otherwise the frame.
+    pub fn render_target(&self) -> Option<&wgpu::TextureView> {
+        match &self.msaa_view {
+            Some(view) => Some(view),
+            None => self.framebuffer.as_ref(),
+        }
+    }
+
+    /// Resolve target for render passes drawing into
+    /// [`GpuRenderer::render_target`]. None without MSAA.
+    pub fn resolve_target(&self) -> Option<&wgpu::TextureView> {
+        self.msaa_view.as_ref().and(self.framebuffer.as_ref())
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.device.sample_count
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.window.is_headless()
+    }
+
     pub fn depth_buffer(&self) -> &wgpu::TextureView {
         &self.depthbuffer
     }
@@ -87,14 +139,33 @@ impl GpuRenderer {
         self.window.size
     }
 
-    pub fn surface(&self) -> &wgpu::Surface {
-        &self.window.surface
+    /// None when headless.
+    pub fn surface(&self) -> Option<&wgpu::Surface> {
+        self.window.surface()
     }
 
     pub fn surface_format(&self) -> wgpu::TextureFormat {
         self.window.surface_format
     }
 
+    /// if the fragment shader can read storage buffers. Lights fall back
+    /// to fixed size uniform arrays without it.
+    pub fn storage_supported(&self) -> bool {
+        self.adapter()
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::FRAGMENT_STORAGE)
+            && self.device().limits().max_storage_buffers_per_shader_stage >= 6
+    }
+
+    /// if the frame can be copied from for effects that sample it.
+    pub fn frame_copyable(&self) -> bool {
+        self.window
+            .surface_config
+            .usage
+            .contains(wgpu::TextureUsages::COPY_SRC)
+    }
+
     pub fn update(
         &mut self,
         event: &Event<()>,
@@ -114,19 +185,69 @@ impl GpuRenderer {
         Ok(true)
     }
 
+    /// Panics if the renderer is headless.
     pub fn window(&self) -> &Window {
-        &self.window.window
+        self.window.window()
     }
 
+    /// Panics if the renderer is headless.
     pub fn window_mut(&mut self) -> &mut Window {
-        &mut self.window.window
+        self.window.window_mut()
+    }
+
+    /// Refresh rate in Hz of the monitor the window is on. None when
+    /// headless or if the platform doesn't report it.
+    pub fn refresh_rate(&self) -> Option<f32> {
+        if self.is_headless() {
+            return None;
+        }
+
+        self.window()
+            .current_monitor()?
+            .refresh_rate_millihertz()
+            .map(|millihertz| millihertz as f32 / 1000.0)
+    }
+
+    /// Locks and hides the cursor for mouse look, or frees it again. Pair
+    /// it with [`input::MouseDeltaSource::Raw`], as a locked cursor doesn't
+    /// move. Does nothing when headless.
+    pub fn set_mouse_locked(
+        &mut self,
+        locked: bool,
+    ) -> Result<(), AscendingError> {
+        if self.is_headless() {
+            return Ok(());
+        }
+
+        let window = self.window();
+
+        if locked {
+            // Some platforms can only keep the cursor inside the window.
+            window
+                .set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_| {
+                    window.set_cursor_grab(CursorGrabMode::Confined)
+                })?;
+        } else {
+            window.set_cursor_grab(CursorGrabMode::None)?;
+        }
+
+        window.set_cursor_visible(!locked);
+        Ok(())
     }
 
+    /// Recreates the size dependent targets. Call after a resize.
     pub fn update_depth_texture(&mut self) {
         self.depthbuffer = self.window.create_depth_texture(&self.device);
+        self.msaa_view = self.window.create_msaa_view(&self.device);
     }
 
+    /// Headless renderers have nothing to present and keep their frame.
     pub fn present(&mut self) -> Result<(), AscendingError> {
+        if self.is_headless() {
+            return Ok(());
+        }
+
         self.framebuffer = None;
 
         match self.frame.take() {
@@ -197,6 +318,15 @@ impl GpuRenderer {
     }
 
     pub fn create_pipelines(&mut self, surface_format: wgpu::TextureFormat) {
+        // Layout mismatches otherwise only show up as garbage on screen.
+        #[cfg(debug_assertions)]
+        if let Err(errors) = crate::shaders::validate_layouts() {
+            for e in errors {
+                log::error!("{e}");
+            }
+        }
+
+        #[cfg(feature = "sprites")]
         self.pipeline_storage.create_pipeline(
             &mut self.device,
             &mut self.layout_storage,
@@ -204,6 +334,15 @@ impl GpuRenderer {
             crate::ImageRenderPipeline,
         );
 
+        #[cfg(feature = "sprites")]
+        self.pipeline_storage.create_pipeline(
+            &mut self.device,
+            &mut self.layout_storage,
+            surface_format,
+            crate::ImageSilhouetteRenderPipeline,
+        );
+
+        #[cfg(feature = "maps")]
         self.pipeline_storage.create_pipeline(
             &mut self.device,
             &mut self.layout_storage,
@@ -211,6 +350,15 @@ impl GpuRenderer {
             crate::MapRenderPipeline,
         );
 
+        #[cfg(feature = "maps")]
+        self.pipeline_storage.create_pipeline(
+            &mut self.device,
+            &mut self.layout_storage,
+            surface_format,
+            crate::MapFadeRenderPipeline,
+        );
+
+        #[cfg(feature = "text")]
         self.pipeline_storage.create_pipeline(
             &mut self.device,
             &mut self.layout_storage,
@@ -218,6 +366,7 @@ impl GpuRenderer {
             crate::TextRenderPipeline,
         );
 
+        #[cfg(feature = "shapes")]
         self.pipeline_storage.create_pipeline(
             &mut self.device,
             &mut self.layout_storage,
@@ -225,12 +374,87 @@ impl GpuRenderer {
             crate::Mesh2DRenderPipeline,
         );
 
+        #[cfg(feature = "lights")]
         self.pipeline_storage.create_pipeline(
             &mut self.device,
             &mut self.layout_storage,
             surface_format,
             crate::LightRenderPipeline,
         );
+
+        #[cfg(feature = "lights")]
+        if self.storage_supported() {
+            self.pipeline_storage.create_pipeline(
+                &mut self.device,
+                &mut self.layout_storage,
+                surface_format,
+                crate::LightStorageRenderPipeline,
+            );
+        }
+
+        #[cfg(feature = "shapes")]
+        self.pipeline_storage.create_pipeline(
+            &mut self.device,
+            &mut self.layout_storage,
+            surface_format,
+            crate::RectRenderPipeline,
+        );
+
+        self.pipeline_storage.create_pipeline(
+            &mut self.device,
+            &mut self.layout_storage,
+            surface_format,
+            crate::DamageClearRenderPipeline,
+        );
+
+        self.pipeline_storage.create_pipeline(
+            &mut self.device,
+            &mut self.layout_storage,
+            surface_format,
+            crate::DisplayRenderPipeline,
+        );
+
+        self.pipeline_storage.create_pipeline(
+            &mut self.device,
+            &mut self.layout_storage,
+            surface_format,
+            crate::TransitionRenderPipeline,
+        );
+
+        self.pipeline_storage.create_pipeline(
+            &mut self.device,
+            &mut self.layout_storage,
+            surface_format,
+            crate::UpscaleRenderPipeline,
+        );
+
+        #[cfg(feature = "maps")]
+        self.pipeline_storage.create_pipeline(
+            &mut self.device,
+            &mut self.layout_storage,
+            surface_format,
+            crate::WaterRenderPipeline,
+        );
+
+        #[cfg(all(feature = "sprites", feature = "shapes"))]
+        self.pipeline_storage.create_pipeline(
+            &mut self.device,
+            &mut self.layout_storage,
+            surface_format,
+            crate::SelectionRenderPipeline,
+        );
+    }
+
+    /// Creates a pipeline outside the built in ones, like a plugin's.
+    pub fn create_pipeline<K: PipeLineLayout>(&mut self, pipeline: K) {
+        let surface_format = self.surface_format();
+
+        self.pipeline_storage.create_pipeline(
+            &mut self.device,
+            &mut self.layout_storage,
+            surface_format,
+            pipeline,
+        );
     }
 
     pub fn get_pipelines<K: PipeLineLayout>(
@@ -239,4 +463,13 @@ impl GpuRenderer {
     ) -> Option<&wgpu::RenderPipeline> {
         self.pipeline_storage.get_pipeline(pipeline)
     }
+
+    /// Name of the layout type a pipeline made by this renderer came from,
+    /// like "ImageRenderPipeline".
+    pub fn pipeline_name(
+        &self,
+        pipeline: &wgpu::RenderPipeline,
+    ) -> Option<&'static str> {
+        self.pipeline_storage.name_of(pipeline)
+    }
 }
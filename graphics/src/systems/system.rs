@@ -1,10 +1,10 @@
-use crate::{GpuDevice, GpuRenderer, Layout, WorldBounds};
+use crate::{
+    AnimationClock, Bounds, CameraGoal, CameraTransition, CoordinateSystem,
+    Easing, EngineUniform, GpuDevice, GpuRenderer, Layout, WorldBounds,
+};
 use bytemuck::{Pod, Zeroable};
-use camera::Projection;
-use crevice::std140::AsStd140;
+use camera::{controls::CameraFocus, Projection};
 use glam::{Mat4, Vec2, Vec3, Vec4};
-use input::FrameTime;
-use wgpu::util::DeviceExt;
 
 #[cfg(feature = "iced")]
 use iced_wgpu::graphics::Viewport;
@@ -23,45 +23,57 @@ impl Layout for SystemLayout {
         gpu_device.device().create_bind_group_layout(
             &wgpu::BindGroupLayoutDescriptor {
                 label: Some("system_bind_group_layout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX
-                        | wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }],
+                entries: &[GlobalUniform::layout_entry(0)],
             },
         )
     }
 }
 
-#[derive(AsStd140)]
-pub struct CameraUniform {
-    view: mint::ColumnMatrix4<f32>,
-    proj: mint::ColumnMatrix4<f32>,
-    inverse_proj: mint::ColumnMatrix4<f32>,
-    eye: mint::Vector3<f32>,
-    scale: f32,
-}
-
-#[derive(AsStd140)]
-pub struct ScreenUniform {
-    size: mint::Vector2<f32>,
-}
-
-#[derive(AsStd140)]
-pub struct TimeUniform {
-    //seconds since the start of the program. given by the FrameTime
-    seconds: f32,
+/// The system bind group's buffer, the `Global` struct of the shaders.
+/// See [`crate::shaders::GLOBAL_SHADER`] for using it in custom shaders.
+#[derive(EngineUniform)]
+pub struct GlobalUniform {
+    pub view: mint::ColumnMatrix4<f32>,
+    pub proj: mint::ColumnMatrix4<f32>,
+    pub inverse_proj: mint::ColumnMatrix4<f32>,
+    pub eye: mint::Vector3<f32>,
+    pub scale: f32,
+    pub size: mint::Vector2<f32>,
+    /// Seconds since the start of the program, given by the clock.
+    pub seconds: f32,
+    /// 0.0 at midnight, 0.5 at noon.
+    pub time_of_day: f32,
+    pub wind: mint::Vector2<f32>,
+    pub seed: u32,
+    pub ambient: mint::Vector4<f32>,
+    /// 1 when positions get rounded to whole screen pixels.
+    pub pixel_snap: u32,
 }
 
 pub struct System<Controls: camera::controls::Controls> {
     camera: camera::Camera<Controls>,
     pub screen_size: [f32; 2],
+    /// Convention of the screen positions the helpers take and return.
+    coordinate_system: CoordinateSystem,
+    /// Part of the world the camera is kept in.
+    world_bounds: Option<Bounds>,
+    /// Window scale factor from the monitor.
+    hidpi_scale: f32,
+    /// User set GUI scale on top of the hidpi one, like 1.25 for 125%.
+    ui_scale: f32,
+    gui_scale_changed: bool,
+    time_of_day: f32,
+    /// Real seconds a whole day takes, or None to only change it by hand.
+    day_length: Option<f32>,
+    wind: Vec2,
+    ambient: Vec4,
+    seed: u32,
+    pixel_snap: bool,
+    /// if wind, ambient, seed or pixel snap need writing to the buffer.
+    globals_changed: bool,
+    camera_transition: Option<CameraTransition>,
+    /// Set when a camera transition finishes and cleared when taken.
+    camera_transition_finished: bool,
     global_buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
     #[cfg(feature = "iced")]
@@ -96,6 +108,7 @@ where
     ) -> Self {
         let mut camera = camera::Camera::new(projection, controls);
 
+        camera.set_depth_mode(renderer.gpu_device().depth_mode());
         camera.update(0.0);
 
         #[cfg(feature = "iced")]
@@ -113,34 +126,23 @@ where
         let eye: mint::Vector3<f32> = camera.eye().into();
         let scale = camera.scale();
 
-        let camera_info = CameraUniform {
+        let global = GlobalUniform {
             view,
             proj,
             inverse_proj: inverse_proj.into(),
             eye,
             scale,
-        };
-        let time_info = TimeUniform { seconds: 0.0 };
-        let screen_info = ScreenUniform {
             size: screen_size.into(),
+            seconds: 0.0,
+            time_of_day: 0.5,
+            wind: [0.0, 0.0].into(),
+            seed: 0,
+            ambient: [1.0, 1.0, 1.0, 1.0].into(),
+            pixel_snap: 0,
         };
 
-        let mut camera_bytes = camera_info.as_std140().as_bytes().to_vec();
-        let mut time_bytes = time_info.as_std140().as_bytes().to_vec();
-        let mut screen_bytes = screen_info.as_std140().as_bytes().to_vec();
-
-        camera_bytes.append(&mut screen_bytes);
-        camera_bytes.append(&mut time_bytes);
-
         // Create the uniform buffers.
-        let global_buffer = renderer.device().create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("camera buffer"),
-                contents: &camera_bytes,
-                usage: wgpu::BufferUsages::UNIFORM
-                    | wgpu::BufferUsages::COPY_DST,
-            },
-        );
+        let global_buffer = global.create_buffer(renderer.gpu_device());
 
         // Create the bind group layout for the camera.
         let layout = renderer.create_layout(SystemLayout);
@@ -161,6 +163,20 @@ where
         Self {
             camera,
             screen_size,
+            coordinate_system: CoordinateSystem::default(),
+            world_bounds: None,
+            hidpi_scale: 1.0,
+            ui_scale: 1.0,
+            gui_scale_changed: false,
+            time_of_day: 0.5,
+            day_length: None,
+            wind: Vec2::ZERO,
+            ambient: Vec4::ONE,
+            seed: 0,
+            pixel_snap: false,
+            globals_changed: false,
+            camera_transition: None,
+            camera_transition_finished: false,
             global_buffer,
             bind_group,
             #[cfg(feature = "iced")]
@@ -168,6 +184,131 @@ where
         }
     }
 
+    pub fn coordinate_system(&self) -> CoordinateSystem {
+        self.coordinate_system
+    }
+
+    pub fn set_coordinate_system(
+        &mut self,
+        coordinate_system: CoordinateSystem,
+    ) {
+        self.coordinate_system = coordinate_system;
+    }
+
+    /// Converts a screen position in the coordinate system into render
+    /// space, bottom left with y up.
+    pub fn to_render_pos(&self, pos: Vec2) -> Vec2 {
+        self.coordinate_system
+            .to_render_pos(pos, self.screen_size[1])
+    }
+
+    pub fn from_render_pos(&self, pos: Vec2) -> Vec2 {
+        self.coordinate_system
+            .from_render_pos(pos, self.screen_size[1])
+    }
+
+    /// Converts a window position, like the mouse position from input or
+    /// iced, into the coordinate system.
+    pub fn window_to_screen_pos(&self, pos: Vec2) -> Vec2 {
+        CoordinateSystem::convert(
+            pos,
+            CoordinateSystem::TopLeftYDown,
+            self.coordinate_system,
+            self.screen_size[1],
+        )
+    }
+
+    /// Converts a screen position into window coordinates for iced.
+    pub fn screen_to_window_pos(&self, pos: Vec2) -> Vec2 {
+        CoordinateSystem::convert(
+            pos,
+            self.coordinate_system,
+            CoordinateSystem::TopLeftYDown,
+            self.screen_size[1],
+        )
+    }
+
+    pub fn hidpi_scale(&self) -> f32 {
+        self.hidpi_scale
+    }
+
+    /// Call with the window's scale factor on start and when it changes.
+    pub fn set_hidpi_scale(&mut self, scale: f32) {
+        self.hidpi_scale = scale.max(f32::EPSILON);
+        self.update_gui_scale();
+    }
+
+    pub fn ui_scale(&self) -> f32 {
+        self.ui_scale
+    }
+
+    /// Scales the GUI's layout, hit testing and text, like from a
+    /// settings menu. Takes effect on the next GUI update.
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        self.ui_scale = scale.max(f32::EPSILON);
+        self.update_gui_scale();
+    }
+
+    /// hidpi scale times UI scale. What the GUI lays out with and what
+    /// window events should be converted with.
+    pub fn gui_scale(&self) -> f32 {
+        self.hidpi_scale * self.ui_scale
+    }
+
+    /// If the GUI scale changed since last asked. The GUI needs a
+    /// relayout then even without new events.
+    pub fn take_gui_scale_changed(&mut self) -> bool {
+        std::mem::take(&mut self.gui_scale_changed)
+    }
+
+    fn update_gui_scale(&mut self) {
+        self.gui_scale_changed = true;
+
+        #[cfg(feature = "iced")]
+        {
+            self.iced_view = Viewport::with_physical_size(
+                Size::new(
+                    self.screen_size[0] as u32,
+                    self.screen_size[1] as u32,
+                ),
+                self.gui_scale() as f64,
+            );
+        }
+    }
+
+    /// Converts a window position into the GUI's scaled logical
+    /// coordinates, top left with y down.
+    pub fn window_to_gui_pos(&self, pos: Vec2) -> Vec2 {
+        pos / self.gui_scale()
+    }
+
+    /// Reverse of [`System::window_to_gui_pos`].
+    pub fn gui_to_window_pos(&self, pos: Vec2) -> Vec2 {
+        pos * self.gui_scale()
+    }
+
+    pub fn world_bounds(&self) -> Option<Bounds> {
+        self.world_bounds
+    }
+
+    /// Stops the camera from showing past the bounds, like a map's edges.
+    /// Worlds smaller than the screen at the current zoom are centered.
+    /// Only controls that support limits, like the flat ones, follow it.
+    pub fn set_world_bounds(&mut self, bounds: Option<Bounds>) {
+        self.world_bounds = bounds;
+        self.send_world_bounds();
+    }
+
+    fn send_world_bounds(&mut self) {
+        let bounds = self
+            .world_bounds
+            .map(|b| [b.left, b.bottom, b.right, b.top]);
+
+        self.camera
+            .controls_mut()
+            .set_bounds(bounds, self.screen_size);
+    }
+
     pub fn projection(&self) -> mint::ColumnMatrix4<f32> {
         self.camera.projection()
     }
@@ -180,8 +321,12 @@ where
         self.camera.set_projection(projection);
     }
 
-    pub fn update(&mut self, renderer: &GpuRenderer, frame_time: &FrameTime) {
-        if self.camera.update(frame_time.delta_seconds()) {
+    /// The camera moves in real time while the shaders get the clock's
+    /// scaled time.
+    pub fn update(&mut self, renderer: &GpuRenderer, clock: &AnimationClock) {
+        self.update_camera_transition(clock.real_delta_seconds());
+
+        if self.camera.update(clock.real_delta_seconds()) {
             let proj = self.camera.projection();
             let view = self.camera.view();
             let mat_proj: Mat4 = proj.clone().into();
@@ -192,30 +337,191 @@ where
             let eye: mint::Vector3<f32> = self.camera.eye().into();
             let scale = self.camera.scale();
 
-            let camera_info = CameraUniform {
-                view,
-                proj,
-                inverse_proj: inverse_proj.into(),
-                eye,
-                scale,
-            };
+            let queue = renderer.queue();
 
-            renderer.queue().write_buffer(
+            GlobalUniform::write_view(queue, &self.global_buffer, &view);
+            GlobalUniform::write_proj(queue, &self.global_buffer, &proj);
+            GlobalUniform::write_inverse_proj(
+                queue,
                 &self.global_buffer,
-                0,
-                camera_info.as_std140().as_bytes(),
+                &inverse_proj.into(),
             );
+            GlobalUniform::write_eye(queue, &self.global_buffer, &eye);
+            GlobalUniform::write_scale(queue, &self.global_buffer, &scale);
         }
 
-        let time_info = TimeUniform {
-            seconds: frame_time.seconds(),
-        };
-
-        renderer.queue().write_buffer(
+        GlobalUniform::write_seconds(
+            renderer.queue(),
             &self.global_buffer,
-            216,
-            time_info.as_std140().as_bytes(),
+            &clock.seconds(),
         );
+
+        if let Some(day_length) = self.day_length {
+            self.set_time_of_day(
+                self.time_of_day + clock.delta_seconds() / day_length,
+            );
+        }
+
+        if self.globals_changed {
+            let queue = renderer.queue();
+
+            GlobalUniform::write_time_of_day(
+                queue,
+                &self.global_buffer,
+                &self.time_of_day,
+            );
+            GlobalUniform::write_wind(
+                queue,
+                &self.global_buffer,
+                &self.wind.into(),
+            );
+            GlobalUniform::write_seed(queue, &self.global_buffer, &self.seed);
+            GlobalUniform::write_ambient(
+                queue,
+                &self.global_buffer,
+                &self.ambient.into(),
+            );
+            GlobalUniform::write_pixel_snap(
+                queue,
+                &self.global_buffer,
+                &u32::from(self.pixel_snap),
+            );
+            self.globals_changed = false;
+        }
+    }
+
+    /// Moves the camera to goal over duration seconds of real time, from
+    /// wherever it is now. Starting another one takes over from the first.
+    /// Only controls with a focus, like the flat and orbit ones, can move.
+    pub fn transition_to(
+        &mut self,
+        goal: impl Into<CameraGoal>,
+        duration: f32,
+        easing: Easing,
+    ) {
+        self.camera_transition = Some(CameraTransition {
+            from_focus: self.camera_focus(),
+            from_projection: self.camera.projection_settings(),
+            goal: goal.into(),
+            easing,
+            duration: duration.max(0.0),
+            elapsed: 0.0,
+        });
+        self.camera_transition_finished = false;
+    }
+
+    /// Stops the camera where the transition has it now.
+    pub fn cancel_camera_transition(&mut self) {
+        self.camera_transition = None;
+    }
+
+    pub fn is_camera_transitioning(&self) -> bool {
+        self.camera_transition.is_some()
+    }
+
+    /// Returns true once after a camera transition finishes.
+    pub fn take_camera_transition_finished(&mut self) -> bool {
+        std::mem::take(&mut self.camera_transition_finished)
+    }
+
+    pub fn camera_focus(&self) -> Option<CameraFocus> {
+        self.camera.controls().focus(self.screen_size)
+    }
+
+    fn update_camera_transition(&mut self, delta: f32) {
+        let Some(transition) = &mut self.camera_transition else {
+            return;
+        };
+
+        let t = transition.advance(delta);
+        let goal = transition.goal;
+
+        if let (Some(from), Some(to)) = (transition.from_focus, goal.focus) {
+            let focus = if t < 1.0 { from.lerp(to, t) } else { to };
+
+            self.camera
+                .controls_mut()
+                .set_focus(focus, self.screen_size);
+        }
+
+        if let Some(to) = goal.projection {
+            let from = transition.from_projection;
+
+            self.camera.set_projection(if t < 1.0 {
+                from.lerp(to, t)
+            } else {
+                to
+            });
+        }
+
+        if t >= 1.0 {
+            self.camera_transition = None;
+            self.camera_transition_finished = true;
+        }
+    }
+
+    pub fn pixel_snap(&self) -> bool {
+        self.pixel_snap
+    }
+
+    /// Rounds the camera and the positions of images, rects, text and map
+    /// tiles to whole screen pixels, so pixel art doesn't shimmer while the
+    /// camera moves. Works best with whole number zooms.
+    pub fn set_pixel_snap(&mut self, pixel_snap: bool) {
+        self.pixel_snap = pixel_snap;
+        self.globals_changed = true;
+    }
+
+    pub fn time_of_day(&self) -> f32 {
+        self.time_of_day
+    }
+
+    /// Wraps into 0.0..1.0, where 0.0 is midnight and 0.5 is noon.
+    pub fn set_time_of_day(&mut self, time_of_day: f32) {
+        self.time_of_day = time_of_day.rem_euclid(1.0);
+        self.globals_changed = true;
+    }
+
+    pub fn day_length(&self) -> Option<f32> {
+        self.day_length
+    }
+
+    /// Advances the time of day by the clock, so a day takes day_length
+    /// seconds. None stops it.
+    pub fn set_day_length(&mut self, day_length: Option<f32>) {
+        self.day_length = day_length.filter(|length| *length > 0.0);
+    }
+
+    pub fn wind(&self) -> Vec2 {
+        self.wind
+    }
+
+    /// Direction and strength of the wind for shaders that sway things.
+    pub fn set_wind(&mut self, wind: Vec2) {
+        self.wind = wind;
+        self.globals_changed = true;
+    }
+
+    pub fn ambient(&self) -> Vec4 {
+        self.ambient
+    }
+
+    /// Ambient color for custom shaders. The lights keep using their own
+    /// world color.
+    pub fn set_ambient(&mut self, ambient: Vec4) {
+        self.ambient = ambient;
+        self.globals_changed = true;
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Seed for shader noise, like one drawn from the app's
+    /// [`crate::RngService`].
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+        self.globals_changed = true;
     }
 
     pub fn update_screen(
@@ -225,17 +531,19 @@ where
     ) {
         if self.screen_size != screen_size {
             self.screen_size = screen_size;
-            let screen_info = ScreenUniform {
-                size: screen_size.into(),
-            };
+
+            // The visible part of the world changed with the screen.
+            if self.world_bounds.is_some() {
+                self.send_world_bounds();
+            }
 
             #[cfg(feature = "iced")]
             self.set_iced_view_size(screen_size);
 
-            renderer.queue().write_buffer(
+            GlobalUniform::write_size(
+                renderer.queue(),
                 &self.global_buffer,
-                208,
-                screen_info.as_std140().as_bytes(),
+                &screen_size.into(),
             );
         }
     }
@@ -294,6 +602,59 @@ where
         Vec4::new(xy.x, xy.y - objh, bw, bh)
     }
 
+    /// Projects a world position through the camera into screen space in
+    /// the coordinate system. With the default system the result matches
+    /// objects rendered without the camera, so it can be used to attach
+    /// UI to world objects.
+    pub fn world_to_screen_pos(&self, pos: Vec3) -> Vec2 {
+        let projection = Mat4::from(self.camera.projection());
+        let view = Mat4::from(self.camera.view());
+        let clip_coords = projection * view * pos.extend(1.0);
+        let coords = Vec3::from_slice(&clip_coords.to_array()) / clip_coords.w;
+
+        self.from_render_pos(Vec2::new(
+            (coords.x + 1.0) * 0.5 * self.screen_size[0],
+            (coords.y + 1.0) * 0.5 * self.screen_size[1],
+        ))
+    }
+
+    /// The area of the world the camera shows, for flat projections.
+    pub fn visible_world_bounds(&self) -> Bounds {
+        let corners =
+            [Vec2::ZERO, Vec2::from(self.screen_size)].map(|corner| {
+                self.screen_to_world_pos(self.from_render_pos(corner))
+            });
+        let (min, max) =
+            (corners[0].min(corners[1]), corners[0].max(corners[1]));
+
+        Bounds::new(min.x, min.y, max.x, max.y)
+    }
+
+    /// Reverse of [`System::world_to_screen_pos`]. Z is dropped as the
+    /// projection is flat.
+    pub fn screen_to_world_pos(&self, pos: Vec2) -> Vec2 {
+        self.screen_to_layer_pos(pos, 1.0)
+    }
+
+    /// Same as [`System::screen_to_world_pos`] for a layer whose camera
+    /// translation is scaled by parallax.
+    pub fn screen_to_layer_pos(&self, pos: Vec2, parallax: f32) -> Vec2 {
+        let projection = Mat4::from(self.camera.projection());
+        let mut view = Mat4::from(self.camera.view());
+
+        view.w_axis = (view.w_axis.truncate() * parallax).extend(view.w_axis.w);
+        let pos = self.to_render_pos(pos);
+
+        let ndc = Vec2::new(
+            pos.x / self.screen_size[0] * 2.0 - 1.0,
+            pos.y / self.screen_size[1] * 2.0 - 1.0,
+        );
+        let world =
+            (projection * view).inverse() * Vec4::new(ndc.x, ndc.y, 0.0, 1.0);
+
+        Vec2::new(world.x, world.y) / world.w
+    }
+
     pub fn world_to_screen(&self, scale: bool, bounds: &WorldBounds) -> Vec4 {
         let projection = Mat4::from(self.camera.projection());
         let model = Mat4::IDENTITY;
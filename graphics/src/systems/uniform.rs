@@ -0,0 +1,198 @@
+use crate::GpuDevice;
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use std::num::NonZeroU64;
+use wgpu::util::DeviceExt;
+
+/// Rounds offset up to a multiple of align.
+#[doc(hidden)]
+pub const fn std140_align(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+/// A type that can be a field of an [`EngineUniform`].
+pub trait UniformField {
+    /// std140 alignment in bytes.
+    const ALIGN: usize;
+    /// Bytes written, without any padding after it.
+    const SIZE: usize;
+
+    /// out is exactly SIZE bytes.
+    fn write_std140(&self, out: &mut [u8]);
+}
+
+macro_rules! uniform_scalar {
+    ($($ty:ty),*) => {
+        $(
+            impl UniformField for $ty {
+                const ALIGN: usize = 4;
+                const SIZE: usize = 4;
+
+                fn write_std140(&self, out: &mut [u8]) {
+                    out.copy_from_slice(&self.to_ne_bytes());
+                }
+            }
+        )*
+    };
+}
+
+uniform_scalar!(f32, u32, i32);
+
+/// vec3 is aligned like a vec4 but takes only 12 bytes.
+macro_rules! uniform_vector {
+    ($($n:literal => $align:literal),*) => {
+        $(
+            impl<T: UniformField + Copy> UniformField for [T; $n] {
+                const ALIGN: usize = $align;
+                const SIZE: usize = 4 * $n;
+
+                fn write_std140(&self, out: &mut [u8]) {
+                    for (value, out) in self.iter().zip(out.chunks_exact_mut(4)) {
+                        value.write_std140(out);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+uniform_vector!(2 => 8, 3 => 16, 4 => 16);
+
+impl UniformField for Vec2 {
+    const ALIGN: usize = 8;
+    const SIZE: usize = 8;
+
+    fn write_std140(&self, out: &mut [u8]) {
+        self.to_array().write_std140(out);
+    }
+}
+
+impl UniformField for Vec3 {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 12;
+
+    fn write_std140(&self, out: &mut [u8]) {
+        self.to_array().write_std140(out);
+    }
+}
+
+impl UniformField for Vec4 {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 16;
+
+    fn write_std140(&self, out: &mut [u8]) {
+        self.to_array().write_std140(out);
+    }
+}
+
+impl UniformField for Mat4 {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 64;
+
+    fn write_std140(&self, out: &mut [u8]) {
+        self.to_cols_array().write_std140(out);
+    }
+}
+
+impl UniformField for [f32; 16] {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 64;
+
+    fn write_std140(&self, out: &mut [u8]) {
+        for (value, out) in self.iter().zip(out.chunks_exact_mut(4)) {
+            value.write_std140(out);
+        }
+    }
+}
+
+impl UniformField for mint::Vector2<f32> {
+    const ALIGN: usize = 8;
+    const SIZE: usize = 8;
+
+    fn write_std140(&self, out: &mut [u8]) {
+        Vec2::from(*self).write_std140(out);
+    }
+}
+
+impl UniformField for mint::Vector3<f32> {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 12;
+
+    fn write_std140(&self, out: &mut [u8]) {
+        Vec3::from(*self).write_std140(out);
+    }
+}
+
+impl UniformField for mint::Vector4<f32> {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 16;
+
+    fn write_std140(&self, out: &mut [u8]) {
+        Vec4::from(*self).write_std140(out);
+    }
+}
+
+impl UniformField for mint::ColumnMatrix4<f32> {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 64;
+
+    fn write_std140(&self, out: &mut [u8]) {
+        Mat4::from(*self).write_std140(out);
+    }
+}
+
+/// A uniform buffer's contents. Derive it with `#[derive(EngineUniform)]`
+/// instead of packing bytes and offsets by hand.
+pub trait EngineUniform {
+    /// std140 size, padded to 16 bytes.
+    const SIZE: usize;
+    const VISIBILITY: wgpu::ShaderStages;
+    const LABEL: &'static str;
+
+    /// out is exactly SIZE bytes.
+    fn write_std140(&self, out: &mut [u8]);
+
+    fn to_std140(&self) -> Vec<u8> {
+        let mut bytes = vec![0; Self::SIZE];
+
+        self.write_std140(&mut bytes);
+        bytes
+    }
+
+    fn layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: Self::VISIBILITY,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: NonZeroU64::new(Self::SIZE as u64),
+            },
+            count: None,
+        }
+    }
+
+    /// A bind group layout with only this uniform at binding 0.
+    fn create_layout(gpu_device: &GpuDevice) -> wgpu::BindGroupLayout {
+        gpu_device.device().create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: Some(Self::LABEL),
+                entries: &[Self::layout_entry(0)],
+            },
+        )
+    }
+
+    fn create_buffer(&self, gpu_device: &GpuDevice) -> wgpu::Buffer {
+        gpu_device.device().create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some(Self::LABEL),
+                contents: &self.to_std140(),
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+            },
+        )
+    }
+
+    fn write_buffer(&self, queue: &wgpu::Queue, buffer: &wgpu::Buffer) {
+        queue.write_buffer(buffer, 0, &self.to_std140());
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     AsBufferPass, Buffer, BufferData, BufferLayout, BufferPass, GpuDevice,
-    GpuRenderer, OrderedIndex,
+    GpuRenderer, OrderedIndex, SortPolicies, SortPolicy,
 };
 use std::ops::Range;
 
@@ -19,6 +19,7 @@ pub struct GpuBuffer<K: BufferLayout> {
     vertex_needed: usize,
     pub index_buffer: Buffer<K>,
     index_needed: usize,
+    sort_policies: SortPolicies,
 }
 
 impl<'a, K: BufferLayout> AsBufferPass<'a> for GpuBuffer<K> {
@@ -50,6 +51,7 @@ impl<K: BufferLayout> GpuBuffer<K> {
                 Some("Index Buffer"),
             ),
             index_needed: 0,
+            sort_policies: SortPolicies::default(),
         }
     }
 
@@ -68,6 +70,15 @@ impl<K: BufferLayout> GpuBuffer<K> {
         }
     }
 
+    /// How every layer without its own policy is ordered.
+    pub fn set_sort_policy(&mut self, policy: SortPolicy) {
+        self.sort_policies.default = policy;
+    }
+
+    pub fn set_layer_sort_policy(&mut self, layer: u32, policy: SortPolicy) {
+        self.sort_policies.set_layer(layer, policy);
+    }
+
     pub fn finalize(&mut self, renderer: &mut GpuRenderer) {
         let (mut changed, mut vertex_pos, mut index_pos) = (false, 0, 0);
 
@@ -85,7 +96,7 @@ impl<K: BufferLayout> GpuBuffer<K> {
         self.vertex_buffer.count = self.vertex_needed / K::stride();
         self.vertex_buffer.len = self.vertex_needed;
 
-        self.unprocessed.sort();
+        self.sort_policies.sort(&mut self.unprocessed);
         self.buffers.clear();
 
         for buf in &self.unprocessed {
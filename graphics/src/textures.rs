@@ -1,7 +1,9 @@
+mod readback;
 mod texture;
 mod texturegroup;
-mod texturelayout;
+pub(crate) mod texturelayout;
 
+pub use readback::{read_back, read_back_with, TextureRegion};
 pub use texture::Texture;
 pub use texturegroup::TextureGroup;
-pub use texturelayout::TextureLayout;
+pub(crate) use texturelayout::TextureLayout;
@@ -0,0 +1,252 @@
+use crate::{AscendingError, Atlas, GpuRenderer, Texture};
+use futures::channel::oneshot;
+use std::{future::Future, hash::Hash, sync::Arc};
+
+/// Part of a texture to read back, in texels from its top left corner.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextureRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Array layer, for texture arrays like the atlases.
+    pub layer: u32,
+}
+
+impl TextureRegion {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            layer: 0,
+        }
+    }
+
+    /// All of a texture's first layer.
+    pub fn whole(texture: &wgpu::Texture) -> Self {
+        Self::new(0, 0, texture.width(), texture.height())
+    }
+
+    pub fn with_layer(mut self, layer: u32) -> Self {
+        self.layer = layer;
+        self
+    }
+}
+
+/// Reads a region of a texture back from the GPU. The bytes are tightly
+/// packed rows in the texture's own format, top row first.
+///
+/// The copy is submitted right away and the future waits on the device
+/// once polled, so it suits tools and one off reads like color picking.
+/// Use [`read_back_with`] to not wait within a frame.
+pub fn read_back<'a>(
+    renderer: &'a GpuRenderer,
+    texture: &wgpu::Texture,
+    region: TextureRegion,
+) -> impl Future<Output = Result<Vec<u8>, AscendingError>> + 'a {
+    let (sender, receiver) = oneshot::channel();
+    let started = read_back_with(renderer, texture, region, move |result| {
+        let _ = sender.send(result);
+    });
+
+    async move {
+        started?;
+        renderer.device().poll(wgpu::Maintain::Wait);
+        receiver
+            .await
+            .map_err(|_| AscendingError::ReadbackCanceled)?
+    }
+}
+
+/// Reads a region of a texture back from the GPU without waiting, calling
+/// callback with the bytes once the copy is done. Callbacks run when the
+/// device is polled, so poll it each frame with
+/// `renderer.device().poll(wgpu::Maintain::Poll)` while reads are pending.
+///
+/// Errors right away if the region can't be read.
+pub fn read_back_with<F>(
+    renderer: &GpuRenderer,
+    texture: &wgpu::Texture,
+    region: TextureRegion,
+    callback: F,
+) -> Result<(), AscendingError>
+where
+    F: FnOnce(Result<Vec<u8>, AscendingError>) + Send + 'static,
+{
+    let row = row_size(texture, region)?;
+    // Buffer copies need rows aligned, which is undone once mapped.
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_row = row.div_ceil(align) * align;
+    let buffer =
+        Arc::new(renderer.device().create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: padded_row as u64 * region.height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        }));
+    let mut encoder = renderer.device().create_command_encoder(
+        &wgpu::CommandEncoderDescriptor {
+            label: Some("Readback Encoder"),
+        },
+    );
+
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: region.x,
+                y: region.y,
+                z: region.layer,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row),
+                rows_per_image: Some(region.height),
+            },
+        },
+        wgpu::Extent3d {
+            width: region.width,
+            height: region.height,
+            depth_or_array_layers: 1,
+        },
+    );
+    renderer.queue().submit(Some(encoder.finish()));
+
+    let mapped = buffer.clone();
+
+    buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, move |result| {
+            let result = result.map_err(AscendingError::from).map(|()| {
+                let data = mapped.slice(..).get_mapped_range();
+                let bytes = data
+                    .chunks(padded_row as usize)
+                    .flat_map(|padded| &padded[..row as usize])
+                    .copied()
+                    .collect();
+
+                drop(data);
+                mapped.unmap();
+                bytes
+            });
+
+            callback(result);
+        });
+
+    Ok(())
+}
+
+/// Bytes in a row of the region, after checking it can be read.
+fn row_size(
+    texture: &wgpu::Texture,
+    region: TextureRegion,
+) -> Result<u32, AscendingError> {
+    let format = texture.format();
+
+    if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+        return Err(AscendingError::InvalidReadback(
+            "texture was not created with COPY_SRC usage",
+        ));
+    }
+
+    if region.width == 0 || region.height == 0 {
+        return Err(AscendingError::InvalidReadback("region is empty"));
+    }
+
+    if region.x.saturating_add(region.width) > texture.width()
+        || region.y.saturating_add(region.height) > texture.height()
+        || region.layer >= texture.depth_or_array_layers()
+    {
+        return Err(AscendingError::InvalidReadback(
+            "region is outside the texture",
+        ));
+    }
+
+    match format.block_size(None) {
+        Some(size) if format.block_dimensions() == (1, 1) => {
+            Ok(size * region.width)
+        }
+        _ => Err(AscendingError::InvalidReadback(
+            "compressed and depth stencil formats can't be read back",
+        )),
+    }
+}
+
+impl Texture {
+    /// Reads a region of a GPU texture into a new texture, like for
+    /// thumbnails. Only RGBA8 and BGRA8 textures can be read this way.
+    pub fn read_back<'a>(
+        name: String,
+        renderer: &'a GpuRenderer,
+        texture: &wgpu::Texture,
+        region: TextureRegion,
+    ) -> impl Future<Output = Result<Texture, AscendingError>> + 'a {
+        let format = texture.format();
+        let supported = matches!(
+            format,
+            wgpu::TextureFormat::Rgba8Unorm
+                | wgpu::TextureFormat::Rgba8UnormSrgb
+                | wgpu::TextureFormat::Bgra8Unorm
+                | wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+        let bytes = supported.then(|| read_back(renderer, texture, region));
+
+        async move {
+            let Some(bytes) = bytes else {
+                return Err(AscendingError::InvalidReadback(
+                    "texture is not RGBA8 or BGRA8",
+                ));
+            };
+            let mut bytes = bytes.await?;
+
+            if matches!(
+                format,
+                wgpu::TextureFormat::Bgra8Unorm
+                    | wgpu::TextureFormat::Bgra8UnormSrgb
+            ) {
+                bytes.chunks_exact_mut(4).for_each(|pixel| pixel.swap(0, 2));
+            }
+
+            Ok(Texture::from_rgba(
+                name,
+                bytes,
+                (region.width, region.height),
+            ))
+        }
+    }
+}
+
+impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
+    /// Reads a region of one of the atlas layers back from the GPU.
+    pub fn read_back<'a>(
+        &self,
+        renderer: &'a GpuRenderer,
+        region: TextureRegion,
+    ) -> impl Future<Output = Result<Vec<u8>, AscendingError>> + 'a {
+        read_back(renderer, &self.texture, region)
+    }
+}
+
+impl GpuRenderer {
+    /// Reads a region of the current frame, like the pixel under the
+    /// cursor. The region is from the top left, so flip y of positions in
+    /// render space. Needs a frame that is copyable.
+    pub fn read_back_frame(
+        &self,
+        region: TextureRegion,
+    ) -> impl Future<Output = Result<Vec<u8>, AscendingError>> + '_ {
+        let started = self
+            .frame_texture()
+            .map(|texture| read_back(self, texture, region))
+            .ok_or(AscendingError::InvalidReadback("there is no frame"));
+
+        async move { started?.await }
+    }
+}
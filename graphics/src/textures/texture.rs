@@ -42,6 +42,11 @@ impl Texture {
         Self { name, bytes, size }
     }
 
+    /// bytes are RGBA8, width * height * 4 of them.
+    pub fn from_rgba(name: String, bytes: Vec<u8>, size: (u32, u32)) -> Self {
+        Self { name, bytes, size }
+    }
+
     pub fn from_memory(
         name: String,
         data: &[u8],
@@ -1,4 +1,4 @@
-use crate::{Allocation, AtlasGroup, GpuRenderer, Texture};
+use crate::{Allocation, AtlasGroup, Color, GpuRenderer, Texture};
 use image::{self, EncodableLayout, ImageBuffer, RgbaImage};
 
 //used to map the tile in the tilesheet back visually
@@ -9,6 +9,38 @@ pub struct Tile {
     pub y: u32,
     pub id: u32,
     pub allocation: Allocation,
+    /// Average of the tile's pixels, weighted by their alpha.
+    pub average: Color,
+}
+
+/// Average color of the pixels weighted by their alpha, with their mean
+/// alpha.
+fn average_color(image: &RgbaImage) -> Color {
+    let mut sum = [0u64; 3];
+    let mut alpha = 0u64;
+
+    for pixel in image.pixels() {
+        let a = pixel.0[3] as u64;
+
+        for (channel, value) in sum.iter_mut().zip(pixel.0) {
+            *channel += value as u64 * a;
+        }
+
+        alpha += a;
+    }
+
+    let count = (image.width() as u64 * image.height() as u64).max(1);
+
+    if alpha == 0 {
+        return Color::rgba(0, 0, 0, 0);
+    }
+
+    Color::rgba(
+        (sum[0] / alpha) as u8,
+        (sum[1] / alpha) as u8,
+        (sum[2] / alpha) as u8,
+        (alpha / count) as u8,
+    )
 }
 
 #[derive(Debug, Default)]
@@ -90,6 +122,7 @@ impl TileSheet {
                     y: tiley,
                     id: 0,
                     allocation: empty.clone(),
+                    average: Color::rgba(0, 0, 0, 0),
                 })
             } else {
                 let (posx, posy) = allocation.position();
@@ -98,16 +131,14 @@ impl TileSheet {
                     y: tiley,
                     id: (posx / tilesize) + ((posy / tilesize) * atlas_width),
                     allocation,
+                    average: average_color(&image),
                 })
             }
         }
 
         // We return as Some(tilesheet) this allows us to check above upon
         // upload if a tile failed to get added or not due to no more room.
-        Some(TileSheet {
-            tiles: Vec::with_capacity(tilecount as usize),
-            texture,
-        })
+        Some(TileSheet { tiles, texture })
     }
 
     pub fn upload(
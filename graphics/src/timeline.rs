@@ -0,0 +1,283 @@
+use crate::{Easing, System, Transition, TransitionKind};
+use camera::controls::CameraFocus;
+use serde::{Deserialize, Serialize};
+
+/// What a [`Timeline`] does at a keyframe. Sprites, texts and sounds are
+/// named by the app, which looks them up when the cue fires.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TimelineAction {
+    /// Moves the camera over duration seconds.
+    Camera {
+        center: [f32; 3],
+        zoom: f32,
+        duration: f32,
+        #[serde(default)]
+        easing: Easing,
+    },
+    /// Switches a sprite's animation clip, crossfading over fade seconds.
+    Animation {
+        target: String,
+        clip: String,
+        #[serde(default)]
+        fade: f32,
+    },
+    /// Types text out on a target over duration seconds.
+    Text {
+        target: String,
+        text: String,
+        duration: f32,
+    },
+    Sound {
+        name: String,
+        volume: f32,
+    },
+    /// Covers the screen, or reveals it when cover is false.
+    Transition {
+        kind: TransitionKind,
+        cover: bool,
+        duration: f32,
+    },
+    /// Anything else, for the app to read.
+    Custom(String),
+}
+
+impl TimelineAction {
+    /// Seconds the action runs for after it starts.
+    pub fn duration(&self) -> f32 {
+        match self {
+            TimelineAction::Camera { duration, .. }
+            | TimelineAction::Text { duration, .. }
+            | TimelineAction::Transition { duration, .. } => *duration,
+            TimelineAction::Animation { fade, .. } => *fade,
+            TimelineAction::Sound { .. } | TimelineAction::Custom(_) => 0.0,
+        }
+    }
+
+    /// Runs camera moves and screen transitions. Returns false for the
+    /// actions the app has to run itself.
+    pub fn apply<Controls>(
+        &self,
+        system: &mut System<Controls>,
+        transition: &mut Transition,
+    ) -> bool
+    where
+        Controls: camera::controls::Controls,
+    {
+        match self {
+            TimelineAction::Camera {
+                center,
+                zoom,
+                duration,
+                easing,
+            } => {
+                let focus = CameraFocus::new(*center, *zoom);
+
+                system.transition_to(focus, *duration, *easing);
+                true
+            }
+            TimelineAction::Transition {
+                kind,
+                cover: true,
+                duration,
+            } => {
+                transition.start_cover(*kind, *duration);
+                true
+            }
+            TimelineAction::Transition { kind, duration, .. } => {
+                transition.start_reveal(*kind, *duration);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    /// Seconds from the start of the timeline.
+    pub time: f32,
+    pub action: TimelineAction,
+}
+
+/// A row of keyframes, like one per actor or one for the camera.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimelineTrack {
+    pub name: String,
+    pub keyframes: Vec<Keyframe>,
+    /// Skips the track's cues.
+    #[serde(default)]
+    pub muted: bool,
+}
+
+/// A scripted scene as data, loadable with any serde format.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimelineData {
+    pub tracks: Vec<TimelineTrack>,
+    /// Seconds it lasts. None ends once the last action finishes.
+    #[serde(default)]
+    pub duration: Option<f32>,
+    #[serde(default)]
+    pub looping: bool,
+}
+
+/// What a [`Timeline`] reports as it plays.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimelineEvent {
+    /// A keyframe was reached.
+    Cue {
+        track: usize,
+        action: TimelineAction,
+    },
+    /// Went back to the start of a looping timeline.
+    Looped,
+    Finished,
+}
+
+/// Plays [`TimelineData`], firing each keyframe's action as a cue once
+/// its time is reached. Cutscenes go through it by taking the events each
+/// frame, letting [`TimelineAction::apply`] run the camera and screen
+/// transitions and running the rest in the app.
+pub struct Timeline {
+    data: TimelineData,
+    /// 1.0 is normal speed.
+    pub speed: f32,
+    time: f32,
+    playing: bool,
+    /// Next keyframe to fire on each track.
+    cursors: Vec<usize>,
+    events: Vec<TimelineEvent>,
+}
+
+impl Timeline {
+    /// Keyframes are sorted by time.
+    pub fn new(mut data: TimelineData) -> Self {
+        for track in &mut data.tracks {
+            track.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        }
+
+        Self {
+            cursors: vec![0; data.tracks.len()],
+            data,
+            speed: 1.0,
+            time: 0.0,
+            playing: false,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn data(&self) -> &TimelineData {
+        &self.data
+    }
+
+    /// Seconds it lasts, see [`TimelineData::duration`].
+    pub fn duration(&self) -> f32 {
+        self.data.duration.unwrap_or_else(|| {
+            self.data
+                .tracks
+                .iter()
+                .flat_map(|track| &track.keyframes)
+                .map(|key| key.time + key.action.duration())
+                .fold(0.0, f32::max)
+        })
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// If it stopped at the end.
+    pub fn is_finished(&self) -> bool {
+        !self.playing && self.time >= self.duration()
+    }
+
+    /// Plays from where it is, or from the start once finished.
+    pub fn play(&mut self) {
+        if self.is_finished() {
+            self.seek(0.0);
+        }
+
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Pauses and goes back to the start.
+    pub fn stop(&mut self) {
+        self.playing = false;
+        self.seek(0.0);
+    }
+
+    /// Jumps to time. Keyframes between the old and new time don't fire,
+    /// ones at time fire on the next update.
+    pub fn seek(&mut self, time: f32) {
+        self.time = time.clamp(0.0, self.duration());
+
+        for (track, cursor) in self.data.tracks.iter().zip(&mut self.cursors) {
+            *cursor =
+                track.keyframes.partition_point(|key| key.time < self.time);
+        }
+    }
+
+    /// Moves along by delta seconds times the speed, firing the keyframes
+    /// reached.
+    pub fn update(&mut self, delta: f32) {
+        if !self.playing {
+            return;
+        }
+
+        let duration = self.duration();
+
+        self.time += delta * self.speed;
+        self.fire(self.time.min(duration));
+
+        if self.time < duration {
+            return;
+        }
+
+        if self.data.looping && duration > 0.0 {
+            let time = self.time % duration;
+
+            self.seek(0.0);
+            self.events.push(TimelineEvent::Looped);
+            self.fire(time);
+            self.time = time;
+        } else {
+            self.time = duration;
+            self.playing = false;
+            self.events.push(TimelineEvent::Finished);
+        }
+    }
+
+    /// Fires every keyframe up to time.
+    fn fire(&mut self, time: f32) {
+        for (index, track) in self.data.tracks.iter().enumerate() {
+            let cursor = &mut self.cursors[index];
+
+            while let Some(key) = track.keyframes.get(*cursor) {
+                if key.time > time {
+                    break;
+                }
+
+                if !track.muted {
+                    self.events.push(TimelineEvent::Cue {
+                        track: index,
+                        action: key.action.clone(),
+                    });
+                }
+
+                *cursor += 1;
+            }
+        }
+    }
+
+    /// Takes what happened since the last call.
+    pub fn take_events(&mut self) -> Vec<TimelineEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
@@ -0,0 +1,9 @@
+pub(crate) mod pipeline;
+mod render;
+mod transition;
+pub(crate) mod uniforms;
+
+pub(crate) use pipeline::*;
+pub use render::*;
+pub use transition::*;
+pub(crate) use uniforms::*;
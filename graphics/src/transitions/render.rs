@@ -0,0 +1,246 @@
+use crate::{
+    AscendingError, GpuRenderer, Pass, Transition, TransitionKind,
+    TransitionLayout, TransitionRaw, TransitionRenderPipeline, WipeDirection,
+};
+use wgpu::util::DeviceExt;
+
+/// Draws a [`Transition`] over the finished frame in its own pass.
+/// Pixelate samples a copy of the frame so this must render after
+/// everything it should cover.
+pub struct TransitionRenderer {
+    buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    frame_texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    /// Size of the frame copy texture.
+    size: [u32; 2],
+    /// if the transition needs to be drawn.
+    active: bool,
+    /// if the frame needs to be copied before drawing.
+    copy_frame: bool,
+}
+
+impl TransitionRenderer {
+    pub fn new(renderer: &mut GpuRenderer) -> Result<Self, AscendingError> {
+        let buffer = renderer.device().create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Transition buffer"),
+                contents: bytemuck::bytes_of(&TransitionRaw::default()),
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+
+        let sampler =
+            renderer.device().create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("transition_sampler"),
+                lod_max_clamp: 0.0,
+                ..Default::default()
+            });
+
+        let size = [
+            (renderer.size().width as u32).max(1),
+            (renderer.size().height as u32).max(1),
+        ];
+        let frame_texture = Self::create_frame_texture(renderer, size);
+        let bind_group = Self::create_bind_group(
+            renderer,
+            &buffer,
+            &sampler,
+            &frame_texture,
+        );
+
+        Ok(Self {
+            buffer,
+            sampler,
+            frame_texture,
+            bind_group,
+            size,
+            active: false,
+            copy_frame: false,
+        })
+    }
+
+    fn create_frame_texture(
+        renderer: &GpuRenderer,
+        size: [u32; 2],
+    ) -> wgpu::Texture {
+        renderer.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some("transition frame texture"),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: renderer.surface_format(),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    fn create_bind_group(
+        renderer: &mut GpuRenderer,
+        buffer: &wgpu::Buffer,
+        sampler: &wgpu::Sampler,
+        frame_texture: &wgpu::Texture,
+    ) -> wgpu::BindGroup {
+        let layout = renderer.create_layout(TransitionLayout);
+        let view =
+            frame_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        renderer
+            .device()
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                ],
+                label: Some("transition_bind_group"),
+            })
+    }
+
+    /// Uploads the transition's current state. Call once per frame after
+    /// [`Transition::update`].
+    pub fn transition_update(
+        &mut self,
+        transition: &mut Transition,
+        renderer: &mut GpuRenderer,
+    ) {
+        let size = [
+            (renderer.size().width as u32).max(1),
+            (renderer.size().height as u32).max(1),
+        ];
+
+        if size != self.size {
+            self.size = size;
+            self.frame_texture = Self::create_frame_texture(renderer, size);
+            self.bind_group = Self::create_bind_group(
+                renderer,
+                &self.buffer,
+                &self.sampler,
+                &self.frame_texture,
+            );
+            transition.changed = true;
+        }
+
+        self.active = transition.is_active();
+
+        // Without a copy of the frame pixelate falls back to a fade.
+        let kind = match transition.kind {
+            TransitionKind::Pixelate if !renderer.frame_copyable() => {
+                TransitionKind::Fade
+            }
+            kind => kind,
+        };
+
+        self.copy_frame = kind == TransitionKind::Pixelate;
+
+        if transition.changed {
+            let center = transition.center.unwrap_or_else(|| {
+                [size[0] as f32 * 0.5, size[1] as f32 * 0.5].into()
+            });
+            let (kind, direction) = match kind {
+                TransitionKind::Fade => (0, 0),
+                TransitionKind::Wipe(direction) => (
+                    1,
+                    match direction {
+                        WipeDirection::Left => 0,
+                        WipeDirection::Right => 1,
+                        WipeDirection::Top => 2,
+                        WipeDirection::Bottom => 3,
+                    },
+                ),
+                TransitionKind::Iris => (2, 0),
+                TransitionKind::Pixelate => (3, 0),
+            };
+
+            let raw = TransitionRaw {
+                center: center.to_array(),
+                size: [size[0] as f32, size[1] as f32],
+                color: transition.color.0,
+                coverage: transition.coverage(),
+                kind,
+                direction,
+                max_pixel_size: transition.max_pixel_size.max(1.0),
+                softness: transition.softness.max(0.0),
+                padding: [0.0; 2],
+            };
+
+            renderer.queue().write_buffer(
+                &self.buffer,
+                0,
+                bytemuck::bytes_of(&raw),
+            );
+            transition.changed = false;
+        }
+    }
+}
+
+impl Pass for TransitionRenderer {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        if !self.active {
+            return;
+        }
+
+        let view = match renderer.frame_buffer() {
+            Some(view) => view,
+            None => return,
+        };
+
+        if self.copy_frame {
+            if let Some(frame) = renderer.frame_texture() {
+                let frame_size = frame.size();
+
+                encoder.copy_texture_to_texture(
+                    frame.as_image_copy(),
+                    self.frame_texture.as_image_copy(),
+                    wgpu::Extent3d {
+                        width: frame_size.width.min(self.size[0]),
+                        height: frame_size.height.min(self.size[1]),
+                        depth_or_array_layers: 1,
+                    },
+                );
+            }
+        }
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("transition pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        pass.set_pipeline(
+            renderer.get_pipelines(TransitionRenderPipeline).unwrap(),
+        );
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
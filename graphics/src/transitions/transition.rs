@@ -0,0 +1,189 @@
+use crate::{AnimationClock, Color, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// Which side a wipe starts covering the screen from.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum WipeDirection {
+    #[default]
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// How the screen gets covered and uncovered.
+#[derive(Copy, Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub enum TransitionKind {
+    /// Fades the whole screen to the transition color.
+    #[default]
+    Fade,
+    /// Slides the transition color across the screen.
+    Wipe(WipeDirection),
+    /// Closes a circle around the center onto the screen.
+    Iris,
+    /// Blocks the frame up into larger and larger pixels before fading out.
+    Pixelate,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum TransitionState {
+    /// Nothing is drawn.
+    #[default]
+    Idle,
+    /// Going from the scene being visible to being fully covered.
+    Covering,
+    /// Fully covered. Stays this way until a reveal is started.
+    Covered,
+    /// Going from fully covered back to the scene being visible.
+    Revealing,
+}
+
+/// Overlays the whole frame to hide scene changes like map loads.
+/// Call [`Transition::start_cover`], wait on [`Transition::is_covered`],
+/// swap out the scene, then call [`Transition::start_reveal`].
+pub struct Transition {
+    pub kind: TransitionKind,
+    pub color: Color,
+    /// Screen space center of the iris. None uses the screen's center.
+    pub center: Option<Vec2>,
+    /// Largest block size in pixels pixelate gets to.
+    pub max_pixel_size: f32,
+    /// Width in pixels of the soft edge of wipes and the iris.
+    pub softness: f32,
+    state: TransitionState,
+    /// In seconds.
+    duration: f32,
+    elapsed: f32,
+    /// Set when covering or revealing finishes and cleared when taken.
+    finished: bool,
+    /// if anything got updated we need to update the renderer's buffer.
+    pub changed: bool,
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transition {
+    pub fn new() -> Self {
+        Self {
+            kind: TransitionKind::Fade,
+            color: Color::rgba(0, 0, 0, 255),
+            center: None,
+            max_pixel_size: 48.0,
+            softness: 16.0,
+            state: TransitionState::Idle,
+            duration: 0.0,
+            elapsed: 0.0,
+            finished: false,
+            changed: true,
+        }
+    }
+
+    pub fn set_color(&mut self, color: Color) -> &mut Self {
+        self.color = color;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_center(&mut self, center: Option<Vec2>) -> &mut Self {
+        self.center = center;
+        self.changed = true;
+        self
+    }
+
+    /// Starts covering the screen. Duration is in seconds.
+    pub fn start_cover(&mut self, kind: TransitionKind, duration: f32) {
+        self.start(kind, duration, TransitionState::Covering);
+    }
+
+    /// Starts uncovering the screen. Duration is in seconds.
+    pub fn start_reveal(&mut self, kind: TransitionKind, duration: f32) {
+        self.start(kind, duration, TransitionState::Revealing);
+    }
+
+    fn start(
+        &mut self,
+        kind: TransitionKind,
+        duration: f32,
+        state: TransitionState,
+    ) {
+        // Starting partway keeps reversing a running transition smooth.
+        let coverage = self.coverage();
+
+        self.kind = kind;
+        self.duration = duration.max(0.0);
+        self.state = state;
+        self.elapsed = match state {
+            TransitionState::Covering => coverage * self.duration,
+            _ => (1.0 - coverage) * self.duration,
+        };
+        self.finished = false;
+        self.changed = true;
+    }
+
+    /// Advances the transition by the clock's unscaled delta so scene
+    /// changes still finish while the game is paused or slowed down.
+    pub fn update(&mut self, clock: &AnimationClock) {
+        match self.state {
+            TransitionState::Covering | TransitionState::Revealing => {}
+            _ => return,
+        }
+
+        self.elapsed += clock.real_delta_seconds();
+        self.changed = true;
+
+        if self.elapsed >= self.duration {
+            self.elapsed = self.duration;
+            self.finished = true;
+            self.state = match self.state {
+                TransitionState::Covering => TransitionState::Covered,
+                _ => TransitionState::Idle,
+            };
+        }
+    }
+
+    pub fn state(&self) -> TransitionState {
+        self.state
+    }
+
+    /// 0.0 is the scene fully visible and 1.0 is fully covered.
+    pub fn coverage(&self) -> f32 {
+        let progress = if self.duration > 0.0 {
+            (self.elapsed / self.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        match self.state {
+            TransitionState::Idle => 0.0,
+            TransitionState::Covering => progress,
+            TransitionState::Covered => 1.0,
+            TransitionState::Revealing => 1.0 - progress,
+        }
+    }
+
+    /// if the transition needs to be drawn this frame.
+    pub fn is_active(&self) -> bool {
+        self.state != TransitionState::Idle
+    }
+
+    /// if the screen is fully covered and the scene can be swapped.
+    pub fn is_covered(&self) -> bool {
+        self.state == TransitionState::Covered
+    }
+
+    /// if no cover or reveal is running.
+    pub fn is_complete(&self) -> bool {
+        matches!(self.state, TransitionState::Idle | TransitionState::Covered)
+    }
+
+    /// Returns true once after a cover or reveal finishes.
+    pub fn take_finished(&mut self) -> bool {
+        std::mem::take(&mut self.finished)
+    }
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Moments a widget can give audio feedback for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UiSound {
+    /// The mouse moved onto the widget.
+    Hover,
+    Click,
+    /// Started editing or opened a popup.
+    Open,
+    Close,
+    /// A typed value was rejected.
+    Error,
+}
+
+/// A widget's sound, sent through [`crate::MessageRouter::play_sound`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UiSoundCue {
+    pub sound: UiSound,
+    /// Replaces the theme's sound for this widget.
+    pub name: Option<String>,
+}
+
+/// A widget's own sound settings on top of the theme. By default every
+/// sound comes from the theme.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WidgetSounds {
+    /// False keeps the widget quiet.
+    pub enabled: bool,
+    /// Some(name) plays another sound, None silences it.
+    overrides: HashMap<UiSound, Option<String>>,
+}
+
+impl Default for WidgetSounds {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl WidgetSounds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Plays name instead of the theme's sound. None silences it.
+    pub fn set(&mut self, sound: UiSound, name: Option<&str>) -> &mut Self {
+        self.overrides.insert(sound, name.map(str::to_owned));
+        self
+    }
+
+    /// Goes back to the theme's sound.
+    pub fn reset(&mut self, sound: UiSound) -> &mut Self {
+        self.overrides.remove(&sound);
+        self
+    }
+
+    /// The cue to send for sound, None if the widget keeps quiet.
+    pub fn cue(&self, sound: UiSound) -> Option<UiSoundCue> {
+        if !self.enabled {
+            return None;
+        }
+
+        match self.overrides.get(&sound) {
+            Some(None) => None,
+            Some(Some(name)) => Some(UiSoundCue {
+                sound,
+                name: Some(name.clone()),
+            }),
+            None => Some(UiSoundCue { sound, name: None }),
+        }
+    }
+}
+
+/// Which sound asset plays for each [`UiSound`], set once for the whole
+/// UI. Sounds without an entry are silent.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UiSoundTheme {
+    pub sounds: HashMap<UiSound, String>,
+    /// 0.0 to 1.0, for the audio plugin to play UI sounds at.
+    pub volume: f32,
+}
+
+impl Default for UiSoundTheme {
+    fn default() -> Self {
+        Self {
+            sounds: HashMap::new(),
+            volume: 1.0,
+        }
+    }
+}
+
+impl UiSoundTheme {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, sound: UiSound, name: &str) -> &mut Self {
+        self.sounds.insert(sound, name.to_owned());
+        self
+    }
+
+    /// The sound asset a cue plays, the widget's own before the theme's.
+    pub fn resolve<'a>(&'a self, cue: &'a UiSoundCue) -> Option<&'a str> {
+        cue.name
+            .as_deref()
+            .or_else(|| self.sounds.get(&cue.sound).map(String::as_str))
+    }
+}
+
+/// The theme and the cues waiting to be played, owned by the
+/// [`crate::Runner`]. Apps move their routers' cues in once a frame and an
+/// audio plugin plays what [`UiSounds::drain`] resolves.
+#[derive(Clone, Debug, Default)]
+pub struct UiSounds {
+    pub theme: UiSoundTheme,
+    queue: Vec<UiSoundCue>,
+}
+
+impl UiSounds {
+    pub fn new(theme: UiSoundTheme) -> Self {
+        Self {
+            theme,
+            queue: Vec::new(),
+        }
+    }
+
+    pub fn extend(&mut self, cues: impl IntoIterator<Item = UiSoundCue>) {
+        self.queue.extend(cues);
+    }
+
+    /// Takes the queued cues as sound asset names. Cues the theme has no
+    /// sound for are dropped.
+    pub fn drain(&mut self) -> Vec<(UiSound, String)> {
+        let theme = &self.theme;
+
+        self.queue
+            .drain(..)
+            .filter_map(|cue| {
+                theme.resolve(&cue).map(|name| (cue.sound, name.to_owned()))
+            })
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+}
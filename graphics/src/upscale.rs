@@ -0,0 +1,9 @@
+pub(crate) mod pipeline;
+mod render;
+mod scale;
+pub(crate) mod uniforms;
+
+pub(crate) use pipeline::*;
+pub use render::*;
+pub use scale::*;
+pub(crate) use uniforms::*;
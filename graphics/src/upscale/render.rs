@@ -0,0 +1,207 @@
+use crate::{
+    AscendingError, GpuRenderer, Pass, RenderTarget, ResolutionScale,
+    UpscaleLayout, UpscaleRaw, UpscaleRenderPipeline,
+};
+use wgpu::util::DeviceExt;
+
+/// How the scene target is stretched over the window.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum UpscaleFilter {
+    #[default]
+    Bilinear,
+    /// Bilinear followed by contrast adaptive sharpening, 0.0 to 1.0.
+    Sharpen(f32),
+}
+
+/// Dynamic resolution. The scene renders into [`UpscaleRenderer::target`]
+/// at the [`ResolutionScale`] of the window, then this pass stretches it
+/// over the frame. Render anything that should stay sharp, like the UI,
+/// into the frame after it.
+///
+/// Draw the scene with the same [`crate::System`] as before, it stays
+/// sized to the window so the view does not change, only how many pixels
+/// cover it. The target has no stencil.
+pub struct UpscaleRenderer {
+    pub scale: ResolutionScale,
+    filter: UpscaleFilter,
+    target: RenderTarget,
+    buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    bind_group: wgpu::BindGroup,
+    /// Size of the window the target was made for.
+    output: (u32, u32),
+    /// if the uniform needs uploading.
+    changed: bool,
+}
+
+impl UpscaleRenderer {
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        scale: ResolutionScale,
+    ) -> Result<Self, AscendingError> {
+        let buffer = renderer.device().create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Upscale buffer"),
+                contents: bytemuck::bytes_of(&UpscaleRaw::default()),
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+
+        let sampler =
+            renderer.device().create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("upscale_sampler"),
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                lod_max_clamp: 0.0,
+                ..Default::default()
+            });
+
+        let output = Self::window_size(renderer);
+        let (width, height) = scale.scaled_size(output);
+        let target = RenderTarget::new(renderer, width, height);
+        let bind_group =
+            Self::create_bind_group(renderer, &buffer, &sampler, &target);
+
+        Ok(Self {
+            scale,
+            filter: UpscaleFilter::default(),
+            target,
+            buffer,
+            sampler,
+            bind_group,
+            output,
+            changed: true,
+        })
+    }
+
+    fn window_size(renderer: &GpuRenderer) -> (u32, u32) {
+        (
+            (renderer.size().width as u32).max(1),
+            (renderer.size().height as u32).max(1),
+        )
+    }
+
+    fn create_bind_group(
+        renderer: &mut GpuRenderer,
+        buffer: &wgpu::Buffer,
+        sampler: &wgpu::Sampler,
+        target: &RenderTarget,
+    ) -> wgpu::BindGroup {
+        let layout = renderer.create_layout(UpscaleLayout);
+
+        renderer
+            .device()
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(
+                            target.view(),
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                ],
+                label: Some("upscale_bind_group"),
+            })
+    }
+
+    /// Where the scene renders, with [`RenderTarget::begin_pass`].
+    pub fn target(&self) -> &RenderTarget {
+        &self.target
+    }
+
+    pub fn filter(&self) -> UpscaleFilter {
+        self.filter
+    }
+
+    pub fn set_filter(&mut self, filter: UpscaleFilter) -> &mut Self {
+        self.filter = filter;
+        self.changed = true;
+        self
+    }
+
+    /// Feeds the last frame's GPU time in milliseconds to the scale and
+    /// remakes the target if the scale or window size changed. Call once
+    /// per frame before rendering into the target.
+    pub fn update(&mut self, renderer: &mut GpuRenderer, gpu_ms: Option<f32>) {
+        self.scale.update(gpu_ms);
+
+        let output = Self::window_size(renderer);
+        let (width, height) = self.scale.scaled_size(output);
+
+        if output != self.output || (width, height) != self.target.size() {
+            self.output = output;
+            self.target = RenderTarget::new(renderer, width, height);
+            self.bind_group = Self::create_bind_group(
+                renderer,
+                &self.buffer,
+                &self.sampler,
+                &self.target,
+            );
+            self.changed = true;
+        }
+
+        if self.changed {
+            let (width, height) = self.target.size();
+            let raw = UpscaleRaw {
+                source_size: [width as f32, height as f32],
+                output_size: [output.0 as f32, output.1 as f32],
+                sharpness: match self.filter {
+                    UpscaleFilter::Bilinear => 0.0,
+                    UpscaleFilter::Sharpen(amount) => amount.clamp(0.0, 1.0),
+                },
+                padding: [0.0; 3],
+            };
+
+            renderer.queue().write_buffer(
+                &self.buffer,
+                0,
+                bytemuck::bytes_of(&raw),
+            );
+            self.changed = false;
+        }
+    }
+}
+
+impl Pass for UpscaleRenderer {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let view = match renderer.frame_buffer() {
+            Some(view) => view,
+            None => return,
+        };
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("upscale pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        pass.set_pipeline(
+            renderer.get_pipelines(UpscaleRenderPipeline).unwrap(),
+        );
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,127 @@
+/// Frames to wait after a change before the next, so the frame time has
+/// settled at the new scale.
+const SETTLE_FRAMES: u32 = 30;
+
+/// How the scene target scales against the window.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScaleMode {
+    /// Stays at the scale it is set to.
+    Fixed,
+    /// Follows the GPU frame time, lowering the scale while frames take
+    /// longer than target_ms and raising it again once they are well
+    /// under.
+    Auto { target_ms: f32 },
+}
+
+/// Render scale of the scene, 1.0 being the window's resolution. Feed it
+/// GPU frame times, like the sum of a [`crate::PassTimer`]'s passes, with
+/// [`ResolutionScale::update`].
+#[derive(Clone, Debug)]
+pub struct ResolutionScale {
+    pub mode: ScaleMode,
+    /// Lowest scale auto goes to.
+    pub min: f32,
+    /// Highest scale auto goes to. Above 1.0 supersamples.
+    pub max: f32,
+    /// Change per adjustment under auto.
+    pub step: f32,
+    scale: f32,
+    /// Smoothed GPU frame time in milliseconds.
+    average_ms: Option<f32>,
+    /// Frames left before auto may change the scale again.
+    settle: u32,
+}
+
+impl ResolutionScale {
+    /// Renders at scale until set otherwise.
+    pub fn fixed(scale: f32) -> Self {
+        Self {
+            mode: ScaleMode::Fixed,
+            min: 0.5,
+            max: 1.0,
+            step: 0.05,
+            scale: scale.max(0.1),
+            average_ms: None,
+            settle: 0,
+        }
+    }
+
+    /// Starts at the window's resolution and scales down to 0.5 to keep
+    /// GPU frames under target_ms.
+    pub fn auto(target_ms: f32) -> Self {
+        Self {
+            mode: ScaleMode::Auto { target_ms },
+            ..Self::fixed(1.0)
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Sets the scale directly. Auto carries on from it.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.max(0.1);
+        self.settle = SETTLE_FRAMES;
+    }
+
+    /// Smoothed GPU frame time auto adjusts by. None until the first
+    /// update with a time.
+    pub fn average_ms(&self) -> Option<f32> {
+        self.average_ms
+    }
+
+    /// Takes the last frame's GPU time in milliseconds, if it was timed,
+    /// and adjusts the scale under auto. Returns if the scale changed.
+    pub fn update(&mut self, gpu_ms: Option<f32>) -> bool {
+        if let Some(ms) = gpu_ms {
+            self.average_ms = Some(match self.average_ms {
+                Some(average) => average + (ms - average) * 0.1,
+                None => ms,
+            });
+        }
+
+        let ScaleMode::Auto { target_ms } = self.mode else {
+            return false;
+        };
+
+        if self.settle > 0 {
+            self.settle -= 1;
+            return false;
+        }
+
+        let Some(average) = self.average_ms else {
+            return false;
+        };
+
+        let scale = if average > target_ms * 1.05 {
+            (self.scale - self.step).max(self.min)
+        } else if average < target_ms * 0.8 {
+            (self.scale + self.step).min(self.max)
+        } else {
+            self.scale
+        };
+
+        if scale == self.scale {
+            return false;
+        }
+
+        self.scale = scale;
+        self.settle = SETTLE_FRAMES;
+        true
+    }
+
+    /// Size of the scene target for a window of size.
+    pub fn scaled_size(&self, size: (u32, u32)) -> (u32, u32) {
+        (
+            ((size.0 as f32 * self.scale).round() as u32).max(1),
+            ((size.1 as f32 * self.scale).round() as u32).max(1),
+        )
+    }
+}
+
+impl Default for ResolutionScale {
+    fn default() -> Self {
+        Self::fixed(1.0)
+    }
+}
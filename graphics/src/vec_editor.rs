@@ -0,0 +1,192 @@
+use crate::{
+    AscendingError, Color, GpuRenderer, MessageRouter, RectRenderer, Spinner,
+    TextAtlas, TextRenderer, Vec2, Vec3, Vec4, VecEditorWidget, WidgetHandle,
+    WidgetInput,
+};
+
+/// Border colors of the x, y, z and w spinners.
+const AXIS_COLORS: [Color; 4] = [
+    Color::rgba(200, 70, 70, 255),
+    Color::rgba(80, 180, 80, 255),
+    Color::rgba(70, 110, 210, 255),
+    Color::rgba(150, 150, 150, 255),
+];
+
+/// A row of 2 to 4 float [`Spinner`]s for editing a vector, like a
+/// position, scale or color. Emits every component when any of them change.
+pub struct VecEditor {
+    pub spinners: Vec<Spinner<f32>>,
+    pub handle: Option<WidgetHandle<VecEditorWidget>>,
+    /// Pixels between the spinners.
+    pub spacing: f32,
+    position: Vec3,
+    size: Vec2,
+}
+
+impl VecEditor {
+    /// values has 2 to 4 components.
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        values: &[f32],
+        min: f32,
+        max: f32,
+        step: f32,
+        render_layer: u32,
+    ) -> Self {
+        assert!(
+            (2..=4).contains(&values.len()),
+            "a VecEditor has 2 to 4 components"
+        );
+
+        let spinners = values
+            .iter()
+            .zip(AXIS_COLORS)
+            .map(|(value, color)| {
+                let mut spinner = Spinner::new(
+                    renderer,
+                    *value,
+                    min,
+                    max,
+                    step,
+                    render_layer,
+                );
+
+                spinner.set_border_color(color);
+                spinner
+            })
+            .collect();
+
+        let mut editor = Self {
+            spinners,
+            handle: None,
+            spacing: 4.0,
+            position: Vec3::ZERO,
+            size: Vec2::new(240.0, 20.0),
+        };
+
+        editor.layout();
+        editor
+    }
+
+    pub fn values(&self) -> Vec<f32> {
+        self.spinners.iter().map(Spinner::value).collect()
+    }
+
+    /// Sets the values without emitting a change. Extra values are ignored.
+    pub fn set_values(&mut self, values: &[f32]) -> &mut Self {
+        for (spinner, value) in self.spinners.iter_mut().zip(values) {
+            spinner.set_value(*value);
+        }
+
+        self
+    }
+
+    pub fn as_vec2(&self) -> Vec2 {
+        Vec2::new(self.component(0), self.component(1))
+    }
+
+    /// Missing components are 0.0.
+    pub fn as_vec3(&self) -> Vec3 {
+        self.as_vec2().extend(self.component(2))
+    }
+
+    /// Missing components are 0.0.
+    pub fn as_vec4(&self) -> Vec4 {
+        self.as_vec3().extend(self.component(3))
+    }
+
+    /// Bottom left corner and z.
+    pub fn set_position(&mut self, position: Vec3) -> &mut Self {
+        self.position = position;
+        self.layout();
+        self
+    }
+
+    /// Size of the whole row.
+    pub fn set_size(&mut self, size: Vec2) -> &mut Self {
+        self.size = size;
+        self.layout();
+        self
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.spinners.iter().any(Spinner::is_editing)
+    }
+
+    /// Applies a frame of input to every spinner. Returns all the values
+    /// when one changed, which are also emitted if a handle is set.
+    pub fn handle_input<M: 'static>(
+        &mut self,
+        input: &WidgetInput,
+        router: &mut MessageRouter<M>,
+    ) -> Option<Vec<f32>> {
+        let mut changed = false;
+
+        for spinner in &mut self.spinners {
+            changed |= spinner.handle_input(input, router).is_some();
+        }
+
+        if !changed {
+            return None;
+        }
+
+        let values = self.values();
+
+        if let Some(handle) = self.handle {
+            router.emit(handle, values.clone());
+        }
+
+        Some(values)
+    }
+
+    /// Frees every spinner's rects and text.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        for spinner in self.spinners {
+            spinner.unload(renderer);
+        }
+    }
+
+    fn component(&self, index: usize) -> f32 {
+        self.spinners.get(index).map_or(0.0, Spinner::value)
+    }
+
+    fn layout(&mut self) {
+        let count = self.spinners.len() as f32;
+        let width = (self.size.x - self.spacing * (count - 1.0)) / count;
+
+        for (i, spinner) in self.spinners.iter_mut().enumerate() {
+            let x = self.position.x + (width + self.spacing) * i as f32;
+
+            spinner
+                .set_position(Vec3::new(x, self.position.y, self.position.z))
+                .set_size(Vec2::new(width, self.size.y));
+        }
+    }
+}
+
+impl RectRenderer {
+    pub fn vec_editor_update(
+        &mut self,
+        editor: &mut VecEditor,
+        renderer: &mut GpuRenderer,
+    ) {
+        for spinner in &mut editor.spinners {
+            self.spinner_update(spinner, renderer);
+        }
+    }
+}
+
+impl TextRenderer {
+    pub fn vec_editor_update(
+        &mut self,
+        editor: &mut VecEditor,
+        atlas: &mut TextAtlas,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        for spinner in &mut editor.spinners {
+            self.spinner_update(spinner, atlas, renderer)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+use crate::{Allocation, AscendingError, AtlasGroup, GpuRenderer, OtherError};
+use image::{
+    codecs::{gif::GifDecoder, png::PngDecoder},
+    AnimationDecoder, Frame, GenericImageView, ImageFormat,
+};
+use std::{
+    fs::File,
+    io::{BufReader, Cursor, Read},
+    path::Path,
+    sync::Arc,
+};
+
+/// One decoded frame, RGBA8 at the size of its video.
+pub struct VideoFrame {
+    pub bytes: Vec<u8>,
+    /// Seconds it shows for.
+    pub delay: f32,
+}
+
+/// Frames of an animated GIF or APNG, or of an image sequence, decoded up
+/// front. Share it between players with an Arc.
+pub struct Video {
+    name: String,
+    size: (u32, u32),
+    frames: Vec<VideoFrame>,
+}
+
+impl Video {
+    /// A GIF or PNG, picked by the extension. A PNG without animation is a
+    /// single frame.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, AscendingError> {
+        let path = path.as_ref();
+        let name = path.to_string_lossy().into_owned();
+        let format = ImageFormat::from_path(path)?;
+
+        Self::from_reader(name, BufReader::new(File::open(path)?), format)
+    }
+
+    pub fn from_memory(
+        name: String,
+        data: &[u8],
+        format: ImageFormat,
+    ) -> Result<Self, AscendingError> {
+        Self::from_reader(name, Cursor::new(data), format)
+    }
+
+    fn from_reader<R: Read>(
+        name: String,
+        reader: R,
+        format: ImageFormat,
+    ) -> Result<Self, AscendingError> {
+        let frames = match format {
+            ImageFormat::Gif => GifDecoder::new(reader)?.into_frames(),
+            ImageFormat::Png => {
+                let decoder = PngDecoder::new(reader)?;
+
+                if !decoder.is_apng() {
+                    let image = image::DynamicImage::from_decoder(decoder)?;
+                    let size = image.dimensions();
+
+                    return Self::from_frames(
+                        name,
+                        size,
+                        vec![VideoFrame {
+                            bytes: image.into_rgba8().into_raw(),
+                            delay: 0.0,
+                        }],
+                    );
+                }
+
+                decoder.apng().into_frames()
+            }
+            _ => {
+                return Err(unsupported(format!(
+                    "{name} is {format:?}, only GIF and PNG can animate"
+                )))
+            }
+        };
+
+        let frames = frames.collect_frames()?;
+        let size = frames
+            .first()
+            .map(|frame| frame.buffer().dimensions())
+            .unwrap_or_default();
+
+        Self::from_frames(
+            name,
+            size,
+            frames.into_iter().map(to_frame).collect(),
+        )
+    }
+
+    /// Each image of the sequence in order, shown for delay seconds.
+    pub fn from_image_sequence(
+        name: String,
+        paths: &[impl AsRef<Path>],
+        delay: f32,
+    ) -> Result<Self, AscendingError> {
+        let mut size = (0, 0);
+        let mut frames = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let image = image::open(path)?;
+
+            size = image.dimensions();
+            frames.push(VideoFrame {
+                bytes: image.into_rgba8().into_raw(),
+                delay,
+            });
+        }
+
+        Self::from_frames(name, size, frames)
+    }
+
+    /// Every frame must be RGBA8 of size.
+    pub fn from_frames(
+        name: String,
+        size: (u32, u32),
+        frames: Vec<VideoFrame>,
+    ) -> Result<Self, AscendingError> {
+        let len = size.0 as usize * size.1 as usize * 4;
+
+        if frames.is_empty() || len == 0 {
+            return Err(unsupported(format!("{name} has no frames")));
+        }
+
+        if frames.iter().any(|frame| frame.bytes.len() != len) {
+            return Err(unsupported(format!(
+                "{name} has frames that are not {}x{}",
+                size.0, size.1
+            )));
+        }
+
+        Ok(Self { name, size, frames })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub fn frames(&self) -> &[VideoFrame] {
+        &self.frames
+    }
+
+    /// Seconds of every frame together.
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|frame| frame.delay).sum()
+    }
+
+    /// The frame showing at time seconds in.
+    pub fn frame_at(&self, time: f32) -> usize {
+        let mut end = 0.0;
+
+        for (index, frame) in self.frames.iter().enumerate() {
+            end += frame.delay;
+
+            if time < end {
+                return index;
+            }
+        }
+
+        self.frames.len() - 1
+    }
+}
+
+fn to_frame(frame: Frame) -> VideoFrame {
+    let (numer, denom) = frame.delay().numer_denom_ms();
+
+    VideoFrame {
+        delay: numer as f32 / denom.max(1) as f32 / 1000.0,
+        bytes: frame.into_buffer().into_raw(),
+    }
+}
+
+fn unsupported(message: String) -> AscendingError {
+    AscendingError::Other(OtherError::new(&message))
+}
+
+/// Plays a [`Video`] into an allocation of an RGBA atlas, so images draw
+/// it like any other texture of the atlas. Only frame changes are
+/// uploaded.
+pub struct VideoPlayer {
+    video: Arc<Video>,
+    /// Atlas key of the allocation.
+    key: String,
+    /// 1.0 is normal speed.
+    pub speed: f32,
+    /// Starts over at the end instead of stopping on the last frame.
+    pub looping: bool,
+    time: f32,
+    playing: bool,
+    /// Frame in the allocation. None before the first upload.
+    uploaded: Option<usize>,
+    allocation: Option<Allocation>,
+}
+
+impl VideoPlayer {
+    /// key names its allocation in the atlas and must not be used by
+    /// anything else in it.
+    pub fn new(video: Arc<Video>, key: impl Into<String>) -> Self {
+        Self {
+            video,
+            key: key.into(),
+            speed: 1.0,
+            looping: false,
+            time: 0.0,
+            playing: false,
+            uploaded: None,
+            allocation: None,
+        }
+    }
+
+    pub fn video(&self) -> &Arc<Video> {
+        &self.video
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// If it stopped at the end.
+    pub fn is_finished(&self) -> bool {
+        !self.playing && self.time >= self.video.duration()
+    }
+
+    /// Plays from where it is, or from the start once finished.
+    pub fn play(&mut self) {
+        if self.is_finished() {
+            self.seek(0.0);
+        }
+
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Pauses and goes back to the first frame.
+    pub fn stop(&mut self) {
+        self.playing = false;
+        self.seek(0.0);
+    }
+
+    pub fn seek(&mut self, time: f32) {
+        self.time = time.clamp(0.0, self.video.duration());
+    }
+
+    /// The frame showing now.
+    pub fn frame(&self) -> usize {
+        self.video.frame_at(self.time)
+    }
+
+    /// The allocation images draw the video from. None until the first
+    /// update.
+    pub fn allocation(&self) -> Option<Allocation> {
+        self.allocation
+    }
+
+    /// Moves along by delta seconds times the speed and uploads the frame
+    /// if it changed. The atlas must be Rgba8UnormSrgb. Returns the
+    /// allocation to draw, which only changes if the atlas evicted it.
+    pub fn update(
+        &mut self,
+        renderer: &GpuRenderer,
+        atlas: &mut AtlasGroup,
+        delta: f32,
+    ) -> Result<Allocation, AscendingError> {
+        if atlas.atlas.format != wgpu::TextureFormat::Rgba8UnormSrgb {
+            return Err(unsupported(format!(
+                "videos need an Rgba8UnormSrgb atlas, not {:?}",
+                atlas.atlas.format
+            )));
+        }
+
+        if self.playing {
+            let duration = self.video.duration();
+
+            self.time += delta * self.speed;
+
+            if self.time >= duration {
+                if self.looping && duration > 0.0 {
+                    self.time %= duration;
+                } else {
+                    self.time = duration;
+                    self.playing = false;
+                }
+            }
+        }
+
+        let (width, height) = self.video.size();
+        let allocation = atlas
+            .atlas
+            .reserve(self.key.clone(), width, height, 0, renderer)
+            .ok_or(AscendingError::AtlasFull)?;
+        let frame = self.frame();
+
+        if self.uploaded != Some(frame)
+            || self.allocation.map(|old| (old.layer, old.rect()))
+                != Some((allocation.layer, allocation.rect()))
+        {
+            atlas.atlas.upload_allocation(
+                &self.video.frames[frame].bytes,
+                &allocation,
+                renderer,
+            );
+            self.uploaded = Some(frame);
+        }
+
+        self.allocation = Some(allocation);
+        Ok(allocation)
+    }
+}
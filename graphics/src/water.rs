@@ -0,0 +1,9 @@
+pub(crate) mod pipeline;
+mod render;
+pub(crate) mod uniforms;
+pub(crate) mod vertex;
+
+pub(crate) use pipeline::*;
+pub use render::*;
+pub(crate) use uniforms::*;
+pub(crate) use vertex::*;
@@ -0,0 +1,288 @@
+use crate::{
+    AsBufferPass, AscendingError, Color, GpuRenderer, InstanceBuffer, Map,
+    OrderedIndex, RenderCommands, SetBuffers, StaticBufferObject, WaterLayout,
+    WaterRaw, WaterRenderPipeline, WaterVertex,
+};
+use wgpu::util::DeviceExt;
+
+/// Draws the water tiles of maps. The reflection is a copy of the frame
+/// taken with [`WaterRenderer::capture`], mirrored around each body of
+/// water's top edge in the shader, so capture after the layers that should
+/// show in the water and render the water after that.
+pub struct WaterRenderer {
+    pub buffer: InstanceBuffer<WaterVertex>,
+    uniform: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    reflection: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    /// Size of the reflection texture.
+    size: [u32; 2],
+    tint: Color,
+    ripple_speed: f32,
+    ripple_strength: f32,
+    ripple_scale: f32,
+    reflectivity: f32,
+    reflection_depth: f32,
+    /// if the uniform needs to be uploaded.
+    changed: bool,
+}
+
+impl WaterRenderer {
+    pub fn new(renderer: &mut GpuRenderer) -> Result<Self, AscendingError> {
+        let uniform = renderer.device().create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Water buffer"),
+                contents: bytemuck::bytes_of(&WaterRaw::default()),
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+
+        let sampler =
+            renderer.device().create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("water_sampler"),
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                lod_max_clamp: 0.0,
+                ..Default::default()
+            });
+
+        let size = [
+            (renderer.size().width as u32).max(1),
+            (renderer.size().height as u32).max(1),
+        ];
+        let reflection = Self::create_reflection(renderer, size);
+        let bind_group =
+            Self::create_bind_group(renderer, &uniform, &sampler, &reflection);
+
+        Ok(Self {
+            buffer: InstanceBuffer::new(renderer.gpu_device()),
+            uniform,
+            sampler,
+            reflection,
+            bind_group,
+            size,
+            tint: Color::rgba(40, 90, 140, 160),
+            ripple_speed: 1.0,
+            ripple_strength: 2.0,
+            ripple_scale: 0.15,
+            reflectivity: 0.6,
+            reflection_depth: 96.0,
+            changed: true,
+        })
+    }
+
+    fn create_reflection(
+        renderer: &GpuRenderer,
+        size: [u32; 2],
+    ) -> wgpu::Texture {
+        renderer.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some("water reflection texture"),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: renderer.surface_format(),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    fn create_bind_group(
+        renderer: &mut GpuRenderer,
+        uniform: &wgpu::Buffer,
+        sampler: &wgpu::Sampler,
+        reflection: &wgpu::Texture,
+    ) -> wgpu::BindGroup {
+        let layout = renderer.create_layout(WaterLayout);
+        let view =
+            reflection.create_view(&wgpu::TextureViewDescriptor::default());
+
+        renderer
+            .device()
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: uniform.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                ],
+                label: Some("water_bind_group"),
+            })
+    }
+
+    /// Color the water fades to where the reflection is weak. Alpha is how
+    /// much of the tiles below still show through.
+    pub fn set_tint(&mut self, tint: Color) {
+        self.tint = tint;
+        self.changed = true;
+    }
+
+    pub fn set_ripple_speed(&mut self, speed: f32) {
+        self.ripple_speed = speed;
+        self.changed = true;
+    }
+
+    /// How far in pixels the ripples push the reflection around.
+    pub fn set_ripple_strength(&mut self, strength: f32) {
+        self.ripple_strength = strength;
+        self.changed = true;
+    }
+
+    /// Frequency of the ripples. Higher gives smaller waves.
+    pub fn set_ripple_scale(&mut self, scale: f32) {
+        self.ripple_scale = scale;
+        self.changed = true;
+    }
+
+    /// Strength of the reflection right at the water's edge. 0.0 - 1.0.
+    pub fn set_reflectivity(&mut self, reflectivity: f32) {
+        self.reflectivity = reflectivity.clamp(0.0, 1.0);
+        self.changed = true;
+    }
+
+    /// Distance in pixels from the water's edge where the reflection has
+    /// faded out to the tint.
+    pub fn set_reflection_depth(&mut self, depth: f32) {
+        self.reflection_depth = depth.max(1.0);
+        self.changed = true;
+    }
+
+    pub fn add_buffer_store(
+        &mut self,
+        renderer: &GpuRenderer,
+        index: OrderedIndex,
+    ) {
+        self.buffer.add_buffer_store(renderer, index);
+    }
+
+    /// Uploads the settings and resizes the reflection if the screen did.
+    pub fn finalize(&mut self, renderer: &mut GpuRenderer) {
+        let size = [
+            (renderer.size().width as u32).max(1),
+            (renderer.size().height as u32).max(1),
+        ];
+
+        if size != self.size {
+            self.size = size;
+            self.reflection = Self::create_reflection(renderer, size);
+            self.bind_group = Self::create_bind_group(
+                renderer,
+                &self.uniform,
+                &self.sampler,
+                &self.reflection,
+            );
+        }
+
+        if self.changed {
+            let raw = WaterRaw {
+                tint: self.tint.0,
+                ripple_speed: self.ripple_speed,
+                ripple_strength: self.ripple_strength,
+                ripple_scale: self.ripple_scale,
+                reflectivity: self.reflectivity,
+                reflection_depth: self.reflection_depth,
+                reflect: u32::from(renderer.frame_copyable()),
+                padding: 0.0,
+            };
+
+            renderer.queue().write_buffer(
+                &self.uniform,
+                0,
+                bytemuck::bytes_of(&raw),
+            );
+            self.changed = false;
+        }
+
+        self.buffer.finalize(renderer)
+    }
+
+    /// Adds the map's water tiles. Call after the map was updated so the
+    /// tiles are current.
+    pub fn water_update(&mut self, map: &Map, renderer: &mut GpuRenderer) {
+        if let Some(index) = map.water_index() {
+            self.add_buffer_store(renderer, index);
+        }
+    }
+
+    /// Copies the frame so far into the reflection texture. Must be called
+    /// between render passes. Does nothing if the surface can't be copied,
+    /// the water then only shows its tint.
+    pub fn capture(
+        &self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        if self.buffer.count() == 0 || !renderer.frame_copyable() {
+            return;
+        }
+
+        if let Some(frame) = renderer.frame_texture() {
+            let frame_size = frame.size();
+
+            encoder.copy_texture_to_texture(
+                frame.as_image_copy(),
+                self.reflection.as_image_copy(),
+                wgpu::Extent3d {
+                    width: frame_size.width.min(self.size[0]),
+                    height: frame_size.height.min(self.size[1]),
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+    }
+}
+
+pub trait RenderWater<'a, 'b>
+where
+    'b: 'a,
+{
+    /// The render pass must have the depth buffer attached.
+    fn render_water(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b WaterRenderer,
+    );
+}
+
+impl<'a, 'b, P> RenderWater<'a, 'b> for P
+where
+    'b: 'a,
+    P: RenderCommands<'a>,
+{
+    fn render_water(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b WaterRenderer,
+    ) {
+        if buffer.buffer.count() > 0 {
+            self.set_buffers(renderer.buffer_object.as_buffer_pass());
+            self.set_bind_group(1, &buffer.bind_group, &[]);
+            self.set_vertex_buffer(1, buffer.buffer.instances(None));
+            self.set_pipeline(
+                renderer.get_pipelines(WaterRenderPipeline).unwrap(),
+            );
+            self.draw_indexed(
+                0..StaticBufferObject::index_count(),
+                0,
+                0..buffer.buffer.count(),
+            );
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{BufferData, BufferLayout};
+use std::iter;
+
+/// Water draws between the ground and the mask layers so anything
+/// standing in the water stays on top of it.
+pub const WATER_Z: f32 = 9.5;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+/// 4 of these per water tile.
+pub struct WaterVertex {
+    pub position: [f32; 3],
+    pub tilesize: f32,
+    /// Y of the top edge of the water the tile belongs to. The reflection
+    /// is mirrored around it.
+    pub surface: f32,
+    pub parallax: f32,
+}
+
+impl Default for WaterVertex {
+    fn default() -> Self {
+        Self {
+            position: [0.0; 3],
+            tilesize: 0.0,
+            surface: 0.0,
+            parallax: 1.0,
+        }
+    }
+}
+
+impl BufferLayout for WaterVertex {
+    fn attributes() -> Vec<wgpu::VertexAttribute> {
+        wgpu::vertex_attr_array![1 => Float32x3, 2 => Float32, 3 => Float32, 4 => Float32]
+            .to_vec()
+    }
+
+    fn default_buffer() -> BufferData {
+        Self::with_capacity(1_024, 0)
+    }
+
+    fn with_capacity(
+        vertex_capacity: usize,
+        _index_capacity: usize,
+    ) -> BufferData {
+        let instance_arr: Vec<WaterVertex> =
+            iter::repeat(WaterVertex::default())
+                .take(vertex_capacity)
+                .collect();
+
+        BufferData {
+            vertexs: bytemuck::cast_slice(&instance_arr).to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn stride() -> usize {
+        std::mem::size_of::<[f32; 6]>()
+    }
+}
@@ -0,0 +1,40 @@
+use crate::{System, Vec2};
+use input::InputHandler;
+use std::hash::Hash;
+
+/// Mouse and keyboard state handed to widgets each frame.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct WidgetInput<'a> {
+    /// Mouse position in render space, None when outside the window.
+    pub mouse_pos: Option<Vec2>,
+    /// if the left mouse button is down.
+    pub mouse_down: bool,
+    /// if either shift key is down.
+    pub shift: bool,
+    /// See [`InputHandler::typed_text`].
+    pub typed: &'a str,
+}
+
+impl<'a> WidgetInput<'a> {
+    pub fn new<ActionId, AxisId, Controls>(
+        input: &'a InputHandler<ActionId, AxisId>,
+        system: &System<Controls>,
+    ) -> Self
+    where
+        ActionId: Clone + Eq + Hash + Send + Sync,
+        AxisId: Clone + Eq + Hash + Send + Sync,
+        Controls: camera::controls::Controls,
+    {
+        let mouse_pos = input.mouse_position().map(|(x, y)| {
+            system.to_render_pos(system.window_to_screen_pos(Vec2::new(x, y)))
+        });
+
+        Self {
+            mouse_pos,
+            mouse_down: input
+                .is_mouse_button_down(winit::event::MouseButton::Left),
+            shift: input.modifiers().shift(),
+            typed: input.typed_text(),
+        }
+    }
+}
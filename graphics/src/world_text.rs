@@ -0,0 +1,479 @@
+use crate::{
+    AscendingError, Color, Easing, GpuRenderer, Pool, PoolHandle, Poolable,
+    Rect, RectRenderer, System, Text, TextAtlas, TextMeasurement, TextRenderer,
+    Vec2, Vec3,
+};
+use cosmic_text::{Attrs, Metrics, Shaping, Wrap};
+
+/// if a box at pos of size overlaps the screen.
+fn overlaps_screen(pos: Vec2, size: Vec2, screen_size: [f32; 2]) -> bool {
+    pos.x + size.x >= 0.0
+        && pos.y + size.y >= 0.0
+        && pos.x <= screen_size[0]
+        && pos.y <= screen_size[1]
+}
+
+/// Lays out content in text at width, returning its size.
+fn shape_text(
+    text: &mut Text,
+    renderer: &mut GpuRenderer,
+    content: &str,
+    width: f32,
+) -> Vec2 {
+    text.set_buffer_size(renderer, width as i32, i32::MAX)
+        .set_text(renderer, content, Attrs::new())
+        .shape_until_scroll(renderer);
+
+    let size = TextMeasurement::from_buffer(&text.buffer).size;
+
+    text.size = size;
+    size
+}
+
+/// Text in a rounded box with a tail pointing down at a world position,
+/// like a character talking. The box grows to fit the text up to
+/// max_width and wraps after that. Its corners and border keep their size
+/// however it stretches, like a nine-slice, as the rect shader draws them.
+///
+/// The anchor is projected through the camera on update and the bubble is
+/// drawn in screen space like a [`crate::WorldUi`], so it keeps its size.
+/// Bubbles off screen are skipped when rendering.
+pub struct SpeechBubble {
+    /// World position the tail points at, usually above a sprite's head.
+    pub anchor: Vec3,
+    /// Pixels from the projected anchor to the tail's tip.
+    pub offset: Vec2,
+    /// Screen space Z the bubble renders at.
+    pub z: f32,
+    pub background: Rect,
+    pub tail: Rect,
+    pub text: Text,
+    /// Seconds left before it hides, None keeps it up.
+    pub lifetime: Option<f32>,
+    content: String,
+    max_width: f32,
+    padding: Vec2,
+    tail_size: f32,
+    text_size: Vec2,
+    screen_pos: Vec2,
+    on_screen: bool,
+    changed: bool,
+}
+
+impl SpeechBubble {
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        content: &str,
+        anchor: Vec3,
+        z: f32,
+    ) -> Self {
+        let mut background = Rect::new(renderer, 0);
+        let mut tail = Rect::new(renderer, 0);
+        let mut text = Text::new(
+            renderer,
+            Some(Metrics::new(14.0, 16.0)),
+            Vec3::ZERO,
+            Vec2::ZERO,
+        );
+
+        background
+            .set_color(Color::rgba(250, 250, 245, 240))
+            .set_border_width(1.0)
+            .set_border_color(Color::rgba(40, 40, 40, 255))
+            .set_radius(6.0);
+        tail.set_color(Color::rgba(250, 250, 245, 240))
+            .set_border_width(1.0)
+            .set_border_color(Color::rgba(40, 40, 40, 255))
+            .set_rotation(45.0);
+        text.set_default_color(Color::rgba(20, 20, 20, 255));
+
+        let mut bubble = Self {
+            anchor,
+            offset: Vec2::new(0.0, 8.0),
+            z,
+            background,
+            tail,
+            text,
+            lifetime: None,
+            content: String::new(),
+            max_width: 200.0,
+            padding: Vec2::new(8.0, 6.0),
+            tail_size: 8.0,
+            text_size: Vec2::ZERO,
+            screen_pos: Vec2::ZERO,
+            on_screen: false,
+            changed: true,
+        };
+
+        bubble.set_text(renderer, content);
+        bubble
+    }
+
+    /// Replaces the text and resizes the box to fit it.
+    pub fn set_text(&mut self, renderer: &mut GpuRenderer, content: &str) {
+        self.text.set_wrap(renderer, Wrap::Word);
+        self.text_size =
+            shape_text(&mut self.text, renderer, content, self.max_width);
+        self.content = content.to_owned();
+        self.changed = true;
+    }
+
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Shows only the first count characters for a typewriter effect. The
+    /// box keeps the size of the whole text so it doesn't grow as it types.
+    pub fn set_revealed(&mut self, renderer: &mut GpuRenderer, count: usize) {
+        let split = self
+            .content
+            .char_indices()
+            .nth(count)
+            .map_or(self.content.len(), |(index, _)| index);
+        let (shown, hidden) = self.content.split_at(split);
+        let color = self.text.default_color;
+
+        self.text.buffer.set_rich_text(
+            &mut renderer.font_sys,
+            [
+                (shown, Attrs::new().color(color)),
+                (hidden, Attrs::new().color(Color::rgba(0, 0, 0, 0))),
+            ],
+            Shaping::Advanced,
+        );
+        self.text.set_change(true);
+    }
+
+    /// Width the text wraps at. Takes effect on the next set_text.
+    pub fn set_max_width(&mut self, max_width: f32) -> &mut Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Space between the text and the box's edge.
+    pub fn set_padding(&mut self, padding: Vec2) -> &mut Self {
+        self.padding = padding;
+        self.changed = true;
+        self
+    }
+
+    /// Side of the square the tail is cut from.
+    pub fn set_tail_size(&mut self, tail_size: f32) -> &mut Self {
+        self.tail_size = tail_size;
+        self.changed = true;
+        self
+    }
+
+    pub fn set_anchor(&mut self, anchor: Vec3) -> &mut Self {
+        if self.anchor != anchor {
+            self.anchor = anchor;
+            self.changed = true;
+        }
+
+        self
+    }
+
+    /// Box size including padding.
+    pub fn size(&self) -> Vec2 {
+        self.text_size + self.padding * 2.0
+    }
+
+    /// if the bubble was on screen and not expired on the last update.
+    pub fn is_on_screen(&self) -> bool {
+        self.on_screen && self.lifetime.is_none_or(|left| left > 0.0)
+    }
+
+    /// if the lifetime ran out.
+    pub fn is_expired(&self) -> bool {
+        self.lifetime.is_some_and(|left| left <= 0.0)
+    }
+
+    /// Counts down the lifetime, projects the anchor and lays the bubble
+    /// out. Must be called before the rect and text renderers update it.
+    pub fn update<Controls>(
+        &mut self,
+        system: &System<Controls>,
+        delta: f32,
+    ) -> bool
+    where
+        Controls: camera::controls::Controls,
+    {
+        if let Some(left) = &mut self.lifetime {
+            *left -= delta;
+        }
+
+        let screen_pos = system.world_to_screen_pos(self.anchor);
+
+        if screen_pos != self.screen_pos {
+            self.screen_pos = screen_pos;
+            self.changed = true;
+        }
+
+        if self.changed {
+            self.layout();
+        }
+
+        let size = self.size();
+        let corner = self.background.position.truncate();
+
+        self.on_screen = overlaps_screen(corner, size, system.screen_size);
+        self.is_on_screen()
+    }
+
+    fn layout(&mut self) {
+        let tip = self.screen_pos + self.offset;
+        let size = self.size();
+        // Rotated 45 degrees around its corner the tail's square stands on
+        // the tip with its widest point half a diagonal up.
+        let half_diagonal = self.tail_size * std::f32::consts::FRAC_1_SQRT_2;
+        let corner = Vec2::new(tip.x - size.x / 2.0, tip.y + half_diagonal);
+
+        // The box is in front of the tail, hiding its top half.
+        self.tail
+            .set_position(tip.extend(self.z))
+            .set_size(Vec2::splat(self.tail_size));
+        self.background
+            .set_position(corner.extend(self.z - 0.01))
+            .set_size(size);
+        self.text
+            .set_position((corner + self.padding).extend(self.z - 0.02));
+
+        self.changed = false;
+    }
+
+    /// Frees the bubble's rects and text.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        self.background.unload(renderer);
+        self.tail.unload(renderer);
+        self.text.unload(renderer);
+    }
+}
+
+/// How [`FloatingText`] moves and fades over its life.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FloatingTextStyle {
+    pub metrics: Metrics,
+    pub color: Color,
+    /// Seconds from spawning to gone.
+    pub duration: f32,
+    /// Pixels moved over the whole duration, up by default.
+    pub motion: Vec2,
+    pub motion_easing: Easing,
+    /// Share of the duration before it starts fading, 0.0 to 1.0.
+    pub fade_start: f32,
+    pub fade_easing: Easing,
+}
+
+impl Default for FloatingTextStyle {
+    fn default() -> Self {
+        Self {
+            metrics: Metrics::new(16.0, 18.0),
+            color: Color::rgba(255, 255, 255, 255),
+            duration: 1.0,
+            motion: Vec2::new(0.0, 40.0),
+            motion_easing: Easing::EaseOut,
+            fade_start: 0.5,
+            fade_easing: Easing::EaseIn,
+        }
+    }
+}
+
+/// Text rising from a world position and fading out, like damage numbers.
+pub struct FloatingText {
+    pub anchor: Vec3,
+    pub style: FloatingTextStyle,
+    pub text: Text,
+    age: f32,
+    on_screen: bool,
+}
+
+impl FloatingText {
+    /// Share of its life gone, 0.0 to 1.0.
+    pub fn progress(&self) -> f32 {
+        if self.style.duration <= 0.0 {
+            return 1.0;
+        }
+
+        (self.age / self.style.duration).clamp(0.0, 1.0)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.age >= self.style.duration
+    }
+
+    fn layout(&mut self, screen_pos: Vec2, z: f32) {
+        let style = self.style;
+        let t = self.progress();
+        let rise = style.motion * style.motion_easing.apply(t);
+        let fade = if t <= style.fade_start {
+            0.0
+        } else {
+            style
+                .fade_easing
+                .apply((t - style.fade_start) / (1.0 - style.fade_start))
+        };
+        let alpha = style.color.a() as f32 * (1.0 - fade);
+        // Centered over the anchor.
+        let pos = screen_pos + rise - Vec2::new(self.text.size.x / 2.0, 0.0);
+
+        self.text
+            .set_default_color(Color::rgba(
+                style.color.r(),
+                style.color.g(),
+                style.color.b(),
+                alpha as u8,
+            ))
+            .set_position(pos.extend(z));
+    }
+}
+
+impl Poolable for FloatingText {
+    fn unload(self, renderer: &mut GpuRenderer) {
+        self.text.unload(renderer);
+    }
+}
+
+/// Every floating text in the scene. Texts are removed once their
+/// duration is up and skipped when rendering while off screen.
+pub struct FloatingTexts {
+    /// Used by [`FloatingTexts::spawn`].
+    pub style: FloatingTextStyle,
+    /// Screen space Z the texts render at.
+    pub z: f32,
+    texts: Pool<FloatingText>,
+}
+
+impl FloatingTexts {
+    pub fn new(z: f32) -> Self {
+        Self {
+            style: FloatingTextStyle::default(),
+            z,
+            texts: Pool::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.texts.is_empty()
+    }
+
+    /// Spawns content at a world position with the default style.
+    pub fn spawn(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        anchor: Vec3,
+        content: &str,
+    ) -> PoolHandle {
+        self.spawn_styled(renderer, anchor, content, self.style)
+    }
+
+    pub fn spawn_styled(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        anchor: Vec3,
+        content: &str,
+        style: FloatingTextStyle,
+    ) -> PoolHandle {
+        let mut text =
+            Text::new(renderer, Some(style.metrics), Vec3::ZERO, Vec2::ZERO);
+
+        text.set_wrap(renderer, Wrap::None);
+        shape_text(&mut text, renderer, content, f32::MAX);
+
+        self.texts.insert(FloatingText {
+            anchor,
+            style,
+            text,
+            age: 0.0,
+            on_screen: false,
+        })
+    }
+
+    pub fn get_mut(&mut self, handle: PoolHandle) -> Option<&mut FloatingText> {
+        self.texts.get_mut(handle)
+    }
+
+    /// Ages the texts by delta, removes finished ones and moves the rest.
+    /// Must be called before the text renderer updates them.
+    pub fn update<Controls>(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        system: &System<Controls>,
+        delta: f32,
+    ) where
+        Controls: camera::controls::Controls,
+    {
+        let z = self.z;
+
+        self.texts.retain(renderer, |_, text| {
+            text.age += delta;
+
+            if text.is_finished() {
+                return false;
+            }
+
+            text.layout(system.world_to_screen_pos(text.anchor), z);
+            text.on_screen = overlaps_screen(
+                text.text.pos.truncate(),
+                text.text.size,
+                system.screen_size,
+            );
+            true
+        });
+    }
+
+    pub fn clear(&mut self, renderer: &mut GpuRenderer) {
+        self.texts.clear(renderer);
+    }
+}
+
+impl RectRenderer {
+    /// Adds the bubble's box and tail if it's on screen.
+    pub fn speech_bubble_update(
+        &mut self,
+        bubble: &mut SpeechBubble,
+        renderer: &mut GpuRenderer,
+    ) {
+        if !bubble.is_on_screen() {
+            return;
+        }
+
+        self.rect_update(&mut bubble.tail, renderer);
+        self.rect_update(&mut bubble.background, renderer);
+    }
+}
+
+impl TextRenderer {
+    /// Adds the bubble's text if it's on screen.
+    pub fn speech_bubble_update(
+        &mut self,
+        bubble: &mut SpeechBubble,
+        atlas: &mut TextAtlas,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        if !bubble.is_on_screen() {
+            return Ok(());
+        }
+
+        self.text_update(&mut bubble.text, atlas, renderer)
+    }
+
+    /// Adds every floating text that's on screen.
+    pub fn floating_texts_update(
+        &mut self,
+        texts: &mut FloatingTexts,
+        atlas: &mut TextAtlas,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        for (_, text) in texts.texts.iter_mut() {
+            if text.on_screen {
+                self.text_update(&mut text.text, atlas, renderer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
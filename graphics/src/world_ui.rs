@@ -0,0 +1,334 @@
+use crate::{
+    AscendingError, Color, GpuRenderer, LocalizedString, Rect, RectRenderer,
+    System, Text, TextAtlas, TextRenderer, UiKey, Vec2, Vec3,
+};
+
+/// How a [`WorldUiElement`] is drawn.
+pub enum WorldUiKind {
+    /// A single flat rect.
+    Rect,
+    /// A background rect with a fill rect drawn over it.
+    /// percent is 0.0..=1.0 of the width to fill.
+    Bar { fill: Rect, percent: f32 },
+}
+
+pub struct WorldUiElement {
+    /// Set when the element was created by [`WorldUi::reconcile`].
+    pub key: Option<UiKey>,
+    /// Offset in pixels from the projected anchor.
+    pub offset: Vec2,
+    pub size: Vec2,
+    pub rect: Rect,
+    pub kind: WorldUiKind,
+}
+
+pub struct WorldUiText {
+    /// Set when the text was created by [`WorldUi::reconcile`].
+    pub key: Option<UiKey>,
+    /// Offset in pixels from the projected anchor.
+    pub offset: Vec2,
+    pub text: Text,
+    /// What the text was last set to by reconcile or a localize.
+    pub(crate) content: String,
+    /// Message the text is looked up from, see [`WorldUi::localize`].
+    pub localized: Option<LocalizedString>,
+}
+
+impl WorldUiElement {
+    /// Frees the element's rects.
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        self.rect.unload(renderer);
+
+        if let WorldUiKind::Bar { fill, .. } = self.kind {
+            fill.unload(renderer);
+        }
+    }
+}
+
+impl WorldUiText {
+    pub fn unload(self, renderer: &mut GpuRenderer) {
+        self.text.unload(renderer);
+    }
+}
+
+/// A group of rects and text attached to a world position, like health bars,
+/// name tags or damage numbers. The anchor is projected through the camera
+/// every update and the elements are rendered in screen space so they keep
+/// their size. Groups outside the screen are skipped when rendering.
+pub struct WorldUi {
+    /// World position the group follows. Usually a sprite's position.
+    pub anchor: Vec3,
+    /// Screen space Z the group renders at.
+    pub z: f32,
+    pub elements: Vec<WorldUiElement>,
+    pub texts: Vec<WorldUiText>,
+    /// if offsets and sizes should be scaled by the camera's zoom.
+    pub use_zoom: bool,
+    /// Set to false to hide the group regardless of where it is.
+    pub visible: bool,
+    /// Keyed widget that has focus. Kept across reconciles while the key
+    /// is still described.
+    pub(crate) focused: Option<UiKey>,
+    /// Localizer generation the texts were last localized with.
+    pub(crate) locale_generation: Option<u64>,
+    /// Projected anchor position from the last update.
+    screen_pos: Vec2,
+    /// Camera scale from the last update.
+    scale: f32,
+    /// if the group was within the screen on the last update.
+    on_screen: bool,
+    /// if anything got updated we need to reposition the elements.
+    changed: bool,
+}
+
+impl WorldUi {
+    pub fn new(anchor: Vec3, z: f32) -> Self {
+        Self {
+            anchor,
+            z,
+            elements: Vec::new(),
+            texts: Vec::new(),
+            use_zoom: false,
+            visible: true,
+            focused: None,
+            locale_generation: None,
+            screen_pos: Vec2::default(),
+            scale: 1.0,
+            on_screen: false,
+            changed: true,
+        }
+    }
+
+    pub fn add_rect(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        offset: Vec2,
+        size: Vec2,
+        color: Color,
+    ) -> usize {
+        let mut rect = Rect::new(renderer, 0);
+        rect.set_color(color);
+
+        self.elements.push(WorldUiElement {
+            key: None,
+            offset,
+            size,
+            rect,
+            kind: WorldUiKind::Rect,
+        });
+        self.changed = true;
+        self.elements.len() - 1
+    }
+
+    pub fn add_bar(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        offset: Vec2,
+        size: Vec2,
+        background: Color,
+        fill_color: Color,
+        percent: f32,
+    ) -> usize {
+        let mut rect = Rect::new(renderer, 0);
+        rect.set_color(background);
+
+        let mut fill = Rect::new(renderer, 0);
+        fill.set_color(fill_color);
+
+        self.elements.push(WorldUiElement {
+            key: None,
+            offset,
+            size,
+            rect,
+            kind: WorldUiKind::Bar {
+                fill,
+                percent: percent.clamp(0.0, 1.0),
+            },
+        });
+        self.changed = true;
+        self.elements.len() - 1
+    }
+
+    /// Sets how much of a bar is filled. Does nothing if index is not a bar.
+    pub fn set_bar_percent(&mut self, index: usize, value: f32) {
+        if let Some(WorldUiElement {
+            kind: WorldUiKind::Bar { percent, .. },
+            ..
+        }) = self.elements.get_mut(index)
+        {
+            *percent = value.clamp(0.0, 1.0);
+            self.changed = true;
+        }
+    }
+
+    pub fn add_text(&mut self, mut text: Text, offset: Vec2) -> usize {
+        text.use_camera = false;
+        text.changed = true;
+        self.texts.push(WorldUiText {
+            key: None,
+            offset,
+            text,
+            content: String::new(),
+            localized: None,
+        });
+        self.changed = true;
+        self.texts.len() - 1
+    }
+
+    pub fn set_anchor(&mut self, anchor: Vec3) -> &mut Self {
+        if self.anchor != anchor {
+            self.anchor = anchor;
+            self.changed = true;
+        }
+
+        self
+    }
+
+    pub fn set_visible(&mut self, visible: bool) -> &mut Self {
+        self.visible = visible;
+        self
+    }
+
+    /// Gives focus to a keyed widget.
+    pub fn focus(&mut self, key: Option<UiKey>) -> &mut Self {
+        self.focused = key;
+        self
+    }
+
+    pub fn focused(&self) -> Option<UiKey> {
+        self.focused
+    }
+
+    /// Forces the elements to be laid out again on the next update.
+    pub fn mark_changed(&mut self) {
+        self.changed = true;
+    }
+
+    /// if the group was within the screen on the last update.
+    pub fn is_on_screen(&self) -> bool {
+        self.visible && self.on_screen
+    }
+
+    /// Projects the anchor and repositions all the elements.
+    /// Must be called before the rect and text renderers update the group.
+    pub fn update<Controls>(&mut self, system: &System<Controls>) -> bool
+    where
+        Controls: camera::controls::Controls,
+    {
+        let screen_pos = system.world_to_screen_pos(self.anchor);
+        let scale = if self.use_zoom {
+            system.controls().scale()
+        } else {
+            1.0
+        };
+
+        if screen_pos != self.screen_pos || scale != self.scale {
+            self.screen_pos = screen_pos;
+            self.scale = scale;
+            self.changed = true;
+        }
+
+        if self.changed {
+            self.layout();
+        }
+
+        self.on_screen = self.check_screen(system.screen_size);
+        self.is_on_screen()
+    }
+
+    fn layout(&mut self) {
+        let (pos, scale, z) = (self.screen_pos, self.scale, self.z);
+
+        for element in &mut self.elements {
+            let position = pos + element.offset * scale;
+            let size = element.size * scale;
+
+            element
+                .rect
+                .set_position(Vec3::new(position.x, position.y, z))
+                .set_size(size);
+
+            if let WorldUiKind::Bar { fill, percent } = &mut element.kind {
+                // lower Z renders over the background rect.
+                fill.set_position(Vec3::new(position.x, position.y, z - 0.01))
+                    .set_size(Vec2::new(size.x * *percent, size.y));
+            }
+        }
+
+        for text in &mut self.texts {
+            let position = pos + text.offset * scale;
+
+            text.text
+                .set_position(Vec3::new(position.x, position.y, z - 0.01));
+        }
+
+        self.changed = false;
+    }
+
+    fn check_screen(&self, screen_size: [f32; 2]) -> bool {
+        let mut min = Vec2::splat(f32::MAX);
+        let mut max = Vec2::splat(f32::MIN);
+
+        for element in &self.elements {
+            min = min.min(element.rect.position.truncate());
+            max = max.max(element.rect.position.truncate() + element.rect.size);
+        }
+
+        for text in &self.texts {
+            min = min.min(text.text.pos.truncate());
+            max = max.max(text.text.pos.truncate() + text.text.size);
+        }
+
+        if min.x > max.x {
+            return false;
+        }
+
+        max.x >= 0.0
+            && max.y >= 0.0
+            && min.x <= screen_size[0]
+            && min.y <= screen_size[1]
+    }
+}
+
+impl RectRenderer {
+    /// Adds the group's rects if the group is visible and on screen.
+    pub fn world_ui_update(
+        &mut self,
+        world_ui: &mut WorldUi,
+        renderer: &mut GpuRenderer,
+    ) {
+        if !world_ui.is_on_screen() {
+            return;
+        }
+
+        for element in &mut world_ui.elements {
+            self.rect_update(&mut element.rect, renderer);
+
+            if let WorldUiKind::Bar { fill, percent } = &mut element.kind {
+                if *percent > 0.0 {
+                    self.rect_update(fill, renderer);
+                }
+            }
+        }
+    }
+}
+
+impl TextRenderer {
+    /// Adds the group's text if the group is visible and on screen.
+    pub fn world_ui_update(
+        &mut self,
+        world_ui: &mut WorldUi,
+        atlas: &mut TextAtlas,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        if !world_ui.is_on_screen() {
+            return Ok(());
+        }
+
+        for text in &mut world_ui.texts {
+            self.text_update(&mut text.text, atlas, renderer)?;
+        }
+
+        Ok(())
+    }
+}
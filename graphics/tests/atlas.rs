@@ -0,0 +1,60 @@
+//! Checks the atlas bookkeeping that needs a device to run, on the
+//! fallback adapter.
+
+use ascending_graphics::*;
+use futures::executor::block_on;
+
+fn renderer() -> Option<GpuRenderer> {
+    let renderer = RendererBuilder::new()
+        .headless(16, 16)
+        .with_fallback_adapter(true)
+        .build();
+
+    match block_on(renderer) {
+        Ok(renderer) => Some(renderer),
+        Err(AscendingError::NoAdapter) => {
+            eprintln!("skipping atlas tests, there is no fallback adapter");
+            None
+        }
+        Err(e) => panic!("{e}"),
+    }
+}
+
+/// A handle from before a repack must not resolve to a slot reused after
+/// its allocation was removed.
+#[test]
+fn removed_relocations_do_not_resolve() {
+    let Some(renderer) = renderer() else {
+        return;
+    };
+    let mut atlas: Atlas =
+        Atlas::new(&renderer, wgpu::TextureFormat::Rgba8UnormSrgb);
+    let texels = [255u8; 16 * 16 * 4];
+    let key = |name: &str| name.to_owned();
+
+    let old = atlas
+        .upload(key("a"), &texels, 16, 16, 0, &renderer)
+        .unwrap();
+    atlas
+        .upload(key("b"), &texels, 16, 16, 0, &renderer)
+        .unwrap();
+    atlas.remove(&key("b"));
+
+    assert!(atlas.repack(&renderer));
+
+    let moved = atlas.current(&old).unwrap();
+
+    assert_eq!(moved.generation, atlas.generation());
+
+    atlas.remove(&key("a"));
+
+    let reused = atlas
+        .upload(key("c"), &texels, 16, 16, 0, &renderer)
+        .unwrap();
+
+    assert_eq!(
+        (reused.layer, reused.position()),
+        (moved.layer, moved.position())
+    );
+    assert!(atlas.current(&old).is_none());
+}
@@ -0,0 +1,65 @@
+//! Renders the scenes in `tests/golden` and compares them against their
+//! goldens. Run with `--features golden`, and with `GOLDEN_BLESS=1` to
+//! update the goldens after an intended change.
+#![cfg(feature = "golden")]
+
+use ascending_graphics::*;
+use futures::executor::block_on;
+use std::path::Path;
+
+const SCENES: [&str; 5] = ["sprites", "map", "shapes", "lights", "text"];
+
+#[test]
+fn golden_scenes() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    // The software adapter renders the same on every machine.
+    let renderer = RendererBuilder::new()
+        .headless(128, 128)
+        .with_fallback_adapter(true)
+        .build();
+    let mut renderer = match block_on(renderer) {
+        Ok(renderer) => renderer,
+        Err(AscendingError::NoAdapter) => {
+            eprintln!("skipping golden scenes, there is no fallback adapter");
+            return;
+        }
+        Err(e) => panic!("{e}"),
+    };
+    let harness = GoldenHarness::new(&dir);
+    let mut failures = Vec::new();
+
+    for name in SCENES {
+        let mut scene = SceneDesc::load(dir.join(format!("{name}.ron")))
+            .and_then(|desc| desc.build(&mut renderer))
+            .unwrap_or_else(|e| panic!("{name}: {e}"));
+
+        scene.prepare(&mut renderer).unwrap();
+
+        if let Err(e) = harness.check(&renderer, name, &mut scene) {
+            failures.push(e.to_string());
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+/// The scenes drawn by the CPU rasterizer, which runs without an adapter.
+/// Its goldens are apart from the GPU ones as the two differ slightly.
+#[test]
+fn software_scenes() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let harness = GoldenHarness::new(dir.join("software"));
+    let mut failures = Vec::new();
+
+    for name in ["sprites", "map", "shapes", "text"] {
+        let image = SceneDesc::load(dir.join(format!("{name}.ron")))
+            .and_then(|desc| desc.rasterize(128, 128))
+            .unwrap_or_else(|e| panic!("{name}: {e}"));
+
+        if let Err(e) = harness.check_image(name, &image) {
+            failures.push(e.to_string());
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -1,5 +1,6 @@
 use super::axis::Axis;
 use super::button::Button;
+use super::device::DeviceSlot;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::hash::Hash;
@@ -13,9 +14,45 @@ where
     AxisId: Clone + Eq + Hash + Send + Sync,
 {
     /// A mapping from the action ID to an array of button combinations.
-    pub(super) actions: HashMap<ActionId, Vec<Vec<Button>>>,
+    pub(super) actions: ActionMap<ActionId>,
+    /// Button combinations that only count for devices in the slot.
+    #[serde(default)]
+    pub(super) slot_actions: HashMap<DeviceSlot, ActionMap<ActionId>>,
     /// A mapping from the axis ID to an array of axes.
     pub(super) axes: HashMap<AxisId, Vec<Axis>>,
+    /// Axes that drive actions with an analog strength.
+    #[serde(default)]
+    pub(super) analog_actions: HashMap<ActionId, Vec<AnalogBinding>>,
+    /// Which action wins when two are bound to the same buttons. Higher
+    /// wins, 0 by default.
+    #[serde(default)]
+    pub(super) priorities: HashMap<ActionId, i32>,
+    /// Actions in the order they were first bound, for breaking ties.
+    #[serde(default)]
+    pub(super) order: Vec<ActionId>,
+}
+
+/// Button combinations of each action.
+pub(super) type ActionMap<ActionId> = HashMap<ActionId, Vec<Vec<Button>>>;
+
+/// An axis driving an action, like a trigger or one side of a stick.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AnalogBinding {
+    pub axis: Axis,
+    /// Reads the negative side of the axis instead of the positive one.
+    pub negative: bool,
+    /// Strength at or below this reads as 0.0 and the action as up.
+    pub dead_zone: ordered_float::NotNan<f32>,
+}
+
+impl AnalogBinding {
+    pub fn new(axis: Axis) -> Self {
+        Self {
+            axis,
+            negative: false,
+            dead_zone: ordered_float::NotNan::default(),
+        }
+    }
 }
 
 impl<'de, ActionId, AxisId> Bindings<ActionId, AxisId>
@@ -31,6 +68,8 @@ where
         // Collect the button combination.
         let action = buttons.into_iter().collect::<Vec<Button>>();
 
+        self.remember_order(&id);
+
         // Add the button combination to the bindings for the given action ID.
         if let Some(actions) = self.actions.get_mut(&id) {
             actions.push(action);
@@ -42,6 +81,40 @@ where
         self.actions.insert(id, bindings);
     }
 
+    /// Like insert_action, but only buttons on the slot's devices press it.
+    /// Read it through [`crate::InputHandler::player`].
+    pub fn insert_action_for<B: IntoIterator<Item = Button>>(
+        &mut self,
+        slot: DeviceSlot,
+        id: ActionId,
+        buttons: B,
+    ) {
+        self.remember_order(&id);
+        self.slot_actions
+            .entry(slot)
+            .or_default()
+            .entry(id)
+            .or_default()
+            .push(buttons.into_iter().collect());
+    }
+
+    /// Lets an axis press the action, with the axis value as its strength.
+    pub fn insert_action_analog(
+        &mut self,
+        id: ActionId,
+        binding: AnalogBinding,
+    ) {
+        self.remember_order(&id);
+        self.analog_actions.entry(id).or_default().push(binding);
+    }
+
+    /// When two actions are bound to the same buttons only the one with
+    /// the higher priority fires. Equal priorities go to the one bound
+    /// first.
+    pub fn set_action_priority(&mut self, id: ActionId, priority: i32) {
+        self.priorities.insert(id, priority);
+    }
+
     pub fn insert_axis(&mut self, id: AxisId, axis: Axis) {
         // Add the axis to the bindings for the given axis ID.
         if let Some(bindings) = self.axes.get_mut(&id) {
@@ -58,6 +131,70 @@ where
         Self {
             actions: HashMap::new(),
             axes: HashMap::new(),
+            slot_actions: HashMap::new(),
+            analog_actions: HashMap::new(),
+            priorities: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+}
+
+impl<ActionId, AxisId> Bindings<ActionId, AxisId>
+where
+    ActionId: Clone + Eq + Hash + Send + Sync,
+    AxisId: Clone + Eq + Hash + Send + Sync,
+{
+    /// Button combinations bound to the action for every device.
+    pub fn action_buttons(&self, id: &ActionId) -> &[Vec<Button>] {
+        self.actions.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Short text for the action's first combination, like "Shift+1".
+    pub fn action_label(&self, id: &ActionId) -> Option<String> {
+        let buttons = self.action_buttons(id).first()?;
+
+        Some(
+            buttons
+                .iter()
+                .map(Button::label)
+                .collect::<Vec<_>>()
+                .join("+"),
+        )
+    }
+
+    /// Drops every combination bound to the action, for rebinding it.
+    pub fn clear_action(&mut self, id: &ActionId) {
+        self.actions.remove(id);
+    }
+
+    fn remember_order(&mut self, id: &ActionId) {
+        if !self.order.contains(id) {
+            self.order.push(id.clone());
+        }
+    }
+
+    /// The button bindings that apply to slot, or to every device if None.
+    pub(super) fn action_maps(
+        &self,
+        slot: Option<DeviceSlot>,
+    ) -> impl Iterator<Item = &ActionMap<ActionId>> {
+        std::iter::once(&self.actions)
+            .chain(slot.and_then(|slot| self.slot_actions.get(&slot)))
+    }
+
+    /// if a wins over b when they are bound to the same buttons.
+    pub(super) fn outranks(&self, a: &ActionId, b: &ActionId) -> bool {
+        let priority = |id| self.priorities.get(id).copied().unwrap_or(0);
+        let position = |id| {
+            self.order
+                .iter()
+                .position(|other| other == id)
+                .unwrap_or(usize::MAX)
+        };
+
+        match priority(a).cmp(&priority(b)) {
+            std::cmp::Ordering::Equal => position(a) < position(b),
+            ordering => ordering.is_gt(),
         }
     }
 }
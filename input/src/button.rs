@@ -10,6 +10,47 @@ pub enum Button {
     Mouse(winit::event::MouseButton),
 }
 
+impl Button {
+    /// Short text for showing the button on screen, like "Q", "1" or
+    /// "Shift".
+    pub fn label(&self) -> String {
+        use winit::event::{MouseButton, VirtualKeyCode as Key};
+
+        match self {
+            Button::Key(key) => match key {
+                Key::LShift | Key::RShift => "Shift".to_owned(),
+                Key::LControl | Key::RControl => "Ctrl".to_owned(),
+                Key::LAlt | Key::RAlt => "Alt".to_owned(),
+                Key::Escape => "Esc".to_owned(),
+                Key::Return => "Enter".to_owned(),
+                Key::Back => "Bksp".to_owned(),
+                Key::Grave => "`".to_owned(),
+                Key::Minus => "-".to_owned(),
+                Key::Equals => "=".to_owned(),
+                _ => {
+                    let name = format!("{key:?}");
+
+                    // Key1 and Numpad1 are named after their digit.
+                    if let Some(digit) = name.strip_prefix("Key") {
+                        digit.to_owned()
+                    } else if let Some(rest) = name.strip_prefix("Numpad") {
+                        format!("N{rest}")
+                    } else {
+                        name
+                    }
+                }
+            },
+            Button::ScanCode(code) => format!("#{code}"),
+            Button::Mouse(button) => match button {
+                MouseButton::Left => "LMB".to_owned(),
+                MouseButton::Right => "RMB".to_owned(),
+                MouseButton::Middle => "MMB".to_owned(),
+                MouseButton::Other(other) => format!("M{other}"),
+            },
+        }
+    }
+}
+
 impl From<winit::event::VirtualKeyCode> for Button {
     fn from(value: winit::event::VirtualKeyCode) -> Self {
         Button::Key(value)
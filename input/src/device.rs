@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// A player's place for input devices in local multiplayer. Devices are
+/// assigned to a slot and bindings can be limited to one.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Hash,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+)]
+pub struct DeviceSlot(pub u8);
@@ -1,11 +1,60 @@
-use std::time::Instant;
+use std::{collections::VecDeque, fmt, rc::Rc, time::Instant};
 
-#[derive(Clone, Debug)]
+/// How many frame deltas are kept for the statistics by default.
+const DEFAULT_HISTORY_LEN: usize = 120;
+/// Most time the fixed step accumulator holds so a long stall doesn't make
+/// the game run hundreds of steps to catch up.
+const DEFAULT_MAX_ACCUMULATED: f32 = 0.25;
+
+pub type SpikeCallback = Rc<dyn Fn(&FrameSpike)>;
+
+/// A frame that took longer than the spike threshold.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FrameSpike {
+    pub delta_seconds: f32,
+    pub threshold: f32,
+    /// Average delta of the frames before it.
+    pub average_delta: f32,
+    /// Seconds since start when it happened.
+    pub seconds: f32,
+}
+
+#[derive(Clone)]
 pub struct FrameTime {
     delta_seconds: f32,
     seconds: f32,
     frame_time: Instant,
     start_time: Instant,
+    /// Last deltas, oldest first.
+    history: VecDeque<f32>,
+    history_len: usize,
+    spike_threshold: Option<f32>,
+    spike_callback: Option<SpikeCallback>,
+    last_spike: Option<FrameSpike>,
+    spike_count: u64,
+    fixed_step: f32,
+    max_accumulated: f32,
+    accumulator: f32,
+    /// Seconds between presents the deltas snap to. None uses them as
+    /// measured.
+    frame_interval: Option<f32>,
+    /// Measured time snapping took out of or added to the deltas, handed
+    /// back once it grows so the seconds don't drift.
+    snap_error: f32,
+}
+
+impl fmt::Debug for FrameTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrameTime")
+            .field("delta_seconds", &self.delta_seconds)
+            .field("seconds", &self.seconds)
+            .field("spike_threshold", &self.spike_threshold)
+            .field("spike_count", &self.spike_count)
+            .field("fixed_step", &self.fixed_step)
+            .field("accumulator", &self.accumulator)
+            .field("frame_interval", &self.frame_interval)
+            .finish()
+    }
 }
 
 impl FrameTime {
@@ -22,6 +71,17 @@ impl FrameTime {
             seconds: 0.0,
             frame_time: instant,
             start_time: instant,
+            history: VecDeque::with_capacity(DEFAULT_HISTORY_LEN),
+            history_len: DEFAULT_HISTORY_LEN,
+            spike_threshold: None,
+            spike_callback: None,
+            last_spike: None,
+            spike_count: 0,
+            fixed_step: 1.0 / 60.0,
+            max_accumulated: DEFAULT_MAX_ACCUMULATED,
+            accumulator: 0.0,
+            frame_interval: None,
+            snap_error: 0.0,
         }
     }
 
@@ -31,10 +91,217 @@ impl FrameTime {
 
     pub fn update(&mut self) {
         let frame_time = Instant::now();
+        let delta =
+            self.snap(frame_time.duration_since(self.frame_time).as_secs_f32());
 
-        self.delta_seconds =
-            frame_time.duration_since(self.frame_time).as_secs_f32();
+        self.delta_seconds = delta;
         self.seconds = frame_time.duration_since(self.start_time).as_secs_f32();
         self.frame_time = frame_time;
+        self.record(delta);
+    }
+
+    /// Rounds delta to whole frame intervals when it is close to one, as
+    /// the frame was shown for exactly that long. Measured deltas wobble
+    /// around it from scheduling, which shows as stutter in animations.
+    fn snap(&mut self, delta: f32) -> f32 {
+        let Some(interval) = self.frame_interval else {
+            return delta;
+        };
+
+        let frames = (delta / interval).round().max(1.0);
+        let snapped = frames * interval;
+
+        if (delta - snapped).abs() > interval * 0.2 {
+            self.snap_error = 0.0;
+            return delta;
+        }
+
+        self.snap_error += delta - snapped;
+
+        if self.snap_error.abs() >= interval * 0.5 {
+            let error = std::mem::take(&mut self.snap_error);
+
+            return (snapped + error).max(0.0);
+        }
+
+        snapped
+    }
+
+    /// Seconds the frames are shown for, like 1 / refresh rate when
+    /// presents follow the monitor. Deltas close to a whole number of
+    /// them snap to it. None uses the deltas as measured.
+    pub fn set_frame_interval(&mut self, interval: Option<f32>) {
+        self.frame_interval = interval.filter(|interval| *interval > 0.0);
+        self.snap_error = 0.0;
+    }
+
+    pub fn frame_interval(&self) -> Option<f32> {
+        self.frame_interval
+    }
+
+    fn record(&mut self, delta: f32) {
+        self.last_spike = None;
+
+        if let Some(threshold) = self.spike_threshold {
+            if delta > threshold {
+                let spike = FrameSpike {
+                    delta_seconds: delta,
+                    threshold,
+                    average_delta: self.average_delta(),
+                    seconds: self.seconds,
+                };
+
+                self.spike_count += 1;
+                self.last_spike = Some(spike);
+
+                if let Some(callback) = &self.spike_callback {
+                    callback(&spike);
+                }
+            }
+        }
+
+        if self.history.len() == self.history_len {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(delta);
+        self.accumulator = (self.accumulator + delta).min(self.max_accumulated);
+    }
+
+    /// How many frames the statistics are taken over.
+    pub fn set_history_len(&mut self, len: usize) {
+        self.history_len = len.max(1);
+
+        while self.history.len() > self.history_len {
+            self.history.pop_front();
+        }
+    }
+
+    pub fn history(&self) -> &VecDeque<f32> {
+        &self.history
+    }
+
+    /// Average delta over the history. 0.0 before the first update.
+    pub fn average_delta(&self) -> f32 {
+        if self.history.is_empty() {
+            return 0.0;
+        }
+
+        self.history.iter().sum::<f32>() / self.history.len() as f32
+    }
+
+    pub fn average_fps(&self) -> f32 {
+        let average = self.average_delta();
+
+        if average > 0.0 {
+            1.0 / average
+        } else {
+            0.0
+        }
+    }
+
+    /// Delta that percent of the history is at or below. percent is
+    /// 0.0 to 100.0.
+    pub fn percentile(&self, percent: f32) -> f32 {
+        if self.history.is_empty() {
+            return 0.0;
+        }
+
+        let mut sorted: Vec<f32> = self.history.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+
+        let rank = (percent.clamp(0.0, 100.0) / 100.0 * sorted.len() as f32)
+            .ceil() as usize;
+
+        sorted[rank.saturating_sub(1)]
+    }
+
+    pub fn p95(&self) -> f32 {
+        self.percentile(95.0)
+    }
+
+    pub fn p99(&self) -> f32 {
+        self.percentile(99.0)
+    }
+
+    /// Standard deviation of the deltas in the history. High jitter
+    /// feels like stutter even when the average is fine.
+    pub fn jitter(&self) -> f32 {
+        if self.history.len() < 2 {
+            return 0.0;
+        }
+
+        let average = self.average_delta();
+        let variance = self
+            .history
+            .iter()
+            .map(|delta| (delta - average).powi(2))
+            .sum::<f32>()
+            / self.history.len() as f32;
+
+        variance.sqrt()
+    }
+
+    /// Frames slower than threshold seconds count as spikes. None turns
+    /// detection off.
+    pub fn set_spike_threshold(&mut self, threshold: Option<f32>) {
+        self.spike_threshold = threshold;
+    }
+
+    pub fn spike_threshold(&self) -> Option<f32> {
+        self.spike_threshold
+    }
+
+    /// Called from update for every spike.
+    pub fn set_spike_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&FrameSpike) + 'static,
+    {
+        self.spike_callback = Some(Rc::new(callback));
+    }
+
+    pub fn clear_spike_callback(&mut self) {
+        self.spike_callback = None;
+    }
+
+    /// The spike of the last update if it was one.
+    pub fn last_spike(&self) -> Option<FrameSpike> {
+        self.last_spike
+    }
+
+    pub fn spike_count(&self) -> u64 {
+        self.spike_count
+    }
+
+    /// Seconds per fixed update. Defaults to 60 updates a second.
+    pub fn set_fixed_step(&mut self, step: f32) {
+        self.fixed_step = step.max(f32::EPSILON);
+    }
+
+    pub fn fixed_step(&self) -> f32 {
+        self.fixed_step
+    }
+
+    /// Caps the time waiting to be stepped.
+    pub fn set_max_accumulated(&mut self, seconds: f32) {
+        self.max_accumulated = seconds.max(0.0);
+        self.accumulator = self.accumulator.min(self.max_accumulated);
+    }
+
+    /// Takes one fixed step from the time accumulated by update. Loop
+    /// over it once per frame: `while frame_time.fixed_update() { .. }`.
+    pub fn fixed_update(&mut self) -> bool {
+        if self.accumulator >= self.fixed_step {
+            self.accumulator -= self.fixed_step;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// How far between the last and next fixed step the frame is, 0.0 to
+    /// 1.0. Used to interpolate rendering between steps.
+    pub fn fixed_alpha(&self) -> f32 {
+        (self.accumulator / self.fixed_step).min(1.0)
     }
 }
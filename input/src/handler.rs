@@ -1,11 +1,14 @@
 use super::axis::{Axis, MouseAxis};
 use super::bindings::Bindings;
 use super::button::Button;
-use std::collections::HashSet;
+use super::device::DeviceSlot;
+use super::mouse::{MouseDeltaSource, MouseSettings};
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
+use std::path::PathBuf;
 use winit::dpi::PhysicalPosition;
 use winit::event::{
-    DeviceEvent, ElementState, Event, KeyboardInput, ModifiersState,
+    DeviceEvent, DeviceId, ElementState, Event, KeyboardInput, ModifiersState,
     MouseScrollDelta, WindowEvent,
 };
 use winit::window::Window;
@@ -31,10 +34,68 @@ where
     last_mouse_position: Option<(f32, f32)>,
     /// The mouse delta, i.e. the relative mouse motion.
     mouse_delta: (f64, f64),
+    /// How the relative mouse motion is read and shaped.
+    mouse_settings: MouseSettings,
     /// The current state of the mouse wheel.
     mouse_wheel: (f32, f32),
     //key modifiers.
     modifiers: ModifiersState,
+    /// Files being dragged over the window.
+    hovered_files: Vec<PathBuf>,
+    /// Files dropped on the window this frame.
+    dropped_files: Vec<PathBuf>,
+    /// Characters typed this frame, including control characters like
+    /// backspace and enter.
+    typed_text: String,
+    /// Buttons held on each device, for telling players apart.
+    device_buttons: HashMap<DeviceId, HashSet<Button>>,
+    device_slots: HashMap<DeviceId, DeviceSlot>,
+    last_device: Option<DeviceId>,
+    auto_assign: bool,
+    /// Device events arrive even while another window has focus, so they
+    /// are dropped until focus comes back.
+    focused: bool,
+}
+
+/// One player's view of an [`InputHandler`], from
+/// [`InputHandler::player`]. Analog bindings still read the shared axes.
+pub struct PlayerInput<'a, ActionId, AxisId>
+where
+    ActionId: Clone + Eq + Hash + Send + Sync,
+    AxisId: Clone + Eq + Hash + Send + Sync,
+{
+    handler: &'a InputHandler<ActionId, AxisId>,
+    slot: DeviceSlot,
+}
+
+impl<ActionId, AxisId> PlayerInput<'_, ActionId, AxisId>
+where
+    ActionId: Clone + Eq + Hash + Send + Sync,
+    AxisId: Clone + Eq + Hash + Send + Sync,
+{
+    pub fn slot(&self) -> DeviceSlot {
+        self.slot
+    }
+
+    pub fn is_action_down<A>(&self, action: &A) -> bool
+    where
+        ActionId: std::borrow::Borrow<A>,
+        A: Hash + Eq + ?Sized,
+    {
+        self.action_strength(action) > 0.0
+    }
+
+    pub fn action_strength<A>(&self, action: &A) -> f32
+    where
+        ActionId: std::borrow::Borrow<A>,
+        A: Hash + Eq + ?Sized,
+    {
+        self.handler.action_strength_in(action, Some(self.slot))
+    }
+
+    pub fn is_button_down(&self, button: Button) -> bool {
+        self.handler.is_button_down_in(button, self.slot)
+    }
 }
 
 impl<ActionId, AxisId> InputHandler<ActionId, AxisId>
@@ -62,24 +123,143 @@ where
         self.last_mouse_position = self.mouse_position;
         self.mouse_delta = (0.0, 0.0);
         self.mouse_wheel = (0.0, 0.0);
+        self.dropped_files.clear();
+        self.typed_text.clear();
+    }
+
+    /// Characters typed this frame, for text entry. Backspace is `'\u{8}'`,
+    /// enter `'\r'` and escape `'\u{1b}'`.
+    pub fn typed_text(&self) -> &str {
+        &self.typed_text
+    }
+
+    /// Files being dragged over the window, empty when nothing is.
+    pub fn hovered_files(&self) -> &[PathBuf] {
+        &self.hovered_files
+    }
+
+    pub fn is_file_hovering(&self) -> bool {
+        !self.hovered_files.is_empty()
+    }
+
+    /// Files dropped on the window this frame. Use the mouse position to
+    /// find where they were dropped.
+    pub fn dropped_files(&self) -> &[PathBuf] {
+        &self.dropped_files
+    }
+
+    /// Takes this frame's dropped files so only one handler gets them.
+    pub fn take_dropped_files(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.dropped_files)
     }
 
     /// Looks up the set of bindings for the action, and then checks if there is any binding for
-    /// which all buttons are currently down.
+    /// which all buttons are currently down, or any analog binding past its dead zone.
+    ///
+    /// A held binding doesn't count while another action's held binding has all of its buttons
+    /// and more, so Ctrl+S doesn't also fire S. Actions bound to the same buttons go by
+    /// [`Bindings::set_action_priority`].
     pub fn is_action_down<A>(&self, action: &A) -> bool
     where
         ActionId: std::borrow::Borrow<A>,
         A: Hash + Eq + ?Sized,
     {
-        self.bindings
-            .actions
-            .get(action)
-            .map(|bindings| {
+        self.action_strength(action) > 0.0
+    }
+
+    /// How hard the action is pressed from 0.0 to 1.0. Button bindings are 1.0 while held and
+    /// analog ones follow their axis.
+    pub fn action_strength<A>(&self, action: &A) -> f32
+    where
+        ActionId: std::borrow::Borrow<A>,
+        A: Hash + Eq + ?Sized,
+    {
+        self.action_strength_in(action, None)
+    }
+
+    /// Strength of the action for the devices in slot, or for every
+    /// device if None.
+    fn action_strength_in<A>(&self, action: &A, slot: Option<DeviceSlot>) -> f32
+    where
+        ActionId: std::borrow::Borrow<A>,
+        A: Hash + Eq + ?Sized,
+    {
+        let held = self.bindings.action_maps(slot).any(|actions| {
+            actions.get_key_value(action).is_some_and(|(id, bindings)| {
                 bindings.iter().any(|buttons| {
-                    buttons.iter().all(|button| self.is_button_down(*button))
+                    self.is_chord_down(buttons, slot)
+                        && !self.is_chord_shadowed(id, buttons, slot)
+                })
+            })
+        });
+
+        if held {
+            1.0
+        } else {
+            self.analog_strength(action)
+        }
+    }
+
+    fn analog_strength<A>(&self, action: &A) -> f32
+    where
+        ActionId: std::borrow::Borrow<A>,
+        A: Hash + Eq + ?Sized,
+    {
+        let Some(bindings) = self.bindings.analog_actions.get(action) else {
+            return 0.0;
+        };
+
+        bindings
+            .iter()
+            .map(|binding| {
+                let value = self.map_axis_value(&binding.axis);
+                let value = if binding.negative { -value } else { value };
+
+                if value > binding.dead_zone.into_inner() {
+                    value.min(1.0)
+                } else {
+                    0.0
+                }
+            })
+            .fold(0.0, f32::max)
+    }
+
+    fn is_chord_down(
+        &self,
+        buttons: &[Button],
+        slot: Option<DeviceSlot>,
+    ) -> bool {
+        buttons.iter().all(|button| match slot {
+            Some(slot) => self.is_button_down_in(*button, slot),
+            None => self.is_button_down(*button),
+        })
+    }
+
+    /// if another action's held binding takes priority over buttons.
+    fn is_chord_shadowed(
+        &self,
+        id: &ActionId,
+        buttons: &[Button],
+        slot: Option<DeviceSlot>,
+    ) -> bool {
+        self.bindings
+            .action_maps(slot)
+            .flatten()
+            .filter(|(other, _)| *other != id)
+            .any(|(other, bindings)| {
+                bindings.iter().any(|other_buttons| {
+                    let covers = buttons
+                        .iter()
+                        .all(|button| other_buttons.contains(button));
+
+                    covers
+                        && self.is_chord_down(other_buttons, slot)
+                        && (other_buttons
+                            .iter()
+                            .any(|button| !buttons.contains(button))
+                            || self.bindings.outranks(other, id))
                 })
             })
-            .unwrap_or(false)
     }
 
     pub fn is_button_down(&self, button: Button) -> bool {
@@ -90,6 +270,76 @@ where
         }
     }
 
+    /// if a device in slot holds the button.
+    pub fn is_button_down_in(&self, button: Button, slot: DeviceSlot) -> bool {
+        self.device_buttons.iter().any(|(device, buttons)| {
+            self.device_slots.get(device) == Some(&slot)
+                && buttons.contains(&button)
+        })
+    }
+
+    /// The actions as one player sees them, from the devices in slot and
+    /// the bindings for it or for every device.
+    pub fn player(
+        &self,
+        slot: DeviceSlot,
+    ) -> PlayerInput<'_, ActionId, AxisId> {
+        PlayerInput {
+            handler: self,
+            slot,
+        }
+    }
+
+    /// Gives a device's input to slot, like when a player presses a
+    /// button on a join screen.
+    pub fn assign_device(&mut self, device: DeviceId, slot: DeviceSlot) {
+        self.device_slots.insert(device, slot);
+    }
+
+    pub fn unassign_device(&mut self, device: DeviceId) -> Option<DeviceSlot> {
+        self.device_slots.remove(&device)
+    }
+
+    pub fn device_slot(&self, device: DeviceId) -> Option<DeviceSlot> {
+        self.device_slots.get(&device).copied()
+    }
+
+    /// The device that last pressed a button, to find out which one a
+    /// joining player is using.
+    pub fn last_device(&self) -> Option<DeviceId> {
+        self.last_device
+    }
+
+    /// Puts each new device into the lowest free slot as soon as it
+    /// presses a button. Off by default.
+    pub fn set_auto_assign(&mut self, auto_assign: bool) {
+        self.auto_assign = auto_assign;
+    }
+
+    fn press_device_button(&mut self, device: DeviceId, button: Button) {
+        self.device_buttons
+            .entry(device)
+            .or_default()
+            .insert(button);
+        self.last_device = Some(device);
+
+        if self.auto_assign && !self.device_slots.contains_key(&device) {
+            let slot = (0..=u8::MAX)
+                .map(DeviceSlot)
+                .find(|slot| !self.device_slots.values().any(|s| s == slot));
+
+            if let Some(slot) = slot {
+                self.device_slots.insert(device, slot);
+            }
+        }
+    }
+
+    fn release_device_button(&mut self, device: DeviceId, button: Button) {
+        if let Some(buttons) = self.device_buttons.get_mut(&device) {
+            buttons.remove(&button);
+        }
+    }
+
     pub fn is_key_down(&self, key: winit::event::VirtualKeyCode) -> bool {
         self.keys.contains(&key)
     }
@@ -143,9 +393,10 @@ where
                 limit,
                 radius,
             } => {
+                let (x, y) = self.mouse_look_delta();
                 let delta = match axis {
-                    MouseAxis::Horizontal => self.mouse_delta.0 as f32,
-                    MouseAxis::Vertical => self.mouse_delta.1 as f32,
+                    MouseAxis::Horizontal => x,
+                    MouseAxis::Vertical => y,
                 };
 
                 let delta = delta / radius.into_inner();
@@ -160,6 +411,50 @@ where
         }
     }
 
+    pub fn bindings(&self) -> &Bindings<ActionId, AxisId> {
+        &self.bindings
+    }
+
+    /// For rebinding while running. Widgets showing keys pick the change up
+    /// on their next input.
+    pub fn bindings_mut(&mut self) -> &mut Bindings<ActionId, AxisId> {
+        &mut self.bindings
+    }
+
+    pub fn mouse_settings(&self) -> &MouseSettings {
+        &self.mouse_settings
+    }
+
+    pub fn set_mouse_settings(&mut self, settings: MouseSettings) {
+        self.mouse_settings = settings;
+    }
+
+    /// This frame's relative mouse motion from the chosen source, shaped by
+    /// the mouse settings. Relative mouse motion axes read it too.
+    pub fn mouse_look_delta(&self) -> (f32, f32) {
+        let delta = match self.mouse_settings.source {
+            MouseDeltaSource::Raw => {
+                (self.mouse_delta.0 as f32, self.mouse_delta.1 as f32)
+            }
+            MouseDeltaSource::Cursor => {
+                match (self.last_mouse_position, self.mouse_position) {
+                    (Some(last), Some(current)) => {
+                        (last.0 - current.0, last.1 - current.1)
+                    }
+                    _ => (0.0, 0.0),
+                }
+            }
+        };
+
+        self.mouse_settings.apply(delta)
+    }
+
+    /// If the window had focus at the last focus event. True until the
+    /// first one.
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
     pub fn mouse_position(&self) -> Option<(f32, f32)> {
         self.mouse_position
     }
@@ -189,8 +484,17 @@ where
             mouse_position: None,
             last_mouse_position: None,
             mouse_delta: (0.0, 0.0),
+            mouse_settings: MouseSettings::default(),
             mouse_wheel: (0.0, 0.0),
             modifiers: ModifiersState::default(),
+            hovered_files: Vec::new(),
+            dropped_files: Vec::new(),
+            typed_text: String::new(),
+            device_buttons: HashMap::new(),
+            device_slots: HashMap::new(),
+            last_device: None,
+            auto_assign: false,
+            focused: true,
         }
     }
 
@@ -201,6 +505,7 @@ where
                 window_id,
             } if window_id == window.id() => match event {
                 WindowEvent::KeyboardInput {
+                    device_id,
                     input:
                         KeyboardInput {
                             state,
@@ -210,19 +515,41 @@ where
                         },
                     ..
                 } => {
+                    let buttons =
+                        [Button::Key(*key_code), Button::ScanCode(*scancode)];
+
                     if *state == ElementState::Pressed {
                         self.keys.insert(*key_code);
                         self.scan_codes.insert(*scancode);
+                        buttons.into_iter().for_each(|button| {
+                            self.press_device_button(*device_id, button)
+                        });
                     } else {
                         self.keys.remove(key_code);
                         self.scan_codes.remove(scancode);
+                        buttons.into_iter().for_each(|button| {
+                            self.release_device_button(*device_id, button)
+                        });
                     }
                 }
-                WindowEvent::MouseInput { state, button, .. } => {
+                WindowEvent::MouseInput {
+                    device_id,
+                    state,
+                    button,
+                    ..
+                } => {
                     if *state == ElementState::Pressed {
                         self.mouse_buttons.insert(*button);
+                        self.press_device_button(
+                            *device_id,
+                            Button::Mouse(*button),
+                        );
                     } else {
                         self.mouse_buttons.remove(button);
+                        self.release_device_button(
+                            *device_id,
+                            Button::Mouse(*button),
+                        );
                     }
                 }
                 WindowEvent::CursorMoved {
@@ -234,16 +561,37 @@ where
                     self.mouse_position =
                         Some(((*x as f32) * hidpi, (*y as f32) * hidpi));
                 }
+                WindowEvent::Focused(true) => {
+                    self.focused = true;
+                }
                 WindowEvent::Focused(false) => {
+                    self.focused = false;
+                    self.mouse_delta = (0.0, 0.0);
                     self.keys.clear();
                     self.scan_codes.clear();
                     self.mouse_buttons.clear();
+                    self.device_buttons.clear();
                 }
                 WindowEvent::ModifiersChanged(new_modifiers) => {
                     self.modifiers = *new_modifiers;
                 }
+                WindowEvent::ReceivedCharacter(c) => {
+                    self.typed_text.push(*c);
+                }
+                // winit sends one event per file.
+                WindowEvent::HoveredFile(path) => {
+                    self.hovered_files.push(path.clone());
+                }
+                WindowEvent::HoveredFileCancelled => {
+                    self.hovered_files.clear();
+                }
+                WindowEvent::DroppedFile(path) => {
+                    self.hovered_files.clear();
+                    self.dropped_files.push(path.clone());
+                }
                 _ => (),
             },
+            Event::DeviceEvent { .. } if !self.focused => {}
             Event::DeviceEvent { ref event, .. } => match *event {
                 DeviceEvent::MouseMotion { delta } => {
                     self.mouse_delta.0 -= delta.0;
@@ -1,11 +1,15 @@
 mod axis;
 mod bindings;
 mod button;
+mod device;
 mod frame_time;
 mod handler;
+mod mouse;
 
 pub use axis::{Axis, MouseAxis};
-pub use bindings::Bindings;
+pub use bindings::{AnalogBinding, Bindings};
 pub use button::Button;
-pub use frame_time::FrameTime;
-pub use handler::InputHandler;
+pub use device::DeviceSlot;
+pub use frame_time::{FrameSpike, FrameTime, SpikeCallback};
+pub use handler::{InputHandler, PlayerInput};
+pub use mouse::{MouseDeltaSource, MouseSettings};
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+/// Where the mouse look delta comes from.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum MouseDeltaSource {
+    /// Device motion before the OS moves the cursor. Keeps working while
+    /// the cursor is locked and isn't slowed down at the screen's edges.
+    #[default]
+    Raw,
+    /// How far the cursor moved. Follows the OS pointer speed settings but
+    /// stops when the cursor is locked.
+    Cursor,
+}
+
+/// How mouse motion turns into look deltas, for camera look controls.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MouseSettings {
+    pub source: MouseDeltaSource,
+    /// Multiplies the delta after the curve.
+    pub sensitivity: f32,
+    /// Power the delta is raised to, keeping its sign. 1.0 is linear, above
+    /// that small motions get finer and large ones faster.
+    pub curve: f32,
+    /// Extra gain per unit moved in a frame, so fast flicks turn further.
+    /// 0.0 turns acceleration off.
+    pub acceleration: f32,
+    /// Most the acceleration can multiply the delta by.
+    pub max_acceleration: f32,
+    pub invert_y: bool,
+}
+
+impl Default for MouseSettings {
+    fn default() -> Self {
+        Self {
+            source: MouseDeltaSource::Raw,
+            sensitivity: 1.0,
+            curve: 1.0,
+            acceleration: 0.0,
+            max_acceleration: 4.0,
+            invert_y: false,
+        }
+    }
+}
+
+impl MouseSettings {
+    /// Applies the curve, acceleration and sensitivity to a frame's delta.
+    pub fn apply(&self, delta: (f32, f32)) -> (f32, f32) {
+        let curve = |value: f32| value.signum() * value.abs().powf(self.curve);
+        let speed = delta.0.hypot(delta.1);
+        let gain = (1.0 + self.acceleration * speed)
+            .min(self.max_acceleration.max(1.0))
+            * self.sensitivity;
+        let y = if self.invert_y { -delta.1 } else { delta.1 };
+
+        (curve(delta.0) * gain, curve(y) * gain)
+    }
+}